@@ -0,0 +1,27 @@
+#!/bin/sh
+# Inside the workspace the dev-dependencies unify features into the builds
+# of the crates, e.g. `rand` enables `rand_core/std`. A crate depending on
+# `scrypt` does not get them, so build one outside of the repository. The
+# lockfile of the workspace is copied, as it pins yanked versions.
+ROOT=`pwd`
+[ -f Cargo.lock ] || cargo generate-lockfile || exit 1
+DIR=`mktemp -d`
+trap 'rm -rf "$DIR"' EXIT
+
+for FEATURES in \
+    'default-features = true' \
+    'default-features = false' \
+    'default-features = false, features = ["simple-verify"]' \
+    'default-features = false, features = ["std", "simple-verify"]' \
+    'default-features = false, features = ["simple"]' \
+    'default-features = true, features = ["parallel", "yescrypt"]'
+do
+    rm -rf "$DIR/dependent"
+    cargo new --lib --quiet --vcs none "$DIR/dependent" || exit 1
+    cp Cargo.lock "$DIR/dependent/" || exit 1
+    echo "scrypt = { path = \"$ROOT/scrypt\", $FEATURES }" >> "$DIR/dependent/Cargo.toml"
+    (cd "$DIR/dependent" && cargo build --verbose) || {
+        echo "dependent with $FEATURES failed"
+        exit 1
+    }
+done
@@ -6,8 +6,7 @@ DIRS=`ls -d */`
 cargo clean
 
 for DIR in $DIRS; do
-    # disable scrypt check as it does not support no_std
-    if [ $DIR = "target/" ] || [ $DIR = "scrypt/" ]
+    if [ $DIR = "target/" ]
     then
         continue
     fi
@@ -18,3 +17,11 @@ for DIR in $DIRS; do
     }
     cd ..
 done
+
+# the verification of `scrypt_simple` strings works without std as well
+cd scrypt
+xargo build --no-default-features --features simple-verify --verbose --target $TARGET || {
+    echo scrypt simple-verify failed
+    exit 1
+}
+cd ..
@@ -1,7 +1,9 @@
+// benchmarks require nightly, so keep them out of stable clippy runs
+#![cfg(not(clippy))]
 #![no_std]
 #![feature(test)]
 extern crate pbkdf2;
-extern crate sha_1;
+extern crate sha1;
 extern crate sha2;
 extern crate hmac;
 
@@ -17,7 +19,7 @@ pub fn pbkdf2_hmac_sha1_16384_20(bh: &mut Bencher) {
     let salt = b"salty salt";
     let mut buf = [0u8; 20];
     bh.iter(|| {
-        pbkdf2::<Hmac<sha_1::Sha1>>(password, salt, 16_384, &mut buf);
+        pbkdf2::<Hmac<sha1::Sha1>>(password, salt, 16_384, &mut buf);
         test::black_box(&buf);
     });
 }
@@ -8,7 +8,7 @@
 //! pbkdf2 = { version = "0.2", default-features = false }
 //! ```
 #![cfg_attr(not(feature = "include_simple"), no_std)]
-#![cfg_attr(feature = "cargo-clippy", allow(inline_always))]
+#![allow(clippy::inline_always)]
 extern crate crypto_mac;
 extern crate generic_array;
 extern crate byteorder;
@@ -170,7 +170,7 @@ pub fn pbkdf2_simple(password: &str, c: u32) -> io::Result<String> {
 /// # Arguments
 /// * `password` - The password to process
 /// * `hashed_value` - A string representing a hashed password returned by
-/// `pbkdf2_simple`
+///   `pbkdf2_simple`
 #[cfg(feature="include_simple")]
 pub fn pbkdf2_check(password: &str, hashed_value: &str)
     -> Result<(), self::errors::CheckError> {
@@ -184,13 +184,8 @@ pub fn pbkdf2_check(password: &str, hashed_value: &str)
 
     // Parse format - currenlty only version 0 is supported
     match iter.next() {
-        Some(fstr) => {
-            match fstr {
-                "0" => { }
-                _ => return Err(CheckError::InvalidFormat)
-            }
-        }
-        None => return Err(CheckError::InvalidFormat)
+        Some("0") => { }
+        _ => return Err(CheckError::InvalidFormat)
     }
 
     // Parse the iteration count
@@ -227,7 +222,7 @@ pub fn pbkdf2_check(password: &str, hashed_value: &str)
     if iter.next() != Some("") { Err(CheckError::InvalidFormat)?; }
 
     // Make sure there is no trailing data after the final "$"
-    if iter.next() != None { Err(CheckError::InvalidFormat)?; }
+    if iter.next().is_some() { Err(CheckError::InvalidFormat)?; }
 
     let mut output = vec![0u8; hash.len()];
     pbkdf2::<Hmac<Sha256>>(password.as_bytes(), &salt, c as usize, &mut output);
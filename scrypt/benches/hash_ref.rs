@@ -0,0 +1,50 @@
+// benchmarks require nightly, so keep them out of stable clippy runs
+#![cfg(not(clippy))]
+#![feature(test)]
+extern crate scrypt;
+
+extern crate test;
+
+use test::Bencher;
+use scrypt::{ScryptHash, ScryptHashRef};
+
+const RSCRYPT: &str =
+    "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$";
+const PHC: &str = "$scrypt$ln=10,r=8,p=1$LRtfeowOkT+kTmsMHS4/QA$\
+                   oQoHWim7dB7vcgd11aYdc4M4LhnrM0sAiuLqU82afpQ";
+
+#[bench]
+pub fn parse_rscrypt_owned(bh: &mut Bencher) {
+    bh.iter(|| test::black_box(ScryptHash::parse(test::black_box(RSCRYPT))));
+}
+
+#[bench]
+pub fn parse_rscrypt_ref(bh: &mut Bencher) {
+    bh.iter(|| test::black_box(ScryptHashRef::parse(test::black_box(RSCRYPT))));
+}
+
+#[bench]
+pub fn parse_phc_owned(bh: &mut Bencher) {
+    bh.iter(|| test::black_box(ScryptHash::parse(test::black_box(PHC))));
+}
+
+#[bench]
+pub fn parse_phc_ref(bh: &mut Bencher) {
+    bh.iter(|| test::black_box(ScryptHashRef::parse(test::black_box(PHC))));
+}
+
+#[bench]
+pub fn verify_rscrypt_owned(bh: &mut Bencher) {
+    bh.iter(|| {
+        let hash = ScryptHash::parse(test::black_box(RSCRYPT)).unwrap();
+        test::black_box(hash.verify("password"))
+    });
+}
+
+#[bench]
+pub fn verify_rscrypt_ref(bh: &mut Bencher) {
+    bh.iter(|| {
+        let hash = ScryptHashRef::parse(test::black_box(RSCRYPT)).unwrap();
+        test::black_box(hash.verify("password"))
+    });
+}
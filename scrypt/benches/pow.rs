@@ -0,0 +1,28 @@
+// benchmarks require nightly, so keep them out of stable clippy runs
+#![cfg(not(clippy))]
+#![feature(test)]
+extern crate scrypt;
+
+extern crate test;
+
+use test::Bencher;
+use scrypt::pow::scrypt_1024_1_1_256;
+use scrypt::{scrypt, Params};
+
+// one hash per iteration, hashes per second are 1e9 / ns per iteration
+#[bench]
+pub fn pow_fixed(bh: &mut Bencher) {
+    let header = [0x5au8; 80];
+    bh.iter(|| test::black_box(scrypt_1024_1_1_256(test::black_box(&header))));
+}
+
+#[bench]
+pub fn pow_generic(bh: &mut Bencher) {
+    let header = [0x5au8; 80];
+    let params = Params::new(10, 1, 1).unwrap();
+    bh.iter(|| {
+        let mut output = [0u8; 32];
+        scrypt(test::black_box(&header), &header, &params, &mut output).unwrap();
+        test::black_box(output)
+    });
+}
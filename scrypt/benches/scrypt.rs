@@ -0,0 +1,86 @@
+// benchmarks require nightly, so keep them out of stable clippy runs
+#![cfg(not(clippy))]
+#![feature(test)]
+extern crate scrypt;
+
+extern crate test;
+
+use test::Bencher;
+use scrypt::{scrypt, Params};
+
+// the recommended interactive parameters, ROMix with 2 N BlockMix calls
+#[bench]
+pub fn scrypt_15_8_1(bh: &mut Bencher) {
+    let params = Params::new(15, 8, 1).unwrap();
+    bh.iter(|| {
+        let mut output = [0u8; 32];
+        scrypt(test::black_box(b"password"), b"salt", &params, &mut output).unwrap();
+        test::black_box(output)
+    });
+}
+
+// V of 128 MiB and 1 GiB, filled with non-temporal stores
+#[bench]
+pub fn scrypt_17_8_1(bh: &mut Bencher) {
+    let params = Params::new(17, 8, 1).unwrap();
+    bh.iter(|| {
+        let mut output = [0u8; 32];
+        scrypt(test::black_box(b"password"), b"salt", &params, &mut output).unwrap();
+        test::black_box(output)
+    });
+}
+
+#[bench]
+pub fn scrypt_20_8_1(bh: &mut Bencher) {
+    let params = Params::new(20, 8, 1).unwrap();
+    bh.iter(|| {
+        let mut output = [0u8; 32];
+        scrypt(test::black_box(b"password"), b"salt", &params, &mut output).unwrap();
+        test::black_box(output)
+    });
+}
+
+// 8 and 16 KiB of blocks, whose PBKDF2 runs on the threads of rayon with
+// the `parallel` feature, compare `cargo bench --features parallel`
+#[bench]
+pub fn scrypt_10_8_8(bh: &mut Bencher) {
+    let params = Params::new(10, 8, 8).unwrap();
+    bh.iter(|| {
+        let mut output = [0u8; 32];
+        scrypt(test::black_box(b"password"), b"salt", &params, &mut output).unwrap();
+        test::black_box(output)
+    });
+}
+
+#[bench]
+pub fn scrypt_10_8_16(bh: &mut Bencher) {
+    let params = Params::new(10, 8, 16).unwrap();
+    bh.iter(|| {
+        let mut output = [0u8; 32];
+        scrypt(test::black_box(b"password"), b"salt", &params, &mut output).unwrap();
+        test::black_box(output)
+    });
+}
+
+// four blocks of 16 MiB, mixed on four threads of rayon with the `parallel`
+// feature, compare `cargo bench --features parallel`
+#[bench]
+pub fn scrypt_14_8_4(bh: &mut Bencher) {
+    let params = Params::new(14, 8, 4).unwrap();
+    bh.iter(|| {
+        let mut output = [0u8; 32];
+        scrypt(test::black_box(b"password"), b"salt", &params, &mut output).unwrap();
+        test::black_box(output)
+    });
+}
+
+// the final expansion of a long output
+#[bench]
+pub fn scrypt_1_1_1_64k(bh: &mut Bencher) {
+    let params = Params::new(1, 1, 1).unwrap();
+    let mut output = vec![0u8; 64 * 1024];
+    bh.iter(|| {
+        scrypt(test::black_box(b"password"), b"salt", &params, &mut output).unwrap();
+        test::black_box(output[0])
+    });
+}
@@ -0,0 +1,201 @@
+//! Hash, verify and benchmark from the command line.
+//!
+//! ```text
+//! scrypt-tool hash [--params ln=15,r=8,p=1] [--format rscrypt|phc]
+//! scrypt-tool verify --hash <hash>
+//! scrypt-tool bench [--target-ms 250] [--max-mem 256MiB]
+//! ```
+//!
+//! Passwords are read from the first line of stdin, without echo if it is
+//! a terminal, and are never printed. `verify` exits with `0` if the
+//! password matches, `1` if it does not and `2` for malformed hashes or
+//! arguments.
+extern crate libc;
+extern crate scrypt;
+
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::time::Instant;
+
+use scrypt::{scrypt, scrypt_check, scrypt_simple_fmt, OutputFormat, Params};
+use scrypt::errors::CheckError;
+
+const MISMATCH: i32 = 1;
+const INVALID: i32 = 2;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let code = match args.first().map(|a| &a[..]) {
+        Some("hash") => hash(&args[1..]),
+        Some("verify") => verify(&args[1..]),
+        Some("bench") => bench(&args[1..]),
+        _ => Err("usage: scrypt-tool hash|verify|bench [options]".to_string()),
+    };
+    match code {
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            eprintln!("scrypt-tool: {}", e);
+            process::exit(INVALID);
+        }
+    }
+}
+
+/// The values of `--name value` pairs, in the order of `names`.
+fn options(args: &[String], names: &[&str]) -> Result<Vec<Option<String>>, String> {
+    let mut values = vec![None; names.len()];
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let name = arg.strip_prefix("--").unwrap_or("");
+        let i = names.iter().position(|n| *n == name)
+            .ok_or_else(|| format!("unknown argument {}", arg))?;
+        let value = iter.next().ok_or_else(|| format!("{} needs a value", arg))?;
+        values[i] = Some(value.clone());
+    }
+    Ok(values)
+}
+
+fn hash(args: &[String]) -> Result<i32, String> {
+    let values = options(args, &["params", "format"])?;
+    let params = match values[0] {
+        Some(ref params) => parse_params(params)?,
+        None => Params::new(15, 8, 1).unwrap(),
+    };
+    let format = match values[1].as_ref().map(|f| &f[..]) {
+        None | Some("rscrypt") => OutputFormat::Rscrypt,
+        Some("phc") => OutputFormat::Phc,
+        Some(other) => Err(format!("unknown format {}", other))?,
+    };
+    let password = read_password()?;
+    let hashed = scrypt_simple_fmt(&password, &params, format).map_err(|e| e.to_string())?;
+    println!("{}", hashed);
+    Ok(0)
+}
+
+fn verify(args: &[String]) -> Result<i32, String> {
+    let values = options(args, &["hash"])?;
+    let hashed = values[0].clone().ok_or("verify needs --hash")?;
+    let password = read_password()?;
+    match scrypt_check(&password, &hashed) {
+        Ok(()) => {
+            println!("match");
+            Ok(0)
+        }
+        Err(CheckError::HashMismatch) => {
+            println!("mismatch");
+            Ok(MISMATCH)
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Find the largest `N` with `r = 8` and `p = 1` which takes at most
+/// `--target-ms` and `--max-mem`.
+fn bench(args: &[String]) -> Result<i32, String> {
+    let values = options(args, &["target-ms", "max-mem"])?;
+    let target_ms: u128 = match values[0] {
+        Some(ref ms) => ms.parse().map_err(|_| format!("invalid --target-ms {}", ms))?,
+        None => 250,
+    };
+    let max_mem = match values[1] {
+        Some(ref mem) => parse_size(mem)?,
+        None => 256 << 20,
+    };
+
+    let mut best = None;
+    for log_n in 1..32 {
+        let params = Params::new(log_n, 8, 1).unwrap();
+        if 128 * 8 * ((1u64 << log_n) + 2) > max_mem { break; }
+        let start = Instant::now();
+        let mut output = [0u8; 32];
+        scrypt(b"password", b"salt", &params, &mut output).expect("32 bytes are a valid length");
+        let ms = start.elapsed().as_millis();
+        if ms > target_ms { break; }
+        best = Some((log_n, ms));
+    }
+    match best {
+        Some((log_n, ms)) => {
+            println!("ln={},r=8,p=1 ({} ms)", log_n, ms);
+            Ok(0)
+        }
+        None => Err("no parameters fit the limits".to_string()),
+    }
+}
+
+/// Parse `ln=<log_n>,r=<r>,p=<p>`, in any order and with defaults for
+/// omitted ones.
+fn parse_params(value: &str) -> Result<Params, String> {
+    let (mut log_n, mut r, mut p) = (15u8, 8u32, 1u32);
+    for pair in value.split(',') {
+        let mut kv = pair.splitn(2, '=');
+        let invalid = || format!("invalid --params {}", value);
+        let (key, v) = (kv.next().unwrap(), kv.next().ok_or_else(invalid)?);
+        match key {
+            "ln" => log_n = v.parse().map_err(|_| invalid())?,
+            "r" => r = v.parse().map_err(|_| invalid())?,
+            "p" => p = v.parse().map_err(|_| invalid())?,
+            _ => Err(invalid())?,
+        }
+    }
+    Params::new(log_n, r, p).map_err(|e| e.to_string())
+}
+
+/// Parse a size of bytes with an optional `KiB`, `MiB` or `GiB` suffix.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let (digits, shift) = [("KiB", 10), ("MiB", 20), ("GiB", 30)].iter()
+        .filter_map(|&(suffix, shift)| value.strip_suffix(suffix).map(|d| (d, shift)))
+        .next()
+        .unwrap_or((value, 0));
+    let size: u64 = digits.parse().map_err(|_| format!("invalid size {}", value))?;
+    size.checked_mul(1 << shift).ok_or_else(|| format!("invalid size {}", value))
+}
+
+/// Read the first line of stdin, without its line ending.
+fn read_password() -> Result<String, String> {
+    let echo = Echo::disable();
+    if echo.is_some() {
+        eprint!("Password: ");
+        io::stderr().flush().ok();
+    }
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line).map_err(|e| e.to_string())?;
+    if echo.is_some() { eprintln!(); }
+    drop(echo);
+
+    if line.ends_with('\n') { line.pop(); }
+    if line.ends_with('\r') { line.pop(); }
+    Ok(line)
+}
+
+/// The terminal settings of stdin while echo is disabled, restored on drop.
+struct Echo {
+    #[cfg(unix)]
+    saved: libc::termios,
+}
+
+impl Echo {
+    /// `None` if stdin is not a terminal, e.g. a pipe.
+    #[cfg(unix)]
+    fn disable() -> Option<Echo> {
+        unsafe {
+            let mut saved = std::mem::zeroed();
+            if libc::isatty(0) != 1 || libc::tcgetattr(0, &mut saved) != 0 { return None; }
+            let mut quiet = saved;
+            quiet.c_lflag &= !libc::ECHO;
+            libc::tcsetattr(0, libc::TCSANOW, &quiet);
+            Some(Echo { saved })
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn disable() -> Option<Echo> { None }
+}
+
+impl Drop for Echo {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        unsafe {
+            libc::tcsetattr(0, libc::TCSANOW, &self.saved);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! Arbitrary stored hashes for `scrypt_check`.
+//!
+//! Run with `cargo fuzz run check corpus/check` from `scrypt/fuzz`. The
+//! corpus holds hashes of `PASSWORD`, so the fuzzer also reaches the
+//! successful paths.
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate scrypt;
+
+use std::str;
+
+use scrypt::{scrypt, scrypt_check_with_limits, Format, ScryptHash, VerifyLimits};
+
+const PASSWORD: &str = "password";
+
+/// A few milliseconds of work per input, parameters above are refused
+/// before anything is allocated.
+const LIMITS: VerifyLimits = VerifyLimits { max_memory: 1 << 22, max_ops: 1 << 16 };
+
+fuzz_target!(|data: &[u8]| {
+    let hashed = match str::from_utf8(data) {
+        Ok(hashed) => hashed,
+        Err(_) => return,
+    };
+    if scrypt_check_with_limits(PASSWORD, hashed, &LIMITS).is_err() {
+        return;
+    }
+
+    // the input must contain the real hash of `PASSWORD`, computed here
+    // without the parser's help
+    let hash = ScryptHash::parse(hashed).expect("verified hashes parse");
+    if let Format::Rscrypt | Format::Phc = hash.format() {
+        let mut expected = vec![0u8; hash.hash_bytes().len()];
+        scrypt(PASSWORD.as_bytes(), hash.salt(), &hash.params(), &mut expected)
+            .expect("the hash length was accepted");
+        assert_eq!(hash.hash_bytes(), &expected[..], "{}", hashed);
+    }
+});
@@ -0,0 +1,32 @@
+//! Arbitrary strings for the parsers of all formats behind `ScryptHash`.
+//!
+//! Run with `cargo fuzz run parse corpus/parse` from `scrypt/fuzz`.
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate scrypt;
+
+use std::str;
+
+use scrypt::{Format, ScryptHash, ScryptHashRef};
+
+fuzz_target!(|data: &[u8]| {
+    let hashed = match str::from_utf8(data) {
+        Ok(hashed) => hashed,
+        Err(_) => return,
+    };
+
+    // the owning and the borrowing parser agree on the formats of both
+    let owned = ScryptHash::parse(hashed);
+    if let Some(Format::Rscrypt) | Some(Format::Phc) = Format::detect(hashed) {
+        let borrowed = ScryptHashRef::parse(hashed).map(ScryptHash::from);
+        assert_eq!(owned, borrowed, "{}", hashed);
+    }
+
+    // the canonical encoding of a hash describes the same hash
+    if let Ok(hash) = owned {
+        let encoded = hash.to_string();
+        assert_eq!(ScryptHash::parse(&encoded).as_ref(), Ok(&hash), "{} {}", hashed, encoded);
+        assert_eq!(ScryptHash::from_bytes(&hash.to_bytes()).as_ref(), Ok(&hash), "{}", hashed);
+    }
+});
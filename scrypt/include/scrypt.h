@@ -0,0 +1,98 @@
+/* C interface of the scrypt crate, built with the `capi` feature. See
+ * src/capi.rs for building the library. Failures of crypto_scrypt and the
+ * *_str functions return -1 and set errno, scrypt_hash_password and
+ * scrypt_verify_password return one of the stable SCRYPT_E_* codes. */
+#ifndef SCRYPT_H
+#define SCRYPT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Derive buflen bytes into buf, compatible with Tarsnap's scrypt and
+ * libscrypt. Returns 0 on success. */
+int crypto_scrypt(const uint8_t *passwd, size_t passwdlen,
+                  const uint8_t *salt, size_t saltlen,
+                  uint64_t N, uint32_t r, uint32_t p,
+                  uint8_t *buf, size_t buflen);
+
+/* Hash password into a NUL-terminated rscrypt string in out, 128 bytes
+ * are always enough. Returns 0 on success. */
+int scrypt_simple_str(const char *password, uint8_t log_n, uint32_t r,
+                      uint32_t p, char *out, size_t outlen);
+
+/* Check password against hashed, like libscrypt_check: 1 if it matches,
+ * 0 if it does not and -1 for malformed strings. */
+int scrypt_check_str(const char *hashed, const char *password);
+
+/* Error codes of scrypt_hash_password and scrypt_verify_password, the
+ * ErrorCode values of the crate. Codes are never renumbered or reused. */
+#define SCRYPT_OK                      0
+#define SCRYPT_E_INVALID_OUTPUT_LEN    1
+#define SCRYPT_E_INVALID_PARAMS        2
+#define SCRYPT_E_HASH_MISMATCH         3
+#define SCRYPT_E_INVALID_FORMAT        4
+#define SCRYPT_E_INVALID_HASH_LEN      5
+#define SCRYPT_E_RNG                   6
+#define SCRYPT_E_UNSUPPORTED_PARAMS    7
+#define SCRYPT_E_INVALID_CHECKSUM      8
+#define SCRYPT_E_UNSUPPORTED_FORMAT    9
+#define SCRYPT_E_UNREPRESENTABLE       10
+#define SCRYPT_E_INVALID_SALT_LEN      11
+#define SCRYPT_E_INVALID_DK_LEN        12
+#define SCRYPT_E_HASH_TOO_SHORT        13
+#define SCRYPT_E_UNSUPPORTED_VERSION   14
+#define SCRYPT_E_UNEXPECTED_FIELD_LEN  15
+#define SCRYPT_E_NON_CANONICAL_BASE64  16
+#define SCRYPT_E_REHASH_FAILED         17
+#define SCRYPT_E_COST_EXCEEDS_LIMIT    18
+#define SCRYPT_E_NEEDS_PEPPER          19
+#define SCRYPT_E_REFUSED               20
+#define SCRYPT_E_NEEDS_ASSOCIATED_DATA 21
+#define SCRYPT_E_CORRUPT               22
+#define SCRYPT_E_INVALID_ARGUMENT      23
+#define SCRYPT_E_BUFFER_TOO_SMALL      24
+#define SCRYPT_E_INTERNAL              25
+#define SCRYPT_E_INVALID_FIELD         26
+#define SCRYPT_E_INVALID_BUFFER_LEN    27
+#define SCRYPT_E_SELF_TEST_FAILED      28
+#define SCRYPT_E_BACKEND               29
+#define SCRYPT_E_PASSWORD_TOO_LONG     30
+#define SCRYPT_E_NEEDS_CLIENT_SCHEME   32
+#define SCRYPT_E_CLIENT_SCHEME_MISMATCH 33
+#define SCRYPT_E_TAMPER_SUSPECTED      34
+#define SCRYPT_E_UNKNOWN_YEAR          37
+#define SCRYPT_E_KEY_UNAVAILABLE       38
+
+/* Parameters of scrypt_hash_password, N = 2^log_n. */
+typedef struct scrypt_params_t {
+    uint8_t log_n;
+    uint32_t r;
+    uint32_t p;
+} scrypt_params_t;
+
+/* Size of the buffer scrypt_hash_password needs for params, including the
+ * terminating NUL. NULL stands for the recommended parameters. */
+size_t scrypt_hash_password_len(const scrypt_params_t *params);
+
+/* Hash password into a NUL-terminated rscrypt string in out. NULL params
+ * stand for log_n = 15, r = 8, p = 1. Returns SCRYPT_OK or an error code,
+ * SCRYPT_E_BUFFER_TOO_SMALL if outlen < scrypt_hash_password_len(params).
+ * Unless out is NULL or outlen is 0, out is NUL-terminated afterwards and
+ * empty on failure. */
+int scrypt_hash_password(const char *password, const scrypt_params_t *params,
+                         char *out, size_t outlen);
+
+/* Check password against hashed. Returns SCRYPT_OK if it matches,
+ * SCRYPT_E_HASH_MISMATCH if it does not and other error codes for
+ * malformed strings. */
+int scrypt_verify_password(const char *password, const char *hashed);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,36 @@
+//! Delegation of the key derivation, e.g. to a service next to an HSM.
+//!
+//! A `HashBackend` only computes scrypt outputs: parsing, policies, salts
+//! and the encoding of hash strings stay in the process which uses this
+//! crate. `Verifier::builder().backend()` routes verifications through a
+//! backend, `SimpleConfig::hash_with_backend()` new hashes.
+use errors::BackendError;
+use params::Params;
+use scrypt;
+
+/// Computes scrypt outputs for `Verifier` and `SimpleConfig`.
+///
+/// Backends are `Send` and `Sync`, so that a verifier using one can be
+/// shared by the threads of a server.
+pub trait HashBackend: Send + Sync {
+    /// Derive `output.len()` bytes from `password` and `salt`, like
+    /// `scrypt()`.
+    ///
+    /// # Return
+    /// `Err(BackendError::InvalidOutputLen)` under the conditions of
+    /// `scrypt()`, the other errors if the backend fails.
+    fn derive(&self, password: &[u8], salt: &[u8], params: &Params, output: &mut [u8])
+        -> Result<(), BackendError>;
+}
+
+/// The backend computing scrypt in this process, which is used without one.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct LocalBackend;
+
+impl HashBackend for LocalBackend {
+    fn derive(&self, password: &[u8], salt: &[u8], params: &Params, output: &mut [u8])
+        -> Result<(), BackendError>
+    {
+        scrypt(password, salt, params, output).map_err(BackendError::InvalidOutputLen)
+    }
+}
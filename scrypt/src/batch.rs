@@ -0,0 +1,141 @@
+//! Verification of many password and hash pairs on several threads, and of
+//! several candidate passwords against one hash.
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use errors::CheckError;
+use {parse_checked, zero, Scratch, VerifyLimits};
+
+/// Options of `verify_many`.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct BatchOptions {
+    /// The most threads to verify on, `0` for one per available CPU. No
+    /// more threads than items are used.
+    pub threads: usize,
+    /// Limits on the cost of each hash, hashes exceeding them are reported
+    /// as `Err(CheckError::CostExceedsLimit)`. Up to `threads` hashes are
+    /// computed at once, so the memory used peaks at `threads * max_memory`.
+    pub limits: Option<VerifyLimits>,
+}
+
+/// The buffers a thread reuses for all of its hashes, wiped when dropped.
+#[derive(Default)]
+pub(crate) struct Buffers {
+    output: Vec<u8>,
+    scratch: Scratch,
+}
+
+impl Drop for Buffers {
+    fn drop(&mut self) {
+        zero(&mut self.output);
+    }
+}
+
+/// `verify_many` verifies each password against its hash like
+/// `scrypt_check_bytes`, distributed over the threads of `options`, e.g.
+/// to screen stored hashes against a list of breached passwords.
+///
+/// Threads take the next unverified item when they are done with one, so
+/// that a few expensive hashes do not hold up the others, and reuse their
+/// scrypt buffers between items.
+///
+/// # Arguments
+/// - items - The passwords, as bytes, and the hashes to verify them against
+/// - options - The number of threads and the limits on each hash
+///
+/// # Return
+/// The result of every item, at its position in `items`.
+pub fn verify_many<P: AsRef<[u8]> + Sync>(items: &[(P, &str)], options: &BatchOptions)
+    -> Vec<Result<(), CheckError>>
+{
+    let threads = match options.threads {
+        0 => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        n => n,
+    };
+    let threads = threads.min(items.len()).max(1);
+
+    let next = AtomicUsize::new(0);
+    let work = || {
+        let mut buffers = Buffers::default();
+        let mut results = Vec::new();
+        loop {
+            let i = next.fetch_add(1, Ordering::Relaxed);
+            let (ref password, stored) = match items.get(i) {
+                Some(item) => item,
+                None => return results,
+            };
+            let result = verify_one(password.as_ref(), stored, options.limits.as_ref(),
+                &mut buffers);
+            results.push((i, result));
+        }
+    };
+
+    let mut results = vec![None; items.len()];
+    thread::scope(|s| {
+        // the calling thread verifies as well
+        let handles: Vec<_> = (1..threads).map(|_| s.spawn(work)).collect();
+        let mut done = work();
+        for handle in handles {
+            done.extend(handle.join().unwrap_or_else(|e| panic::resume_unwind(e)));
+        }
+        for (i, result) in done {
+            results[i] = Some(result);
+        }
+    });
+    results.into_iter().map(|r| r.expect("every item was taken by a thread")).collect()
+}
+
+/// Verify one item of `verify_many`.
+pub(crate) fn verify_one(
+    password: &[u8], stored: &str, limits: Option<&VerifyLimits>, buffers: &mut Buffers,
+) -> Result<(), CheckError> {
+    let hash = parse_checked(stored)?;
+    let params = hash.params();
+    if let Some(limits) = limits {
+        if params.serial_memory() > limits.max_memory as u128
+            || params.salsa_ops() > limits.max_ops as u128
+        {
+            Err(CheckError::CostExceedsLimit)?;
+        }
+    }
+    let len = hash.hash_bytes().len();
+    if buffers.output.len() < len { buffers.output.resize(len, 0); }
+    hash.verify_with(password, &mut buffers.output[..len], &mut buffers.scratch)
+}
+
+/// `verify_candidates` checks several candidate passwords against one
+/// stored hash, e.g. the current password of a user and the previous ones
+/// a device may still send. The hash is parsed once and the scrypt buffers
+/// are reused for every candidate.
+///
+/// Every candidate is hashed and compared in constant time, also after a
+/// match, so the time depends on the number of candidates but not on which
+/// of them matched.
+///
+/// # Arguments
+/// - candidates - The passwords to try, as bytes
+/// - stored - A string of any format of `scrypt_check()`
+///
+/// # Return
+/// `Ok(Some(i))` for the first matching candidate `i`, `Ok(None)` if none
+/// matches and the errors of `scrypt_check` other than
+/// `CheckError::HashMismatch` for the hash.
+pub fn verify_candidates<P: AsRef<[u8]>>(candidates: &[P], stored: &str)
+    -> Result<Option<usize>, CheckError>
+{
+    let hash = parse_checked(stored)?;
+    let mut buffers = Buffers::default();
+    buffers.output.resize(hash.hash_bytes().len(), 0);
+
+    let mut matched = None;
+    for (i, candidate) in candidates.iter().enumerate() {
+        match hash.verify_with(candidate.as_ref(), &mut buffers.output, &mut buffers.scratch) {
+            Ok(()) => { matched = matched.or(Some(i)); }
+            Err(CheckError::HashMismatch) => (),
+            // the other errors are those of the hash, the same for every candidate
+            Err(e) => Err(e)?,
+        }
+    }
+    Ok(matched)
+}
@@ -0,0 +1,84 @@
+//! The scrypt key derivations of BIP-38 encrypted private keys, without the
+//! Base58Check encoding, the AES encryption and the elliptic curve
+//! arithmetic around them.
+//!
+//! - Without EC multiplication, `derive_bip38_key` derives `derivedhalf1`
+//!   and `derivedhalf2` from the passphrase and the address hash.
+//! - With EC multiplication, `derive_bip38_passfactor` derives the
+//!   `passfactor` of the passphrase, whose point `passfactor * G` is the
+//!   `passpoint`, and `derive_bip38_ec_key` derives `derivedhalf1` and
+//!   `derivedhalf2` from the `passpoint`.
+//!
+//! BIP-38 requires passphrases in Unicode normalization form C, which is
+//! left to the caller.
+use sha2::{Digest, Sha256};
+
+use params::Params;
+use scrypt;
+
+/// `N = 16384`, `r = 8`, `p = 8`, for derivations from the passphrase.
+fn passphrase_params() -> Params {
+    Params::new(14, 8, 8).unwrap()
+}
+
+/// `N = 1024`, `r = 1`, `p = 1`, for the derivation from the `passpoint`.
+fn passpoint_params() -> Params {
+    Params::new(10, 1, 1).unwrap()
+}
+
+/// The 64 bytes of `derivedhalf1 || derivedhalf2` of a key encrypted without
+/// EC multiplication.
+///
+/// # Arguments
+/// - `passphrase` - The passphrase, NFC normalized
+/// - `address_hash` - The first 4 bytes of the double SHA-256 of the
+///   address, bytes 3 to 7 of the encrypted key
+pub fn derive_bip38_key(passphrase: &str, address_hash: &[u8; 4]) -> [u8; 64] {
+    let mut derived = [0u8; 64];
+    scrypt(passphrase.as_bytes(), address_hash, &passphrase_params(), &mut derived).unwrap();
+    derived
+}
+
+/// The `passfactor` of the owner of an EC multiplied key, from the
+/// passphrase and the 8 bytes of `ownerentropy`.
+///
+/// # Arguments
+/// - `passphrase` - The passphrase, NFC normalized
+/// - `owner_entropy` - The `ownerentropy`, bytes 7 to 15 of the encrypted
+///   key
+/// - `lot_sequence` - Whether the last 4 bytes of `ownerentropy` are a lot
+///   and sequence number, flag `0x04` of the encrypted key. The salt is
+///   then only the first 4 bytes and the result is hashed once more.
+pub fn derive_bip38_passfactor(passphrase: &str, owner_entropy: &[u8; 8], lot_sequence: bool)
+    -> [u8; 32]
+{
+    let salt = if lot_sequence { &owner_entropy[..4] } else { &owner_entropy[..] };
+    let mut passfactor = [0u8; 32];
+    scrypt(passphrase.as_bytes(), salt, &passphrase_params(), &mut passfactor).unwrap();
+    if lot_sequence {
+        let mut sha = Sha256::default();
+        sha.input(&passfactor);
+        sha.input(owner_entropy);
+        passfactor.copy_from_slice(&Sha256::digest(&sha.result()));
+    }
+    passfactor
+}
+
+/// The 64 bytes of `derivedhalf1 || derivedhalf2` of an EC multiplied key.
+///
+/// # Arguments
+/// - `passpoint` - The compressed point `passfactor * G`
+/// - `address_hash` - The first 4 bytes of the double SHA-256 of the
+///   address, bytes 3 to 7 of the encrypted key
+/// - `owner_entropy` - The `ownerentropy`, bytes 7 to 15 of the encrypted
+///   key
+pub fn derive_bip38_ec_key(passpoint: &[u8; 33], address_hash: &[u8; 4], owner_entropy: &[u8; 8])
+    -> [u8; 64]
+{
+    let mut salt = [0u8; 12];
+    salt[..4].copy_from_slice(address_hash);
+    salt[4..].copy_from_slice(owner_entropy);
+    let mut derived = [0u8; 64];
+    scrypt(passpoint, &salt, &passpoint_params(), &mut derived).unwrap();
+    derived
+}
@@ -0,0 +1,41 @@
+//! Blind indexes of values, for equality searches of encrypted columns.
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use errors::InvalidOutputLen;
+use params::Params;
+use {derive_salt, scrypt};
+
+/// The domain-separation tag of `blind_index`, from which the salt of the
+/// index is derived.
+pub const BLIND_INDEX_TAG: &[u8] = b"scrypt blind index v1";
+
+/// A blind index of `value` under the secret server `key`, e.g. to look up
+/// rows by an encrypted e-mail address as in CipherSweet: the column holds
+/// `blind_index(key, email, ...)` next to the ciphertext, and the query
+/// computes the index of the searched address.
+///
+/// The index is scrypt of `value` under the salt
+/// `derive_salt(key, BLIND_INDEX_TAG)` with `params`, `out_len` bytes long.
+/// It is deterministic on purpose, the same key, value and parameters
+/// always give the same index. **It is not a password hash**: equal values
+/// have equal indexes, which shows which rows share a value, and all values
+/// share one salt. Use `scrypt_simple()` for passwords.
+///
+/// Indexes of fewer bytes are the prefixes of longer ones. A short index
+/// leaks less: with `n` rows and an index of `b` bits, a lookup returns
+/// about `n / 2^b` false positives, which are filtered after decryption.
+/// Choose `b` so that every index is shared by a few rows, e.g. 16 bits for
+/// 2^18 rows, instead of the full 32 bytes, which identify a value as soon
+/// as it is guessed.
+///
+/// # Return
+/// `Err(InvalidOutputLen)` if `out_len` is 0 or above the limit of
+/// `scrypt()`.
+pub fn blind_index(key: &[u8], value: &[u8], params: &Params, out_len: usize)
+    -> Result<Vec<u8>, InvalidOutputLen>
+{
+    let mut index = vec![0u8; out_len];
+    scrypt(value, &derive_salt(key, BLIND_INDEX_TAG), params, &mut index)?;
+    Ok(index)
+}
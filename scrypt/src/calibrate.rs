@@ -0,0 +1,158 @@
+//! Parameters calibrated to a target time on the running machine.
+use core::time::Duration;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_os = "emscripten")))))]
+use std::time::Instant;
+
+use params::Params;
+use scrypt;
+
+/// A monotonic clock for `Params::calibrate_with`.
+///
+/// `StdTime` is the clock of `std`. On targets without `Instant`, e.g.
+/// `wasm32-unknown-unknown` where it panics, or on embedded targets, the
+/// caller implements it on a clock of the host, e.g. `performance.now()` in
+/// browsers and Node.js or a hardware timer:
+///
+/// ```text
+/// struct Performance(web_sys::Performance);
+///
+/// impl TimeSource for Performance {
+///     fn now(&self) -> Duration {
+///         Duration::from_secs_f64(self.0.now() / 1000.0)
+///     }
+/// }
+/// ```
+pub trait TimeSource {
+    /// The time since an origin of the source, which does not go back.
+    fn now(&self) -> Duration;
+}
+
+/// The `TimeSource` of `std::time::Instant`, from its creation.
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_os = "emscripten")))))]
+#[derive(Clone, Copy, Debug)]
+pub struct StdTime {
+    origin: Instant,
+}
+
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_os = "emscripten")))))]
+impl StdTime {
+    /// A clock starting now.
+    pub fn new() -> StdTime {
+        StdTime { origin: Instant::now() }
+    }
+}
+
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_os = "emscripten")))))]
+impl Default for StdTime {
+    fn default() -> StdTime {
+        StdTime::new()
+    }
+}
+
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_os = "emscripten")))))]
+impl TimeSource for StdTime {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// The smallest `log_n` of the calibration, with `r = 8` 1 MiB.
+const MIN_LOG_N: u8 = 10;
+
+impl Params {
+    /// The parameters with `r = 8` and `p = 1` and the largest `N` whose
+    /// hash takes at most `target` on this machine and whose
+    /// `required_memory()` is at most `max_memory`, measured with
+    /// `StdTime`.
+    ///
+    /// `N` starts at `2^10` and doubles as long as the measured time of a
+    /// doubled `N` is expected to stay below `target`, so the calibration
+    /// takes about twice `target`. The result is never below `log_n = 10`,
+    /// even on a machine slower than `target` at 1 MiB. Measurements vary
+    /// with the load of the machine, the result should be stored in the
+    /// configuration rather than calibrated at every start, or the speed of
+    /// the machine cached with `calibrate_cached`.
+    ///
+    /// Not available on `wasm32-unknown-unknown`, where `Instant` panics,
+    /// use `calibrate_with` with a `TimeSource` of the host there.
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", not(target_os = "emscripten")))))]
+    pub fn calibrate(target: Duration, max_memory: usize) -> Params {
+        Params::calibrate_with(target, max_memory, &StdTime::new())
+    }
+
+    /// `calibrate`, measured with `clock`.
+    pub fn calibrate_with(target: Duration, max_memory: usize, clock: &dyn TimeSource)
+        -> Params
+    {
+        Throughput::measure(target, max_memory, clock).params(target, max_memory)
+    }
+}
+
+/// The parameters of the calibration, `r = 8` and `p = 1`.
+fn at(log_n: u8) -> Option<Params> {
+    Params::new(log_n, 8, 1).ok()
+}
+
+/// The speed of a machine: the time of one hash at the largest `N` the
+/// calibration measured, from which the time of the other `N` is
+/// extrapolated, doubling with every doubling of `N`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Throughput {
+    pub(crate) log_n: u8,
+    pub(crate) elapsed: Duration,
+}
+
+impl Throughput {
+    /// Measure hashes from `log_n = 10` on, doubling `N` as long as the
+    /// next `N` is expected to take at most `target` and to need at most
+    /// `max_memory`.
+    pub(crate) fn measure(target: Duration, max_memory: usize, clock: &dyn TimeSource)
+        -> Throughput
+    {
+        let mut log_n = MIN_LOG_N;
+        loop {
+            let params = at(log_n).expect("r = 8 accepts the calibrated N");
+            let elapsed = measure(&params, clock);
+            match at(log_n + 1) {
+                // the next `N` takes about twice as long
+                Some(next) if elapsed * 2 <= target
+                    && next.required_memory() <= max_memory as u128 => log_n += 1,
+                _ => return Throughput { log_n, elapsed },
+            }
+        }
+    }
+
+    /// The expected time of a hash at `log_n`, `None` if it overflows.
+    fn time(&self, log_n: u8) -> Option<Duration> {
+        if log_n < self.log_n {
+            return Some(self.elapsed / (1 << (self.log_n - log_n)));
+        }
+        1u32.checked_shl((log_n - self.log_n) as u32)
+            .and_then(|factor| self.elapsed.checked_mul(factor))
+    }
+
+    /// The parameters with the largest `N` expected to take at most
+    /// `target` and to need at most `max_memory`, at least `log_n = 10`.
+    pub(crate) fn params(&self, target: Duration, max_memory: usize) -> Params {
+        let mut best = at(MIN_LOG_N).expect("r = 8 accepts the calibrated N");
+        let mut log_n = MIN_LOG_N + 1;
+        while let Some(params) = at(log_n) {
+            if params.required_memory() > max_memory as u128 { break; }
+            match self.time(log_n) {
+                Some(time) if time <= target => best = params,
+                _ => break,
+            }
+            log_n += 1;
+        }
+        best
+    }
+}
+
+/// The time of one hash under `params`.
+fn measure(params: &Params, clock: &dyn TimeSource) -> Duration {
+    let mut output = [0u8; 32];
+    let start = clock.now();
+    scrypt(b"calibration", b"calibration salt", params, &mut output)
+        .expect("32 bytes are a valid output");
+    clock.now().checked_sub(start).unwrap_or_default()
+}
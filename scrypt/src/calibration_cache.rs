@@ -0,0 +1,125 @@
+//! Calibration results stored across process restarts.
+//!
+//! The cache file stores the speed of the machine, not the parameters, so
+//! one file serves every target:
+//!
+//! ```text
+//! scrypt-calibration 1
+//! hardware x86_64/Intel(R) Xeon(R) CPU @ 2.20GHz/8
+//! measured 1767225600
+//! log_n 14
+//! nanos 121634512
+//! ```
+//!
+//! `hardware` is the architecture, CPU model and number of cores the file
+//! was measured on, `measured` the time of the measurement in seconds since
+//! the Unix epoch, `log_n` and `nanos` the largest `N` measured and the time
+//! of its hash.
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use calibrate::{StdTime, Throughput, TimeSource};
+use params::Params;
+
+/// The first line of the cache file, with the version of its format.
+const HEADER: &str = "scrypt-calibration 1";
+
+/// How long a measurement is trusted, the load and the firmware of a
+/// machine change.
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Distinguishes the temporary files of the threads of a process.
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+impl Params {
+    /// `calibrate`, with the speed of the machine stored in the file
+    /// `cache`, so that only the first start of a process measures it.
+    ///
+    /// The file is measured again if it is missing, malformed, of another
+    /// version, older than 30 days or from other hardware, i.e. another
+    /// architecture, CPU model or number of cores. It is replaced
+    /// atomically, so that concurrent processes never read a partial file.
+    /// If it can not be read or written, e.g. in a read-only directory,
+    /// this is `calibrate`.
+    pub fn calibrate_cached(target: Duration, max_memory: usize, cache: &Path) -> Params {
+        Params::calibrate_cached_with(target, max_memory, cache, &StdTime::new())
+    }
+
+    /// `calibrate_cached`, measured with `clock`.
+    pub fn calibrate_cached_with(
+        target: Duration, max_memory: usize, cache: &Path, clock: &dyn TimeSource,
+    ) -> Params {
+        let hardware = hardware();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let cached = fs::read_to_string(cache).ok()
+            .and_then(|contents| parse(&contents, &hardware, now));
+        let throughput = match cached {
+            Some(throughput) => throughput,
+            None => {
+                let throughput = Throughput::measure(target, max_memory, clock);
+                // a cache which can not be written only costs the next start
+                // another measurement
+                let _ = store(cache, &encode(&hardware, now, &throughput));
+                throughput
+            }
+        };
+        throughput.params(target, max_memory)
+    }
+}
+
+/// The hardware a measurement is valid on.
+fn hardware() -> String {
+    let model = fs::read_to_string("/proc/cpuinfo").ok()
+        .and_then(|info| {
+            info.lines()
+                .find(|line| line.starts_with("model name"))
+                .and_then(|line| line.split(':').nth(1))
+                .map(|model| model.trim().to_string())
+        })
+        .unwrap_or_default();
+    let cores = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    format!("{}/{}/{}", env::consts::ARCH, model, cores)
+}
+
+fn encode(hardware: &str, now: Duration, throughput: &Throughput) -> String {
+    format!("{}\nhardware {}\nmeasured {}\nlog_n {}\nnanos {}\n",
+        HEADER, hardware, now.as_secs(), throughput.log_n, throughput.elapsed.as_nanos())
+}
+
+/// The measurement of `contents`, `None` if it is malformed, stale or of
+/// other hardware.
+fn parse(contents: &str, hardware: &str, now: Duration) -> Option<Throughput> {
+    let mut lines = contents.lines();
+    if lines.next() != Some(HEADER) { return None; }
+    let mut field = |name: &str| {
+        lines.next()
+            .and_then(|line| line.strip_prefix(name))
+            .and_then(|line| line.strip_prefix(' '))
+    };
+    if field("hardware")? != hardware { return None; }
+    let measured = Duration::from_secs(field("measured")?.parse().ok()?);
+    // measurements from the future are as suspicious as old ones
+    if measured > now || now - measured > MAX_AGE { return None; }
+    let log_n = field("log_n")?.parse().ok()?;
+    let elapsed = Duration::from_nanos(field("nanos")?.parse().ok()?);
+    if lines.next().is_some() || Params::new(log_n, 8, 1).is_err() { return None; }
+    Some(Throughput { log_n, elapsed })
+}
+
+/// Replace `cache` by `contents` through a temporary file in the same
+/// directory, so that readers see the old or the new file.
+fn store(cache: &Path, contents: &str) -> io::Result<()> {
+    let mut temp = cache.as_os_str().to_owned();
+    temp.push(format!(".{}.{}.tmp", process::id(), TEMP_FILES.fetch_add(1, Ordering::Relaxed)));
+    let result = fs::write(&temp, contents).and_then(|()| fs::rename(&temp, cache));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
@@ -0,0 +1,277 @@
+//! C interface, compatible with `crypto_scrypt` of Tarsnap's scrypt and
+//! libscrypt.
+//!
+//! The functions are declared in `include/scrypt.h`. Build a C library with
+//!
+//! ```text
+//! cargo rustc --release --features capi --crate-type staticlib
+//! ```
+//!
+//! or `--crate-type cdylib` for a shared one. Failures return `-1` and set
+//! `errno`, like the C implementations:
+//!
+//! - `EINVAL`: `N` is not a power of 2 greater than 1, other invalid
+//!   parameters, malformed strings or null pointers
+//! - `EFBIG`: `r * p >= 2^30` or `buflen > (2^32 - 1) * 32`
+//! - `ENOMEM`: the buffers of scrypt would not fit into the address space
+//! - `ERANGE`: the output buffer is too small
+//! - `EIO`: the random number generator failed
+//! - `EFAULT`: a panic was caught at the boundary, which is a bug
+//!
+//! Unlike Tarsnap's scrypt, `N >= 2^(16 * r)` is rejected with `EINVAL`, as
+//! required by RFC 7914.
+//!
+//! `scrypt_hash_password` and `scrypt_verify_password` do not use `errno`,
+//! they return `0` on success and the stable `ErrorCode` of the failure
+//! otherwise, declared as `SCRYPT_E_*` in the header.
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::panic;
+use std::slice;
+
+use libc;
+
+use config::recommended_params;
+use errors::{CheckError, ErrorCode};
+use params::Params;
+use {scrypt, scrypt_check_bytes, scrypt_simple_bytes};
+
+/// The parameters of `scrypt_hash_password`.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug)]
+pub struct scrypt_params_t {
+    /// log2 of `N`.
+    pub log_n: u8,
+    /// The scrypt parameter `r`.
+    pub r: u32,
+    /// The scrypt parameter `p`.
+    pub p: u32,
+}
+
+/// Result of the functions of this module.
+type Result<T> = ::core::result::Result<T, c_int>;
+
+/// Largest `buflen` of PBKDF2-HMAC-SHA256, `(2^32 - 1) * 32`.
+const MAX_BUFLEN: u64 = ((1 << 32) - 1) * 32;
+
+/// Derive `buflen` bytes into `buf` from the password and salt, with the
+/// parameters `N`, `r` and `p`.
+///
+/// # Return
+/// `0` on success, `-1` with `errno` set otherwise.
+///
+/// # Safety
+/// `passwd`, `salt` and `buf` must be valid for `passwdlen`, `saltlen` and
+/// `buflen` bytes, they may be null if their length is `0`.
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn crypto_scrypt(
+    passwd: *const u8, passwdlen: usize, salt: *const u8, saltlen: usize,
+    N: u64, r: u32, p: u32, buf: *mut u8, buflen: usize,
+) -> c_int {
+    guard(|| {
+        let params = params(N, r, p)?;
+        if buflen as u64 > MAX_BUFLEN { Err(libc::EFBIG)?; }
+        let password = input(passwd, passwdlen)?;
+        let salt = input(salt, saltlen)?;
+        if buflen == 0 { return Ok(0); }
+        if buf.is_null() { Err(libc::EINVAL)?; }
+        let output = slice::from_raw_parts_mut(buf, buflen);
+        scrypt(password, salt, &params, output).map_err(|_| libc::EINVAL)?;
+        Ok(0)
+    })
+}
+
+/// Hash the NUL-terminated `password` into an rscrypt string like
+/// `scrypt_simple()`, written with a terminating NUL into `out`.
+///
+/// # Return
+/// `0` on success, `-1` with `errno` set otherwise. 128 bytes hold the
+/// string for all parameters.
+///
+/// # Safety
+/// `password` must be NUL-terminated and `out` valid for `outlen` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn scrypt_simple_str(
+    password: *const c_char, log_n: u8, r: u32, p: u32, out: *mut c_char, outlen: usize,
+) -> c_int {
+    guard(|| {
+        let password = c_str(password)?;
+        let params = Params::new(log_n, r, p).map_err(|_| libc::EINVAL)?;
+        let hashed = scrypt_simple_bytes(password, &params).map_err(|_| libc::EIO)?;
+        if out.is_null() { Err(libc::EINVAL)?; }
+        if hashed.len() >= outlen { Err(libc::ERANGE)?; }
+        let out = slice::from_raw_parts_mut(out as *mut u8, outlen);
+        out[..hashed.len()].copy_from_slice(hashed.as_bytes());
+        out[hashed.len()] = 0;
+        Ok(0)
+    })
+}
+
+/// Check the NUL-terminated `password` against `hashed` like
+/// `scrypt_check()`, with the results of `libscrypt_check`.
+///
+/// # Return
+/// `1` if the password matches, `0` if it does not and `-1` with `errno`
+/// set for malformed strings.
+///
+/// # Safety
+/// `hashed` and `password` must be NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn scrypt_check_str(hashed: *const c_char, password: *const c_char)
+    -> c_int
+{
+    guard(|| {
+        let hashed = c_str(hashed)?;
+        let password = c_str(password)?;
+        let hashed = ::core::str::from_utf8(hashed).map_err(|_| libc::EINVAL)?;
+        match scrypt_check_bytes(password, hashed) {
+            Ok(()) => Ok(1),
+            Err(CheckError::HashMismatch) => Ok(0),
+            Err(_) => Err(libc::EINVAL),
+        }
+    })
+}
+
+/// The size of the buffer `scrypt_hash_password` needs for `params`,
+/// including the terminating NUL. `NULL` stands for the recommended
+/// parameters.
+///
+/// # Safety
+/// `params` must be null or valid.
+#[no_mangle]
+pub unsafe extern "C" fn scrypt_hash_password_len(params: *const scrypt_params_t) -> usize {
+    match params.as_ref() {
+        // `$rscrypt$1$` with 9 bytes of parameters
+        Some(params) if params.r >= 256 || params.p >= 256 => 95,
+        // `$rscrypt$0$` with 3 bytes of parameters
+        _ => 87,
+    }
+}
+
+/// Hash the NUL-terminated `password` into an rscrypt string like
+/// `scrypt_simple()`, written with a terminating NUL into `out`. `NULL`
+/// `params` stand for the recommended `log_n = 15`, `r = 8` and `p = 1`.
+///
+/// # Return
+/// `0` on success or an `ErrorCode`, e.g. `BufferTooSmall` if `outlen` is
+/// less than `scrypt_hash_password_len()`. Unless `out` is null or `outlen`
+/// is `0`, `out` holds a NUL-terminated string afterwards, the empty one on
+/// failure.
+///
+/// # Safety
+/// `password` must be NUL-terminated, `params` null or valid and `out`
+/// valid for `outlen` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn scrypt_hash_password(
+    password: *const c_char, params: *const scrypt_params_t, out: *mut c_char, outlen: usize,
+) -> c_int {
+    if !out.is_null() && outlen > 0 { *out = 0; }
+    guard_code(|| {
+        let password = c_str(password).map_err(|_| ErrorCode::InvalidArgument)?;
+        let params = match params.as_ref() {
+            Some(params) => Params::new(params.log_n, params.r, params.p)
+                .map_err(|e| e.code())?,
+            None => recommended_params(),
+        };
+        if out.is_null() { Err(ErrorCode::InvalidArgument)?; }
+        let hashed = scrypt_simple_bytes(password, &params).map_err(|e| e.code())?;
+        if hashed.len() >= outlen { Err(ErrorCode::BufferTooSmall)?; }
+        let out = slice::from_raw_parts_mut(out as *mut u8, outlen);
+        out[..hashed.len()].copy_from_slice(hashed.as_bytes());
+        out[hashed.len()] = 0;
+        Ok(())
+    })
+}
+
+/// Check the NUL-terminated `password` against `hashed` like
+/// `scrypt_check()`.
+///
+/// # Return
+/// `0` if the password matches, `ErrorCode::HashMismatch` (`3`) if it does
+/// not and other `ErrorCode`s for malformed strings.
+///
+/// # Safety
+/// `password` and `hashed` must be NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn scrypt_verify_password(password: *const c_char, hashed: *const c_char)
+    -> c_int
+{
+    guard_code(|| {
+        let password = c_str(password).map_err(|_| ErrorCode::InvalidArgument)?;
+        let hashed = c_str(hashed).map_err(|_| ErrorCode::InvalidArgument)?;
+        let hashed = ::core::str::from_utf8(hashed).map_err(|_| ErrorCode::InvalidFormat)?;
+        scrypt_check_bytes(password, hashed).map_err(|e| e.code())
+    })
+}
+
+/// Run `f`, turning errors and panics into their `ErrorCode`.
+fn guard_code<F>(f: F) -> c_int
+    where F: FnOnce() -> ::core::result::Result<(), ErrorCode> + panic::UnwindSafe
+{
+    match panic::catch_unwind(f) {
+        Ok(Ok(())) => 0,
+        Ok(Err(code)) => code.code() as c_int,
+        Err(_) => ErrorCode::Internal.code() as c_int,
+    }
+}
+
+/// Run `f`, turning errors and panics into `-1` and `errno`.
+fn guard<F: FnOnce() -> Result<c_int> + panic::UnwindSafe>(f: F) -> c_int {
+    match panic::catch_unwind(f) {
+        Ok(Ok(result)) => result,
+        Ok(Err(errno)) => fail(errno),
+        Err(_) => fail(libc::EFAULT),
+    }
+}
+
+fn fail(errno: c_int) -> c_int {
+    set_errno(errno);
+    -1
+}
+
+/// Validate the parameters in the order and with the errors of Tarsnap's
+/// `crypto_scrypt`.
+fn params(n: u64, r: u32, p: u32) -> Result<Params> {
+    if r as u64 * p as u64 >= 1 << 30 { Err(libc::EFBIG)?; }
+    if n < 2 || n & (n - 1) != 0 || r == 0 || p == 0 { Err(libc::EINVAL)?; }
+    let max = usize::MAX as u64;
+    if r as u64 > max / 128 / p as u64 || r as u64 > max / 256 || n > max / 128 / r as u64 {
+        Err(libc::ENOMEM)?;
+    }
+    Params::new(n.trailing_zeros() as u8, r, p).map_err(|_| libc::EINVAL)
+}
+
+unsafe fn input<'a>(data: *const u8, len: usize) -> Result<&'a [u8]> {
+    match (data.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(libc::EINVAL),
+        (false, len) => Ok(slice::from_raw_parts(data, len)),
+    }
+}
+
+unsafe fn c_str<'a>(s: *const c_char) -> Result<&'a [u8]> {
+    if s.is_null() { Err(libc::EINVAL)?; }
+    Ok(CStr::from_ptr(s).to_bytes())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "emscripten"))]
+fn set_errno(errno: c_int) {
+    unsafe { *libc::__errno_location() = errno; }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+fn set_errno(errno: c_int) {
+    unsafe { *libc::__error() = errno; }
+}
+
+#[cfg(any(target_os = "openbsd", target_os = "netbsd"))]
+fn set_errno(errno: c_int) {
+    unsafe { *libc::__errno() = errno; }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "emscripten",
+    target_os = "macos", target_os = "ios", target_os = "freebsd",
+    target_os = "openbsd", target_os = "netbsd")))]
+fn set_errno(_errno: c_int) {}
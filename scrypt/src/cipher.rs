@@ -0,0 +1,52 @@
+//! Keys of ciphers derived directly into arrays of their key size, e.g.
+//! the `GenericArray<u8, U32>` of AES-256-GCM or ChaCha20Poly1305:
+//!
+//! ```
+//! use scrypt::cipher::{derive_key, typenum::U32};
+//! use scrypt::Params;
+//!
+//! let params = Params::new(10, 8, 1).unwrap();
+//! let key = derive_key::<U32>(b"password", b"a unique salt", &params).unwrap();
+//! assert_eq!(key.len(), 32);
+//! ```
+//!
+//! The arrays are those of `generic-array` 0.9, the version of the hash
+//! crates of this crate. The `KeySize` of the `KeySizeUser` of the
+//! `crypto-common` crate is an array size of a later version, the key is
+//! converted with `clone_from_slice` of that version. Unlike `DerivedKey`
+//! the arrays are not overwritten with zeros when they are dropped.
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use errors::InvalidOutputLen;
+use {derive_key_and_iv, scrypt, Params};
+
+pub use generic_array::{typenum, ArrayLength, GenericArray};
+
+/// Derive a key of `N` bytes, the output of `scrypt()` of that length.
+///
+/// # Return
+/// `Err(InvalidOutputLen)` if `N` is 0.
+pub fn derive_key<N: ArrayLength<u8>>(password: &[u8], salt: &[u8], params: &Params)
+    -> Result<GenericArray<u8, N>, InvalidOutputLen>
+{
+    let mut key = GenericArray::default();
+    scrypt(password, salt, params, &mut key)?;
+    Ok(key)
+}
+
+/// Derive a key of `N` bytes and a nonce of `nonce_len` bytes, split like
+/// those of `derive_key_and_iv()`: the key is the first `N` bytes of one
+/// output, the nonce the rest.
+///
+/// **WARNING:** the nonce is a function of the password and the salt, use
+/// a new random salt for every encryption.
+///
+/// # Return
+/// The errors of `derive_key_and_iv()`.
+pub fn derive_key_and_nonce<N: ArrayLength<u8>>(
+    password: &[u8], salt: &[u8], params: &Params, nonce_len: usize,
+) -> Result<(GenericArray<u8, N>, Vec<u8>), InvalidOutputLen> {
+    let (key, nonce) = derive_key_and_iv(password, salt, params, N::to_usize(), nonce_len)?;
+    Ok((GenericArray::clone_from_slice(&key), nonce))
+}
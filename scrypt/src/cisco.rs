@@ -0,0 +1,60 @@
+//! Cisco IOS type 9 secrets:
+//!
+//! `$9$<salt>$<base64(hash)>`
+//!
+//! The parameters are fixed to `N = 16384`, `r = 1` and `p = 1`. The salt is
+//! 14 characters of the `./0-9A-Za-z` alphabet passed to scrypt verbatim.
+//! The hash uses the same alphabet with the bit order of standard base64
+//! and no padding.
+// `Base64Crypt` is deprecated in favor of the bit order of crypt(3), which
+// type 9 secrets do not use
+#![allow(deprecated)]
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use base64ct::{Base64Crypt, Encoding};
+
+use errors::CheckError;
+use params::Params;
+
+/// Prefix identifying type 9 secrets.
+pub(crate) const PREFIX: &str = "$9$";
+
+/// Alphabet of salt and hash.
+pub(crate) const ALPHABET: &[u8] =
+    b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Length of the salt.
+pub(crate) const SALT_LEN: usize = 14;
+
+/// The fixed parameters of type 9 secrets.
+pub(crate) fn params() -> Params {
+    Params { log_n: 14, r: 1, p: 1 }
+}
+
+/// Encode a type 9 secret.
+pub(crate) fn encode(salt: &str, hash: &[u8]) -> String {
+    format!("$9${}${}", salt, Base64Crypt::encode_string(hash))
+}
+
+/// Decode a type 9 secret into parameters, salt and hash.
+pub(crate) fn decode(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    if !hashed_value.starts_with(PREFIX) { Err(CheckError::InvalidFormat)?; }
+    let fields: Vec<&str> = hashed_value[PREFIX.len()..].split('$').collect();
+    if fields.len() != 2 { Err(CheckError::InvalidFormat)?; }
+
+    let salt = fields[0];
+    if salt.len() != SALT_LEN || !salt.bytes().all(|b| ALPHABET.contains(&b)) {
+        Err(CheckError::InvalidFormat)?;
+    }
+
+    // `base64ct` rejects non-zero trailing bits, only the canonical encoding
+    // is accepted
+    let hash = Base64Crypt::decode_vec(fields[1]).map_err(|_| CheckError::InvalidFormat)?;
+    if hash.len() != 32 { Err(CheckError::InvalidFormat)?; }
+
+    Ok((params(), salt.as_bytes().to_vec(), hash))
+}
@@ -0,0 +1,18 @@
+//! Schemes of passwords digested by the client before they are sent.
+
+/// The scheme of a client-side digest of a password, e.g. of a web client
+/// which sends `SHA-256(password)` instead of the password. The server
+/// hashes the digest with `scrypt_simple_client`, which records the id of
+/// the scheme in the string, so that a digest of another scheme or a hash
+/// of the plain password is not mistaken for it when the client changes.
+///
+/// Ids are assigned by the application, the crate only compares them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ClientScheme(pub u8);
+
+impl ClientScheme {
+    /// The id recorded in hash strings.
+    pub fn id(&self) -> u8 {
+        self.0
+    }
+}
@@ -0,0 +1,644 @@
+//! Configuration of the `scrypt_simple` family in a single value.
+use std::num::NonZeroU32;
+use std::time::{Instant, SystemTime};
+
+use rand_core::{CryptoRng, RngCore};
+
+use backend::HashBackend;
+use client::ClientScheme;
+use errors::{CheckError, HashError};
+use format::{Format, HashFormat, HashParts};
+use key::DerivedKey;
+use observer::{check_hash_params, global_observer, HashEvent, ScryptObserver};
+use params::Params;
+use pepper::{prehash_provided, KeyProvider};
+use {cisco, crypt7, django, phpscrypt, rubyscrypt, scryptkdf, simplescrypt, werkzeug};
+use prehash::{check_len, LengthPolicy, DEFAULT_MAX_PASSWORD_LEN};
+use {check_policy_limited, constant_time_eq, derive_salt, derived_output_len,
+    encode_rscrypt_config, os_rng, rscrypt_v2_supports, scrypt, text_salt, unix_days, V2Options,
+    Verified, ALNUM};
+use {MAX_DK_LEN, MAX_SALT_LEN, MIN_DK_LEN, MIN_SALT_LEN};
+
+/// The parameters, salt length, hash length and format of new hashes and
+/// the longest password accepted.
+///
+/// `SimpleConfig::default()` produces the strings of `scrypt_simple()` with
+/// the recommended parameters `log_n = 15`, `r = 8` and `p = 1`, other
+/// configurations are created with `SimpleConfig::builder()`, which checks
+/// that the format can hold the result. The builder also selects the
+/// rscrypt strings of the other `scrypt_simple_*` functions, e.g.
+/// `version(2)` those of `scrypt_simple_v2()`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SimpleConfig {
+    params: Params,
+    salt_len: usize,
+    dk_len: usize,
+    format: Format,
+    max_password_len: Option<usize>,
+    record_created_at: bool,
+    url_safe: bool,
+    version: u8,
+    /// The extensions of version 2 strings, without the day of creation.
+    v2: V2Options,
+}
+
+impl SimpleConfig {
+    /// Start a configuration from the defaults.
+    pub fn builder() -> SimpleConfigBuilder {
+        SimpleConfigBuilder {
+            params: recommended_params(),
+            salt_len: None,
+            dk_len: None,
+            format: Format::Rscrypt,
+            max_password_len: Some(DEFAULT_MAX_PASSWORD_LEN),
+            record_created_at: false,
+            url_safe: false,
+            version: None,
+            v2: V2Options::default(),
+        }
+    }
+
+    /// The parameters of new hashes.
+    pub fn params(&self) -> Params { self.params }
+
+    /// The salt length of new hashes in bytes, or characters for formats
+    /// which store the salt as plain text.
+    pub fn salt_len(&self) -> usize { self.salt_len }
+
+    /// The hash length of new hashes in bytes.
+    pub fn dk_len(&self) -> usize { self.dk_len }
+
+    /// The format of new hashes.
+    pub fn format(&self) -> Format { self.format }
+
+    /// The longest password in bytes hashed or verified, `None` if the
+    /// length is not limited.
+    pub fn max_password_len(&self) -> Option<usize> { self.max_password_len }
+
+    /// If new hashes record the day they were created on.
+    pub fn records_created_at(&self) -> bool { self.record_created_at }
+
+    /// If new rscrypt strings use the URL-safe base64 alphabet.
+    pub fn url_safe(&self) -> bool { self.url_safe }
+
+    /// The version of new rscrypt strings, 2 or 0 for version 0 or 1
+    /// depending on the parameters.
+    pub fn version(&self) -> u8 { self.version }
+
+    /// The threshold of pre-hashing, `None` if passwords are not pre-hashed.
+    pub fn prehash_above(&self) -> Option<u16> { self.v2.prehash_above }
+
+    /// The iterations of the final PBKDF2, `None` for the single one of
+    /// scrypt.
+    pub fn final_rounds(&self) -> Option<NonZeroU32> { self.v2.final_rounds }
+
+    /// If the header of new strings is bound into the salt.
+    pub fn bound(&self) -> bool { self.v2.bound }
+
+    /// The scheme of the client-side digests hashed, `None` for passwords.
+    pub fn client_scheme(&self) -> Option<ClientScheme> {
+        self.v2.client_scheme.map(ClientScheme)
+    }
+
+    /// The id of the pepper mixed into passwords, `None` without a pepper.
+    pub fn pepper_id(&self) -> Option<u8> { self.v2.pepper }
+
+    /// If new strings are bound to associated data.
+    pub fn associated_data(&self) -> bool { self.v2.associated }
+
+    /// The extensions of a new rscrypt version 2 string, with the current
+    /// day if new hashes record it.
+    pub(crate) fn v2_options(&self) -> V2Options {
+        let created_at = if self.record_created_at {
+            Some(unix_days(SystemTime::now()))
+        } else {
+            None
+        };
+        V2Options { created_at, ..self.v2 }
+    }
+
+    /// Encode the parts of a new hash in the format of this configuration.
+    fn encode(&self, parts: &HashParts, v2: &V2Options) -> String {
+        if self.version == 2 {
+            v2.encode(&parts.params, &parts.salt, &parts.hash)
+        } else if self.url_safe {
+            encode_rscrypt_config(&parts.params, &parts.salt, &parts.hash, true)
+        } else {
+            self.format.encode(parts)
+        }
+    }
+
+    /// Hash `password` under a random salt drawn from `OsRng`.
+    ///
+    /// # Return
+    /// `Err(HashError::PasswordTooLong)` if the password is longer than
+    /// `max_password_len()`, `Err(HashError::UnknownPepper)` if the
+    /// configuration has a pepper, which `hash_with_keys` mixes in,
+    /// `Err(HashError::UnsupportedParams)` if it binds associated data,
+    /// which `hash_with_ad` takes, and `Err(HashError::Rng)` in the case of
+    /// an unlikely `OsRng` failure.
+    pub fn hash_with(&self, password: &str) -> Result<String, HashError> {
+        let mut rng = os_rng();
+        self.hash_with_rng(password, &mut rng)
+    }
+
+    /// Same as `hash_with`, but draws the salt from the provided `rng`
+    /// instead of `OsRng`.
+    pub fn hash_with_rng<R: RngCore + CryptoRng>(&self, password: &str, rng: &mut R)
+        -> Result<String, HashError>
+    {
+        match global_observer() {
+            Some(observer) => self.hash_observed(password, rng, &*observer),
+            None => self.hash_bytes_with_rng(password.as_bytes(), rng),
+        }
+    }
+
+    /// Same as `hash_with_rng`, but reports the event to `observer` instead
+    /// of the one of `set_global_observer()`.
+    pub fn hash_observed<R: RngCore + CryptoRng>(
+        &self, password: &str, rng: &mut R, observer: &dyn ScryptObserver,
+    ) -> Result<String, HashError> {
+        let start = Instant::now();
+        let result = self.hash_bytes_with_rng(password.as_bytes(), rng);
+        observer.on_hash(&HashEvent {
+            params: self.params,
+            format: self.format,
+            result: result.as_ref().map(|_| ()).map_err(|e| e.code()),
+            elapsed: start.elapsed(),
+        });
+        result
+    }
+
+    /// `hash_with_rng()` for passwords which are not valid UTF-8.
+    pub(crate) fn hash_bytes_with_rng<R: RngCore>(&self, password: &[u8], rng: &mut R)
+        -> Result<String, HashError>
+    {
+        if let Some(id) = self.v2.pepper { Err(HashError::UnknownPepper(id))?; }
+        if self.v2.associated { Err(HashError::UnsupportedParams)?; }
+        let (parts, v2) = self.parts_with_rng(password, rng)?;
+        Ok(self.encode(&parts, &v2))
+    }
+
+    /// `hash_bytes_with_rng()` under `salt` instead of a random salt, for
+    /// `scrypt_simple_with_salt`.
+    pub(crate) fn hash_bytes_with_salt(&self, password: &[u8], salt: &[u8])
+        -> Result<String, HashError>
+    {
+        if self.v2.needs_input() { Err(HashError::UnsupportedParams)?; }
+        check_len(password, self.max_password_len)?;
+        let (parts, v2) = self.parts_with_salt(password, &[], salt.to_vec());
+        Ok(self.encode(&parts, &v2))
+    }
+
+    /// Hash `password` under a random salt drawn from `OsRng`, mixing in the
+    /// pepper of `pepper_id()` from `keys`, like `scrypt_simple_provided`.
+    ///
+    /// # Return
+    /// `Err(HashError::UnsupportedParams)` if the configuration has no
+    /// pepper, `Err(HashError::UnknownPepper)` if `keys` has no key of its
+    /// id, `Err(HashError::KeyUnavailable)` if it can not be fetched and
+    /// the errors of `hash_with` otherwise.
+    pub fn hash_with_keys<K: KeyProvider + ?Sized>(&self, password: &str, keys: &K)
+        -> Result<String, HashError>
+    {
+        let mut rng = os_rng();
+        self.hash_with_keys_rng(password, keys, &mut rng)
+    }
+
+    /// Same as `hash_with_keys`, but draws the salt from the provided `rng`
+    /// instead of `OsRng`.
+    pub fn hash_with_keys_rng<K: KeyProvider + ?Sized, R: RngCore + CryptoRng>(
+        &self, password: &str, keys: &K, rng: &mut R,
+    ) -> Result<String, HashError> {
+        let id = self.v2.pepper.ok_or(HashError::UnsupportedParams)?;
+        check_len(password.as_bytes(), self.max_password_len)?;
+        let peppered = prehash_provided(keys, id, password.as_bytes())
+            .map_err(|e| e.hash_error(id))?;
+        let (parts, v2) = self.parts_unchecked(&peppered, &[], rng)?;
+        Ok(self.encode(&parts, &v2))
+    }
+
+    /// Hash `password` under a random salt drawn from `OsRng`, bound to the
+    /// associated data `ad` like `scrypt_simple_ad()`.
+    ///
+    /// # Return
+    /// `Err(HashError::UnsupportedParams)` if the configuration does not
+    /// bind associated data, see `SimpleConfigBuilder::associated_data`, and
+    /// the errors of `hash_with` otherwise.
+    pub fn hash_with_ad(&self, password: &str, ad: &[u8]) -> Result<String, HashError> {
+        let mut rng = os_rng();
+        self.hash_with_ad_rng(password, ad, &mut rng)
+    }
+
+    /// Same as `hash_with_ad`, but draws the salt from the provided `rng`
+    /// instead of `OsRng`.
+    pub fn hash_with_ad_rng<R: RngCore + CryptoRng>(
+        &self, password: &str, ad: &[u8], rng: &mut R,
+    ) -> Result<String, HashError> {
+        if !self.v2.associated { Err(HashError::UnsupportedParams)?; }
+        check_len(password.as_bytes(), self.max_password_len)?;
+        let (parts, v2) = self.parts_unchecked(password.as_bytes(), ad, rng)?;
+        Ok(self.encode(&parts, &v2))
+    }
+
+    /// Hash `password` under a random salt drawn from `OsRng` like
+    /// `hash_and_derive()`, and return a key of `key_len` bytes from the same
+    /// computation of scrypt with the string. The string is of rscrypt
+    /// version 2 with the flag of `hash_and_derive()`, which no other option
+    /// of version 2 strings is combined with.
+    ///
+    /// # Return
+    /// `Err(HashError::UnsupportedParams)` if the configuration is not of
+    /// the rscrypt format, is URL-safe or has an option of version 2
+    /// strings, `Err(HashError::InvalidOutputLen)` if `key_len` is 0 or
+    /// scrypt can not produce the hash and the key, and the errors of
+    /// `hash_with` otherwise.
+    pub fn hash_and_derive(&self, password: &str, key_len: usize)
+        -> Result<(String, DerivedKey), HashError>
+    {
+        let mut rng = os_rng();
+        self.hash_and_derive_with_rng(password, key_len, &mut rng)
+    }
+
+    /// Same as `hash_and_derive`, but draws the salt from the provided `rng`
+    /// instead of `OsRng`.
+    pub fn hash_and_derive_with_rng<R: RngCore + CryptoRng>(
+        &self, password: &str, key_len: usize, rng: &mut R,
+    ) -> Result<(String, DerivedKey), HashError> {
+        let plain = V2Options::default();
+        if self.format != Format::Rscrypt || self.url_safe || self.record_created_at
+            || self.v2 != plain
+        {
+            Err(HashError::UnsupportedParams)?;
+        }
+        let output_len = derived_output_len(self.dk_len, key_len)
+            .map_err(HashError::InvalidOutputLen)?;
+        check_len(password.as_bytes(), self.max_password_len)?;
+        check_hash_params(&self.params);
+        let salt = self.salt_with_rng(rng)?;
+        let v2 = V2Options { derived: true, ..plain };
+        let mut output = vec![0u8; output_len];
+        v2.hash_into(password.as_bytes(), &self.params, &salt, &[], &mut output)
+            .map_err(HashError::InvalidOutputLen)?;
+        let key = DerivedKey::new(output.split_off(self.dk_len));
+        Ok((v2.encode(&self.params, &salt, &output), key))
+    }
+
+    /// Same as `hash_with_rng`, but the hash is computed by `backend`
+    /// instead of in this process. The salt is drawn and the string encoded
+    /// locally, so the result is the same as that of `hash_with_rng` for
+    /// the same `rng`.
+    ///
+    /// # Return
+    /// `Err(HashError::Backend)` if `backend` fails and
+    /// `Err(HashError::UnsupportedParams)` if the configuration changes the
+    /// computation of scrypt, e.g. with `final_rounds`, or adds an input.
+    pub fn hash_with_backend<R: RngCore + CryptoRng>(
+        &self, password: &str, rng: &mut R, backend: &dyn HashBackend,
+    ) -> Result<String, HashError> {
+        let v2 = self.v2_options();
+        if v2.changes_hash() || v2.needs_input() { Err(HashError::UnsupportedParams)?; }
+        check_len(password.as_bytes(), self.max_password_len)?;
+        check_hash_params(&self.params);
+        let salt = self.salt_with_rng(rng)?;
+        let mut hash = vec![0u8; self.dk_len];
+        backend.derive(password.as_bytes(), &salt, &self.params, &mut hash)
+            .map_err(HashError::Backend)?;
+        Ok(self.encode(&HashParts { params: self.params, salt, hash }, &v2))
+    }
+
+    /// The parts of a new hash of `password` and the extensions of its
+    /// string, drawing the salt from `rng`.
+    pub(crate) fn parts_with_rng<R: RngCore>(&self, password: &[u8], rng: &mut R)
+        -> Result<(HashParts, V2Options), HashError>
+    {
+        check_len(password, self.max_password_len)?;
+        self.parts_unchecked(password, &[], rng)
+    }
+
+    /// `parts_with_rng()` without the limit on the length of `password`,
+    /// bound to the associated data `ad` if the configuration is.
+    fn parts_unchecked<R: RngCore>(&self, password: &[u8], ad: &[u8], rng: &mut R)
+        -> Result<(HashParts, V2Options), HashError>
+    {
+        check_hash_params(&self.params);
+        let salt = self.salt_with_rng(rng)?;
+        Ok(self.parts_with_salt(password, ad, salt))
+    }
+
+    /// The parts of the hash of `password` under `salt` and `ad`, and the
+    /// extensions of its string.
+    fn parts_with_salt(&self, password: &[u8], ad: &[u8], salt: Vec<u8>)
+        -> (HashParts, V2Options)
+    {
+        // the day is bound into the salt, so it is only read once
+        let v2 = self.v2_options();
+        let mut hash = vec![0u8; self.dk_len];
+        v2.hash_into(password, &self.params, &salt, ad, &mut hash)
+            .expect("the builder checked the hash length");
+        (HashParts { params: self.params, salt, hash }, v2)
+    }
+
+    /// A new salt of this configuration drawn from `rng`.
+    fn salt_with_rng<R: RngCore>(&self, rng: &mut R) -> Result<Vec<u8>, HashError> {
+        Ok(match salt_chars(self.format) {
+            Some(chars) => text_salt(chars, self.salt_len, rng)?.into_bytes(),
+            None => {
+                let mut salt = vec![0u8; self.salt_len];
+                rng.try_fill_bytes(&mut salt)?;
+                // the Ruby gem removes leading zero bytes from its salts
+                while self.format == Format::RubyScrypt && salt[0] == 0 {
+                    rng.try_fill_bytes(&mut salt[..1])?;
+                }
+                salt
+            }
+        })
+    }
+
+    /// Compare `password` against `stored`, like `Verifier::verify_strength`
+    /// with the parameters of this configuration as the `min_params` and its
+    /// limit on the length of the password.
+    ///
+    /// # Return
+    /// `Ok(Verified::OkButWeak)` if the password matches, but `stored` was
+    /// computed with weaker parameters, the errors of `scrypt_check`
+    /// otherwise.
+    pub fn verify(&self, password: &str, stored: &str) -> Result<Verified, CheckError> {
+        check_policy_limited(password, stored, &self.params, self.max_password_len)
+    }
+
+    /// The hash of `password` under the salt `derive_salt(global_key,
+    /// identifier)`, of the parameters and hash length of this
+    /// configuration. Its salt length, format and rscrypt options do not
+    /// apply: the result is the bare hash, to be stored without a salt and
+    /// verified with `verify_derived`.
+    ///
+    /// For storage-constrained systems only, see `derive_salt` for why
+    /// random salts are better.
+    pub fn derive_hash(&self, password: &[u8], global_key: &[u8], identifier: &[u8])
+        -> Vec<u8>
+    {
+        check_hash_params(&self.params);
+        let mut hash = vec![0u8; self.dk_len];
+        scrypt(password, &derive_salt(global_key, identifier), &self.params, &mut hash)
+            .expect("the builder checked the hash length");
+        hash
+    }
+
+    /// Compare `password` against a hash of `derive_hash`, recomputing the
+    /// salt from `global_key` and `identifier`.
+    ///
+    /// # Return
+    /// `Err(CheckError::HashMismatch)` if the password does not match and
+    /// `Err(CheckError::InvalidFormat)` if `stored` is not of the hash
+    /// length of this configuration.
+    pub fn verify_derived(
+        &self, password: &[u8], global_key: &[u8], identifier: &[u8], stored: &[u8],
+    ) -> Result<(), CheckError> {
+        if stored.len() != self.dk_len { Err(CheckError::InvalidFormat)?; }
+        if constant_time_eq(&self.derive_hash(password, global_key, identifier), stored) {
+            Ok(())
+        } else {
+            Err(CheckError::HashMismatch)
+        }
+    }
+}
+
+impl Default for SimpleConfig {
+    fn default() -> SimpleConfig {
+        SimpleConfig::builder().build().expect("the defaults are valid")
+    }
+}
+
+/// Builder of `SimpleConfig`, see `SimpleConfig::builder()`.
+#[derive(Clone, Copy, Debug)]
+pub struct SimpleConfigBuilder {
+    params: Params,
+    salt_len: Option<usize>,
+    dk_len: Option<usize>,
+    format: Format,
+    max_password_len: Option<usize>,
+    record_created_at: bool,
+    url_safe: bool,
+    version: Option<u8>,
+    v2: V2Options,
+}
+
+impl SimpleConfigBuilder {
+    /// Set the parameters, `log_n = 15`, `r = 8` and `p = 1` by default.
+    pub fn params(&mut self, params: Params) -> &mut SimpleConfigBuilder {
+        self.params = params;
+        self
+    }
+
+    /// Set the salt length. Defaults to the length of the format, e.g. 16
+    /// bytes for rscrypt and 22 characters for Django.
+    pub fn salt_len(&mut self, salt_len: usize) -> &mut SimpleConfigBuilder {
+        self.salt_len = Some(salt_len);
+        self
+    }
+
+    /// Set the hash length. Defaults to the length of the format, e.g. 32
+    /// bytes for rscrypt and 64 for Django.
+    pub fn dk_len(&mut self, dk_len: usize) -> &mut SimpleConfigBuilder {
+        self.dk_len = Some(dk_len);
+        self
+    }
+
+    /// Set the format, `Format::Rscrypt` by default.
+    pub fn format(&mut self, format: Format) -> &mut SimpleConfigBuilder {
+        self.format = format;
+        self
+    }
+
+    /// Set the longest password in bytes `hash_with` and `verify` accept,
+    /// `DEFAULT_MAX_PASSWORD_LEN` by default. `None` removes the limit.
+    pub fn max_password_len(&mut self, max: Option<usize>) -> &mut SimpleConfigBuilder {
+        self.max_password_len = max;
+        self
+    }
+
+    /// Record the day new hashes are created on in the string, for
+    /// `needs_rotation()`, off by default. The day is stored in days since
+    /// the Unix epoch, as the time of day would tell users apart which
+    /// changed their passwords on the same day. The strings are of rscrypt
+    /// version 2, which only `Format::Rscrypt` supports.
+    pub fn record_created_at(&mut self, record: bool) -> &mut SimpleConfigBuilder {
+        self.record_created_at = record;
+        self
+    }
+
+    /// Encode rscrypt strings with the URL-safe base64 alphabet, like
+    /// `scrypt_simple_url_safe()`, off by default. Only strings of version
+    /// 0 and 1 have the alphabet.
+    pub fn url_safe(&mut self, url_safe: bool) -> &mut SimpleConfigBuilder {
+        self.url_safe = url_safe;
+        self
+    }
+
+    /// Set the version of rscrypt strings: 2 for those of
+    /// `scrypt_simple_v2()`, 0 for version 0, or 1 if `r` or `p` are above
+    /// 255, like `scrypt_simple()`. The options below produce version 2
+    /// strings, which is the default with them and 0 without.
+    pub fn version(&mut self, version: u8) -> &mut SimpleConfigBuilder {
+        self.version = Some(version);
+        self
+    }
+
+    /// Pre-hash passwords longer than `limits.prehash_above` and reject
+    /// those longer than `limits.max_len`, like
+    /// `scrypt_simple_prehashed()`. Replaces the `max_password_len`.
+    pub fn prehash(&mut self, limits: LengthPolicy) -> &mut SimpleConfigBuilder {
+        self.v2.prehash_above = Some(limits.prehash_above);
+        self.max_password_len = Some(limits.max_len);
+        self
+    }
+
+    /// Run `c` iterations of the final PBKDF2, like
+    /// `scrypt_simple_final_rounds()`. `c = 1`, the default, runs the
+    /// single one of scrypt.
+    pub fn final_rounds(&mut self, c: NonZeroU32) -> &mut SimpleConfigBuilder {
+        self.v2.final_rounds = if c.get() > 1 { Some(c) } else { None };
+        self
+    }
+
+    /// Bind the header of the string into the salt, like
+    /// `scrypt_simple_bound()`, off by default.
+    pub fn bound(&mut self, bound: bool) -> &mut SimpleConfigBuilder {
+        self.v2.bound = bound;
+        self
+    }
+
+    /// Hash client-side digests of `scheme` instead of passwords, like
+    /// `scrypt_simple_client()`.
+    pub fn client_scheme(&mut self, scheme: ClientScheme) -> &mut SimpleConfigBuilder {
+        self.v2.client_scheme = Some(scheme.id());
+        self
+    }
+
+    /// Mix the pepper `id` into passwords, like `scrypt_simple_provided()`.
+    /// The key is passed to `SimpleConfig::hash_with_keys`.
+    pub fn pepper_id(&mut self, id: u8) -> &mut SimpleConfigBuilder {
+        self.v2.pepper = Some(id);
+        self
+    }
+
+    /// Bind new strings to associated data, like `scrypt_simple_ad()`, off
+    /// by default. The data is passed to `SimpleConfig::hash_with_ad`.
+    pub fn associated_data(&mut self, associated: bool) -> &mut SimpleConfigBuilder {
+        self.v2.associated = associated;
+        self
+    }
+
+    /// Check the configuration.
+    ///
+    /// # Return
+    /// `Err(HashError::InvalidSaltLen)` if the salt length is outside of
+    /// `MIN_SALT_LEN..=MAX_SALT_LEN` or not supported by the format,
+    /// `Err(HashError::InvalidDkLen)` if the hash length is outside of
+    /// `MIN_DK_LEN..=MAX_DK_LEN` or not supported by the format and
+    /// `Err(HashError::UnsupportedParams)` if the format can not represent
+    /// the parameters, e.g. `r` above 255 for Lambdaworks, or the rscrypt
+    /// options. Version 2 strings hold at most one of pre-hashing, final
+    /// rounds, a client scheme, a pepper and the day of creation, besides
+    /// the bound header. Associated data is only combined with the bound
+    /// header.
+    pub fn build(&self) -> Result<SimpleConfig, HashError> {
+        let (default_salt_len, default_dk_len) = default_lens(self.format);
+        let salt_len = self.salt_len.unwrap_or(default_salt_len);
+        let dk_len = self.dk_len.unwrap_or(default_dk_len);
+        if !(MIN_SALT_LEN..=MAX_SALT_LEN).contains(&salt_len) {
+            Err(HashError::InvalidSaltLen(salt_len))?;
+        }
+        if !(MIN_DK_LEN..=MAX_DK_LEN).contains(&dk_len) {
+            Err(HashError::InvalidDkLen(dk_len))?;
+        }
+
+        // find the first setting the format can not hold, starting from its
+        // own lengths
+        let supports = |salt_len, dk_len| self.format.supports(&probe(
+            self.format, self.params, salt_len, dk_len));
+        if !supports(default_salt_len, default_dk_len) {
+            Err(HashError::UnsupportedParams)?;
+        }
+        if !supports(salt_len, default_dk_len) { Err(HashError::InvalidSaltLen(salt_len))?; }
+        if !supports(salt_len, dk_len) { Err(HashError::InvalidDkLen(dk_len))?; }
+
+        let v2 = &self.v2;
+        let extensions = [v2.prehash_above.is_some(), v2.final_rounds.is_some(),
+            v2.client_scheme.is_some(), v2.pepper.is_some(), self.record_created_at];
+        let extensions = extensions.iter().filter(|&&set| set).count();
+        let version = match (self.version, extensions > 0 || v2.bound || v2.associated) {
+            (None, false) | (Some(0), false) => 0,
+            (None, true) | (Some(2), _) => 2,
+            _ => Err(HashError::UnsupportedParams)?,
+        };
+        let rscrypt = self.format == Format::Rscrypt;
+        if (!rscrypt && (self.version.is_some() || version == 2 || self.url_safe))
+            || (self.url_safe && version == 2)
+            || extensions > 1
+            || (v2.associated && extensions > 0)
+        {
+            Err(HashError::UnsupportedParams)?;
+        }
+        if version == 2 {
+            let probe = probe(self.format, self.params, salt_len, dk_len);
+            if probe.salt.len() > 255 { Err(HashError::InvalidSaltLen(salt_len))?; }
+            if !rscrypt_v2_supports(&probe.salt, &probe.hash) {
+                Err(HashError::InvalidDkLen(dk_len))?;
+            }
+        }
+
+        Ok(SimpleConfig {
+            params: self.params,
+            salt_len,
+            dk_len,
+            format: self.format,
+            max_password_len: self.max_password_len,
+            record_created_at: self.record_created_at,
+            url_safe: self.url_safe,
+            version,
+            v2: self.v2,
+        })
+    }
+}
+
+/// The parameters recommended by `Params::new`.
+pub(crate) fn recommended_params() -> Params {
+    Params::new(15, 8, 1).expect("the recommended parameters are valid")
+}
+
+/// The salt and hash lengths of the strings other implementations produce.
+fn default_lens(format: Format) -> (usize, usize) {
+    match format {
+        Format::Django => (django::SALT_LEN, django::HASH_LEN),
+        Format::Werkzeug => (werkzeug::SALT_LEN, werkzeug::HASH_LEN),
+        Format::Cisco => (cisco::SALT_LEN, 32),
+        Format::ScryptKdf => (scryptkdf::SALT_LEN, scryptkdf::SALT_LEN),
+        Format::PhpScrypt => (phpscrypt::SALT_LEN, phpscrypt::HASH_LEN),
+        Format::SimpleScrypt => (simplescrypt::SALT_LEN, 32),
+        Format::RubyScrypt => (rubyscrypt::SALT_LEN, rubyscrypt::SALT_LEN),
+        _ => (16, 32),
+    }
+}
+
+/// The characters of salts stored as plain text, `None` for random bytes.
+fn salt_chars(format: Format) -> Option<&'static [u8]> {
+    match format {
+        Format::Crypt7 => Some(crypt7::ITOA64),
+        Format::Django | Format::Werkzeug => Some(ALNUM),
+        Format::Cisco => Some(cisco::ALPHABET),
+        Format::PhpScrypt => Some(phpscrypt::SALT_CHARS),
+        _ => None,
+    }
+}
+
+/// Parts shaped like the output of `hash_with`, to check which settings the
+/// format supports.
+fn probe(format: Format, params: Params, salt_len: usize, dk_len: usize)
+    -> HashParts
+{
+    // salts of random bytes may not start with zero for the Ruby gem
+    let salt_byte = salt_chars(format).map_or(1, |chars| chars[0]);
+    HashParts { params, salt: vec![salt_byte; salt_len], hash: vec![0u8; dk_len] }
+}
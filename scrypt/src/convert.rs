@@ -0,0 +1,47 @@
+//! Re-encoding of hash strings between formats, without the password.
+//!
+//! Salt, parameters and hash are carried over unchanged, so a converted
+//! string verifies exactly like the original one.
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use errors::CheckError;
+use format::{Format, HashFormat, HashParts};
+
+/// Length of the hash of the strings produced by `scrypt_simple`.
+const RSCRYPT_HASH_LEN: usize = 32;
+
+/// Convert an rscrypt string of `scrypt_simple` into a PHC string.
+///
+/// Parameters are always written explicitly. The conversion is lossless for
+/// all versions, so rscrypt strings can be handled by tooling built on PHC
+/// strings, e.g. during a migration.
+///
+/// # Return
+/// `Err(CheckError::InvalidFormat)` if `hashed_value` is not a valid rscrypt
+/// string and `Err(CheckError::Unrepresentable)` if its hash is empty.
+/// Peppered strings return `Err(CheckError::NeedsPepper)` and strings bound
+/// to associated data `Err(CheckError::NeedsAssociatedData)`, the PHC string
+/// could not record either.
+pub fn rscrypt_to_phc(hashed_value: &str) -> Result<String, CheckError> {
+    encode(Format::Phc, Format::Rscrypt.parse(hashed_value)?)
+}
+
+/// Convert a PHC string into an rscrypt string of `scrypt_simple`.
+///
+/// Omitted PHC parameters are written with their defaults.
+///
+/// # Return
+/// `Err(CheckError::InvalidFormat)` if `hashed_value` is not a valid PHC
+/// string and `Err(CheckError::Unrepresentable)` if its hash is not 256 bits
+/// long, which rscrypt strings always are.
+pub fn phc_to_rscrypt(hashed_value: &str) -> Result<String, CheckError> {
+    let parts = Format::Phc.parse(hashed_value)?;
+    if parts.hash.len() != RSCRYPT_HASH_LEN { Err(CheckError::Unrepresentable)?; }
+    encode(Format::Rscrypt, parts)
+}
+
+fn encode(format: Format, parts: HashParts) -> Result<String, CheckError> {
+    if !format.supports(&parts) { Err(CheckError::Unrepresentable)?; }
+    Ok(format.encode(&parts))
+}
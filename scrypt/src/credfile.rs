@@ -0,0 +1,167 @@
+//! Flat credentials files of `user:hash` lines, like htpasswd files with
+//! scrypt hashes.
+//!
+//! ```text
+//! # comment
+//! alice:$rscrypt$0$DwgB$...$...$
+//! ```
+//!
+//! Blank lines and lines starting with `#` are kept as they are. Lines
+//! which are not a user and a hash of a format known to `scrypt_check` are
+//! reported by `CredFile::errors()` and kept as well, so `write()`
+//! reproduces every line it did not change. Lines are written with `\n`.
+use std::io::{self, BufRead, Write};
+
+use config::recommended_params;
+use errors::{CheckError, HashError, LineError, LineErrorKind};
+use params::Params;
+use {cost_of, scrypt_simple, verify_or_dummy};
+
+/// A credentials file, read by `load()`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CredFile {
+    lines: Vec<Line>,
+    errors: Vec<LineError>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Line {
+    Entry { user: String, hash: String },
+    Other(String),
+}
+
+/// Read a credentials file from `reader`.
+///
+/// # Return
+/// `Err` only if reading fails or the file is not UTF-8, malformed lines
+/// are reported by `CredFile::errors()`.
+pub fn load<R: BufRead>(reader: R) -> io::Result<CredFile> {
+    let mut file = CredFile::default();
+    for (i, line) in reader.lines().enumerate() {
+        let mut line = line?;
+        if line.ends_with('\r') { line.pop(); }
+        match parse_line(&line) {
+            Ok(None) => file.lines.push(Line::Other(line)),
+            Ok(Some((user, hash))) => {
+                if file.hash_of(user).is_some() {
+                    file.errors.push(LineError::new(i + 1, LineErrorKind::DuplicateUser));
+                    file.lines.push(Line::Other(line));
+                } else {
+                    let (user, hash) = (user.to_string(), hash.to_string());
+                    file.lines.push(Line::Entry { user, hash });
+                }
+            }
+            Err(kind) => {
+                file.errors.push(LineError::new(i + 1, kind));
+                file.lines.push(Line::Other(line));
+            }
+        }
+    }
+    Ok(file)
+}
+
+/// `None` for comments and blank lines.
+fn parse_line(line: &str) -> Result<Option<(&str, &str)>, LineErrorKind> {
+    if line.trim().is_empty() || line.starts_with('#') { return Ok(None); }
+    let sep = line.find(':').ok_or(LineErrorKind::MissingSeparator)?;
+    let (user, hash) = (&line[..sep], &line[sep + 1..]);
+    if user.is_empty() { Err(LineErrorKind::EmptyUser)?; }
+    cost_of(hash).map_err(LineErrorKind::InvalidHash)?;
+    Ok(Some((user, hash)))
+}
+
+impl CredFile {
+    /// Check `password` against the hash of `user`.
+    ///
+    /// For a user without an entry the work of a verification with the
+    /// parameters of the first entry is done anyway, see `verify_or_dummy`.
+    ///
+    /// # Return
+    /// `Err(CheckError::HashMismatch)` for a wrong password or an unknown
+    /// user, the result of `scrypt_check` otherwise.
+    pub fn verify(&self, user: &str, password: &str) -> Result<(), CheckError> {
+        let default_params = self.lines.iter()
+            .filter_map(|line| match *line {
+                Line::Entry { ref hash, .. } => cost_of(hash).ok(),
+                Line::Other(_) => None,
+            })
+            .map(|cost| cost.params)
+            .next()
+            .unwrap_or_else(recommended_params);
+        verify_or_dummy(password, self.hash_of(user), &default_params)
+    }
+
+    /// Hash `password` with `params` like `scrypt_simple()` and store it
+    /// for `user`, replacing an existing entry in place.
+    ///
+    /// # Return
+    /// `Err(HashError::InvalidUser)` if `user` is empty or contains `:` or a
+    /// line break, the file is unchanged.
+    pub fn set(&mut self, user: &str, password: &str, params: &Params)
+        -> Result<(), HashError>
+    {
+        if user.is_empty() || user.contains(&[':', '\n', '\r'][..]) {
+            Err(HashError::InvalidUser)?;
+        }
+        let new_hash = scrypt_simple(password, params)?;
+        for line in &mut self.lines {
+            if let Line::Entry { user: ref u, ref mut hash } = *line {
+                if u == user {
+                    *hash = new_hash;
+                    return Ok(());
+                }
+            }
+        }
+        self.lines.push(Line::Entry { user: user.to_string(), hash: new_hash });
+        Ok(())
+    }
+
+    /// Remove the entry of `user`.
+    ///
+    /// # Return
+    /// `false` if there was none.
+    pub fn remove(&mut self, user: &str) -> bool {
+        let len = self.lines.len();
+        self.lines.retain(|line| match *line {
+            Line::Entry { user: ref u, .. } => u != user,
+            Line::Other(_) => true,
+        });
+        self.lines.len() != len
+    }
+
+    /// Write all lines to `writer`.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for line in &self.lines {
+            match *line {
+                Line::Entry { ref user, ref hash } => writeln!(writer, "{}:{}", user, hash)?,
+                Line::Other(ref line) => writeln!(writer, "{}", line)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// The users with an entry, in the order of the file.
+    pub fn users(&self) -> Vec<&str> {
+        self.lines.iter()
+            .filter_map(|line| match *line {
+                Line::Entry { ref user, .. } => Some(&user[..]),
+                Line::Other(_) => None,
+            })
+            .collect()
+    }
+
+    /// The hash stored for `user`.
+    pub fn hash_of(&self, user: &str) -> Option<&str> {
+        self.lines.iter()
+            .filter_map(|line| match *line {
+                Line::Entry { user: ref u, ref hash } if u == user => Some(&hash[..]),
+                _ => None,
+            })
+            .next()
+    }
+
+    /// The lines `load()` could not use, with their line numbers.
+    pub fn errors(&self) -> &[LineError] {
+        &self.errors
+    }
+}
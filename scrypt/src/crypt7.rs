@@ -0,0 +1,188 @@
+//! The `$7$` crypt(3) format used by libxcrypt, libsodium and the BSDs.
+//!
+//! `$7$<N><r><p><salt>$<hash>`
+//!
+//! `N` is a single character holding `log_n`, `r` and `p` are 30-bit values
+//! encoded little-endian in five characters each. The salt characters are
+//! passed to scrypt verbatim and the 256-bit hash is encoded with the same
+//! little-endian base64 variant. All fields use the `./0-9A-Za-z` alphabet.
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use errors::CheckError;
+#[cfg(feature="simple")]
+use errors::HashError;
+#[cfg(feature="simple")]
+use os_rng;
+use params::Params;
+#[cfg(feature="simple")]
+use rand_core::{CryptoRng, RngCore};
+use scrypt;
+
+/// Prefix identifying `$7$` strings.
+pub(crate) const PREFIX: &str = "$7$";
+
+pub(crate) const ITOA64: &[u8; 64] =
+    b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Generate a `$7$` setting string for `params` with a random 128-bit salt
+/// drawn from `OsRng`.
+///
+/// The result can be passed to `crypt()`.
+#[cfg(feature="simple")]
+pub fn gensalt(params: &Params) -> Result<String, HashError> {
+    let mut rng = os_rng();
+    gensalt_with_rng(params, &mut rng)
+}
+
+/// Same as `gensalt`, but draws the salt from the provided `rng`.
+#[cfg(feature="simple")]
+pub fn gensalt_with_rng<R: RngCore + CryptoRng>(
+    params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    let mut salt = [0u8; 16];
+    rng.try_fill_bytes(&mut salt)?;
+    Ok(setting(params, &salt))
+}
+
+/// Build a `$7$` setting string from `params` and the raw `salt` bytes.
+pub(crate) fn setting(params: &Params, salt: &[u8]) -> String {
+    let len = PREFIX.len() + 11 + (salt.len() * 4).div_ceil(3);
+    let mut setting = String::with_capacity(len);
+    setting.push_str(PREFIX);
+    setting.push(ITOA64[params.log_n as usize] as char);
+    encode_u32(&mut setting, params.r, 30);
+    encode_u32(&mut setting, params.p, 30);
+    encode(&mut setting, salt);
+    setting
+}
+
+/// Hash `password` according to the `$7$` `setting`, like `crypt(3)` does.
+///
+/// Anything after the salt of `setting` (e.g. the hash of a complete `$7$`
+/// string) is ignored, so the result can be compared with a stored string.
+///
+/// # Return
+/// The complete `$7$` string, or `Err(CheckError::InvalidFormat)` if
+/// `setting` is malformed.
+pub fn crypt(password: &[u8], setting: &str) -> Result<String, CheckError> {
+    let (params, salt) = decode_setting(setting)?;
+
+    let mut hash = [0u8; 32];
+    scrypt(password, salt.as_bytes(), &params, &mut hash)
+        .expect("32 bytes always satisfy output length requirements");
+
+    Ok(encode_string(&params, salt, &hash))
+}
+
+/// Check if `params` can be represented in the 30-bit `r` and `p` fields.
+pub(crate) fn supports(params: &Params) -> bool {
+    params.r < 1 << 30 && params.p < 1 << 30
+}
+
+/// Check if `salt` only contains characters of the `$7$` alphabet.
+pub(crate) fn is_salt(salt: &[u8]) -> bool {
+    salt.iter().all(|&b| decode_char(b).is_ok())
+}
+
+/// Encode a complete `$7$` string, `params` must satisfy `supports()` and
+/// `salt` `is_salt()`.
+pub(crate) fn encode_string(params: &Params, salt: &str, hash: &[u8]) -> String {
+    let mut result = String::with_capacity(PREFIX.len() + 11 + salt.len() + 44);
+    result.push_str(PREFIX);
+    result.push(ITOA64[params.log_n as usize] as char);
+    encode_u32(&mut result, params.r, 30);
+    encode_u32(&mut result, params.p, 30);
+    result.push_str(salt);
+    result.push('$');
+    encode(&mut result, hash);
+    result
+}
+
+/// Decode a complete `$7$` string into parameters, salt and hash.
+pub(crate) fn decode(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    let (params, salt) = decode_setting(hashed_value)?;
+    let rest = &hashed_value[PREFIX.len() + 11 + salt.len()..];
+    if !rest.starts_with('$') { Err(CheckError::InvalidFormat)?; }
+
+    let hash = decode_bytes(&rest[1..])?;
+    if hash.len() != 32 { Err(CheckError::InvalidFormat)?; }
+    Ok((params, salt.as_bytes().to_vec(), hash))
+}
+
+/// Decode the parameters and salt of a `$7$` setting.
+fn decode_setting(setting: &str) -> Result<(Params, &str), CheckError> {
+    if !setting.starts_with(PREFIX) { Err(CheckError::InvalidFormat)?; }
+    let setting = &setting[PREFIX.len()..];
+    if setting.len() < 11 || !setting.is_char_boundary(11) {
+        Err(CheckError::InvalidFormat)?;
+    }
+
+    let params = decode_params(&setting.as_bytes()[..11])?;
+    let salt = setting[11..].split('$').next().unwrap_or("");
+    if !is_salt(salt.as_bytes()) {
+        Err(CheckError::InvalidFormat)?;
+    }
+    Ok((params, salt))
+}
+
+/// Decode the 11 parameter characters following the `$7$` prefix.
+pub(crate) fn decode_params(src: &[u8]) -> Result<Params, CheckError> {
+    if src.len() != 11 { Err(CheckError::InvalidFormat)?; }
+    let log_n = decode_char(src[0])?;
+    let r = decode_u32(&src[1..6])?;
+    let p = decode_u32(&src[6..11])?;
+    if log_n == 0 { Err(CheckError::InvalidFormat)?; }
+    Params::new(log_n as u8, r, p).map_err(|_| CheckError::InvalidFormat)
+}
+
+pub(crate) fn decode_char(c: u8) -> Result<u32, CheckError> {
+    ITOA64.iter().position(|&a| a == c)
+        .map(|i| i as u32)
+        .ok_or(CheckError::InvalidFormat)
+}
+
+fn encode_u32(dst: &mut String, mut value: u32, bits: u32) {
+    for _ in 0..bits.div_ceil(6) {
+        dst.push(ITOA64[(value & 0x3f) as usize] as char);
+        value >>= 6;
+    }
+}
+
+fn decode_u32(src: &[u8]) -> Result<u32, CheckError> {
+    let mut value = 0;
+    for (i, &c) in src.iter().enumerate() {
+        value |= decode_char(c)? << (6 * i);
+    }
+    Ok(value)
+}
+
+/// Encode bytes in groups of three, least significant bits first.
+pub(crate) fn encode(dst: &mut String, src: &[u8]) {
+    for chunk in src.chunks(3) {
+        let mut value = 0;
+        for (i, &b) in chunk.iter().enumerate() {
+            value |= (b as u32) << (8 * i);
+        }
+        encode_u32(dst, value, 8 * chunk.len() as u32);
+    }
+}
+
+/// Decode bytes encoded by `encode`, rejecting non-canonical encodings.
+pub(crate) fn decode_bytes(src: &str) -> Result<Vec<u8>, CheckError> {
+    let mut dst = Vec::with_capacity(src.len() * 3 / 4);
+    for chunk in src.as_bytes().chunks(4) {
+        // a single character can not hold a whole byte
+        if chunk.len() == 1 { Err(CheckError::InvalidFormat)?; }
+        let value = decode_u32(chunk)?;
+        let bytes = chunk.len() * 6 / 8;
+        // unused high bits must be zero
+        if value >> (8 * bytes) != 0 { Err(CheckError::InvalidFormat)?; }
+        for i in 0..bytes {
+            dst.push((value >> (8 * i)) as u8);
+        }
+    }
+    Ok(dst)
+}
@@ -0,0 +1,101 @@
+//! Base64 decoding in constant time with respect to the decoded bytes, for
+//! the salt and hash fields of hash strings. The table lookups and early
+//! returns of the `base64` crate depend on the characters, this decoder
+//! computes every character with the same arithmetic, like libsodium's
+//! `sodium_base642bin`. Only the length of a field and the position of
+//! its padding, which are public, influence the control flow.
+//!
+//! The decoder accepts what the `base64` crate accepts, canonical encoding
+//! is checked separately, e.g. by `check_canonical`.
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use errors::CheckError;
+
+/// The characters of the values 62 and 63 of the standard alphabet.
+pub(crate) const STANDARD: [u8; 2] = *b"+/";
+/// The characters of the values 62 and 63 of the URL-safe alphabet.
+pub(crate) const URL_SAFE: [u8; 2] = *b"-_";
+/// The characters of the values 62 and 63 of the adapted alphabet of
+/// passlib, the standard one with `.` for `+`.
+pub(crate) const AB64: [u8; 2] = *b"./";
+
+/// Decode `field` of the standard or the URL-safe alphabet, padded or not.
+pub(crate) fn decode(field: &str, url_safe: bool) -> Result<Vec<u8>, CheckError> {
+    decode_in(field, if url_safe { URL_SAFE } else { STANDARD })
+}
+
+/// Decode `field` of the alphabet with the characters `alphabet` for 62
+/// and 63.
+pub(crate) fn decode_in(field: &str, alphabet: [u8; 2]) -> Result<Vec<u8>, CheckError> {
+    let mut output = vec![0u8; field.len().div_ceil(4) * 3];
+    let len = decode_slice_in(field, alphabet, &mut output)?;
+    output.truncate(len);
+    Ok(output)
+}
+
+/// Decode `field` into `output`, returning the decoded length.
+///
+/// # Return
+/// `Err(CheckError::InvalidFormat)` for characters outside of the alphabet,
+/// misplaced padding, an impossible length or an `output` shorter than the
+/// decoded bytes.
+pub(crate) fn decode_slice(field: &str, url_safe: bool, output: &mut [u8])
+    -> Result<usize, CheckError>
+{
+    decode_slice_in(field, if url_safe { URL_SAFE } else { STANDARD }, output)
+}
+
+/// `decode_slice` of the alphabet with the characters `alphabet` for 62
+/// and 63.
+pub(crate) fn decode_slice_in(field: &str, alphabet: [u8; 2], output: &mut [u8])
+    -> Result<usize, CheckError>
+{
+    let data = field.trim_end_matches('=').as_bytes();
+    let pad = field.len() - data.len();
+    let rem = data.len() % 4;
+    if rem == 1 || pad > 2 || (pad != 0 && rem + pad != 4) {
+        Err(CheckError::InvalidFormat)?;
+    }
+    let len = data.len() / 4 * 3 + rem.saturating_sub(1);
+    if output.len() < len { Err(CheckError::InvalidFormat)?; }
+
+    // the OR of all sextets, negative if any character was invalid
+    let mut invalid = 0i16;
+    let mut out = 0;
+    for group in data.chunks(4) {
+        let mut acc = 0u32;
+        for &c in group {
+            let v = decode_sextet(c, alphabet);
+            invalid |= v;
+            acc = (acc << 6) | (v as u32 & 0x3f);
+        }
+        // left-align partial groups, their unused bits are dropped
+        acc <<= 6 * (4 - group.len());
+        let bytes = [(acc >> 16) as u8, (acc >> 8) as u8, acc as u8];
+        let n = group.len() - 1;
+        output[out..out + n].copy_from_slice(&bytes[..n]);
+        out += n;
+    }
+    if invalid < 0 { Err(CheckError::InvalidFormat)?; }
+    Ok(len)
+}
+
+/// `-1` if `lo <= c <= hi`, `0` otherwise, without branches.
+fn in_range(c: i16, lo: i16, hi: i16) -> i16 {
+    ((lo - 1 - c) & (c - hi - 1)) >> 8
+}
+
+/// The value of the base64 character `c`, `-1` if it is not part of the
+/// alphabet.
+fn decode_sextet(c: u8, alphabet: [u8; 2]) -> i16 {
+    let c = c as i16;
+    let [c62, c63] = alphabet;
+    let mut v = -1;
+    v += in_range(c, b'A' as i16, b'Z' as i16) & (c - b'A' as i16 + 1);
+    v += in_range(c, b'a' as i16, b'z' as i16) & (c - b'a' as i16 + 27);
+    v += in_range(c, b'0' as i16, b'9' as i16) & (c - b'0' as i16 + 53);
+    v += in_range(c, c62 as i16, c62 as i16) & 63;
+    v += in_range(c, c63 as i16, c63 as i16) & 64;
+    v
+}
@@ -0,0 +1,64 @@
+//! The format of Django's `ScryptPasswordHasher`:
+//!
+//! `scrypt$<N>$<salt>$<r>$<p>$<base64(hash)>`
+//!
+//! Unlike the other formats `N` itself is stored instead of `log_n` and the
+//! salt is stored as plain text, it is passed to scrypt verbatim. Django
+//! generates 22 alphanumeric characters, but accepts any salt without `$`.
+//! The hash is always 512 bits, encoded with the standard base64 alphabet
+//! with padding.
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use base64ct::{Base64, Encoding};
+
+use errors::CheckError;
+use params::Params;
+use phc::decode_decimal;
+
+/// Prefix identifying Django strings.
+pub(crate) const PREFIX: &str = "scrypt$";
+
+/// Length of the hash produced by Django.
+pub(crate) const HASH_LEN: usize = 64;
+
+/// Length of the salt generated by Django.
+#[cfg(feature="simple")]
+pub(crate) const SALT_LEN: usize = 22;
+
+/// Encode a Django string.
+pub(crate) fn encode(params: &Params, salt: &str, hash: &[u8]) -> String {
+    format!(
+        "scrypt${}${}${}${}${}",
+        1u64 << params.log_n, salt, params.r, params.p, Base64::encode_string(hash),
+    )
+}
+
+/// Decode a Django string into parameters, salt and hash.
+pub(crate) fn decode(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    let fields: Vec<&str> = hashed_value.split('$').collect();
+    if fields.len() != 6 || fields[0] != "scrypt" {
+        Err(CheckError::InvalidFormat)?;
+    }
+
+    let n: u64 = decode_decimal(fields[1])?;
+    if !n.is_power_of_two() || n < 2 { Err(CheckError::InvalidFormat)?; }
+    let salt = fields[2];
+    if salt.is_empty() { Err(CheckError::InvalidFormat)?; }
+    let params = Params::new(
+        n.trailing_zeros() as u8,
+        decode_decimal(fields[3])?,
+        decode_decimal(fields[4])?,
+    ).map_err(|_| CheckError::InvalidFormat)?;
+
+    // Django compares the re-encoded string, so only the canonical encoding
+    // of a 512-bit hash can ever match
+    let hash = Base64::decode_vec(fields[5]).map_err(|_| CheckError::InvalidFormat)?;
+    if hash.len() != HASH_LEN {
+        Err(CheckError::InvalidFormat)?;
+    }
+
+    Ok((params, salt.as_bytes().to_vec(), hash))
+}
@@ -0,0 +1,391 @@
+//! The encrypted file format of Colin Percival's `scrypt` utility
+//! (`scrypt enc` and `scrypt dec`), version 0.
+//!
+//! | Offset | Length | Content                                             |
+//! |--------|--------|-----------------------------------------------------|
+//! | 0      | 6      | `scrypt`                                            |
+//! | 6      | 1      | version, `0`                                        |
+//! | 7      | 1      | `log_n`                                             |
+//! | 8      | 4      | `r`, big-endian                                     |
+//! | 12     | 4      | `p`, big-endian                                     |
+//! | 16     | 32     | salt                                                |
+//! | 48     | 16     | the first 16 bytes of SHA-256 of the bytes above    |
+//! | 64     | 32     | HMAC-SHA256 of the bytes above                      |
+//! | 96     | *      | the plaintext, encrypted with AES-256-CTR           |
+//! | *      | 32     | HMAC-SHA256 of all bytes above                      |
+//!
+//! The 64 byte scrypt output `dk` of the password and salt provides the
+//! AES key, `dk[0..32]`, and the HMAC key, `dk[32..64]`. The counter of
+//! AES-256-CTR starts at zero.
+//!
+//! The HMAC of the header tells a wrong password from a damaged file before
+//! anything is decrypted. The parameters are taken from the file, use
+//! `params()` to check them before decrypting files of untrusted origin.
+//!
+//! `encrypt()` and `decrypt()` work on buffers, `Encryptor` and `Decryptor`
+//! on streams of any length, e.g. backups which do not fit into memory.
+use std::io::{self, Read, Write};
+
+use byteorder::{BigEndian, ByteOrder};
+use hmac::{Hmac, Mac};
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+use aes::Aes256;
+use constant_time_eq;
+use errors::{EncError, HashError};
+use os_rng;
+use params::Params;
+use scrypt;
+
+const MAGIC: &[u8] = b"scrypt";
+/// Length of the header, up to the encrypted data.
+const HEADER_LEN: usize = 96;
+/// Length of the HMAC after the encrypted data.
+const TRAILER_LEN: usize = 32;
+
+/// Encrypt `plaintext` with a key derived from `password` with `params`
+/// and a random salt, in the format of `scrypt enc`.
+///
+/// # Return
+/// `Err(HashError::Rng)` in the case of an unlikely `OsRng` failure.
+pub fn encrypt(password: &[u8], plaintext: &[u8], params: &Params)
+    -> Result<Vec<u8>, HashError>
+{
+    let mut rng = os_rng();
+    encrypt_with_rng(password, plaintext, params, &mut rng)
+}
+
+/// Same as `encrypt`, but draws the salt from the provided `rng` instead of
+/// `OsRng`.
+pub fn encrypt_with_rng<R: RngCore + CryptoRng>(
+    password: &[u8], plaintext: &[u8], params: &Params, rng: &mut R,
+) -> Result<Vec<u8>, HashError> {
+    let mut output = vec![0u8; HEADER_LEN + plaintext.len() + TRAILER_LEN];
+    let (aes_key, hmac_key) = write_header(password, params, rng, &mut output[..HEADER_LEN])?;
+
+    let end = HEADER_LEN + plaintext.len();
+    output[HEADER_LEN..end].copy_from_slice(plaintext);
+    apply_ctr(&aes_key, &mut output[HEADER_LEN..end]);
+    let mac = hmac(&hmac_key, &output[..end]);
+    output[end..].copy_from_slice(&mac);
+    Ok(output)
+}
+
+/// Decrypt the output of `encrypt` or `scrypt enc`.
+///
+/// # Return
+/// `Err(EncError::WrongPassword)` if the password does not match and the
+/// other `EncError`s if `ciphertext` is not a valid file.
+pub fn decrypt(password: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, EncError> {
+    let params = params(ciphertext)?;
+    let (aes_key, hmac_key) = derive_keys(password, &ciphertext[16..48], &params);
+    if !constant_time_eq(&hmac(&hmac_key, &ciphertext[..64]), &ciphertext[64..96]) {
+        Err(EncError::WrongPassword)?;
+    }
+
+    let end = ciphertext.len() - TRAILER_LEN;
+    if !constant_time_eq(&hmac(&hmac_key, &ciphertext[..end]), &ciphertext[end..]) {
+        Err(EncError::Corrupt)?;
+    }
+    let mut plaintext = ciphertext[HEADER_LEN..end].to_vec();
+    apply_ctr(&aes_key, &mut plaintext);
+    Ok(plaintext)
+}
+
+/// Read the parameters from the header of `ciphertext`, without deriving
+/// the key, e.g. to refuse expensive files before `decrypt()`.
+///
+/// # Return
+/// `Err(EncError::NotEncrypted)` if `ciphertext` does not start with the
+/// `scrypt` magic, `Err(EncError::UnsupportedVersion)` for versions other
+/// than 0, `Err(EncError::Corrupt)` if it is too short,
+/// `Err(EncError::InvalidChecksum)` if the header is damaged and
+/// `Err(EncError::InvalidParams)` if the parameters are invalid.
+pub fn params(ciphertext: &[u8]) -> Result<Params, EncError> {
+    if ciphertext.len() < 7 || &ciphertext[..6] != MAGIC { Err(EncError::NotEncrypted)?; }
+    if ciphertext[6] != 0 { Err(EncError::UnsupportedVersion)?; }
+    if ciphertext.len() < HEADER_LEN + TRAILER_LEN { Err(EncError::Corrupt)?; }
+    header_params(ciphertext)
+}
+
+/// `params()` of a complete header, which is not followed by a trailer yet.
+fn header_params(ciphertext: &[u8]) -> Result<Params, EncError> {
+    if &ciphertext[..6] != MAGIC { Err(EncError::NotEncrypted)?; }
+    if ciphertext[6] != 0 { Err(EncError::UnsupportedVersion)?; }
+    let checksum = Sha256::digest(&ciphertext[..48]);
+    if checksum[..16] != ciphertext[48..64] { Err(EncError::InvalidChecksum)?; }
+
+    let r = BigEndian::read_u32(&ciphertext[8..12]);
+    let p = BigEndian::read_u32(&ciphertext[12..16]);
+    Params::new(ciphertext[7], r, p).map_err(|_| EncError::InvalidParams)
+}
+
+/// Write the header of a new file into the `HEADER_LEN` bytes of `header`,
+/// with a salt drawn from `rng`. Returns the AES and the HMAC key.
+fn write_header<R: RngCore>(
+    password: &[u8], params: &Params, rng: &mut R, header: &mut [u8],
+) -> Result<([u8; 32], [u8; 32]), HashError> {
+    rng.try_fill_bytes(&mut header[16..48])?;
+    header[..6].copy_from_slice(MAGIC);
+    header[7] = params.log_n();
+    BigEndian::write_u32(&mut header[8..12], params.r());
+    BigEndian::write_u32(&mut header[12..16], params.p());
+    let checksum = Sha256::digest(&header[..48]);
+    header[48..64].copy_from_slice(&checksum[..16]);
+
+    let (aes_key, hmac_key) = derive_keys(password, &header[16..48], params);
+    let mac = hmac(&hmac_key, &header[..64]);
+    header[64..96].copy_from_slice(&mac);
+    Ok((aes_key, hmac_key))
+}
+
+/// Derive the AES and the HMAC key.
+fn derive_keys(password: &[u8], salt: &[u8], params: &Params) -> ([u8; 32], [u8; 32]) {
+    let mut dk = [0u8; 64];
+    scrypt(password, salt, params, &mut dk)
+        .expect("64 bytes always satisfy output length requirements");
+    let mut aes_key = [0u8; 32];
+    let mut hmac_key = [0u8; 32];
+    aes_key.copy_from_slice(&dk[..32]);
+    hmac_key.copy_from_slice(&dk[32..]);
+    (aes_key, hmac_key)
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_varkey(key)
+        .expect("HMAC accepts keys of any length");
+    mac.input(data);
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&mac.result().code());
+    result
+}
+
+/// Encrypt or decrypt `data` in place with AES-256-CTR, with a big-endian
+/// counter starting at zero.
+fn apply_ctr(key: &[u8; 32], data: &mut [u8]) {
+    Ctr::new(key).apply(data);
+}
+
+/// The keystream of AES-256-CTR, applied in pieces of any length.
+struct Ctr {
+    aes: Aes256,
+    counter: u64,
+    block: [u8; 16],
+    // the used bytes of `block`, 16 before the first use
+    pos: usize,
+}
+
+impl Ctr {
+    fn new(key: &[u8; 32]) -> Ctr {
+        Ctr { aes: Aes256::new(key), counter: 0, block: [0u8; 16], pos: 16 }
+    }
+
+    fn apply(&mut self, data: &mut [u8]) {
+        for b in data.iter_mut() {
+            if self.pos == 16 {
+                self.block = [0u8; 16];
+                BigEndian::write_u64(&mut self.block[8..], self.counter);
+                self.aes.encrypt_block(&mut self.block);
+                self.counter += 1;
+                self.pos = 0;
+            }
+            *b ^= self.block[self.pos];
+            self.pos += 1;
+        }
+    }
+}
+
+/// Bytes encrypted or decrypted per call of the inner writer or reader.
+const CHUNK_LEN: usize = 8192;
+
+/// A writer which encrypts into `inner` in the format of `scrypt enc`.
+///
+/// The header is written by `new()`, the HMAC of the file by `finish()`.
+/// A file whose encryptor is dropped without `finish()` is truncated and
+/// can not be decrypted.
+pub struct Encryptor<W: Write> {
+    inner: W,
+    ctr: Ctr,
+    mac: Hmac<Sha256>,
+}
+
+impl<W: Write> Encryptor<W> {
+    /// Start a file encrypted with a key derived from `password` with
+    /// `params` and a random salt, and write its header to `inner`.
+    ///
+    /// # Return
+    /// The errors of `inner` and an error of kind `Other` in the case of an
+    /// unlikely `OsRng` failure.
+    pub fn new(inner: W, password: &[u8], params: &Params) -> io::Result<Encryptor<W>> {
+        let mut rng = os_rng();
+        Encryptor::with_rng(inner, password, params, &mut rng)
+    }
+
+    /// Same as `new`, but draws the salt from the provided `rng` instead of
+    /// `OsRng`.
+    pub fn with_rng<R: RngCore + CryptoRng>(
+        mut inner: W, password: &[u8], params: &Params, rng: &mut R,
+    ) -> io::Result<Encryptor<W>> {
+        let mut header = [0u8; HEADER_LEN];
+        let (aes_key, hmac_key) = write_header(password, params, rng, &mut header)?;
+        inner.write_all(&header)?;
+        let mut mac = Hmac::<Sha256>::new_varkey(&hmac_key)
+            .expect("HMAC accepts keys of any length");
+        mac.input(&header);
+        Ok(Encryptor { inner, ctr: Ctr::new(&aes_key), mac })
+    }
+
+    /// Write the HMAC which ends the file and return `inner`, flushed.
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut trailer = [0u8; TRAILER_LEN];
+        trailer.copy_from_slice(&self.mac.result().code());
+        self.inner.write_all(&trailer)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for Encryptor<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut chunk = [0u8; CHUNK_LEN];
+        let len = buf.len().min(CHUNK_LEN);
+        chunk[..len].copy_from_slice(&buf[..len]);
+        self.ctr.apply(&mut chunk[..len]);
+        self.mac.input(&chunk[..len]);
+        // the keystream has advanced, so the chunk is written completely
+        self.inner.write_all(&chunk[..len])?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A reader which decrypts a file in the format of `scrypt enc` from
+/// `inner`.
+///
+/// `new()` reads the header and checks the password before anything is
+/// decrypted, the HMAC of the whole file can only be checked at its end,
+/// by `finish()`. **Until `finish()` succeeded, the plaintext read is not
+/// authenticated**: it may have been modified or truncated, so it must not
+/// be used or made visible before, e.g. written to a temporary file which
+/// is only moved into place afterwards.
+pub struct Decryptor<R: Read> {
+    inner: R,
+    params: Params,
+    ctr: Ctr,
+    mac: Hmac<Sha256>,
+    // read ahead of the plaintext, the last `TRAILER_LEN` bytes may be the
+    // HMAC
+    buf: Vec<u8>,
+    len: usize,
+    eof: bool,
+}
+
+impl<R: Read> Decryptor<R> {
+    /// Read the header of a file from `inner` and derive its key from
+    /// `password`.
+    ///
+    /// # Return
+    /// The errors of `inner` and the `EncError`s of `decrypt()` for the
+    /// header, converted into `io::Error`, e.g. of kind `InvalidInput` for
+    /// `EncError::WrongPassword`.
+    pub fn new(inner: R, password: &[u8]) -> io::Result<Decryptor<R>> {
+        Decryptor::new_checked(inner, password, |_| true)
+    }
+
+    /// Same as `new`, but passes the parameters of the header to `accept`
+    /// before the key is derived, e.g. to refuse expensive files of
+    /// untrusted origin.
+    ///
+    /// # Return
+    /// `EncError::InvalidParams` if `accept` returns `false`.
+    pub fn new_checked<F>(mut inner: R, password: &[u8], accept: F)
+        -> io::Result<Decryptor<R>>
+        where F: FnOnce(&Params) -> bool
+    {
+        let mut header = [0u8; HEADER_LEN];
+        let len = read_full(&mut inner, &mut header)?;
+        // a short header is reported like by `decrypt()`
+        let params = if len < HEADER_LEN {
+            params(&header[..len])?
+        } else {
+            header_params(&header)?
+        };
+        if !accept(&params) { Err(EncError::InvalidParams)?; }
+
+        let (aes_key, hmac_key) = derive_keys(password, &header[16..48], &params);
+        if !constant_time_eq(&hmac(&hmac_key, &header[..64]), &header[64..96]) {
+            Err(EncError::WrongPassword)?;
+        }
+        let mut mac = Hmac::<Sha256>::new_varkey(&hmac_key)
+            .expect("HMAC accepts keys of any length");
+        mac.input(&header);
+        Ok(Decryptor {
+            inner,
+            params,
+            ctr: Ctr::new(&aes_key),
+            mac,
+            buf: vec![0u8; TRAILER_LEN + CHUNK_LEN],
+            len: 0,
+            eof: false,
+        })
+    }
+
+    /// The parameters of the file.
+    pub fn params(&self) -> Params { self.params }
+
+    /// Read the rest of the file and check its HMAC, then return `inner`.
+    ///
+    /// # Return
+    /// `EncError::Corrupt`, converted into an `io::Error` of kind
+    /// `InvalidData`, if the file is truncated or the HMAC does not match.
+    /// The plaintext read before must then be discarded.
+    pub fn finish(mut self) -> io::Result<R> {
+        let mut rest = [0u8; CHUNK_LEN];
+        while self.read(&mut rest)? > 0 {}
+        let code = self.mac.result().code();
+        if self.len != TRAILER_LEN || !constant_time_eq(&code, &self.buf[..TRAILER_LEN]) {
+            Err(EncError::Corrupt)?;
+        }
+        Ok(self.inner)
+    }
+}
+
+impl<R: Read> Read for Decryptor<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() { return Ok(0); }
+        while self.len <= TRAILER_LEN && !self.eof {
+            match self.inner.read(&mut self.buf[self.len..]) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.len += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let len = self.len.saturating_sub(TRAILER_LEN).min(out.len());
+        out[..len].copy_from_slice(&self.buf[..len]);
+        self.mac.input(&out[..len]);
+        self.ctr.apply(&mut out[..len]);
+        self.buf.copy_within(len..self.len, 0);
+        self.len -= len;
+        Ok(len)
+    }
+}
+
+/// Read into `buf` until it is full or `reader` ends, returns the bytes
+/// read.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(len)
+}
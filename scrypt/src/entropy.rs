@@ -0,0 +1,136 @@
+//! The source of the salts of the functions without an `rng` argument.
+//!
+//! `scrypt_simple()`, `SimpleConfig::hash_with()` and the other functions
+//! which do not take a random number generator draw from `OsRng`, unless a
+//! source is installed with `set_entropy_source()`, e.g. a hardware
+//! generator of a device. The `_with_rng` variants are not affected.
+//!
+//! `OsRng` is the generator of the `getrandom` crate, e.g. `getrandom(2)`
+//! on Linux and `random_get` on WASI. The targets which
+//! `getrandom` does not support fail to compile with the `simple` feature;
+//! on `wasm32-unknown-unknown` the `js` feature selects
+//! `crypto.getRandomValues`.
+//!
+//! Under Miri, which can not run the system calls of `OsRng`, the default
+//! is a `DeterministicSource`, so that the tests of the simple API run
+//! there unchanged:
+//!
+//! ```text
+//! cargo +nightly miri test --test miri
+//! ```
+//!
+//! `tests/miri.rs` runs the simple API with reduced parameters, the other
+//! tests use parameters which take Miri hours.
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use rand_core::{self, CryptoRng, ErrorKind, RngCore};
+use sha2::{Digest, Sha256};
+
+/// Random bytes for salts.
+///
+/// Sources are `Send` and `Sync`, a single one is shared by all threads.
+pub trait EntropySource: Send + Sync {
+    /// Fill `dest` with random bytes.
+    ///
+    /// # Return
+    /// `Err` if the source failed, which is reported as `HashError::Rng`.
+    fn fill(&self, dest: &mut [u8]) -> Result<(), rand_core::Error>;
+}
+
+/// **INSECURE, FOR TESTS ONLY.** An `EntropySource` producing the same
+/// bytes for the same seed: `SHA-256(seed || counter)` for a counter of 8
+/// bytes, little-endian, which counts the blocks drawn so far.
+#[derive(Debug)]
+pub struct DeterministicSource {
+    seed: [u8; 32],
+    counter: AtomicU64,
+}
+
+impl DeterministicSource {
+    /// A source starting at the first block of `seed`.
+    pub fn new(seed: [u8; 32]) -> DeterministicSource {
+        DeterministicSource { seed, counter: AtomicU64::new(0) }
+    }
+}
+
+impl EntropySource for DeterministicSource {
+    fn fill(&self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        for chunk in dest.chunks_mut(32) {
+            let counter = self.counter.fetch_add(1, Ordering::Relaxed);
+            let mut sha = Sha256::default();
+            sha.input(&self.seed);
+            sha.input(&counter.to_le_bytes());
+            chunk.copy_from_slice(&sha.result()[..chunk.len()]);
+        }
+        Ok(())
+    }
+}
+
+static SOURCE: RwLock<Option<Arc<dyn EntropySource>>> = RwLock::new(None);
+
+/// Install `source` for all functions without an `rng` argument, `None`
+/// restores `OsRng`.
+pub fn set_entropy_source(source: Option<Arc<dyn EntropySource>>) {
+    *SOURCE.write().unwrap_or_else(|e| e.into_inner()) = source;
+}
+
+/// The random number generator of the operating system.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct OsRng;
+
+impl RngCore for OsRng {
+    fn next_u32(&mut self) -> u32 { rand_core::impls::next_u32_via_fill(self) }
+    fn next_u64(&mut self) -> u64 { rand_core::impls::next_u64_via_fill(self) }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).expect("the OS random number generator failed")
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        getrandom::getrandom(dest).map_err(|e| rand_core::Error::with_cause(
+            ErrorKind::Unavailable, "the OS random number generator failed", e))
+    }
+}
+
+impl CryptoRng for OsRng {}
+
+/// The generator of the functions without an `rng` argument: the source of
+/// `set_entropy_source()` or `OsRng`.
+pub(crate) enum SystemRng {
+    Os(OsRng),
+    Source(Arc<dyn EntropySource>),
+}
+
+impl SystemRng {
+    pub(crate) fn new() -> SystemRng {
+        let source = SOURCE.read().unwrap_or_else(|e| e.into_inner()).clone();
+        match source {
+            Some(source) => SystemRng::Source(source),
+            None if cfg!(miri) => {
+                static MIRI: OnceLock<Arc<DeterministicSource>> = OnceLock::new();
+                let source = MIRI.get_or_init(|| Arc::new(DeterministicSource::new([0; 32])));
+                SystemRng::Source(source.clone())
+            }
+            None => SystemRng::Os(OsRng),
+        }
+    }
+}
+
+impl RngCore for SystemRng {
+    fn next_u32(&mut self) -> u32 { rand_core::impls::next_u32_via_fill(self) }
+    fn next_u64(&mut self) -> u64 { rand_core::impls::next_u64_via_fill(self) }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).expect("the entropy source failed")
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        match *self {
+            SystemRng::Os(ref mut rng) => rng.try_fill_bytes(dest),
+            SystemRng::Source(ref source) => source.fill(dest),
+        }
+    }
+}
+
+impl CryptoRng for SystemRng {}
@@ -0,0 +1,139 @@
+//! Parameters and policies from environment variables.
+//!
+//! The parameters are read from either `<PREFIX>_PARAMS`, in the form
+//! `ln=15,r=8,p=1` of PHC strings, or from `<PREFIX>_LOG_N`, `<PREFIX>_R`
+//! and `<PREFIX>_P`. Omitted parameters take the values `ln=15`, `r=8` and
+//! `p=1`, like in PHC strings.
+use std::env;
+
+use errors::{EnvError, EnvErrorKind, ParamsReason};
+use params::Params;
+#[cfg(feature="simple-verify")]
+use verifier::ParamsPolicy;
+
+impl Params {
+    /// Read the parameters from the environment variables of `prefix`, see
+    /// above.
+    ///
+    /// # Return
+    /// `Ok(None)` if none of the variables is set and `Err(EnvError)` with
+    /// the first variable which can not be used otherwise, in the order
+    /// `PARAMS`, `LOG_N`, `R` and `P`.
+    pub fn from_env(prefix: &str) -> Result<Option<Params>, EnvError> {
+        let combined = read(prefix, "PARAMS")?;
+        let log_n = read(prefix, "LOG_N")?;
+        let r = read(prefix, "R")?;
+        let p = read(prefix, "P")?;
+
+        if let Some((var, value)) = combined {
+            if let Some((other, value)) = log_n.or(r).or(p) {
+                Err(EnvError::new(other, value, EnvErrorKind::Conflict))?;
+            }
+            let result = parse_combined(&value).and_then(|(log_n, r, p)| {
+                Params::new(log_n, r, p).map_err(EnvErrorKind::InvalidParams)
+            });
+            return result.map(Some).map_err(|kind| EnvError::new(var, value, kind));
+        }
+
+        if log_n.is_none() && r.is_none() && p.is_none() { return Ok(None); }
+        let params = Params::new(
+            number(&log_n)?.unwrap_or(DEFAULTS.0),
+            number(&r)?.unwrap_or(DEFAULTS.1),
+            number(&p)?.unwrap_or(DEFAULTS.2),
+        );
+        params.map(Some).map_err(|e| {
+            // the variable of the broken constraint, or the first one set if
+            // it took its default
+            let blame = match e.reason() {
+                ParamsReason::RZero => &r,
+                ParamsReason::PZero | ParamsReason::RpTooLarge => &p,
+                _ => &log_n,
+            };
+            let (var, value) = blame.clone().or(log_n).or(r).or(p)
+                .expect("one of the variables is set");
+            EnvError::new(var, value, EnvErrorKind::InvalidParams(e))
+        })
+    }
+}
+
+#[cfg(feature="simple-verify")]
+impl ParamsPolicy {
+    /// Read a policy from `<PREFIX>_MIN_LOG_N`, `<PREFIX>_MAX_MEMORY` and
+    /// `<PREFIX>_MAX_OPS`. Omitted rules take the values of
+    /// `ParamsPolicy::default()`, which accept all parameters.
+    ///
+    /// # Return
+    /// `Ok(None)` if none of the variables is set and `Err(EnvError)` with
+    /// the first variable which can not be used otherwise.
+    pub fn from_env(prefix: &str) -> Result<Option<ParamsPolicy>, EnvError> {
+        let min_log_n = number(&read(prefix, "MIN_LOG_N")?)?;
+        let max_memory = number(&read(prefix, "MAX_MEMORY")?)?;
+        let max_ops = number(&read(prefix, "MAX_OPS")?)?;
+        if min_log_n.is_none() && max_memory.is_none() && max_ops.is_none() {
+            return Ok(None);
+        }
+        let default = ParamsPolicy::default();
+        Ok(Some(ParamsPolicy {
+            min_log_n: min_log_n.unwrap_or(default.min_log_n),
+            max_memory: max_memory.unwrap_or(default.max_memory),
+            max_ops: max_ops.unwrap_or(default.max_ops),
+        }))
+    }
+}
+
+const DEFAULTS: (u8, u32, u32) = (15, 8, 1);
+
+/// The name and value of `<prefix>_<name>`, `None` if it is not set.
+fn read(prefix: &str, name: &str) -> Result<Option<(String, String)>, EnvError> {
+    let var = format!("{}_{}", prefix, name);
+    match env::var_os(&var) {
+        None => Ok(None),
+        Some(value) => match value.into_string() {
+            Ok(value) => Ok(Some((var, value))),
+            Err(value) => {
+                let value = value.to_string_lossy().into_owned();
+                Err(EnvError::new(var, value, EnvErrorKind::NotUnicode))
+            }
+        },
+    }
+}
+
+/// The decimal value of a variable of `read`.
+fn number<T: ::core::str::FromStr>(var: &Option<(String, String)>)
+    -> Result<Option<T>, EnvError>
+{
+    match *var {
+        None => Ok(None),
+        Some((ref var, ref value)) => match decimal(value) {
+            Some(number) => Ok(Some(number)),
+            None => Err(EnvError::new(var.clone(), value.clone(), EnvErrorKind::InvalidNumber)),
+        },
+    }
+}
+
+/// Parse a decimal without sign, surrounding whitespace is ignored.
+fn decimal<T: ::core::str::FromStr>(value: &str) -> Option<T> {
+    let value = value.trim();
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) { return None; }
+    value.parse().ok()
+}
+
+/// Parse `ln=<log_n>,r=<r>,p=<p>`, each key at most once, in any order.
+fn parse_combined(value: &str) -> Result<(u8, u32, u32), EnvErrorKind> {
+    let (mut log_n, mut r, mut p) = (None, None, None);
+    for pair in value.split(',') {
+        let mut kv = pair.splitn(2, '=');
+        let (key, value) = match (kv.next(), kv.next()) {
+            (Some(key), Some(value)) => (key.trim(), value),
+            _ => Err(EnvErrorKind::Malformed)?,
+        };
+        let duplicate = match key {
+            "ln" => log_n.replace(decimal(value).ok_or(EnvErrorKind::InvalidNumber)?).is_some(),
+            "r" => r.replace(decimal(value).ok_or(EnvErrorKind::InvalidNumber)?).is_some(),
+            "p" => p.replace(decimal(value).ok_or(EnvErrorKind::InvalidNumber)?).is_some(),
+            _ => Err(EnvErrorKind::Malformed)?,
+        };
+        if duplicate { Err(EnvErrorKind::Malformed)?; }
+    }
+    Ok((log_n.unwrap_or(DEFAULTS.0), r.unwrap_or(DEFAULTS.1), p.unwrap_or(DEFAULTS.2)))
+}
@@ -1,59 +1,1560 @@
-use std::{fmt, error};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature="simple")]
+use rand_core;
+
+use SelfTestError;
 
 /// `scrypt()` error
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub struct InvalidOutputLen;
+pub struct InvalidOutputLen {
+    len: usize,
+}
 
-/// `ScryptParams` error
+/// `scrypt_core()` error
+#[cfg(feature="low-level")]
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub struct InvalidParams;
+pub struct InvalidBufferLen {
+    len: usize,
+    expected: usize,
+}
+
+/// `Params` error
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InvalidParams {
+    reason: ParamsReason,
+}
+
+/// The constraint which invalid parameters break, see
+/// `InvalidParams::reason()`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ParamsReason {
+    /// `log_n` is 0. `N = 1` leaves ROMix a single entry of `V`, which
+    /// makes it no longer memory-hard.
+    LogNZero,
+    /// `N` does not fit into a `usize`.
+    LogNTooLarge,
+    /// `r` is 0.
+    RZero,
+    /// `p` is 0.
+    PZero,
+    /// The `128 * r * N` bytes of `V` or the `128 * r * p` bytes of `B` do
+    /// not fit into a `usize`, or `r` or `p` does not on 16 bit platforms.
+    MemoryOverflow,
+    /// `N >= 2^(16 * r)`, which scrypt does not allow.
+    NTooLargeForR,
+    /// `r * p >= 2^30`, which scrypt does not allow.
+    RpTooLarge,
+    /// `N` is stored directly, e.g. by keystores, and is not a power of 2.
+    NotPowerOfTwo,
+    /// A constraint of another function, e.g. of a yescrypt flavor or of the
+    /// key length of a keystore.
+    Other,
+    /// The memory budget of `Params::from_costs` is too small for any
+    /// parameters.
+    BudgetTooSmall,
+    /// `Params::for_year` has no presets for the year.
+    UnknownYear,
+}
 
 /// `scrypt_check` error
-#[cfg(feature="include_simple")]
+#[cfg(feature="simple-verify")]
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum CheckError {
     /// Password hash mismatch, e.g. due to the incorrect password.
     HashMismatch,
     /// Invalid format of the hash string.
     InvalidFormat,
+    /// The hash field of the hash string has a length `scrypt` can not
+    /// produce.
+    InvalidHashLen(InvalidOutputLen),
+    /// The checksum of the hashed value does not match, it has been
+    /// corrupted or tampered with.
+    InvalidChecksum,
+    /// The format of the hash string is not known.
+    UnsupportedFormat,
+    /// The hash can not be represented in the target format of a conversion,
+    /// by `ScryptHashRef` or by `HashParts`, e.g. an rscrypt string with
+    /// final PBKDF2 iterations, which only `ScryptHash` keeps.
+    Unrepresentable,
+    /// The hash field is shorter than `MIN_DK_LEN` bytes, e.g. because it
+    /// was truncated.
+    HashTooShort(usize),
+    /// The hash string uses a version of its format which is newer than
+    /// this crate.
+    UnsupportedVersion,
+    /// Salt or hash of an rscrypt string do not have the lengths produced
+    /// by `scrypt_simple`, reported by a `Verifier` with `default_lengths`.
+    UnexpectedFieldLen {
+        /// Length of the decoded salt.
+        salt_len: usize,
+        /// Length of the decoded hash.
+        hash_len: usize,
+    },
+    /// A field of an rscrypt string is not canonical base64: it has nonzero
+    /// trailing bits or padding its version does not use.
+    NonCanonicalBase64(Field),
+    /// The password matches, but the new hash of
+    /// `scrypt_check_and_rehash` could not be computed because the random
+    /// number generator failed.
+    RehashFailed,
+    /// Verifying the hash would take more memory or work than the
+    /// `VerifyLimits` of `BatchOptions` allow.
+    CostExceedsLimit,
+    /// The hash string was created with the pepper of this id, which
+    /// `Verifier::verify_with_keys` needs to verify it. Also returned if the
+    /// `KeyProvider` has no key of the id.
+    NeedsPepper(u8),
+    /// The hash string breaks a rule of a `Verifier`.
+    Refused(Rule),
+    /// The hash string is bound to associated data, which
+    /// `Verifier::verify_with_ad` needs to verify it.
+    NeedsAssociatedData,
+    /// A field of the hash string is missing or malformed, reported by the
+    /// parsers of formats whose fields are named, e.g. `Crypt::ScryptKDF`.
+    InvalidField(Field),
+    /// The `HashBackend` of a `Verifier` failed to compute the hash.
+    Backend(BackendError),
+    /// The password is longer than the limit of the function, e.g.
+    /// `DEFAULT_MAX_PASSWORD_LEN` or the `max_password_len` of a
+    /// `Verifier`, it was not hashed.
+    PasswordTooLong {
+        /// The length of the password in bytes.
+        len: usize,
+        /// The longest password accepted.
+        max: usize,
+    },
+    /// The hash string was computed over a client-side digest of the
+    /// `ClientScheme` of this id, which a `Verifier` with that
+    /// `client_scheme` needs to verify it.
+    NeedsClientScheme(u8),
+    /// The digest passed to a `Verifier` is of another `client_scheme` than
+    /// the one recorded in the hash string, `stored` is `None` for strings
+    /// without a scheme. It was not hashed.
+    ClientSchemeMismatch {
+        /// The scheme id of the hash string.
+        stored: Option<u8>,
+        /// The scheme id passed by the caller.
+        claimed: u8,
+    },
+    /// The MAC of a record of `RecordSealer` does not verify, is of an
+    /// unknown key or is missing, the record was likely replaced. The
+    /// password was not hashed.
+    TamperSuspected,
+    /// `Params::for_year` has no presets for the year of the policy.
+    UnknownYear(u16),
+    /// The `KeyProvider` could not fetch the pepper of this id, e.g.
+    /// because its KMS is unreachable. The password was not hashed.
+    KeyUnavailable(u8),
+    /// The hash and the key of `check_and_derive` do not fit into the
+    /// output of scrypt, or the key is empty.
+    InvalidOutputLen(InvalidOutputLen),
+}
+
+/// `KeyProvider::key` error
+#[cfg(feature="simple-verify")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum KeyError {
+    /// The provider has no key of the id.
+    UnknownId,
+    /// The key can not be fetched, e.g. because the KMS is down.
+    Unavailable,
+}
+
+/// `HashBackend::derive` error
+#[cfg(feature="simple-verify")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BackendError {
+    /// The output length is not supported by `scrypt()`.
+    InvalidOutputLen(InvalidOutputLen),
+    /// The backend can not be reached, e.g. because its service is down.
+    Unavailable,
+    /// The backend refused to compute the hash, e.g. for its parameters.
+    Rejected,
+}
+
+/// A field of a hash string.
+#[cfg(feature="simple-verify")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Field {
+    /// The parameters, in one field.
+    Params,
+    /// The salt.
+    Salt,
+    /// The hash.
+    Hash,
+    /// `N`, of formats storing the parameters in separate fields.
+    N,
+    /// `r`, of formats storing the parameters in separate fields.
+    R,
+    /// `p`, of formats storing the parameters in separate fields.
+    P,
+}
+
+#[cfg(feature="simple-verify")]
+impl Field {
+    fn name(self) -> &'static str {
+        match self {
+            Field::Params => "parameter",
+            Field::Salt => "salt",
+            Field::Hash => "hash",
+            Field::N => "N",
+            Field::R => "r",
+            Field::P => "p",
+        }
+    }
+}
+
+/// A rule of a `Verifier`, reported by `CheckError::Refused`.
+#[cfg(feature="simple-verify")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Rule {
+    /// The format is not allowed.
+    Format,
+    /// The rscrypt version is not allowed.
+    Version,
+    /// The string is not the canonical encoding of its hash.
+    Encoding,
+    /// `log_n` is smaller than `ParamsPolicy::min_log_n`.
+    MinLogN,
+    /// The memory of scrypt exceeds `ParamsPolicy::max_memory`.
+    MaxMemory,
+    /// The work of scrypt exceeds `ParamsPolicy::max_ops`.
+    MaxOps,
+}
+
+/// A line of a credentials file which `credfile::load` could not use.
+#[cfg(feature="simple")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LineError {
+    line: usize,
+    kind: LineErrorKind,
+}
+
+/// The problem of a `LineError`.
+#[cfg(feature="simple")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LineErrorKind {
+    /// The line has no `:` between user and hash.
+    MissingSeparator,
+    /// The user before the `:` is empty.
+    EmptyUser,
+    /// The hash after the `:` can not be parsed.
+    InvalidHash(CheckError),
+    /// An earlier line has an entry for the same user, which wins.
+    DuplicateUser,
+}
+
+/// `scrypt_simple` error
+#[cfg(feature="simple")]
+#[derive(Debug)]
+pub enum HashError {
+    /// The random number generator failed to produce a salt.
+    Rng(rand_core::Error),
+    /// The parameters can not be represented in the requested format.
+    UnsupportedParams,
+    /// The requested salt length is outside of
+    /// `MIN_SALT_LEN..=MAX_SALT_LEN`.
+    InvalidSaltLen(usize),
+    /// The requested derived key length is outside of
+    /// `MIN_DK_LEN..=MAX_DK_LEN`.
+    InvalidDkLen(usize),
+    /// The `HashBackend` failed to compute the hash.
+    Backend(BackendError),
+    /// The password is longer than the limit of the function, e.g.
+    /// `DEFAULT_MAX_PASSWORD_LEN` or the `LengthPolicy` of
+    /// `scrypt_simple_prehashed`, it was not hashed.
+    PasswordTooLong {
+        /// The length of the password in bytes.
+        len: usize,
+        /// The longest password accepted.
+        max: usize,
+    },
+    /// The key of this length can not be wrapped, AES-KW requires a
+    /// multiple of 8 bytes of at least 16.
+    InvalidKeyLen(usize),
+    /// The `KeyProvider` has no pepper of this id.
+    UnknownPepper(u8),
+    /// The `KeyProvider` could not fetch the pepper of this id.
+    KeyUnavailable(u8),
+    /// The user name of `CredFile::set` is empty or contains `:` or a line
+    /// break.
+    InvalidUser,
+    /// The hash and the key of `hash_and_derive` do not fit into the
+    /// output of scrypt, or the key is empty.
+    InvalidOutputLen(InvalidOutputLen),
+}
+
+/// `enc::decrypt` error
+#[cfg(feature="enc")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EncError {
+    /// The data does not start with the magic of the encrypted format.
+    NotEncrypted,
+    /// The data uses a version of the format other than 0.
+    UnsupportedVersion,
+    /// The checksum of the header does not match, it has been corrupted.
+    InvalidChecksum,
+    /// The parameters of the header are invalid.
+    InvalidParams,
+    /// The password does not match the header.
+    WrongPassword,
+    /// The data is truncated or its HMAC does not match, it has been
+    /// corrupted or tampered with.
+    Corrupt,
+}
+
+/// `keywrap::unwrap_key` error
+#[cfg(feature="keywrap")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum UnwrapError {
+    /// The data does not start with the magic of the wrapped key format.
+    NotWrapped,
+    /// The data uses a version of the format other than 0.
+    UnsupportedVersion,
+    /// The data is truncated or the wrapped key is not of a length of
+    /// AES-KW.
+    Malformed,
+    /// The parameters of the data are invalid.
+    InvalidParams,
+    /// The integrity check of AES-KW failed: the passphrase is wrong or the
+    /// wrapped key has been corrupted.
+    IntegrityCheckFailed,
+}
+
+/// `pkcs8::KdfParams` error
+#[cfg(feature="pkcs8-params")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DerError {
+    /// The data is not a DER encoded `scrypt-params`, e.g. truncated, with
+    /// trailing bytes or non-minimal lengths or integers.
+    Malformed,
+    /// The `AlgorithmIdentifier` is not that of `id-scrypt`.
+    UnsupportedAlgorithm,
+    /// The parameters are out of range or rejected by `Params::new`.
+    InvalidParams,
+    /// The key length is out of range or not the length of the output.
+    InvalidKeyLength,
+}
+
+/// `Params::from_env` error
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EnvError {
+    var: String,
+    value: String,
+    kind: EnvErrorKind,
+}
+
+/// `PasswordBuilder::update` error
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InputTooLong {
+    len: usize,
+    max: usize,
+}
+
+/// The problem of an `EnvError`.
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EnvErrorKind {
+    /// The value is not valid Unicode.
+    NotUnicode,
+    /// The value, or a value of the `PARAMS` variable, is not a decimal
+    /// integer of the range of its field.
+    InvalidNumber,
+    /// The `PARAMS` variable is not of the form `ln=<log_n>,r=<r>,p=<p>`,
+    /// e.g. with an unknown or repeated key.
+    Malformed,
+    /// The parameters are set both by the `PARAMS` variable and by this
+    /// one.
+    Conflict,
+    /// The parameters are rejected by `Params::new`.
+    InvalidParams(InvalidParams),
+}
+
+/// Error of the futures of `nonblocking`
+#[cfg(feature="async")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TaskError<E> {
+    /// The job ran and failed.
+    Failed(E),
+    /// The job panicked, a bug of this crate or of the job.
+    Panicked,
+    /// The spawner dropped the job without running it, e.g. when its
+    /// runtime shut down.
+    Dropped,
+}
+
+/// Any error of this crate, for code which handles them in one place, e.g.
+/// a function returning `Result<T, Error>` which uses `?` on the results of
+/// `Params::new()`, `scrypt()` and `scrypt_check()`.
+#[derive(Debug)]
+pub enum Error {
+    /// `scrypt()` error
+    InvalidOutputLen(InvalidOutputLen),
+    /// `Params` error
+    InvalidParams(InvalidParams),
+    /// `scrypt_core()` error
+    #[cfg(feature="low-level")]
+    InvalidBufferLen(InvalidBufferLen),
+    /// `scrypt_check` error
+    #[cfg(feature="simple-verify")]
+    Check(CheckError),
+    /// `scrypt_simple` error
+    #[cfg(feature="simple")]
+    Hash(HashError),
+    /// `credfile` error
+    #[cfg(feature="simple")]
+    Line(LineError),
+    /// `enc::decrypt` error
+    #[cfg(feature="enc")]
+    Enc(EncError),
+    /// `keywrap::unwrap_key` error
+    #[cfg(feature="keywrap")]
+    Unwrap(UnwrapError),
+    /// `pkcs8::KdfParams` error
+    #[cfg(feature="pkcs8-params")]
+    Der(DerError),
+    /// `Params::from_env` error
+    #[cfg(feature = "std")]
+    Env(EnvError),
+    /// `PasswordBuilder::update` error
+    #[cfg(feature = "std")]
+    Input(InputTooLong),
+    /// `self_test()` error
+    SelfTest(SelfTestError),
+}
+
+/// Stable numeric codes for the error conditions of this crate, e.g. for
+/// FFI boundaries and structured logs.
+///
+/// The mapping is append-only: a code is never renumbered or reused, new
+/// conditions always get a new code.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ErrorCode {
+    /// `InvalidOutputLen`, `DerError::InvalidKeyLength`,
+    /// `HashError::InvalidKeyLen`, `HashError::InvalidOutputLen`,
+    /// `CheckError::InvalidOutputLen`
+    InvalidOutputLen = 1,
+    /// `InvalidParams`, `EncError::InvalidParams`, `DerError::InvalidParams`,
+    /// `UnwrapError::InvalidParams`
+    InvalidParams = 2,
+    /// `CheckError::HashMismatch`, `EncError::WrongPassword`,
+    /// `UnwrapError::IntegrityCheckFailed`
+    HashMismatch = 3,
+    /// `CheckError::InvalidFormat`, `DerError::Malformed`,
+    /// `UnwrapError::Malformed`
+    InvalidFormat = 4,
+    /// `CheckError::InvalidHashLen`
+    InvalidHashLen = 5,
+    /// `HashError::Rng`
+    Rng = 6,
+    /// `HashError::UnsupportedParams`
+    UnsupportedParams = 7,
+    /// `CheckError::InvalidChecksum`, `EncError::InvalidChecksum`
+    InvalidChecksum = 8,
+    /// `CheckError::UnsupportedFormat`, `EncError::NotEncrypted`,
+    /// `DerError::UnsupportedAlgorithm`, `UnwrapError::NotWrapped`
+    UnsupportedFormat = 9,
+    /// `CheckError::Unrepresentable`
+    Unrepresentable = 10,
+    /// `HashError::InvalidSaltLen`
+    InvalidSaltLen = 11,
+    /// `HashError::InvalidDkLen`
+    InvalidDkLen = 12,
+    /// `CheckError::HashTooShort`
+    HashTooShort = 13,
+    /// `CheckError::UnsupportedVersion`, `EncError::UnsupportedVersion`,
+    /// `UnwrapError::UnsupportedVersion`
+    UnsupportedVersion = 14,
+    /// `CheckError::UnexpectedFieldLen`
+    UnexpectedFieldLen = 15,
+    /// `CheckError::NonCanonicalBase64`
+    NonCanonicalBase64 = 16,
+    /// `CheckError::RehashFailed`
+    RehashFailed = 17,
+    /// `CheckError::CostExceedsLimit`
+    CostExceedsLimit = 18,
+    /// `CheckError::NeedsPepper`, `HashError::UnknownPepper`
+    NeedsPepper = 19,
+    /// `CheckError::Refused`
+    Refused = 20,
+    /// `CheckError::NeedsAssociatedData`
+    NeedsAssociatedData = 21,
+    /// `EncError::Corrupt`
+    Corrupt = 22,
+    /// A null pointer or otherwise unusable argument at the C interface,
+    /// `HashError::InvalidUser`
+    InvalidArgument = 23,
+    /// The output buffer of the C interface is too small
+    BufferTooSmall = 24,
+    /// A panic caught at the C interface, which is a bug
+    Internal = 25,
+    /// `CheckError::InvalidField`
+    InvalidField = 26,
+    /// `InvalidBufferLen`
+    InvalidBufferLen = 27,
+    /// `SelfTestError`
+    SelfTestFailed = 28,
+    /// `BackendError::Unavailable`, `BackendError::Rejected`
+    Backend = 29,
+    /// `CheckError::PasswordTooLong`, `HashError::PasswordTooLong`,
+    /// `InputTooLong`
+    PasswordTooLong = 30,
+    /// `CheckError::NeedsClientScheme`
+    NeedsClientScheme = 32,
+    /// `CheckError::ClientSchemeMismatch`
+    ClientSchemeMismatch = 33,
+    /// `CheckError::TamperSuspected`
+    TamperSuspected = 34,
+    /// `CheckError::UnknownYear`
+    UnknownYear = 37,
+    /// `CheckError::KeyUnavailable`, `HashError::KeyUnavailable`
+    KeyUnavailable = 38,
+}
+
+impl ErrorCode {
+    /// The numeric value of the code.
+    pub fn code(self) -> u32 { self as u32 }
+
+    /// Look up the code for a numeric value, returns `None` for unknown
+    /// values and the retired values 31, 35 and 36, which are not
+    /// reused.
+    pub fn from_code(code: u32) -> Option<ErrorCode> {
+        Some(match code {
+            1 => ErrorCode::InvalidOutputLen,
+            2 => ErrorCode::InvalidParams,
+            3 => ErrorCode::HashMismatch,
+            4 => ErrorCode::InvalidFormat,
+            5 => ErrorCode::InvalidHashLen,
+            6 => ErrorCode::Rng,
+            7 => ErrorCode::UnsupportedParams,
+            8 => ErrorCode::InvalidChecksum,
+            9 => ErrorCode::UnsupportedFormat,
+            10 => ErrorCode::Unrepresentable,
+            11 => ErrorCode::InvalidSaltLen,
+            12 => ErrorCode::InvalidDkLen,
+            13 => ErrorCode::HashTooShort,
+            14 => ErrorCode::UnsupportedVersion,
+            15 => ErrorCode::UnexpectedFieldLen,
+            16 => ErrorCode::NonCanonicalBase64,
+            17 => ErrorCode::RehashFailed,
+            18 => ErrorCode::CostExceedsLimit,
+            19 => ErrorCode::NeedsPepper,
+            20 => ErrorCode::Refused,
+            21 => ErrorCode::NeedsAssociatedData,
+            22 => ErrorCode::Corrupt,
+            23 => ErrorCode::InvalidArgument,
+            24 => ErrorCode::BufferTooSmall,
+            25 => ErrorCode::Internal,
+            26 => ErrorCode::InvalidField,
+            27 => ErrorCode::InvalidBufferLen,
+            28 => ErrorCode::SelfTestFailed,
+            29 => ErrorCode::Backend,
+            30 => ErrorCode::PasswordTooLong,
+            32 => ErrorCode::NeedsClientScheme,
+            33 => ErrorCode::ClientSchemeMismatch,
+            34 => ErrorCode::TamperSuspected,
+            37 => ErrorCode::UnknownYear,
+            38 => ErrorCode::KeyUnavailable,
+            _ => return None,
+        })
+    }
+}
+
+impl InvalidOutputLen {
+    /// Minimal supported output length.
+    pub const MIN: usize = 1;
+    /// Maximal supported output length, `(2^32 - 1) * 32`.
+    pub const MAX: u64 = 0xffff_ffff * 32;
+
+    pub(crate) fn new(len: usize) -> InvalidOutputLen {
+        InvalidOutputLen { len }
+    }
+
+    /// Length of the output buffer which was provided.
+    pub fn provided_len(&self) -> usize { self.len }
+
+    /// Minimal supported output length.
+    pub fn min(&self) -> usize { Self::MIN }
+
+    /// Maximal supported output length.
+    pub fn max(&self) -> u64 { Self::MAX }
+
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode { ErrorCode::InvalidOutputLen }
+}
+
+impl InvalidParams {
+    pub(crate) fn new(reason: ParamsReason) -> InvalidParams {
+        InvalidParams { reason }
+    }
+
+    /// The constraint which the parameters break.
+    pub fn reason(&self) -> ParamsReason { self.reason }
+
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode { ErrorCode::InvalidParams }
+}
+
+#[cfg(feature="simple-verify")]
+impl CheckError {
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match *self {
+            CheckError::HashMismatch => ErrorCode::HashMismatch,
+            CheckError::InvalidFormat => ErrorCode::InvalidFormat,
+            CheckError::InvalidHashLen(_) => ErrorCode::InvalidHashLen,
+            CheckError::InvalidChecksum => ErrorCode::InvalidChecksum,
+            CheckError::UnsupportedFormat => ErrorCode::UnsupportedFormat,
+            CheckError::Unrepresentable => ErrorCode::Unrepresentable,
+            CheckError::HashTooShort(_) => ErrorCode::HashTooShort,
+            CheckError::UnsupportedVersion => ErrorCode::UnsupportedVersion,
+            CheckError::UnexpectedFieldLen { .. } => ErrorCode::UnexpectedFieldLen,
+            CheckError::NonCanonicalBase64(_) => ErrorCode::NonCanonicalBase64,
+            CheckError::RehashFailed => ErrorCode::RehashFailed,
+            CheckError::CostExceedsLimit => ErrorCode::CostExceedsLimit,
+            CheckError::NeedsPepper(_) => ErrorCode::NeedsPepper,
+            CheckError::Refused(_) => ErrorCode::Refused,
+            CheckError::NeedsAssociatedData => ErrorCode::NeedsAssociatedData,
+            CheckError::InvalidField(_) => ErrorCode::InvalidField,
+            CheckError::Backend(ref e) => e.code(),
+            CheckError::PasswordTooLong { .. } => ErrorCode::PasswordTooLong,
+            CheckError::NeedsClientScheme(_) => ErrorCode::NeedsClientScheme,
+            CheckError::ClientSchemeMismatch { .. } => ErrorCode::ClientSchemeMismatch,
+            CheckError::TamperSuspected => ErrorCode::TamperSuspected,
+            CheckError::UnknownYear(_) => ErrorCode::UnknownYear,
+            CheckError::InvalidOutputLen(_) => ErrorCode::InvalidOutputLen,
+            CheckError::KeyUnavailable(_) => ErrorCode::KeyUnavailable,
+        }
+    }
+}
+
+#[cfg(feature="simple-verify")]
+impl BackendError {
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match *self {
+            BackendError::InvalidOutputLen(_) => ErrorCode::InvalidOutputLen,
+            BackendError::Unavailable | BackendError::Rejected => ErrorCode::Backend,
+        }
+    }
+}
+
+#[cfg(feature="simple-verify")]
+impl KeyError {
+    /// The error of verifying a string peppered with the key `id`.
+    #[cfg(feature="std")]
+    pub(crate) fn check_error(self, id: u8) -> CheckError {
+        match self {
+            KeyError::UnknownId => CheckError::NeedsPepper(id),
+            KeyError::Unavailable => CheckError::KeyUnavailable(id),
+        }
+    }
+
+    /// The error of hashing with the key `id`.
+    #[cfg(feature="simple")]
+    pub(crate) fn hash_error(self, id: u8) -> HashError {
+        match self {
+            KeyError::UnknownId => HashError::UnknownPepper(id),
+            KeyError::Unavailable => HashError::KeyUnavailable(id),
+        }
+    }
+}
+
+#[cfg(feature="simple")]
+impl HashError {
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match *self {
+            HashError::Rng(_) => ErrorCode::Rng,
+            HashError::UnsupportedParams => ErrorCode::UnsupportedParams,
+            HashError::InvalidSaltLen(_) => ErrorCode::InvalidSaltLen,
+            HashError::InvalidDkLen(_) => ErrorCode::InvalidDkLen,
+            HashError::Backend(ref e) => e.code(),
+            HashError::PasswordTooLong { .. } => ErrorCode::PasswordTooLong,
+            HashError::InvalidKeyLen(_) => ErrorCode::InvalidOutputLen,
+            HashError::UnknownPepper(_) => ErrorCode::NeedsPepper,
+            HashError::KeyUnavailable(_) => ErrorCode::KeyUnavailable,
+            HashError::InvalidUser => ErrorCode::InvalidArgument,
+            HashError::InvalidOutputLen(_) => ErrorCode::InvalidOutputLen,
+        }
+    }
+}
+
+#[cfg(feature="enc")]
+impl EncError {
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match *self {
+            EncError::NotEncrypted => ErrorCode::UnsupportedFormat,
+            EncError::UnsupportedVersion => ErrorCode::UnsupportedVersion,
+            EncError::InvalidChecksum => ErrorCode::InvalidChecksum,
+            EncError::InvalidParams => ErrorCode::InvalidParams,
+            EncError::WrongPassword => ErrorCode::HashMismatch,
+            EncError::Corrupt => ErrorCode::Corrupt,
+        }
+    }
+}
+
+#[cfg(feature="keywrap")]
+impl UnwrapError {
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match *self {
+            UnwrapError::NotWrapped => ErrorCode::UnsupportedFormat,
+            UnwrapError::UnsupportedVersion => ErrorCode::UnsupportedVersion,
+            UnwrapError::Malformed => ErrorCode::InvalidFormat,
+            UnwrapError::InvalidParams => ErrorCode::InvalidParams,
+            UnwrapError::IntegrityCheckFailed => ErrorCode::HashMismatch,
+        }
+    }
+}
+
+#[cfg(feature="pkcs8-params")]
+impl DerError {
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match *self {
+            DerError::Malformed => ErrorCode::InvalidFormat,
+            DerError::UnsupportedAlgorithm => ErrorCode::UnsupportedFormat,
+            DerError::InvalidParams => ErrorCode::InvalidParams,
+            DerError::InvalidKeyLength => ErrorCode::InvalidOutputLen,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl EnvError {
+    pub(crate) fn new(var: String, value: String, kind: EnvErrorKind) -> EnvError {
+        EnvError { var, value, kind }
+    }
+
+    /// The name of the variable, e.g. `SCRYPT_LOG_N`.
+    pub fn var(&self) -> &str { &self.var }
+
+    /// The value of the variable, lossily converted if it is not Unicode.
+    pub fn value(&self) -> &str { &self.value }
+
+    /// What is wrong with the value.
+    pub fn kind(&self) -> EnvErrorKind { self.kind }
+
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self.kind {
+            EnvErrorKind::InvalidParams(ref e) => e.code(),
+            _ => ErrorCode::InvalidFormat,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl InputTooLong {
+    pub(crate) fn new(len: usize, max: usize) -> InputTooLong {
+        InputTooLong { len, max }
+    }
+
+    /// The length the password would have had with the rejected input.
+    pub fn provided_len(&self) -> usize { self.len }
+
+    /// The maximal length of the `PasswordBuilder`.
+    pub fn max(&self) -> usize { self.max }
+
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode { ErrorCode::PasswordTooLong }
+}
+
+#[cfg(feature="simple")]
+impl LineError {
+    pub(crate) fn new(line: usize, kind: LineErrorKind) -> LineError {
+        LineError { line, kind }
+    }
+
+    /// The number of the line, starting at 1.
+    pub fn line(&self) -> usize { self.line }
+
+    /// What is wrong with the line.
+    pub fn kind(&self) -> LineErrorKind { self.kind }
 }
 
 impl fmt::Display for InvalidOutputLen {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str("invalid output buffer length")
+        write!(f, "invalid output buffer length {}, expected {}..={}",
+            self.len, Self::MIN, Self::MAX)
     }
 }
 
+#[cfg(feature = "std")]
 impl error::Error for InvalidOutputLen {
     fn description(&self) -> &str { "invalid output buffer length" }
 }
 
+#[cfg(feature="low-level")]
+impl InvalidBufferLen {
+    pub(crate) fn new(len: usize, expected: usize) -> InvalidBufferLen {
+        InvalidBufferLen { len, expected }
+    }
+
+    /// Length of the buffer which was provided.
+    pub fn provided_len(&self) -> usize { self.len }
+
+    /// The `128 * r * p` bytes the parameters require.
+    pub fn expected_len(&self) -> usize { self.expected }
+
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode { ErrorCode::InvalidBufferLen }
+}
+
+#[cfg(feature="low-level")]
+impl fmt::Display for InvalidBufferLen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid block buffer length {}, expected {}", self.len, self.expected)
+    }
+}
+
+#[cfg(all(feature="low-level", feature = "std"))]
+impl error::Error for InvalidBufferLen {
+    fn description(&self) -> &str { "invalid block buffer length" }
+}
+
 impl fmt::Display for InvalidParams {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str("invalid scrypt parameters")
     }
 }
 
+#[cfg(feature = "std")]
 impl error::Error for InvalidParams {
     fn description(&self) -> &str { "invalid scrypt parameters" }
 }
 
-#[cfg(feature="include_simple")]
+#[cfg(feature="simple-verify")]
 impl fmt::Display for CheckError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CheckError::HashTooShort(len) => {
+                return write!(f,
+                    "hash of {} bytes in `hashed_value` is shorter than {}",
+                    len, ::MIN_DK_LEN);
+            }
+            CheckError::UnexpectedFieldLen { salt_len, hash_len } => {
+                return write!(f,
+                    "salt of {} and hash of {} bytes in `hashed_value`, \
+                     expected 16 and 32", salt_len, hash_len);
+            }
+            CheckError::NonCanonicalBase64(field) => {
+                return write!(f,
+                    "non-canonical base64 in the {} field of `hashed_value`", field.name());
+            }
+            CheckError::InvalidField(field) => {
+                return write!(f,
+                    "missing or invalid {} field in `hashed_value`", field.name());
+            }
+            CheckError::NeedsPepper(id) => {
+                return write!(f, "`hashed_value` requires the pepper {}", id);
+            }
+            CheckError::NeedsClientScheme(id) => {
+                return write!(f, "`hashed_value` requires a digest of the client scheme {}", id);
+            }
+            CheckError::UnknownYear(year) => {
+                return write!(f, "no parameter presets for the year {}", year);
+            }
+            CheckError::KeyUnavailable(id) => {
+                return write!(f, "the pepper {} can not be fetched", id);
+            }
+            CheckError::InvalidOutputLen(ref e) => {
+                return write!(f, "invalid length of the derived key: {}", e);
+            }
+            CheckError::ClientSchemeMismatch { stored: Some(stored), claimed } => {
+                return write!(f,
+                    "digest of the client scheme {}, `stored` requires {}",
+                    claimed, stored);
+            }
+            CheckError::ClientSchemeMismatch { stored: None, claimed } => {
+                return write!(f,
+                    "digest of the client scheme {}, `stored` has no client scheme",
+                    claimed);
+            }
+            CheckError::Backend(e) => {
+                return write!(f, "hash backend failed: {}", e);
+            }
+            CheckError::PasswordTooLong { len, max } => {
+                return write!(f,
+                    "password of {} bytes is too long, expected at most {}", len, max);
+            }
+            CheckError::Refused(rule) => {
+                let rule = match rule {
+                    Rule::Format => "its format is not allowed",
+                    Rule::Version => "its version is not allowed",
+                    Rule::Encoding => "it is not canonically encoded",
+                    Rule::MinLogN => "`N` is too small",
+                    Rule::MaxMemory => "it requires too much memory",
+                    Rule::MaxOps => "it requires too much work",
+                };
+                return write!(f, "`hashed_value` is refused: {}", rule);
+            }
+            _ => {}
+        }
         f.write_str(match *self {
             CheckError::HashMismatch => "password hash mismatch",
             CheckError::InvalidFormat => "invalid `hashed_value` format",
+            CheckError::InvalidHashLen(_) => "invalid hash length in `hashed_value`",
+            CheckError::InvalidChecksum => "invalid checksum in `hashed_value`",
+            CheckError::UnsupportedFormat => "unsupported `hashed_value` format",
+            CheckError::Unrepresentable => "hash can not be represented in the target format",
+            CheckError::HashTooShort(_) => "hash in `hashed_value` is too short",
+            CheckError::UnsupportedVersion => "unsupported version of the `hashed_value` format",
+            CheckError::UnexpectedFieldLen { .. } => "unexpected salt or hash length in `hashed_value`",
+            CheckError::NonCanonicalBase64(_) => "non-canonical base64 in `hashed_value`",
+            CheckError::RehashFailed => "computing the new hash failed",
+            CheckError::CostExceedsLimit => "cost of `hashed_value` exceeds the limits",
+            CheckError::NeedsPepper(_) => "`hashed_value` requires a pepper",
+            CheckError::Refused(_) => "`hashed_value` is refused by the verifier",
+            CheckError::NeedsAssociatedData => "`hashed_value` requires associated data",
+            CheckError::InvalidField(_) => "missing or invalid field in `hashed_value`",
+            CheckError::Backend(_) => "hash backend failed",
+            CheckError::PasswordTooLong { .. } => "password is too long",
+            CheckError::NeedsClientScheme(_) => "`hashed_value` requires a client digest",
+            CheckError::ClientSchemeMismatch { .. } => "client scheme mismatch",
+            CheckError::TamperSuspected => "the MAC of `hashed_value` does not verify",
+            CheckError::UnknownYear(_) => "no parameter presets for the year",
+            CheckError::KeyUnavailable(_) => "the pepper can not be fetched",
+            CheckError::InvalidOutputLen(_) => "invalid length of the derived key",
         })
     }
 }
 
-#[cfg(feature="include_simple")]
+#[cfg(all(feature="simple-verify", feature="std"))]
 impl error::Error for CheckError {
     fn description(&self) -> &str {
         match *self {
             CheckError::HashMismatch => "password hash mismatch",
             CheckError::InvalidFormat => "invalid `hashed_value` format",
+            CheckError::InvalidHashLen(_) => "invalid hash length in `hashed_value`",
+            CheckError::InvalidChecksum => "invalid checksum in `hashed_value`",
+            CheckError::UnsupportedFormat => "unsupported `hashed_value` format",
+            CheckError::Unrepresentable => "hash can not be represented in the target format",
+            CheckError::HashTooShort(_) => "hash in `hashed_value` is too short",
+            CheckError::UnsupportedVersion => "unsupported version of the `hashed_value` format",
+            CheckError::UnexpectedFieldLen { .. } => "unexpected salt or hash length in `hashed_value`",
+            CheckError::NonCanonicalBase64(_) => "non-canonical base64 in `hashed_value`",
+            CheckError::RehashFailed => "computing the new hash failed",
+            CheckError::CostExceedsLimit => "cost of `hashed_value` exceeds the limits",
+            CheckError::NeedsPepper(_) => "`hashed_value` requires a pepper",
+            CheckError::Refused(_) => "`hashed_value` is refused by the verifier",
+            CheckError::NeedsAssociatedData => "`hashed_value` requires associated data",
+            CheckError::InvalidField(_) => "missing or invalid field in `hashed_value`",
+            CheckError::Backend(_) => "hash backend failed",
+            CheckError::PasswordTooLong { .. } => "password is too long",
+            CheckError::NeedsClientScheme(_) => "`hashed_value` requires a client digest",
+            CheckError::ClientSchemeMismatch { .. } => "client scheme mismatch",
+            CheckError::TamperSuspected => "the MAC of `hashed_value` does not verify",
+            CheckError::UnknownYear(_) => "no parameter presets for the year",
+            CheckError::KeyUnavailable(_) => "the pepper can not be fetched",
+            CheckError::InvalidOutputLen(_) => "invalid length of the derived key",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            CheckError::InvalidHashLen(ref e) | CheckError::InvalidOutputLen(ref e) => Some(e),
+            CheckError::Backend(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature="simple-verify")]
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BackendError::InvalidOutputLen(ref e) => e.fmt(f),
+            BackendError::Unavailable => f.write_str("hash backend unavailable"),
+            BackendError::Rejected => f.write_str("hash backend rejected the request"),
+        }
+    }
+}
+
+#[cfg(all(feature="simple-verify", feature="std"))]
+impl error::Error for BackendError {
+    fn description(&self) -> &str {
+        match *self {
+            BackendError::InvalidOutputLen(_) => "invalid output buffer length",
+            BackendError::Unavailable => "hash backend unavailable",
+            BackendError::Rejected => "hash backend rejected the request",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            BackendError::InvalidOutputLen(ref e) => Some(e),
+            _ => None,
         }
     }
 }
+
+#[cfg(feature="simple-verify")]
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            KeyError::UnknownId => "unknown key id",
+            KeyError::Unavailable => "key unavailable",
+        })
+    }
+}
+
+#[cfg(all(feature="simple-verify", feature="std"))]
+impl error::Error for KeyError {
+    fn description(&self) -> &str {
+        match *self {
+            KeyError::UnknownId => "unknown key id",
+            KeyError::Unavailable => "key unavailable",
+        }
+    }
+}
+
+#[cfg(feature="simple")]
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HashError::Rng(ref e) => write!(f, "salt generation failed: {}", e),
+            HashError::UnsupportedParams => {
+                f.write_str("parameters not supported by the format")
+            }
+            HashError::InvalidSaltLen(len) => {
+                write!(f, "invalid salt length {}, expected {}..={}",
+                    len, ::MIN_SALT_LEN, ::MAX_SALT_LEN)
+            }
+            HashError::InvalidDkLen(len) => {
+                write!(f, "invalid derived key length {}, expected {}..={}",
+                    len, ::MIN_DK_LEN, ::MAX_DK_LEN)
+            }
+            HashError::Backend(ref e) => write!(f, "hash backend failed: {}", e),
+            HashError::PasswordTooLong { len, max } => {
+                write!(f, "password of {} bytes is too long, expected at most {}", len, max)
+            }
+            HashError::InvalidKeyLen(len) => {
+                write!(f, "key of {} bytes can not be wrapped, expected a multiple of 8 of at \
+                    least 16", len)
+            }
+            HashError::UnknownPepper(id) => write!(f, "unknown pepper {}", id),
+            HashError::KeyUnavailable(id) => write!(f, "the pepper {} can not be fetched", id),
+            HashError::InvalidUser => f.write_str("invalid user name"),
+            HashError::InvalidOutputLen(ref e) => {
+                write!(f, "invalid length of the derived key: {}", e)
+            }
+        }
+    }
+}
+
+#[cfg(feature="simple")]
+impl error::Error for HashError {
+    fn description(&self) -> &str {
+        match *self {
+            HashError::Rng(_) => "salt generation failed",
+            HashError::UnsupportedParams => "parameters not supported by the format",
+            HashError::InvalidSaltLen(_) => "invalid salt length",
+            HashError::InvalidDkLen(_) => "invalid derived key length",
+            HashError::Backend(_) => "hash backend failed",
+            HashError::PasswordTooLong { .. } => "password is too long",
+            HashError::InvalidKeyLen(_) => "invalid length of the key to wrap",
+            HashError::UnknownPepper(_) => "unknown pepper",
+            HashError::KeyUnavailable(_) => "the pepper can not be fetched",
+            HashError::InvalidUser => "invalid user name",
+            HashError::InvalidOutputLen(_) => "invalid length of the derived key",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            HashError::Rng(ref e) => Some(e),
+            HashError::Backend(ref e) => Some(e),
+            HashError::InvalidOutputLen(ref e) => Some(e),
+            HashError::UnsupportedParams
+            | HashError::InvalidSaltLen(_)
+            | HashError::InvalidDkLen(_)
+            | HashError::PasswordTooLong { .. }
+            | HashError::InvalidKeyLen(_)
+            | HashError::UnknownPepper(_)
+            | HashError::KeyUnavailable(_)
+            | HashError::InvalidUser => None,
+        }
+    }
+}
+
+#[cfg(feature="simple")]
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            LineErrorKind::MissingSeparator => write!(f, "line {}: missing `:`", self.line),
+            LineErrorKind::EmptyUser => write!(f, "line {}: empty user", self.line),
+            LineErrorKind::InvalidHash(e) => write!(f, "line {}: {}", self.line, e),
+            LineErrorKind::DuplicateUser => write!(f, "line {}: duplicate user", self.line),
+        }
+    }
+}
+
+#[cfg(feature="simple")]
+impl error::Error for LineError {
+    fn description(&self) -> &str {
+        match self.kind {
+            LineErrorKind::MissingSeparator => "missing `:` in credentials line",
+            LineErrorKind::EmptyUser => "empty user in credentials line",
+            LineErrorKind::InvalidHash(_) => "invalid hash in credentials line",
+            LineErrorKind::DuplicateUser => "duplicate user in credentials line",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.kind {
+            LineErrorKind::InvalidHash(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature="enc")]
+impl fmt::Display for EncError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            EncError::NotEncrypted => "data is not in the scrypt encrypted format",
+            EncError::UnsupportedVersion => "unsupported version of the scrypt encrypted format",
+            EncError::InvalidChecksum => "invalid checksum in the header of the encrypted data",
+            EncError::InvalidParams => "invalid scrypt parameters in the encrypted data",
+            EncError::WrongPassword => "wrong password for the encrypted data",
+            EncError::Corrupt => "encrypted data is truncated or corrupted",
+        })
+    }
+}
+
+#[cfg(feature="enc")]
+impl error::Error for EncError {
+    fn description(&self) -> &str {
+        match *self {
+            EncError::NotEncrypted => "data is not in the scrypt encrypted format",
+            EncError::UnsupportedVersion => "unsupported version of the scrypt encrypted format",
+            EncError::InvalidChecksum => "invalid checksum in the header of the encrypted data",
+            EncError::InvalidParams => "invalid scrypt parameters in the encrypted data",
+            EncError::WrongPassword => "wrong password for the encrypted data",
+            EncError::Corrupt => "encrypted data is truncated or corrupted",
+        }
+    }
+}
+
+#[cfg(feature="keywrap")]
+impl fmt::Display for UnwrapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            UnwrapError::NotWrapped => "data is not in the wrapped key format",
+            UnwrapError::UnsupportedVersion => "unsupported version of the wrapped key format",
+            UnwrapError::Malformed => "wrapped key is truncated or of an invalid length",
+            UnwrapError::InvalidParams => "invalid scrypt parameters in the wrapped key",
+            UnwrapError::IntegrityCheckFailed => {
+                "wrong passphrase for the wrapped key or the key is corrupted"
+            }
+        })
+    }
+}
+
+#[cfg(feature="keywrap")]
+impl error::Error for UnwrapError {
+    fn description(&self) -> &str {
+        match *self {
+            UnwrapError::NotWrapped => "data is not in the wrapped key format",
+            UnwrapError::UnsupportedVersion => "unsupported version of the wrapped key format",
+            UnwrapError::Malformed => "wrapped key is truncated or of an invalid length",
+            UnwrapError::InvalidParams => "invalid scrypt parameters in the wrapped key",
+            UnwrapError::IntegrityCheckFailed => {
+                "wrong passphrase for the wrapped key or the key is corrupted"
+            }
+        }
+    }
+}
+
+#[cfg(feature="pkcs8-params")]
+impl fmt::Display for DerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            DerError::Malformed => "malformed DER encoding of scrypt-params",
+            DerError::UnsupportedAlgorithm => "algorithm identifier is not id-scrypt",
+            DerError::InvalidParams => "invalid scrypt parameters in scrypt-params",
+            DerError::InvalidKeyLength => "invalid key length in scrypt-params",
+        })
+    }
+}
+
+#[cfg(all(feature="pkcs8-params", feature="std"))]
+impl error::Error for DerError {
+    fn description(&self) -> &str {
+        match *self {
+            DerError::Malformed => "malformed DER encoding of scrypt-params",
+            DerError::UnsupportedAlgorithm => "algorithm identifier is not id-scrypt",
+            DerError::InvalidParams => "invalid scrypt parameters in scrypt-params",
+            DerError::InvalidKeyLength => "invalid key length in scrypt-params",
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let problem = match self.kind {
+            EnvErrorKind::NotUnicode => "not unicode",
+            EnvErrorKind::InvalidNumber => "not a valid number",
+            EnvErrorKind::Malformed => "expected `ln=<log_n>,r=<r>,p=<p>`",
+            EnvErrorKind::Conflict => "the parameters are already set by `_PARAMS`",
+            EnvErrorKind::InvalidParams(_) => "invalid scrypt parameters",
+        };
+        write!(f, "{}={:?}: {}", self.var, self.value, problem)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for EnvError {
+    fn description(&self) -> &str { "invalid environment variable" }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.kind {
+            EnvErrorKind::InvalidParams(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for InputTooLong {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "password of {} bytes is too long, expected at most {}", self.len, self.max)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for InputTooLong {
+    fn description(&self) -> &str { "password is too long" }
+}
+
+#[cfg(feature="async")]
+impl<E: fmt::Display> fmt::Display for TaskError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TaskError::Failed(ref e) => e.fmt(f),
+            TaskError::Panicked => f.write_str("the scrypt job panicked"),
+            TaskError::Dropped => f.write_str("the scrypt job was dropped without running"),
+        }
+    }
+}
+
+#[cfg(feature="async")]
+impl<E: error::Error> error::Error for TaskError<E> {
+    fn description(&self) -> &str {
+        match *self {
+            TaskError::Failed(_) => "the scrypt job failed",
+            TaskError::Panicked => "the scrypt job panicked",
+            TaskError::Dropped => "the scrypt job was dropped without running",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            TaskError::Failed(ref e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match *self {
+            Error::InvalidOutputLen(ref e) => e.code(),
+            Error::InvalidParams(ref e) => e.code(),
+            #[cfg(feature="low-level")]
+            Error::InvalidBufferLen(ref e) => e.code(),
+            #[cfg(feature="simple-verify")]
+            Error::Check(ref e) => e.code(),
+            #[cfg(feature="simple")]
+            Error::Hash(ref e) => e.code(),
+            #[cfg(feature="simple")]
+            Error::Line(ref e) => match e.kind() {
+                LineErrorKind::InvalidHash(e) => e.code(),
+                _ => ErrorCode::InvalidFormat,
+            },
+            #[cfg(feature="enc")]
+            Error::Enc(ref e) => e.code(),
+            #[cfg(feature="keywrap")]
+            Error::Unwrap(ref e) => e.code(),
+            #[cfg(feature="pkcs8-params")]
+            Error::Der(ref e) => e.code(),
+            #[cfg(feature = "std")]
+            Error::Env(ref e) => e.code(),
+            #[cfg(feature = "std")]
+            Error::Input(ref e) => e.code(),
+            Error::SelfTest(ref e) => e.code(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidOutputLen(ref e) => e.fmt(f),
+            Error::InvalidParams(ref e) => e.fmt(f),
+            #[cfg(feature="low-level")]
+            Error::InvalidBufferLen(ref e) => e.fmt(f),
+            #[cfg(feature="simple-verify")]
+            Error::Check(ref e) => e.fmt(f),
+            #[cfg(feature="simple")]
+            Error::Hash(ref e) => e.fmt(f),
+            #[cfg(feature="simple")]
+            Error::Line(ref e) => e.fmt(f),
+            #[cfg(feature="enc")]
+            Error::Enc(ref e) => e.fmt(f),
+            #[cfg(feature="keywrap")]
+            Error::Unwrap(ref e) => e.fmt(f),
+            #[cfg(feature="pkcs8-params")]
+            Error::Der(ref e) => e.fmt(f),
+            #[cfg(feature = "std")]
+            Error::Env(ref e) => e.fmt(f),
+            #[cfg(feature = "std")]
+            Error::Input(ref e) => e.fmt(f),
+            Error::SelfTest(ref e) => e.fmt(f),
+        }
+    }
+}
+
+/// The error wrapped by a variant is its `source()`.
+#[cfg(feature = "std")]
+impl error::Error for Error {
+    fn description(&self) -> &str { "scrypt error" }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(match *self {
+            Error::InvalidOutputLen(ref e) => e,
+            Error::InvalidParams(ref e) => e,
+            #[cfg(feature="low-level")]
+            Error::InvalidBufferLen(ref e) => e,
+            #[cfg(feature="simple-verify")]
+            Error::Check(ref e) => e,
+            #[cfg(feature="simple")]
+            Error::Hash(ref e) => e,
+            #[cfg(feature="simple")]
+            Error::Line(ref e) => e,
+            #[cfg(feature="enc")]
+            Error::Enc(ref e) => e,
+            #[cfg(feature="keywrap")]
+            Error::Unwrap(ref e) => e,
+            #[cfg(feature="pkcs8-params")]
+            Error::Der(ref e) => e,
+            #[cfg(feature = "std")]
+            Error::Env(ref e) => e,
+            #[cfg(feature = "std")]
+            Error::Input(ref e) => e,
+            Error::SelfTest(ref e) => e,
+        })
+    }
+}
+
+macro_rules! impl_from_error {
+    ($($(#[$cfg:meta])* $variant:ident($error:ty);)*) => {
+        $(
+            $(#[$cfg])*
+            impl From<$error> for Error {
+                fn from(e: $error) -> Error { Error::$variant(e) }
+            }
+        )*
+    }
+}
+
+impl_from_error! {
+    InvalidOutputLen(InvalidOutputLen);
+    InvalidParams(InvalidParams);
+    #[cfg(feature="low-level")]
+    InvalidBufferLen(InvalidBufferLen);
+    #[cfg(feature="simple-verify")]
+    Check(CheckError);
+    #[cfg(feature="simple")]
+    Hash(HashError);
+    #[cfg(feature="simple")]
+    Line(LineError);
+    #[cfg(feature="enc")]
+    Enc(EncError);
+    #[cfg(feature="keywrap")]
+    Unwrap(UnwrapError);
+    #[cfg(feature="pkcs8-params")]
+    Der(DerError);
+    #[cfg(feature = "std")]
+    Env(EnvError);
+    #[cfg(feature = "std")]
+    Input(InputTooLong);
+    SelfTest(SelfTestError);
+}
+
+#[cfg(feature="simple")]
+impl From<rand_core::Error> for HashError {
+    fn from(e: rand_core::Error) -> HashError {
+        HashError::Rng(e)
+    }
+}
+
+#[cfg(feature="simple")]
+impl From<HashError> for io::Error {
+    fn from(e: HashError) -> io::Error {
+        match e {
+            HashError::Rng(_) | HashError::Backend(_) | HashError::KeyUnavailable(_) => {
+                io::Error::other(e)
+            }
+            HashError::UnsupportedParams
+            | HashError::InvalidSaltLen(_)
+            | HashError::InvalidDkLen(_)
+            | HashError::PasswordTooLong { .. }
+            | HashError::InvalidKeyLen(_)
+            | HashError::UnknownPepper(_)
+            | HashError::InvalidUser
+            | HashError::InvalidOutputLen(_) => {
+                io::Error::new(io::ErrorKind::InvalidInput, e)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<InvalidOutputLen> for io::Error {
+    fn from(e: InvalidOutputLen) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<InputTooLong> for io::Error {
+    fn from(e: InputTooLong) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<InvalidParams> for io::Error {
+    fn from(e: InvalidParams) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, e)
+    }
+}
+
+/// A mismatch or a password which is too long is reported as `InvalidInput`
+/// (the password is wrong), while
+/// problems with the stored hash are reported as `InvalidData` and a failed
+/// rehash or backend as `Other`.
+#[cfg(all(feature="simple-verify", feature="std"))]
+impl From<CheckError> for io::Error {
+    fn from(e: CheckError) -> io::Error {
+        let kind = match e {
+            CheckError::HashMismatch
+            | CheckError::PasswordTooLong { .. }
+            | CheckError::UnknownYear(_)
+            | CheckError::InvalidOutputLen(_) => {
+                io::ErrorKind::InvalidInput
+            }
+            CheckError::InvalidFormat
+            | CheckError::InvalidHashLen(_)
+            | CheckError::InvalidChecksum
+            | CheckError::UnsupportedFormat
+            | CheckError::Unrepresentable
+            | CheckError::HashTooShort(_)
+            | CheckError::UnsupportedVersion
+            | CheckError::UnexpectedFieldLen { .. }
+            | CheckError::NonCanonicalBase64(_)
+            | CheckError::CostExceedsLimit
+            | CheckError::NeedsPepper(_)
+            | CheckError::Refused(_)
+            | CheckError::NeedsAssociatedData
+            | CheckError::InvalidField(_)
+            | CheckError::NeedsClientScheme(_)
+            | CheckError::ClientSchemeMismatch { .. }
+            | CheckError::TamperSuspected => {
+                io::ErrorKind::InvalidData
+            }
+            CheckError::RehashFailed
+            | CheckError::Backend(_)
+            | CheckError::KeyUnavailable(_) => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+/// A wrong password is reported as `InvalidInput`, problems with the data
+/// as `InvalidData`.
+#[cfg(feature="enc")]
+impl From<EncError> for io::Error {
+    fn from(e: EncError) -> io::Error {
+        let kind = match e {
+            EncError::WrongPassword => io::ErrorKind::InvalidInput,
+            EncError::NotEncrypted
+            | EncError::UnsupportedVersion
+            | EncError::InvalidChecksum
+            | EncError::InvalidParams
+            | EncError::Corrupt => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+/// A wrong passphrase is reported as `InvalidInput`, problems with the data
+/// as `InvalidData`.
+#[cfg(feature="keywrap")]
+impl From<UnwrapError> for io::Error {
+    fn from(e: UnwrapError) -> io::Error {
+        let kind = match e {
+            UnwrapError::IntegrityCheckFailed => io::ErrorKind::InvalidInput,
+            UnwrapError::NotWrapped
+            | UnwrapError::UnsupportedVersion
+            | UnwrapError::Malformed
+            | UnwrapError::InvalidParams => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+#[cfg(feature = "std")]
+fn downcast_io<E: error::Error + Copy + 'static>(e: &io::Error) -> Option<E> {
+    e.get_ref().and_then(|inner| inner.downcast_ref::<E>()).cloned()
+}
+
+#[cfg(feature = "std")]
+impl InvalidOutputLen {
+    /// Recover the error from an `io::Error` created by its `From`
+    /// conversion. Returns `None` if `e` wraps any other error.
+    pub fn from_io_error(e: &io::Error) -> Option<InvalidOutputLen> {
+        downcast_io(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl InvalidParams {
+    /// Recover the error from an `io::Error` created by its `From`
+    /// conversion. Returns `None` if `e` wraps any other error.
+    pub fn from_io_error(e: &io::Error) -> Option<InvalidParams> {
+        downcast_io(e)
+    }
+}
+
+#[cfg(all(feature="simple-verify", feature="std"))]
+impl CheckError {
+    /// Recover the error from an `io::Error` created by its `From`
+    /// conversion. Returns `None` if `e` wraps any other error.
+    pub fn from_io_error(e: &io::Error) -> Option<CheckError> {
+        downcast_io(e)
+    }
+}
+
+#[cfg(feature="enc")]
+impl EncError {
+    /// Recover the error from an `io::Error` created by its `From`
+    /// conversion. Returns `None` if `e` wraps any other error.
+    pub fn from_io_error(e: &io::Error) -> Option<EncError> {
+        downcast_io(e)
+    }
+}
@@ -0,0 +1,65 @@
+//! Non-standard variants of scrypt, for research on the cores of
+//! memory-hard functions. **Not scrypt:** their outputs differ from those
+//! of every scrypt implementation, no hash string format of this crate
+//! stores them, and they may change or be removed in any release.
+//!
+//! `scrypt_chacha` is scrypt with the Salsa20/8 core of BlockMix replaced
+//! by ChaCha20/8: the 8 round ChaCha20 block function of RFC 8439 on the
+//! 16 little-endian words of the block, without its constants, key or
+//! counter, plus the input, as with Salsa20/8. PBKDF2, ROMix, BlockMix and
+//! the parameters are those of `scrypt()`.
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use errors::InvalidOutputLen;
+use params::Params;
+use romix::{Block, MixingCore};
+use {block_len, check_output_len, core_with, pbkdf2_sha256};
+
+/// The ChaCha20/8 core of `scrypt_chacha`.
+struct ChaCha8;
+
+impl MixingCore for ChaCha8 {
+    fn hash(input: &Block, output: &mut Block) {
+        let mut b = input.0;
+        let mut x = b;
+        for _ in 0..4 {
+            quarter_round(&mut x, 0, 4, 8, 12);
+            quarter_round(&mut x, 1, 5, 9, 13);
+            quarter_round(&mut x, 2, 6, 10, 14);
+            quarter_round(&mut x, 3, 7, 11, 15);
+            quarter_round(&mut x, 0, 5, 10, 15);
+            quarter_round(&mut x, 1, 6, 11, 12);
+            quarter_round(&mut x, 2, 7, 8, 13);
+            quarter_round(&mut x, 3, 4, 9, 14);
+        }
+        for (b_i, &x_i) in b.iter_mut().zip(x.iter()) {
+            *b_i = b_i.wrapping_add(x_i);
+        }
+        output.0 = b;
+    }
+}
+
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[a] = x[a].wrapping_add(x[b]); x[d] = (x[d] ^ x[a]).rotate_left(16);
+    x[c] = x[c].wrapping_add(x[d]); x[b] = (x[b] ^ x[c]).rotate_left(12);
+    x[a] = x[a].wrapping_add(x[b]); x[d] = (x[d] ^ x[a]).rotate_left(8);
+    x[c] = x[c].wrapping_add(x[d]); x[b] = (x[b] ^ x[c]).rotate_left(7);
+}
+
+/// scrypt with the ChaCha20/8 core, see the module documentation. **Not
+/// interoperable with scrypt**, for research only.
+///
+/// # Return
+/// `Err(InvalidOutputLen)` under the conditions of `scrypt()`.
+pub fn scrypt_chacha(password: &[u8], salt: &[u8], params: &Params, output: &mut [u8])
+    -> Result<(), InvalidOutputLen>
+{
+    check_output_len(output)?;
+    let mut b = vec![0u8; block_len(params)];
+    let (mut x, mut v, mut t) = (Vec::new(), Vec::new(), Vec::new());
+    pbkdf2_sha256(password, salt, &mut b);
+    core_with::<ChaCha8>(&mut b, params, &mut x, &mut v, &mut t);
+    pbkdf2_sha256(password, &b, output);
+    Ok(())
+}
@@ -0,0 +1,451 @@
+//! Hash string formats, with a trait for application-defined formats.
+use core::fmt;
+use core::num::NonZeroU32;
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+#[cfg(feature="simple")]
+use rand_core::{CryptoRng, RngCore};
+
+use backend::HashBackend;
+#[cfg(feature="simple")]
+use config::SimpleConfig;
+use errors::{BackendError, CheckError, Field, InvalidOutputLen, ParamsReason};
+#[cfg(feature="simple")]
+use errors::HashError;
+use observer::check_memory;
+use params::Params;
+use redact::{Fingerprint, Redacted};
+use {cisco, crypt7, django, hex_string, lambdaworks, phc, phpscrypt, scryptkdf};
+use {rubyscrypt, simplescrypt, werkzeug};
+use {constant_time_eq, decode_rscrypt, derived_output_len, encode_rscrypt, scrypt,
+    scrypt_rounds_with};
+use {DerivedKey, Scratch};
+use {MAX_DK_LEN, MAX_SALT_LEN, MIN_DK_LEN, MIN_SALT_LEN};
+
+/// The parameters, salt and hash stored in a hash string.
+///
+/// Applications which store them in fields of their own, e.g. columns of a
+/// database table, create them with `generate()`, read them with
+/// `from_fields()` and convert them with `ScryptHash::from_parts()` and
+/// `From<ScryptHash>`.
+///
+/// `Debug` prints a fingerprint of the salt and only the length of the
+/// hash, like `ScryptHash`.
+#[derive(Clone, Eq, PartialEq)]
+pub struct HashParts {
+    /// The parameters used to compute `hash`.
+    pub params: Params,
+    /// The salt as passed to scrypt, e.g. the plain text salt of Django.
+    pub salt: Vec<u8>,
+    /// The scrypt output.
+    pub hash: Vec<u8>,
+}
+
+impl HashParts {
+    /// Hash `password` with `params` and `salt` into `len` bytes.
+    pub fn new(password: &[u8], params: &Params, salt: &[u8], len: usize)
+        -> Result<HashParts, InvalidOutputLen>
+    {
+        let mut hash = vec![0u8; len];
+        scrypt(password, salt, params, &mut hash)?;
+        Ok(HashParts { params: *params, salt: salt.to_vec(), hash })
+    }
+
+    /// Hash `password` like `config.hash_with_rng()`, but return the parts
+    /// instead of the string, e.g. to store them in columns of their own.
+    ///
+    /// # Return
+    /// `Err(HashError::Rng)` if `rng` fails and
+    /// `Err(HashError::UnsupportedParams)` if `config` hashes other than
+    /// plain scrypt, e.g. with `final_rounds`, which the parts do not
+    /// record.
+    #[cfg(feature="simple")]
+    pub fn generate<R: RngCore + CryptoRng>(
+        password: &str, config: &SimpleConfig, rng: &mut R,
+    ) -> Result<HashParts, HashError> {
+        let v2 = config.v2_options();
+        if v2.changes_hash() || v2.needs_input() { Err(HashError::UnsupportedParams)?; }
+        config.parts_with_rng(password.as_bytes(), rng).map(|(parts, _)| parts)
+    }
+
+    /// The parts stored in separate fields, e.g. the columns of a database
+    /// table, with the parameters of `Params::new()`.
+    ///
+    /// # Return
+    /// `Err(CheckError::InvalidField)` with the first field which is
+    /// invalid: `N`, `r` or `p` if the parameters are, the salt unless it is
+    /// of `MIN_SALT_LEN..=MAX_SALT_LEN` and the hash unless it is of
+    /// `MIN_DK_LEN..=MAX_DK_LEN` bytes, the lengths of `SimpleConfig`.
+    /// Parts of other lengths, e.g. of an imported hash string, are
+    /// created from the fields of `HashParts` or with `From<ScryptHash>`.
+    pub fn from_fields(log_n: u8, r: u32, p: u32, salt: &[u8], hash: &[u8])
+        -> Result<HashParts, CheckError>
+    {
+        let params = Params::new(log_n, r, p).map_err(|e| {
+            CheckError::InvalidField(match e.reason() {
+                ParamsReason::RZero => Field::R,
+                ParamsReason::PZero | ParamsReason::RpTooLarge => Field::P,
+                _ => Field::N,
+            })
+        })?;
+        if !(MIN_SALT_LEN..=MAX_SALT_LEN).contains(&salt.len()) {
+            Err(CheckError::InvalidField(Field::Salt))?;
+        }
+        if !(MIN_DK_LEN..=MAX_DK_LEN).contains(&hash.len()) {
+            Err(CheckError::InvalidField(Field::Hash))?;
+        }
+        Ok(HashParts { params, salt: salt.to_vec(), hash: hash.to_vec() })
+    }
+
+    /// Check if `password` hashes to `hash`, compared in constant time.
+    ///
+    /// # Return
+    /// `Err(CheckError::HashMismatch)` if it does not and
+    /// `Err(CheckError::InvalidHashLen)` if `hash` has an invalid length.
+    pub fn verify(&self, password: &[u8]) -> Result<(), CheckError> {
+        let mut output = vec![0u8; self.hash.len()];
+        verify(password, &self.params, &self.salt, &self.hash, &mut output)
+    }
+
+    /// The `Debug` output with the salt and hash bytes, see
+    /// `ScryptHash::reveal_debug()`.
+    pub fn reveal_debug(&self) -> String {
+        format!("HashParts {{ params: {:?}, salt: {:?}, hash: {:?} }}",
+            self.params, self.salt, self.hash)
+    }
+}
+
+impl fmt::Debug for HashParts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HashParts")
+            .field("params", &self.params)
+            .field("salt", &Fingerprint(&self.salt))
+            .field("hash", &Redacted(self.hash.len()))
+            .finish()
+    }
+}
+
+/// Check if `password` hashes to `hash`, using `output` of the same length
+/// as scratch space.
+pub(crate) fn verify(
+    password: &[u8], params: &Params, salt: &[u8], hash: &[u8],
+    output: &mut [u8],
+) -> Result<(), CheckError> {
+    verify_with(password, params, salt, hash, output, &mut Scratch::default())
+}
+
+/// `verify`, with the buffers of `scratch` for scrypt.
+pub(crate) fn verify_with(
+    password: &[u8], params: &Params, salt: &[u8], hash: &[u8],
+    output: &mut [u8], scratch: &mut Scratch,
+) -> Result<(), CheckError> {
+    verify_rounds_with(password, params, salt, hash, NonZeroU32::MIN, output, scratch)
+}
+
+/// `verify_with`, with `c` iterations of the final PBKDF2, see
+/// `scrypt_with_final_rounds`.
+pub(crate) fn verify_rounds_with(
+    password: &[u8], params: &Params, salt: &[u8], hash: &[u8], c: NonZeroU32,
+    output: &mut [u8], scratch: &mut Scratch,
+) -> Result<(), CheckError> {
+    // an empty hash is left to `scrypt`, which reports it as
+    // `InvalidHashLen`
+    if !hash.is_empty() && hash.len() < ::MIN_DK_LEN {
+        Err(CheckError::HashTooShort(hash.len()))?;
+    }
+    check_memory(params);
+    scrypt_rounds_with(password, salt, params, c, output, scratch)
+        .map_err(CheckError::InvalidHashLen)?;
+    compare(output, hash)
+}
+
+/// `verify`, with `key_len` more bytes of output after the hash, which are
+/// returned if the password matches, see `check_and_derive`.
+pub(crate) fn verify_and_derive(
+    password: &[u8], params: &Params, salt: &[u8], hash: &[u8], key_len: usize,
+) -> Result<DerivedKey, CheckError> {
+    // without a hash any password would match
+    if hash.is_empty() { Err(CheckError::InvalidHashLen(InvalidOutputLen::new(0)))?; }
+    if hash.len() < ::MIN_DK_LEN { Err(CheckError::HashTooShort(hash.len()))?; }
+    let output_len = derived_output_len(hash.len(), key_len)
+        .map_err(CheckError::InvalidOutputLen)?;
+    check_memory(params);
+    let mut output = vec![0u8; output_len];
+    scrypt(password, salt, params, &mut output).map_err(CheckError::InvalidHashLen)?;
+    // dropped and zeroed on a mismatch
+    let key = DerivedKey::new(output.split_off(hash.len()));
+    compare(&output, hash).map(|_| key)
+}
+
+/// `verify`, with the output computed by `backend`.
+pub(crate) fn verify_backend(
+    password: &[u8], params: &Params, salt: &[u8], hash: &[u8],
+    backend: &dyn HashBackend,
+) -> Result<(), CheckError> {
+    if !hash.is_empty() && hash.len() < ::MIN_DK_LEN {
+        Err(CheckError::HashTooShort(hash.len()))?;
+    }
+    let mut output = vec![0u8; hash.len()];
+    backend.derive(password, salt, params, &mut output).map_err(|e| match e {
+        BackendError::InvalidOutputLen(e) => CheckError::InvalidHashLen(e),
+        e => CheckError::Backend(e),
+    })?;
+    compare(&output, hash)
+}
+
+/// Compare the computed `output` against the stored `hash`.
+pub(crate) fn compare(output: &[u8], hash: &[u8]) -> Result<(), CheckError> {
+    // Be careful here - its important that the comparison be done using a fixed
+    // time equality check. Otherwise an adversary that can measure how long
+    // this step takes can learn about the hashed value which would allow them
+    // to mount an offline brute force attack against the hashed password.
+    if constant_time_eq(output, hash) {
+        Ok(())
+    } else {
+        Err(CheckError::HashMismatch)
+    }
+}
+
+/// A hash string format.
+///
+/// The built-in formats are implemented by `Format`, applications can
+/// implement their own and add them to a `Registry`. Formats are `Send` and
+/// `Sync`, so that a registry can be shared by the threads of a server.
+pub trait HashFormat: Send + Sync {
+    /// Check if `hashed_value` belongs to this format, usually by its prefix.
+    ///
+    /// Only strings of this format should match, a well-formed string of
+    /// another format matching is reported as an ambiguous format by
+    /// `Registry`.
+    fn matches(&self, hashed_value: &str) -> bool;
+
+    /// Decode a string which `matches()`.
+    ///
+    /// # Return
+    /// `Err(CheckError::InvalidFormat)` if `hashed_value` is malformed.
+    fn parse(&self, hashed_value: &str) -> Result<HashParts, CheckError>;
+
+    /// Encode `parts`, which must satisfy `supports()`.
+    fn encode(&self, parts: &HashParts) -> String;
+
+    /// Check if the format can represent `parts`, so that `parse()` returns
+    /// them unchanged from the output of `encode()`.
+    fn supports(&self, _parts: &HashParts) -> bool { true }
+}
+
+/// The built-in hash string formats.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Format {
+    /// `$rscrypt$`, produced by `scrypt_simple`.
+    Rscrypt,
+    /// `$scrypt$`, PHC strings produced by `scrypt_simple_phc`.
+    Phc,
+    /// `$7$`, produced by libxcrypt, libsodium and `crypt7::crypt`.
+    Crypt7,
+    /// `$s0$`, produced by Java's Lambdaworks `SCryptUtil`.
+    Lambdaworks,
+    /// `scrypt$`, produced by Django's `ScryptPasswordHasher`.
+    Django,
+    /// `scrypt:`, produced by Werkzeug's `generate_password_hash`.
+    Werkzeug,
+    /// `$9$`, Cisco IOS type 9 secrets.
+    Cisco,
+    /// `scrypt:ln=`, hex strings produced by `scrypt_simple_hex`.
+    Hex,
+    /// `SCRYPT:`, produced by Perl's `Crypt::ScryptKDF`.
+    ScryptKdf,
+    /// `<N>$<r>$<p>$`, produced by the PHP extension `php-scrypt`.
+    PhpScrypt,
+    /// `<N>$<r>$<p>$<hex salt>`, produced by Go's `simple-scrypt`.
+    SimpleScrypt,
+    /// `<hex N>$<hex r>$<hex p>$`, produced by the Ruby gem `scrypt`.
+    RubyScrypt,
+}
+
+const ALL: &[Format] = &[
+    Format::Rscrypt, Format::Phc, Format::Crypt7, Format::Lambdaworks,
+    Format::Django, Format::Werkzeug, Format::Cisco, Format::Hex, Format::ScryptKdf,
+    Format::PhpScrypt, Format::SimpleScrypt, Format::RubyScrypt,
+];
+
+impl Format {
+    /// Detect the format of `hashed_value` from its prefix.
+    ///
+    /// # Return
+    /// `None` if no format or more than one format matches.
+    pub fn detect(hashed_value: &str) -> Option<Format> {
+        let mut matching = ALL.iter().filter(|f| f.matches(hashed_value));
+        match (matching.next(), matching.next()) {
+            (Some(&format), None) => Some(format),
+            _ => None,
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            Format::Rscrypt => "$rscrypt$",
+            Format::Phc => phc::PREFIX,
+            Format::Crypt7 => crypt7::PREFIX,
+            Format::Lambdaworks => lambdaworks::PREFIX,
+            Format::Django => django::PREFIX,
+            Format::Werkzeug => werkzeug::PREFIX,
+            Format::Cisco => cisco::PREFIX,
+            Format::Hex => hex_string::PREFIX,
+            Format::ScryptKdf => scryptkdf::PREFIX,
+            // the strings start with their parameters, see `matches`
+            Format::PhpScrypt | Format::SimpleScrypt | Format::RubyScrypt => "",
+        }
+    }
+}
+
+impl HashFormat for Format {
+    fn matches(&self, hashed_value: &str) -> bool {
+        match *self {
+            Format::Werkzeug => werkzeug::matches(hashed_value),
+            Format::PhpScrypt => phpscrypt::matches(hashed_value),
+            Format::SimpleScrypt => simplescrypt::matches(hashed_value),
+            Format::RubyScrypt => rubyscrypt::matches(hashed_value),
+            _ => hashed_value.starts_with(self.prefix()),
+        }
+    }
+
+    fn parse(&self, hashed_value: &str) -> Result<HashParts, CheckError> {
+        let (params, salt, hash) = match *self {
+            Format::Rscrypt => decode_rscrypt(hashed_value),
+            Format::Phc => phc::decode(hashed_value),
+            Format::Crypt7 => crypt7::decode(hashed_value),
+            Format::Lambdaworks => lambdaworks::decode(hashed_value),
+            Format::Django => django::decode(hashed_value),
+            Format::Werkzeug => werkzeug::decode(hashed_value),
+            Format::Cisco => cisco::decode(hashed_value),
+            Format::Hex => hex_string::decode(hashed_value),
+            Format::ScryptKdf => scryptkdf::decode(hashed_value),
+            Format::PhpScrypt => phpscrypt::decode(hashed_value),
+            Format::SimpleScrypt => simplescrypt::decode(hashed_value),
+            Format::RubyScrypt => rubyscrypt::decode(hashed_value),
+        }?;
+        Ok(HashParts { params, salt, hash })
+    }
+
+    fn encode(&self, parts: &HashParts) -> String {
+        assert!(self.supports(parts), "{:?} can not represent the hash", self);
+        let (params, salt, hash) = (&parts.params, &parts.salt, &parts.hash);
+        // `supports` checked that text salts are valid UTF-8
+        let text = || String::from_utf8_lossy(salt);
+        match *self {
+            Format::Rscrypt => encode_rscrypt(params, salt, hash),
+            Format::Phc => phc::encode(params, salt, hash),
+            Format::Crypt7 => crypt7::encode_string(params, &text(), hash),
+            Format::Lambdaworks => lambdaworks::encode(params, salt, hash),
+            Format::Django => django::encode(params, &text(), hash),
+            Format::Werkzeug => werkzeug::encode(params, &text(), hash),
+            Format::Cisco => cisco::encode(&text(), hash),
+            Format::Hex => hex_string::encode(params, salt, hash),
+            Format::ScryptKdf => scryptkdf::encode(params, salt, hash),
+            Format::PhpScrypt => phpscrypt::encode(params, &text(), hash),
+            Format::SimpleScrypt => simplescrypt::encode(params, salt, hash),
+            Format::RubyScrypt => rubyscrypt::encode(params, salt, hash),
+        }
+    }
+
+    fn supports(&self, parts: &HashParts) -> bool {
+        let HashParts { ref params, ref salt, ref hash } = *parts;
+        // salts stored as plain text end at the next `$`
+        let text = || ::core::str::from_utf8(salt).is_ok() && !salt.contains(&b'$');
+        match *self {
+            Format::Rscrypt => true,
+            Format::Phc => !hash.is_empty(),
+            Format::Crypt7 => {
+                crypt7::supports(params) && crypt7::is_salt(salt) && hash.len() == 32
+            }
+            Format::Lambdaworks => lambdaworks::supports(params) && hash.len() == 32,
+            Format::Django => text() && !salt.is_empty() && hash.len() == django::HASH_LEN,
+            Format::Werkzeug => text() && hash.len() == werkzeug::HASH_LEN,
+            Format::Cisco => {
+                *params == cisco::params() && salt.len() == cisco::SALT_LEN
+                    && salt.iter().all(|b| cisco::ALPHABET.contains(b))
+                    && hash.len() == 32
+            }
+            Format::Hex => hex_string::supports(salt, hash),
+            Format::ScryptKdf => !salt.is_empty() && !hash.is_empty(),
+            Format::PhpScrypt => {
+                text() && hash.len() == phpscrypt::HASH_LEN
+                    && !simplescrypt::is_salt(salt)
+            }
+            Format::SimpleScrypt => {
+                salt.len() >= simplescrypt::MIN_SALT_LEN
+                    && hash.len() >= simplescrypt::MIN_HASH_LEN
+            }
+            Format::RubyScrypt => {
+                rubyscrypt::supports(params) && rubyscrypt::supports_salt(salt)
+                    && rubyscrypt::supports_hash(hash.len())
+            }
+        }
+    }
+}
+
+/// The formats consulted by `Registry::verify`.
+///
+/// Registries are passed explicitly, there is no global registry.
+/// `Registry::builtin()` starts with the formats of `Format`, to which
+/// applications can add their own at startup.
+pub struct Registry {
+    formats: Vec<Box<dyn HashFormat>>,
+}
+
+impl Registry {
+    /// Create an empty registry.
+    pub fn new() -> Registry {
+        Registry { formats: Vec::new() }
+    }
+
+    /// Create a registry of the built-in formats.
+    pub fn builtin() -> Registry {
+        let mut registry = Registry::new();
+        for &format in ALL {
+            registry.register(format);
+        }
+        registry
+    }
+
+    /// Add `format` to the registry.
+    pub fn register<F: HashFormat + 'static>(&mut self, format: F) -> &mut Registry {
+        self.formats.push(Box::new(format));
+        self
+    }
+
+    /// Find the format `hashed_value` belongs to.
+    ///
+    /// # Return
+    /// `None` if no format or more than one format matches.
+    pub fn detect(&self, hashed_value: &str) -> Option<&dyn HashFormat> {
+        let mut matching = self.formats.iter().filter(|f| f.matches(hashed_value));
+        match (matching.next(), matching.next()) {
+            (Some(format), None) => Some(&**format),
+            _ => None,
+        }
+    }
+
+    /// `verify` compares a password against a hash string in any of the
+    /// registered formats, like `verify_any`.
+    ///
+    /// # Return
+    /// The matching format if the password matches,
+    /// `Err(CheckError::HashMismatch)` if it does not and
+    /// `Err(CheckError::UnsupportedFormat)` if no format or more than one
+    /// format matches `hashed_value`. Malformed strings are reported as
+    /// `Err(CheckError::InvalidFormat)`.
+    pub fn verify(&self, password: &str, hashed_value: &str)
+        -> Result<&dyn HashFormat, CheckError>
+    {
+        let format = self.detect(hashed_value)
+            .ok_or(CheckError::UnsupportedFormat)?;
+        format.parse(hashed_value)?.verify(password.as_bytes())?;
+        Ok(format)
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Registry { Registry::new() }
+}
@@ -0,0 +1,581 @@
+//! Parsed hash strings.
+use core::fmt;
+use core::num::NonZeroU32;
+use core::str::FromStr;
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use byteorder::{ByteOrder, LittleEndian};
+#[cfg(feature="simple")]
+use rand_core::{CryptoRng, RngCore};
+
+#[cfg(feature="simple")]
+use config::SimpleConfig;
+use backend::HashBackend;
+use errors::CheckError;
+#[cfg(feature="simple")]
+use errors::HashError;
+use format::{verify_backend, Format, HashFormat, HashParts};
+use observer::check_version;
+use params::Params;
+use redact::{Fingerprint, Redacted};
+use {ct_base64, phc};
+use {decode_rscrypt_v2, rscrypt_v2_supports, split_rscrypt, Scratch, V2Options};
+
+/// Capacity of the inline salt and hash buffers of `ScryptHashRef`.
+const INLINE_LEN: usize = 64;
+
+/// Longest base64 field which can decode into `INLINE_LEN` bytes.
+const MAX_FIELD_LEN: usize = INLINE_LEN.div_ceil(3) * 4;
+
+/// The formats in the order of their identifiers in `ScryptHash::to_bytes()`.
+const FORMAT_IDS: &[Format] = &[
+    Format::Rscrypt, Format::Phc, Format::Crypt7, Format::Lambdaworks,
+    Format::Django, Format::Werkzeug, Format::Cisco, Format::Hex, Format::ScryptKdf,
+    Format::PhpScrypt, Format::SimpleScrypt, Format::RubyScrypt,
+];
+
+/// A parsed hash string in any of the formats accepted by `scrypt_check`.
+///
+/// `Display` re-encodes the hash in the canonical form of its format, e.g.
+/// with explicit PHC parameters and as rscrypt version 0 if `r` and `p` fit
+/// (version 2 strings stay version 2, with their extensions, e.g. the final
+/// PBKDF2 iterations of `scrypt_simple_final_rounds`), so the result can be
+/// stored. `Debug`
+/// prints a fingerprint of the salt and only the length of the hash, so it
+/// is safe to log, `reveal_debug()` prints both.
+#[derive(Clone, Eq, PartialEq)]
+pub struct ScryptHash {
+    format: Format,
+    version: Option<u8>,
+    parts: HashParts,
+    v2: V2Options,
+}
+
+impl ScryptHash {
+    /// Parse `hashed_value`, detecting its format from the prefix.
+    ///
+    /// # Return
+    /// `Err(CheckError::UnsupportedFormat)` if the format is unknown and
+    /// `Err(CheckError::InvalidFormat)` if `hashed_value` is malformed.
+    pub fn parse(hashed_value: &str) -> Result<ScryptHash, CheckError> {
+        let format = Format::detect(hashed_value)
+            .ok_or(CheckError::UnsupportedFormat)?;
+        let (parts, v2) = match format {
+            Format::Rscrypt => {
+                let (params, salt, hash, v2) = decode_rscrypt_v2(hashed_value)?;
+                (HashParts { params, salt, hash }, v2)
+            }
+            _ => (format.parse(hashed_value)?, V2Options::default()),
+        };
+        // the parser only accepts `$rscrypt$0$` to `$rscrypt$2$`
+        let version = match format {
+            Format::Rscrypt => Some(hashed_value.as_bytes()[9] - b'0'),
+            _ => None,
+        };
+        Ok(ScryptHash { format, version, parts, v2 })
+    }
+
+    /// Hash `password` like `config.hash_with_rng()`, but return the parsed
+    /// hash instead of its string, e.g. to store the salt and hash in
+    /// columns of their own. `to_string()` encodes it, with the standard
+    /// base64 alphabet even if `config` is URL-safe.
+    ///
+    /// # Return
+    /// `Err(HashError::Rng)` if `rng` fails and
+    /// `Err(HashError::UnsupportedParams)` if `config` has a pepper,
+    /// associated data or a client scheme, which `ScryptHash` does not
+    /// verify.
+    #[cfg(feature="simple")]
+    pub fn generate<R: RngCore + CryptoRng>(
+        password: &str, config: &SimpleConfig, rng: &mut R,
+    ) -> Result<ScryptHash, HashError> {
+        if config.v2_options().needs_input() { Err(HashError::UnsupportedParams)?; }
+        let (parts, v2) = config.parts_with_rng(password.as_bytes(), rng)?;
+        let version = match config.version() {
+            2 => Some(2),
+            _ => version_of(config.format(), &parts.params),
+        };
+        Ok(ScryptHash { format: config.format(), version, parts, v2 })
+    }
+
+    /// The hash of `parts` in `format`, e.g. of salt and hash stored in
+    /// columns of their own, which `to_string()` encodes like
+    /// `ScryptHash::generate()`.
+    ///
+    /// # Return
+    /// `Err(CheckError::Unrepresentable)` if `format` can not store the
+    /// parts, e.g. a salt of another length than its own.
+    pub fn from_parts(format: Format, parts: HashParts) -> Result<ScryptHash, CheckError> {
+        if !format.supports(&parts) { Err(CheckError::Unrepresentable)?; }
+        let version = version_of(format, &parts.params);
+        Ok(ScryptHash { format, version, parts, v2: V2Options::default() })
+    }
+
+    /// The format of the hash string.
+    pub fn format(&self) -> Format { self.format }
+
+    /// The version of rscrypt strings: `0` if `r` and `p` are stored in a
+    /// single byte each, `1` if they are stored in four and `2` for the
+    /// layout of `scrypt_simple_v2`. `None` for the other formats.
+    pub fn format_version(&self) -> Option<u8> { self.version }
+
+    /// The scrypt parameters.
+    pub fn params(&self) -> Params { self.parts.params }
+
+    /// The salt as passed to scrypt.
+    pub fn salt(&self) -> &[u8] { &self.parts.salt }
+
+    /// The stored scrypt output.
+    pub fn hash_bytes(&self) -> &[u8] { &self.parts.hash }
+
+    /// The parameters, salt and hash. `HashParts::verify()` does not apply
+    /// the final PBKDF2 iterations, the pre-hashing or the bound header of
+    /// version 2 strings, use `verify()`.
+    pub fn parts(&self) -> &HashParts { &self.parts }
+
+    /// The day the hash was created on, in days since the Unix epoch, if
+    /// the string records it, see `SimpleConfigBuilder::record_created_at`.
+    /// `None` for the strings of other functions and formats. Neither
+    /// `to_bytes()` nor `to_record()` store it.
+    pub fn created_at(&self) -> Option<u32> { self.v2.created_at }
+
+    /// The iterations of the final PBKDF2 of strings of
+    /// `scrypt_simple_final_rounds()`, `None` for one.
+    pub fn final_rounds(&self) -> Option<NonZeroU32> { self.v2.final_rounds }
+
+    /// The length above which passwords are hashed with SHA-256 before
+    /// scrypt, for strings of `scrypt_simple_prehashed()`.
+    pub fn prehash_above(&self) -> Option<u16> { self.v2.prehash_above }
+
+    /// The extensions of version 2 strings, default for other strings.
+    pub(crate) fn v2_options(&self) -> V2Options { self.v2 }
+
+    /// The `Debug` output with the salt and hash bytes, e.g. for tests.
+    /// Never log it.
+    pub fn reveal_debug(&self) -> String {
+        format!("ScryptHash {{ format: {:?}, version: {:?}, params: {:?}, salt: {:?}, hash: {:?} }}",
+            self.format, self.version, self.parts.params, self.parts.salt, self.parts.hash)
+    }
+
+    /// Check if `password` hashes to the stored value, like `scrypt_check`.
+    ///
+    /// # Return
+    /// `Err(CheckError::HashMismatch)` if it does not and
+    /// `Err(CheckError::InvalidHashLen)` if the stored hash has a length
+    /// `scrypt` can not produce.
+    pub fn verify(&self, password: &str) -> Result<(), CheckError> {
+        self.verify_bytes(password.as_bytes())
+    }
+
+    /// `verify()` with the hash computed by `backend`.
+    ///
+    /// # Return
+    /// `Err(CheckError::Unrepresentable)` for strings with final PBKDF2
+    /// iterations, which `HashBackend` does not compute.
+    pub fn verify_with_backend(&self, password: &str, backend: &dyn HashBackend)
+        -> Result<(), CheckError>
+    {
+        self.verify_bytes_with_backend(password.as_bytes(), backend)
+    }
+
+    /// `verify_with_backend()` for passwords which are not valid UTF-8.
+    pub(crate) fn verify_bytes_with_backend(&self, password: &[u8], backend: &dyn HashBackend)
+        -> Result<(), CheckError>
+    {
+        check_version(self.format, self.version);
+        if self.v2.final_rounds.is_some() { Err(CheckError::Unrepresentable)?; }
+        let HashParts { ref params, ref salt, ref hash } = self.parts;
+        let mut digest = [0u8; 32];
+        let password = self.v2.prehash(password, &mut digest);
+        let salt = self.v2.scrypt_salt(params, salt, hash.len(), &[]);
+        verify_backend(password, params, &salt, hash, backend)
+    }
+
+    /// `verify()` for passwords which are not valid UTF-8.
+    pub(crate) fn verify_bytes(&self, password: &[u8]) -> Result<(), CheckError> {
+        let mut output = vec![0u8; self.parts.hash.len()];
+        self.verify_with(password, &mut output, &mut Scratch::default())
+    }
+
+    /// `verify_bytes()` with `output` of the length of the hash as scratch
+    /// space and the buffers of `scratch` for scrypt.
+    pub(crate) fn verify_with(&self, password: &[u8], output: &mut [u8], scratch: &mut Scratch)
+        -> Result<(), CheckError>
+    {
+        check_version(self.format, self.version);
+        let HashParts { ref params, ref salt, ref hash } = self.parts;
+        self.v2.verify_with(password, params, salt, hash, &[], output, scratch)
+    }
+
+    /// Encode the hash in a compact binary form, for storage which is not
+    /// textual. Integers are little-endian. Only the fields of the table
+    /// are stored, none of the extensions of version 2 strings, e.g. the
+    /// creation day or the final PBKDF2 iterations.
+    ///
+    /// | Length | Content                                          |
+    /// |--------|--------------------------------------------------|
+    /// | 1      | format, in the order of the `Format` variants    |
+    /// | 1      | rscrypt version, `0` for the other formats       |
+    /// | 1      | `log_n`                                          |
+    /// | 4      | `r`                                              |
+    /// | 4      | `p`                                              |
+    /// | 4      | salt length                                      |
+    /// | *      | salt                                             |
+    /// | 4      | hash length                                      |
+    /// | *      | hash                                             |
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let HashParts { ref params, ref salt, ref hash } = self.parts;
+        let mut bytes = vec![0u8; 19 + salt.len() + hash.len()];
+        bytes[0] = FORMAT_IDS.iter().position(|&f| f == self.format)
+            .expect("all formats have an identifier") as u8;
+        bytes[1] = self.version.unwrap_or(0);
+        bytes[2] = params.log_n;
+        LittleEndian::write_u32(&mut bytes[3..7], params.r);
+        LittleEndian::write_u32(&mut bytes[7..11], params.p);
+        LittleEndian::write_u32(&mut bytes[11..15], salt.len() as u32);
+        bytes[15..15 + salt.len()].copy_from_slice(salt);
+        let rest = &mut bytes[15 + salt.len()..];
+        LittleEndian::write_u32(&mut rest[..4], hash.len() as u32);
+        rest[4..].copy_from_slice(hash);
+        bytes
+    }
+
+    /// Decode the binary form of `to_bytes()`.
+    ///
+    /// The result is validated like the strings accepted by `parse()`: it
+    /// must be representable in its format and have a non-empty hash.
+    ///
+    /// # Return
+    /// `Err(CheckError::InvalidFormat)` if `bytes` is malformed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<ScryptHash, CheckError> {
+        if bytes.len() < 15 { Err(CheckError::InvalidFormat)?; }
+        let format = *FORMAT_IDS.get(bytes[0] as usize)
+            .ok_or(CheckError::InvalidFormat)?;
+        let params = Params::new(
+            bytes[2],
+            LittleEndian::read_u32(&bytes[3..7]),
+            LittleEndian::read_u32(&bytes[7..11]),
+        ).map_err(|_| CheckError::InvalidFormat)?;
+        let (salt, rest) = split_field(&bytes[11..])?;
+        let (hash, rest) = split_field(rest)?;
+        if !rest.is_empty() || hash.is_empty() { Err(CheckError::InvalidFormat)?; }
+
+        let version = match (format, bytes[1]) {
+            (Format::Rscrypt, 0) if params.r < 256 && params.p < 256 => Some(0),
+            (Format::Rscrypt, 1) => Some(1),
+            (Format::Rscrypt, 2) if rscrypt_v2_supports(salt, hash) => Some(2),
+            (Format::Rscrypt, _) => Err(CheckError::InvalidFormat)?,
+            (_, 0) => None,
+            _ => Err(CheckError::InvalidFormat)?,
+        };
+
+        let parts = HashParts { params, salt: salt.to_vec(), hash: hash.to_vec() };
+        if !format.supports(&parts) { Err(CheckError::InvalidFormat)?; }
+        Ok(ScryptHash { format, version, parts, v2: V2Options::default() })
+    }
+
+    /// Length of the record of `to_record()` without salt and hash.
+    pub const RECORD_OVERHEAD: usize = 14;
+
+    /// Length of the record of a hash with a salt of `salt_len` and a hash
+    /// of `dk_len` bytes, e.g. to size storage before hashing.
+    pub const fn record_len(salt_len: usize, dk_len: usize) -> usize {
+        ScryptHash::RECORD_OVERHEAD + salt_len + dk_len
+    }
+
+    /// Encode parameters, salt and hash in a binary record, which is smaller
+    /// than `to_bytes()` and the strings, e.g. for fixed-size slots of
+    /// flash. Integers are little-endian.
+    ///
+    /// | Length | Content                   |
+    /// |--------|---------------------------|
+    /// | 2      | magic, `sR`               |
+    /// | 1      | version, `0`              |
+    /// | 1      | `log_n`                   |
+    /// | 4      | `r`                       |
+    /// | 4      | `p`                       |
+    /// | 1      | salt length               |
+    /// | *      | salt                      |
+    /// | 1      | hash length               |
+    /// | *      | hash                      |
+    ///
+    /// The format of the string is not stored, `from_record()` returns an
+    /// rscrypt hash.
+    ///
+    /// # Return
+    /// `Err(CheckError::Unrepresentable)` if salt or hash are longer than
+    /// 255 bytes or the hash has final PBKDF2 iterations, a threshold of
+    /// pre-hashing or a bound header.
+    pub fn to_record(&self) -> Result<Vec<u8>, CheckError> {
+        let HashParts { ref params, ref salt, ref hash } = self.parts;
+        if salt.len() > 255 || hash.len() > 255 || self.v2.changes_hash() {
+            Err(CheckError::Unrepresentable)?;
+        }
+        let mut record = Vec::with_capacity(ScryptHash::record_len(salt.len(), hash.len()));
+        record.extend_from_slice(&RECORD_MAGIC);
+        record.push(0);
+        record.push(params.log_n);
+        let mut rp = [0u8; 8];
+        LittleEndian::write_u32_into(&[params.r, params.p], &mut rp);
+        record.extend_from_slice(&rp);
+        record.push(salt.len() as u8);
+        record.extend_from_slice(salt);
+        record.push(hash.len() as u8);
+        record.extend_from_slice(hash);
+        Ok(record)
+    }
+
+    /// Decode a record of `to_record()` into an rscrypt hash, which
+    /// `to_string()` encodes like `ScryptHash::generate()`.
+    ///
+    /// # Return
+    /// `Err(CheckError::UnsupportedFormat)` if `record` does not start with
+    /// the magic, `Err(CheckError::UnsupportedVersion)` for versions other
+    /// than 0 and `Err(CheckError::InvalidFormat)` if it is truncated, has
+    /// trailing bytes, invalid parameters or an empty hash.
+    pub fn from_record(record: &[u8]) -> Result<ScryptHash, CheckError> {
+        if record.len() < 2 || record[..2] != RECORD_MAGIC {
+            Err(CheckError::UnsupportedFormat)?;
+        }
+        if record.len() < 3 { Err(CheckError::InvalidFormat)?; }
+        if record[2] != 0 { Err(CheckError::UnsupportedVersion)?; }
+        if record.len() < ScryptHash::RECORD_OVERHEAD { Err(CheckError::InvalidFormat)?; }
+        let params = Params::new(
+            record[3],
+            LittleEndian::read_u32(&record[4..8]),
+            LittleEndian::read_u32(&record[8..12]),
+        ).map_err(|_| CheckError::InvalidFormat)?;
+        let (salt, rest) = split_short_field(&record[12..])?;
+        let (hash, rest) = split_short_field(rest)?;
+        if !rest.is_empty() || hash.is_empty() { Err(CheckError::InvalidFormat)?; }
+
+        let version = if params.r < 256 && params.p < 256 { 0 } else { 1 };
+        let parts = HashParts { params, salt: salt.to_vec(), hash: hash.to_vec() };
+        Ok(ScryptHash {
+            format: Format::Rscrypt, version: Some(version), parts, v2: V2Options::default(),
+        })
+    }
+}
+
+/// The version of `format` which `Display` encodes `params` in.
+fn version_of(format: Format, params: &Params) -> Option<u8> {
+    match format {
+        Format::Rscrypt if params.r < 256 && params.p < 256 => Some(0),
+        Format::Rscrypt => Some(1),
+        _ => None,
+    }
+}
+
+/// The magic of `ScryptHash::to_record()`.
+const RECORD_MAGIC: [u8; 2] = *b"sR";
+
+/// Split a field with a 1-byte length prefix off `bytes`.
+fn split_short_field(bytes: &[u8]) -> Result<(&[u8], &[u8]), CheckError> {
+    let (&len, rest) = bytes.split_first().ok_or(CheckError::InvalidFormat)?;
+    if rest.len() < len as usize { Err(CheckError::InvalidFormat)?; }
+    Ok(rest.split_at(len as usize))
+}
+
+/// Split a field with a 4-byte length prefix off `bytes`.
+fn split_field(bytes: &[u8]) -> Result<(&[u8], &[u8]), CheckError> {
+    if bytes.len() < 4 { Err(CheckError::InvalidFormat)?; }
+    let len = LittleEndian::read_u32(&bytes[..4]) as usize;
+    if bytes.len() - 4 < len { Err(CheckError::InvalidFormat)?; }
+    Ok(bytes[4..].split_at(len))
+}
+
+/// A parsed rscrypt or PHC string which borrows the input and stores salt
+/// and hash inline, so parsing does not allocate.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct ScryptHashRef<'a> {
+    hashed_value: &'a str,
+    format: Format,
+    version: Option<u8>,
+    params: Params,
+    // bytes past the lengths are always zero, the derived comparison relies
+    // on it
+    salt: [u8; INLINE_LEN],
+    salt_len: usize,
+    hash: [u8; INLINE_LEN],
+    hash_len: usize,
+    v2: V2Options,
+}
+
+impl<'a> ScryptHashRef<'a> {
+    /// Longest salt and hash supported.
+    pub const MAX_LEN: usize = INLINE_LEN;
+
+    /// Parse an rscrypt or PHC string without allocating.
+    ///
+    /// # Return
+    /// `Err(CheckError::UnsupportedFormat)` for strings of other formats,
+    /// `Err(CheckError::InvalidFormat)` if `hashed_value` is malformed and
+    /// `Err(CheckError::Unrepresentable)` if salt or hash are longer than
+    /// `MAX_LEN` bytes.
+    pub fn parse(hashed_value: &'a str) -> Result<ScryptHashRef<'a>, CheckError> {
+        let format = Format::detect(hashed_value);
+        let mut v2 = V2Options::default();
+        let (version, params, salt, hash) = match format {
+            Some(Format::Rscrypt) => {
+                let fields = split_rscrypt(hashed_value)?;
+                if let Some(id) = fields.pepper { Err(CheckError::NeedsPepper(id))?; }
+                if fields.associated { Err(CheckError::NeedsAssociatedData)?; }
+                if let Some(id) = fields.client_scheme {
+                    Err(CheckError::NeedsClientScheme(id))?;
+                }
+                v2 = V2Options::of(&fields);
+                let decode = |field: &str, output: &mut [u8]| {
+                    ct_base64::decode_slice(field, fields.url_safe, output)
+                };
+                (Some(fields.version), fields.params,
+                    decode_inline(fields.salt, decode)?,
+                    decode_inline(fields.hash, decode)?)
+            }
+            Some(Format::Phc) => {
+                let (params, salt, hash) = phc::split(hashed_value)?;
+                let hash = decode_inline(hash, phc::decode_b64_slice)?;
+                if hash.1 == 0 { Err(CheckError::InvalidFormat)?; }
+                (None, params, decode_inline(salt, phc::decode_b64_slice)?, hash)
+            }
+            _ => Err(CheckError::UnsupportedFormat)?,
+        };
+        Ok(ScryptHashRef {
+            hashed_value,
+            format: format.expect("only matched formats are parsed"),
+            version,
+            params,
+            salt: salt.0,
+            salt_len: salt.1,
+            hash: hash.0,
+            hash_len: hash.1,
+            v2,
+        })
+    }
+
+    /// The parsed string.
+    pub fn as_str(&self) -> &'a str { self.hashed_value }
+
+    /// The format of the hash string, `Format::Rscrypt` or `Format::Phc`.
+    pub fn format(&self) -> Format { self.format }
+
+    /// The version of rscrypt strings, see `ScryptHash::format_version()`.
+    pub fn format_version(&self) -> Option<u8> { self.version }
+
+    /// The scrypt parameters.
+    pub fn params(&self) -> Params { self.params }
+
+    /// The salt as passed to scrypt.
+    pub fn salt(&self) -> &[u8] { &self.salt[..self.salt_len] }
+
+    /// The stored scrypt output.
+    pub fn hash_bytes(&self) -> &[u8] { &self.hash[..self.hash_len] }
+
+    /// The day the hash was created on, see `ScryptHash::created_at()`.
+    pub fn created_at(&self) -> Option<u32> { self.v2.created_at }
+
+    /// The iterations of the final PBKDF2, see `ScryptHash::final_rounds()`.
+    pub fn final_rounds(&self) -> Option<NonZeroU32> { self.v2.final_rounds }
+
+    /// The threshold of pre-hashing, see `ScryptHash::prehash_above()`.
+    pub fn prehash_above(&self) -> Option<u16> { self.v2.prehash_above }
+
+    /// The `Debug` output with the salt and hash bytes, see
+    /// `ScryptHash::reveal_debug()`.
+    pub fn reveal_debug(&self) -> String {
+        format!("ScryptHashRef {{ format: {:?}, version: {:?}, params: {:?}, salt: {:?}, hash: {:?} }}",
+            self.format, self.version, self.params, self.salt(), self.hash_bytes())
+    }
+
+    /// Check if `password` hashes to the stored value, like
+    /// `ScryptHash::verify()`.
+    pub fn verify(&self, password: &str) -> Result<(), CheckError> {
+        check_version(self.format, self.version);
+        let mut output = [0u8; INLINE_LEN];
+        self.v2.verify_with(
+            password.as_bytes(), &self.params, self.salt(), self.hash_bytes(), &[],
+            &mut output[..self.hash_len], &mut Scratch::default(),
+        )
+    }
+}
+
+impl<'a> fmt::Debug for ScryptHashRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScryptHashRef")
+            .field("format", &self.format)
+            .field("version", &self.version)
+            .field("params", &self.params)
+            .field("salt", &Fingerprint(self.salt()))
+            .field("hash", &Redacted(self.hash_len))
+            .finish()
+    }
+}
+
+impl From<ScryptHash> for HashParts {
+    fn from(hash: ScryptHash) -> HashParts {
+        hash.parts
+    }
+}
+
+impl<'a> From<ScryptHashRef<'a>> for ScryptHash {
+    fn from(hash: ScryptHashRef<'a>) -> ScryptHash {
+        ScryptHash {
+            format: hash.format,
+            version: hash.version,
+            parts: HashParts {
+                params: hash.params,
+                salt: hash.salt().to_vec(),
+                hash: hash.hash_bytes().to_vec(),
+            },
+            v2: hash.v2,
+        }
+    }
+}
+
+impl fmt::Display for ScryptHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // everything `parse` accepts can be encoded again, an opt-in to
+        // rscrypt version 2 is kept
+        if self.version == Some(2) {
+            let HashParts { ref params, ref salt, ref hash } = self.parts;
+            return f.write_str(&self.v2.encode(params, salt, hash));
+        }
+        f.write_str(&self.format.encode(&self.parts))
+    }
+}
+
+impl fmt::Debug for ScryptHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScryptHash")
+            .field("format", &self.format)
+            .field("version", &self.version)
+            .field("params", &self.parts.params)
+            .field("salt", &Fingerprint(&self.parts.salt))
+            .field("hash", &Redacted(self.parts.hash.len()))
+            .finish()
+    }
+}
+
+impl FromStr for ScryptHash {
+    type Err = CheckError;
+
+    fn from_str(s: &str) -> Result<ScryptHash, CheckError> {
+        ScryptHash::parse(s)
+    }
+}
+
+/// Decode a base64 field with `decode` into an inline buffer.
+fn decode_inline<F>(field: &str, decode: F)
+    -> Result<([u8; INLINE_LEN], usize), CheckError>
+    where F: Fn(&str, &mut [u8]) -> Result<usize, CheckError>
+{
+    if field.len() > MAX_FIELD_LEN { Err(CheckError::Unrepresentable)?; }
+    // `decode` needs room for three bytes per four characters
+    let mut buf = [0u8; MAX_FIELD_LEN / 4 * 3];
+    let len = decode(field, &mut buf)?;
+    if len > INLINE_LEN { Err(CheckError::Unrepresentable)?; }
+
+    let mut inline = [0u8; INLINE_LEN];
+    inline[..len].copy_from_slice(&buf[..len]);
+    Ok((inline, len))
+}
@@ -0,0 +1,88 @@
+//! Hashing behind a trait object, with a fast double for tests.
+use rand_core::RngCore;
+
+use config::SimpleConfig;
+use errors::{CheckError, HashError};
+use params::Params;
+use {check_limited, constant_time_eq, hex, os_rng, scrypt};
+
+/// Hashing and verification of passwords, e.g. for services which inject
+/// the hasher, so their tests can use `InsecureFastHasher`.
+///
+/// The trait is object-safe, `Box<dyn PasswordHasher>` and
+/// `Arc<dyn PasswordHasher>` can be shared by the threads of a server.
+pub trait PasswordHasher: Send + Sync {
+    /// Hash `password` under a new random salt.
+    fn hash(&self, password: &[u8]) -> Result<String, HashError>;
+
+    /// Check if `password` hashes to `stored`.
+    ///
+    /// # Return
+    /// `Err(CheckError::HashMismatch)` if it does not, the other errors if
+    /// `stored` can not be verified.
+    fn verify(&self, password: &[u8], stored: &str) -> Result<(), CheckError>;
+}
+
+/// Hashes like `hash_with` with a salt from `OsRng` and verifies like
+/// `scrypt_check`, which accepts every format, not only this configuration.
+/// Both apply the limit of `max_password_len()`.
+impl PasswordHasher for SimpleConfig {
+    fn hash(&self, password: &[u8]) -> Result<String, HashError> {
+        self.hash_bytes_with_rng(password, &mut os_rng())
+    }
+
+    fn verify(&self, password: &[u8], stored: &str) -> Result<(), CheckError> {
+        check_limited(password, stored, self.max_password_len()).map(|_| ())
+    }
+}
+
+/// Prefix of the strings of `InsecureFastHasher`.
+const INSECURE_PREFIX: &str = "$insecure$";
+
+/// **INSECURE, FOR TESTS ONLY.** A `PasswordHasher` which computes scrypt
+/// with `log_n = 1`, `r = 1` and `p = 1`, so that test suites creating many
+/// users run fast.
+///
+/// Its hashes can be brute-forced almost at the speed of SHA-256. Never use
+/// it in production, not even behind a flag. To keep its strings from being
+/// mistaken for real hashes they are `$insecure$<hex salt>$<hex hash>`,
+/// which `scrypt_check`, `Verifier` and `SimpleConfig` reject as malformed
+/// and `ScryptHash::parse` as of an unsupported format. This hasher only
+/// verifies its own strings.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct InsecureFastHasher;
+
+impl InsecureFastHasher {
+    fn compute(password: &[u8], salt: &[u8]) -> [u8; 32] {
+        let params = Params::new(1, 1, 1).expect("the minimal parameters are valid");
+        let mut hash = [0u8; 32];
+        scrypt(password, salt, &params, &mut hash)
+            .expect("32 bytes always satisfy output length requirements");
+        hash
+    }
+}
+
+impl PasswordHasher for InsecureFastHasher {
+    fn hash(&self, password: &[u8]) -> Result<String, HashError> {
+        let mut salt = [0u8; 16];
+        os_rng().try_fill_bytes(&mut salt)?;
+        let hash = InsecureFastHasher::compute(password, &salt);
+        Ok(format!("{}{}${}", INSECURE_PREFIX, hex::encode(&salt), hex::encode(&hash)))
+    }
+
+    fn verify(&self, password: &[u8], stored: &str) -> Result<(), CheckError> {
+        if !stored.starts_with(INSECURE_PREFIX) { Err(CheckError::UnsupportedFormat)?; }
+        let mut fields = stored[INSECURE_PREFIX.len()..].split('$');
+        let salt = fields.next().and_then(hex::decode);
+        let hash = fields.next().and_then(hex::decode);
+        let (salt, expected) = match (salt, hash, fields.next()) {
+            (Some(salt), Some(hash), None) if hash.len() == 32 => (salt, hash),
+            _ => Err(CheckError::InvalidFormat)?,
+        };
+        if constant_time_eq(&InsecureFastHasher::compute(password, &salt), &expected) {
+            Ok(())
+        } else {
+            Err(CheckError::HashMismatch)
+        }
+    }
+}
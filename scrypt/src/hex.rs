@@ -0,0 +1,33 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(all(feature="simple-verify", not(feature = "std")))]
+use alloc::string::String;
+
+/// Decode hex digits of either case, `None` for any other input.
+pub(crate) fn decode(src: &str) -> Option<Vec<u8>> {
+    fn nibble(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    if src.len() % 2 != 0 { return None; }
+    src.as_bytes().chunks(2)
+        .map(|pair| Some(nibble(pair[0])? << 4 | nibble(pair[1])?))
+        .collect()
+}
+
+/// Encode `bytes` as lowercase hex digits.
+#[cfg(feature="simple-verify")]
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    const DIGITS: &[u8] = b"0123456789abcdef";
+    let mut result = String::with_capacity(2 * bytes.len());
+    for &b in bytes {
+        result.push(DIGITS[(b >> 4) as usize] as char);
+        result.push(DIGITS[(b & 0xf) as usize] as char);
+    }
+    result
+}
@@ -0,0 +1,58 @@
+//! Hex strings, for storage which can hold neither `$` nor base64:
+//!
+//! `scrypt:ln=<log_n>,r=<r>,p=<p>:<hex(salt)>:<hex(hash)>`
+//!
+//! All three parameters are required, in this order. Salt and hash are
+//! written as lowercase hex, either case is accepted. Their lengths are
+//! limited to those `scrypt_simple` can produce: `MIN_SALT_LEN` to
+//! `MAX_SALT_LEN` bytes of salt and `MIN_DK_LEN` to `MAX_DK_LEN` bytes of
+//! hash.
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use errors::CheckError;
+use params::Params;
+use phc::decode_decimal;
+use {hex, MAX_DK_LEN, MAX_SALT_LEN, MIN_DK_LEN, MIN_SALT_LEN};
+
+/// Prefix identifying hex strings.
+pub(crate) const PREFIX: &str = "scrypt:ln=";
+
+/// Check if the format can store a salt and hash of these lengths.
+pub(crate) fn supports(salt: &[u8], hash: &[u8]) -> bool {
+    (MIN_SALT_LEN..=MAX_SALT_LEN).contains(&salt.len())
+        && (MIN_DK_LEN..=MAX_DK_LEN).contains(&hash.len())
+}
+
+/// Encode a hex string.
+pub(crate) fn encode(params: &Params, salt: &[u8], hash: &[u8]) -> String {
+    format!(
+        "scrypt:ln={},r={},p={}:{}:{}",
+        params.log_n, params.r, params.p, hex::encode(salt), hex::encode(hash),
+    )
+}
+
+/// Decode a hex string into parameters, salt and hash.
+pub(crate) fn decode(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    if !hashed_value.starts_with(PREFIX) { Err(CheckError::InvalidFormat)?; }
+    let fields: Vec<&str> = hashed_value[PREFIX.len()..].split(':').collect();
+    if fields.len() != 3 { Err(CheckError::InvalidFormat)?; }
+
+    let params: Vec<&str> = fields[0].split(',').collect();
+    if params.len() != 3 || !params[1].starts_with("r=") || !params[2].starts_with("p=") {
+        Err(CheckError::InvalidFormat)?;
+    }
+    let params = Params::new(
+        decode_decimal(params[0])?,
+        decode_decimal(&params[1][2..])?,
+        decode_decimal(&params[2][2..])?,
+    ).map_err(|_| CheckError::InvalidFormat)?;
+
+    let salt = hex::decode(fields[1]).ok_or(CheckError::InvalidFormat)?;
+    let hash = hex::decode(fields[2]).ok_or(CheckError::InvalidFormat)?;
+    if !supports(&salt, &hash) { Err(CheckError::InvalidFormat)?; }
+
+    Ok((params, salt, hash))
+}
@@ -0,0 +1,95 @@
+//! scrypt in two phases: the expensive mixing once, the final PBKDF2 for
+//! each key derived from it.
+use core::fmt;
+use core::mem;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use sha2::Sha256;
+
+use errors::InvalidOutputLen;
+use {check_output_len, context_salt, mix, zero, Params, Scratch};
+
+/// The mixed blocks of scrypt for a password and a salt, from which keys of
+/// any length are derived without repeating the ROMix.
+///
+/// `expand()` is the final PBKDF2-HMAC-SHA256 of scrypt with one iteration,
+/// whose output is a sequence of 32 byte blocks `HMAC(password, B || i)`
+/// for `i = 1, 2, ...`. Outputs of different lengths are therefore prefixes
+/// of each other: the 32 byte key is the first half of the 64 byte key,
+/// and expanding 32 bytes twice gives the same key twice, not the 64 byte
+/// key. Keys for different purposes need different salts, or a split of
+/// one longer output.
+///
+/// The blocks are as secret as the password, they are overwritten with
+/// zeros when the intermediate is dropped and `Debug` does not print them.
+///
+/// ```
+/// use scrypt::{scrypt, ScryptIntermediate, Params};
+///
+/// let params = Params::new(10, 8, 1).unwrap();
+/// let intermediate = ScryptIntermediate::compute(b"password", b"salt", &params);
+/// let mut key = [0u8; 64];
+/// intermediate.expand(b"password", &mut key).unwrap();
+///
+/// let mut expected = [0u8; 64];
+/// scrypt(b"password", b"salt", &params, &mut expected).unwrap();
+/// assert_eq!(&key[..], &expected[..]);
+/// ```
+pub struct ScryptIntermediate {
+    b: Vec<u8>,
+    params: Params,
+}
+
+impl ScryptIntermediate {
+    /// Compute the first PBKDF2 and the ROMix of scrypt, the expensive part.
+    ///
+    /// # Arguments
+    /// - `password` - The password to process as a byte vector
+    /// - `salt` - The salt value to use as a byte vector
+    /// - `params` - The Params to use
+    pub fn compute(password: &[u8], salt: &[u8], params: &Params) -> ScryptIntermediate {
+        let mut scratch = Scratch::default();
+        mix(password, salt, params, &mut scratch);
+        ScryptIntermediate { b: mem::take(&mut scratch.b), params: *params }
+    }
+
+    /// `compute()` for the keys of `scrypt_ctx()` with `context`.
+    pub fn compute_ctx(password: &[u8], salt: &[u8], context: &[u8], params: &Params)
+        -> ScryptIntermediate
+    {
+        ScryptIntermediate::compute(password, &context_salt(context, salt), params)
+    }
+
+    /// Derive a key, the final PBKDF2 of scrypt. `output` receives the same
+    /// bytes as from `scrypt()` for the password, salt and parameters of
+    /// `compute()`, if `password` is the password of `compute()`.
+    ///
+    /// # Return
+    /// `Err(InvalidOutputLen)` under the conditions of `scrypt()`.
+    pub fn expand(&self, password: &[u8], output: &mut [u8]) -> Result<(), InvalidOutputLen> {
+        check_output_len(output)?;
+        pbkdf2::<Hmac<Sha256>>(password, &self.b, 1, output);
+        Ok(())
+    }
+
+    /// The parameters of `compute()`.
+    pub fn params(&self) -> Params { self.params }
+}
+
+impl Drop for ScryptIntermediate {
+    fn drop(&mut self) {
+        zero(&mut self.b);
+    }
+}
+
+impl fmt::Debug for ScryptIntermediate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScryptIntermediate")
+            .field("b", &format_args!("<{} bytes, redacted>", self.b.len()))
+            .field("params", &self.params)
+            .finish()
+    }
+}
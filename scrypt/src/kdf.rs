@@ -0,0 +1,173 @@
+//! The binary header of the reference `scrypt` utility, also produced by
+//! the `kdf()` of the Node `scrypt-kdf` package.
+//!
+//! | Offset | Length | Content                                         |
+//! |--------|--------|-------------------------------------------------|
+//! | 0      | 6      | `scrypt`                                        |
+//! | 6      | 1      | version, always `0`                             |
+//! | 7      | 1      | `log_n`                                         |
+//! | 8      | 4      | `r`, big-endian                                 |
+//! | 12     | 4      | `p`, big-endian                                 |
+//! | 16     | 32     | salt                                            |
+//! | 48     | 16     | first 16 bytes of `SHA256(bytes[0..48])`        |
+//! | 64     | 32     | `HMAC-SHA256(bytes[0..64])`                     |
+//!
+//! The HMAC is keyed with the second half of a 64-byte scrypt output, so
+//! the header checksum can be validated without the password.
+use core::fmt;
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use byteorder::{BigEndian, ByteOrder};
+use hmac::{Hmac, Mac};
+#[cfg(feature="simple")]
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+use errors::CheckError;
+#[cfg(feature="simple")]
+use errors::HashError;
+#[cfg(feature="simple")]
+use os_rng;
+use params::Params;
+use redact::{Fingerprint, Redacted};
+use scrypt;
+
+/// Length of a complete record.
+pub const LEN: usize = 96;
+
+const MAGIC: &[u8; 6] = b"scrypt";
+const VERSION: u8 = 0;
+
+/// A parsed record with a valid header checksum. `Debug` prints a
+/// fingerprint of the salt and only the length of the HMAC.
+#[derive(Clone, Eq, PartialEq)]
+pub struct KdfRecord {
+    params: Params,
+    salt: [u8; 32],
+    hmac: [u8; 32],
+    header: [u8; 64],
+}
+
+impl KdfRecord {
+    /// The scrypt parameters of the record.
+    pub fn params(&self) -> Params { self.params }
+
+    /// The 256-bit salt of the record.
+    pub fn salt(&self) -> &[u8; 32] { &self.salt }
+
+    /// Check if `password` produced this record.
+    ///
+    /// # Return
+    /// `Ok(())` if the HMAC matches and `Err(CheckError::HashMismatch)`
+    /// otherwise.
+    pub fn verify(&self, password: &[u8]) -> Result<(), CheckError> {
+        let mut mac = header_mac(password, &self.params, &self.salt);
+        mac.input(&self.header);
+        // `verify` compares in constant time
+        mac.verify(&self.hmac).map_err(|_| CheckError::HashMismatch)
+    }
+
+    /// The `Debug` output with the salt and HMAC bytes, see
+    /// `ScryptHash::reveal_debug()`.
+    pub fn reveal_debug(&self) -> String {
+        format!("KdfRecord {{ params: {:?}, salt: {:?}, hmac: {:?} }}",
+            self.params, self.salt, self.hmac)
+    }
+}
+
+impl fmt::Debug for KdfRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KdfRecord")
+            .field("params", &self.params)
+            .field("salt", &Fingerprint(&self.salt))
+            .field("hmac", &Redacted(self.hmac.len()))
+            .finish()
+    }
+}
+
+/// Parse a record and validate its header checksum.
+///
+/// # Return
+/// `Err(CheckError::InvalidChecksum)` if the header checksum does not match
+/// and `Err(CheckError::InvalidFormat)` if `bytes` is not a version 0 record
+/// with valid parameters.
+pub fn parse(bytes: &[u8]) -> Result<KdfRecord, CheckError> {
+    if bytes.len() != LEN || &bytes[..6] != MAGIC || bytes[6] != VERSION {
+        Err(CheckError::InvalidFormat)?;
+    }
+    // like the reference implementation, check the checksum before the
+    // parameters. It is not secret, no need for a constant time comparison.
+    if Sha256::digest(&bytes[..48])[..16] != bytes[48..64] {
+        Err(CheckError::InvalidChecksum)?;
+    }
+
+    let log_n = bytes[7];
+    let r = BigEndian::read_u32(&bytes[8..12]);
+    let p = BigEndian::read_u32(&bytes[12..16]);
+    if log_n == 0 { Err(CheckError::InvalidFormat)?; }
+    let params = Params::new(log_n, r, p)
+        .map_err(|_| CheckError::InvalidFormat)?;
+
+    let mut record = KdfRecord {
+        params,
+        salt: [0u8; 32],
+        hmac: [0u8; 32],
+        header: [0u8; 64],
+    };
+    record.salt.copy_from_slice(&bytes[16..48]);
+    record.hmac.copy_from_slice(&bytes[64..96]);
+    record.header.copy_from_slice(&bytes[..64]);
+    Ok(record)
+}
+
+/// Verify `password` against a record, like `scrypt-kdf`'s `verify()`.
+///
+/// The header checksum is validated before the password, so a corrupted
+/// record is reported as such instead of as a wrong password.
+pub fn verify(password: &[u8], bytes: &[u8]) -> Result<(), CheckError> {
+    parse(bytes)?.verify(password)
+}
+
+/// Create a record for `password` with a random salt drawn from `OsRng`.
+#[cfg(feature="simple")]
+pub fn kdf(password: &[u8], params: &Params)
+    -> Result<[u8; LEN], HashError>
+{
+    let mut rng = os_rng();
+    kdf_with_rng(password, params, &mut rng)
+}
+
+/// Same as `kdf`, but draws the salt from the provided `rng`.
+#[cfg(feature="simple")]
+pub fn kdf_with_rng<R: RngCore + CryptoRng>(
+    password: &[u8], params: &Params, rng: &mut R,
+) -> Result<[u8; LEN], HashError> {
+    let mut salt = [0u8; 32];
+    rng.try_fill_bytes(&mut salt)?;
+
+    let mut record = [0u8; LEN];
+    record[..6].copy_from_slice(MAGIC);
+    record[6] = VERSION;
+    record[7] = params.log_n;
+    BigEndian::write_u32(&mut record[8..12], params.r);
+    BigEndian::write_u32(&mut record[12..16], params.p);
+    record[16..48].copy_from_slice(&salt);
+    let checksum = Sha256::digest(&record[..48]);
+    record[48..64].copy_from_slice(&checksum[..16]);
+
+    let mut mac = header_mac(password, params, &salt);
+    mac.input(&record[..64]);
+    record[64..].copy_from_slice(&mac.result().code());
+    Ok(record)
+}
+
+/// HMAC keyed with the second half of the 64-byte scrypt output.
+fn header_mac(password: &[u8], params: &Params, salt: &[u8])
+    -> Hmac<Sha256>
+{
+    let mut dk = [0u8; 64];
+    scrypt(password, salt, params, &mut dk)
+        .expect("64 bytes always satisfy output length requirements");
+    Hmac::new_varkey(&dk[32..]).expect("HMAC accepts keys of any length")
+}
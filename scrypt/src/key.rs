@@ -0,0 +1,56 @@
+//! Derived keys which are overwritten with zeros when they are dropped.
+use core::fmt;
+use core::ops::Deref;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use zero;
+
+/// A key of `derive_key_and_iv()`, overwritten with zeros when it is
+/// dropped. `Debug` prints `<redacted>` instead of the key.
+#[derive(Clone)]
+pub struct DerivedKey {
+    bytes: Vec<u8>,
+}
+
+impl DerivedKey {
+    pub(crate) fn new(bytes: Vec<u8>) -> DerivedKey {
+        DerivedKey { bytes }
+    }
+
+    /// The key as bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    #[cfg(feature="keywrap")]
+    pub(crate) fn as_mut_bytes(&mut self) -> &mut [u8] {
+        &mut self.bytes
+    }
+}
+
+impl Drop for DerivedKey {
+    fn drop(&mut self) {
+        zero(&mut self.bytes);
+    }
+}
+
+impl Deref for DerivedKey {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl AsRef<[u8]> for DerivedKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl fmt::Debug for DerivedKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("DerivedKey(<redacted>)")
+    }
+}
@@ -0,0 +1,88 @@
+//! Ed25519 and X25519 keys derived from a passphrase, e.g. the "brain keys"
+//! of backup and recovery flows.
+//!
+//! **A key derived from a passphrase is only as strong as the passphrase.**
+//! Anyone who learns the salt and the public key can guess passphrases
+//! offline for as long as they like, and a recovered key can not be
+//! revoked like a leaked password hash can be replaced. scrypt makes every
+//! guess expensive, it does not add entropy: a passphrase of four words of
+//! a 7776 word list has about 52 bits, which a determined attacker can
+//! exhaust even at `log_n = 20`. Use generated passphrases of at least 128
+//! bits, e.g. ten random words, the strongest parameters the recovering
+//! device can afford, and an unique salt, e.g. the account name, so that
+//! guesses do not apply to every user at once.
+//!
+//! The seed is the 32 byte output of `scrypt_ctx(passphrase, salt,
+//! context, params)`, with the context `scrypt keygen ed25519 v1` and
+//! `scrypt keygen x25519 v1`, so a passphrase and salt give unrelated keys
+//! of the two types. The construction is fixed: the same passphrase, salt
+//! and parameters give the same key in every version of this crate.
+use core::fmt;
+use core::ops::Deref;
+
+use params::Params;
+use {scrypt_ctx, zero};
+
+const ED25519_CONTEXT: &[u8] = b"scrypt keygen ed25519 v1";
+const X25519_CONTEXT: &[u8] = b"scrypt keygen x25519 v1";
+
+/// A secret key seed, overwritten with zeros when it is dropped. `Debug`
+/// prints `<redacted>` instead of the seed.
+#[derive(Clone)]
+pub struct Seed {
+    bytes: [u8; 32],
+}
+
+impl Seed {
+    /// The seed as bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.bytes
+    }
+}
+
+impl Drop for Seed {
+    fn drop(&mut self) {
+        zero(&mut self.bytes);
+    }
+}
+
+impl Deref for Seed {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &[u8; 32] {
+        &self.bytes
+    }
+}
+
+impl AsRef<[u8]> for Seed {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl fmt::Debug for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Seed(<redacted>)")
+    }
+}
+
+/// The Ed25519 secret key of `passphrase` and `salt`, the 32 byte seed of
+/// RFC 8032 from which the signing scalar and the public key are computed,
+/// e.g. by `SigningKey::from_bytes` of `ed25519-dalek`.
+pub fn derive_ed25519_seed(passphrase: &[u8], salt: &[u8], params: &Params) -> Seed {
+    derive(passphrase, salt, ED25519_CONTEXT, params)
+}
+
+/// The X25519 secret key of `passphrase` and `salt`, the 32 byte scalar of
+/// RFC 7748, which X25519 clamps itself, e.g. for `StaticSecret::from` of
+/// `x25519-dalek`.
+pub fn derive_x25519_seed(passphrase: &[u8], salt: &[u8], params: &Params) -> Seed {
+    derive(passphrase, salt, X25519_CONTEXT, params)
+}
+
+fn derive(passphrase: &[u8], salt: &[u8], context: &[u8], params: &Params) -> Seed {
+    let mut seed = Seed { bytes: [0u8; 32] };
+    scrypt_ctx(passphrase, salt, context, params, &mut seed.bytes)
+        .expect("32 bytes always satisfy output length requirements");
+    seed
+}
@@ -0,0 +1,89 @@
+//! The `scrypt` key derivation of Ethereum keystore v3 files ("Web3 Secret
+//! Storage"):
+//!
+//! ```json
+//! "kdf": "scrypt",
+//! "kdfparams": {"dklen": 32, "n": 262144, "p": 1, "r": 8, "salt": "<hex>"}
+//! ```
+//!
+//! The first 16 bytes of the derived key decrypt the private key, the last
+//! 16 bytes are the MAC key (`keccak256(dk[16..32] ++ ciphertext)`).
+//!
+//! Parameters are validated by `Params::new`, which enforces
+//! `N < 2^(16 * r)` as required by RFC 7914. Some implementations, e.g.
+//! go-ethereum, do not, so files like the `r = 1`, `N = 2^18` test vector of
+//! the Web3 Secret Storage definition are rejected.
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use core::fmt;
+
+use errors::{InvalidParams, ParamsReason};
+use hex;
+use params::Params;
+use redact::Fingerprint;
+use scrypt;
+
+/// The `kdfparams` object of a keystore file using `"kdf": "scrypt"`.
+/// `Debug` prints a fingerprint of the salt instead of the salt.
+#[derive(Clone, Eq, PartialEq)]
+pub struct KdfParams {
+    /// Length of the derived key, always 32 for version 3 files.
+    pub dklen: u32,
+    /// The scrypt parameter `N` itself, not its log2.
+    pub n: u64,
+    /// The scrypt parameter `p`.
+    pub p: u32,
+    /// The scrypt parameter `r`.
+    pub r: u32,
+    /// The hex encoded salt.
+    pub salt: String,
+}
+
+impl KdfParams {
+    /// Validate `n`, `r` and `p`.
+    ///
+    /// # Return
+    /// `Err(InvalidParams)` if `n` is not a power of two larger than one or
+    /// the parameters are rejected by `Params::new`.
+    pub fn scrypt_params(&self) -> Result<Params, InvalidParams> {
+        if !self.n.is_power_of_two() { Err(InvalidParams::new(ParamsReason::NotPowerOfTwo))?; }
+        Params::new(self.n.trailing_zeros() as u8, self.r, self.p)
+    }
+
+    /// Decode the hex encoded salt.
+    pub fn salt_bytes(&self) -> Result<Vec<u8>, InvalidParams> {
+        hex::decode(&self.salt).ok_or(InvalidParams::new(ParamsReason::Other))
+    }
+}
+
+impl fmt::Debug for KdfParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // of the hex digits, the salt may not be valid hex
+        f.debug_struct("KdfParams")
+            .field("dklen", &self.dklen)
+            .field("n", &self.n)
+            .field("p", &self.p)
+            .field("r", &self.r)
+            .field("salt", &Fingerprint(self.salt.as_bytes()))
+            .finish()
+    }
+}
+
+/// Derive the 256-bit key of a keystore file from `password`.
+///
+/// # Return
+/// `Err(InvalidParams)` if `dklen` is not 32, the salt is not valid hex or
+/// `KdfParams::scrypt_params()` fails.
+pub fn derive_keystore_key(password: &[u8], params: &KdfParams)
+    -> Result<[u8; 32], InvalidParams>
+{
+    if params.dklen != 32 { Err(InvalidParams::new(ParamsReason::Other))?; }
+    let scrypt_params = params.scrypt_params()?;
+    let salt = params.salt_bytes()?;
+
+    let mut key = [0u8; 32];
+    scrypt(password, &salt, &scrypt_params, &mut key)
+        .expect("32 bytes always satisfy output length requirements");
+    Ok(key)
+}
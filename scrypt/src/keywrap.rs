@@ -0,0 +1,189 @@
+//! Wrapping of data keys with AES Key Wrap (RFC 3394) under a key derived
+//! from a passphrase with scrypt.
+//!
+//! | Offset | Length | Content                                             |
+//! |--------|--------|-----------------------------------------------------|
+//! | 0      | 3      | `sKW`                                               |
+//! | 3      | 1      | version, `0`                                        |
+//! | 4      | 1      | `log_n`                                             |
+//! | 5      | 4      | `r`, little-endian                                  |
+//! | 9      | 4      | `p`, little-endian                                  |
+//! | 13     | 16     | salt                                                |
+//! | 29     | *      | the data key wrapped with AES-256-KW, 8 bytes longer |
+//!
+//! The key-encryption key is the 32 byte scrypt output of the passphrase
+//! and salt. Every wrap draws a new salt, so no two wraps share a key. The
+//! integrity check of AES-KW tells a wrong passphrase or a corrupted blob,
+//! which can not be told apart, from the right one.
+//!
+//! The parameters are taken from the blob, check `WrappedKey::params()`
+//! before unwrapping blobs of untrusted origin.
+use byteorder::{ByteOrder, LittleEndian};
+use rand_core::{CryptoRng, RngCore};
+
+use aes::Aes256;
+use errors::{HashError, UnwrapError};
+use params::Params;
+use {constant_time_eq, os_rng, scrypt, zero, DerivedKey};
+
+const MAGIC: &[u8] = b"sKW";
+const VERSION: u8 = 0;
+const SALT_LEN: usize = 16;
+/// Length of the bytes before the wrapped key.
+const HEADER_LEN: usize = 13 + SALT_LEN;
+/// The initial value of RFC 3394, section 2.2.3.1.
+const IV: [u8; 8] = [0xa6; 8];
+
+/// A data key wrapped under a passphrase, see `wrap_key`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WrappedKey {
+    params: Params,
+    salt: [u8; SALT_LEN],
+    wrapped: Vec<u8>,
+}
+
+impl WrappedKey {
+    /// The parameters of the key-encryption key.
+    pub fn params(&self) -> Params { self.params }
+
+    /// The salt of the key-encryption key.
+    pub fn salt(&self) -> &[u8] { &self.salt }
+
+    /// The length of the data key.
+    pub fn key_len(&self) -> usize { self.wrapped.len() - 8 }
+
+    /// The blob of the format of the module documentation.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; HEADER_LEN + self.wrapped.len()];
+        bytes[..3].copy_from_slice(MAGIC);
+        bytes[3] = VERSION;
+        bytes[4] = self.params.log_n;
+        LittleEndian::write_u32(&mut bytes[5..9], self.params.r);
+        LittleEndian::write_u32(&mut bytes[9..13], self.params.p);
+        bytes[13..HEADER_LEN].copy_from_slice(&self.salt);
+        bytes[HEADER_LEN..].copy_from_slice(&self.wrapped);
+        bytes
+    }
+
+    /// Parse a blob of `to_bytes`.
+    ///
+    /// # Return
+    /// `Err(UnwrapError::NotWrapped)` if `bytes` does not start with the
+    /// magic, `Err(UnwrapError::UnsupportedVersion)` for versions other
+    /// than 0, `Err(UnwrapError::InvalidParams)` if `Params::new` rejects
+    /// the parameters and `Err(UnwrapError::Malformed)` if the wrapped key
+    /// is not a multiple of 8 bytes of at least 24.
+    pub fn from_bytes(bytes: &[u8]) -> Result<WrappedKey, UnwrapError> {
+        if !bytes.starts_with(MAGIC) { Err(UnwrapError::NotWrapped)?; }
+        match bytes.get(3) {
+            Some(&VERSION) => (),
+            Some(_) => Err(UnwrapError::UnsupportedVersion)?,
+            None => Err(UnwrapError::Malformed)?,
+        }
+        if bytes.len() < HEADER_LEN { Err(UnwrapError::Malformed)?; }
+        let wrapped = &bytes[HEADER_LEN..];
+        if wrapped.len() < 24 || wrapped.len() % 8 != 0 { Err(UnwrapError::Malformed)?; }
+        let params = Params::new(bytes[4], LittleEndian::read_u32(&bytes[5..9]),
+            LittleEndian::read_u32(&bytes[9..13])).map_err(|_| UnwrapError::InvalidParams)?;
+        let mut salt = [0u8; SALT_LEN];
+        salt.copy_from_slice(&bytes[13..HEADER_LEN]);
+        Ok(WrappedKey { params, salt, wrapped: wrapped.to_vec() })
+    }
+}
+
+/// Wrap `data_key` under a key derived from `passphrase` with `params` and
+/// a random salt.
+///
+/// # Return
+/// `Err(HashError::InvalidKeyLen)` if `data_key` is not a multiple of 8
+/// bytes of at least 16, as AES-KW requires, and `Err(HashError::Rng)` in
+/// the case of an unlikely `OsRng` failure.
+pub fn wrap_key(passphrase: &[u8], data_key: &[u8], params: &Params)
+    -> Result<WrappedKey, HashError>
+{
+    let mut rng = os_rng();
+    wrap_key_with_rng(passphrase, data_key, params, &mut rng)
+}
+
+/// Same as `wrap_key`, but draws the salt from the provided `rng` instead
+/// of `OsRng`.
+pub fn wrap_key_with_rng<R: RngCore + CryptoRng>(
+    passphrase: &[u8], data_key: &[u8], params: &Params, rng: &mut R,
+) -> Result<WrappedKey, HashError> {
+    if data_key.len() < 16 || data_key.len() % 8 != 0 {
+        Err(HashError::InvalidKeyLen(data_key.len()))?;
+    }
+    let mut salt = [0u8; SALT_LEN];
+    rng.try_fill_bytes(&mut salt)?;
+    let aes = kek(passphrase, &salt, params);
+
+    // RFC 3394, section 2.2.1
+    let n = data_key.len() / 8;
+    let mut wrapped = vec![0u8; 8 + data_key.len()];
+    wrapped[..8].copy_from_slice(&IV);
+    wrapped[8..].copy_from_slice(data_key);
+    let mut block = [0u8; 16];
+    for j in 0..6 {
+        for i in 1..=n {
+            block[..8].copy_from_slice(&wrapped[..8]);
+            block[8..].copy_from_slice(&wrapped[8 * i..8 * i + 8]);
+            aes.encrypt_block(&mut block);
+            let t = (n * j + i) as u64;
+            wrapped[..8].copy_from_slice(&block[..8]);
+            xor_counter(&mut wrapped[..8], t);
+            wrapped[8 * i..8 * i + 8].copy_from_slice(&block[8..]);
+        }
+    }
+    zero(&mut block);
+    Ok(WrappedKey { params: *params, salt, wrapped })
+}
+
+/// Unwrap the data key of `wrapped` with `passphrase`.
+///
+/// # Return
+/// `Err(UnwrapError::IntegrityCheckFailed)` if the passphrase is wrong or
+/// the wrapped key has been corrupted.
+pub fn unwrap_key(passphrase: &[u8], wrapped: &WrappedKey)
+    -> Result<DerivedKey, UnwrapError>
+{
+    let aes = kek(passphrase, &wrapped.salt, &wrapped.params);
+
+    // RFC 3394, section 2.2.2
+    let n = wrapped.key_len() / 8;
+    let mut a = [0u8; 8];
+    a.copy_from_slice(&wrapped.wrapped[..8]);
+    let mut key = DerivedKey::new(wrapped.wrapped[8..].to_vec());
+    let r = key.as_mut_bytes();
+    let mut block = [0u8; 16];
+    for j in (0..6).rev() {
+        for i in (1..=n).rev() {
+            let t = (n * j + i) as u64;
+            xor_counter(&mut a, t);
+            block[..8].copy_from_slice(&a);
+            block[8..].copy_from_slice(&r[8 * (i - 1)..8 * i]);
+            aes.decrypt_block(&mut block);
+            a.copy_from_slice(&block[..8]);
+            r[8 * (i - 1)..8 * i].copy_from_slice(&block[8..]);
+        }
+    }
+    zero(&mut block);
+    if !constant_time_eq(&a, &IV) { Err(UnwrapError::IntegrityCheckFailed)?; }
+    Ok(key)
+}
+
+/// The AES-256 key-encryption key of `passphrase` and `salt`.
+fn kek(passphrase: &[u8], salt: &[u8], params: &Params) -> Aes256 {
+    let mut key = [0u8; 32];
+    scrypt(passphrase, salt, params, &mut key)
+        .expect("32 bytes always satisfy output length requirements");
+    let aes = Aes256::new(&key);
+    zero(&mut key);
+    aes
+}
+
+/// `a ^= t` for the big-endian 64 bit counter `t`.
+fn xor_counter(a: &mut [u8], t: u64) {
+    for (a, t) in a.iter_mut().zip(t.to_be_bytes().iter()) {
+        *a ^= t;
+    }
+}
@@ -0,0 +1,57 @@
+//! The format of `com.lambdaworks.crypto.SCryptUtil`:
+//!
+//! `$s0$<hex(log_n << 16 | r << 8 | p)>$<base64(salt)>$<base64(hash)>`
+//!
+//! `r` and `p` have to fit in a single byte, salt and hash use the standard
+//! base64 alphabet with padding. The hash is always 256 bits.
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use base64ct::{Base64, Encoding};
+
+use ct_base64;
+use errors::CheckError;
+use params::Params;
+
+/// Prefix identifying Lambdaworks strings.
+pub(crate) const PREFIX: &str = "$s0$";
+
+/// Check if `params` can be represented in the packed parameter field.
+pub(crate) fn supports(params: &Params) -> bool {
+    params.r < 256 && params.p < 256
+}
+
+/// Encode a Lambdaworks string, `params` must satisfy `supports()`.
+pub(crate) fn encode(params: &Params, salt: &[u8], hash: &[u8]) -> String {
+    let packed = (params.log_n as u32) << 16 | params.r << 8 | params.p;
+    format!("$s0${:x}${}${}", packed, Base64::encode_string(salt),
+        Base64::encode_string(hash))
+}
+
+/// Decode a Lambdaworks string into parameters, salt and hash.
+pub(crate) fn decode(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    if !hashed_value.starts_with(PREFIX) { Err(CheckError::InvalidFormat)?; }
+    let fields: Vec<&str> = hashed_value[PREFIX.len()..].split('$').collect();
+    if fields.len() != 3 { Err(CheckError::InvalidFormat)?; }
+
+    // like Java's `Long.parseLong(s, 16)`, but without a sign
+    if fields[0].is_empty() || fields[0].len() > 8
+        || !fields[0].bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        Err(CheckError::InvalidFormat)?;
+    }
+    let packed = u32::from_str_radix(fields[0], 16)
+        .map_err(|_| CheckError::InvalidFormat)?;
+    if packed >> 16 > 0xff { Err(CheckError::InvalidFormat)?; }
+    let params = Params::new(
+        (packed >> 16) as u8, packed >> 8 & 0xff, packed & 0xff,
+    ).map_err(|_| CheckError::InvalidFormat)?;
+
+    let salt = ct_base64::decode(fields[1], false)?;
+    let hash = ct_base64::decode(fields[2], false)?;
+    if hash.len() != 32 { Err(CheckError::InvalidFormat)?; }
+
+    Ok((params, salt, hash))
+}
@@ -0,0 +1,121 @@
+//! Legacy password digests wrapped by `wrap_legacy_hash`.
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use byteorder::{ByteOrder, LittleEndian};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+/// The digest of a legacy hash which was wrapped by `wrap_legacy_hash`,
+/// recorded in the wrapped string.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum LegacyDigest {
+    /// The password itself, e.g. of a table of plain text passwords.
+    Plain,
+    /// The 16 byte MD5 digest of the password.
+    Md5,
+    /// The 20 byte SHA-1 digest of the password.
+    Sha1,
+    /// The 32 byte SHA-256 digest of the password.
+    Sha256,
+}
+
+impl LegacyDigest {
+    /// The name of the digest in wrapped strings.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            LegacyDigest::Plain => "plain",
+            LegacyDigest::Md5 => "md5",
+            LegacyDigest::Sha1 => "sha1",
+            LegacyDigest::Sha256 => "sha256",
+        }
+    }
+
+    /// The digest of `name`, `None` if it is unknown.
+    pub fn from_name(name: &str) -> Option<LegacyDigest> {
+        match name {
+            "plain" => Some(LegacyDigest::Plain),
+            "md5" => Some(LegacyDigest::Md5),
+            "sha1" => Some(LegacyDigest::Sha1),
+            "sha256" => Some(LegacyDigest::Sha256),
+            _ => None,
+        }
+    }
+
+    /// The raw digest of `password`, as stored by the legacy system.
+    pub fn digest(&self, password: &[u8]) -> Vec<u8> {
+        match *self {
+            LegacyDigest::Plain => password.to_vec(),
+            LegacyDigest::Md5 => md5(password).to_vec(),
+            LegacyDigest::Sha1 => Sha1::digest(password).to_vec(),
+            LegacyDigest::Sha256 => Sha256::digest(password).to_vec(),
+        }
+    }
+}
+
+/// Per-round shift amounts of MD5 (RFC 1321).
+const MD5_SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+
+/// Per-step constants of MD5, `floor(abs(sin(i + 1)) * 2^32)`.
+const MD5_K: [u32; 64] = [
+    0xd76a_a478, 0xe8c7_b756, 0x2420_70db, 0xc1bd_ceee,
+    0xf57c_0faf, 0x4787_c62a, 0xa830_4613, 0xfd46_9501,
+    0x6980_98d8, 0x8b44_f7af, 0xffff_5bb1, 0x895c_d7be,
+    0x6b90_1122, 0xfd98_7193, 0xa679_438e, 0x49b4_0821,
+    0xf61e_2562, 0xc040_b340, 0x265e_5a51, 0xe9b6_c7aa,
+    0xd62f_105d, 0x0244_1453, 0xd8a1_e681, 0xe7d3_fbc8,
+    0x21e1_cde6, 0xc337_07d6, 0xf4d5_0d87, 0x455a_14ed,
+    0xa9e3_e905, 0xfcef_a3f8, 0x676f_02d9, 0x8d2a_4c8a,
+    0xfffa_3942, 0x8771_f681, 0x6d9d_6122, 0xfde5_380c,
+    0xa4be_ea44, 0x4bde_cfa9, 0xf6bb_4b60, 0xbebf_bc70,
+    0x289b_7ec6, 0xeaa1_27fa, 0xd4ef_3085, 0x0488_1d05,
+    0xd9d4_d039, 0xe6db_99e5, 0x1fa2_7cf8, 0xc4ac_5665,
+    0xf429_2244, 0x432a_ff97, 0xab94_23a7, 0xfc93_a039,
+    0x655b_59c3, 0x8f0c_cc92, 0xffef_f47d, 0x8584_5dd1,
+    0x6fa8_7e4f, 0xfe2c_e6e0, 0xa301_4314, 0x4e08_11a1,
+    0xf753_7e82, 0xbd3a_f235, 0x2ad7_d2bb, 0xeb86_d391,
+];
+
+/// MD5 of `data`. No crate of the dependency tree provides it, and it is
+/// only needed to recompute existing digests.
+fn md5(data: &[u8]) -> [u8; 16] {
+    let mut state: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+
+    // padding: a one bit, zeros and the bit length, to a multiple of 64 bytes
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    let mut len = [0u8; 8];
+    LittleEndian::write_u64(&mut len, (data.len() as u64).wrapping_mul(8));
+    message.extend_from_slice(&len);
+
+    for chunk in message.chunks(64) {
+        let mut m = [0u32; 16];
+        LittleEndian::read_u32_into(chunk, &mut m);
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let shift = MD5_SHIFTS[(i / 16) * 4 + i % 4];
+            let rotated = a.wrapping_add(f).wrapping_add(MD5_K[i]).wrapping_add(m[g])
+                .rotate_left(shift);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(rotated);
+        }
+        for (s, v) in state.iter_mut().zip(&[a, b, c, d]) {
+            *s = s.wrapping_add(*v);
+        }
+    }
+
+    let mut result = [0u8; 16];
+    LittleEndian::write_u32_into(&state, &mut result);
+    result
+}
@@ -8,70 +8,324 @@
 //! scrypt = { version = "0.1", default-features = false }
 //! ```
 //!
+//! Without the `std` feature the crate is `no_std` and only requires `alloc`.
+//!
+//! The convenience functions are split into two features, both enabled by
+//! default:
+//! - `simple-verify` - parsing and verification of hashes, e.g.
+//!   `scrypt_check` and `Verifier`, without a random number generator
+//! - `simple` - creation of hashes, e.g. `scrypt_simple` and `SimpleConfig`,
+//!   which draw their salts from the OS random number generator, see
+//!   `entropy`
+//!
+//! `include_simple` enables both. A service which only checks passwords can
+//! depend on `scrypt` without `rand_core`:
+//! ```toml
+//! [dependencies]
+//! scrypt = { version = "0.1", default-features = false, features = ["simple-verify"] }
+//! ```
+//!
+//! `simple-verify` does not require `std` either, `scrypt_check` and the
+//! parsers work on embedded targets. `Verifier`, `verify_many` and the
+//! global observer need `std` for their clocks and threads.
+//!
+//! On `wasm32-wasi` the default features draw their salts from
+//! `random_get`. `wasm32-unknown-unknown` has no OS random number
+//! generator, there `simple` requires the `js` feature, which uses
+//! `crypto.getRandomValues` of browsers and Node.js, or a generator
+//! registered with the `custom` feature of `getrandom`. `simple-verify`
+//! compiles without either: `scrypt_check` and the other verification
+//! functions need no random numbers and work unchanged, `verify_many`
+//! should be used with `threads: 1` or `0`, which is one thread there. `Params::calibrate`
+//! needs `Instant`, use `Params::calibrate_with` with a `TimeSource` of the
+//! host, e.g. `performance.now()`.
+//!
+//! # Thread safety
+//!
+//! Every public type is `Send` and `Sync`, including the stateful ones,
+//! e.g. `Verifier`, `SimpleConfig`, `Registry`, `PepperSet` and `CredFile`,
+//! so one instance can be shared by all threads, e.g. in an `Arc`. Their
+//! methods take `&self` for hashing and verification.
+//!
+//! The only global state is the observer of `set_global_observer()` and
+//! the warnings of `observer`, behind locks which are held for a few
+//! instructions and never while hashing or calling an observer. A thread
+//! panicking, e.g. in an observer, does not poison them for the others.
+//! There are no global caches or buffer pools, every hash allocates and
+//! frees its own memory.
+//!
 //! # Usage
 //!
 //! ```
 //! extern crate scrypt;
 //!
 //! # fn main() {
-//! use scrypt::{ScryptParams, scrypt_simple, scrypt_check};
+//! # #[cfg(feature = "simple")] {
+//! use scrypt::{Params, scrypt_simple, scrypt_check};
 //!
-//! // First setup the ScryptParams arguments with:
+//! // First setup the Params arguments with:
 //! // r = 8, p = 1, n = 32768 (log2(n) = 15)
-//! let params = ScryptParams::new(15, 8, 1).unwrap();
+//! let params = Params::new(15, 8, 1).unwrap();
 //! // Hash the password for storage
 //! let hashed_password = scrypt_simple("Not so secure password", &params)
 //!     .expect("OS RNG should not fail");
 //! // Verifying a stored password
 //! assert!(scrypt_check("Not so secure password", &hashed_password).is_ok());
 //! # }
+//! # }
 //! ```
 //!
 //! # References
 //! \[1\] - [C. Percival. Stronger Key Derivation Via Sequential
 //! Memory-Hard Functions](http://www.tarsnap.com/scrypt/scrypt.pdf)
+#![cfg_attr(not(feature = "std"), no_std)]
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate core;
 extern crate sha2;
 extern crate pbkdf2;
 extern crate hmac;
 extern crate byteorder;
-extern crate byte_tools;
-#[cfg(feature="include_simple")]
-extern crate constant_time_eq;
-#[cfg(feature="include_simple")]
-extern crate base64;
-#[cfg(feature="include_simple")]
-extern crate rand;
-
-#[cfg(feature="include_simple")]
-use std::io;
-
-#[cfg(feature="include_simple")]
-use byteorder::{ByteOrder, LittleEndian};
+#[cfg(feature="simple-verify")]
+extern crate subtle;
+#[cfg(feature="simple-verify")]
+extern crate base64ct;
+#[cfg(feature="simple-verify")]
+extern crate sha1;
+#[cfg(feature="simple")]
+extern crate rand_core;
+#[cfg(feature="simple")]
+extern crate getrandom;
+#[cfg(feature="capi")]
+extern crate libc;
+#[cfg(feature="cipher")]
+extern crate generic_array;
+#[cfg(feature="parallel")]
+extern crate rayon;
+
+#[cfg(all(feature="simple-verify", feature="std"))]
+use core::cell::Cell;
+use core::num::NonZeroU32;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(all(feature="simple-verify", not(feature = "std")))]
+use alloc::string::{String, ToString};
+#[cfg(all(feature="simple-verify", feature="std"))]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature="simple-verify")]
+use base64ct::{Base64, Base64Unpadded, Base64UrlUnpadded, Encoding};
+#[cfg(feature="simple-verify")]
+use subtle::ConstantTimeEq;
+use byteorder::{BigEndian, ByteOrder};
+#[cfg(feature="simple-verify")]
+use byteorder::LittleEndian;
 use hmac::Hmac;
+#[cfg(feature="parallel")]
+use hmac::Mac;
 use pbkdf2::pbkdf2;
-use sha2::Sha256;
-#[cfg(feature="include_simple")]
-use constant_time_eq::constant_time_eq;
-// TODO: replace with rand core and seprate os-rng crate
-#[cfg(feature="include_simple")]
-use rand::{OsRng, RngCore};
+#[cfg(feature="parallel")]
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+#[cfg(feature="simple")]
+use rand_core::{CryptoRng, RngCore};
 
 mod params;
+mod calibrate;
+#[cfg(all(feature="std", not(all(target_arch = "wasm32", not(target_os = "emscripten")))))]
+mod calibration_cache;
 mod romix;
+#[cfg(feature="simple-verify")]
+mod phc;
+#[cfg(feature="simple-verify")]
+mod lambdaworks;
+#[cfg(feature="simple-verify")]
+mod django;
+#[cfg(feature="simple-verify")]
+mod werkzeug;
+#[cfg(feature="simple-verify")]
+mod scryptkdf;
+#[cfg(feature="simple-verify")]
+mod phpscrypt;
+#[cfg(feature="simple-verify")]
+mod simplescrypt;
+#[cfg(feature="simple-verify")]
+mod rubyscrypt;
+#[cfg(feature="simple-verify")]
+mod cisco;
+#[cfg(feature="simple-verify")]
+mod hex_string;
+#[cfg(feature="simple-verify")]
+mod pepper;
+#[cfg(feature="simple-verify")]
+mod prehash;
+#[cfg(feature="simple-verify")]
+mod client;
+#[cfg(feature="simple-verify")]
+mod seal;
+#[cfg(feature="simple-verify")]
+mod legacy;
+mod salt;
+mod blind;
+#[cfg(feature="simple-verify")]
+mod format;
+#[cfg(feature="simple")]
+mod config;
+#[cfg(feature="simple")]
+mod hasher;
+#[cfg(all(feature="simple-verify", feature="std"))]
+mod verifier;
+#[cfg(feature="simple-verify")]
+mod hash;
+#[cfg(all(feature="simple-verify", feature="std"))]
+mod batch;
+#[cfg(feature="simple-verify")]
+mod backend;
+#[cfg(feature="simple-verify")]
+mod ct_base64;
+#[cfg(feature="simple-verify")]
+pub mod observer;
+#[cfg(feature="simple")]
+pub mod entropy;
+/// The `$7$` crypt(3) format.
+#[cfg(feature="simple-verify")]
+pub mod crypt7;
+/// yescrypt and the `$y$` crypt(3) format.
+#[cfg(feature="yescrypt")]
+pub mod yescrypt;
+/// libsodium compatible `$7$` strings.
+#[cfg(feature="simple-verify")]
+pub mod sodium;
+/// The binary format of the reference `scrypt` utility and `scrypt-kdf`.
+#[cfg(feature="simple-verify")]
+pub mod kdf;
+/// Conversion between rscrypt and PHC strings.
+#[cfg(feature="simple-verify")]
+pub mod convert;
+/// Ethereum keystore v3 key derivation.
+#[cfg(feature="keystore")]
+pub mod keystore;
+/// BIP-38 key derivation.
+#[cfg(feature="bip38")]
+pub mod bip38;
+/// The `scrypt-params` of RFC 7914 for PBES2 encrypted PKCS#8 keys.
+#[cfg(feature="pkcs8-params")]
+pub mod pkcs8;
+/// Keys of ciphers derived into arrays of their key size.
+#[cfg(feature="cipher")]
+pub mod cipher;
+/// Hashing and verification as jobs of the blocking pool of any runtime.
+#[cfg(feature="async")]
+pub mod nonblocking;
+/// The scrypt proof of work of Litecoin.
+pub mod pow;
+mod intermediate;
+mod stepper;
+#[cfg(kani)]
+mod verification;
+mod vectored;
+mod key;
+/// The test vectors of RFC 7914, for the tests of this crate and its
+/// dependents.
+#[doc(hidden)]
+pub mod test_vectors;
+mod selftest;
+/// The pseudocode of RFC 7914, for review and differential tests.
+#[cfg(feature="reference")]
+pub mod reference;
+#[cfg(feature="capi")]
+pub mod capi;
+#[cfg(feature="simple")]
+pub mod credfile;
+#[cfg(any(feature="enc", feature="keywrap"))]
+mod aes;
+#[cfg(feature="enc")]
+pub mod enc;
+#[cfg(feature="keywrap")]
+pub mod keywrap;
+#[cfg(feature="keygen")]
+pub mod keygen;
+#[cfg(feature="experimental-chacha")]
+pub mod experimental;
+#[cfg(feature="research")]
+pub mod research;
+#[cfg(feature="test-util")]
+#[macro_use]
+pub mod testing;
+#[cfg(any(feature="simple-verify", feature="keystore"))]
+mod hex;
+#[cfg(feature="std")]
+mod password;
+#[cfg(feature="std")]
+mod env;
+#[cfg(any(feature="simple-verify", feature="keystore", feature="pkcs8-params"))]
+mod redact;
 /// Errors for `scrypt` operations.
 pub mod errors;
 
-pub use params::ScryptParams;
+pub use params::Params;
+pub use calibrate::TimeSource;
+#[cfg(all(feature="std", not(all(target_arch = "wasm32", not(target_os = "emscripten")))))]
+pub use calibrate::StdTime;
+/// The former name of `Params`.
+#[deprecated(note = "renamed to `Params`")]
+pub type ScryptParams = Params;
+pub use intermediate::ScryptIntermediate;
+pub use stepper::{Progress, ScryptStepper, StepResult};
+pub use vectored::scrypt_vectored;
+pub use key::DerivedKey;
+pub use selftest::{self_test, Backend, SelfTestError};
+#[cfg(feature="std")]
+pub use password::{Password, PasswordBuilder};
+#[cfg(feature="simple-verify")]
+pub use format::{Format, HashFormat, HashParts, Registry};
+#[cfg(feature="simple-verify")]
+pub use hash::{ScryptHash, ScryptHashRef};
+#[cfg(feature="simple-verify")]
+pub use pepper::{KeyProvider, Pepper, PepperSet, StaticKeys};
+#[cfg(feature="simple-verify")]
+pub use prehash::{LengthPolicy, DEFAULT_MAX_PASSWORD_LEN};
+#[cfg(feature="simple-verify")]
+pub use client::ClientScheme;
+#[cfg(feature="simple-verify")]
+pub use seal::RecordSealer;
+pub use salt::derive_salt;
+pub use blind::{blind_index, BLIND_INDEX_TAG};
+#[cfg(feature="simple-verify")]
+pub use legacy::LegacyDigest;
+#[cfg(feature="simple")]
+pub use config::{SimpleConfig, SimpleConfigBuilder};
+#[cfg(feature="simple")]
+pub use hasher::{InsecureFastHasher, PasswordHasher};
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub use verifier::{ParamsPolicy, Verifier, VerifierBuilder};
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub use batch::{verify_candidates, verify_many, BatchOptions};
+#[cfg(feature="simple-verify")]
+pub use backend::{HashBackend, LocalBackend};
 use errors::InvalidOutputLen;
-#[cfg(feature="include_simple")]
-use errors::CheckError;
+#[cfg(feature="low-level")]
+use errors::InvalidBufferLen;
+#[cfg(feature="simple-verify")]
+use errors::{CheckError, Field};
+#[cfg(feature="simple")]
+use errors::HashError;
+#[cfg(all(feature="simple-verify", feature="std"))]
+use errors::KeyError;
+use romix::{Block, MixingCore, Salsa8};
 
 /// The scrypt key derivation function.
 ///
+/// With the `parallel` feature, the `p` blocks of `p` above 1 are mixed on
+/// the threads of rayon, at up to `min(p, threads)` times the memory, see
+/// `Params::required_memory()`. The result is the same.
+///
 /// # Arguments
 /// - `password` - The password to process as a byte vector
 /// - `salt` - The salt value to use as a byte vector
-/// - `params` - The ScryptParams to use
+/// - `params` - The Params to use
 /// - `output` - The resulting derived key is returned in this byte vector.
 ///   **WARNING: Make sure to compare this value in constant time!**
 ///
@@ -80,41 +334,422 @@ use errors::CheckError;
 /// `output` does not satisfy the following condition:
 /// `output.len() > 0 && output.len() <= (2^32 - 1) * 32`.
 pub fn scrypt(
-    password: &[u8], salt: &[u8], params: &ScryptParams, output: &mut [u8]
+    password: &[u8], salt: &[u8], params: &Params, output: &mut [u8]
+) -> Result<(), InvalidOutputLen> {
+    scrypt_with(password, salt, params, output, &mut Scratch::default())
+}
+
+/// `scrypt_with_final_rounds` derives a key like `scrypt()`, but with `c`
+/// iterations of the final PBKDF2 instead of one, as a CPU-only cost on top
+/// of the memory of `params`.
+///
+/// **Non-standard:** RFC 7914 fixes `c = 1`, the keys of any other `c`
+/// are only derived again by this crate. `c = 1` is `scrypt()`.
+///
+/// # Arguments
+/// - `password` - The password to process as a byte vector
+/// - `salt` - The salt value to use as a byte vector
+/// - `params` - The Params to use
+/// - `c` - The iterations of the final PBKDF2
+/// - `output` - The resulting derived key is returned in this byte vector.
+///
+/// # Return
+/// `Err(InvalidOutputLen)` under the conditions of `scrypt()`.
+pub fn scrypt_with_final_rounds(
+    password: &[u8], salt: &[u8], params: &Params, c: NonZeroU32, output: &mut [u8]
+) -> Result<(), InvalidOutputLen> {
+    scrypt_rounds_with(password, salt, params, c, output, &mut Scratch::default())
+}
+
+/// `scrypt_ctx` derives a key like `scrypt()`, separated by `context` from
+/// the keys of the same password and salt for other purposes, e.g.
+/// `b"disk"` and `b"backup"`. The context is public, it does not need to
+/// be secret or random.
+///
+/// The salt of scrypt is `SHA-256(len || context || salt)`, where `len` is
+/// the length of `context` as a big-endian 64 bit integer, so that no two
+/// pairs of context and salt give the same salt. An empty context is not a
+/// special case: its keys differ from those of `scrypt()`.
+///
+/// # Arguments
+/// - `password` - The password to process as a byte vector
+/// - `salt` - The salt value to use as a byte vector
+/// - `context` - The purpose of the key
+/// - `params` - The Params to use
+/// - `output` - The resulting derived key is returned in this byte vector.
+///
+/// # Return
+/// `Err(InvalidOutputLen)` under the conditions of `scrypt()`.
+pub fn scrypt_ctx(
+    password: &[u8], salt: &[u8], context: &[u8], params: &Params, output: &mut [u8]
+) -> Result<(), InvalidOutputLen> {
+    scrypt(password, &context_salt(context, salt), params, output)
+}
+
+/// `derive_key_and_iv` derives the key and the IV of a cipher from one
+/// scrypt output of `key_len + iv_len` bytes: the key is its first
+/// `key_len` bytes, the IV the rest. The split is fixed, keys and IVs of
+/// earlier versions are derived again for the same arguments.
+///
+/// **WARNING:** the IV is a function of the password and the salt, the same
+/// pair always gives the same key and IV. Use a new random salt for every
+/// encryption, never one salt for two messages.
+///
+/// # Arguments
+/// - `password` - The password to process as a byte vector
+/// - `salt` - The salt value to use as a byte vector, new for each use
+/// - `params` - The Params to use
+/// - `key_len` - The length of the key, at least 1
+/// - `iv_len` - The length of the IV, 0 for ciphers without one
+///
+/// # Return
+/// `Err(InvalidOutputLen)` if `key_len` is 0 or `key_len + iv_len` does not
+/// satisfy the conditions of `scrypt()`.
+pub fn derive_key_and_iv(
+    password: &[u8], salt: &[u8], params: &Params, key_len: usize, iv_len: usize,
+) -> Result<(DerivedKey, Vec<u8>), InvalidOutputLen> {
+    let len = key_len.checked_add(iv_len).ok_or(InvalidOutputLen::new(usize::MAX))?;
+    if key_len == 0 || len as u64 > InvalidOutputLen::MAX { Err(InvalidOutputLen::new(len))?; }
+    let mut output = vec![0u8; len];
+    scrypt(password, salt, params, &mut output)?;
+    // the IV is not secret, the truncated bytes are not zeroed
+    let iv = output[key_len..].to_vec();
+    output.truncate(key_len);
+    Ok((DerivedKey::new(output), iv))
+}
+
+/// The salt of `scrypt_ctx`.
+pub(crate) fn context_salt(context: &[u8], salt: &[u8]) -> [u8; 32] {
+    let mut len = [0u8; 8];
+    BigEndian::write_u64(&mut len, context.len() as u64);
+    let mut hash = Sha256::default();
+    hash.input(&len);
+    hash.input(context);
+    hash.input(salt);
+    let mut salt = [0u8; 32];
+    salt.copy_from_slice(&hash.result());
+    salt
+}
+
+/// The buffers of `scrypt`, kept to be reused by later computations of the
+/// same thread. They hold values derived from the password and are wiped
+/// when dropped.
+#[derive(Default)]
+pub(crate) struct Scratch {
+    pub(crate) b: Vec<u8>,
+    x: Vec<Block>,
+    v: Vec<Block>,
+    t: Vec<Block>,
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        zero(&mut self.b);
+        zero(Block::as_bytes_mut(&mut self.x));
+        zero(Block::as_bytes_mut(&mut self.v));
+        zero(Block::as_bytes_mut(&mut self.t));
+    }
+}
+
+/// `scrypt`, with the buffers of `scratch`. Every byte of the buffers is
+/// written before it is read, so they are not cleared between uses.
+pub(crate) fn scrypt_with(
+    password: &[u8], salt: &[u8], params: &Params, output: &mut [u8],
+    scratch: &mut Scratch,
+) -> Result<(), InvalidOutputLen> {
+    scrypt_rounds_with(password, salt, params, NonZeroU32::MIN, output, scratch)
+}
+
+/// `scrypt_with`, with `c` iterations of the final PBKDF2, see
+/// `scrypt_with_final_rounds`.
+pub(crate) fn scrypt_rounds_with(
+    password: &[u8], salt: &[u8], params: &Params, c: NonZeroU32, output: &mut [u8],
+    scratch: &mut Scratch,
 ) -> Result<(), InvalidOutputLen> {
+    check_output_len(output)?;
+    let b = mix(password, salt, params, scratch);
+    if c.get() == 1 {
+        pbkdf2_sha256(password, b, output);
+    } else {
+        pbkdf2::<Hmac<Sha256>>(password, b, c.get() as usize, output);
+    }
+    Ok(())
+}
+
+/// PBKDF2-HMAC-SHA256 with one iteration, of `salt` into `output`. With the
+/// `parallel` feature, outputs of `PARALLEL_PBKDF2_LEN` bytes and more are
+/// computed by the threads of rayon, e.g. the blocks of `p` of 8 and above.
+pub(crate) fn pbkdf2_sha256(password: &[u8], salt: &[u8], output: &mut [u8]) {
+    #[cfg(feature="parallel")]
+    {
+        if output.len() >= PARALLEL_PBKDF2_LEN {
+            return pbkdf2_parallel(password, salt, output);
+        }
+    }
+    pbkdf2::<Hmac<Sha256>>(password, salt, 1, output);
+}
+
+/// Shortest output of `pbkdf2_sha256` computed in parallel, the blocks of
+/// `r = 8` and `p = 8`. Below it the threads cost more than they save.
+#[cfg(feature="parallel")]
+const PARALLEL_PBKDF2_LEN: usize = 8 * 1024;
+
+/// Bytes of output computed by one task of `pbkdf2_parallel`.
+#[cfg(feature="parallel")]
+const PARALLEL_PBKDF2_CHUNK: usize = 2 * 1024;
+
+/// `pbkdf2_sha256`, with chunks of the output computed by rayon: every
+/// block only depends on its index and the keyed HMAC state.
+#[cfg(feature="parallel")]
+fn pbkdf2_parallel(password: &[u8], salt: &[u8], output: &mut [u8]) {
+    let prf = Hmac::<Sha256>::new_varkey(password).expect("HMAC accepts all key sizes");
+    output.par_chunks_mut(PARALLEL_PBKDF2_CHUNK).enumerate().for_each(|(i, chunk)| {
+        let first = i * (PARALLEL_PBKDF2_CHUNK / 32);
+        for (j, block) in chunk.chunks_mut(32).enumerate() {
+            let mut index = [0u8; 4];
+            BigEndian::write_u32(&mut index, (first + j) as u32 + 1);
+            let mut mac = prf.clone();
+            mac.input(salt);
+            mac.input(&index);
+            block.copy_from_slice(&mac.result().code()[..block.len()]);
+        }
+    });
+}
+
+/// Check the length of an output buffer of `scrypt`.
+pub(crate) fn check_output_len(output: &[u8]) -> Result<(), InvalidOutputLen> {
     // This check required by Scrypt:
     // check output.len() > 0 && output.len() <= (2^32 - 1) * 32
-    if !(output.len() > 0 && output.len() / 32 <= 0xffffffff) {
-        Err(InvalidOutputLen)?;
+    if output.len() < InvalidOutputLen::MIN
+        || output.len() as u64 > InvalidOutputLen::MAX
+    {
+        Err(InvalidOutputLen::new(output.len()))?;
     }
+    Ok(())
+}
+
+/// The first PBKDF2 and the ROMix of all `p` blocks of `scrypt`, returning
+/// the mixed blocks in the buffer `b` of `scratch`.
+pub(crate) fn mix<'a>(
+    password: &[u8], salt: &[u8], params: &Params, scratch: &'a mut Scratch,
+) -> &'a mut [u8] {
+    mix_with(params, scratch, |b| pbkdf2_sha256(password, salt, b))
+}
 
-    // The checks in the ScryptParams constructor guarantee
+/// `mix`, with the first PBKDF2 computed by `pbkdf2_into`.
+pub(crate) fn mix_with<'a, F: FnOnce(&mut [u8])>(
+    params: &Params, scratch: &'a mut Scratch, pbkdf2_into: F,
+) -> &'a mut [u8] {
+    let b = grow(&mut scratch.b, block_len(params));
+    pbkdf2_into(b);
+    core_with::<Salsa8>(b, params, &mut scratch.x, &mut scratch.v, &mut scratch.t);
+    b
+}
+
+/// The `128 * r * p` bytes of the blocks `B` of scrypt.
+pub(crate) fn block_len(params: &Params) -> usize {
+    // The checks in the Params constructor guarantee
     // that the following is safe:
-    let n = 1 << params.log_n;
-    let r128 = (params.r as usize) * 128;
-    let pr128 = (params.p as usize) * r128;
-    let nr128 = n * r128;
+    (params.p as usize) * (params.r as usize) * 128
+}
 
-    let mut b = vec![0u8; pr128];
-    pbkdf2::<Hmac<Sha256>>(&password, salt, 1, &mut b);
+/// The ROMix of all `p` blocks of `b` with the core `C`, with `x`, `v` and
+/// `t` as its buffers. `b` is read into the blocks of `x` and written back
+/// after the last ROMix. `v` and `t` hold a `V` and `T` for each of the
+/// `romix_lanes` blocks mixed at once, see `ro_mix_parallel`.
+pub(crate) fn core_with<C: MixingCore>(
+    b: &mut [u8], params: &Params, x: &mut Vec<Block>, v: &mut Vec<Block>, t: &mut Vec<Block>,
+) {
+    let n = 1 << params.log_n;
+    let r2 = (params.r as usize) * 2;
+    let lanes = romix_lanes(params);
 
-    let mut v = vec![0u8; nr128];
-    let mut t = vec![0u8; r128];
+    let x = grow(x, b.len() / 64);
+    Block::load(b, x);
+    let v = grow(v, lanes * n * r2);
+    let t = grow(t, lanes * r2);
+    #[cfg(feature="parallel")]
+    {
+        if lanes > 1 {
+            ro_mix_parallel::<C>(x, v, t, r2, n, lanes);
+            return Block::store(x, b);
+        }
+    }
 
-    for chunk in &mut b.chunks_mut(r128) {
-        romix::scrypt_ro_mix(chunk, &mut v, &mut t, n);
+    for chunk in x.chunks_mut(r2) {
+        romix::ro_mix::<C>(chunk, v, t, n);
     }
+    Block::store(x, b);
+}
+
+/// The ROMix of the blocks of `x`, `r2` Salsa20/8 blocks each, on the
+/// threads of rayon: the blocks are independent. They are split into
+/// `lanes` runs of consecutive blocks, each mixed with its own `V` and `T`
+/// of `v` and `t`, so the buffers of the caller's `Scratch` are reused and
+/// `Params::required_memory` bounds the memory in use.
+#[cfg(feature="parallel")]
+fn ro_mix_parallel<C: MixingCore>(
+    x: &mut [Block], v: &mut [Block], t: &mut [Block], r2: usize, n: usize, lanes: usize,
+) {
+    let per_lane = (x.len() / r2).div_ceil(lanes);
+    x.par_chunks_mut(per_lane * r2)
+        .zip(v.par_chunks_mut(n * r2))
+        .zip(t.par_chunks_mut(r2))
+        .for_each(|((run, v), t)| {
+            for chunk in run.chunks_mut(r2) {
+                romix::ro_mix::<C>(chunk, v, t, n);
+            }
+        });
+}
+
+/// The number of blocks of `params` mixed at once, each with a `V` of its
+/// own: with the `parallel` feature `p`, up to the threads of rayon.
+#[cfg(feature="parallel")]
+pub(crate) fn romix_lanes(params: &Params) -> usize {
+    (params.p as usize).min(rayon::current_num_threads()).max(1)
+}
 
-    pbkdf2::<Hmac<Sha256>>(&password, &b, 1, output);
+/// Without the `parallel` feature the blocks are mixed one after another.
+#[cfg(not(feature="parallel"))]
+pub(crate) fn romix_lanes(_params: &Params) -> usize { 1 }
+
+/// `scrypt_core` is the middle of `scrypt()` without the PBKDF2 before and
+/// after it, the `scryptROMix` of each of the `p` blocks of `b` under `N`
+/// and `r`, e.g. for proofs of space and memory-hard puzzles. `scrypt()`
+/// is `PBKDF2(password, salt)`, `scrypt_core` and `PBKDF2(password, b)`.
+///
+/// # Arguments
+/// - `b` - The `128 * r * p` bytes of the blocks, mixed in place
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Err(InvalidBufferLen)` if `b` is not `128 * r * p` bytes long.
+#[cfg(feature="low-level")]
+pub fn scrypt_core(b: &mut [u8], params: &Params) -> Result<(), InvalidBufferLen> {
+    let len = block_len(params);
+    if b.len() != len { Err(InvalidBufferLen::new(b.len(), len))?; }
+    core_with::<Salsa8>(b, params, &mut Vec::new(), &mut Vec::new(), &mut Vec::new());
     Ok(())
 }
 
+/// Overwrite `bytes` with zeros, without the writes being optimized away.
+pub(crate) fn zero(bytes: &mut [u8]) {
+    for b in bytes.iter_mut() {
+        unsafe { core::ptr::write_volatile(b, 0) };
+    }
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// Compare `a` and `b` in constant time, only their lengths influence the
+/// time it takes.
+#[cfg(feature="simple-verify")]
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// The first `len` bytes of `buf`, which is extended if it is shorter.
+fn grow<T: Copy + Default>(buf: &mut Vec<T>, len: usize) -> &mut [T] {
+    if buf.len() < len { buf.resize(len, T::default()); }
+    &mut buf[..len]
+}
+
+/// `hash_password` hashes `password` with the defaults of this crate, for
+/// applications without specific requirements. The string verifies with
+/// `verify_password()` and `scrypt_check()`.
+///
+/// The defaults are those of `SimpleConfig::default()`: `log_n = 15`,
+/// `r = 8`, `p = 1` (32 MiB), a 16 byte salt from `OsRng`, a 32 byte hash
+/// and the rscrypt format of `scrypt_simple()`. They only change in new
+/// minor versions, noted in the changelog, and old strings keep verifying.
+///
+/// ```
+/// # extern crate scrypt;
+/// # fn main() {
+/// # #[cfg(feature = "simple")] {
+/// let stored = scrypt::hash_password("hunter2").expect("OS RNG should not fail");
+/// assert!(stored.starts_with("$rscrypt$0$DwgB$"));
+/// assert!(scrypt::verify_password("hunter2", &stored).is_ok());
+/// # }
+/// # }
+/// ```
+///
+/// `password` may be a `&str`, a `&String`, raw bytes or a `&Password`.
+///
+/// # Return
+/// `Err(HashError::Rng)` in the case of an unlikely `OsRng` failure.
+#[cfg(feature="simple")]
+pub fn hash_password<P: AsRef<[u8]>>(password: P) -> Result<String, HashError> {
+    SimpleConfig::default().hash_bytes_with_rng(password.as_ref(), &mut os_rng())
+}
+
+/// `verify_password` checks `password` against a string of
+/// `hash_password()`, or of any other format of `scrypt_check()`. Like
+/// `hash_password()` it takes any password type.
+///
+/// ```
+/// # extern crate scrypt;
+/// # fn main() {
+/// # #[cfg(feature = "simple-verify")] {
+/// use scrypt::errors::CheckError;
+///
+/// let stored = "$rscrypt$0$BAgB$15ieabOWBi+lTfggDq1u4A==$\
+///               LSoB1to848I8SrfvmQAiAa2ajqs+iVgB5TecmxomqfU=$";
+/// assert!(scrypt::verify_password("password", stored).is_ok());
+/// assert_eq!(scrypt::verify_password("hunter2", stored), Err(CheckError::HashMismatch));
+/// # }
+/// # }
+/// ```
+///
+/// # Return
+/// `Err(CheckError::HashMismatch)` for a wrong password, the errors of
+/// `scrypt_check` otherwise.
+#[cfg(feature="simple-verify")]
+pub fn verify_password<P: AsRef<[u8]>>(password: P, stored: &str) -> Result<(), CheckError> {
+    scrypt_check_bytes(password.as_ref(), stored)
+}
+
+/// `verify` checks `password` against `stored` like `scrypt_check`, but
+/// reports a wrong password as `Ok(false)` instead of an error.
+///
+/// ```
+/// # extern crate scrypt;
+/// # fn main() {
+/// # #[cfg(feature = "simple-verify")] {
+/// let stored = "$rscrypt$0$BAgB$15ieabOWBi+lTfggDq1u4A==$\
+///               LSoB1to848I8SrfvmQAiAa2ajqs+iVgB5TecmxomqfU=$";
+/// assert_eq!(scrypt::verify("password", stored), Ok(true));
+/// assert_eq!(scrypt::verify("hunter2", stored), Ok(false));
+/// assert!(scrypt::verify("password", "$rscrypt$0$").is_err());
+/// # }
+/// # }
+/// ```
+///
+/// # Return
+/// `Ok(true)` if the password matches, `Ok(false)` if it does not and the
+/// other errors of `scrypt_check`, e.g. `Err(CheckError::InvalidFormat)`,
+/// for strings which could not be verified. `Err(CheckError::HashMismatch)`
+/// is never returned.
+#[cfg(feature="simple-verify")]
+pub fn verify(password: &str, stored: &str) -> Result<bool, CheckError> {
+    match check(password.as_bytes(), stored) {
+        Ok(_) => Ok(true),
+        Err(CheckError::HashMismatch) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
 /// `scrypt_simple` is a helper function that should be sufficient for the
 /// majority of cases where an application needs to use Scrypt to hash a
 /// password for storage. The result is a String that contains the parameters
 /// used as part of its encoding. The `scrypt_check` function may be used on
 /// a password to check if it is equal to a hashed value.
 ///
+/// The password is hashed as given, without Unicode normalization, so the
+/// composed and decomposed forms of e.g. "café" produce different hashes.
+/// Applications which receive passwords from different platforms should
+/// normalize them, e.g. to NFKC, before hashing and checking. NFKC leaves
+/// ASCII unchanged, so introducing it invalidates no pure-ASCII password.
+///
 /// # Format
 /// The format of the output is a modified version of the Modular Crypt Format
 /// that encodes algorithm used and the parameter values. If all parameter
@@ -128,123 +763,2839 @@ pub fn scrypt(
 ///
 /// # Arguments
 /// - `password` - The password to process as a str
-/// - `params` - The ScryptParams to use
+/// - `params` - The Params to use
+///
+/// Passwords longer than `DEFAULT_MAX_PASSWORD_LEN` bytes are rejected, like
+/// by all functions of the `scrypt_simple` family, `SimpleConfig` can raise
+/// or remove the limit.
 ///
 /// # Return
 /// `Ok(String)` if calculation is succesfull with the computation result.
-/// It will return `io::Error` error in the case of an unlikely `OsRng` failure.
-#[cfg(feature="include_simple")]
-pub fn scrypt_simple(password: &str, params: &ScryptParams)
-    -> io::Result<String>
+/// It will return `Err(HashError::PasswordTooLong)` for longer passwords
+/// and `Err(HashError::Rng)` in the case of an unlikely `OsRng` failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple(password: &str, params: &Params)
+    -> Result<String, HashError>
 {
-    let mut rng = OsRng::new()?;
+    scrypt_simple_bytes(password.as_bytes(), params)
+}
 
-    let mut salt = [0u8; 16];
-    rng.try_fill_bytes(&mut salt)?;
+/// `scrypt_simple_bytes` works like `scrypt_simple`, but takes the password
+/// as raw bytes, e.g. the output of a hardware token. The strings verify
+/// with `scrypt_check_bytes()`, and with `scrypt_check()` if the bytes are
+/// valid UTF-8.
+///
+/// As in every scrypt implementation, trailing NUL bytes of passwords of up
+/// to 64 bytes do not change the hash: HMAC pads its key with zeros.
+///
+/// # Arguments
+/// - `password` - The password to process as a byte slice
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_bytes(password: &[u8], params: &Params)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).build()?
+        .hash_bytes_with_rng(password, &mut os_rng())
+}
 
-    // 256-bit derived key
-    let mut dk = [0u8; 32];
+/// `scrypt_simple_password` works like `scrypt_simple`, but takes a
+/// `Password`, which may not be UTF-8.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_password(password: &Password, params: &Params)
+    -> Result<String, HashError>
+{
+    scrypt_simple_bytes(password.as_bytes(), params)
+}
 
-    scrypt(password.as_bytes(), &salt, params, &mut dk)
-        .expect("32 bytes always satisfy output length requirements");
+/// Same as `scrypt_simple`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+///
+/// The `CryptoRng` bound keeps predictable generators out of production
+/// code; tests can use a seeded `ChaChaRng` for reproducible strings.
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+/// - `rng` - The random number generator used to produce the salt
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` if `rng` fails to produce a salt.
+#[deprecated(note = "use `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).build()?.hash_bytes_with_rng(password.as_bytes(), rng)
+}
 
-    // usually 128 bytes is enough
-    let mut result = String::with_capacity(128);
-    result.push_str("$rscrypt$");
-    if params.r < 256 && params.p < 256 {
-        result.push_str("0$");
-        let mut tmp = [0u8; 3];
-        tmp[0] = params.log_n;
-        tmp[1] = params.r as u8;
-        tmp[2] = params.p as u8;
-        result.push_str(&base64::encode(&tmp));
-    } else {
-        result.push_str("1$");
-        let mut tmp = [0u8; 9];
-        tmp[0] = params.log_n;
-        LittleEndian::write_u32(&mut tmp[1..5], params.r);
-        LittleEndian::write_u32(&mut tmp[5..9], params.p);
-        result.push_str(&base64::encode(&tmp));
-    }
-    result.push('$');
-    result.push_str(&base64::encode(&salt));
-    result.push('$');
-    result.push_str(&base64::encode(&dk));
-    result.push('$');
+/// Same as `scrypt_simple_bytes`, but draws the salt from the provided
+/// `rng` instead of `OsRng`.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_bytes_with_rng<R: RngCore + CryptoRng>(
+    password: &[u8], params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).build()?.hash_bytes_with_rng(password, rng)
+}
 
-    Ok(result)
+/// Same as `scrypt_simple`, but uses the provided `salt` instead of
+/// generating one, so the output is fully determined by the inputs.
+///
+/// Meant for test fixtures and for salts derived from an external source.
+/// Never reuse a salt across passwords or users in production: equal
+/// passwords would produce equal strings, and precomputed tables would
+/// apply to every hash sharing the salt.
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `salt` - The 128-bit salt to use
+/// - `params` - The Params to use
+///
+/// # Return
+/// The resulting string, or `Err(HashError::PasswordTooLong)` for a
+/// password longer than `DEFAULT_MAX_PASSWORD_LEN` like `scrypt_simple`.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_with_salt(
+    password: &str, salt: &[u8; 16], params: &Params,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).build()?
+        .hash_bytes_with_salt(password.as_bytes(), salt)
 }
 
-/// `scrypt_check` compares a password against the result of a previous call
-/// to scrypt_simple and returns `Ok(())` if the passed in password hashes to
-/// the same value, `Err(CheckError::HashMismatch)` if hashes have
-/// different values, and `Err(CheckError::InvalidFormat)` if `hashed_value`
-/// has an invalid format.
+/// `scrypt_simple_url_safe` works like `scrypt_simple`, but encodes the
+/// fields with the URL-safe base64 alphabet (`-` and `_` instead of `+` and
+/// `/`) and without padding, for strings passed through URLs, JWT claims or
+/// environment variables. `scrypt_check` detects the alphabet, see its
+/// documentation.
 ///
 /// # Arguments
-/// - password - The password to process as a str
-/// - hashed_value - A string representing a hashed password returned
-/// by `scrypt_simple()`
-#[cfg(feature="include_simple")]
-pub fn scrypt_check(password: &str, hashed_value: &str)
-    -> Result<(), CheckError>
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_url_safe(password: &str, params: &Params)
+    -> Result<String, HashError>
 {
-    let mut iter = hashed_value.split('$');
+    SimpleConfig::builder().params(*params).url_safe(true).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
 
-    // Check that there are no characters before the first "$"
-    if iter.next() != Some("") { Err(CheckError::InvalidFormat)?; }
+/// `scrypt_simple_v2` works like `scrypt_simple`, but produces an rscrypt
+/// string of version 2, which other MCF parsers handle more easily.
+///
+/// # Format
+/// The header holds `log_n` (1 byte), `r` and `p` (4 bytes each,
+/// little-endian), the lengths of salt and hash (1 byte each) and a flags
+/// byte, which is zero. All fields use the standard base64 alphabet without
+/// padding and there is no trailing `$`.
+///
+/// `$rscrypt$2$<base64(header)>$<base64(salt)>$<base64(hash)>`
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_v2(password: &str, params: &Params)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).version(2).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
 
-    // Check the name
-    if iter.next() != Some("rscrypt") { Err(CheckError::InvalidFormat)?; }
-
-    // Parse format - currenlty only version 0 (compact) and 1 (expanded) are
-    // supported
-    let fstr = iter.next().ok_or(CheckError::InvalidFormat)?;
-    let pvec = iter.next().ok_or(CheckError::InvalidFormat)
-        .and_then(|s| base64::decode(s).map_err(|_| CheckError::InvalidFormat))?;
-    let params = match fstr {
-        "0" if pvec.len() == 3 => {
-            let log_n = pvec[0];
-            let r = pvec[1] as u32;
-            let p = pvec[2] as u32;
-            ScryptParams::new(log_n, r, p)
-                .map_err(|_| CheckError::InvalidFormat)
-        }
-        "1" if pvec.len() == 9 => {
-            let log_n = pvec[0];
-            let mut pval = [0u32; 2];
-            LittleEndian::read_u32_into(&pvec[1..9], &mut pval);
-            ScryptParams::new(log_n, pval[0], pval[1])
-                .map_err(|_| CheckError::InvalidFormat)
-        }
-        _ => Err(CheckError::InvalidFormat),
-    }?;
+/// `scrypt_simple_peppered` works like `scrypt_simple_v2`, but mixes a
+/// secret `pepper` into the password, so that stolen hash strings can not be
+/// attacked without the key. The hash string records `pepper.id`, which
+/// `Verifier::verify_with_keys()` uses to look up the key.
+///
+/// # Format
+/// scrypt hashes `HMAC-SHA256(pepper.key, password)` instead of the
+/// password. The string is of version 2, with bit 0 of the flags set and
+/// the pepper id appended to the header.
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+/// - `pepper` - The secret key and its identifier
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_peppered(password: &str, params: &Params, pepper: &Pepper)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).pepper_id(pepper.id).build()?
+        .hash_with_keys(password, pepper)
+}
 
-    // Salt
-    let salt = iter.next().ok_or(CheckError::InvalidFormat)
-        .and_then(|s| base64::decode(s).map_err(|_| CheckError::InvalidFormat))?;
+/// Same as `scrypt_simple_peppered`, but draws the salt from the provided
+/// `rng` instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::pepper_id` and `SimpleConfig::hash_with_keys_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_peppered_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, pepper: &Pepper, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).pepper_id(pepper.id).build()?
+        .hash_with_keys_rng(password, pepper, rng)
+}
 
-    // Hashed value
-    let hash = iter.next().ok_or(CheckError::InvalidFormat)
-        .and_then(|s| base64::decode(s).map_err(|_| CheckError::InvalidFormat))?;
+/// `scrypt_simple_provided` works like `scrypt_simple_peppered`, with the
+/// key `id` of `keys`, e.g. of a KMS. The key is only borrowed while it is
+/// mixed into the password.
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+/// - `keys` - The provider of the pepper
+/// - `id` - The identifier of the pepper
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::UnknownPepper)` if `keys` has no key of
+/// `id`, `Err(HashError::KeyUnavailable)` if it can not be fetched and
+/// `Err(HashError::Rng)` in the case of an unlikely `OsRng` failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_provided<K: KeyProvider + ?Sized>(
+    password: &str, params: &Params, keys: &K, id: u8,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).pepper_id(id).build()?.hash_with_keys(password, keys)
+}
+
+/// Same as `scrypt_simple_provided`, but draws the salt from the provided
+/// `rng` instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::pepper_id` and `SimpleConfig::hash_with_keys_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_provided_with_rng<K: KeyProvider + ?Sized, R: RngCore + CryptoRng>(
+    password: &str, params: &Params, keys: &K, id: u8, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).pepper_id(id).build()?
+        .hash_with_keys_rng(password, keys, rng)
+}
+
+/// `scrypt_simple_ad` works like `scrypt_simple_v2`, but binds the hash to
+/// the associated data `ad`, e.g. a tenant or site identifier. The string
+/// does not contain `ad`, it only verifies with `Verifier::verify_with_ad()`
+/// and the same `ad`, so that a hash copied to the row of another tenant
+/// does not verify there.
+///
+/// # Format
+/// scrypt is computed with the salt `HMAC-SHA256(salt, ad)` instead of the
+/// stored salt. The string is of version 2, with bit 1 of the flags set.
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+/// - `ad` - The associated data, required again for verification
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_ad(password: &str, params: &Params, ad: &[u8])
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).associated_data(true).build()?
+        .hash_with_ad(password, ad)
+}
 
-    // Make sure that the input ends with a "$"
-    if iter.next() != Some("") { Err(CheckError::InvalidFormat)?; }
+/// Same as `scrypt_simple_ad`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::associated_data` and `SimpleConfig::hash_with_ad_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_ad_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, ad: &[u8], rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).associated_data(true).build()?
+        .hash_with_ad_rng(password, ad, rng)
+}
+
+/// `scrypt_simple_prehashed` works like `scrypt_simple_v2`, but bounds the
+/// cost of long passwords by `limits`: passwords longer than
+/// `limits.prehash_above` bytes are hashed with SHA-256 before scrypt and
+/// passwords longer than `limits.max_len` bytes are rejected. The threshold
+/// is stored in the string, which `scrypt_check()` applies, up to its
+/// default limit of the password length, and a `Verifier` up to its
+/// `max_password_len`.
+///
+/// # Format
+/// The string is of version 2, with bit 2 of the flags set and the
+/// threshold appended to the header (2 bytes, little-endian). Passwords up
+/// to the threshold are hashed like by `scrypt_simple_v2`.
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+/// - `limits` - The threshold of pre-hashing and the maximal length
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::PasswordTooLong)` if the password is
+/// longer than `limits.max_len` bytes and `Err(HashError::Rng)` in the case
+/// of an unlikely `OsRng` failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_prehashed(password: &str, params: &Params, limits: &LengthPolicy)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).prehash(*limits).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
 
-    // Make sure there is no trailing data after the final "$"
-    if iter.next() != None { Err(CheckError::InvalidFormat)?; }
+/// Same as `scrypt_simple_prehashed`, but draws the salt from the provided
+/// `rng` instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::prehash` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_prehashed_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, limits: &LengthPolicy, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).prehash(*limits).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
 
-    let mut output = vec![0u8; hash.len()];
-    scrypt(password.as_bytes(), &salt, &params, &mut output)
-        .map_err(|_| CheckError::InvalidFormat)?;
+/// `scrypt_simple_client` hashes `client_digest`, a password digested by
+/// the client under `scheme`, like `scrypt_simple_v2` hashes a password.
+/// The id of the scheme is stored in the string, which only verifies with
+/// a `Verifier` of the same `client_scheme` and a digest of that scheme.
+///
+/// # Format
+/// The string is of version 2, with bit 3 of the flags set and the scheme
+/// id appended to the header.
+///
+/// # Arguments
+/// - `client_digest` - The digest sent by the client
+/// - `scheme` - The scheme of the digest
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_client(client_digest: &[u8], scheme: ClientScheme, params: &Params)
+    -> Result<String, HashError>
+{
+    let mut rng = os_rng();
+    scrypt_simple_client_with_rng(client_digest, scheme, params, &mut rng)
+}
 
-    // Be careful here - its important that the comparison be done using a fixed
-    // time equality check. Otherwise an adversary that can measure how long
-    // this step takes can learn about the hashed value which would allow them
-    // to mount an offline brute force attack against the hashed password.
-    if constant_time_eq(&output, &hash) {
-        Ok(())
-    } else {
-        Err(CheckError::HashMismatch)?
-    }
+/// Same as `scrypt_simple_client`, but draws the salt from the provided
+/// `rng` instead of `OsRng`.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_client_with_rng<R: RngCore + CryptoRng>(
+    client_digest: &[u8], scheme: ClientScheme, params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).client_scheme(scheme).build()?
+        .hash_bytes_with_rng(client_digest, rng)
+}
+
+/// `scrypt_simple_final_rounds` works like `scrypt_simple_v2`, but runs `c`
+/// iterations of the final PBKDF2 of scrypt, see
+/// `scrypt_with_final_rounds()`. `c` is stored in the string, which
+/// `scrypt_check()` applies.
+///
+/// **Non-standard:** no other implementation of scrypt or of the rscrypt
+/// format verifies these strings. `c = 1` produces the plain strings of
+/// `scrypt_simple_v2`.
+///
+/// # Format
+/// The string is of version 2, with bit 4 of the flags set and `c` appended
+/// to the header as 4 bytes, little-endian. The flag is only combined with
+/// that of `scrypt_simple_bound`.
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+/// - `c` - The iterations of the final PBKDF2
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_final_rounds(password: &str, params: &Params, c: NonZeroU32)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).version(2).final_rounds(c).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Same as `scrypt_simple_final_rounds`, but draws the salt from the
+/// provided `rng` instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::final_rounds` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_final_rounds_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, c: NonZeroU32, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).version(2).final_rounds(c).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
+
+/// `scrypt_simple_bound` works like `scrypt_simple_v2`, but binds the
+/// header of the string into the salt, so that a string whose parameters,
+/// lengths or flags were edited does not verify, instead of being verified
+/// at the cost of the edited parameters. `scrypt_check()` verifies the
+/// string.
+///
+/// # Format
+/// The string is of version 2, with bit 5 of the flags set, which may be
+/// combined with the others. scrypt is computed with the salt
+/// `SHA-256(header || salt)`, where `header` are all decoded bytes of the
+/// header field, including the field which follows the flags, and the
+/// random salt is stored. The salt is bound before associated data is
+/// mixed into it.
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_bound(password: &str, params: &Params) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).bound(true).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Same as `scrypt_simple_bound`, but draws the salt from the provided
+/// `rng` instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::bound` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_bound_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).bound(true).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
+
+/// The salt scrypt computes the strings of `scrypt_simple_bound` with,
+/// from the decoded `header` field.
+#[cfg(feature="simple-verify")]
+fn bound_salt(header: &[u8], salt: &[u8]) -> [u8; 32] {
+    let mut hash = Sha256::default();
+    hash.input(header);
+    hash.input(salt);
+    let mut bound = [0u8; 32];
+    bound.copy_from_slice(&hash.result());
+    bound
+}
+
+/// `hash_and_derive` works like `scrypt_simple`, and also returns a key of
+/// `key_len` bytes, e.g. for the encryption of the user's data, from the
+/// same computation of scrypt instead of a second one. The key is not
+/// stored in the string, `check_and_derive()` derives it again when the
+/// password is verified.
+///
+/// scrypt computes `32 + key_len` bytes: the first 32 are the hash of the
+/// string, the rest are the key. Every 32 bytes of the output are a
+/// separate HMAC of the password, the hash tells nothing about the key.
+///
+/// # Format
+/// An rscrypt version 2 string with the flag `0x80`, which marks the
+/// strings `check_and_derive()` accepts. It also verifies with
+/// `scrypt_check()`.
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+/// - `key_len` - The length of the key, at least 1
+///
+/// # Return
+/// `Ok((String, DerivedKey))` if calculation is succesfull with the
+/// computation result and the key. It will return
+/// `Err(HashError::InvalidOutputLen)` if `key_len` is 0 or scrypt can not
+/// produce `32 + key_len` bytes, `Err(HashError::PasswordTooLong)` for
+/// longer passwords and `Err(HashError::Rng)` in the case of an unlikely
+/// `OsRng` failure.
+#[cfg(feature="simple")]
+pub fn hash_and_derive(password: &str, params: &Params, key_len: usize)
+    -> Result<(String, DerivedKey), HashError>
+{
+    SimpleConfig::builder().params(*params).build()?.hash_and_derive(password, key_len)
+}
+
+/// Same as `hash_and_derive`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfig::hash_and_derive_with_rng`")]
+#[cfg(feature="simple")]
+pub fn hash_and_derive_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, key_len: usize, rng: &mut R,
+) -> Result<(String, DerivedKey), HashError> {
+    SimpleConfig::builder().params(*params).build()?
+        .hash_and_derive_with_rng(password, key_len, rng)
+}
+
+/// The length of the output of scrypt for a hash of `hash_len` and a key of
+/// `key_len` bytes, see `hash_and_derive`. The key must not be empty.
+#[cfg(feature="simple-verify")]
+fn derived_output_len(hash_len: usize, key_len: usize) -> Result<usize, InvalidOutputLen> {
+    let len = hash_len.checked_add(key_len).ok_or(InvalidOutputLen::new(usize::MAX))?;
+    if key_len == 0 || len as u64 > InvalidOutputLen::MAX { Err(InvalidOutputLen::new(len))?; }
+    Ok(len)
+}
+
+/// `wrap_legacy_hash` protects an existing digest of a password without
+/// knowing the password: `legacy_digest` is hashed by scrypt like a
+/// password, and a `Verifier` with `legacy_wrapped` verifies the password by
+/// computing `scrypt(inner(password))`. Tables of weak hashes can so be
+/// strengthened at once, instead of when each user logs in next.
+///
+/// # Format
+/// `$rscrypt-wrapped$<inner>` followed by the rscrypt string of the digest
+/// without its `$rscrypt` prefix, e.g. `$rscrypt-wrapped$sha1$0$...$`.
+/// `<inner>` is the `LegacyDigest::name()` of `inner`.
+///
+/// # Arguments
+/// - `inner` - The digest the legacy system computed of the password
+/// - `legacy_digest` - The raw digest bytes, hex encoded digests must be
+///   decoded first
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure, and `Err(HashError::PasswordTooLong)` for a digest longer than
+/// `DEFAULT_MAX_PASSWORD_LEN`, which is no digest of a legacy system.
+#[cfg(feature="simple")]
+pub fn wrap_legacy_hash(inner: LegacyDigest, legacy_digest: &[u8], params: &Params)
+    -> Result<String, HashError>
+{
+    let mut rng = os_rng();
+    wrap_legacy_hash_with_rng(inner, legacy_digest, params, &mut rng)
+}
+
+/// Same as `wrap_legacy_hash`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+#[cfg(feature="simple")]
+pub fn wrap_legacy_hash_with_rng<R: RngCore + CryptoRng>(
+    inner: LegacyDigest, legacy_digest: &[u8], params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    let rscrypt = SimpleConfig::builder().params(*params).build()?
+        .hash_bytes_with_rng(legacy_digest, rng)?;
+    Ok(format!("{}{}{}", WRAPPED_PREFIX, inner.name(), &rscrypt["$rscrypt".len()..]))
+}
+
+/// The encodings `scrypt_simple_fmt` can produce, all of them are accepted
+/// by `scrypt_check` and `verify_any`.
+#[cfg(feature="simple")]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum OutputFormat {
+    /// rscrypt of version 0 or 1 like `scrypt_simple`, depending on whether
+    /// `r` and `p` fit in a single byte. The default.
+    #[default]
+    Rscrypt,
+    /// rscrypt of version 2 like `scrypt_simple_v2`.
+    RscryptV2,
+    /// PHC strings like `scrypt_simple_phc`.
+    Phc,
+    /// PHC strings in the adapted base64 of Python's passlib, `.` for `+`,
+    /// for systems which still read them with passlib.
+    Passlib,
+}
+
+/// `scrypt_simple_fmt` works like `scrypt_simple`, but produces the string
+/// in the encoding selected by `format`.
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+/// - `format` - The encoding of the result
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_fmt(password: &str, params: &Params, format: OutputFormat)
+    -> Result<String, HashError>
+{
+    let mut rng = os_rng();
+    scrypt_simple_fmt_with_rng(password, params, format, &mut rng)
+}
+
+/// Same as `scrypt_simple_fmt`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_fmt_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, format: OutputFormat, rng: &mut R,
+) -> Result<String, HashError> {
+    let mut builder = SimpleConfig::builder();
+    builder.params(*params);
+    match format {
+        OutputFormat::Rscrypt => {}
+        OutputFormat::RscryptV2 => { builder.version(2); }
+        OutputFormat::Phc | OutputFormat::Passlib => { builder.format(Format::Phc); }
+    }
+    let config = builder.build()?;
+    if format != OutputFormat::Passlib {
+        return config.hash_bytes_with_rng(password.as_bytes(), rng);
+    }
+    let (parts, _) = config.parts_with_rng(password.as_bytes(), rng)?;
+    Ok(phc::encode_passlib(&parts.params, &parts.salt, &parts.hash))
+}
+
+/// Shortest salt accepted by `scrypt_simple_with_salt_len`.
+#[cfg(feature="simple-verify")]
+pub const MIN_SALT_LEN: usize = 8;
+/// Longest salt accepted by `scrypt_simple_with_salt_len`.
+#[cfg(feature="simple-verify")]
+pub const MAX_SALT_LEN: usize = 64;
+
+/// Generate a random salt of `len` bytes with `OsRng`, e.g. to store salt
+/// and hash of `scrypt()` separately.
+///
+/// # Arguments
+/// - `len` - The salt length in bytes, `MIN_SALT_LEN..=MAX_SALT_LEN`
+///
+/// # Return
+/// `Err(HashError::InvalidSaltLen)` if `len` is out of range and
+/// `Err(HashError::Rng)` in the case of an unlikely `OsRng` failure.
+#[cfg(feature="simple")]
+pub fn generate_salt(len: usize) -> Result<Vec<u8>, HashError> {
+    let mut rng = os_rng();
+    generate_salt_with_rng(len, &mut rng)
+}
+
+/// Same as `generate_salt`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+#[cfg(feature="simple")]
+pub fn generate_salt_with_rng<R: RngCore + CryptoRng>(len: usize, rng: &mut R)
+    -> Result<Vec<u8>, HashError>
+{
+    if !(MIN_SALT_LEN..=MAX_SALT_LEN).contains(&len) {
+        Err(HashError::InvalidSaltLen(len))?;
+    }
+    let mut salt = vec![0u8; len];
+    rng.try_fill_bytes(&mut salt)?;
+    Ok(salt)
+}
+
+/// Same as `generate_salt`, for a salt of `N` bytes.
+#[cfg(feature="simple")]
+pub fn generate_salt_array<const N: usize>() -> Result<[u8; N], HashError> {
+    let mut rng = os_rng();
+    generate_salt_array_with_rng(&mut rng)
+}
+
+/// Same as `generate_salt_array`, but draws the salt from the provided
+/// `rng` instead of `OsRng`.
+#[cfg(feature="simple")]
+pub fn generate_salt_array_with_rng<const N: usize, R: RngCore + CryptoRng>(rng: &mut R)
+    -> Result<[u8; N], HashError>
+{
+    if !(MIN_SALT_LEN..=MAX_SALT_LEN).contains(&N) {
+        Err(HashError::InvalidSaltLen(N))?;
+    }
+    let mut salt = [0u8; N];
+    rng.try_fill_bytes(&mut salt)?;
+    Ok(salt)
+}
+
+/// Same as `scrypt_simple`, but generates a salt of `salt_len` bytes instead
+/// of 16. `scrypt_check` verifies the result like any other rscrypt string,
+/// it takes the salt length from the encoded salt.
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+/// - `salt_len` - The salt length in bytes, `MIN_SALT_LEN..=MAX_SALT_LEN`
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::InvalidSaltLen)` if `salt_len` is out of
+/// range and `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_with_salt_len(
+    password: &str, params: &Params, salt_len: usize,
+) -> Result<String, HashError> {
+    let config = SimpleConfig::builder().params(*params).salt_len(salt_len).build()?;
+    config.hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Shortest hash produced by `scrypt_simple_with_dk_len` and accepted when
+/// verifying.
+#[cfg(feature="simple-verify")]
+pub const MIN_DK_LEN: usize = 16;
+/// Longest hash produced by `scrypt_simple_with_dk_len`.
+#[cfg(feature="simple-verify")]
+pub const MAX_DK_LEN: usize = 64;
+
+/// Same as `scrypt_simple`, but produces a hash of `dk_len` bytes instead of
+/// 32, e.g. 64 for a 512-bit digest. `scrypt_check` sizes the computation
+/// from the decoded hash, so the result verifies like any other rscrypt
+/// string.
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+/// - `dk_len` - The hash length in bytes, `MIN_DK_LEN..=MAX_DK_LEN`
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::InvalidDkLen)` if `dk_len` is out of range
+/// and `Err(HashError::Rng)` in the case of an unlikely `OsRng` failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_with_dk_len(
+    password: &str, params: &Params, dk_len: usize,
+) -> Result<String, HashError> {
+    let config = SimpleConfig::builder().params(*params).dk_len(dk_len).build()?;
+    config.hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// `scrypt_simple_phc` works like `scrypt_simple`, but produces a string in
+/// the standard [PHC string format][1] understood by other scrypt
+/// implementations, e.g. passlib and the crates built on RustCrypto's
+/// `password-hash`, which `scrypt_check` verifies in turn.
+///
+/// # Format
+/// The parameters are encoded as decimal numbers, salt (128-bit) and hash
+/// (256-bit) use the standard base64 alphabet without padding.
+///
+/// `$scrypt$ln=<log_n>,r=<r>,p=<p>$<base64(salt)>$<base64(hash)>`
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+///
+/// [1]: https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md
+#[cfg(feature="simple")]
+pub fn scrypt_simple_phc(password: &str, params: &Params)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).format(Format::Phc).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Same as `scrypt_simple_phc`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::format(Format::Phc)` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_phc_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).format(Format::Phc).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
+
+/// `scrypt_simple_lambdaworks` works like `scrypt_simple`, but produces a
+/// string understood by the Java `com.lambdaworks.crypto.SCryptUtil`.
+///
+/// # Format
+/// The parameters are packed into a single hexadecimal number, salt (128-bit)
+/// and hash (256-bit) use the standard base64 alphabet with padding.
+///
+/// `$s0$<hex(log_n << 16 | r << 8 | p)>$<base64(salt)>$<base64(hash)>`
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::UnsupportedParams)` if `r` or `p` do not fit
+/// in a single byte and `Err(HashError::Rng)` in the case of an unlikely
+/// `OsRng` failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_lambdaworks(password: &str, params: &Params)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).format(Format::Lambdaworks).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Same as `scrypt_simple_lambdaworks`, but draws the salt from the provided
+/// `rng` instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::format(Format::Lambdaworks)` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_lambdaworks_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).format(Format::Lambdaworks).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
+
+/// `scrypt_simple_django` works like `scrypt_simple`, but produces a string
+/// understood by Django's `ScryptPasswordHasher`.
+///
+/// # Format
+/// `N` is stored directly instead of `log_n`, the salt is a random string
+/// of 22 alphanumeric characters stored as plain text and the 512-bit hash
+/// uses the standard base64 alphabet with padding.
+///
+/// `scrypt$<N>$<salt>$<r>$<p>$<base64(hash)>`
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_django(password: &str, params: &Params)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).format(Format::Django).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Same as `scrypt_simple_django`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::format(Format::Django)` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_django_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).format(Format::Django).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
+
+/// `scrypt_simple_werkzeug` works like `scrypt_simple`, but produces a string
+/// understood by Werkzeug's `check_password_hash()`, as used by Flask.
+///
+/// # Format
+/// `N` is stored directly instead of `log_n`, the salt is a random string
+/// of 16 alphanumeric characters stored as plain text and the 512-bit hash
+/// is encoded as lowercase hex.
+///
+/// `scrypt:<N>:<r>:<p>$<salt>$<hex(hash)>`
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_werkzeug(password: &str, params: &Params)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).format(Format::Werkzeug).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Same as `scrypt_simple_werkzeug`, but draws the salt from the provided
+/// `rng` instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::format(Format::Werkzeug)` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_werkzeug_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).format(Format::Werkzeug).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
+
+/// `scrypt_simple_scryptkdf` works like `scrypt_simple`, but produces a
+/// string understood by `scrypt_hash_verify()` of Perl's `Crypt::ScryptKDF`,
+/// e.g. while a Perl system still verifies the hashes written here.
+///
+/// # Format
+/// `N` is stored directly instead of `log_n`, the 32 byte salt and the 256
+/// bit hash are encoded in padded base64.
+///
+/// `SCRYPT:<N>:<r>:<p>:<base64(salt)>:<base64(hash)>`
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_scryptkdf(password: &str, params: &Params)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).format(Format::ScryptKdf).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Same as `scrypt_simple_scryptkdf`, but draws the salt from the provided
+/// `rng` instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::format(Format::ScryptKdf)` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_scryptkdf_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).format(Format::ScryptKdf).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
+
+/// `scrypt_simple_php` works like `scrypt_simple`, but produces a string
+/// understood by `Password::check()` of the PHP extension `php-scrypt`, e.g.
+/// while a PHP system still verifies the hashes written here.
+///
+/// # Format
+/// `N` is stored directly instead of `log_n`, the salt is a random string
+/// of 12 base64 characters, with `.` instead of `+`, stored as plain text
+/// and the 256-bit hash is encoded as lowercase hex. There is no prefix.
+///
+/// `<N>$<r>$<p>$<salt>$<hex(hash)>`
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_php(password: &str, params: &Params)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).format(Format::PhpScrypt).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Same as `scrypt_simple_php`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::format(Format::PhpScrypt)` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_php_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).format(Format::PhpScrypt).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
+
+/// `scrypt_simple_go` works like `scrypt_simple`, but produces a string
+/// understood by `CompareHashAndPassword()` of Go's `simple-scrypt`, e.g.
+/// while a Go service still verifies the hashes written here.
+///
+/// # Format
+/// `N` is stored directly instead of `log_n`, the 16 byte salt and the
+/// 256-bit hash are encoded as lowercase hex. There is no prefix.
+///
+/// `<N>$<r>$<p>$<hex(salt)>$<hex(hash)>`
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_go(password: &str, params: &Params)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).format(Format::SimpleScrypt).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Same as `scrypt_simple_go`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::format(Format::SimpleScrypt)` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_go_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).format(Format::SimpleScrypt).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
+
+/// `scrypt_simple_ruby` works like `scrypt_simple`, but produces a string
+/// understood by `SCrypt::Password` of the Ruby gem `scrypt`, e.g. while a
+/// Ruby application still verifies the hashes written here.
+///
+/// # Format
+/// `N`, `r` and `p` are stored in hex, the 32 byte salt and the 256-bit
+/// hash are encoded as lowercase hex. There is no prefix.
+///
+/// `<hex(N)>$<hex(r)>$<hex(p)>$<hex(salt)>$<hex(hash)>`
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_ruby(password: &str, params: &Params)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).format(Format::RubyScrypt).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Same as `scrypt_simple_ruby`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::format(Format::RubyScrypt)` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_ruby_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).format(Format::RubyScrypt).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
+
+/// `scrypt_simple_cisco` produces a Cisco IOS type 9 secret, as used in
+/// `username <name> secret 9 <secret>`.
+///
+/// # Format
+/// The parameters are fixed to `N = 16384`, `r = 1` and `p = 1`. The salt is
+/// a random string of 14 characters stored as plain text and the 256-bit
+/// hash is encoded with the standard base64 bit order, but the `crypt(3)`
+/// alphabet and no padding.
+///
+/// `$9$<salt>$<base64(hash)>`
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_cisco(password: &str) -> Result<String, HashError> {
+    SimpleConfig::builder().params(cisco::params()).format(Format::Cisco).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Same as `scrypt_simple_cisco`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::format(Format::Cisco)` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_cisco_with_rng<R: RngCore + CryptoRng>(
+    password: &str, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(cisco::params()).format(Format::Cisco).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
+
+/// `scrypt_simple_hex` is `scrypt_simple` for storage which can hold neither
+/// `$` nor base64, e.g. legacy database columns.
+///
+/// # Format
+/// A random 128-bit salt and the 256-bit hash are encoded as lowercase hex.
+/// `scrypt_check` accepts digits of either case.
+///
+/// `scrypt:ln=<log_n>,r=<r>,p=<p>:<hex(salt)>:<hex(hash)>`
+///
+/// # Arguments
+/// - `password` - The password to process as a str
+/// - `params` - The Params to use
+///
+/// # Return
+/// `Ok(String)` if calculation is succesfull with the computation result.
+/// It will return `Err(HashError::Rng)` in the case of an unlikely `OsRng`
+/// failure.
+#[cfg(feature="simple")]
+pub fn scrypt_simple_hex(password: &str, params: &Params)
+    -> Result<String, HashError>
+{
+    SimpleConfig::builder().params(*params).format(Format::Hex).build()?
+        .hash_bytes_with_rng(password.as_bytes(), &mut os_rng())
+}
+
+/// Same as `scrypt_simple_hex`, but draws the salt from the provided `rng`
+/// instead of `OsRng`.
+#[deprecated(note = "use `SimpleConfigBuilder::format(Format::Hex)` and `SimpleConfig::hash_with_rng`")]
+#[cfg(feature="simple")]
+pub fn scrypt_simple_hex_with_rng<R: RngCore + CryptoRng>(
+    password: &str, params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    SimpleConfig::builder().params(*params).format(Format::Hex).build()?
+        .hash_bytes_with_rng(password.as_bytes(), rng)
+}
+
+/// The OS random number generator, or the source of
+/// `entropy::set_entropy_source()`. Failures of the OS are reported when
+/// the salt is drawn.
+#[cfg(feature="simple")]
+pub(crate) fn os_rng() -> entropy::SystemRng {
+    entropy::SystemRng::new()
+}
+
+/// Characters of the salts generated by Django and Werkzeug.
+#[cfg(feature="simple")]
+const ALNUM: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Generate a salt of `len` characters chosen uniformly from `chars`, like
+/// the `get_random_string()` of Django and the `gen_salt()` of Werkzeug.
+#[cfg(feature="simple")]
+fn text_salt<R: RngCore>(chars: &[u8], len: usize, rng: &mut R)
+    -> Result<String, HashError>
+{
+    // bytes at or above the largest multiple of `chars.len()` would bias the
+    // choice towards the first characters
+    let limit = 256 - 256 % chars.len();
+    let mut salt = String::with_capacity(len);
+    let mut byte = [0u8; 1];
+    while salt.len() < len {
+        rng.try_fill_bytes(&mut byte)?;
+        if (byte[0] as usize) < limit {
+            salt.push(chars[byte[0] as usize % chars.len()] as char);
+        }
+    }
+    Ok(salt)
+}
+
+#[cfg(feature="simple-verify")]
+fn encode_rscrypt(params: &Params, salt: &[u8], dk: &[u8]) -> String {
+    encode_rscrypt_config(params, salt, dk, false)
+}
+
+#[cfg(feature="simple-verify")]
+fn encode_rscrypt_config(
+    params: &Params, salt: &[u8], dk: &[u8], url_safe: bool,
+) -> String {
+    // the URL-safe fields are not padded
+    let encode = |bytes: &[u8]| if url_safe {
+        Base64UrlUnpadded::encode_string(bytes)
+    } else {
+        Base64::encode_string(bytes)
+    };
+    // usually 128 bytes is enough
+    let mut result = String::with_capacity(128);
+    result.push_str("$rscrypt$");
+    if params.r < 256 && params.p < 256 {
+        result.push_str("0$");
+        let mut tmp = [0u8; 3];
+        tmp[0] = params.log_n;
+        tmp[1] = params.r as u8;
+        tmp[2] = params.p as u8;
+        result.push_str(&encode(&tmp));
+    } else {
+        result.push_str("1$");
+        let mut tmp = [0u8; 9];
+        tmp[0] = params.log_n;
+        LittleEndian::write_u32(&mut tmp[1..5], params.r);
+        LittleEndian::write_u32(&mut tmp[5..9], params.p);
+        result.push_str(&encode(&tmp));
+    }
+    result.push('$');
+    result.push_str(&encode(salt));
+    result.push('$');
+    result.push_str(&encode(dk));
+    result.push('$');
+
+    result
+}
+
+/// The field which follows the header of an rscrypt version 2 string, with
+/// the flag announcing it. Without `simple`, which creates the others,
+/// only the extensions `ScryptHash` keeps are encoded again.
+#[cfg(feature="simple-verify")]
+#[cfg_attr(not(feature="simple"), allow(dead_code))]
+#[derive(Clone, Copy)]
+pub(crate) enum V2Extension {
+    None,
+    /// The id of the pepper mixed into the password.
+    Pepper(u8),
+    /// The threshold of pre-hashing.
+    Prehash(u16),
+    /// The id of the client scheme.
+    ClientScheme(u8),
+    /// The iterations of the final PBKDF2, above 1.
+    FinalRounds(u32),
+    /// The day the hash was created on, in days since the Unix epoch.
+    Created(u32),
+}
+
+/// Encode an rscrypt version 2 string, with `flags`, the flags without a
+/// field (`V2_ASSOCIATED`, `V2_BOUND` and `V2_DERIVED`), and the field of `extension`.
+/// The caller computed `dk` with the salt of `V2_BOUND`.
+#[cfg(feature="simple-verify")]
+fn encode_rscrypt_v2_with(
+    params: &Params, salt: &[u8], dk: &[u8], flags: u8, extension: V2Extension,
+) -> String {
+    assert!(rscrypt_v2_supports(salt, dk), "salt or hash too long");
+    let (header, len) = v2_header(params, salt.len(), dk.len(), flags, extension);
+    encode_rscrypt_v2_header(&header[..len], salt, dk)
+}
+
+/// The header of an rscrypt version 2 string and its length, see
+/// `encode_rscrypt_v2_with`.
+#[cfg(feature="simple-verify")]
+fn v2_header(
+    params: &Params, salt_len: usize, dk_len: usize, flags: u8, extension: V2Extension,
+) -> ([u8; V2_HEADER_LEN + 4], usize) {
+    let mut header = [0u8; V2_HEADER_LEN + 4];
+    header[0] = params.log_n;
+    LittleEndian::write_u32(&mut header[1..5], params.r);
+    LittleEndian::write_u32(&mut header[5..9], params.p);
+    header[9] = salt_len as u8;
+    header[10] = dk_len as u8;
+    header[11] = flags;
+    let len = match extension {
+        V2Extension::Pepper(id) => {
+            header[11] |= V2_PEPPERED;
+            header[12] = id;
+            V2_HEADER_LEN + 1
+        }
+        V2Extension::Prehash(threshold) => {
+            header[11] |= V2_PREHASHED;
+            LittleEndian::write_u16(&mut header[12..14], threshold);
+            V2_HEADER_LEN + 2
+        }
+        V2Extension::ClientScheme(id) => {
+            header[11] |= V2_CLIENT_SCHEME;
+            header[12] = id;
+            V2_HEADER_LEN + 1
+        }
+        V2Extension::FinalRounds(c) => {
+            header[11] |= V2_FINAL_ROUNDS;
+            LittleEndian::write_u32(&mut header[12..16], c);
+            V2_HEADER_LEN + 4
+        }
+        V2Extension::Created(days) => {
+            header[11] |= V2_CREATED;
+            LittleEndian::write_u32(&mut header[12..16], days);
+            V2_HEADER_LEN + 4
+        }
+        V2Extension::None => V2_HEADER_LEN,
+    };
+    (header, len)
+}
+
+/// The salt scrypt computes the string of `encode_rscrypt_v2_with` with:
+/// `salt`, bound to the header if `flags` has `V2_BOUND`.
+#[cfg(feature="simple-verify")]
+fn v2_salt(params: &Params, salt: &[u8], dk_len: usize, flags: u8, extension: V2Extension)
+    -> Vec<u8>
+{
+    if flags & V2_BOUND == 0 { return salt.to_vec(); }
+    let (header, len) = v2_header(params, salt.len(), dk_len, flags, extension);
+    bound_salt(&header[..len], salt).to_vec()
+}
+
+/// Encode an rscrypt version 2 string of the encoded `header`.
+#[cfg(feature="simple-verify")]
+fn encode_rscrypt_v2_header(header: &[u8], salt: &[u8], dk: &[u8]) -> String {
+    let mut result = String::with_capacity(128);
+    result.push_str("$rscrypt$2$");
+    result.push_str(&Base64Unpadded::encode_string(header));
+    result.push('$');
+    result.push_str(&Base64Unpadded::encode_string(salt));
+    result.push('$');
+    result.push_str(&Base64Unpadded::encode_string(dk));
+
+    result
+}
+
+/// `scrypt_check` compares a password against the result of a previous call
+/// to scrypt_simple and returns `Ok(())` if the passed in password hashes to
+/// the same value, `Err(CheckError::HashMismatch)` if hashes have
+/// different values, and `Err(CheckError::InvalidFormat)` if `hashed_value`
+/// has an invalid format. An empty hash field is reported as
+/// `Err(CheckError::InvalidHashLen)`.
+///
+/// rscrypt strings of versions 0 and 1 (`scrypt_simple()`) and 2
+/// (`scrypt_simple_v2()`) are accepted, later versions are reported as
+/// `Err(CheckError::UnsupportedVersion)`. The final `$` of versions 0 and 1
+/// may be omitted. Their fields must be canonical base64, otherwise
+/// `Err(CheckError::NonCanonicalBase64)` names the offending field.
+///
+/// Fields of rscrypt strings may also use the URL-safe alphabet without
+/// padding, as written by `scrypt_simple_url_safe()`. The alphabet applies
+/// to the whole string: it is URL-safe if any field contains `-` or `_`, or
+/// if no field is padded although a string of version 0 or 1 needs padding.
+/// Strings mixing `+` or `/` with the URL-safe alphabet are rejected.
+///
+/// Versions 0 and 1 are the format of `scrypt_simple()` of rust-crypto
+/// 0.2.x, its strings verify unchanged, including empty passwords and `r`
+/// or `p` of 256 and above in version 1 (see `tests/rust_crypto.rs`).
+///
+/// Besides the rscrypt strings, PHC strings as
+/// returned by `scrypt_simple_phc()` and other scrypt implementations are
+/// accepted. Omitted PHC parameters default to `ln=15`, `r=8` and `p=1`.
+/// The `$7$` strings of libxcrypt and `crypt7::crypt()` and the `$s0$`
+/// strings of Java's Lambdaworks `SCryptUtil`, the `scrypt$` strings of
+/// Django's `ScryptPasswordHasher`, the `scrypt:` strings of Werkzeug,
+/// Cisco IOS type 9 (`$9$`) secrets, the hex strings of
+/// `scrypt_simple_hex()`, the `SCRYPT:` strings of Perl's `Crypt::ScryptKDF`
+/// the `<N>$<r>$<p>$` strings of PHP's `php-scrypt` and Go's `simple-scrypt`
+/// and the hex parameters of the Ruby gem `scrypt` are accepted as well.
+///
+/// `ScryptHash::parse()` gives access to the parameters, salt and hash.
+///
+/// The cost of the verification is taken from `hashed_value` and not
+/// limited. Use a `Verifier` with a `ParamsPolicy` for hashes which may have
+/// been written by an attacker. Passwords longer than `DEFAULT_MAX_PASSWORD_LEN`
+/// bytes are rejected with `Err(CheckError::PasswordTooLong)` before
+/// `hashed_value` is parsed, like by all functions of the `scrypt_check`
+/// family, `Verifier` and `SimpleConfig` can raise or remove the limit.
+///
+/// Malformed rscrypt strings are rejected after the same steps whichever
+/// field is malformed: every field is checked and decoded before the first
+/// error is reported. The time still distinguishes strings of another or
+/// no known prefix, strings of the other formats, which stop at their
+/// first error, and the lengths of the fields. For well-formed strings the
+/// computation of scrypt dominates, the time tells them apart from
+/// malformed ones, but not a wrong password from a right one.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - hashed_value - A string representing a hashed password returned
+///   by `scrypt_simple()` or `scrypt_simple_phc()`
+#[cfg(feature="simple-verify")]
+pub fn scrypt_check(password: &str, hashed_value: &str)
+    -> Result<(), CheckError>
+{
+    scrypt_check_bytes(password.as_bytes(), hashed_value)
+}
+
+/// `scrypt_check_bytes` works like `scrypt_check`, but takes the password
+/// as raw bytes, e.g. when it is not valid UTF-8.
+///
+/// # Arguments
+/// - password - The password to process as a byte slice
+/// - hashed_value - A string representing a hashed password returned
+///   by `scrypt_simple()` or `scrypt_simple_phc()`
+#[cfg(feature="simple-verify")]
+pub fn scrypt_check_bytes(password: &[u8], hashed_value: &str)
+    -> Result<(), CheckError>
+{
+    check(password, hashed_value).map(|_| ())
+}
+
+/// `scrypt_check_password` works like `scrypt_check`, but takes a
+/// `Password`.
+#[deprecated(note = "use `Verifier::verify_bytes` with `Password::as_bytes`")]
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn scrypt_check_password(password: &Password, hashed_value: &str)
+    -> Result<(), CheckError>
+{
+    Verifier::builder().build().verify_bytes(password.as_bytes(), hashed_value)
+}
+
+/// Proof that a password matched its hash, returned by
+/// `Verifier::verify_token()`.
+///
+/// Functions which must only run after a successful verification, e.g. the
+/// creation of a session, take a `PasswordVerified` argument, so that
+/// calling them without verifying, or after ignoring an `Err`, does not
+/// compile. The token is zero-sized, not `Clone` and only created by this
+/// crate, one verification gives one token:
+///
+/// ```
+/// # use scrypt::{PasswordVerified, Verifier};
+/// struct Session;
+///
+/// fn create_session(_user: &str, _proof: PasswordVerified) -> Session {
+///     Session
+/// }
+///
+/// # let stored = "$rscrypt$0$BAgB$AAECAwQFBgcICQoLDA0ODw==$YS0n/chNhzpLhADNCgDufxTTrJzNvexT9eayccEeaLc=$";
+/// let proof = Verifier::builder().build().verify_token("hunter2", &stored)?;
+/// let session = create_session("alice", proof);
+/// # Ok::<(), scrypt::errors::CheckError>(())
+/// ```
+///
+/// ```compile_fail
+/// # use scrypt::PasswordVerified;
+/// let forged = PasswordVerified { _private: () };
+/// ```
+///
+/// ```compile_fail
+/// # use scrypt::PasswordVerified;
+/// # fn reuse(proof: PasswordVerified) {
+/// let copy = proof.clone();
+/// # }
+/// ```
+#[cfg(all(feature="simple-verify", feature="std"))]
+#[must_use = "the password is only verified if the token is used"]
+#[derive(Debug)]
+pub struct PasswordVerified {
+    _private: (),
+}
+
+#[cfg(all(feature="simple-verify", feature="std"))]
+impl PasswordVerified {
+    /// **INSECURE, FOR TESTS ONLY.** A token without a verification, for
+    /// the tests of functions taking a `PasswordVerified`, with the
+    /// `test-util` feature. Never call it outside of tests: it defeats the
+    /// purpose of the token.
+    #[cfg(any(test, feature="test-util"))]
+    pub fn insecure_for_tests() -> PasswordVerified {
+        PasswordVerified { _private: () }
+    }
+}
+
+/// `scrypt_check_token` works like `scrypt_check_bytes`, but returns a
+/// `PasswordVerified` token if the password matches.
+///
+/// # Arguments
+/// - password - The password to process as a byte slice
+/// - hashed_value - A string representing a hashed password returned
+///   by `scrypt_simple()` or `scrypt_simple_phc()`
+///
+/// # Return
+/// `Ok(PasswordVerified)` if the password matches, the errors of
+/// `scrypt_check` otherwise.
+#[deprecated(note = "use `Verifier::verify_token`")]
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn scrypt_check_token<P: AsRef<[u8]>>(password: P, hashed_value: &str)
+    -> Result<PasswordVerified, CheckError>
+{
+    Verifier::builder().build().verify_token(password, hashed_value)
+}
+
+/// `scrypt_check_detailed` works like `scrypt_check`, and reports the
+/// format, parameters and hash length of `hashed_value` if the password
+/// matches, e.g. to track the migration to new parameters without parsing
+/// the string again.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - hashed_value - A string representing a hashed password returned
+///   by `scrypt_simple()` or `scrypt_simple_phc()`
+///
+/// # Return
+/// `Ok(VerifiedWith)` if the password matches, the errors of
+/// `scrypt_check` otherwise.
+#[deprecated(note = "use `Verifier::verify_detailed`")]
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn scrypt_check_detailed(password: &str, hashed_value: &str)
+    -> Result<VerifiedWith, CheckError>
+{
+    Verifier::builder().build().verify_detailed(password, hashed_value)
+}
+
+/// `scrypt_check_peppered` works like `scrypt_check`, and also verifies the
+/// strings of `scrypt_simple_peppered()`. `lookup` returns the key of a
+/// pepper id, it is only called for peppered strings.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - hashed_value - A string representing a hashed password
+/// - lookup - Returns the key of a pepper id, `None` if it is unknown
+///
+/// # Return
+/// `Err(CheckError::NeedsPepper)` if `lookup` does not know the pepper,
+/// the errors of `scrypt_check` otherwise.
+#[deprecated(note = "use `Verifier::verify_with_keys`")]
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn scrypt_check_peppered<F, K>(password: &str, hashed_value: &str, lookup: F)
+    -> Result<(), CheckError>
+    where F: FnOnce(u8) -> Option<K>, K: AsRef<[u8]>
+{
+    let keys = Lookup(Cell::new(Some(lookup)));
+    Verifier::builder().build().verify_with_keys(password, hashed_value, &keys)
+}
+
+/// The `lookup` of `scrypt_check_peppered` as a `KeyProvider`, which is
+/// called at most once.
+#[cfg(all(feature="simple-verify", feature="std"))]
+struct Lookup<F>(Cell<Option<F>>);
+
+#[cfg(all(feature="simple-verify", feature="std"))]
+impl<F, K> KeyProvider for Lookup<F> where F: FnOnce(u8) -> Option<K>, K: AsRef<[u8]> {
+    fn key(&self, id: u8, f: &mut dyn FnMut(&[u8])) -> Result<(), KeyError> {
+        let lookup = self.0.take().ok_or(KeyError::Unavailable)?;
+        lookup(id).map(|key| f(key.as_ref())).ok_or(KeyError::UnknownId)
+    }
+}
+
+/// `scrypt_check_with_peppers` works like `scrypt_check_peppered`, with the
+/// keys of `peppers`, e.g. to verify hashes of old keys during a rotation.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - hashed_value - A string representing a hashed password
+/// - peppers - The keys of all pepper ids in use
+///
+/// # Return
+/// `Err(CheckError::NeedsPepper)` if `peppers` has no key for the id of
+/// `hashed_value`, the errors of `scrypt_check` otherwise.
+#[deprecated(note = "use `Verifier::verify_with_keys`")]
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn scrypt_check_with_peppers(password: &str, hashed_value: &str, peppers: &PepperSet)
+    -> Result<(), CheckError>
+{
+    Verifier::builder().build().verify_with_keys(password, hashed_value, peppers)
+}
+
+/// `scrypt_check_ad` verifies the strings of `scrypt_simple_ad()`, which
+/// are bound to the associated data `ad`.
+///
+/// Strings without the binding are rejected like a wrong `ad`, otherwise
+/// any string of `scrypt_simple()` could be copied into the context.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - hashed_value - A string of `scrypt_simple_ad()`
+/// - ad - The associated data the string was created with
+///
+/// # Return
+/// `Ok(())` if password and `ad` match, `Err(CheckError::HashMismatch)` if
+/// either does not or if `hashed_value` is not bound to associated data,
+/// `Err(CheckError::NeedsPepper)` for peppered strings and the errors of
+/// `scrypt_check` for malformed strings.
+#[deprecated(note = "use `Verifier::verify_with_ad`")]
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn scrypt_check_ad(password: &str, hashed_value: &str, ad: &[u8])
+    -> Result<(), CheckError>
+{
+    Verifier::builder().build().verify_with_ad(password, hashed_value, ad)
+}
+
+/// `scrypt_check_prehashed` works like `scrypt_check`, with the limit
+/// `limits.max_len` on the length of the password instead of
+/// `DEFAULT_MAX_PASSWORD_LEN`, for the strings of
+/// `scrypt_simple_prehashed()`. Passwords longer than the limit are
+/// rejected before `hashed_value` is parsed, whatever its format, so they
+/// cost no more than a short one. The threshold of pre-hashing is that
+/// stored in the string, `limits.prehash_above` is not used.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - hashed_value - A string representing a hashed password
+/// - limits - The maximal length of the password
+///
+/// # Return
+/// `Err(CheckError::PasswordTooLong)` if the password is too long, the
+/// errors of `scrypt_check` otherwise.
+#[deprecated(note = "use `VerifierBuilder::max_password_len` and `Verifier::verify`")]
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn scrypt_check_prehashed(password: &str, hashed_value: &str, limits: &LengthPolicy)
+    -> Result<(), CheckError>
+{
+    Verifier::builder().max_password_len(Some(limits.max_len)).build()
+        .verify(password, hashed_value)
+}
+
+/// `check_and_derive` works like `scrypt_check`, and returns the key of
+/// `key_len` bytes `hash_and_derive()` returned with `stored` if the
+/// password matches.
+///
+/// The key follows the hash in the output of scrypt. Only the strings of
+/// `hash_and_derive()` are accepted, those of other functions and formats
+/// never returned a key.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - stored - A string of `hash_and_derive()`
+/// - key_len - The length of the key, as passed to `hash_and_derive()`
+///
+/// # Return
+/// The key if the password matches. It will return
+/// `Err(CheckError::UnsupportedFormat)` if `stored` is not a string of
+/// `hash_and_derive()`, `Err(CheckError::InvalidOutputLen)` if `key_len` is
+/// 0 or scrypt can not produce the hash and the key, and the errors of
+/// `scrypt_check` otherwise.
+#[cfg(feature="simple-verify")]
+pub fn check_and_derive(password: &str, stored: &str, key_len: usize)
+    -> Result<DerivedKey, CheckError>
+{
+    check_default_len(password.as_bytes())?;
+    let hash = parse_checked(stored)?;
+    observer::check_version(hash.format(), hash.format_version());
+    let v2 = hash.v2_options();
+    if !v2.derived { Err(CheckError::UnsupportedFormat)?; }
+    let salt = v2.scrypt_salt(&hash.params(), hash.salt(), hash.hash_bytes().len(), &[]);
+    format::verify_and_derive(
+        password.as_bytes(), &hash.params(), &salt, hash.hash_bytes(), key_len)
+}
+
+/// `scrypt_check_wrapped` verifies the strings of `wrap_legacy_hash()`: the
+/// inner digest of `password` is checked against the wrapped rscrypt
+/// string.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - hashed_value - A string of `wrap_legacy_hash()`
+///
+/// # Return
+/// `Ok(())` if the password matches, `Err(CheckError::UnsupportedFormat)`
+/// if the inner digest is unknown and the errors of `scrypt_check`
+/// otherwise. Only rscrypt versions 0 and 1 are wrapped.
+#[deprecated(note = "use `VerifierBuilder::legacy_wrapped` and `Verifier::verify`")]
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn scrypt_check_wrapped(password: &str, hashed_value: &str)
+    -> Result<(), CheckError>
+{
+    if !hashed_value.starts_with(WRAPPED_PREFIX) { Err(CheckError::InvalidFormat)?; }
+    Verifier::builder().legacy_wrapped(true).build().verify(password, hashed_value)
+}
+
+/// Split a string of `wrap_legacy_hash` into the inner digest and the
+/// wrapped rscrypt string.
+#[cfg(feature="simple-verify")]
+fn unwrap_legacy(hashed_value: &str) -> Result<(LegacyDigest, String), CheckError> {
+    if !hashed_value.starts_with(WRAPPED_PREFIX) { Err(CheckError::InvalidFormat)?; }
+    let rest = &hashed_value[WRAPPED_PREFIX.len()..];
+    let end = rest.find('$').ok_or(CheckError::InvalidFormat)?;
+    let inner = LegacyDigest::from_name(&rest[..end]).ok_or(CheckError::UnsupportedFormat)?;
+    Ok((inner, format!("$rscrypt{}", &rest[end..])))
+}
+
+/// The prefix of the strings of `wrap_legacy_hash`, the name of the inner
+/// digest follows.
+#[cfg(feature="simple-verify")]
+const WRAPPED_PREFIX: &str = "$rscrypt-wrapped$";
+
+/// The decoded fields of `hashed_value` if it is an rscrypt string with a
+/// pepper or a client scheme, `None` for other strings.
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub(crate) fn split_input(hashed_value: &str) -> Result<Option<InputHash>, CheckError> {
+    let fields = match split_rscrypt(hashed_value) {
+        Ok(fields) => fields,
+        Err(_) => return Ok(None),
+    };
+    let extension = match (fields.pepper, fields.client_scheme) {
+        (Some(id), _) => V2Extension::Pepper(id),
+        (None, Some(id)) => V2Extension::ClientScheme(id),
+        (None, None) => return Ok(None),
+    };
+    let (params, salt, hash) = decode_rscrypt_fields(&fields)?;
+    if fields.associated { Err(CheckError::NeedsAssociatedData)?; }
+    let scrypt_salt = fields.scrypt_salt(salt.clone());
+    Ok(Some(InputHash {
+        extension,
+        parts: HashParts { params, salt, hash },
+        scrypt_salt,
+        bound: fields.bound.is_some(),
+    }))
+}
+
+/// An rscrypt string of a password combined with an input it does not
+/// hold, see `split_input`.
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub(crate) struct InputHash {
+    /// The pepper or the client scheme.
+    pub(crate) extension: V2Extension,
+    /// The stored parameters, salt and hash.
+    pub(crate) parts: HashParts,
+    /// The salt scrypt computed the hash with, see `scrypt_simple_bound`.
+    pub(crate) scrypt_salt: Vec<u8>,
+    /// If the header is bound into the salt.
+    bound: bool,
+}
+
+#[cfg(all(feature="simple-verify", feature="std"))]
+impl InputHash {
+    /// Encode the string again, in its canonical form.
+    pub(crate) fn encode(&self) -> String {
+        let HashParts { ref params, ref salt, ref hash } = self.parts;
+        let flags = if self.bound { V2_BOUND } else { 0 };
+        encode_rscrypt_v2_with(params, salt, hash, flags, self.extension)
+    }
+
+    /// Check if `input`, the password with the pepper mixed in or the
+    /// client-side digest, hashes to the stored value.
+    pub(crate) fn verify(&self, input: &[u8]) -> Result<(), CheckError> {
+        let HashParts { ref params, ref hash, .. } = self.parts;
+        format::verify(input, params, &self.scrypt_salt, hash, &mut vec![0u8; hash.len()])
+    }
+}
+
+/// Verify `password` against the fields of a peppered rscrypt string and
+/// return its parameters, salt and hash.
+#[cfg(feature="simple")]
+fn verify_peppered(password: &str, fields: &RscryptFields, key: &[u8])
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    let (params, salt, hash) = decode_rscrypt_fields(fields)?;
+    if fields.associated { Err(CheckError::NeedsAssociatedData)?; }
+    let prehashed = pepper::prehash(key, password.as_bytes());
+    let scrypt_salt = fields.scrypt_salt(salt.clone());
+    format::verify(&prehashed, &params, &scrypt_salt, &hash, &mut vec![0u8; hash.len()])?;
+    Ok((params, salt, hash))
+}
+
+/// `scrypt_check_strict` works like `scrypt_check`, but rejects rscrypt
+/// strings of versions 0 and 1 unless their salt is 16 and their hash 32
+/// bytes long, as produced by `scrypt_simple()`. The lengths are checked
+/// before the hash is computed. Version 2 strings declare their lengths
+/// and other formats are checked like by `scrypt_check`.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - hashed_value - A string representing a hashed password returned
+///   by `scrypt_simple()`
+///
+/// # Return
+/// `Err(CheckError::UnexpectedFieldLen)` for other lengths, the errors of
+/// `scrypt_check` otherwise.
+#[deprecated(note = "use `VerifierBuilder::default_lengths` and `Verifier::verify`")]
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn scrypt_check_strict(password: &str, hashed_value: &str)
+    -> Result<(), CheckError>
+{
+    Verifier::builder().default_lengths(true).build().verify(password, hashed_value)
+}
+
+/// Upper bounds on the cost of verifying a hash, see
+/// `BatchOptions::limits`.
+#[cfg(feature="simple-verify")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct VerifyLimits {
+    /// Bytes `scrypt` may allocate mixing one lane at a time,
+    /// `Params::serial_memory()`.
+    pub max_memory: u64,
+    /// Salsa20/8 core invocations `scrypt` may perform, `4 * N * r * p`.
+    pub max_ops: u64,
+}
+
+/// The cost of verifying a stored hash, see `cost_of`.
+#[cfg(feature="simple-verify")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CostEstimate {
+    /// Bytes `scrypt` allocates mixing one lane at a time,
+    /// `Params::serial_memory()`, which the limits compare against.
+    pub memory_bytes: u128,
+    /// Bytes `scrypt` allocates on this machine, `Params::required_memory()`,
+    /// more than `memory_bytes` with the `parallel` feature.
+    pub parallel_memory_bytes: u128,
+    /// Salsa20/8 core invocations `scrypt` performs, `4 * N * r * p`.
+    pub approx_ops: u128,
+    /// The parameters of the hash.
+    pub params: Params,
+}
+
+/// `cost_of` reports what verifying `stored` would take, without computing
+/// scrypt, e.g. to queue expensive verifications separately. Only the
+/// parameters are parsed and checked, so it takes microseconds whatever
+/// they are.
+///
+/// All formats of `scrypt_check` are supported, as well as the strings of
+/// `scrypt_simple_peppered()`, `scrypt_simple_ad()`,
+/// `scrypt_simple_prehashed()` and `wrap_legacy_hash()`.
+///
+/// `memory_bytes` is the same on every machine, like the limits of
+/// `ParamsPolicy` and `VerifyLimits` it is compared with;
+/// `parallel_memory_bytes` adds the lanes mixed at once on this one.
+///
+/// # Arguments
+/// - stored - A hash string of any supported format
+///
+/// # Return
+/// `Ok(CostEstimate)` with the requirements of the parameters, the parse
+/// errors of `scrypt_check` otherwise.
+#[cfg(feature="simple-verify")]
+pub fn cost_of(stored: &str) -> Result<CostEstimate, CheckError> {
+    let params = if stored.starts_with(WRAPPED_PREFIX) {
+        split_rscrypt(&unwrap_legacy(stored)?.1)?.params
+    } else if let Some(Format::Rscrypt) = Format::detect(stored) {
+        // the parameters of peppered and bound strings are known without
+        // the pepper or the associated data
+        split_rscrypt(stored)?.params
+    } else {
+        parse_checked(stored)?.params()
+    };
+    Ok(CostEstimate {
+        memory_bytes: params.serial_memory(),
+        parallel_memory_bytes: params.required_memory(),
+        approx_ops: params.salsa_ops(),
+        params,
+    })
+}
+
+/// Whether the password of a `VerifyReport` matched.
+#[cfg(all(feature="simple-verify", feature="std"))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum VerifyOutcome {
+    /// The password matches the hash.
+    Match,
+    /// The password does not match the hash.
+    Mismatch,
+}
+
+/// The outcome and the cost of a verification by `Verifier::verify_report`.
+#[cfg(all(feature="simple-verify", feature="std"))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct VerifyReport {
+    /// Whether the password matched.
+    pub outcome: VerifyOutcome,
+    /// The parameters of the hash.
+    pub params: Params,
+    /// The time the verification took, including parsing.
+    pub elapsed: Duration,
+    /// Bytes `scrypt` allocates mixing one lane at a time,
+    /// `Params::serial_memory()`.
+    pub memory_bytes: u128,
+    /// Bytes `scrypt` allocated, `Params::required_memory()`, more than
+    /// `memory_bytes` with the `parallel` feature.
+    pub parallel_memory_bytes: u128,
+}
+
+/// `scrypt_check_report` works like `scrypt_check`, but also reports how
+/// long the verification took and what it cost, e.g. for anomaly detection
+/// in logs. A mismatch is part of the report, so that failed attempts are
+/// timed as well.
+///
+/// `Instant` is used for the timing, which is not available on
+/// `wasm32-unknown-unknown`.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - stored - A hash string of any format of `scrypt_check`
+///
+/// # Return
+/// `Ok(VerifyReport)` for a match and a mismatch, the other errors of
+/// `scrypt_check` for malformed hashes.
+#[deprecated(note = "use `Verifier::verify_report`")]
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn scrypt_check_report(password: &str, stored: &str) -> Result<VerifyReport, CheckError> {
+    Verifier::builder().build().verify_report(password, stored)
+}
+
+/// `scrypt_check_with_limits` works like `scrypt_check`, but refuses hashes
+/// whose parameters exceed `limits`, before any memory for scrypt is
+/// allocated.
+///
+/// The parameters are part of the hash string, so whoever can write stored
+/// hashes can make `scrypt_check` allocate gigabytes and run for minutes.
+/// Hashes which are not exclusively produced by the application, e.g. of
+/// federated sources, should be checked with limits. `Verifier` applies
+/// further rules, e.g. allowed formats.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - hashed_value - A string representing a hashed password returned
+///   by `scrypt_simple()`
+/// - limits - The most memory and work a verification may take
+///
+/// # Return
+/// `Err(CheckError::CostExceedsLimit)` if the parameters of `hashed_value`
+/// exceed `limits`, the errors of `scrypt_check` otherwise.
+#[deprecated(note = "use `VerifierBuilder::require` and `Verifier::verify`")]
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn scrypt_check_with_limits(
+    password: &str, hashed_value: &str, limits: &VerifyLimits,
+) -> Result<(), CheckError> {
+    let policy = ParamsPolicy {
+        max_memory: limits.max_memory,
+        max_ops: limits.max_ops,
+        ..ParamsPolicy::default()
+    };
+    Verifier::builder().require(policy).build().verify(password, hashed_value)
+        .map_err(|e| match e {
+            CheckError::Refused(_) => CheckError::CostExceedsLimit,
+            e => e,
+        })
+}
+
+/// `verify_or_dummy` works like `scrypt_check` for a stored hash and does
+/// the same work for a user without one, so that login endpoints do not
+/// reveal which users exist by answering faster.
+///
+/// Without `stored` the password is hashed with `default_params` under a
+/// fixed salt into a 256-bit hash, like `scrypt_simple()`, and the result
+/// is discarded. `default_params` should be the parameters of most stored
+/// hashes for the timing to match.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - stored - The hash of the user, `None` if there is none
+/// - default_params - The parameters of the dummy computation
+///
+/// # Return
+/// `Err(CheckError::HashMismatch)` without `stored`, the result of
+/// `scrypt_check` otherwise.
+#[cfg(feature="simple-verify")]
+pub fn verify_or_dummy(
+    password: &str, stored: Option<&str>, default_params: &Params,
+) -> Result<(), CheckError> {
+    check_default_len(password.as_bytes())?;
+    match stored {
+        Some(hashed_value) => scrypt_check(password, hashed_value),
+        None => {
+            const DUMMY_SALT: [u8; 16] = *b"scrypt dummy sal";
+            let _ = format::verify(
+                password.as_bytes(), default_params, &DUMMY_SALT, &[0u8; 32],
+                &mut [0u8; 32],
+            );
+            Err(CheckError::HashMismatch)
+        }
+    }
+}
+
+/// Successful outcome of `Verifier::verify_strength` and
+/// `SimpleConfig::verify`.
+#[cfg(feature="simple-verify")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Verified {
+    /// The password matches and the hash satisfies the policy.
+    Ok,
+    /// The password matches, but the hash was computed with parameters
+    /// weaker than the policy. The password should be rehashed.
+    OkButWeak(Params),
+}
+
+/// `scrypt_check` with the limit `max` on the length of `password`, which
+/// also compares the parameters of `hashed_value` against `min_params`.
+/// The password is verified first: a mismatch is reported as
+/// `Err(CheckError::HashMismatch)` regardless of the parameter strength.
+#[cfg(feature="simple")]
+pub(crate) fn check_policy_limited(
+    password: &str, hashed_value: &str, min_params: &Params, max: Option<usize>,
+) -> Result<Verified, CheckError> {
+    let params = check_limited(password.as_bytes(), hashed_value, max)?.params();
+    if params.is_weaker_than(min_params) {
+        Ok(Verified::OkButWeak(params))
+    } else {
+        Ok(Verified::Ok)
+    }
+}
+
+/// `needs_rehash` checks if a stored hash falls short of the current policy
+/// without verifying a password, e.g. to upgrade hashes after raising the
+/// parameters.
+///
+/// A hash needs to be rehashed if its parameters are weaker than `desired`,
+/// as defined by `Params::is_weaker_than()`, or if its salt is shorter
+/// than 16 or its hash shorter than 32 bytes, the lengths `scrypt_simple()`
+/// produces. Parameters which differ from `desired` but are not weaker,
+/// e.g. a larger `N` for a smaller `r`, are accepted. Strings of a
+/// deprecated format version, rscrypt version 1, always need to be
+/// rehashed.
+///
+/// # Arguments
+/// - stored - A hash string in one of the formats of `Format`
+/// - desired - The parameters new hashes are computed with
+///
+/// # Return
+/// `Err(CheckError::UnsupportedFormat)` if the format of `stored` is
+/// unknown and `Err(CheckError::InvalidFormat)` if it is malformed.
+#[cfg(feature="simple-verify")]
+pub fn needs_rehash(stored: &str, desired: &Params) -> Result<bool, CheckError> {
+    let hash = ScryptHash::parse(stored)?;
+    Ok(observer::is_deprecated(hash.format(), hash.format_version())
+        || is_outdated(&hash.params(), hash.salt(), hash.hash_bytes(), desired))
+}
+
+/// `needs_rehash_for_year` works like `needs_rehash`, with the parameters
+/// of `Params::for_year(year)` as `desired`, for policies which require at
+/// least the parameters recommended in a year.
+///
+/// # Arguments
+/// - stored - A hash string in one of the formats of `Format`
+/// - year - The year of the parameters new hashes are computed with
+///
+/// # Return
+/// `Err(CheckError::UnknownYear)` if `Params::for_year` has no presets for
+/// `year` and the errors of `needs_rehash` otherwise.
+#[cfg(feature="simple-verify")]
+pub fn needs_rehash_for_year(stored: &str, year: u16) -> Result<bool, CheckError> {
+    let desired = Params::for_year(year).map_err(|_| CheckError::UnknownYear(year))?;
+    needs_rehash(stored, &desired)
+}
+
+/// `needs_rotation` checks if a stored hash is at least `max_age` old, e.g.
+/// for policies which require a new password after a number of months.
+///
+/// The age is counted from the start of the day recorded by
+/// `SimpleConfigBuilder::record_created_at`, in UTC, so a hash is never
+/// considered younger than it is. Hashes without the day, in any format,
+/// never need rotation, whose parameters are checked by `needs_rehash`.
+///
+/// # Arguments
+/// - stored - A hash string in one of the formats of `Format`
+/// - max_age - The age at which hashes need rotation
+///
+/// # Return
+/// `Err(CheckError::UnsupportedFormat)` if the format of `stored` is
+/// unknown and `Err(CheckError::InvalidFormat)` if it is malformed.
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn needs_rotation(stored: &str, max_age: Duration) -> Result<bool, CheckError> {
+    needs_rotation_at(stored, max_age, SystemTime::now())
+}
+
+/// Same as `needs_rotation`, but at the time `now` instead of the current
+/// time, e.g. for tests.
+#[cfg(all(feature="simple-verify", feature="std"))]
+pub fn needs_rotation_at(stored: &str, max_age: Duration, now: SystemTime)
+    -> Result<bool, CheckError>
+{
+    let days = match ScryptHash::parse(stored)?.created_at() {
+        Some(days) => days,
+        None => return Ok(false),
+    };
+    let created = UNIX_EPOCH + Duration::from_secs(days as u64 * SECS_PER_DAY);
+    // hashes of a day after `now` are not old
+    Ok(now.duration_since(created).map(|age| age >= max_age).unwrap_or(false))
+}
+
+/// Seconds of a day of the Unix time.
+#[cfg(all(feature="simple-verify", feature="std"))]
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The day of `time` in days since the Unix epoch, as recorded by
+/// `SimpleConfigBuilder::record_created_at`. Times before the epoch are
+/// day 0.
+#[cfg(feature="simple")]
+pub(crate) fn unix_days(time: SystemTime) -> u32 {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    (secs / SECS_PER_DAY) as u32
+}
+
+/// The policy of `needs_rehash`.
+#[cfg(feature="simple-verify")]
+fn is_outdated(params: &Params, salt: &[u8], hash: &[u8], desired: &Params)
+    -> bool
+{
+    params.is_weaker_than(desired) || salt.len() < 16 || hash.len() < 32
+}
+
+/// `scrypt_check_and_rehash` works like `scrypt_check`, and upgrades
+/// hashes which fall short of `desired` once the password is known to
+/// match.
+///
+/// A new hash is computed with `desired` if `needs_rehash()` is true for
+/// `stored`, or if `stored` is in a format of another implementation, e.g.
+/// Django or `$7$`. The new hash keeps the format of rscrypt (including
+/// version 2), PHC and hex strings, other formats are upgraded to the
+/// rscrypt strings of `scrypt_simple()`. Strings of the deprecated rscrypt
+/// version 1, and new hashes whose parameters would need it, are written
+/// in version 2. New hashes of strings which record
+/// the day they were created on record the current day. Strings of
+/// `hash_and_derive()` are never rehashed, a new salt or new parameters
+/// would change the key of `check_and_derive()`.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - stored - A string representing a hashed password
+/// - desired - The parameters new hashes are computed with
+///
+/// # Return
+/// `Ok(Some(String))` with the new hash to store if the password matches
+/// and `stored` is outdated, `Ok(None)` if it matches and `stored` is up to
+/// date and the errors of `scrypt_check` otherwise. No new hash is ever
+/// returned for a wrong password. `Err(CheckError::RehashFailed)` reports
+/// an unlikely `OsRng` failure after the password matched.
+#[cfg(feature="simple")]
+pub fn scrypt_check_and_rehash(password: &str, stored: &str, desired: &Params)
+    -> Result<Option<String>, CheckError>
+{
+    check_default_len(password.as_bytes())?;
+    let hash = parse_checked(stored)?;
+    hash.verify(password)?;
+    if !is_upgraded(&hash, desired) { return Ok(None); }
+    let mut rng = os_rng();
+    rehash(password, &hash, desired, &mut rng).map(Some)
+}
+
+/// Same as `scrypt_check_and_rehash`, but draws the new salt from the
+/// provided `rng` instead of `OsRng`.
+#[cfg(feature="simple")]
+pub fn scrypt_check_and_rehash_with_rng<R: RngCore + CryptoRng>(
+    password: &str, stored: &str, desired: &Params, rng: &mut R,
+) -> Result<Option<String>, CheckError> {
+    check_default_len(password.as_bytes())?;
+    let hash = parse_checked(stored)?;
+    hash.verify(password)?;
+    if !is_upgraded(&hash, desired) { return Ok(None); }
+    rehash(password, &hash, desired, rng).map(Some)
+}
+
+/// The policy of `scrypt_check_and_rehash`.
+#[cfg(feature="simple")]
+fn is_upgraded(hash: &ScryptHash, desired: &Params) -> bool {
+    if hash.v2_options().derived { return false; }
+    let own = matches!(hash.format(), Format::Rscrypt | Format::Phc | Format::Hex);
+    !own || observer::is_deprecated(hash.format(), hash.format_version())
+        || is_outdated(&hash.params(), hash.salt(), hash.hash_bytes(), desired)
+}
+
+/// Compute the new hash of `scrypt_check_and_rehash` in the format of
+/// `hash`, the password must have been verified against it.
+#[cfg(feature="simple")]
+fn rehash<R: RngCore>(
+    password: &str, hash: &ScryptHash, desired: &Params, rng: &mut R,
+) -> Result<String, CheckError> {
+    // the extensions of version 2 strings are kept, with the current day
+    let created_at = hash.created_at().map(|_| unix_days(SystemTime::now()));
+    let v2 = V2Options { created_at, ..hash.v2_options() };
+    let (salt, dk) = v2.hash(password.as_bytes(), desired, rng)
+        .map_err(|_| CheckError::RehashFailed)?;
+    Ok(match (hash.format(), hash.format_version()) {
+        (Format::Phc, _) => phc::encode(desired, &salt, &dk),
+        (Format::Hex, _) => hex_string::encode(desired, &salt, &dk),
+        // version 1 is deprecated, its parameters are written in version 2
+        (_, Some(1)) | (_, Some(2)) => v2.encode(desired, &salt, &dk),
+        _ if desired.r >= 256 || desired.p >= 256 => v2.encode(desired, &salt, &dk),
+        _ => encode_rscrypt(desired, &salt, &dk),
+    })
+}
+
+/// `scrypt_check_and_rehash_peppered` works like `scrypt_check_and_rehash`
+/// for peppered hashes, and moves them to the newest key of `peppers`.
+///
+/// A new hash is computed with `desired` and the newest pepper if `stored`
+/// was peppered with an older key, is not peppered, or if `needs_rehash()`
+/// would be true for its parameters and lengths, strings of
+/// `hash_and_derive()` are kept. New hashes are the strings of
+/// `scrypt_simple_peppered()`. If `peppers` is empty, this is
+/// `scrypt_check_and_rehash`.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - stored - A string representing a hashed password
+/// - desired - The parameters new hashes are computed with
+/// - peppers - The keys of all pepper ids in use
+///
+/// # Return
+/// `Ok(Some(String))` with the new hash to store if the password matches
+/// and `stored` is outdated, `Ok(None)` if it matches and `stored` is up to
+/// date and the errors of `Verifier::verify_with_keys` otherwise.
+/// `Err(CheckError::RehashFailed)` reports an unlikely `OsRng` failure after
+/// the password matched.
+#[cfg(feature="simple")]
+pub fn scrypt_check_and_rehash_peppered(
+    password: &str, stored: &str, desired: &Params, peppers: &PepperSet,
+) -> Result<Option<String>, CheckError> {
+    check_default_len(password.as_bytes())?;
+    let newest = match peppers.newest() {
+        Some(newest) => newest,
+        None => return scrypt_check_and_rehash(password, stored, desired),
+    };
+    if !is_repeppered(password, stored, desired, peppers, newest.id)? { return Ok(None); }
+    let mut rng = os_rng();
+    repepper(password, stored, desired, &newest, &mut rng).map(Some)
+}
+
+/// Same as `scrypt_check_and_rehash_peppered`, but draws the new salt from
+/// the provided `rng` instead of `OsRng`.
+#[cfg(feature="simple")]
+pub fn scrypt_check_and_rehash_peppered_with_rng<R: RngCore + CryptoRng>(
+    password: &str, stored: &str, desired: &Params, peppers: &PepperSet, rng: &mut R,
+) -> Result<Option<String>, CheckError> {
+    check_default_len(password.as_bytes())?;
+    let newest = match peppers.newest() {
+        Some(newest) => newest,
+        None => return scrypt_check_and_rehash_with_rng(password, stored, desired, rng),
+    };
+    if !is_repeppered(password, stored, desired, peppers, newest.id)? { return Ok(None); }
+    repepper(password, stored, desired, &newest, rng).map(Some)
+}
+
+/// Verify `password` against `stored` and apply the policy of
+/// `scrypt_check_and_rehash_peppered`.
+#[cfg(feature="simple")]
+fn is_repeppered(
+    password: &str, stored: &str, desired: &Params, peppers: &PepperSet, newest: u8,
+) -> Result<bool, CheckError> {
+    let (id, fields) = match split_rscrypt(stored).ok()
+        .and_then(|fields| fields.pepper.map(|id| (id, fields)))
+    {
+        Some(peppered) => peppered,
+        None => {
+            // unpeppered hashes are always upgraded, except the keys of
+            // `hash_and_derive()`
+            let hash = parse_checked(stored)?;
+            hash.verify(password)?;
+            return Ok(!hash.v2_options().derived);
+        }
+    };
+    let key = peppers.get(id).ok_or(CheckError::NeedsPepper(id))?;
+    let (params, salt, hash) = verify_peppered(password, &fields, key)?;
+    Ok(id != newest || is_outdated(&params, &salt, &hash, desired))
+}
+
+/// Compute the new hash of `scrypt_check_and_rehash_peppered`, the password
+/// must have been verified against `stored`, whose header stays bound.
+#[cfg(feature="simple")]
+fn repepper<R: RngCore + CryptoRng>(
+    password: &str, stored: &str, desired: &Params, pepper: &Pepper, rng: &mut R,
+) -> Result<String, CheckError> {
+    let bound = split_rscrypt(stored).map(|fields| fields.bound.is_some()).unwrap_or(false);
+    SimpleConfig::builder().params(*desired).pepper_id(pepper.id).bound(bound).build()
+        .and_then(|config| config.hash_with_keys_rng(password, pepper, rng))
+        .map_err(|_| CheckError::RehashFailed)
+}
+
+/// Successful outcome of `verify_any` and `Verifier::verify_detailed`.
+#[cfg(feature="simple-verify")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct VerifiedWith {
+    format: Format,
+    version: Option<u8>,
+    params: Params,
+    dk_len: usize,
+}
+
+#[cfg(feature="simple-verify")]
+impl VerifiedWith {
+    fn new(hash: &ScryptHash) -> VerifiedWith {
+        VerifiedWith {
+            format: hash.format(),
+            version: hash.format_version(),
+            params: hash.params(),
+            dk_len: hash.hash_bytes().len(),
+        }
+    }
+
+    /// The outcome of an rscrypt string which `ScryptHash` does not parse,
+    /// e.g. a peppered one.
+    #[cfg(feature="std")]
+    fn rscrypt(version: u8, params: &Params, hash: &[u8]) -> VerifiedWith {
+        VerifiedWith {
+            format: Format::Rscrypt,
+            version: Some(version),
+            params: *params,
+            dk_len: hash.len(),
+        }
+    }
+
+    /// The format of the verified hash string.
+    pub fn format(&self) -> Format { self.format }
+
+    /// The version of verified rscrypt strings, see
+    /// `ScryptHash::format_version()`.
+    pub fn format_version(&self) -> Option<u8> { self.version }
+
+    /// The parameters of the verified hash string.
+    pub fn params(&self) -> Params { self.params }
+
+    /// The length of the verified hash in bytes.
+    pub fn dk_len(&self) -> usize { self.dk_len }
+}
+
+/// `verify_any` compares a password against a hash string in any of the
+/// supported formats, detected from its prefix, and reports the format that
+/// matched, e.g. to re-encode hashes in other formats.
+///
+/// # Arguments
+/// - password - The password to process as a str
+/// - hashed_value - A hash string in one of the formats of `Format`
+///
+/// # Return
+/// `Ok(VerifiedWith)` if the password matches, `Err(CheckError::HashMismatch)`
+/// if it does not and `Err(CheckError::UnsupportedFormat)` if the format of
+/// `hashed_value` is unknown. Malformed strings of a known format are
+/// reported as `Err(CheckError::InvalidFormat)`.
+#[cfg(feature="simple-verify")]
+pub fn verify_any(password: &str, hashed_value: &str)
+    -> Result<VerifiedWith, CheckError>
+{
+    check_default_len(password.as_bytes())?;
+    let hash = ScryptHash::parse(hashed_value)?;
+    hash.verify(password)?;
+    Ok(VerifiedWith::new(&hash))
+}
+
+/// `canonicalize` re-encodes `stored` in the canonical form of its format,
+/// the form the `scrypt_simple` family writes, without the password, e.g.
+/// to normalize stored strings in a maintenance job. The result verifies
+/// the same passwords as `stored`.
+///
+/// rscrypt strings are repaired: missing, partial or superfluous padding,
+/// nonzero unused bits, the URL-safe alphabet, a missing or repeated
+/// trailing `$` and version 1 for `r` and `p` below 256 are written as
+/// `scrypt_simple` and `scrypt_simple_v2` would. Strings of the other
+/// formats are re-encoded like the `Display` of `ScryptHash`. Canonical
+/// strings are returned unchanged.
+///
+/// # Return
+/// The errors of `ScryptHash::parse` for strings which can not be
+/// repaired, except for the `NeedsPepper` and `NeedsAssociatedData` of
+/// peppered and bound strings.
+#[cfg(feature="simple-verify")]
+pub fn canonicalize(stored: &str) -> Result<String, CheckError> {
+    if Format::detect(stored) != Some(Format::Rscrypt) {
+        return Ok(ScryptHash::parse(stored)?.to_string());
+    }
+    let repaired = repair_rscrypt(stored).unwrap_or_else(|| stored.to_string());
+    match ScryptHash::parse(&repaired) {
+        Ok(hash) => Ok(hash.to_string()),
+        // only valid strings get this far, and version 2 is written canonical
+        Err(CheckError::NeedsPepper(_)) | Err(CheckError::NeedsAssociatedData) => Ok(repaired),
+        Err(e) => Err(e),
+    }
+}
+
+/// Re-encode the fields of an rscrypt string with the standard alphabet
+/// and the canonical padding, `None` if they can not be decoded.
+#[cfg(feature="simple-verify")]
+fn repair_rscrypt(stored: &str) -> Option<String> {
+    let parts: Vec<&str> = stored.trim_end_matches('$').split('$').collect();
+    if parts.len() != 6 { return None; }
+    let fields = &parts[3..];
+    let url_safe = fields.iter().any(|f| f.contains(&['-', '_'][..]));
+    let decode = |field: &str| ct_base64::decode(field.trim_end_matches('='), url_safe).ok();
+    let (header, salt, hash) = (decode(fields[0])?, decode(fields[1])?, decode(fields[2])?);
+    let params = match (parts[2], header.len()) {
+        ("0", 3) => Params::new(header[0], header[1] as u32, header[2] as u32).ok()?,
+        ("1", 9) => Params::new(header[0], LittleEndian::read_u32(&header[1..5]),
+            LittleEndian::read_u32(&header[5..9])).ok()?,
+        // the header also holds the lengths and flags, it is kept as it is
+        ("2", _) => {
+            let encode = |bytes: &[u8]| Base64Unpadded::encode_string(bytes);
+            return Some(format!("$rscrypt$2${}${}${}", encode(&header), encode(&salt),
+                encode(&hash)));
+        }
+        _ => return None,
+    };
+    Some(encode_rscrypt(&params, &salt, &hash))
+}
+
+#[cfg(feature="simple-verify")]
+fn check(password: &[u8], hashed_value: &str)
+    -> Result<VerifiedWith, CheckError>
+{
+    check_limited(password, hashed_value, Some(DEFAULT_MAX_PASSWORD_LEN))
+}
+
+/// `check` with the limit `max` on the length of `password`.
+#[cfg(feature="simple-verify")]
+pub(crate) fn check_limited(password: &[u8], hashed_value: &str, max: Option<usize>)
+    -> Result<VerifiedWith, CheckError>
+{
+    prehash::check_len(password, max)?;
+    let hash = parse_checked(hashed_value)?;
+    hash.verify_bytes(password)?;
+    Ok(VerifiedWith::new(&hash))
+}
+
+/// Reject passwords longer than `DEFAULT_MAX_PASSWORD_LEN` bytes.
+#[cfg(feature="simple-verify")]
+fn check_default_len(password: &[u8]) -> Result<(), prehash::TooLong> {
+    prehash::check_len(password, Some(DEFAULT_MAX_PASSWORD_LEN))
+}
+
+/// Parse `hashed_value` for the `scrypt_check` family.
+#[cfg(feature="simple-verify")]
+fn parse_checked(hashed_value: &str) -> Result<ScryptHash, CheckError> {
+    ScryptHash::parse(hashed_value).map_err(|e| match e {
+        // unknown strings are reported as malformed rscrypt strings, which
+        // `scrypt_check` always did
+        CheckError::UnsupportedFormat => CheckError::InvalidFormat,
+        e => e,
+    })
+}
+
+/// Decode an rscrypt string into parameters, salt and hash, for
+/// `Format::Rscrypt`. The strings of `decode_rscrypt_v2` whose hash is not
+/// scrypt of the password and the salt alone are `Unrepresentable`.
+#[cfg(feature="simple-verify")]
+fn decode_rscrypt(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    let (params, salt, hash, options) = decode_rscrypt_v2(hashed_value)?;
+    if options.changes_hash() { Err(CheckError::Unrepresentable)?; }
+    Ok((params, salt, hash))
+}
+
+/// Decode an rscrypt string into parameters, salt, hash and the extensions
+/// of version 2 which need no input besides the password, for
+/// `ScryptHash`.
+#[cfg(feature="simple-verify")]
+pub(crate) fn decode_rscrypt_v2(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>, V2Options), CheckError>
+{
+    let (fields, error) = split_rscrypt_all(hashed_value);
+    // salt and hash are decoded even if the string is malformed, see
+    // `split_rscrypt_all`
+    let decoded = decode_rscrypt_fields(&fields);
+    if let Some(e) = error { Err(e)?; }
+    // peppered strings are only verified with their keys, see `Verifier`
+    if let Some(id) = fields.pepper { Err(CheckError::NeedsPepper(id))?; }
+    if fields.associated { Err(CheckError::NeedsAssociatedData)?; }
+    if let Some(id) = fields.client_scheme { Err(CheckError::NeedsClientScheme(id))?; }
+    let (params, salt, hash) = decoded?;
+    Ok((params, salt, hash, V2Options::of(&fields)))
+}
+
+/// The extensions of an rscrypt version 2 string which `ScryptHash` and
+/// `ScryptHashRef` keep besides parameters, salt and hash, so that they
+/// verify them like `scrypt_check` and encode them again.
+#[cfg(feature="simple-verify")]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct V2Options {
+    /// The iterations of the final PBKDF2 if more than one, see
+    /// `scrypt_simple_final_rounds`.
+    pub(crate) final_rounds: Option<NonZeroU32>,
+    /// The threshold of pre-hashing, see `scrypt_simple_prehashed`.
+    pub(crate) prehash_above: Option<u16>,
+    /// If the header is bound into the salt, see `scrypt_simple_bound`.
+    pub(crate) bound: bool,
+    /// If the salt is bound to associated data, see `scrypt_simple_ad`.
+    pub(crate) associated: bool,
+    /// The day the hash was created on, see `ScryptHash::created_at`.
+    pub(crate) created_at: Option<u32>,
+    /// If a key follows the hash, see `hash_and_derive`.
+    pub(crate) derived: bool,
+    /// The id of the pepper mixed into the password, see
+    /// `scrypt_simple_peppered`.
+    pub(crate) pepper: Option<u8>,
+    /// The id of the scheme of the client-side digest, see
+    /// `scrypt_simple_client`.
+    pub(crate) client_scheme: Option<u8>,
+}
+
+#[cfg(feature="simple-verify")]
+impl V2Options {
+    pub(crate) fn of(fields: &RscryptFields) -> V2Options {
+        V2Options {
+            final_rounds: fields.final_rounds,
+            prehash_above: fields.prehash_above,
+            bound: fields.bound.is_some(),
+            associated: fields.associated,
+            created_at: fields.created_at,
+            derived: fields.derived,
+            pepper: fields.pepper,
+            client_scheme: fields.client_scheme,
+        }
+    }
+
+    /// If the hash is not scrypt of the password and the salt alone, so
+    /// that `HashParts` can not verify it.
+    pub(crate) fn changes_hash(&self) -> bool {
+        self.final_rounds.is_some() || self.prehash_above.is_some() || self.bound
+    }
+
+    /// If the password is combined with an input the string does not hold,
+    /// a pepper, associated data or a client-side digest, which `ScryptHash`
+    /// can not verify.
+    #[cfg(feature="simple")]
+    pub(crate) fn needs_input(&self) -> bool {
+        self.pepper.is_some() || self.associated || self.client_scheme.is_some()
+    }
+
+    /// The flags without a field and the extension of the header, of which
+    /// there is at most one.
+    fn header(&self) -> (u8, V2Extension) {
+        let flags = if self.bound { V2_BOUND } else { 0 }
+            | if self.associated { V2_ASSOCIATED } else { 0 }
+            | if self.derived { V2_DERIVED } else { 0 };
+        let extension = if let Some(c) = self.final_rounds {
+            V2Extension::FinalRounds(c.get())
+        } else if let Some(threshold) = self.prehash_above {
+            V2Extension::Prehash(threshold)
+        } else if let Some(id) = self.pepper {
+            V2Extension::Pepper(id)
+        } else if let Some(id) = self.client_scheme {
+            V2Extension::ClientScheme(id)
+        } else if let Some(days) = self.created_at {
+            V2Extension::Created(days)
+        } else {
+            V2Extension::None
+        };
+        (flags, extension)
+    }
+
+    /// The salt scrypt computes the hash with, see `v2_salt`, bound to the
+    /// associated data `ad` if the string is, see `scrypt_simple_ad`.
+    pub(crate) fn scrypt_salt(&self, params: &Params, salt: &[u8], dk_len: usize, ad: &[u8])
+        -> Vec<u8>
+    {
+        let (flags, extension) = self.header();
+        let salt = v2_salt(params, salt, dk_len, flags, extension);
+        if self.associated { pepper::prehash(&salt, ad).to_vec() } else { salt }
+    }
+
+    /// The password scrypt hashes for `password`, see `prehash::prehash`.
+    pub(crate) fn prehash<'a>(&self, password: &'a [u8], digest: &'a mut [u8; 32])
+        -> &'a [u8]
+    {
+        match self.prehash_above {
+            Some(threshold) => prehash::prehash(password, threshold, digest),
+            None => password,
+        }
+    }
+
+    /// Encode a version 2 string with these extensions.
+    pub(crate) fn encode(&self, params: &Params, salt: &[u8], hash: &[u8]) -> String {
+        let (flags, extension) = self.header();
+        encode_rscrypt_v2_with(params, salt, hash, flags, extension)
+    }
+
+    /// Check if `password` hashes to `hash` under `params`, `salt`, these
+    /// extensions and the associated data `ad`, with the buffers of
+    /// `scratch`, see `format::verify_with`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn verify_with(
+        &self, password: &[u8], params: &Params, salt: &[u8], hash: &[u8], ad: &[u8],
+        output: &mut [u8], scratch: &mut Scratch,
+    ) -> Result<(), CheckError> {
+        let mut digest = [0u8; 32];
+        let password = self.prehash(password, &mut digest);
+        let salt = self.scrypt_salt(params, salt, hash.len(), ad);
+        let c = self.final_rounds.unwrap_or(NonZeroU32::MIN);
+        format::verify_rounds_with(password, params, &salt, hash, c, output, scratch)
+    }
+
+    /// Hash `password` with these extensions under a random salt drawn
+    /// from `rng`, see `hash_into`.
+    #[cfg(feature="simple")]
+    fn hash<R: RngCore>(&self, password: &[u8], params: &Params, rng: &mut R)
+        -> Result<([u8; 16], [u8; 32]), HashError>
+    {
+        observer::check_hash_params(params);
+        let mut salt = [0u8; 16];
+        rng.try_fill_bytes(&mut salt)?;
+        let mut dk = [0u8; 32];
+        self.hash_into(password, params, &salt, &[], &mut dk)
+            .expect("32 bytes always satisfy output length requirements");
+        Ok((salt, dk))
+    }
+
+    /// Hash `password` with these extensions under `salt` and the
+    /// associated data `ad` into `output`, see `scrypt_with_final_rounds`.
+    #[cfg(feature="simple")]
+    pub(crate) fn hash_into(
+        &self, password: &[u8], params: &Params, salt: &[u8], ad: &[u8], output: &mut [u8],
+    ) -> Result<(), InvalidOutputLen> {
+        let mut digest = [0u8; 32];
+        let password = self.prehash(password, &mut digest);
+        let c = self.final_rounds.unwrap_or(NonZeroU32::MIN);
+        let salt = self.scrypt_salt(params, salt, output.len(), ad);
+        scrypt_with_final_rounds(password, &salt, params, c, output)
+    }
+}
+
+#[cfg(feature="simple-verify")]
+fn decode_rscrypt_fields(fields: &RscryptFields)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    let decode = |field| ct_base64::decode(field, fields.url_safe);
+    // both fields are decoded before an error is reported
+    let (salt, hash) = (decode(fields.salt), decode(fields.hash));
+    Ok((fields.params, salt?, hash?))
+}
+
+/// The fields of an rscrypt string, see `split_rscrypt`.
+#[cfg(feature="simple-verify")]
+struct RscryptFields<'a> {
+    version: u8,
+    params: Params,
+    /// The base64 encoded salt.
+    salt: &'a str,
+    /// The base64 encoded hash.
+    hash: &'a str,
+    /// If salt and hash use the URL-safe alphabet instead of the standard
+    /// one.
+    url_safe: bool,
+    /// The id of the pepper mixed into the password, see
+    /// `scrypt_simple_peppered`.
+    pepper: Option<u8>,
+    /// If the salt is bound to associated data, see `scrypt_simple_ad`.
+    associated: bool,
+    /// The longest password hashed without SHA-256, see
+    /// `scrypt_simple_prehashed`.
+    prehash_above: Option<u16>,
+    /// The id of the scheme of the client-side digest which was hashed, see
+    /// `scrypt_simple_client`.
+    client_scheme: Option<u8>,
+    /// The iterations of the final PBKDF2 if more than one, see
+    /// `scrypt_simple_final_rounds`.
+    final_rounds: Option<NonZeroU32>,
+    /// The decoded header, with the field which follows the flags, and its
+    /// length if it is bound into the salt, see `scrypt_simple_bound`.
+    bound: Option<([u8; V2_HEADER_LEN + 4], usize)>,
+    /// The day the hash was created on, in days since the Unix epoch, see
+    /// `ScryptHash::created_at`.
+    created_at: Option<u32>,
+    /// If a key follows the hash in the output of scrypt, see
+    /// `hash_and_derive`.
+    derived: bool,
+}
+
+#[cfg(feature="simple-verify")]
+impl<'a> RscryptFields<'a> {
+    /// The salt scrypt computed the hash with: the decoded `salt`, bound to
+    /// the header if the flag is set.
+    #[cfg(feature="std")]
+    fn scrypt_salt(&self, salt: Vec<u8>) -> Vec<u8> {
+        match self.bound {
+            Some((ref header, len)) => bound_salt(&header[..len], &salt).to_vec(),
+            None => salt,
+        }
+    }
+}
+
+/// Split an rscrypt string into format version, parameters and the base64
+/// encoded salt and hash. The alphabet is detected as documented on
+/// `scrypt_check`.
+#[cfg(feature="simple-verify")]
+fn split_rscrypt(hashed_value: &str)
+    -> Result<RscryptFields<'_>, CheckError>
+{
+    match split_rscrypt_all(hashed_value) {
+        (fields, None) => Ok(fields),
+        (_, Some(e)) => Err(e),
+    }
+}
+
+/// `split_rscrypt`, but runs every check on every string and returns the
+/// first error with the fields, so that rejecting a malformed string takes
+/// comparable time whichever field is malformed. Missing fields are empty.
+#[cfg(feature="simple-verify")]
+fn split_rscrypt_all(hashed_value: &str) -> (RscryptFields<'_>, Option<CheckError>) {
+    let mut error = None;
+    let mut fail = |e: CheckError| if error.is_none() { error = Some(e); };
+    let mut iter = hashed_value.split('$');
+
+    // Check that there are no characters before the first "$"
+    if iter.next() != Some("") { fail(CheckError::InvalidFormat); }
+
+    // Check the name
+    if iter.next() != Some("rscrypt") { fail(CheckError::InvalidFormat); }
+
+    // Parse format - version 0 (compact), 1 (expanded) and 2 (explicit
+    // lengths) are supported
+    let fstr = iter.next().unwrap_or_else(|| { fail(CheckError::InvalidFormat); "" });
+    match fstr {
+        "0" | "1" | "2" => {}
+        _ if !fstr.is_empty() && fstr.bytes().all(|b| b.is_ascii_digit()) => {
+            fail(CheckError::UnsupportedVersion)
+        }
+        _ => fail(CheckError::InvalidFormat),
+    }
+    let pstr = iter.next().unwrap_or_else(|| { fail(CheckError::InvalidFormat); "" });
+
+    // Salt
+    let salt = iter.next().unwrap_or_else(|| { fail(CheckError::InvalidFormat); "" });
+
+    // Hashed value
+    let hash = iter.next().unwrap_or_else(|| { fail(CheckError::InvalidFormat); "" });
+
+    let fields = [pstr, salt, hash];
+    let has = |chars: &[char]| fields.iter().any(|f| f.contains(chars));
+    let url_safe = has(&['-', '_']) || (fstr != "2" && !has(&['='])
+        && fields.iter().any(|f| f.len() % 4 != 0));
+    if url_safe && has(&['+', '/']) { fail(CheckError::InvalidFormat); }
+    // standard fields of versions 0 and 1 are padded, the others are not
+    let padded = !url_safe && fstr != "2";
+    if let Err(e) = check_canonical(pstr, url_safe, padded, Field::Params) { fail(e); }
+
+    // no valid parameter field is longer, and 17 bytes hold the decoding of
+    // 22 characters
+    if pstr.len() > 22 { fail(CheckError::InvalidFormat); }
+    let mut pbuf = [0u8; 17];
+    let pfield = if pstr.len() > 22 { "" } else { pstr };
+    let plen = ct_base64::decode_slice(pfield, url_safe, &mut pbuf)
+        .unwrap_or_else(|e| { fail(e); 0 });
+    let pvec = &pbuf[..plen];
+    let mut pepper = None;
+    let mut associated = false;
+    let mut prehash_above = None;
+    let mut client_scheme = None;
+    let mut final_rounds = None;
+    let mut bound = None;
+    let mut created_at = None;
+    let mut derived = false;
+    // declared salt and hash lengths of version 2
+    let mut lens = None;
+    let (version, header) = match fstr {
+        "0" if pvec.len() == 3 => (0, Some((pvec[0], pvec[1] as u32, pvec[2] as u32))),
+        "1" if pvec.len() == 9 => {
+            let mut pval = [0u32; 2];
+            LittleEndian::read_u32_into(&pvec[1..9], &mut pval);
+            (1, Some((pvec[0], pval[0], pval[1])))
+        }
+        "2" if pvec.len() >= V2_HEADER_LEN => {
+            // every bit of the flags is assigned, a new variant of the
+            // layout needs a new version
+            let flags = pvec[11];
+            // a pepper id, the threshold of pre-hashing, a client scheme id,
+            // the final iterations or the creation day follow the header if
+            // the flag is set, pre-hashing, client schemes, final iterations,
+            // creation days and derived keys are not combined with the others
+            // except the bound header, which is combined with all but derived
+            // keys
+            let others = flags & !V2_BOUND;
+            let peppered = flags & V2_PEPPERED != 0;
+            let prehashed = flags & V2_PREHASHED != 0;
+            let client = flags & V2_CLIENT_SCHEME != 0;
+            let rounds = flags & V2_FINAL_ROUNDS != 0;
+            let created = flags & V2_CREATED != 0;
+            derived = flags & V2_DERIVED != 0;
+            let extra = match (rounds || created, prehashed) {
+                (true, _) => 4,
+                (false, true) => 2,
+                _ => (peppered || client) as usize,
+            };
+            if pvec.len() != V2_HEADER_LEN + extra
+                || (prehashed && flags & (V2_PEPPERED | V2_ASSOCIATED | V2_CLIENT_SCHEME) != 0)
+                || (client && flags & (V2_PEPPERED | V2_ASSOCIATED) != 0)
+                || (rounds && others != V2_FINAL_ROUNDS)
+                || (created && others != V2_CREATED)
+                || (derived && flags != V2_DERIVED)
+            {
+                fail(CheckError::InvalidFormat);
+            } else if peppered {
+                pepper = Some(pvec[V2_HEADER_LEN]);
+            } else if prehashed {
+                prehash_above = Some(LittleEndian::read_u16(&pvec[V2_HEADER_LEN..]));
+            } else if client {
+                client_scheme = Some(pvec[V2_HEADER_LEN]);
+            } else if rounds {
+                // one iteration is written without the flag
+                match NonZeroU32::new(LittleEndian::read_u32(&pvec[V2_HEADER_LEN..])) {
+                    Some(c) if c.get() > 1 => final_rounds = Some(c),
+                    _ => fail(CheckError::InvalidFormat),
+                }
+            } else if created {
+                created_at = Some(LittleEndian::read_u32(&pvec[V2_HEADER_LEN..]));
+            }
+            if flags & V2_BOUND != 0 {
+                let mut header = [0u8; V2_HEADER_LEN + 4];
+                header[..pvec.len()].copy_from_slice(pvec);
+                bound = Some((header, pvec.len()));
+            }
+            associated = flags & V2_ASSOCIATED != 0;
+            lens = Some((pvec[9], pvec[10]));
+            let mut pval = [0u32; 2];
+            LittleEndian::read_u32_into(&pvec[1..9], &mut pval);
+            (2, Some((pvec[0], pval[0], pval[1])))
+        }
+        "2" => (2, None),
+        _ => (0, None),
+    };
+    let params = header
+        .and_then(|(log_n, r, p)| Params::new(log_n, r, p).ok())
+        .unwrap_or_else(|| {
+            fail(CheckError::InvalidFormat);
+            Params { log_n: 0, r: 1, p: 1 }
+        });
+    if let Err(e) = check_canonical(salt, url_safe, padded, Field::Salt) { fail(e); }
+    if let Err(e) = check_canonical(hash, url_safe, padded, Field::Hash) { fail(e); }
+
+    if version == 2 {
+        // The fields must have the unpadded length of the declared number of
+        // bytes, and the hash must not be empty
+        if let Some((salt_len, hash_len)) = lens {
+            if salt.len() != b64_unpadded_len(salt_len as usize)
+                || hash.len() != b64_unpadded_len(hash_len as usize)
+                || hash_len == 0
+            {
+                fail(CheckError::InvalidFormat);
+            }
+        }
+        // No trailing "$"
+        if iter.next().is_some() { fail(CheckError::InvalidFormat); }
+    } else {
+        // The final "$" is optional, as other tools strip it. Without it the
+        // hash must not be empty, or the string may have been truncated
+        match iter.next() {
+            None if !hash.is_empty() => {}
+            // Make sure there is no trailing data after the final "$"
+            Some("") if iter.next().is_none() => {}
+            _ => fail(CheckError::InvalidFormat),
+        }
+    }
+
+    let fields = RscryptFields {
+        version, params, salt, hash, url_safe, pepper, associated, prehash_above,
+        client_scheme, final_rounds, bound, created_at, derived,
+    };
+    (fields, error)
+}
+
+/// Check that a base64 `field` is the canonical encoding of its bytes, so
+/// that no two strings of an alphabet verify as the same hash: unused
+/// trailing bits must be zero and padding must be present exactly if
+/// `padded`. Characters outside of the alphabet are left to the decoder.
+#[cfg(feature="simple-verify")]
+fn check_canonical(field: &str, url_safe: bool, padded: bool, which: Field)
+    -> Result<(), CheckError>
+{
+    let data = field.trim_end_matches('=');
+    let pad = field.len() - data.len();
+    let rem = data.len() % 4;
+    let expected_pad = if padded && rem != 0 { 4 - rem } else { 0 };
+    let last = data.bytes().last().and_then(|c| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' if !url_safe => Some(62),
+        b'/' if !url_safe => Some(63),
+        b'-' if url_safe => Some(62),
+        b'_' if url_safe => Some(63),
+        _ => None,
+    });
+    // 2 and 3 characters of the last group hold 4 and 2 unused bits
+    let unused = match (rem, last) {
+        (2, Some(v)) => v & 0xf,
+        (3, Some(v)) => v & 0x3,
+        _ => 0,
+    };
+    if pad != expected_pad || unused != 0 {
+        Err(CheckError::NonCanonicalBase64(which))?;
+    }
+    Ok(())
+}
+
+/// Length of the header of rscrypt version 2: `log_n`, `r` and `p`, the
+/// salt and hash lengths and a flags byte.
+#[cfg(feature="simple-verify")]
+const V2_HEADER_LEN: usize = 12;
+
+/// Flag of rscrypt version 2: a pepper was mixed into the password and its
+/// id follows the header.
+#[cfg(feature="simple-verify")]
+const V2_PEPPERED: u8 = 0x01;
+
+/// Flag of rscrypt version 2: scrypt was computed with the salt bound to
+/// associated data, which is not stored.
+#[cfg(feature="simple-verify")]
+const V2_ASSOCIATED: u8 = 0x02;
+
+/// Flag of rscrypt version 2: passwords above a threshold were hashed with
+/// SHA-256 before scrypt, the threshold follows the header.
+#[cfg(feature="simple-verify")]
+const V2_PREHASHED: u8 = 0x04;
+
+/// Flag of rscrypt version 2: the password is a client-side digest, the id
+/// of its `ClientScheme` follows the header.
+#[cfg(feature="simple-verify")]
+const V2_CLIENT_SCHEME: u8 = 0x08;
+
+/// Flag of rscrypt version 2: the final PBKDF2 ran more than one iteration,
+/// their number follows the header, see `scrypt_simple_final_rounds`.
+#[cfg(feature="simple-verify")]
+const V2_FINAL_ROUNDS: u8 = 0x10;
+
+/// Flag of rscrypt version 2: the header is bound into the salt of scrypt,
+/// see `scrypt_simple_bound`.
+#[cfg(feature="simple-verify")]
+const V2_BOUND: u8 = 0x20;
+
+/// Flag of rscrypt version 2: the day the hash was created on follows the
+/// header, see `ScryptHash::created_at`.
+#[cfg(feature="simple-verify")]
+const V2_CREATED: u8 = 0x40;
+
+/// Flag of rscrypt version 2: a key follows the hash in the output of
+/// scrypt, see `hash_and_derive`.
+#[cfg(feature="simple-verify")]
+const V2_DERIVED: u8 = 0x80;
+
+/// Number of characters of the unpadded base64 encoding of `len` bytes.
+#[cfg(feature="simple-verify")]
+fn b64_unpadded_len(len: usize) -> usize {
+    (len * 4).div_ceil(3)
+}
+
+/// Check if salt and hash fit the one-byte lengths of rscrypt version 2.
+#[cfg(feature="simple-verify")]
+pub(crate) fn rscrypt_v2_supports(salt: &[u8], hash: &[u8]) -> bool {
+    salt.len() <= 255 && !hash.is_empty() && hash.len() <= 255
 }
@@ -0,0 +1,346 @@
+//! Hashing and verification off the threads of an async executor, on any
+//! runtime.
+//!
+//! scrypt blocks its thread for the whole computation, which stalls the
+//! other tasks of an executor thread. The functions of this module run it
+//! as a job of a `Spawner`, e.g. the blocking pool of the runtime, and
+//! return a future of the result which any executor can poll. No runtime
+//! is a dependency, an adapter is a few lines:
+//!
+//! ```
+//! use scrypt::nonblocking::Spawner;
+//!
+//! struct TokioSpawner;
+//!
+//! impl Spawner for TokioSpawner {
+//!     fn spawn_blocking(&self, job: Box<dyn FnOnce() + Send>) {
+//!         // tokio::task::spawn_blocking(job);
+//!         // or async_std::task::spawn_blocking(job), blocking::unblock(job)
+//! #       job();
+//!     }
+//! }
+//! ```
+//!
+//! `ThreadSpawner` starts a thread for every job, for processes without a
+//! blocking pool.
+//!
+//! A job which panics completes its future with `TaskError::Panicked`. A
+//! future dropped before its job started, e.g. on a timeout of the request
+//! it belongs to, cancels the job: it returns without computing scrypt.
+//! A job already computing runs to its end, its result is dropped.
+//!
+//! `verify_stream` verifies a stream of jobs with a bounded number of them
+//! running at once. `Stream` is the trait of `futures_core::Stream`, which
+//! is not a dependency either: a stream of the `futures` crate is adapted
+//! by forwarding `poll_next`, an iterator with `iter()`.
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use batch::{verify_one, BatchOptions, Buffers};
+use config::SimpleConfig;
+use errors::{CheckError, HashError, TaskError};
+use password::Password;
+use {os_rng, verify_password};
+
+/// Runner of the blocking jobs of this module.
+pub trait Spawner {
+    /// Run `job` on a thread which may block, e.g. with
+    /// `tokio::task::spawn_blocking`. A job which is dropped without being
+    /// run completes its future with `TaskError::Dropped`.
+    fn spawn_blocking(&self, job: Box<dyn FnOnce() + Send>);
+}
+
+impl<S: Spawner + ?Sized> Spawner for &S {
+    fn spawn_blocking(&self, job: Box<dyn FnOnce() + Send>) {
+        (**self).spawn_blocking(job)
+    }
+}
+
+/// A `Spawner` which starts a new thread for every job.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ThreadSpawner;
+
+impl Spawner for ThreadSpawner {
+    fn spawn_blocking(&self, job: Box<dyn FnOnce() + Send>) {
+        thread::spawn(job);
+    }
+}
+
+/// The result of a job, shared by the job and its future.
+struct Shared<T, E> {
+    state: Mutex<State<T, E>>,
+    cancelled: AtomicBool,
+}
+
+struct State<T, E> {
+    result: Option<Result<T, TaskError<E>>>,
+    waker: Option<Waker>,
+    done: bool,
+}
+
+impl<T, E> Shared<T, E> {
+    fn complete(&self, result: Result<T, TaskError<E>>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.done { return; }
+        state.result = Some(result);
+        state.done = true;
+        if let Some(waker) = state.waker.take() { waker.wake(); }
+    }
+}
+
+/// The end of a job, which completes the future with `TaskError::Dropped`
+/// if the job is dropped before it completed it.
+struct Completion<T, E>(Arc<Shared<T, E>>);
+
+impl<T, E> Drop for Completion<T, E> {
+    fn drop(&mut self) {
+        self.0.complete(Err(TaskError::Dropped));
+    }
+}
+
+/// The future of a job of `spawn`, `hash_async` or `verify_async`.
+///
+/// Dropping the future before the job started cancels the job.
+#[must_use = "the job is cancelled if the future is dropped"]
+pub struct ScryptFuture<T, E> {
+    shared: Arc<Shared<T, E>>,
+}
+
+impl<T, E> Future for ScryptFuture<T, E> {
+    type Output = Result<T, TaskError<E>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.shared.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T, E> Drop for ScryptFuture<T, E> {
+    fn drop(&mut self) {
+        self.shared.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<T, E> fmt::Debug for ScryptFuture<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ScryptFuture { .. }")
+    }
+}
+
+/// Run `job` with `spawner`, e.g. a `scrypt()` of its own or a
+/// `Verifier::verify()`, and return the future of its result.
+pub fn spawn<S, T, E, F>(spawner: &S, job: F) -> ScryptFuture<T, E>
+    where S: Spawner + ?Sized, T: Send + 'static, E: Send + 'static,
+          F: FnOnce() -> Result<T, E> + Send + 'static
+{
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State { result: None, waker: None, done: false }),
+        cancelled: AtomicBool::new(false),
+    });
+    let completion = Completion(shared.clone());
+    spawner.spawn_blocking(Box::new(move || {
+        let shared = &completion.0;
+        // cancelled before the job started
+        if shared.cancelled.load(Ordering::Relaxed) { return; }
+        let result = match panic::catch_unwind(AssertUnwindSafe(job)) {
+            Ok(result) => result.map_err(TaskError::Failed),
+            Err(_) => Err(TaskError::Panicked),
+        };
+        shared.complete(result);
+    }));
+    ScryptFuture { shared }
+}
+
+/// `hash_async` hashes `password` with `config` like
+/// `SimpleConfig::hash_with`, as a job of `spawner`.
+pub fn hash_async<S: Spawner + ?Sized>(spawner: &S, config: SimpleConfig, password: Password)
+    -> ScryptFuture<String, HashError>
+{
+    spawn(spawner, move || config.hash_bytes_with_rng(password.as_bytes(), &mut os_rng()))
+}
+
+/// `verify_async` checks `password` against `stored` like
+/// `verify_password()`, as a job of `spawner`.
+///
+/// # Return
+/// `Err(TaskError::Failed(CheckError::HashMismatch))` for a wrong password.
+pub fn verify_async<S: Spawner + ?Sized>(spawner: &S, password: Password, stored: String)
+    -> ScryptFuture<(), CheckError>
+{
+    spawn(spawner, move || verify_password(&password, &stored))
+}
+
+/// An asynchronous sequence of items, the `Stream` of `futures_core`.
+pub trait Stream {
+    /// The items of the stream.
+    type Item;
+
+    /// Poll for the next item, `Poll::Ready(None)` at the end of the stream.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>>;
+
+    /// The future of the next item, e.g. for
+    /// `while let Some(result) = stream.next().await`.
+    fn next<'a>(&'a mut self) -> Next<'a, Self> where Self: Unpin + Sized {
+        Next(self)
+    }
+}
+
+/// The future of `Stream::next()`.
+#[derive(Debug)]
+pub struct Next<'a, S: 'a>(&'a mut S);
+
+impl<'a, S: Stream + Unpin> Future for Next<'a, S> {
+    type Output = Option<S::Item>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        Pin::new(&mut *self.0).poll_next(cx)
+    }
+}
+
+/// The stream of the items of an iterator, always ready.
+#[derive(Debug)]
+pub struct Iter<I>(I);
+
+/// A stream of the items of `iter`.
+pub fn iter<I: IntoIterator>(iter: I) -> Iter<I::IntoIter> {
+    Iter(iter.into_iter())
+}
+
+impl<I: Iterator + Unpin> Stream for Iter<I> {
+    type Item = I::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Option<I::Item>> {
+        Poll::Ready(self.0.next())
+    }
+}
+
+/// A job of `verify_stream`: a password, the stored hash to verify it
+/// against and an id of the caller, e.g. of the user or the record.
+#[derive(Debug)]
+pub struct VerifyJob<Id> {
+    /// The id, returned with the result.
+    pub id: Id,
+    /// The password to verify.
+    pub password: Password,
+    /// The stored hash, of any format of `scrypt_check()`.
+    pub stored: String,
+}
+
+/// The result of a `VerifyJob`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyResult<Id> {
+    /// The id of the job.
+    pub id: Id,
+    /// `Ok(())` if the password matches, the error of the verification or
+    /// of its job otherwise.
+    pub result: Result<(), TaskError<CheckError>>,
+}
+
+/// The stream of `verify_stream`.
+pub struct VerifyStream<S, I, Id> {
+    spawner: S,
+    input: Pin<Box<I>>,
+    options: BatchOptions,
+    concurrency: usize,
+    running: Vec<(Id, ScryptFuture<(), CheckError>)>,
+    exhausted: bool,
+}
+
+/// `verify_stream` verifies the jobs of `input` with `spawner`, at most
+/// `options.threads` of them at once, and yields their results in the
+/// order in which they complete.
+///
+/// The next job is only taken from `input` when one of the running ones is
+/// done, so a slow consumer of the results slows down the reading of the
+/// input. Hashes exceeding `options.limits` are reported as
+/// `Err(CheckError::CostExceedsLimit)` without being computed, like by
+/// `verify_many`, and the memory used peaks at `threads * max_memory`.
+///
+/// # Arguments
+/// - spawner - The runner of the verifications, e.g. `&ThreadSpawner`
+/// - input - The jobs
+/// - options - The number of concurrent verifications, `0` for one per
+///   available CPU, and the limits on each hash
+pub fn verify_stream<S, I, Id>(spawner: S, input: I, options: &BatchOptions)
+    -> VerifyStream<S, I, Id>
+    where S: Spawner, I: Stream<Item = VerifyJob<Id>>
+{
+    let concurrency = match options.threads {
+        0 => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        n => n,
+    };
+    VerifyStream {
+        spawner,
+        input: Box::pin(input),
+        options: *options,
+        concurrency,
+        running: Vec::with_capacity(concurrency),
+        exhausted: false,
+    }
+}
+
+impl<S, I, Id> VerifyStream<S, I, Id> {
+    /// The number of jobs being verified.
+    pub fn running(&self) -> usize {
+        self.running.len()
+    }
+}
+
+impl<S, I, Id> Stream for VerifyStream<S, I, Id>
+    where S: Spawner + Unpin, I: Stream<Item = VerifyJob<Id>>, Id: Unpin
+{
+    type Item = VerifyResult<Id>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<VerifyResult<Id>>> {
+        let this = self.get_mut();
+        while !this.exhausted && this.running.len() < this.concurrency {
+            match this.input.as_mut().poll_next(cx) {
+                Poll::Ready(Some(job)) => {
+                    let VerifyJob { id, password, stored } = job;
+                    let limits = this.options.limits;
+                    let future = spawn(&this.spawner, move || {
+                        verify_one(password.as_bytes(), &stored, limits.as_ref(),
+                            &mut Buffers::default())
+                    });
+                    this.running.push((id, future));
+                }
+                Poll::Ready(None) => this.exhausted = true,
+                Poll::Pending => break,
+            }
+        }
+
+        for i in 0..this.running.len() {
+            if let Poll::Ready(result) = Pin::new(&mut this.running[i].1).poll(cx) {
+                let (id, _) = this.running.swap_remove(i);
+                return Poll::Ready(Some(VerifyResult { id, result }));
+            }
+        }
+        if this.exhausted && this.running.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<S, I, Id> fmt::Debug for VerifyStream<S, I, Id> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("VerifyStream")
+            .field("concurrency", &self.concurrency)
+            .field("running", &self.running.len())
+            .field("exhausted", &self.exhausted)
+            .finish()
+    }
+}
@@ -0,0 +1,214 @@
+//! Hooks for metrics of hashing and verification, e.g. counters of
+//! mismatches and the time spent in scrypt, without a dependency on a
+//! metrics library.
+//!
+//! Events are reported by `Verifier::verify()` and the hashing methods of
+//! `SimpleConfig`, to the observer passed to them or else to the one of
+//! `set_global_observer()`. The free functions, e.g. `scrypt_check()`, do
+//! not report events. Events never contain passwords, salts or hashes.
+//!
+//! Warnings about weak or deprecated usage are reported by every function,
+//! free functions included, to the observer of `set_global_observer()`,
+//! each distinct `Warning` once per process. The `log` crate is not a
+//! dependency, an observer can forward them with `log::warn!`:
+//!
+//! ```
+//! use scrypt::observer::{ScryptObserver, Warning};
+//!
+//! struct Logger;
+//!
+//! impl ScryptObserver for Logger {
+//!     fn on_warning(&self, warning: &Warning) {
+//!         // log::warn!("{}", warning);
+//! #       let _ = warning.to_string();
+//!     }
+//! }
+//! ```
+use core::fmt;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex, RwLock};
+
+#[cfg(feature="simple")]
+use config::recommended_params;
+use errors::{CheckError, ErrorCode};
+use format::Format;
+use params::Params;
+
+/// Receiver of hashing and verification events. Both methods do nothing by
+/// default.
+///
+/// The methods are called on the thread of the operation after it
+/// finished, so they should return quickly, e.g. after incrementing
+/// counters.
+pub trait ScryptObserver: Send + Sync {
+    /// A hash was computed, or computing it failed.
+    fn on_hash(&self, _event: &HashEvent) {}
+
+    /// A password was verified, or the stored hash was malformed or refused.
+    fn on_verify(&self, _event: &VerifyEvent) {}
+
+    /// Weak or deprecated usage was detected, only called on the observer
+    /// of `set_global_observer()`.
+    fn on_warning(&self, _warning: &Warning) {}
+}
+
+/// A hash computed by `SimpleConfig`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct HashEvent {
+    /// The parameters of the hash.
+    pub params: Params,
+    /// The format of the hash.
+    pub format: Format,
+    /// `Err` with the code of the `HashError` if hashing failed.
+    pub result: Result<(), ErrorCode>,
+    /// The time hashing took.
+    pub elapsed: Duration,
+}
+
+/// A verification by `Verifier`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct VerifyEvent {
+    /// The parameters of the stored hash, `None` if it could not be parsed.
+    pub params: Option<Params>,
+    /// The format of the stored hash, `None` if it could not be parsed.
+    pub format: Option<Format>,
+    /// The result of the verification, e.g. `Err(CheckError::HashMismatch)`
+    /// for a wrong password or `Err(CheckError::Refused)` for a hash
+    /// breaking a rule.
+    pub result: Result<(), CheckError>,
+    /// The time the verification took.
+    pub elapsed: Duration,
+}
+
+/// Weak or deprecated usage. Warnings contain parameters and formats, but
+/// never passwords, salts or hashes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Warning {
+    /// A hash was computed with parameters weaker than the recommended
+    /// `log_n = 15`, `r = 8`, `p = 1`.
+    WeakParams(Params),
+    /// A hash string of a deprecated format version was verified: rscrypt
+    /// version 1, superseded by the lengths recording version 2 of
+    /// `scrypt_simple_v2`.
+    DeprecatedVersion {
+        /// The format of the string.
+        format: Format,
+        /// The version of the string.
+        version: u8,
+    },
+    /// Verifying a hash needed more memory than the threshold of
+    /// `set_memory_warning_threshold()`.
+    MemoryAboveThreshold {
+        /// The parameters of the hash.
+        params: Params,
+        /// The bytes allocated by scrypt.
+        memory_bytes: u64,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Warning::WeakParams(ref params) => write!(f,
+                "hashing with log_n = {}, r = {}, p = {}, weaker than the recommended \
+                 log_n = 15, r = 8, p = 1", params.log_n(), params.r(), params.p()),
+            Warning::DeprecatedVersion { format, version } => write!(f,
+                "verified a {:?} string of the deprecated version {}, rehash it",
+                format, version),
+            Warning::MemoryAboveThreshold { ref params, memory_bytes } => write!(f,
+                "verifying a hash with log_n = {}, r = {}, p = {} needed {} bytes, \
+                 above the warning threshold", params.log_n(), params.r(), params.p(),
+                memory_bytes),
+        }
+    }
+}
+
+/// Distinct warnings remembered for the rate limit, later ones are dropped.
+#[cfg(feature = "std")]
+const MAX_WARNINGS: usize = 64;
+
+#[cfg(feature = "std")]
+static WARNED: Mutex<Vec<Warning>> = Mutex::new(Vec::new());
+
+/// 1 GiB, 32 times the memory of the recommended parameters.
+#[cfg(feature = "std")]
+static MEMORY_THRESHOLD: AtomicU64 = AtomicU64::new(1 << 30);
+
+/// Warn about verifications which need more than `bytes` bytes of memory,
+/// by default 1 GiB. `u64::MAX` disables the warning.
+#[cfg(feature = "std")]
+pub fn set_memory_warning_threshold(bytes: u64) {
+    MEMORY_THRESHOLD.store(bytes, Ordering::Relaxed);
+}
+
+/// Report `warning` to the global observer, if it was not reported before.
+#[cfg(feature = "std")]
+fn warn(warning: Warning) {
+    let observer = match global_observer() {
+        Some(observer) => observer,
+        // kept for an observer installed later
+        None => return,
+    };
+    {
+        let mut warned = WARNED.lock().unwrap_or_else(|e| e.into_inner());
+        if warned.contains(&warning) || warned.len() >= MAX_WARNINGS { return; }
+        warned.push(warning);
+    }
+    observer.on_warning(&warning);
+}
+
+/// Warn if new hashes use `params` weaker than the recommended ones.
+#[cfg(feature="simple")]
+pub(crate) fn check_hash_params(params: &Params) {
+    if params.is_weaker_than(&recommended_params()) { warn(Warning::WeakParams(*params)); }
+}
+
+/// Warn if a verified string has a deprecated version.
+pub(crate) fn check_version(format: Format, version: Option<u8>) {
+    if let (true, Some(version)) = (is_deprecated(format, version), version) {
+        warn(Warning::DeprecatedVersion { format, version });
+    }
+}
+
+/// If strings of `version` of `format` are deprecated: rscrypt version 1,
+/// see `Warning::DeprecatedVersion`. `needs_rehash` upgrades them.
+pub(crate) fn is_deprecated(format: Format, version: Option<u8>) -> bool {
+    format == Format::Rscrypt && version == Some(1)
+}
+
+/// Without `std` there is no global observer, warnings are dropped.
+#[cfg(not(feature = "std"))]
+fn warn(_warning: Warning) {}
+
+/// Warn if verifying a hash of `params` exceeds the memory threshold.
+#[cfg(feature = "std")]
+pub(crate) fn check_memory(params: &Params) {
+    let memory_bytes = params.required_memory();
+    if memory_bytes > MEMORY_THRESHOLD.load(Ordering::Relaxed) as u128 {
+        let memory_bytes = memory_bytes.min(u64::MAX as u128) as u64;
+        warn(Warning::MemoryAboveThreshold { params: *params, memory_bytes });
+    }
+}
+
+/// Without `std` there is no threshold, see `warn`.
+#[cfg(not(feature = "std"))]
+pub(crate) fn check_memory(_params: &Params) {}
+
+#[cfg(feature = "std")]
+static GLOBAL: RwLock<Option<Arc<dyn ScryptObserver>>> = RwLock::new(None);
+
+/// Install `observer` for all operations which are not passed one, `None`
+/// removes the installed one.
+#[cfg(feature = "std")]
+pub fn set_global_observer(observer: Option<Arc<dyn ScryptObserver>>) {
+    *GLOBAL.write().unwrap_or_else(|e| e.into_inner()) = observer;
+}
+
+/// The observer of `set_global_observer()`.
+#[cfg(feature = "std")]
+pub(crate) fn global_observer() -> Option<Arc<dyn ScryptObserver>> {
+    GLOBAL.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
@@ -1,64 +1,255 @@
-use std::mem::size_of;
-use std::usize;
+use core::mem::size_of;
 
-use errors::InvalidParams;
+use errors::{InvalidParams, ParamsReason};
 
 /// The Scrypt parameter values.
-#[derive(Clone, Copy)]
-pub struct ScryptParams {
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Params {
     pub(crate) log_n: u8,
     pub(crate) r: u32,
     pub(crate) p: u32
 }
 
-impl ScryptParams {
-    /// Create a new instance of ScryptParams.
+/// The presets of `Params::for_year`: the first year of each entry, with
+/// `log_n`, `r` and `p`.
+const PRESETS: &[(u16, u8, u32, u32)] = &[
+    // Percival (2009), section 7: N = 2^14, r = 8, p = 1 for interactive
+    // logins
+    (2015, 14, 8, 1),
+    // golang.org/x/crypto/scrypt: N = 32768, r = 8, p = 1 for interactive
+    // logins as of 2017
+    (2017, 15, 8, 1),
+    // OWASP Password Storage Cheat Sheet (2021): N = 2^16 (64 MiB), r = 8,
+    // p = 1
+    (2021, 16, 8, 1),
+    // OWASP Password Storage Cheat Sheet (2023): N = 2^17 (128 MiB), r = 8,
+    // p = 1
+    (2023, 17, 8, 1),
+];
+
+/// The last year of `PRESETS`.
+const LATEST_YEAR: u16 = 2026;
+
+impl Params {
+    /// Create a new instance of Params.
     ///
     /// # Arguments
     /// - `log_n` - The log2 of the Scrypt parameter `N`
     /// - `r` - The Scrypt parameter `r`
     /// - `p` - The Scrypt parameter `p`
     /// # Conditions
-    /// - `log_n` must be less than `64`
-    /// - `r` must be greater than `0` and less than or equal to `4294967295`
-    /// - `p` must be greater than `0` and less than `4294967295`
+    /// - `log_n` must be greater than `0` and less than the bits of a `usize`
+    /// - `r` must be greater than `0` and `N` less than `2^(16 * r)`
+    /// - `p` must be greater than `0` and `r * p` less than `2^30`
+    /// - the `128 * r * N` and `128 * r * p` bytes must fit into a `usize`
+    /// # Platform limits
+    /// The indices into `V` are computed in `usize` and `u64`, `V` may be
+    /// larger than 4 GiB on 64 bit targets, up to `log_n = 54` with `r = 4`.
+    /// There the limit is the memory the allocator can provide. On 32 bit
+    /// targets `V` is below 4 GiB, e.g. `log_n <= 21` for `r = 8`, and in
+    /// practice below the 2 to 3 GiB of address space of a process.
     /// # Recommended values sufficient for most use-cases
     /// - `log_n = 15` (`n = 32768`)
     /// - `r = 8`
     /// - `p = 1`
-    pub fn new(log_n: u8, r: u32, p: u32) -> Result<ScryptParams, InvalidParams> {
-        let cond1 = (log_n as usize) < size_of::<usize>() * 8;
-        let cond2 = size_of::<usize>() >= size_of::<u32>();
-        let cond3 = r <= usize::MAX as u32 && p < usize::MAX as u32;
-        if !(r > 0 && p > 0 && cond1 && (cond2 || cond3)) {
-            Err(InvalidParams)?;
-        }
+    ///
+    /// # Return
+    /// `Err(InvalidParams)` with the `ParamsReason` of the first condition
+    /// which does not hold, in the order above.
+    pub fn new(log_n: u8, r: u32, p: u32) -> Result<Params, InvalidParams> {
+        let invalid = InvalidParams::new;
+        if log_n == 0 { Err(invalid(ParamsReason::LogNZero))?; }
+        if (log_n as usize) >= usize::BITS as usize { Err(invalid(ParamsReason::LogNTooLarge))?; }
+
+        // This check required by Scrypt:
+        // check: n < 2^(128 * r / 8)
+        if r == 0 { Err(invalid(ParamsReason::RZero))?; }
+        if log_n as u64 >= r as u64 * 16 { Err(invalid(ParamsReason::NTooLargeForR))?; }
+
+        // This check required by Scrypt:
+        // check: p <= ((2^32-1) * 32) / (128 * r)
+        // It takes a bit of re-arranging to get the check above into this form,
+        // but it is indeed the same.
+        if p == 0 { Err(invalid(ParamsReason::PZero))?; }
+        if r as u64 * p as u64 >= 0x40000000 { Err(invalid(ParamsReason::RpTooLarge))?; }
+
+        // on 16 bit platforms `r` and `p` may not fit into a `usize`
+        let fits = size_of::<usize>() >= size_of::<u32>()
+            || (r <= usize::MAX as u32 && p < usize::MAX as u32);
+        if !fits { Err(invalid(ParamsReason::MemoryOverflow))?; }
 
         let r = r as usize;
         let p = p as usize;
-
         let n: usize = 1 << log_n;
 
         // check that r * 128 doesn't overflow
-        let r128 = r.checked_mul(128).ok_or(InvalidParams)?;
+        let r128 = r.checked_mul(128).ok_or(invalid(ParamsReason::MemoryOverflow))?;
 
         // check that n * r * 128 doesn't overflow
-        r128.checked_mul(n).ok_or(InvalidParams)?;
+        r128.checked_mul(n).ok_or(invalid(ParamsReason::MemoryOverflow))?;
 
         // check that p * r * 128 doesn't overflow
-        r128.checked_mul(p).ok_or(InvalidParams)?;
+        r128.checked_mul(p).ok_or(invalid(ParamsReason::MemoryOverflow))?;
 
-        // This check required by Scrypt:
-        // check: n < 2^(128 * r / 8)
-        // r * 16 won't overflow since r128 didn't
-        if !((log_n as usize) < r * 16) { Err(InvalidParams)?; }
+        Ok(Params { log_n, r: r as u32, p: p as u32 })
+    }
 
-        // This check required by Scrypt:
-        // check: p <= ((2^32-1) * 32) / (128 * r)
-        // It takes a bit of re-arranging to get the check above into this form,
-        // but it is indeed the same.
-        if !(r * p < 0x40000000) { Err(InvalidParams)?; }
+    /// Create parameters from a memory budget and a CPU cost multiplier,
+    /// for callers thinking in bytes and relative slowdowns rather than in
+    /// `log_n`, `r` and `p`.
+    ///
+    /// The mapping is stable, the same arguments give the same parameters
+    /// in every release of this major version:
+    /// - `p = cpu_multiplier`, so 2 doubles the time of 1 for the same
+    ///   memory
+    /// - `r = 8` and `N` the largest power of 2 for which
+    ///   `1024 * (N + p + 1)`, the `serial_memory()`, is at most `memory`
+    /// - if not even `N = 2` fits with `r = 8`, `N = 2` and `r` the largest
+    ///   below 8 which fits
+    ///
+    /// The budget includes the buffers besides `V`: 32 MiB gives
+    /// `log_n = 14`, the recommended `log_n = 15`, `r = 8`, `p = 1` needs
+    /// 32 MiB and 2 KiB.
+    ///
+    /// # Arguments
+    /// - `memory` - The most bytes `scrypt` may allocate
+    /// - `cpu_multiplier` - The time relative to `p = 1`
+    ///
+    /// # Return
+    /// `Err(InvalidParams)` with `ParamsReason::BudgetTooSmall` if `memory`
+    /// is below `128 * (p + 3)` bytes, the smallest parameters, and the
+    /// reasons of `new` otherwise, e.g. `ParamsReason::PZero` for a
+    /// multiplier of 0.
+    pub fn from_costs(memory: usize, cpu_multiplier: u32) -> Result<Params, InvalidParams> {
+        let p = cpu_multiplier;
+        if p == 0 { Err(InvalidParams::new(ParamsReason::PZero))?; }
+        let memory = memory as u128;
+        let required = |log_n: u8, r: u32| 128 * r as u128 * ((1u128 << log_n) + p as u128 + 1);
+
+        if required(1, 8) <= memory {
+            let mut log_n = 1;
+            while log_n < 63 && required(log_n + 1, 8) <= memory { log_n += 1; }
+            return Params::new(log_n, 8, p);
+        }
+        let r = (1..8).rev().find(|&r| required(1, r) <= memory)
+            .ok_or(InvalidParams::new(ParamsReason::BudgetTooSmall))?;
+        Params::new(1, r, p)
+    }
+
+    /// Create parameters from a single cost, like the cost of bcrypt, for
+    /// applications which expose one integer knob.
+    ///
+    /// `cost` is mapped to `log_n = cost + 4`, `r = 8` and `p = 1`, so each
+    /// step doubles the memory and the time:
+    ///
+    /// | `cost` | `log_n` | memory  |
+    /// |--------|---------|---------|
+    /// | 0      | 4       | 16 KiB  |
+    /// | 8      | 12      | 4 MiB   |
+    /// | 10     | 14      | 16 MiB  |
+    /// | 11     | 15      | 32 MiB, the recommended parameters |
+    /// | 14     | 18      | 256 MiB |
+    /// | 16     | 20      | 1 GiB   |
+    ///
+    /// The mapping is stable, services comparing costs can rely on it. A
+    /// different mapping would be a new function. Costs above `MAX_COST`,
+    /// whose memory does not fit into a `usize`, are clamped to it.
+    pub fn from_cost(cost: u8) -> Params {
+        let log_n = cost.min(Self::MAX_COST) + 4;
+        Params::new(log_n, 8, 1).expect("costs up to MAX_COST are valid")
+    }
+
+    /// The largest cost of `from_cost()`: 49 (8 PiB) on 64 bit platforms,
+    /// 17 (2 GiB) on 32 bit ones.
+    #[cfg(target_pointer_width = "64")]
+    pub const MAX_COST: u8 = 49;
+    /// The largest cost of `from_cost()`: 49 (8 PiB) on 64 bit platforms,
+    /// 17 (2 GiB) on 32 bit ones.
+    #[cfg(not(target_pointer_width = "64"))]
+    pub const MAX_COST: u8 = 17;
+
+    /// The cost of `from_cost()` which gives these parameters, `None` if
+    /// they are not of that table, e.g. for `r != 8`.
+    pub fn cost(&self) -> Option<u8> {
+        if self.r == 8 && self.p == 1 && self.log_n >= 4 { Some(self.log_n - 4) } else { None }
+    }
+
+    /// The parameters recommended for new hashes in `year`, from the table
+    /// below, for policies which should follow published guidance instead
+    /// of fixing parameters once.
+    ///
+    /// | years     | `log_n` | `r` | `p` | source |
+    /// |-----------|---------|-----|-----|--------|
+    /// | 2015-2016 | 14      | 8   | 1   | Percival, "Stronger key derivation via sequential memory-hard functions" (2009), interactive logins |
+    /// | 2017-2020 | 15      | 8   | 1   | `golang.org/x/crypto/scrypt`, "recommended parameters for interactive logins as of 2017" |
+    /// | 2021-2022 | 16      | 8   | 1   | OWASP Password Storage Cheat Sheet (2021) |
+    /// | 2023-2026 | 17      | 8   | 1   | OWASP Password Storage Cheat Sheet (2023) |
+    ///
+    /// Entries are only added for years which have passed and are never
+    /// changed, a year always gives the same parameters.
+    ///
+    /// # Return
+    /// `Err(InvalidParams)` with `ParamsReason::UnknownYear` for years
+    /// outside the table, which are not extrapolated.
+    pub fn for_year(year: u16) -> Result<Params, InvalidParams> {
+        let &(_, log_n, r, p) = PRESETS.iter().rev()
+            .find(|&&(since, ..)| since <= year)
+            .filter(|_| year <= LATEST_YEAR)
+            .ok_or(InvalidParams::new(ParamsReason::UnknownYear))?;
+        Params::new(log_n, r, p)
+    }
+
+    /// The parameters of the newest year of `for_year()`.
+    pub fn latest() -> Params {
+        Params::for_year(LATEST_YEAR).expect("the table covers its last year")
+    }
+
+    /// The log2 of the Scrypt parameter `N`.
+    pub fn log_n(&self) -> u8 { self.log_n }
+
+    /// The Scrypt parameter `r`.
+    pub fn r(&self) -> u32 { self.r }
+
+    /// The Scrypt parameter `p`.
+    pub fn p(&self) -> u32 { self.p }
+
+    /// Check if these parameters are weaker than `other`.
+    ///
+    /// Parameters are weaker if they require less memory (`N * r`) or less
+    /// total work (`N * r * p`) than `other`.
+    pub fn is_weaker_than(&self, other: &Params) -> bool {
+        self.memory_cost() < other.memory_cost()
+            || self.work_cost() < other.work_cost()
+    }
+
+    /// Bytes allocated by `scrypt`: `128 * r * (lanes * (N + 1) + p)`.
+    /// `lanes` is 1, with the `parallel` feature it is `min(p, threads)`
+    /// for the threads of the current rayon pool, which each mix with
+    /// `128 * r * (N + 1)` bytes of their own.
+    pub fn required_memory(&self) -> u128 {
+        let lanes = ::romix_lanes(self) as u128;
+        self.serial_memory() + (lanes - 1) * 128 * (self.r as u128) * ((1u128 << self.log_n) + 1)
+    }
+
+    /// Bytes allocated by `scrypt` mixing one lane at a time:
+    /// `128 * r * (N + p + 1)`, the `required_memory()` without the
+    /// `parallel` feature. Unlike it, this does not depend on the threads of
+    /// the machine, so the memory limits of verification compare against it.
+    pub fn serial_memory(&self) -> u128 {
+        128 * (self.r as u128) * ((1u128 << self.log_n) + self.p as u128 + 1)
+    }
+
+    /// Salsa20/8 core invocations of `scrypt`: `4 * N * r * p`.
+    #[cfg(feature="simple-verify")]
+    pub(crate) fn salsa_ops(&self) -> u128 {
+        4 * self.work_cost()
+    }
+
+    fn memory_cost(&self) -> u128 {
+        (self.r as u128) << self.log_n
+    }
 
-        Ok(ScryptParams { log_n, r: r as u32, p: p as u32 })
+    fn work_cost(&self) -> u128 {
+        self.memory_cost() * self.p as u128
     }
 }
@@ -0,0 +1,213 @@
+//! Passwords which are overwritten with zeros when they are dropped.
+use std::fmt;
+use std::io::{self, Read};
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{self, Ordering};
+
+use errors::{InputTooLong, InvalidOutputLen};
+#[cfg(feature="simple")]
+use errors::HashError;
+use params::Params;
+#[cfg(feature="simple")]
+use scrypt_simple_bytes;
+use {scrypt, zero};
+
+/// A password, overwritten with zeros when it is dropped.
+///
+/// `Debug` and `Display` print `<redacted>` instead of the password, so a
+/// `Password` in a logged struct does not leak it. Converting a `String`
+/// or a `Vec<u8>` takes over its buffer without a copy, copies made before
+/// the conversion are not scrubbed.
+///
+/// `hash_password()` and `verify_password()` take a `&Password` directly,
+/// `scrypt_simple_password()` is the `Password` version of
+/// `scrypt_simple()`, and every function taking the password as `&[u8]`,
+/// e.g. `Verifier::verify_bytes()`, takes `password.as_bytes()`.
+#[derive(Clone, Default)]
+pub struct Password {
+    bytes: Vec<u8>,
+}
+
+impl Password {
+    /// Read a password from `reader` until the end, e.g. from a pipe. A
+    /// single trailing `\n` or `\r\n` is removed.
+    ///
+    /// The buffer is grown without leaving copies of the password behind.
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Password> {
+        let mut password = Password::default();
+        let mut chunk = [0u8; 64];
+        let result = loop {
+            match reader.read(&mut chunk) {
+                Ok(0) => break Ok(()),
+                Ok(n) => password.extend(&chunk[..n]),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => break Err(e),
+            }
+        };
+        zero(&mut chunk);
+        result?;
+        if password.bytes.ends_with(b"\n") { password.bytes.pop(); }
+        if password.bytes.ends_with(b"\r") { password.bytes.pop(); }
+        Ok(password)
+    }
+
+    /// The password as bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The password as a `str`, `None` if it is not UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        ::std::str::from_utf8(&self.bytes).ok()
+    }
+
+    /// Append `bytes`, moving the password to a larger buffer and zeroing
+    /// the old one when it is full.
+    fn extend(&mut self, bytes: &[u8]) {
+        self.extend_within(bytes, usize::MAX);
+    }
+
+    /// `extend`, growing the buffer to at most `max` bytes, which is at
+    /// least the length with `bytes`.
+    fn extend_within(&mut self, bytes: &[u8], max: usize) {
+        let needed = self.bytes.len() + bytes.len();
+        if needed > self.bytes.capacity() {
+            let doubled = self.bytes.capacity().saturating_mul(2);
+            let mut grown = Vec::with_capacity(needed.max(doubled).min(max));
+            grown.extend_from_slice(&self.bytes);
+            drop(Password { bytes: mem::replace(&mut self.bytes, grown) });
+        }
+        self.bytes.extend_from_slice(bytes);
+    }
+}
+
+/// A `Password` which arrives in chunks, e.g. from a streaming form parser
+/// or a serial link.
+///
+/// The chunks are appended to a single buffer, which doubles when it is
+/// full up to the maximal length. The buffers left behind are zeroed, as is
+/// the last one when the builder or its `Password` is dropped. Input past
+/// the maximal length is rejected by `update` before any hashing, the hash
+/// of the password does not depend on how it was split into chunks.
+///
+/// ```
+/// # use scrypt::{Params, PasswordBuilder};
+/// let mut builder = PasswordBuilder::new();
+/// builder.update(b"correct horse ")?;
+/// builder.update(b"battery staple")?;
+/// let mut key = [0u8; 32];
+/// builder.finalize_hash(b"salt", &Params::new(4, 8, 1)?, &mut key)?;
+/// # Ok::<(), scrypt::errors::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct PasswordBuilder {
+    password: Password,
+    max_len: usize,
+}
+
+impl PasswordBuilder {
+    /// The maximal length of `new()`, 64 KiB as of `LengthPolicy::default()`.
+    pub const DEFAULT_MAX_LEN: usize = 64 * 1024;
+
+    /// An empty password of at most `DEFAULT_MAX_LEN` bytes.
+    pub fn new() -> PasswordBuilder {
+        PasswordBuilder::with_max_len(PasswordBuilder::DEFAULT_MAX_LEN)
+    }
+
+    /// An empty password of at most `max_len` bytes.
+    pub fn with_max_len(max_len: usize) -> PasswordBuilder {
+        PasswordBuilder { password: Password::default(), max_len }
+    }
+
+    /// Append `bytes` to the password.
+    ///
+    /// # Return
+    /// `Err(InputTooLong)` if the password would be longer than the maximal
+    /// length, in which case nothing is appended.
+    pub fn update(&mut self, bytes: &[u8]) -> Result<(), InputTooLong> {
+        let len = self.password.bytes.len().saturating_add(bytes.len());
+        if len > self.max_len { Err(InputTooLong::new(len, self.max_len))?; }
+        self.password.extend_within(bytes, self.max_len);
+        Ok(())
+    }
+
+    /// The number of bytes appended so far.
+    pub fn len(&self) -> usize {
+        self.password.bytes.len()
+    }
+
+    /// `true` if nothing has been appended.
+    pub fn is_empty(&self) -> bool {
+        self.password.bytes.is_empty()
+    }
+
+    /// The password, without a copy.
+    pub fn finish(self) -> Password {
+        self.password
+    }
+
+    /// Derive a key from the password like `scrypt()`.
+    pub fn finalize_hash(self, salt: &[u8], params: &Params, output: &mut [u8])
+        -> Result<(), InvalidOutputLen>
+    {
+        scrypt(self.password.as_bytes(), salt, params, output)
+    }
+
+    /// Hash the password like `scrypt_simple_bytes()`.
+    #[cfg(feature="simple")]
+    pub fn finalize_simple(self, params: &Params) -> Result<String, HashError> {
+        scrypt_simple_bytes(self.password.as_bytes(), params)
+    }
+}
+
+impl Default for PasswordBuilder {
+    fn default() -> PasswordBuilder {
+        PasswordBuilder::new()
+    }
+}
+
+impl Drop for Password {
+    fn drop(&mut self) {
+        // the whole buffer, the bytes removed by `read_from` are beyond `len`
+        let capacity = self.bytes.capacity();
+        self.bytes.clear();
+        unsafe {
+            let buffer = self.bytes.as_mut_ptr();
+            for i in 0..capacity {
+                ptr::write_volatile(buffer.add(i), 0);
+            }
+        }
+        atomic::compiler_fence(Ordering::SeqCst);
+    }
+}
+
+impl From<String> for Password {
+    fn from(password: String) -> Password {
+        Password { bytes: password.into_bytes() }
+    }
+}
+
+impl From<Vec<u8>> for Password {
+    fn from(password: Vec<u8>) -> Password {
+        Password { bytes: password }
+    }
+}
+
+impl AsRef<[u8]> for Password {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Password(<redacted>)")
+    }
+}
+
+impl fmt::Display for Password {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
@@ -0,0 +1,136 @@
+//! Peppers, secret keys mixed into the password before hashing.
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use errors::KeyError;
+
+/// A secret key mixed into passwords by `scrypt_simple_peppered`, with an
+/// identifier which is stored in the hash string instead of the key.
+#[derive(Clone, Copy)]
+pub struct Pepper<'a> {
+    /// Identifies the key in the hash string, e.g. to rotate keys.
+    pub id: u8,
+    /// The secret key.
+    pub key: &'a [u8],
+}
+
+/// Mix `key` into `password`: `HMAC-SHA256(key, password)` is hashed by
+/// scrypt instead of the password.
+pub(crate) fn prehash(key: &[u8], password: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_varkey(key)
+        .expect("HMAC accepts keys of any length");
+    mac.input(password);
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&mac.result().code());
+    result
+}
+
+/// The peppers of an application during a key rotation: old keys verify
+/// existing hashes, the newest one is used for new hashes.
+///
+/// Keys are ordered by insertion, the last one inserted is the newest.
+#[derive(Clone, Default)]
+pub struct PepperSet {
+    keys: Vec<(u8, Vec<u8>)>,
+}
+
+impl PepperSet {
+    /// An empty set.
+    pub fn new() -> PepperSet {
+        PepperSet { keys: Vec::new() }
+    }
+
+    /// Add the key `key` with the identifier `id` as the newest key. A key
+    /// already stored for `id` is replaced.
+    pub fn insert(&mut self, id: u8, key: &[u8]) {
+        self.keys.retain(|&(i, _)| i != id);
+        self.keys.push((id, key.to_vec()));
+    }
+
+    /// The key of `id`, `None` if it is unknown.
+    pub fn get(&self, id: u8) -> Option<&[u8]> {
+        self.keys.iter().find(|&&(i, _)| i == id).map(|(_, key)| &key[..])
+    }
+
+    /// The key inserted last, `None` if the set is empty.
+    pub fn newest(&self) -> Option<Pepper<'_>> {
+        self.keys.last().map(|&(id, ref key)| Pepper { id, key })
+    }
+}
+
+/// A source of peppers which are not kept in memory, e.g. a KMS or an HSM.
+///
+/// The key is only lent to a callback, so that the provider can fetch it
+/// before and wipe it after the call. `StaticKeys` provides keys held in
+/// memory.
+pub trait KeyProvider {
+    /// Call `f` once with the key of `id`.
+    ///
+    /// # Return
+    /// `Err(KeyError::UnknownId)` if the provider has no key of `id` and
+    /// `Err(KeyError::Unavailable)` if it can not be fetched, `f` is not
+    /// called then.
+    fn key(&self, id: u8, f: &mut dyn FnMut(&[u8])) -> Result<(), KeyError>;
+}
+
+/// The `KeyProvider` of deployments without a KMS, which holds its keys in
+/// memory like `PepperSet`.
+#[derive(Clone, Default)]
+pub struct StaticKeys {
+    keys: PepperSet,
+}
+
+impl StaticKeys {
+    /// A provider without keys.
+    pub fn new() -> StaticKeys {
+        StaticKeys::default()
+    }
+
+    /// Add the key `key` with the identifier `id`, replacing a key already
+    /// stored for `id`.
+    pub fn insert(&mut self, id: u8, key: &[u8]) {
+        self.keys.insert(id, key);
+    }
+}
+
+impl From<PepperSet> for StaticKeys {
+    fn from(keys: PepperSet) -> StaticKeys {
+        StaticKeys { keys }
+    }
+}
+
+impl KeyProvider for StaticKeys {
+    fn key(&self, id: u8, f: &mut dyn FnMut(&[u8])) -> Result<(), KeyError> {
+        self.keys.key(id, f)
+    }
+}
+
+impl KeyProvider for PepperSet {
+    fn key(&self, id: u8, f: &mut dyn FnMut(&[u8])) -> Result<(), KeyError> {
+        self.get(id).map(f).ok_or(KeyError::UnknownId)
+    }
+}
+
+/// The single key of a `Pepper`, e.g. for `SimpleConfig::hash_with_keys`.
+impl<'a> KeyProvider for Pepper<'a> {
+    fn key(&self, id: u8, f: &mut dyn FnMut(&[u8])) -> Result<(), KeyError> {
+        if id != self.id { Err(KeyError::UnknownId)?; }
+        f(self.key);
+        Ok(())
+    }
+}
+
+/// `prehash()` with the key of `id` from `keys`, which is only borrowed
+/// for the HMAC.
+#[cfg(feature="std")]
+pub(crate) fn prehash_provided<K: KeyProvider + ?Sized>(keys: &K, id: u8, password: &[u8])
+    -> Result<[u8; 32], KeyError>
+{
+    let mut result = None;
+    keys.key(id, &mut |key| result = Some(prehash(key, password)))?;
+    // a provider which does not call `f` has not provided the key
+    result.ok_or(KeyError::Unavailable)
+}
@@ -0,0 +1,158 @@
+//! The [PHC string format][1] for scrypt hashes:
+//!
+//! `$scrypt$ln=<log_n>,r=<r>,p=<p>$<base64(salt)>$<base64(hash)>`
+//!
+//! Omitted parameters take the values `ln=15`, `r=8` and `p=1`.
+//!
+//! Python's passlib encodes salt and hash in its adapted base64 instead,
+//! the standard alphabet with `.` for `+`. Strings with a `.` in salt or
+//! hash are decoded in that alphabet, and their omitted parameters take
+//! the defaults of passlib, `ln=16`, `r=8` and `p=1`. Fields of neither
+//! `+` nor `.` are the same in both alphabets, so a passlib string without
+//! a `.` and with omitted parameters is read with the defaults above.
+//!
+//! [1]: https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md
+use core::str::FromStr;
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use base64ct::{Base64Unpadded, Encoding};
+
+use ct_base64;
+use errors::CheckError;
+use params::Params;
+
+/// Prefix identifying PHC scrypt strings.
+pub(crate) const PREFIX: &str = "$scrypt$";
+
+const DEFAULTS: (u8, u32, u32) = (15, 8, 1);
+/// The defaults of passlib's `scrypt` hasher.
+const PASSLIB_DEFAULTS: (u8, u32, u32) = (16, 8, 1);
+
+/// Encode a PHC string using the B64 encoding (standard alphabet without
+/// padding) for salt and hash.
+pub(crate) fn encode(params: &Params, salt: &[u8], hash: &[u8]) -> String {
+    format!(
+        "$scrypt$ln={},r={},p={}${}${}",
+        params.log_n, params.r, params.p,
+        Base64Unpadded::encode_string(salt),
+        Base64Unpadded::encode_string(hash),
+    )
+}
+
+/// Encode a PHC string in the adapted base64 of passlib, with all
+/// parameters.
+#[cfg(feature="simple")]
+pub(crate) fn encode_passlib(params: &Params, salt: &[u8], hash: &[u8]) -> String {
+    let ab64 = |bytes| Base64Unpadded::encode_string(bytes).replace('+', ".");
+    format!("$scrypt$ln={},r={},p={}${}${}", params.log_n, params.r, params.p, ab64(salt),
+        ab64(hash))
+}
+
+/// Decode a PHC string into parameters, salt and hash.
+pub(crate) fn decode(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    let (params, salt, hash) = split(hashed_value)?;
+    let salt = decode_b64(salt)?;
+    let hash = decode_b64(hash)?;
+    if hash.is_empty() { Err(CheckError::InvalidFormat)?; }
+
+    Ok((params, salt, hash))
+}
+
+/// Split a PHC string into parameters and the B64 encoded salt and hash.
+pub(crate) fn split(hashed_value: &str)
+    -> Result<(Params, &str, &str), CheckError>
+{
+    if !hashed_value.starts_with(PREFIX) { Err(CheckError::InvalidFormat)?; }
+    let mut fields = hashed_value[PREFIX.len()..].split('$');
+    let fields = (fields.next(), fields.next(), fields.next(), fields.next());
+
+    // the parameters field is optional, salt and hash are required
+    match fields {
+        (Some(params), Some(salt), Some(hash), None) => {
+            Ok((decode_params(params, defaults(salt, hash)?)?, salt, hash))
+        }
+        (Some(salt), Some(hash), None, None) => {
+            let (log_n, r, p) = defaults(salt, hash)?;
+            let params = Params::new(log_n, r, p)
+                .map_err(|_| CheckError::InvalidFormat)?;
+            Ok((params, salt, hash))
+        }
+        _ => Err(CheckError::InvalidFormat),
+    }
+}
+
+/// The defaults of omitted parameters, those of passlib for salt and hash
+/// in its alphabet, which must not be mixed with the standard one.
+fn defaults(salt: &str, hash: &str) -> Result<(u8, u32, u32), CheckError> {
+    let ab64 = is_ab64(salt) || is_ab64(hash);
+    if ab64 && (salt.contains('+') || hash.contains('+')) { Err(CheckError::InvalidFormat)?; }
+    Ok(if ab64 { PASSLIB_DEFAULTS } else { DEFAULTS })
+}
+
+fn is_ab64(field: &str) -> bool {
+    field.contains('.')
+}
+
+fn alphabet(field: &str) -> [u8; 2] {
+    if is_ab64(field) { ct_base64::AB64 } else { ct_base64::STANDARD }
+}
+
+fn decode_params(field: &str, defaults: (u8, u32, u32)) -> Result<Params, CheckError> {
+    let mut log_n = None;
+    let mut r = None;
+    let mut p = None;
+
+    for pair in field.split(',') {
+        let mut kv = pair.splitn(2, '=');
+        let key = kv.next().ok_or(CheckError::InvalidFormat)?;
+        let value = kv.next().ok_or(CheckError::InvalidFormat)
+            .and_then(decode_decimal)?;
+        let slot = match key {
+            "ln" => &mut log_n,
+            "r" => &mut r,
+            "p" => &mut p,
+            _ => Err(CheckError::InvalidFormat)?,
+        };
+        if slot.replace(value).is_some() { Err(CheckError::InvalidFormat)?; }
+    }
+
+    let log_n = log_n.unwrap_or(defaults.0 as u32);
+    if log_n > 0xff { Err(CheckError::InvalidFormat)?; }
+    Params::new(log_n as u8, r.unwrap_or(defaults.1), p.unwrap_or(defaults.2))
+        .map_err(|_| CheckError::InvalidFormat)
+}
+
+/// Parse a decimal number without sign or leading zeros.
+pub(crate) fn decode_decimal<T: FromStr>(value: &str) -> Result<T, CheckError> {
+    // PHC decimal values carry neither signs nor leading zeros
+    let canonical = !value.is_empty()
+        && value.bytes().all(|b| b.is_ascii_digit())
+        && (value == "0" || !value.starts_with('0'));
+    if !canonical { Err(CheckError::InvalidFormat)?; }
+    value.parse().map_err(|_| CheckError::InvalidFormat)
+}
+
+fn decode_b64(field: &str) -> Result<Vec<u8>, CheckError> {
+    check_b64(field)?;
+    ct_base64::decode_in(field, alphabet(field))
+}
+
+/// Decode a B64 field, of the standard or the adapted alphabet, into
+/// `output`, returning the decoded length.
+///
+/// `output` must hold `(field.len() + 3) / 4 * 3` bytes.
+pub(crate) fn decode_b64_slice(field: &str, output: &mut [u8])
+    -> Result<usize, CheckError>
+{
+    check_b64(field)?;
+    ct_base64::decode_slice_in(field, alphabet(field), output)
+}
+
+fn check_b64(field: &str) -> Result<(), CheckError> {
+    // the `base64` crate silently accepts padding, B64 has none
+    if field.contains('=') { Err(CheckError::InvalidFormat)?; }
+    Ok(())
+}
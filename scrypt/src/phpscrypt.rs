@@ -0,0 +1,82 @@
+//! The format of `Password::hash()` of the PHP extension `php-scrypt`:
+//!
+//! `<N>$<r>$<p>$<salt>$<hex(hash)>`
+//!
+//! The strings have no prefix. `N` is stored itself, the hash is always 256
+//! bits, encoded as lowercase hex. The salt is the literal text passed to
+//! scrypt, it is not decoded: the library generates it as the base64 of 8
+//! random bytes with `+` replaced by `.`, and a salt given by the caller is
+//! stored the same way, with `$` removed. Any text without `$` is accepted,
+//! but `Format::detect` takes hex salts of 16 digits or more for
+//! simple-scrypt strings.
+//!
+//! `Password::check()` ignores fields after the hash, they are rejected
+//! here, as are parameters with signs or leading zeros.
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use errors::CheckError;
+use params::Params;
+use phc::decode_decimal;
+use {hex, rubyscrypt, simplescrypt, werkzeug};
+
+/// Length of the hash produced by `php-scrypt`.
+pub(crate) const HASH_LEN: usize = 32;
+
+/// Length of the salts generated by `php-scrypt`, base64 of 8 bytes.
+#[cfg(feature="simple")]
+pub(crate) const SALT_LEN: usize = 12;
+
+/// Characters of base64 with `+` replaced by `.`, the salt characters of
+/// `php-scrypt` except its padding.
+#[cfg(feature="simple")]
+pub(crate) const SALT_CHARS: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789./";
+
+/// The salt of a string starting with three decimal fields, as those of
+/// `php-scrypt` and simple-scrypt do, unless `N` is in hex for the Ruby
+/// gem, see `rubyscrypt`.
+pub(crate) fn salt_field(hashed_value: &str) -> Option<&str> {
+    if rubyscrypt::matches(hashed_value) { return None; }
+    // without allocating, `ScryptHashRef::parse` detects formats too
+    let mut fields = hashed_value.splitn(5, '$');
+    let params = fields.by_ref().take(3)
+        .filter(|f| !f.is_empty() && f.bytes().all(|b| b.is_ascii_digit()))
+        .count();
+    if params == 3 { fields.next() } else { None }
+}
+
+/// Check if `hashed_value` is a `php-scrypt` string, which starts with
+/// three decimal fields and has no hex salt, see `simplescrypt`.
+pub(crate) fn matches(hashed_value: &str) -> bool {
+    salt_field(hashed_value).is_some_and(|salt| !simplescrypt::is_salt(salt.as_bytes()))
+}
+
+/// Encode a `php-scrypt` string.
+pub(crate) fn encode(params: &Params, salt: &str, hash: &[u8]) -> String {
+    format!(
+        "{}${}${}${}${}",
+        1u64 << params.log_n, params.r, params.p, salt, hex::encode(hash),
+    )
+}
+
+/// Decode a `php-scrypt` string into parameters, salt and hash.
+pub(crate) fn decode(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    let fields: Vec<&str> = hashed_value.split('$').collect();
+    if fields.len() != 5 { Err(CheckError::InvalidFormat)?; }
+
+    let n: u64 = decode_decimal(fields[0])?;
+    if !n.is_power_of_two() || n < 2 { Err(CheckError::InvalidFormat)?; }
+    let params = Params::new(
+        n.trailing_zeros() as u8,
+        decode_decimal(fields[1])?,
+        decode_decimal(fields[2])?,
+    ).map_err(|_| CheckError::InvalidFormat)?;
+
+    let hash = werkzeug::decode_hex(fields[4])?;
+    if hash.len() != HASH_LEN { Err(CheckError::InvalidFormat)?; }
+
+    Ok((params, fields[3].as_bytes().to_vec(), hash))
+}
@@ -0,0 +1,224 @@
+//! The `scrypt-params` of RFC 7914 section 7, the key derivation of PBES2
+//! encrypted PKCS#8 keys written by e.g. `openssl pkcs8 -topk8 -scrypt`:
+//!
+//! ```text
+//! scrypt-params ::= SEQUENCE {
+//!     salt OCTET STRING,
+//!     costParameter INTEGER (1..MAX),
+//!     blockSize INTEGER (1..MAX),
+//!     parallelizationParameter INTEGER (1..MAX),
+//!     keyLength INTEGER (1..MAX) OPTIONAL }
+//! ```
+//!
+//! Within PBES2 they follow `id-scrypt` in the `AlgorithmIdentifier` of the
+//! `keyDerivationFunc`. Only the DER encoding is accepted. The encryption
+//! scheme and the decryption of the key are left to the caller.
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use core::fmt;
+
+use errors::DerError;
+use params::Params;
+use redact::Fingerprint;
+use scrypt;
+
+/// The DER contents of the object identifier `id-scrypt`,
+/// 1.3.6.1.4.1.11591.4.11.
+pub const ID_SCRYPT: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xda, 0x47, 0x04, 0x0b];
+
+const SEQUENCE: u8 = 0x30;
+const OCTET_STRING: u8 = 0x04;
+const INTEGER: u8 = 0x02;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+
+/// Decoded `scrypt-params`. `Debug` prints a fingerprint of the salt
+/// instead of the salt.
+#[derive(Clone, Eq, PartialEq)]
+pub struct KdfParams {
+    /// The salt.
+    pub salt: Vec<u8>,
+    /// `costParameter`, `blockSize` and `parallelizationParameter`.
+    pub params: Params,
+    /// The length of the derived key, if stored.
+    pub key_length: Option<u32>,
+}
+
+impl KdfParams {
+    /// Decode the DER encoding of `scrypt-params`.
+    ///
+    /// # Return
+    /// `Err(DerError::Malformed)` if `der` is not exactly one DER encoded
+    /// `scrypt-params`, `Err(DerError::InvalidParams)` if the cost is not a
+    /// power of two or the parameters are rejected by `Params::new`
+    /// and `Err(DerError::InvalidKeyLength)` if `keyLength` is 0 or above
+    /// `u32::MAX`.
+    pub fn from_der(der: &[u8]) -> Result<KdfParams, DerError> {
+        let mut outer = Reader(der);
+        let mut fields = Reader(outer.read(SEQUENCE)?);
+        outer.finish()?;
+
+        let salt = fields.read(OCTET_STRING)?.to_vec();
+        let n = decode_integer(fields.read(INTEGER)?)?;
+        let r = decode_integer(fields.read(INTEGER)?)?;
+        let p = decode_integer(fields.read(INTEGER)?)?;
+        let key_length = if fields.0.is_empty() {
+            None
+        } else {
+            match decode_integer(fields.read(INTEGER)?)? {
+                Some(len) if (1..=u64::from(u32::MAX)).contains(&len) => Some(len as u32),
+                _ => Err(DerError::InvalidKeyLength)?,
+            }
+        };
+        fields.finish()?;
+
+        let params = match (n, r, p) {
+            (Some(n), Some(r), Some(p))
+                if n.is_power_of_two() && r <= u64::from(u32::MAX) && p <= u64::from(u32::MAX) =>
+            {
+                Params::new(n.trailing_zeros() as u8, r as u32, p as u32).ok()
+            }
+            _ => None,
+        }.ok_or(DerError::InvalidParams)?;
+
+        Ok(KdfParams { salt, params, key_length })
+    }
+
+    /// Encode `scrypt-params` in DER.
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut fields = Vec::new();
+        write(&mut fields, OCTET_STRING, &self.salt);
+        write_integer(&mut fields, 1u64 << self.params.log_n);
+        write_integer(&mut fields, u64::from(self.params.r));
+        write_integer(&mut fields, u64::from(self.params.p));
+        if let Some(len) = self.key_length { write_integer(&mut fields, u64::from(len)); }
+        let mut der = Vec::new();
+        write(&mut der, SEQUENCE, &fields);
+        der
+    }
+
+    /// Decode the DER encoding of the `AlgorithmIdentifier` of `id-scrypt`
+    /// with `scrypt-params`, the `keyDerivationFunc` of PBES2.
+    ///
+    /// # Return
+    /// `Err(DerError::UnsupportedAlgorithm)` for other algorithms and the
+    /// errors of `from_der()`.
+    pub fn from_algorithm_identifier(der: &[u8]) -> Result<KdfParams, DerError> {
+        let mut outer = Reader(der);
+        let mut fields = Reader(outer.read(SEQUENCE)?);
+        outer.finish()?;
+        if fields.read(OBJECT_IDENTIFIER)? != ID_SCRYPT { Err(DerError::UnsupportedAlgorithm)?; }
+        KdfParams::from_der(fields.0)
+    }
+
+    /// Encode the `AlgorithmIdentifier` of `id-scrypt` with these parameters.
+    pub fn to_algorithm_identifier(&self) -> Vec<u8> {
+        let mut fields = Vec::new();
+        write(&mut fields, OBJECT_IDENTIFIER, ID_SCRYPT);
+        fields.extend_from_slice(&self.to_der());
+        let mut der = Vec::new();
+        write(&mut der, SEQUENCE, &fields);
+        der
+    }
+
+    /// Derive the key of the encryption scheme, e.g. the 32 bytes of
+    /// AES-256-CBC, from `password` into `output`.
+    ///
+    /// # Return
+    /// `Err(DerError::InvalidKeyLength)` if `keyLength` is stored and is not
+    /// the length of `output`, or `scrypt()` rejects the length.
+    pub fn derive(&self, password: &[u8], output: &mut [u8]) -> Result<(), DerError> {
+        if self.key_length.is_some_and(|len| len as usize != output.len()) {
+            Err(DerError::InvalidKeyLength)?;
+        }
+        scrypt(password, &self.salt, &self.params, output)
+            .map_err(|_| DerError::InvalidKeyLength)
+    }
+}
+
+impl fmt::Debug for KdfParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KdfParams")
+            .field("salt", &Fingerprint(&self.salt))
+            .field("params", &self.params)
+            .field("key_length", &self.key_length)
+            .finish()
+    }
+}
+
+/// The DER elements of a slice, one at a time.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    /// Read the contents of the next element, which must have `tag`.
+    fn read(&mut self, tag: u8) -> Result<&'a [u8], DerError> {
+        let data = self.0;
+        if data.len() < 2 || data[0] != tag { Err(DerError::Malformed)?; }
+        // the short form below 128, else the long form of at most 4 bytes
+        // without leading zeros, no indefinite length
+        let (len, header) = match data[1] {
+            len @ 0..=0x7f => (len as usize, 2),
+            0x81..=0x84 => {
+                let count = (data[1] & 0x7f) as usize;
+                let bytes = data.get(2..2 + count).ok_or(DerError::Malformed)?;
+                let len = bytes.iter().fold(0usize, |len, &b| len << 8 | b as usize);
+                if bytes[0] == 0 || len < 0x80 { Err(DerError::Malformed)?; }
+                (len, 2 + count)
+            }
+            _ => Err(DerError::Malformed)?,
+        };
+        let contents = data.get(header..header + len).ok_or(DerError::Malformed)?;
+        self.0 = &data[header + len..];
+        Ok(contents)
+    }
+
+    /// Check that all elements were read.
+    fn finish(&self) -> Result<(), DerError> {
+        if !self.0.is_empty() { Err(DerError::Malformed)?; }
+        Ok(())
+    }
+}
+
+/// Decode the contents of a DER `INTEGER`.
+///
+/// # Return
+/// `Ok(None)` for negative values and values above `u64::MAX`, which are
+/// well-formed but out of range, `Err(DerError::Malformed)` for encodings
+/// which are not minimal.
+fn decode_integer(contents: &[u8]) -> Result<Option<u64>, DerError> {
+    let minimal = match *contents {
+        [] => false,
+        [0, second, ..] => second & 0x80 != 0,
+        [0xff, second, ..] => second & 0x80 == 0,
+        _ => true,
+    };
+    if !minimal { Err(DerError::Malformed)?; }
+    if contents[0] & 0x80 != 0 { return Ok(None); }
+    let digits = if contents[0] == 0 { &contents[1..] } else { contents };
+    if digits.len() > 8 { return Ok(None); }
+    Ok(Some(digits.iter().fold(0u64, |value, &b| value << 8 | u64::from(b))))
+}
+
+fn write(der: &mut Vec<u8>, tag: u8, contents: &[u8]) {
+    der.push(tag);
+    let len = contents.len();
+    if len < 0x80 {
+        der.push(len as u8);
+    } else {
+        let bytes = (len as u64).to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        der.push(0x80 | (8 - skip) as u8);
+        der.extend_from_slice(&bytes[skip..]);
+    }
+    der.extend_from_slice(contents);
+}
+
+fn write_integer(der: &mut Vec<u8>, value: u64) {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take(7).take_while(|&&b| b == 0).count();
+    let mut contents = Vec::with_capacity(9);
+    // a leading zero keeps the value positive
+    if bytes[skip] & 0x80 != 0 { contents.push(0); }
+    contents.extend_from_slice(&bytes[skip..]);
+    write(der, INTEGER, &contents);
+}
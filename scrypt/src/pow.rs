@@ -0,0 +1,63 @@
+//! The proof of work of Litecoin and the coins derived from it, e.g.
+//! Dogecoin: scrypt with `N = 1024`, `r = 1` and `p = 1` of an 80-byte block
+//! header, which is both password and salt.
+use byteorder::{ByteOrder, LittleEndian};
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use sha2::Sha256;
+
+use romix::salsa20_words;
+
+const N: usize = 1024;
+
+/// A block of `r = 1` as two halves of 16 little-endian words.
+type Block = [[u32; 16]; 2];
+
+/// The 256-bit proof of work hash of the block header `input`, equal to
+/// `scrypt(input, input, N = 1024, r = 1, p = 1)`.
+///
+/// The parameters are fixed, so the 128 KiB of `V` are on the stack and
+/// ROMix works on words instead of bytes, without heap allocations. The
+/// calling thread needs about 130 KiB of stack.
+///
+/// The hash is compared with the target of the header as a little-endian
+/// 256-bit number.
+pub fn scrypt_1024_1_1_256(input: &[u8; 80]) -> [u8; 32] {
+    let mut bytes = [0u8; 128];
+    pbkdf2::<Hmac<Sha256>>(input, input, 1, &mut bytes);
+    let mut x: Block = [[0u32; 16]; 2];
+    LittleEndian::read_u32_into(&bytes[..64], &mut x[0]);
+    LittleEndian::read_u32_into(&bytes[64..], &mut x[1]);
+
+    let mut v: [Block; N] = [[[0u32; 16]; 2]; N];
+    for v_i in v.iter_mut() {
+        *v_i = x;
+        block_mix(&mut x);
+    }
+    for _ in 0..N {
+        // Integerify: the first word of the last half
+        let j = x[1][0] as usize & (N - 1);
+        for (x_half, v_half) in x.iter_mut().zip(v[j].iter()) {
+            for (x_i, &v_i) in x_half.iter_mut().zip(v_half.iter()) {
+                *x_i ^= v_i;
+            }
+        }
+        block_mix(&mut x);
+    }
+
+    LittleEndian::write_u32_into(&x[0], &mut bytes[..64]);
+    LittleEndian::write_u32_into(&x[1], &mut bytes[64..]);
+    let mut output = [0u8; 32];
+    pbkdf2::<Hmac<Sha256>>(input, &bytes, 1, &mut output);
+    output
+}
+
+/// BlockMix of `r = 1` in place: `Y0 = H(B1 ^ B0)`, `Y1 = H(Y0 ^ B1)`.
+fn block_mix(x: &mut Block) {
+    let (lo, hi) = x.split_at_mut(1);
+    let (lo, hi) = (&mut lo[0], &mut hi[0]);
+    for (lo_i, &hi_i) in lo.iter_mut().zip(hi.iter()) { *lo_i ^= hi_i; }
+    salsa20_words(lo, 8);
+    for (hi_i, &lo_i) in hi.iter_mut().zip(lo.iter()) { *hi_i ^= lo_i; }
+    salsa20_words(hi, 8);
+}
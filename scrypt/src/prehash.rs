@@ -0,0 +1,74 @@
+//! Pre-hashing of long passwords and limits on their length.
+use sha2::{Digest, Sha256};
+
+use errors::CheckError;
+#[cfg(feature="simple")]
+use errors::HashError;
+
+/// Longest password in bytes accepted by the `scrypt_simple` and
+/// `scrypt_check` families, `SimpleConfig` and `Verifier` by default.
+///
+/// PBKDF2 hashes the whole password before scrypt starts its memory-hard
+/// work, so a megabyte sent by a hostile client costs CPU time without any
+/// limit. `scrypt()` itself accepts passwords of any length.
+pub const DEFAULT_MAX_PASSWORD_LEN: usize = 4096;
+
+/// Limits on the length of passwords, see `scrypt_simple_prehashed`.
+///
+/// PBKDF2 hashes the password twice for every block of output, so every
+/// kilobyte of a password adds to the cost of a login. Passwords longer
+/// than `prehash_above` bytes are hashed with SHA-256 before scrypt and
+/// passwords longer than `max_len` bytes are rejected without hashing.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LengthPolicy {
+    /// Longest password hashed as is, longer ones are replaced by their
+    /// SHA-256 digest. Stored in the hash string.
+    pub prehash_above: u16,
+    /// Longest password accepted, both for new hashes and verification.
+    pub max_len: usize,
+}
+
+impl Default for LengthPolicy {
+    /// Pre-hash passwords above 1 KiB and reject those above 64 KiB.
+    fn default() -> LengthPolicy {
+        LengthPolicy { prehash_above: 1024, max_len: 64 * 1024 }
+    }
+}
+
+/// The password scrypt hashes for `password`: its SHA-256 digest if it is
+/// longer than `threshold` bytes, the password itself otherwise.
+pub(crate) fn prehash<'a>(password: &'a [u8], threshold: u16, digest: &'a mut [u8; 32])
+    -> &'a [u8]
+{
+    if password.len() <= threshold as usize { return password; }
+    digest.copy_from_slice(&Sha256::digest(password));
+    &digest[..]
+}
+
+/// A password longer than the limit of an entry point, converted into the
+/// `PasswordTooLong` error of its family.
+pub(crate) struct TooLong {
+    len: usize,
+    max: usize,
+}
+
+#[cfg(feature="simple")]
+impl From<TooLong> for HashError {
+    fn from(e: TooLong) -> HashError {
+        HashError::PasswordTooLong { len: e.len, max: e.max }
+    }
+}
+
+impl From<TooLong> for CheckError {
+    fn from(e: TooLong) -> CheckError {
+        CheckError::PasswordTooLong { len: e.len, max: e.max }
+    }
+}
+
+/// Check `password` against the limit `max`, `None` accepts any length.
+pub(crate) fn check_len(password: &[u8], max: Option<usize>) -> Result<(), TooLong> {
+    match max {
+        Some(max) if password.len() > max => Err(TooLong { len: password.len(), max }),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,30 @@
+//! `Debug` output for salts and hashes, so types holding them can be
+//! logged.
+use core::fmt;
+
+use sha2::{Digest, Sha256};
+
+/// Prints the length and a fingerprint of bytes which identify a hash but
+/// are not secret, e.g. salts: the first 4 bytes of their SHA-256, which
+/// are the same for the same bytes, so log lines can be correlated.
+pub(crate) struct Fingerprint<'a>(pub &'a [u8]);
+
+impl<'a> fmt::Debug for Fingerprint<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digest = Sha256::digest(self.0);
+        write!(f, "<{} bytes, fingerprint {:02x}{:02x}{:02x}{:02x}>",
+            self.0.len(), digest[0], digest[1], digest[2], digest[3])
+    }
+}
+
+/// Prints only the length of secret bytes, e.g. hashes. Even a short
+/// fingerprint of a hash would let guesses of the password be checked.
+#[cfg(feature="simple-verify")]
+pub(crate) struct Redacted(pub usize);
+
+#[cfg(feature="simple-verify")]
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{} bytes, redacted>", self.0)
+    }
+}
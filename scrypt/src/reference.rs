@@ -0,0 +1,129 @@
+//! A transcription of the pseudocode of RFC 7914 \[1\], for review and for
+//! differential tests of the optimized implementation of this crate. It
+//! checks no parameters and is slow, use `scrypt()` instead.
+//!
+//! Each function is one section of the RFC, with its variable names. The
+//! crate's tests include this file to run against it on every test run, so
+//! it only uses the dependencies of the crate, not its internals.
+//!
+//! \[1\] - [RFC 7914](https://tools.ietf.org/html/rfc7914)
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use byteorder::{ByteOrder, LittleEndian};
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use sha2::Sha256;
+
+/// Section 3, the Salsa20/8 core of a 64 byte block.
+pub fn salsa20_8(input: &[u8]) -> Vec<u8> {
+    fn r(a: u32, b: u32) -> u32 { a.rotate_left(b) }
+
+    let mut b = [0u32; 16];
+    LittleEndian::read_u32_into(input, &mut b);
+    let mut x = b;
+    for _ in 0..4 {
+        // operate on columns
+        x[ 4] ^= r(x[ 0].wrapping_add(x[12]), 7);  x[ 8] ^= r(x[ 4].wrapping_add(x[ 0]), 9);
+        x[12] ^= r(x[ 8].wrapping_add(x[ 4]),13);  x[ 0] ^= r(x[12].wrapping_add(x[ 8]),18);
+        x[ 9] ^= r(x[ 5].wrapping_add(x[ 1]), 7);  x[13] ^= r(x[ 9].wrapping_add(x[ 5]), 9);
+        x[ 1] ^= r(x[13].wrapping_add(x[ 9]),13);  x[ 5] ^= r(x[ 1].wrapping_add(x[13]),18);
+        x[14] ^= r(x[10].wrapping_add(x[ 6]), 7);  x[ 2] ^= r(x[14].wrapping_add(x[10]), 9);
+        x[ 6] ^= r(x[ 2].wrapping_add(x[14]),13);  x[10] ^= r(x[ 6].wrapping_add(x[ 2]),18);
+        x[ 3] ^= r(x[15].wrapping_add(x[11]), 7);  x[ 7] ^= r(x[ 3].wrapping_add(x[15]), 9);
+        x[11] ^= r(x[ 7].wrapping_add(x[ 3]),13);  x[15] ^= r(x[11].wrapping_add(x[ 7]),18);
+        // operate on rows
+        x[ 1] ^= r(x[ 0].wrapping_add(x[ 3]), 7);  x[ 2] ^= r(x[ 1].wrapping_add(x[ 0]), 9);
+        x[ 3] ^= r(x[ 2].wrapping_add(x[ 1]),13);  x[ 0] ^= r(x[ 3].wrapping_add(x[ 2]),18);
+        x[ 6] ^= r(x[ 5].wrapping_add(x[ 4]), 7);  x[ 7] ^= r(x[ 6].wrapping_add(x[ 5]), 9);
+        x[ 4] ^= r(x[ 7].wrapping_add(x[ 6]),13);  x[ 5] ^= r(x[ 4].wrapping_add(x[ 7]),18);
+        x[11] ^= r(x[10].wrapping_add(x[ 9]), 7);  x[ 8] ^= r(x[11].wrapping_add(x[10]), 9);
+        x[ 9] ^= r(x[ 8].wrapping_add(x[11]),13);  x[10] ^= r(x[ 9].wrapping_add(x[ 8]),18);
+        x[12] ^= r(x[15].wrapping_add(x[14]), 7);  x[13] ^= r(x[12].wrapping_add(x[15]), 9);
+        x[14] ^= r(x[13].wrapping_add(x[12]),13);  x[15] ^= r(x[14].wrapping_add(x[13]),18);
+    }
+    for (b_i, x_i) in b.iter_mut().zip(&x) {
+        *b_i = x_i.wrapping_add(*b_i);
+    }
+    let mut output = vec![0u8; 64];
+    LittleEndian::write_u32_into(&b, &mut output);
+    output
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(a, b)| a ^ b).collect()
+}
+
+/// Section 4, scryptBlockMix of the `2 * r` 64 byte blocks of `b`.
+pub fn block_mix(b: &[u8]) -> Vec<u8> {
+    let b: Vec<&[u8]> = b.chunks(64).collect();
+    let r = b.len() / 2;
+
+    // 1. X = B[2 * r - 1]
+    let mut x = b[2 * r - 1].to_vec();
+    // 2. for i = 0 to 2 * r - 1 do
+    let mut y = Vec::new();
+    for b_i in &b {
+        //   T = X xor B[i]
+        let t = xor(&x, b_i);
+        //   X = Salsa (T)
+        x = salsa20_8(&t);
+        //   Y[i] = X
+        y.push(x.clone());
+    }
+    // 3. B' = (Y[0], Y[2], ..., Y[2 * r - 2], Y[1], Y[3], ..., Y[2 * r - 1])
+    let mut b_ = Vec::new();
+    for i in 0..r { b_.extend_from_slice(&y[2 * i]); }
+    for i in 0..r { b_.extend_from_slice(&y[2 * i + 1]); }
+    b_
+}
+
+/// Integerify, the last 64 byte block of `x` as a little-endian integer,
+/// modulo the power of two `n`.
+fn integerify(x: &[u8], n: u64) -> u64 {
+    LittleEndian::read_u64(&x[x.len() - 64..]) % n
+}
+
+/// Section 5, scryptROMix of the `128 * r` bytes `b` with the CPU/memory
+/// cost `n`, a power of two.
+pub fn ro_mix(b: &[u8], n: u64) -> Vec<u8> {
+    // 1. X = B
+    let mut x = b.to_vec();
+    // 2. for i = 0 to N - 1 do
+    let mut v = Vec::new();
+    for _ in 0..n {
+        //   V[i] = X
+        v.push(x.clone());
+        //   X = scryptBlockMix (X)
+        x = block_mix(&x);
+    }
+    // 3. for i = 0 to N - 1 do
+    for _ in 0..n {
+        //   j = Integerify (X) mod N
+        let j = integerify(&x, n);
+        //   T = X xor V[j]
+        let t = xor(&x, &v[j as usize]);
+        //   X = scryptBlockMix (T)
+        x = block_mix(&t);
+    }
+    // 4. B' = X
+    x
+}
+
+/// Section 6, scrypt of `password` and `salt` with `N = n`, `r` and `p`, a
+/// key of `dk_len` bytes.
+pub fn scrypt(password: &[u8], salt: &[u8], n: u64, r: usize, p: usize, dk_len: usize)
+    -> Vec<u8>
+{
+    // 1. Initialize an array B consisting of p blocks of 128 * r octets each:
+    //    B[0] || B[1] || ... || B[p - 1] = PBKDF2-HMAC-SHA256 (P, S, 1, p * 128 * r)
+    let mut b = vec![0u8; p * 128 * r];
+    pbkdf2::<Hmac<Sha256>>(password, salt, 1, &mut b);
+    // 2. for i = 0 to p - 1 do
+    //      B[i] = scryptROMix (r, B[i], N)
+    let b: Vec<u8> = b.chunks(128 * r).flat_map(|b_i| ro_mix(b_i, n)).collect();
+    // 3. DK = PBKDF2-HMAC-SHA256 (P, B, 1, dkLen)
+    let mut dk = vec![0u8; dk_len];
+    pbkdf2::<Hmac<Sha256>>(password, &b, 1, &mut dk);
+    dk
+}
@@ -0,0 +1,73 @@
+//! Instrumentation of scrypt for research, e.g. on its side channels.
+//! **Unstable:** the events and functions of this module may change or be
+//! removed in any release, it only exists with the `research` feature.
+//!
+//! `scrypt_instrumented` reports the index `j` of the block of `V` read by
+//! every iteration of the second loop of ROMix, where scrypt depends on the
+//! password. Secret dependent indices are the reason to study them: never
+//! log or transmit the events of real passwords.
+use errors::InvalidOutputLen;
+use params::Params;
+use romix::{scrypt_ro_mix_observed, Block, STREAM_MIN};
+use selftest::Backend;
+use {block_len, check_output_len, pbkdf2_sha256};
+
+/// An iteration of the second loop of ROMix.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RomixEvent {
+    /// The block of `B`, `0..p`, whose ROMix this is.
+    pub lane: usize,
+    /// The iteration of the second loop, `0..N`.
+    pub iteration: usize,
+    /// The block of `V` read by the iteration, `integerify(X) mod N`.
+    pub j: usize,
+}
+
+/// `scrypt()`, calling `observer` for every iteration of the second loop of
+/// ROMix, `N` times for each of the `p` lanes in order. The derived key is
+/// that of `scrypt()`, which runs on the backend of `scrypt()` for the size
+/// of `V`.
+///
+/// # Return
+/// `Err(InvalidOutputLen)` under the conditions of `scrypt()`.
+pub fn scrypt_instrumented(
+    password: &[u8], salt: &[u8], params: &Params, output: &mut [u8],
+    observer: &mut dyn FnMut(RomixEvent),
+) -> Result<(), InvalidOutputLen> {
+    let stream = (128 * params.r as usize) << params.log_n >= STREAM_MIN;
+    instrumented(password, salt, params, output, stream, observer)
+}
+
+/// `scrypt_instrumented` on `backend`, whatever the size of `V`, e.g. to
+/// compare the events of every backend of `Backend::compiled()`.
+///
+/// # Return
+/// `Err(InvalidOutputLen)` under the conditions of `scrypt()`.
+pub fn scrypt_instrumented_on(
+    backend: Backend, password: &[u8], salt: &[u8], params: &Params, output: &mut [u8],
+    observer: &mut dyn FnMut(RomixEvent),
+) -> Result<(), InvalidOutputLen> {
+    instrumented(password, salt, params, output, backend == Backend::Streaming, observer)
+}
+
+fn instrumented(
+    password: &[u8], salt: &[u8], params: &Params, output: &mut [u8], stream: bool,
+    observer: &mut dyn FnMut(RomixEvent),
+) -> Result<(), InvalidOutputLen> {
+    check_output_len(output)?;
+    let n = 1 << params.log_n;
+    let r2 = (params.r as usize) * 2;
+    let mut b = vec![0u8; block_len(params)];
+    let mut x = vec![Block::default(); b.len() / 64];
+    let (mut v, mut t) = (vec![Block::default(); n * r2], vec![Block::default(); r2]);
+    pbkdf2_sha256(password, salt, &mut b);
+    Block::load(&b, &mut x);
+    for (lane, chunk) in x.chunks_mut(r2).enumerate() {
+        scrypt_ro_mix_observed(chunk, &mut v, &mut t, n, stream, &mut |iteration, j| {
+            observer(RomixEvent { lane, iteration, j })
+        });
+    }
+    Block::store(&x, &mut b);
+    pbkdf2_sha256(password, &b, output);
+    Ok(())
+}
@@ -1,14 +1,85 @@
-use byte_tools::copy as copy_memory;
 use byteorder::{ByteOrder, LittleEndian};
 
 
-/// The salsa20/8 core function.
-fn salsa20_8(input: &[u8], output: &mut [u8]) {
+/// A 64 byte block of BlockMix, as the 16 little-endian words Salsa20/8
+/// works on.
+///
+/// ROMix, BlockMix and their cores work on slices of blocks, `2 * r` of
+/// them for each block of `B`, and bytes are only read into and written
+/// from them at the boundary, see `Block::load` and `Block::store`. The
+/// alignment to a cache line also aligns every block of `V` for the
+/// vector loads and stores of SIMD code.
+#[repr(C, align(64))]
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Block(pub(crate) [u32; 16]);
+
+impl Block {
+    /// Read the little-endian words of `bytes` into `blocks`, 64 bytes per
+    /// block.
+    pub(crate) fn load(bytes: &[u8], blocks: &mut [Block]) {
+        debug_assert_eq!(bytes.len(), blocks.len() * 64);
+        for (chunk, block) in bytes.chunks(64).zip(blocks.iter_mut()) {
+            LittleEndian::read_u32_into(chunk, &mut block.0);
+        }
+    }
+
+    /// Write the words of `blocks` into `bytes`, little-endian.
+    pub(crate) fn store(blocks: &[Block], bytes: &mut [u8]) {
+        debug_assert_eq!(bytes.len(), blocks.len() * 64);
+        for (block, chunk) in blocks.iter().zip(bytes.chunks_mut(64)) {
+            LittleEndian::write_u32_into(&block.0, chunk);
+        }
+    }
+
+    /// The memory of `blocks` as bytes in the order of the target, to
+    /// overwrite it with zeros.
+    pub(crate) fn as_bytes_mut(blocks: &mut [Block]) -> &mut [u8] {
+        // a block is 64 bytes without padding, any bytes are valid words
+        unsafe {
+            core::slice::from_raw_parts_mut(blocks.as_mut_ptr() as *mut u8, blocks.len() * 64)
+        }
+    }
+
+    /// The xor of the words of `self` and `other`.
+    fn xor(&self, other: &Block) -> Block {
+        let mut out = *self;
+        for (out_i, &other_i) in out.0.iter_mut().zip(other.0.iter()) {
+            *out_i ^= other_i;
+        }
+        out
+    }
+}
 
-    let mut x = [0u32; 16];
-    LittleEndian::read_u32_into(input, &mut x);
+/// The core function of BlockMix, which hashes one block.
+///
+/// ROMix and BlockMix are generic over it so that experimental variants,
+/// see `experimental`, reuse them. Scrypt is `Salsa8`, the functions
+/// without a core parameter are those of scrypt.
+pub(crate) trait MixingCore {
+    /// Hash `input` into `output`.
+    fn hash(input: &Block, output: &mut Block);
+}
 
-    let rounds = 8;
+/// The Salsa20/8 core of scrypt.
+pub(crate) struct Salsa8;
+
+impl MixingCore for Salsa8 {
+    fn hash(input: &Block, output: &mut Block) {
+        salsa20_8(input, output);
+    }
+}
+
+/// The salsa20/8 core function.
+pub(crate) fn salsa20_8(input: &Block, output: &mut Block) {
+    let mut b = input.0;
+    salsa20_words(&mut b, 8);
+    output.0 = b;
+}
+
+/// The salsa20 core function of `rounds` rounds on the little-endian words
+/// of a block, in place.
+pub(crate) fn salsa20_words(b: &mut [u32; 16], rounds: usize) {
+    let mut x = *b;
 
     macro_rules! run_round (
         ($($set_idx:expr, $idx_a:expr, $idx_b:expr, $rot:expr);*) => { {
@@ -53,62 +124,175 @@ fn salsa20_8(input: &[u8], output: &mut [u8]) {
         )
     }
 
-    for i in 0..16 {
-        LittleEndian::write_u32(
-            &mut output[i * 4..(i + 1) * 4],
-            x[i].wrapping_add(LittleEndian::read_u32(&input[i * 4..(i + 1) * 4])));
+    for (b_i, &x_i) in b.iter_mut().zip(x.iter()) {
+        *b_i = b_i.wrapping_add(x_i);
     }
 }
 
-fn xor(x: &[u8], y: &[u8], output: &mut [u8]) {
-    for ((out, &x_i), &y_i) in output.iter_mut().zip(x.iter()).zip(y.iter()) {
-        *out = x_i ^ y_i;
+pub(crate) fn xor(x: &[Block], y: &[Block], output: &mut [Block]) {
+    for ((out, x_i), y_i) in output.iter_mut().zip(x.iter()).zip(y.iter()) {
+        *out = x_i.xor(y_i);
     }
 }
 
 /// Execute the BlockMix operation
-/// input - the input vector. The length must be a multiple of 128.
+/// input - the input vector of `2 * r` blocks.
 /// output - the output vector. Must be the same length as input.
-fn scrypt_block_mix(input: &[u8], output: &mut [u8]) {
-    let mut x = [0u8; 64];
-    copy_memory(&input[input.len() - 64..], &mut x);
-
-    let mut t = [0u8; 64];
+///
+/// Each Salsa20/8 result is written straight to its shuffled position in
+/// `output`, even blocks to the first half and odd ones to the second, and
+/// read back from there as the next `X`, so no block is copied.
+pub(crate) fn scrypt_block_mix(input: &[Block], output: &mut [Block]) {
+    block_mix::<Salsa8>(input, output)
+}
 
-    for (i, chunk) in input.chunks(64).enumerate() {
-        xor(&x, chunk, &mut t);
-        salsa20_8(&t, &mut x);
-        let pos = if i % 2 == 0 { (i / 2) * 64 } else { (i / 2) * 64 + input.len() / 2 };
-        copy_memory(&x, &mut output[pos..pos + 64]);
+/// `scrypt_block_mix` with the core `C`.
+pub(crate) fn block_mix<C: MixingCore>(input: &[Block], output: &mut [Block]) {
+    // X starts as the last block of the input
+    let mut prev: Option<usize> = None;
+    for (i, chunk) in input.iter().enumerate() {
+        let t = match prev {
+            None => input[input.len() - 1].xor(chunk),
+            Some(pos) => output[pos].xor(chunk),
+        };
+        let pos = shuffled_index(i, input.len());
+        C::hash(&t, &mut output[pos]);
+        prev = Some(pos);
     }
 }
 
+/// The index in the output of BlockMix of its `i`-th Salsa20/8 block, for
+/// `len = 2 * r` blocks: the even blocks in order in the first half, the
+/// odd ones in the second.
+pub(crate) fn shuffled_index(i: usize, len: usize) -> usize {
+    if i % 2 == 0 { i / 2 } else { i / 2 + len / 2 }
+}
+
+/// Integerify of ROMix, the little-endian integer of the last block of `x`
+/// modulo `n`, a power of two.
+///
+/// The low 64 bits of the block are read, so `j` is not truncated for an
+/// `N` of more than `2^32`, and masked with `n - 1` before the conversion
+/// to a `usize`.
+pub(crate) fn integerify(x: &[Block], n: usize) -> usize {
+    let mask = n as u64 - 1;
+    let last = &x[x.len() - 1].0;
+    ((u64::from(last[1]) << 32 | u64::from(last[0])) & mask) as usize
+}
+
+/// The offset of the block `j` in `V`, for blocks of `B` of `len = 2 * r`
+/// blocks.
+///
+/// `j < N` and `128 * r * N` fits into a `usize` by the checks of
+/// `Params::new`, so the product does not overflow.
+pub(crate) fn v_offset(j: usize, len: usize) -> usize {
+    j * len
+}
+
+/// The size in bytes of `V` from which `scrypt_ro_mix` fills it with
+/// non-temporal stores, far above the caches. Below a few MiB they are
+/// slower, from N = 2^17 with r = 8, 128 MiB, they save a few percent.
+pub(crate) const STREAM_MIN: usize = 64 << 20;
+
 /// Execute the ROMix operation in-place.
-/// b - the data to operate on
+/// b - the `2 * r` blocks to operate on
 /// v - a temporary variable to store the vector V
 /// t - a temporary variable to store the result of the xor
 /// n - the scrypt parameter N
-pub(crate) fn scrypt_ro_mix(b: &mut [u8], v: &mut [u8], t: &mut [u8], n: usize) {
-    fn integerify(x: &[u8], n: usize) -> usize {
-        // n is a power of 2, so n - 1 gives us a bitmask that we can use to perform a calculation
-        // mod n using a simple bitwise and.
-        let mask = n - 1;
-        // This cast is safe since we're going to get the value mod n (which is a power of 2), so we
-        // don't have to care about truncating any of the high bits off
-        let result = (LittleEndian::read_u32(&x[x.len() - 64..x.len() - 60]) as usize) & mask;
-        result
-    }
+pub(crate) fn scrypt_ro_mix(b: &mut [Block], v: &mut [Block], t: &mut [Block], n: usize) {
+    ro_mix::<Salsa8>(b, v, t, n)
+}
+
+/// `scrypt_ro_mix` with the core `C`.
+pub(crate) fn ro_mix<C: MixingCore>(b: &mut [Block], v: &mut [Block], t: &mut [Block], n: usize) {
+    let stream = v.len() * 64 >= STREAM_MIN;
+    ro_mix_stores::<C>(b, v, t, n, stream)
+}
+
+/// `scrypt_ro_mix`, with `V` filled by non-temporal stores if `stream`.
+///
+/// `V` is only read again in the second loop, after the first one wrote
+/// all of it, so for a large `V` the cache lines of the stores just evict
+/// those of `X`. Non-temporal stores bypass the cache, where the target
+/// has them.
+pub(crate) fn scrypt_ro_mix_stores(
+    b: &mut [Block], v: &mut [Block], t: &mut [Block], n: usize, stream: bool,
+) {
+    ro_mix_stores::<Salsa8>(b, v, t, n, stream)
+}
+
+/// `scrypt_ro_mix_stores` with the core `C`.
+fn ro_mix_stores<C: MixingCore>(
+    b: &mut [Block], v: &mut [Block], t: &mut [Block], n: usize, stream: bool,
+) {
+    ro_mix_observed::<C, _>(b, v, t, n, stream, |_, _| ())
+}
 
+/// `ro_mix_stores`, calling `observe` with the iteration and the index `j`
+/// of every iteration of the second loop. The closure of `ro_mix_stores`
+/// does nothing, which compiles to the loop without it.
+fn ro_mix_observed<C: MixingCore, O: FnMut(usize, usize)>(
+    b: &mut [Block], v: &mut [Block], t: &mut [Block], n: usize, stream: bool, mut observe: O,
+) {
     let len = b.len();
 
-    for chunk in v.chunks_mut(len) {
-        copy_memory(b, chunk);
-        scrypt_block_mix(chunk, b);
+    let streamed = stream && stream_fill::<C>(b, v, len);
+    if !streamed {
+        for chunk in v.chunks_mut(len) {
+            chunk.copy_from_slice(b);
+            block_mix::<C>(chunk, b);
+        }
     }
 
-    for _ in 0..n {
+    for i in 0..n {
         let j = integerify(b, n);
-        xor(b, &v[j * len..(j + 1) * len], t);
-        scrypt_block_mix(t, b);
+        observe(i, j);
+        let offset = v_offset(j, len);
+        xor(b, &v[offset..offset + len], t);
+        block_mix::<C>(t, b);
     }
 }
+
+/// The first loop of ROMix with non-temporal stores into `v`, in blocks of
+/// `len` blocks, or `false` if it cannot be done.
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+fn stream_fill<C: MixingCore>(b: &mut [Block], v: &mut [Block], len: usize) -> bool {
+    use core::arch::x86_64::{__m128i, _mm_load_si128, _mm_sfence, _mm_stream_si128};
+    use zero;
+
+    let mut x = vec![Block::default(); len];
+    for chunk in v.chunks_mut(len) {
+        // SSE2 is part of x86_64, the pointers are in bounds and blocks are
+        // aligned to 64 bytes
+        for (src, dst) in b.iter().zip(chunk.iter_mut()) {
+            let src = src as *const Block as *const __m128i;
+            let dst = dst as *mut Block as *mut __m128i;
+            for k in 0..4 {
+                unsafe { _mm_stream_si128(dst.add(k), _mm_load_si128(src.add(k))) };
+            }
+        }
+        // BlockMix reads `X` from a copy, the streamed block is not cached
+        x.copy_from_slice(b);
+        block_mix::<C>(&x, b);
+    }
+    // order the non-temporal stores before the loads of the second loop
+    unsafe { _mm_sfence() };
+    zero(Block::as_bytes_mut(&mut x));
+    true
+}
+
+/// Other targets and Miri fill `v` with plain stores.
+#[cfg(any(not(target_arch = "x86_64"), miri))]
+fn stream_fill<C: MixingCore>(_b: &mut [Block], _v: &mut [Block], _len: usize) -> bool {
+    false
+}
+
+/// `scrypt_ro_mix_stores`, calling `observe` with the iteration and the
+/// index `j` of every iteration of the second loop, see `research`.
+#[cfg(feature="research")]
+pub(crate) fn scrypt_ro_mix_observed(
+    b: &mut [Block], v: &mut [Block], t: &mut [Block], n: usize, stream: bool,
+    observe: &mut dyn FnMut(usize, usize),
+) {
+    ro_mix_observed::<Salsa8, _>(b, v, t, n, stream, observe)
+}
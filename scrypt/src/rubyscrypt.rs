@@ -0,0 +1,132 @@
+//! The format of `SCrypt::Password` of the Ruby gem `scrypt`:
+//!
+//! `<hex(N)>$<hex(r)>$<hex(p)>$<hex(salt)>$<hex(hash)>`
+//!
+//! All three parameters are lowercase hex, `400$8$1$` is `N = 1024`. The
+//! gem generates 32 byte salts and hashes by default, and derives the
+//! length of the hash from the string when verifying.
+//!
+//! The salt is the hex of 8 to 32 bytes. Before it is passed to scrypt the
+//! gem removes leading `00` pairs, and an odd number of digits, which it
+//! writes for 20 byte salts, is padded with a `0`. Salts of 40 digits are
+//! the SHA-1 hashes of older versions of the gem, they are not supported.
+//!
+//! Like `php-scrypt` and simple-scrypt strings, the strings have no prefix.
+//! They are told apart by `N`: a string is taken for the gem if its first
+//! field read as hex is a power of two and read as decimal is not, e.g.
+//! `400` or `4000`. For `N` of 2, 4 and 8, which read the same in both,
+//! the decimal formats are chosen.
+use core::ops::RangeInclusive;
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use errors::CheckError;
+use params::Params;
+use {hex, werkzeug};
+
+/// Lengths of the salts of the gem in hex digits.
+const SALT_DIGITS: RangeInclusive<usize> = 16..=64;
+
+/// Length in hex digits of the salts of the older SHA-1 hashes.
+const SHA1_SALT_DIGITS: usize = 40;
+
+/// Lengths of the hashes of the gem in hex digits.
+const HASH_DIGITS: RangeInclusive<usize> = 32..=1024;
+
+/// Length of the salt and the hash generated by the gem.
+#[cfg(feature="simple")]
+pub(crate) const SALT_LEN: usize = 32;
+
+/// Check if `hashed_value` is a string of the gem, see above.
+pub(crate) fn matches(hashed_value: &str) -> bool {
+    let mut fields = hashed_value.splitn(4, '$');
+    let cost = fields.next().unwrap_or("");
+    let is_hex = |f: &str| !f.is_empty() && f.bytes().all(is_lower_hex);
+    is_hex(cost) && fields.by_ref().take(2).filter(|f| is_hex(f)).count() == 2
+        && fields.next().is_some()
+        && u64::from_str_radix(cost, 16).is_ok_and(|n| n.is_power_of_two() && n >= 2)
+        && !cost.parse::<u64>().is_ok_and(u64::is_power_of_two)
+}
+
+fn is_lower_hex(b: u8) -> bool {
+    b.is_ascii_digit() || (b'a'..=b'f').contains(&b)
+}
+
+/// Encode a string of the gem.
+pub(crate) fn encode(params: &Params, salt: &[u8], hash: &[u8]) -> String {
+    format!(
+        "{:x}${:x}${:x}${}${}",
+        1u64 << params.log_n, params.r, params.p, hex::encode(salt), hex::encode(hash),
+    )
+}
+
+/// Check if strings with `params` are detected as strings of the gem, `N` of
+/// 2, 4 and 8 are taken for the decimal formats.
+pub(crate) fn supports(params: &Params) -> bool {
+    params.log_n > 3
+}
+
+/// Check if the gem reads the hex of `salt` back as `salt`.
+pub(crate) fn supports_salt(salt: &[u8]) -> bool {
+    let digits = 2 * salt.len();
+    SALT_DIGITS.contains(&digits) && digits != SHA1_SALT_DIGITS && salt[0] != 0
+}
+
+/// Check if the gem can verify a hash of `len` bytes.
+pub(crate) fn supports_hash(len: usize) -> bool {
+    HASH_DIGITS.contains(&(2 * len))
+}
+
+/// Decode a string of the gem into parameters, salt and hash.
+///
+/// The salt is the one passed to scrypt, without the leading zero bytes
+/// of the string.
+pub(crate) fn decode(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    let fields: Vec<&str> = hashed_value.split('$').collect();
+    if fields.len() != 5 { Err(CheckError::InvalidFormat)?; }
+
+    let n = u64::from_str_radix(canonical(fields[0])?, 16)
+        .map_err(|_| CheckError::InvalidFormat)?;
+    if !n.is_power_of_two() || n < 2 { Err(CheckError::InvalidFormat)?; }
+    let r = u32::from_str_radix(canonical(fields[1])?, 16);
+    let p = u32::from_str_radix(canonical(fields[2])?, 16);
+    let params = match (r, p) {
+        (Ok(r), Ok(p)) => Params::new(n.trailing_zeros() as u8, r, p).ok(),
+        _ => None,
+    }.ok_or(CheckError::InvalidFormat)?;
+
+    let salt = decode_salt(fields[3])?;
+    // the gem compares the hex strings themselves
+    let hash = werkzeug::decode_hex(fields[4])?;
+    if !supports_hash(hash.len()) { Err(CheckError::InvalidFormat)?; }
+
+    Ok((params, salt, hash))
+}
+
+/// Check that `field` is a lowercase hex number without leading zeros.
+fn canonical(field: &str) -> Result<&str, CheckError> {
+    let canonical = !field.is_empty() && field.bytes().all(is_lower_hex)
+        && (field == "0" || !field.starts_with('0'));
+    if !canonical { Err(CheckError::InvalidFormat)?; }
+    Ok(field)
+}
+
+/// Decode a salt the way the gem passes it to scrypt.
+fn decode_salt(field: &str) -> Result<Vec<u8>, CheckError> {
+    let digits = field.len();
+    if !SALT_DIGITS.contains(&digits) || digits == SHA1_SALT_DIGITS
+        || !field.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        Err(CheckError::InvalidFormat)?;
+    }
+    let mut salt = field;
+    while salt.starts_with("00") { salt = &salt[2..]; }
+    let padded;
+    if salt.len() % 2 != 0 {
+        padded = format!("{}0", salt);
+        salt = &padded;
+    }
+    hex::decode(salt).ok_or(CheckError::InvalidFormat)
+}
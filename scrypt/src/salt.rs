@@ -0,0 +1,27 @@
+//! Salts derived from an identifier, for systems which can not store one.
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// The salt of `identifier`, e.g. a user or device name, under a secret
+/// `global_key`: the first 16 bytes of `HMAC-SHA-256(global_key, identifier)`.
+///
+/// For systems which can not store a random salt per credential, e.g. the
+/// stateless firmware of a device. Derived salts are strictly worse than
+/// random ones: the same identifier always gets the same salt, so a new
+/// password of a user is hashed under the salt of the old one, and who
+/// learns the key can precompute the hashes of an identifier before a
+/// breach. They are strictly better than a salt shared by all credentials,
+/// which lets one guess be checked against every hash at once. Prefer
+/// `scrypt_simple()` wherever a salt can be stored.
+///
+/// `SimpleConfig::derive_hash()` and `SimpleConfig::verify_derived()` hash
+/// and verify a password under the derived salt, so only the hash needs to
+/// be stored.
+pub fn derive_salt(global_key: &[u8], identifier: &[u8]) -> [u8; 16] {
+    let mut mac = Hmac::<Sha256>::new_varkey(global_key)
+        .expect("HMAC accepts keys of any length");
+    mac.input(identifier);
+    let mut salt = [0u8; 16];
+    salt.copy_from_slice(&mac.result().code()[..16]);
+    salt
+}
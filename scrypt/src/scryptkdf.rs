@@ -0,0 +1,80 @@
+//! The format of Perl's `Crypt::ScryptKDF::scrypt_hash`:
+//!
+//! `SCRYPT:<N>:<r>:<p>:<base64(salt)>:<base64(hash)>`
+//!
+//! `N` is stored itself, salt and hash in the standard base64 alphabet
+//! with padding. The module generates 32 byte salts and hashes with
+//! `N = 16384`, `r = 8` and `p = 1` by default.
+//!
+//! Malformed strings are reported with the field at fault, as
+//! `CheckError::InvalidField`, to help migrating them.
+use core::str::FromStr;
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use base64ct::{Base64, Encoding};
+
+use errors::{CheckError, Field, ParamsReason};
+use params::Params;
+use {ct_base64, phc};
+
+/// Prefix identifying `Crypt::ScryptKDF` strings.
+pub(crate) const PREFIX: &str = "SCRYPT:";
+
+/// Length of the salt and the hash generated by `Crypt::ScryptKDF`.
+#[cfg(feature="simple")]
+pub(crate) const SALT_LEN: usize = 32;
+
+/// Encode a `Crypt::ScryptKDF` string.
+pub(crate) fn encode(params: &Params, salt: &[u8], hash: &[u8]) -> String {
+    format!(
+        "SCRYPT:{}:{}:{}:{}:{}",
+        1u64 << params.log_n, params.r, params.p,
+        Base64::encode_string(salt),
+        Base64::encode_string(hash),
+    )
+}
+
+/// Decode a `Crypt::ScryptKDF` string into parameters, salt and hash.
+///
+/// # Return
+/// `Err(CheckError::InvalidField)` with the first field which is missing
+/// or malformed, `Err(CheckError::InvalidFormat)` for extra fields or
+/// parameters which are only invalid in combination.
+pub(crate) fn decode(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    if !hashed_value.starts_with(PREFIX) { Err(CheckError::InvalidFormat)?; }
+    let mut fields = hashed_value[PREFIX.len()..].split(':');
+    let mut next = |which| fields.next().ok_or(CheckError::InvalidField(which));
+
+    let n: u64 = decimal(next(Field::N)?, Field::N)?;
+    if !n.is_power_of_two() || n < 2 { Err(CheckError::InvalidField(Field::N))?; }
+    let r = decimal(next(Field::R)?, Field::R)?;
+    let p = decimal(next(Field::P)?, Field::P)?;
+    let params = Params::new(n.trailing_zeros() as u8, r, p).map_err(|e| {
+        match e.reason() {
+            ParamsReason::LogNTooLarge => CheckError::InvalidField(Field::N),
+            ParamsReason::RZero => CheckError::InvalidField(Field::R),
+            ParamsReason::PZero => CheckError::InvalidField(Field::P),
+            _ => CheckError::InvalidFormat,
+        }
+    })?;
+
+    let salt = decode_b64(next(Field::Salt)?, Field::Salt)?;
+    let hash = decode_b64(next(Field::Hash)?, Field::Hash)?;
+    if fields.next().is_some() { Err(CheckError::InvalidFormat)?; }
+    Ok((params, salt, hash))
+}
+
+fn decimal<T: FromStr>(field: &str, which: Field) -> Result<T, CheckError> {
+    phc::decode_decimal(field).map_err(|_| CheckError::InvalidField(which))
+}
+
+/// Decode a non-empty, padded base64 field.
+fn decode_b64(field: &str, which: Field) -> Result<Vec<u8>, CheckError> {
+    if field.is_empty() || field.len() % 4 != 0 {
+        Err(CheckError::InvalidField(which))?;
+    }
+    ct_base64::decode(field, false).map_err(|_| CheckError::InvalidField(which))
+}
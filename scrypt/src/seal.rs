@@ -0,0 +1,149 @@
+//! Hash strings sealed with a MAC under a server key.
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use base64ct::{Base64Unpadded, Encoding};
+use byteorder::{ByteOrder, LittleEndian};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use {constant_time_eq, ct_base64};
+use errors::CheckError;
+use pepper::PepperSet;
+use scrypt_check;
+
+/// Domain separation of the MACs of `RecordSealer`.
+const SEAL_TAG: &[u8] = b"scrypt sealed record v1";
+
+/// Separator of the MAC field, which follows the hash string.
+const MAC_FIELD: &str = "$mac=";
+
+/// Seals hash strings with an HMAC-SHA256 under a server key, so that a
+/// record replaced by someone with write access to the credentials table,
+/// but without the key, is detected before the password is hashed.
+///
+/// The MAC covers the hash string and an `identifier` of the record, e.g.
+/// the user id, so that the sealed record of an account whose password is
+/// known does not verify in the row of another account either.
+///
+/// # Format
+/// `<hash string>$mac=<key id>,<base64(mac)>`, where the MAC is
+/// `HMAC-SHA256(key, "scrypt sealed record v1" || key id ||
+/// len(identifier) || identifier || hash string)` with the length as 8
+/// bytes, little-endian, and the standard base64 alphabet without padding.
+///
+/// Every key of the `PepperSet` verifies records, the newest one seals new
+/// records, see `reseal()` for key rotation.
+#[derive(Clone)]
+pub struct RecordSealer {
+    keys: PepperSet,
+    allow_unsealed: bool,
+}
+
+impl RecordSealer {
+    /// A sealer with the keys of `keys`, which rejects records without a
+    /// MAC. `None` if `keys` is empty.
+    pub fn new(keys: PepperSet) -> Option<RecordSealer> {
+        keys.newest()?;
+        Some(RecordSealer { keys, allow_unsealed: false })
+    }
+
+    /// Accept records without a MAC, e.g. while existing records are
+    /// sealed on login. Off by default. Anyone who can write a record can
+    /// also write an unsealed one, so unsealed records should only be
+    /// allowed until all records are sealed.
+    pub fn allow_unsealed(&mut self, allow: bool) -> &mut RecordSealer {
+        self.allow_unsealed = allow;
+        self
+    }
+
+    /// Append the MAC of `hashed_value` and `identifier` under the newest
+    /// key.
+    pub fn seal(&self, hashed_value: &str, identifier: &[u8]) -> String {
+        let newest = self.keys.newest().expect("`new` checked that there is a key");
+        let mac = record_mac(newest.key, newest.id, identifier, hashed_value);
+        format!("{}{}{},{}", hashed_value, MAC_FIELD, newest.id,
+            Base64Unpadded::encode_string(&mac))
+    }
+
+    /// Check the MAC of `sealed` and return the hash string it seals, or
+    /// `sealed` itself if it has no MAC and unsealed records are allowed.
+    ///
+    /// # Return
+    /// `Err(CheckError::TamperSuspected)` if the MAC does not verify, its
+    /// key is unknown or it is missing and unsealed records are not
+    /// allowed, `Err(CheckError::InvalidFormat)` if the MAC field is
+    /// malformed.
+    pub fn unseal<'a>(&self, sealed: &'a str, identifier: &[u8])
+        -> Result<&'a str, CheckError>
+    {
+        let at = match sealed.rfind(MAC_FIELD) {
+            Some(at) => at,
+            None if self.allow_unsealed => return Ok(sealed),
+            None => Err(CheckError::TamperSuspected)?,
+        };
+        let (hashed_value, field) = (&sealed[..at], &sealed[at + MAC_FIELD.len()..]);
+        let mut parts = field.splitn(2, ',');
+        let id = parts.next().and_then(|id| id.parse::<u8>().ok());
+        let mac = parts.next()
+            .and_then(|mac| ct_base64::decode(mac, false).ok());
+        let (id, mac) = match (id, mac) {
+            (Some(id), Some(mac)) if mac.len() == 32 => (id, mac),
+            _ => Err(CheckError::InvalidFormat)?,
+        };
+        let key = self.keys.get(id).ok_or(CheckError::TamperSuspected)?;
+        if !constant_time_eq(&record_mac(key, id, identifier, hashed_value), &mac) {
+            Err(CheckError::TamperSuspected)?;
+        }
+        Ok(hashed_value)
+    }
+
+    /// Compare `password` against the hash string sealed in `sealed`, like
+    /// `scrypt_check` once the MAC verified.
+    ///
+    /// # Return
+    /// The errors of `unseal()` before the password is hashed,
+    /// `Err(CheckError::HashMismatch)` for a wrong password and the errors
+    /// of `scrypt_check` otherwise.
+    pub fn verify(&self, password: &str, sealed: &str, identifier: &[u8])
+        -> Result<(), CheckError>
+    {
+        scrypt_check(password, self.unseal(sealed, identifier)?)
+    }
+
+    /// If `sealed` is not sealed by the newest key, e.g. after a rotation,
+    /// and should be resealed.
+    pub fn needs_reseal(&self, sealed: &str) -> bool {
+        let newest = self.keys.newest().expect("`new` checked that there is a key");
+        match sealed.rfind(MAC_FIELD) {
+            Some(at) => !sealed[at + MAC_FIELD.len()..]
+                .starts_with(&format!("{},", newest.id)),
+            None => true,
+        }
+    }
+
+    /// Check the MAC of `sealed` and seal its hash string under the newest
+    /// key, e.g. after a rotation or to seal an unsealed record.
+    ///
+    /// # Return
+    /// The errors of `unseal()`.
+    pub fn reseal(&self, sealed: &str, identifier: &[u8]) -> Result<String, CheckError> {
+        Ok(self.seal(self.unseal(sealed, identifier)?, identifier))
+    }
+}
+
+/// The MAC of a record of `RecordSealer`.
+fn record_mac(key: &[u8], id: u8, identifier: &[u8], hashed_value: &str) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_varkey(key)
+        .expect("HMAC accepts keys of any length");
+    let mut len = [0u8; 8];
+    LittleEndian::write_u64(&mut len, identifier.len() as u64);
+    mac.input(SEAL_TAG);
+    mac.input(&[id]);
+    mac.input(&len);
+    mac.input(identifier);
+    mac.input(hashed_value.as_bytes());
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&mac.result().code());
+    result
+}
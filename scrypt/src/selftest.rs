@@ -0,0 +1,130 @@
+//! The power-on self test of the crate.
+use core::fmt;
+#[cfg(feature="test-util")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use errors::ErrorCode;
+use test_vectors::{ro_mix_stores, Operation, TestVector, BLOCK_MIX, RO_MIX, SALSA20_8, SCRYPT};
+
+/// An implementation of ROMix compiled into the crate.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Backend {
+    /// Plain loads and stores, on every target.
+    Portable,
+    /// `V` filled with the non-temporal stores of SSE2 on x86_64, used by
+    /// `scrypt` from a `V` of 64 MiB.
+    Streaming,
+}
+
+impl Backend {
+    /// The backends of the target.
+    pub fn compiled() -> &'static [Backend] {
+        if cfg!(target_arch = "x86_64") {
+            &[Backend::Portable, Backend::Streaming]
+        } else {
+            &[Backend::Portable]
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Backend::Portable => "portable",
+            Backend::Streaming => "streaming",
+        })
+    }
+}
+
+/// `self_test()` error, a vector whose result differs from the expected one.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SelfTestError {
+    vector: &'static str,
+    backend: Backend,
+}
+
+impl SelfTestError {
+    /// The name of the failing vector, e.g. `"RFC 7914 ROMix"`.
+    pub fn vector(&self) -> &'static str { self.vector }
+
+    /// The backend the vector failed on.
+    pub fn backend(&self) -> Backend { self.backend }
+
+    /// Stable numeric code of the error.
+    pub fn code(&self) -> ErrorCode { ErrorCode::SelfTestFailed }
+}
+
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "scrypt self test failed: `{}` does not match on the {} backend",
+            self.vector, self.backend)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for SelfTestError {
+    fn description(&self) -> &str { "scrypt self test failed" }
+}
+
+/// The backend whose results `self_test` corrupts, `0` for none, else the
+/// position in `Backend::compiled()` plus one.
+#[cfg(feature="test-util")]
+static CORRUPT: AtomicUsize = AtomicUsize::new(0);
+
+/// **FOR TESTS ONLY.** Make `self_test()` see wrong results of `backend`,
+/// for the tests of the failure path of applications, `None` restores the
+/// right ones. Other functions are not affected.
+#[cfg(feature="test-util")]
+pub fn corrupt_self_test(backend: Option<Backend>) {
+    let index = backend.and_then(|b| Backend::compiled().iter().position(|&c| c == b));
+    CORRUPT.store(index.map_or(0, |i| i + 1), Ordering::SeqCst);
+}
+
+/// If `corrupt_self_test` selected `backend`.
+#[cfg(feature="test-util")]
+fn corrupted(backend: Backend) -> bool {
+    let corrupt = CORRUPT.load(Ordering::SeqCst);
+    corrupt != 0 && Backend::compiled()[corrupt - 1] == backend
+}
+
+#[cfg(not(feature="test-util"))]
+fn corrupted(_backend: Backend) -> bool { false }
+
+fn check(vector: &TestVector, backend: Backend, mut actual: Vec<u8>)
+    -> Result<(), SelfTestError>
+{
+    if corrupted(backend) { actual[0] ^= 1; }
+    if actual != vector.expected {
+        Err(SelfTestError { vector: vector.name, backend })?;
+    }
+    Ok(())
+}
+
+/// `self_test` checks the crate against the known answers of RFC 7914, e.g.
+/// when a service starts, to refuse to run with a miscompiled scrypt. It
+/// runs the vectors of Salsa20/8, BlockMix, ROMix and the first two of
+/// scrypt, up to `N = 1024` with `r = 8` and `p = 16`, and the ROMix
+/// vector on every backend of `Backend::compiled()`.
+///
+/// A release build takes a few milliseconds and 1 MiB.
+///
+/// # Return
+/// `Err(SelfTestError)` naming the first vector which does not match and
+/// its backend.
+pub fn self_test() -> Result<(), SelfTestError> {
+    for &vector in [&SALSA20_8, &BLOCK_MIX, &RO_MIX, &SCRYPT[0], &SCRYPT[1]].iter() {
+        // the buffers of these vectors are far below those of `Streaming`
+        check(vector, Backend::Portable, vector.compute())?;
+    }
+    if let Operation::RoMix { input, log_n } = RO_MIX.operation {
+        for &backend in Backend::compiled() {
+            let actual = ro_mix_stores(input, log_n, backend == Backend::Streaming);
+            check(&RO_MIX, backend, actual)?;
+        }
+    }
+    Ok(())
+}
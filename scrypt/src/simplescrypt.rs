@@ -0,0 +1,72 @@
+//! The format of Go's `github.com/elithrar/simple-scrypt`:
+//!
+//! `<N>$<r>$<p>$<hex(salt)>$<hex(hash)>`
+//!
+//! `N` is stored itself. The package generates 16 byte salts and 32 byte
+//! hashes by default, and rejects salts below 8 bytes and hashes below 16
+//! bytes, so do we. It writes lowercase hex and reads hex of either case.
+//!
+//! The strings start like those of `php-scrypt`, with three decimal fields
+//! and no prefix. They are told apart by the salt: a hex salt of at least 8
+//! bytes, 16 digits, is simple-scrypt, any other salt text is php-scrypt,
+//! whose generated salts are 12 characters long. A php-scrypt salt given
+//! by its caller whose base64 is 16 or more hex digits is only parsed with
+//! `Format::PhpScrypt` itself.
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use errors::CheckError;
+use params::Params;
+use phc::decode_decimal;
+use {hex, phpscrypt};
+
+/// Shortest salt accepted by simple-scrypt.
+pub(crate) const MIN_SALT_LEN: usize = 8;
+
+/// Shortest hash accepted by simple-scrypt.
+pub(crate) const MIN_HASH_LEN: usize = 16;
+
+/// Length of the salt generated with simple-scrypt's `DefaultParams`.
+#[cfg(feature="simple")]
+pub(crate) const SALT_LEN: usize = 16;
+
+/// Check if `salt` is the hex salt of a simple-scrypt string.
+pub(crate) fn is_salt(salt: &[u8]) -> bool {
+    salt.len() >= 2 * MIN_SALT_LEN && salt.len() % 2 == 0
+        && salt.iter().all(u8::is_ascii_hexdigit)
+}
+
+/// Check if `hashed_value` is a simple-scrypt string.
+pub(crate) fn matches(hashed_value: &str) -> bool {
+    phpscrypt::salt_field(hashed_value).is_some_and(|salt| is_salt(salt.as_bytes()))
+}
+
+/// Encode a simple-scrypt string.
+pub(crate) fn encode(params: &Params, salt: &[u8], hash: &[u8]) -> String {
+    format!(
+        "{}${}${}${}${}",
+        1u64 << params.log_n, params.r, params.p, hex::encode(salt), hex::encode(hash),
+    )
+}
+
+/// Decode a simple-scrypt string into parameters, salt and hash.
+pub(crate) fn decode(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    let fields: Vec<&str> = hashed_value.split('$').collect();
+    if fields.len() != 5 || !is_salt(fields[3].as_bytes()) { Err(CheckError::InvalidFormat)?; }
+
+    let n: u64 = decode_decimal(fields[0])?;
+    if !n.is_power_of_two() || n < 2 { Err(CheckError::InvalidFormat)?; }
+    let params = Params::new(
+        n.trailing_zeros() as u8,
+        decode_decimal(fields[1])?,
+        decode_decimal(fields[2])?,
+    ).map_err(|_| CheckError::InvalidFormat)?;
+
+    let salt = hex::decode(fields[3]).ok_or(CheckError::InvalidFormat)?;
+    let hash = hex::decode(fields[4]).ok_or(CheckError::InvalidFormat)?;
+    if hash.len() < MIN_HASH_LEN { Err(CheckError::InvalidFormat)?; }
+
+    Ok((params, salt, hash))
+}
@@ -0,0 +1,197 @@
+//! Compatibility with libsodium's `crypto_pwhash_scryptsalsa208sha256_str`.
+//!
+//! libsodium produces `$7$` strings which can also be verified by
+//! `scrypt_check`, but its dialect of the format differs from libxcrypt's in
+//! a few places:
+//!
+//! - parameters are derived from `opslimit` and `memlimit` (see
+//!   `pickparams`), `r` is always `8`
+//! - the salt is 32 random bytes encoded to 43 characters, libxcrypt uses
+//!   16 bytes (22 characters) by default
+//! - strings must be exactly 101 characters long
+//! - the salt extends to the *last* `$` and may contain any character,
+//!   libxcrypt stops at the first `$` and only accepts `./0-9A-Za-z`
+//!
+//! libsodium never produces salts outside of the alphabet, so the last two
+//! differences only matter for strings crafted by other means. `verify`
+//! follows libsodium exactly.
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+#[cfg(feature="simple")]
+use rand_core::{CryptoRng, RngCore};
+
+use {constant_time_eq, crypt7};
+use errors::{CheckError, InvalidParams};
+#[cfg(feature="simple")]
+use errors::HashError;
+#[cfg(feature="simple")]
+use os_rng;
+use params::Params;
+use scrypt;
+
+/// Length of a libsodium `$7$` string, excluding the terminating NUL.
+pub const STR_LEN: usize = 101;
+/// Length of the raw salt.
+pub const SALT_LEN: usize = 32;
+
+/// libsodium's `crypto_pwhash_scryptsalsa208sha256_OPSLIMIT_INTERACTIVE`.
+pub const OPSLIMIT_INTERACTIVE: u64 = 524_288;
+/// libsodium's `crypto_pwhash_scryptsalsa208sha256_MEMLIMIT_INTERACTIVE`.
+pub const MEMLIMIT_INTERACTIVE: usize = 16_777_216;
+/// libsodium's `crypto_pwhash_scryptsalsa208sha256_OPSLIMIT_SENSITIVE`.
+pub const OPSLIMIT_SENSITIVE: u64 = 33_554_432;
+/// libsodium's `crypto_pwhash_scryptsalsa208sha256_MEMLIMIT_SENSITIVE`.
+pub const MEMLIMIT_SENSITIVE: usize = 1_073_741_824;
+/// libsodium's `crypto_pwhash_scryptsalsa208sha256_OPSLIMIT_MIN`, smaller
+/// limits are raised to it.
+pub const OPSLIMIT_MIN: u64 = 32_768;
+/// libsodium's `crypto_pwhash_scryptsalsa208sha256_OPSLIMIT_MAX`.
+pub const OPSLIMIT_MAX: u64 = 4_294_967_295;
+/// libsodium's `crypto_pwhash_scryptsalsa208sha256_MEMLIMIT_MIN`.
+pub const MEMLIMIT_MIN: usize = 16_777_216;
+/// libsodium's `crypto_pwhash_scryptsalsa208sha256_MEMLIMIT_MAX`, 64 GiB or
+/// the address space.
+#[cfg(target_pointer_width = "64")]
+pub const MEMLIMIT_MAX: usize = 68_719_476_736;
+/// libsodium's `crypto_pwhash_scryptsalsa208sha256_MEMLIMIT_MAX`, 64 GiB or
+/// the address space.
+#[cfg(not(target_pointer_width = "64"))]
+pub const MEMLIMIT_MAX: usize = usize::MAX;
+
+impl Params {
+    /// The parameters libsodium picks for `opslimit` and `memlimit`, see
+    /// `sodium::pickparams`.
+    pub fn from_sodium_limits(opslimit: u64, memlimit: usize)
+        -> Result<Params, InvalidParams>
+    {
+        pickparams(opslimit, memlimit)
+    }
+
+    /// Limits for which libsodium picks these parameters, the inverse of
+    /// `from_sodium_limits`: `memlimit = 1024 * N` and
+    /// `opslimit = 32 * N * p`.
+    ///
+    /// # Return
+    /// `None` if libsodium picks other parameters for every limit, e.g. for
+    /// `r != 8` or `N * p < 1024`, or if the limits do not fit.
+    pub fn sodium_limits(&self) -> Option<(u64, usize)> {
+        if self.r != 8 { return None; }
+        let memlimit = 1024usize.checked_mul(1usize.checked_shl(self.log_n as u32)?)?;
+        let opslimit = (32u64 << self.log_n).checked_mul(self.p as u64)?;
+        match pickparams(opslimit, memlimit) {
+            Ok(params) if params == *self => Some((opslimit, memlimit)),
+            _ => None,
+        }
+    }
+}
+
+/// Derive scrypt parameters from libsodium's `opslimit` and `memlimit`,
+/// exactly like libsodium does.
+///
+/// # Return
+/// `Err(InvalidParams)` if the derived parameters are not valid on this
+/// platform.
+pub fn pickparams(opslimit: u64, memlimit: usize)
+    -> Result<Params, InvalidParams>
+{
+    let opslimit = opslimit.max(32768);
+    let memlimit = memlimit as u64;
+    let r: u64 = 8;
+
+    let (log_n, p) = if opslimit < memlimit / 32 {
+        (pick_log_n(opslimit / (r * 4)), 1)
+    } else {
+        let log_n = pick_log_n(memlimit / (r * 128));
+        let max_rp = ((opslimit / 4) >> log_n).min(0x3fff_ffff);
+        (log_n, max_rp / r)
+    };
+    Params::new(log_n as u8, r as u32, p as u32)
+}
+
+/// The smallest `log_n` for which `N` exceeds half of `max_n`.
+fn pick_log_n(max_n: u64) -> u32 {
+    let mut log_n = 1;
+    while log_n < 63 && (1u64 << log_n) <= max_n / 2 {
+        log_n += 1;
+    }
+    log_n
+}
+
+/// Hash `password` like `crypto_pwhash_scryptsalsa208sha256_str`, with a
+/// random salt drawn from `OsRng`.
+///
+/// Use `pickparams` to get `params` from libsodium's limits.
+#[cfg(feature="simple")]
+pub fn hash(password: &[u8], params: &Params)
+    -> Result<String, HashError>
+{
+    let mut rng = os_rng();
+    hash_with_rng(password, params, &mut rng)
+}
+
+/// Same as `hash`, but draws the salt from the provided `rng`.
+#[cfg(feature="simple")]
+pub fn hash_with_rng<R: RngCore + CryptoRng>(
+    password: &[u8], params: &Params, rng: &mut R,
+) -> Result<String, HashError> {
+    let mut salt = [0u8; SALT_LEN];
+    rng.try_fill_bytes(&mut salt)?;
+    Ok(hash_with_salt(password, params, &salt))
+}
+
+/// Hash `password` with a fixed `salt`.
+///
+/// Given the same parameters and salt the result is byte-identical to the
+/// string produced by libsodium.
+pub fn hash_with_salt(password: &[u8], params: &Params,
+    salt: &[u8; SALT_LEN]) -> String
+{
+    let setting = crypt7::setting(params, salt);
+    let hash = compute(password, setting.as_bytes(), params);
+
+    let mut result = setting;
+    result.push('$');
+    crypt7::encode(&mut result, &hash);
+    result
+}
+
+/// Verify `password` against a string produced by libsodium, like
+/// `crypto_pwhash_scryptsalsa208sha256_str_verify`.
+///
+/// # Return
+/// `Ok(())` if the password matches, `Err(CheckError::HashMismatch)` if it
+/// does not and `Err(CheckError::InvalidFormat)` if `hashed_value` is not a
+/// valid libsodium string.
+pub fn verify(password: &[u8], hashed_value: &str) -> Result<(), CheckError> {
+    let bytes = hashed_value.as_bytes();
+    if bytes.len() != STR_LEN || !hashed_value.starts_with(crypt7::PREFIX) {
+        Err(CheckError::InvalidFormat)?;
+    }
+    let params_start = crypt7::PREFIX.len();
+    let params_end = params_start + 11;
+    let params = crypt7::decode_params(&bytes[params_start..params_end])?;
+    let salt_end = match hashed_value.rfind('$') {
+        Some(i) if i >= params_end => i,
+        _ => Err(CheckError::InvalidFormat)?,
+    };
+    let expected = crypt7::decode_bytes(&hashed_value[salt_end + 1..])?;
+    if expected.len() != 32 { Err(CheckError::InvalidFormat)?; }
+
+    let hash = compute(password, &bytes[..salt_end], &params);
+    if constant_time_eq(&hash, &expected) {
+        Ok(())
+    } else {
+        Err(CheckError::HashMismatch)
+    }
+}
+
+/// Compute the hash for a `setting`, the salt is everything after the
+/// parameters.
+fn compute(password: &[u8], setting: &[u8], params: &Params) -> [u8; 32] {
+    let salt = &setting[crypt7::PREFIX.len() + 11..];
+    let mut hash = [0u8; 32];
+    scrypt(password, salt, params, &mut hash)
+        .expect("32 bytes always satisfy output length requirements");
+    hash
+}
@@ -0,0 +1,194 @@
+//! scrypt in bounded steps, for a main loop which has to return to a
+//! watchdog or a display between them.
+use core::fmt;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use sha2::Sha256;
+
+use errors::InvalidOutputLen;
+use romix::{integerify, scrypt_block_mix, v_offset, xor, Block};
+use {block_len, check_output_len, zero, Params};
+
+/// Where a `ScryptStepper` is, in the ROMix of one of the `p` blocks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Progress {
+    /// The block being mixed, from 0 to `p - 1`.
+    pub block: u32,
+    /// The number of blocks, `p`.
+    pub blocks: u32,
+    /// The iterations done in the ROMix of the block.
+    pub iteration: u64,
+    /// The iterations of the ROMix of each block, `2 * N`.
+    pub iterations: u64,
+}
+
+impl Progress {
+    /// The share of all iterations which is done, from 0 to 1, e.g. for a
+    /// progress bar.
+    pub fn fraction(&self) -> f64 {
+        (f64::from(self.block) + self.iteration as f64 / self.iterations as f64)
+            / f64::from(self.blocks)
+    }
+}
+
+/// The result of `ScryptStepper::step()`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StepResult {
+    /// Iterations are left, at the given position.
+    Pending(Progress),
+    /// All blocks are mixed, the key is ready for `finish()`.
+    Done,
+}
+
+/// scrypt, run by the caller in steps of at most a given number of ROMix
+/// iterations. Each iteration is one BlockMix of `128 * r` bytes, there are
+/// `2 * N * p` of them.
+///
+/// The first PBKDF2 runs in `new()` and the last one in `finish()`, each
+/// takes about as long as a few iterations. The buffers, `128 * r * N`
+/// bytes for `V`, are allocated once in `new()`, also without `std`. A
+/// copy of the password is kept for `finish()`. The buffers and the copy
+/// are overwritten with zeros when the stepper is dropped, finished or not,
+/// and `Debug` does not print them.
+///
+/// ```
+/// use scrypt::{scrypt, Params, ScryptStepper, StepResult};
+///
+/// let params = Params::new(10, 8, 1).unwrap();
+/// let mut stepper = ScryptStepper::new(b"password", b"salt", &params);
+/// while let StepResult::Pending(_progress) = stepper.step(64) {
+///     // feed the watchdog, redraw the display
+/// }
+/// let mut key = [0u8; 32];
+/// stepper.finish(&mut key).unwrap();
+///
+/// let mut expected = [0u8; 32];
+/// scrypt(b"password", b"salt", &params, &mut expected).unwrap();
+/// assert_eq!(key, expected);
+/// ```
+pub struct ScryptStepper {
+    password: Vec<u8>,
+    params: Params,
+    b: Vec<Block>,
+    v: Vec<Block>,
+    t: Vec<Block>,
+    block: u32,
+    iteration: u64,
+}
+
+impl ScryptStepper {
+    /// Compute the first PBKDF2 of scrypt and allocate the buffers of the
+    /// ROMix.
+    ///
+    /// # Arguments
+    /// - `password` - The password to process as a byte vector
+    /// - `salt` - The salt value to use as a byte vector
+    /// - `params` - The Params to use
+    pub fn new(password: &[u8], salt: &[u8], params: &Params) -> ScryptStepper {
+        let r2 = (params.r as usize) * 2;
+        let mut bytes = vec![0u8; block_len(params)];
+        pbkdf2::<Hmac<Sha256>>(password, salt, 1, &mut bytes);
+        let mut b = vec![Block::default(); bytes.len() / 64];
+        Block::load(&bytes, &mut b);
+        zero(&mut bytes);
+        ScryptStepper {
+            password: password.to_vec(),
+            params: *params,
+            b,
+            v: vec![Block::default(); r2 << params.log_n],
+            t: vec![Block::default(); r2],
+            block: 0,
+            iteration: 0,
+        }
+    }
+
+    /// Run at most `max_iterations` iterations of the ROMix.
+    ///
+    /// # Return
+    /// `StepResult::Done` once all blocks are mixed, which is returned again
+    /// by later calls without any work, else `StepResult::Pending` with the
+    /// position after the iterations.
+    pub fn step(&mut self, max_iterations: usize) -> StepResult {
+        let n = 1usize << self.params.log_n;
+        let r2 = (self.params.r as usize) * 2;
+        for _ in 0..max_iterations {
+            if self.block == self.params.p { break; }
+            let start = self.block as usize * r2;
+            let x = &mut self.b[start..start + r2];
+            let i = self.iteration as usize;
+            if i < n {
+                let v_i = &mut self.v[v_offset(i, r2)..v_offset(i + 1, r2)];
+                v_i.copy_from_slice(x);
+                scrypt_block_mix(v_i, x);
+            } else {
+                let offset = v_offset(integerify(x, n), r2);
+                xor(x, &self.v[offset..offset + r2], &mut self.t);
+                scrypt_block_mix(&self.t, x);
+            }
+            self.iteration += 1;
+            if self.iteration == 2 * n as u64 {
+                self.iteration = 0;
+                self.block += 1;
+            }
+        }
+        match self.progress() {
+            Some(progress) => StepResult::Pending(progress),
+            None => StepResult::Done,
+        }
+    }
+
+    /// The position of the next iteration, `None` once all blocks are
+    /// mixed.
+    pub fn progress(&self) -> Option<Progress> {
+        if self.block == self.params.p { return None; }
+        Some(Progress {
+            block: self.block,
+            blocks: self.params.p,
+            iteration: self.iteration,
+            iterations: 2 << self.params.log_n,
+        })
+    }
+
+    /// Derive the key, the final PBKDF2 of scrypt. `output` receives the
+    /// same bytes as from `scrypt()` for the password, salt and parameters
+    /// of `new()`.
+    ///
+    /// Iterations which are left are run first, without a bound, so call
+    /// it after `step()` returned `StepResult::Done`.
+    ///
+    /// # Return
+    /// `Err(InvalidOutputLen)` under the conditions of `scrypt()`.
+    pub fn finish(&mut self, output: &mut [u8]) -> Result<(), InvalidOutputLen> {
+        check_output_len(output)?;
+        self.step(usize::MAX);
+        let mut b = vec![0u8; self.b.len() * 64];
+        Block::store(&self.b, &mut b);
+        pbkdf2::<Hmac<Sha256>>(&self.password, &b, 1, output);
+        zero(&mut b);
+        Ok(())
+    }
+
+    /// The parameters of `new()`.
+    pub fn params(&self) -> Params { self.params }
+}
+
+impl Drop for ScryptStepper {
+    fn drop(&mut self) {
+        zero(&mut self.password);
+        zero(Block::as_bytes_mut(&mut self.b));
+        zero(Block::as_bytes_mut(&mut self.v));
+        zero(Block::as_bytes_mut(&mut self.t));
+    }
+}
+
+impl fmt::Debug for ScryptStepper {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScryptStepper")
+            .field("params", &self.params)
+            .field("progress", &self.progress())
+            .finish()
+    }
+}
@@ -0,0 +1,368 @@
+//! The test vectors of RFC 7914 \[1\], for the tests of this crate and of
+//! crates wrapping it, e.g. to smoke-test an FFI layer, a wasm build or a
+//! combination of features against the specification.
+//!
+//! ```
+//! use scrypt::test_vectors::{self_test, SCRYPT};
+//!
+//! assert_eq!(self_test(), Ok(()));
+//! for vector in &SCRYPT[..3] {
+//!     assert_eq!(vector.run(), Ok(()), "{}", vector.name);
+//! }
+//! ```
+//!
+//! \[1\] - [RFC 7914](https://tools.ietf.org/html/rfc7914)
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use romix::{self, salsa20_8, scrypt_block_mix, scrypt_ro_mix, Block};
+use {scrypt, Params};
+
+/// The function a `TestVector` checks.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Operation {
+    /// The Salsa20/8 core of a 64 byte block.
+    Salsa20(&'static [u8; 64]),
+    /// BlockMix of `2 * r` blocks, a multiple of 128 bytes.
+    BlockMix(&'static [u8]),
+    /// ROMix with `N = 2^log_n` of `2 * r` blocks, a multiple of 128 bytes.
+    RoMix {
+        /// The blocks.
+        input: &'static [u8],
+        /// log2(N).
+        log_n: u8,
+    },
+    /// The scrypt key derivation, of the output length of the vector.
+    Scrypt {
+        /// The password.
+        password: &'static [u8],
+        /// The salt.
+        salt: &'static [u8],
+        /// log2(N).
+        log_n: u8,
+        /// The block size.
+        r: u32,
+        /// The parallelization.
+        p: u32,
+    },
+}
+
+/// A test vector, the result of an operation on fixed inputs.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TestVector {
+    /// The name of the vector, e.g. `"RFC 7914 scrypt 1"`.
+    pub name: &'static str,
+    /// The checked function and its inputs.
+    pub operation: Operation,
+    /// The expected result.
+    pub expected: &'static [u8],
+}
+
+/// A `TestVector` whose result differs from the expected one.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VectorMismatch {
+    /// The name of the vector.
+    pub name: &'static str,
+    /// The computed result.
+    pub actual: Vec<u8>,
+}
+
+impl TestVector {
+    /// Compute the result of the operation.
+    ///
+    /// # Panics
+    /// If the input of `BlockMix` or `RoMix` is not a non-zero multiple of
+    /// 128 bytes, or if the parameters of `Scrypt` or the length of
+    /// `expected` are invalid for `scrypt()`.
+    pub fn compute(&self) -> Vec<u8> {
+        match self.operation {
+            Operation::Salsa20(input) => {
+                let (mut x, mut y) = ([Block::default()], [Block::default()]);
+                Block::load(input, &mut x);
+                salsa20_8(&x[0], &mut y[0]);
+                let mut output = vec![0u8; 64];
+                Block::store(&y, &mut output);
+                output
+            }
+            Operation::BlockMix(input) => {
+                assert!(!input.is_empty() && input.len() % 128 == 0, "invalid BlockMix input");
+                let x = blocks(input);
+                let mut y = vec![Block::default(); x.len()];
+                scrypt_block_mix(&x, &mut y);
+                bytes(&y)
+            }
+            Operation::RoMix { input, log_n } => {
+                assert!(!input.is_empty() && input.len() % 128 == 0, "invalid ROMix input");
+                assert!(log_n > 0 && log_n < 32, "invalid ROMix log_n");
+                let n = 1 << log_n;
+                let mut b = blocks(input);
+                let mut v = vec![Block::default(); b.len() * n];
+                let mut t = vec![Block::default(); b.len()];
+                scrypt_ro_mix(&mut b, &mut v, &mut t, n);
+                bytes(&b)
+            }
+            Operation::Scrypt { password, salt, log_n, r, p } => {
+                let params = Params::new(log_n, r, p).expect("invalid scrypt parameters");
+                let mut output = vec![0u8; self.expected.len()];
+                scrypt(password, salt, &params, &mut output).expect("invalid output length");
+                output
+            }
+        }
+    }
+
+    /// Check the vector.
+    ///
+    /// # Return
+    /// `Err(VectorMismatch)` with the computed result if it differs from
+    /// `expected`.
+    pub fn run(&self) -> Result<(), VectorMismatch> {
+        let actual = self.compute();
+        if actual != self.expected {
+            Err(VectorMismatch { name: self.name, actual })?;
+        }
+        Ok(())
+    }
+}
+
+/// `self_test` checks every vector of this module but the last scrypt one,
+/// which needs 1 GiB of memory and seconds of time. The others take a few
+/// milliseconds and 16 MiB.
+///
+/// # Return
+/// The first mismatch.
+pub fn self_test() -> Result<(), VectorMismatch> {
+    for vector in [&SALSA20_8, &BLOCK_MIX, &RO_MIX].iter().cloned().chain(&SCRYPT[..3]) {
+        vector.run()?;
+    }
+    Ok(())
+}
+
+/// Integerify of ROMix, the index `j` into `V` for the blocks `x` and `N =
+/// n`, for tests of the index arithmetic with an `N` too large to allocate.
+pub fn integerify(x: &[u8], n: usize) -> usize {
+    romix::integerify(&blocks(&x[x.len() - 64..]), n)
+}
+
+/// The offset of the block `j` in `V`, for blocks of `len` bytes.
+pub fn v_offset(j: usize, len: usize) -> usize {
+    romix::v_offset(j, len)
+}
+
+/// The offset in the output of BlockMix of its `i`-th Salsa20/8 block, for
+/// blocks of `len` bytes.
+pub fn shuffled_offset(i: usize, len: usize) -> usize {
+    romix::shuffled_index(i, len / 64) * 64
+}
+
+/// ROMix of `input` with `N = 2^log_n`, with `V` filled by non-temporal
+/// stores if `stream` and the target has them, whatever the size of `V`.
+pub fn ro_mix_stores(input: &[u8], log_n: u8, stream: bool) -> Vec<u8> {
+    let n = 1 << log_n;
+    let mut b = blocks(input);
+    let mut v = vec![Block::default(); b.len() * n];
+    let mut t = vec![Block::default(); b.len()];
+    romix::scrypt_ro_mix_stores(&mut b, &mut v, &mut t, n, stream);
+    bytes(&b)
+}
+
+/// The blocks of `input`, a multiple of 64 bytes.
+fn blocks(input: &[u8]) -> Vec<Block> {
+    let mut blocks = vec![Block::default(); input.len() / 64];
+    Block::load(input, &mut blocks);
+    blocks
+}
+
+/// The bytes of `blocks`.
+fn bytes(blocks: &[Block]) -> Vec<u8> {
+    let mut bytes = vec![0u8; blocks.len() * 64];
+    Block::store(blocks, &mut bytes);
+    bytes
+}
+
+/// Section 8, the Salsa20/8 core.
+pub const SALSA20_8: TestVector = TestVector {
+    name: "RFC 7914 Salsa20/8",
+    operation: Operation::Salsa20(&SALSA20_8_INPUT),
+    expected: &SALSA20_8_OUTPUT,
+};
+
+/// Section 9, BlockMix with `r = 1`.
+pub const BLOCK_MIX: TestVector = TestVector {
+    name: "RFC 7914 BlockMix",
+    operation: Operation::BlockMix(&BLOCK_MIX_INPUT),
+    expected: &BLOCK_MIX_OUTPUT,
+};
+
+/// Section 10, ROMix with `r = 1`, `N = 16`.
+pub const RO_MIX: TestVector = TestVector {
+    name: "RFC 7914 ROMix",
+    operation: Operation::RoMix { input: &BLOCK_MIX_INPUT, log_n: 4 },
+    expected: &RO_MIX_OUTPUT,
+};
+
+/// Section 12, scrypt with 64 byte outputs. The last vector needs 1 GiB.
+pub const SCRYPT: [TestVector; 4] = [
+    TestVector {
+        name: "RFC 7914 scrypt 1",
+        operation: Operation::Scrypt { password: b"", salt: b"", log_n: 4, r: 1, p: 1 },
+        expected: &SCRYPT_1_OUTPUT,
+    },
+    TestVector {
+        name: "RFC 7914 scrypt 2",
+        operation: Operation::Scrypt {
+            password: b"password", salt: b"NaCl", log_n: 10, r: 8, p: 16,
+        },
+        expected: &SCRYPT_2_OUTPUT,
+    },
+    TestVector {
+        name: "RFC 7914 scrypt 3",
+        operation: Operation::Scrypt {
+            password: b"pleaseletmein", salt: b"SodiumChloride", log_n: 14, r: 8, p: 1,
+        },
+        expected: &SCRYPT_3_OUTPUT,
+    },
+    TestVector {
+        name: "RFC 7914 scrypt 4",
+        operation: Operation::Scrypt {
+            password: b"pleaseletmein", salt: b"SodiumChloride", log_n: 20, r: 8, p: 1,
+        },
+        expected: &SCRYPT_4_OUTPUT,
+    },
+];
+
+impl fmt::Display for VectorMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "test vector `{}` does not match", self.name)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for VectorMismatch {
+    fn description(&self) -> &str { "test vector mismatch" }
+}
+
+const SALSA20_8_INPUT: [u8; 64] = [
+    0x7e, 0x87, 0x9a, 0x21, 0x4f, 0x3e, 0xc9, 0x86,
+    0x7c, 0xa9, 0x40, 0xe6, 0x41, 0x71, 0x8f, 0x26,
+    0xba, 0xee, 0x55, 0x5b, 0x8c, 0x61, 0xc1, 0xb5,
+    0x0d, 0xf8, 0x46, 0x11, 0x6d, 0xcd, 0x3b, 0x1d,
+    0xee, 0x24, 0xf3, 0x19, 0xdf, 0x9b, 0x3d, 0x85,
+    0x14, 0x12, 0x1e, 0x4b, 0x5a, 0xc5, 0xaa, 0x32,
+    0x76, 0x02, 0x1d, 0x29, 0x09, 0xc7, 0x48, 0x29,
+    0xed, 0xeb, 0xc6, 0x8d, 0xb8, 0xb8, 0xc2, 0x5e,
+];
+
+const SALSA20_8_OUTPUT: [u8; 64] = [
+    0xa4, 0x1f, 0x85, 0x9c, 0x66, 0x08, 0xcc, 0x99,
+    0x3b, 0x81, 0xca, 0xcb, 0x02, 0x0c, 0xef, 0x05,
+    0x04, 0x4b, 0x21, 0x81, 0xa2, 0xfd, 0x33, 0x7d,
+    0xfd, 0x7b, 0x1c, 0x63, 0x96, 0x68, 0x2f, 0x29,
+    0xb4, 0x39, 0x31, 0x68, 0xe3, 0xc9, 0xe6, 0xbc,
+    0xfe, 0x6b, 0xc5, 0xb7, 0xa0, 0x6d, 0x96, 0xba,
+    0xe4, 0x24, 0xcc, 0x10, 0x2c, 0x91, 0x74, 0x5c,
+    0x24, 0xad, 0x67, 0x3d, 0xc7, 0x61, 0x8f, 0x81,
+];
+
+const BLOCK_MIX_INPUT: [u8; 128] = [
+    0xf7, 0xce, 0x0b, 0x65, 0x3d, 0x2d, 0x72, 0xa4,
+    0x10, 0x8c, 0xf5, 0xab, 0xe9, 0x12, 0xff, 0xdd,
+    0x77, 0x76, 0x16, 0xdb, 0xbb, 0x27, 0xa7, 0x0e,
+    0x82, 0x04, 0xf3, 0xae, 0x2d, 0x0f, 0x6f, 0xad,
+    0x89, 0xf6, 0x8f, 0x48, 0x11, 0xd1, 0xe8, 0x7b,
+    0xcc, 0x3b, 0xd7, 0x40, 0x0a, 0x9f, 0xfd, 0x29,
+    0x09, 0x4f, 0x01, 0x84, 0x63, 0x95, 0x74, 0xf3,
+    0x9a, 0xe5, 0xa1, 0x31, 0x52, 0x17, 0xbc, 0xd7,
+    0x89, 0x49, 0x91, 0x44, 0x72, 0x13, 0xbb, 0x22,
+    0x6c, 0x25, 0xb5, 0x4d, 0xa8, 0x63, 0x70, 0xfb,
+    0xcd, 0x98, 0x43, 0x80, 0x37, 0x46, 0x66, 0xbb,
+    0x8f, 0xfc, 0xb5, 0xbf, 0x40, 0xc2, 0x54, 0xb0,
+    0x67, 0xd2, 0x7c, 0x51, 0xce, 0x4a, 0xd5, 0xfe,
+    0xd8, 0x29, 0xc9, 0x0b, 0x50, 0x5a, 0x57, 0x1b,
+    0x7f, 0x4d, 0x1c, 0xad, 0x6a, 0x52, 0x3c, 0xda,
+    0x77, 0x0e, 0x67, 0xbc, 0xea, 0xaf, 0x7e, 0x89,
+];
+
+const BLOCK_MIX_OUTPUT: [u8; 128] = [
+    0xa4, 0x1f, 0x85, 0x9c, 0x66, 0x08, 0xcc, 0x99,
+    0x3b, 0x81, 0xca, 0xcb, 0x02, 0x0c, 0xef, 0x05,
+    0x04, 0x4b, 0x21, 0x81, 0xa2, 0xfd, 0x33, 0x7d,
+    0xfd, 0x7b, 0x1c, 0x63, 0x96, 0x68, 0x2f, 0x29,
+    0xb4, 0x39, 0x31, 0x68, 0xe3, 0xc9, 0xe6, 0xbc,
+    0xfe, 0x6b, 0xc5, 0xb7, 0xa0, 0x6d, 0x96, 0xba,
+    0xe4, 0x24, 0xcc, 0x10, 0x2c, 0x91, 0x74, 0x5c,
+    0x24, 0xad, 0x67, 0x3d, 0xc7, 0x61, 0x8f, 0x81,
+    0x20, 0xed, 0xc9, 0x75, 0x32, 0x38, 0x81, 0xa8,
+    0x05, 0x40, 0xf6, 0x4c, 0x16, 0x2d, 0xcd, 0x3c,
+    0x21, 0x07, 0x7c, 0xfe, 0x5f, 0x8d, 0x5f, 0xe2,
+    0xb1, 0xa4, 0x16, 0x8f, 0x95, 0x36, 0x78, 0xb7,
+    0x7d, 0x3b, 0x3d, 0x80, 0x3b, 0x60, 0xe4, 0xab,
+    0x92, 0x09, 0x96, 0xe5, 0x9b, 0x4d, 0x53, 0xb6,
+    0x5d, 0x2a, 0x22, 0x58, 0x77, 0xd5, 0xed, 0xf5,
+    0x84, 0x2c, 0xb9, 0xf1, 0x4e, 0xef, 0xe4, 0x25,
+];
+
+const RO_MIX_OUTPUT: [u8; 128] = [
+    0x79, 0xcc, 0xc1, 0x93, 0x62, 0x9d, 0xeb, 0xca,
+    0x04, 0x7f, 0x0b, 0x70, 0x60, 0x4b, 0xf6, 0xb6,
+    0x2c, 0xe3, 0xdd, 0x4a, 0x96, 0x26, 0xe3, 0x55,
+    0xfa, 0xfc, 0x61, 0x98, 0xe6, 0xea, 0x2b, 0x46,
+    0xd5, 0x84, 0x13, 0x67, 0x3b, 0x99, 0xb0, 0x29,
+    0xd6, 0x65, 0xc3, 0x57, 0x60, 0x1f, 0xb4, 0x26,
+    0xa0, 0xb2, 0xf4, 0xbb, 0xa2, 0x00, 0xee, 0x9f,
+    0x0a, 0x43, 0xd1, 0x9b, 0x57, 0x1a, 0x9c, 0x71,
+    0xef, 0x11, 0x42, 0xe6, 0x5d, 0x5a, 0x26, 0x6f,
+    0xdd, 0xca, 0x83, 0x2c, 0xe5, 0x9f, 0xaa, 0x7c,
+    0xac, 0x0b, 0x9c, 0xf1, 0xbe, 0x2b, 0xff, 0xca,
+    0x30, 0x0d, 0x01, 0xee, 0x38, 0x76, 0x19, 0xc4,
+    0xae, 0x12, 0xfd, 0x44, 0x38, 0xf2, 0x03, 0xa0,
+    0xe4, 0xe1, 0xc4, 0x7e, 0xc3, 0x14, 0x86, 0x1f,
+    0x4e, 0x90, 0x87, 0xcb, 0x33, 0x39, 0x6a, 0x68,
+    0x73, 0xe8, 0xf9, 0xd2, 0x53, 0x9a, 0x4b, 0x8e,
+];
+
+const SCRYPT_1_OUTPUT: [u8; 64] = [
+    0x77, 0xd6, 0x57, 0x62, 0x38, 0x65, 0x7b, 0x20,
+    0x3b, 0x19, 0xca, 0x42, 0xc1, 0x8a, 0x04, 0x97,
+    0xf1, 0x6b, 0x48, 0x44, 0xe3, 0x07, 0x4a, 0xe8,
+    0xdf, 0xdf, 0xfa, 0x3f, 0xed, 0xe2, 0x14, 0x42,
+    0xfc, 0xd0, 0x06, 0x9d, 0xed, 0x09, 0x48, 0xf8,
+    0x32, 0x6a, 0x75, 0x3a, 0x0f, 0xc8, 0x1f, 0x17,
+    0xe8, 0xd3, 0xe0, 0xfb, 0x2e, 0x0d, 0x36, 0x28,
+    0xcf, 0x35, 0xe2, 0x0c, 0x38, 0xd1, 0x89, 0x06,
+];
+
+const SCRYPT_2_OUTPUT: [u8; 64] = [
+    0xfd, 0xba, 0xbe, 0x1c, 0x9d, 0x34, 0x72, 0x00,
+    0x78, 0x56, 0xe7, 0x19, 0x0d, 0x01, 0xe9, 0xfe,
+    0x7c, 0x6a, 0xd7, 0xcb, 0xc8, 0x23, 0x78, 0x30,
+    0xe7, 0x73, 0x76, 0x63, 0x4b, 0x37, 0x31, 0x62,
+    0x2e, 0xaf, 0x30, 0xd9, 0x2e, 0x22, 0xa3, 0x88,
+    0x6f, 0xf1, 0x09, 0x27, 0x9d, 0x98, 0x30, 0xda,
+    0xc7, 0x27, 0xaf, 0xb9, 0x4a, 0x83, 0xee, 0x6d,
+    0x83, 0x60, 0xcb, 0xdf, 0xa2, 0xcc, 0x06, 0x40,
+];
+
+const SCRYPT_3_OUTPUT: [u8; 64] = [
+    0x70, 0x23, 0xbd, 0xcb, 0x3a, 0xfd, 0x73, 0x48,
+    0x46, 0x1c, 0x06, 0xcd, 0x81, 0xfd, 0x38, 0xeb,
+    0xfd, 0xa8, 0xfb, 0xba, 0x90, 0x4f, 0x8e, 0x3e,
+    0xa9, 0xb5, 0x43, 0xf6, 0x54, 0x5d, 0xa1, 0xf2,
+    0xd5, 0x43, 0x29, 0x55, 0x61, 0x3f, 0x0f, 0xcf,
+    0x62, 0xd4, 0x97, 0x05, 0x24, 0x2a, 0x9a, 0xf9,
+    0xe6, 0x1e, 0x85, 0xdc, 0x0d, 0x65, 0x1e, 0x40,
+    0xdf, 0xcf, 0x01, 0x7b, 0x45, 0x57, 0x58, 0x87,
+];
+
+const SCRYPT_4_OUTPUT: [u8; 64] = [
+    0x21, 0x01, 0xcb, 0x9b, 0x6a, 0x51, 0x1a, 0xae,
+    0xad, 0xdb, 0xbe, 0x09, 0xcf, 0x70, 0xf8, 0x81,
+    0xec, 0x56, 0x8d, 0x57, 0x4a, 0x2f, 0xfd, 0x4d,
+    0xab, 0xe5, 0xee, 0x98, 0x20, 0xad, 0xaa, 0x47,
+    0x8e, 0x56, 0xfd, 0x8f, 0x4b, 0xa5, 0xd0, 0x9f,
+    0xfa, 0x1c, 0x6d, 0x92, 0x7c, 0x40, 0xf4, 0xc3,
+    0x37, 0x30, 0x40, 0x49, 0xe8, 0xa9, 0x52, 0xfb,
+    0xcb, 0xf4, 0x5c, 0x6f, 0xa7, 0x7a, 0x41, 0xa4,
+];
@@ -0,0 +1,98 @@
+//! **INSECURE, FOR TESTS ONLY.** Fast parameters and fixed hash strings for
+//! the test suites of applications using this crate.
+//!
+//! **Never use anything of this module in production, not even behind a
+//! flag.** `FAST_PARAMS` can be brute-forced almost at the speed of
+//! SHA-256 and `fixed_hash` uses the same public salt for every password,
+//! so equal passwords give equal strings. The module only exists with the
+//! `test-util` feature, which belongs into `[dev-dependencies]`:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! scrypt = { version = "*", features = ["test-util"] }
+//! ```
+//!
+//! The strings of `fixed_hash` are ordinary rscrypt strings, so the code
+//! under test verifies them with `scrypt_check` like real hashes, and they
+//! are the same in every run and release, e.g. for snapshot tests.
+//! `assert_verifies!` and `assert_rejects!` check a password against a
+//! string with `scrypt_check` and report both on failure:
+//!
+//! ```
+//! #[macro_use]
+//! extern crate scrypt;
+//!
+//! use scrypt::testing::fixed_hash;
+//!
+//! # fn main() {
+//! let stored = fixed_hash("hunter2");
+//! assert_verifies!("hunter2", &stored);
+//! assert_rejects!("hunter3", &stored);
+//! # }
+//! ```
+use errors::CheckError;
+use format;
+use params::Params;
+use {parse_checked, scrypt_simple_with_salt};
+
+pub use selftest::corrupt_self_test;
+
+/// **INSECURE, FOR TESTS ONLY.** `log_n = 1`, `r = 1` and `p = 1`, the
+/// smallest parameters, which hash in microseconds.
+pub const FAST_PARAMS: Params = Params { log_n: 1, r: 1, p: 1 };
+
+/// The salt of `fixed_hash`.
+const FIXED_SALT: [u8; 16] = *b"scrypt test salt";
+
+/// **INSECURE, FOR TESTS ONLY.** The rscrypt string of `password` under
+/// `FAST_PARAMS` and a constant salt, the same for the same password in
+/// every run.
+///
+/// # Panics
+/// If `password` is longer than `DEFAULT_MAX_PASSWORD_LEN`.
+pub fn fixed_hash(password: &str) -> String {
+    scrypt_simple_with_salt(password, &FIXED_SALT, &FAST_PARAMS)
+        .expect("the test password is too long")
+}
+
+/// The steps of `scrypt_check` after scrypt: decode the hash of
+/// `hashed_value` and compare it against the computed `output`, e.g. to
+/// time the comparison on its own.
+///
+/// # Return
+/// `Err(CheckError::HashMismatch)` if `output` differs from the hash and the
+/// parse errors of `scrypt_check` otherwise.
+pub fn compare_stored(hashed_value: &str, output: &[u8]) -> Result<(), CheckError> {
+    let hash = parse_checked(hashed_value)?;
+    format::compare(output, hash.hash_bytes())
+}
+
+/// Assert that a password verifies against a hash string with
+/// `scrypt_check`, see `testing`.
+#[macro_export]
+macro_rules! assert_verifies {
+    ($password:expr, $hashed:expr) => {
+        match $crate::scrypt_check($password, $hashed) {
+            Ok(()) => {}
+            Err(e) => panic!("password {:?} does not verify against {:?}: {}",
+                $password, $hashed, e),
+        }
+    };
+}
+
+/// Assert that a password is rejected by `scrypt_check` as a wrong
+/// password of a hash string, or with the given `CheckError`, see
+/// `testing`.
+#[macro_export]
+macro_rules! assert_rejects {
+    ($password:expr, $hashed:expr) => {
+        assert_rejects!($password, $hashed, $crate::errors::CheckError::HashMismatch)
+    };
+    ($password:expr, $hashed:expr, $error:expr) => {
+        match $crate::scrypt_check($password, $hashed) {
+            Err(ref e) if *e == $error => {}
+            result => panic!("password {:?} against {:?}: expected Err({:?}), got {:?}",
+                $password, $hashed, $error, result),
+        }
+    };
+}
@@ -0,0 +1,88 @@
+//! scrypt of passwords and salts given in parts, e.g. a salt assembled
+//! from a version tag, a user id and random bytes, without concatenating
+//! them into a buffer which then has to be zeroed.
+use byteorder::{BigEndian, ByteOrder};
+use sha2::{Digest, Sha256};
+
+use errors::InvalidOutputLen;
+use {check_output_len, mix_with, zero, Params, Scratch};
+
+/// The block size of SHA-256.
+const BLOCK_LEN: usize = 64;
+
+/// HMAC-SHA256 keyed with a password in parts: the hash states after the
+/// inner and the outer padded key.
+struct PartsHmac {
+    inner: Sha256,
+    outer: Sha256,
+}
+
+impl PartsHmac {
+    fn new(key: &[&[u8]]) -> PartsHmac {
+        // keys longer than a block are hashed, like by `Hmac::new_varkey`
+        let mut block = [0u8; BLOCK_LEN];
+        let len: usize = key.iter().map(|part| part.len()).sum();
+        if len > BLOCK_LEN {
+            let mut hash = Sha256::default();
+            for part in key { hash.input(part); }
+            block[..32].copy_from_slice(&hash.result());
+        } else {
+            let mut pos = 0;
+            for part in key {
+                block[pos..pos + part.len()].copy_from_slice(part);
+                pos += part.len();
+            }
+        }
+
+        let mut inner = Sha256::default();
+        let mut outer = Sha256::default();
+        let mut pad = [0u8; BLOCK_LEN];
+        for (p, &k) in pad.iter_mut().zip(block.iter()) { *p = k ^ 0x36; }
+        inner.input(&pad);
+        for (p, &k) in pad.iter_mut().zip(block.iter()) { *p = k ^ 0x5c; }
+        outer.input(&pad);
+        zero(&mut block);
+        zero(&mut pad);
+        PartsHmac { inner, outer }
+    }
+
+    /// PBKDF2 with one iteration, of the salt `salt`, into `output`.
+    fn pbkdf2(&self, salt: &[&[u8]], output: &mut [u8]) {
+        for (i, chunk) in output.chunks_mut(32).enumerate() {
+            let mut index = [0u8; 4];
+            BigEndian::write_u32(&mut index, i as u32 + 1);
+            let mut inner = self.inner.clone();
+            for part in salt { inner.input(part); }
+            inner.input(&index);
+            let mut outer = self.outer.clone();
+            outer.input(&inner.result());
+            chunk.copy_from_slice(&outer.result()[..chunk.len()]);
+        }
+    }
+}
+
+/// `scrypt_vectored` is `scrypt()` of the concatenation of the parts of
+/// `password` and of the parts of `salt`, computed without concatenating
+/// them. Parts may be empty and split the password or the salt anywhere.
+///
+/// The password is only copied into a block on the stack, which is zeroed
+/// afterwards, or hashed if it is longer than 64 bytes, like HMAC does.
+///
+/// # Arguments
+/// - `password` - The parts of the password
+/// - `salt` - The parts of the salt
+/// - `params` - The Params to use
+/// - `output` - The resulting derived key is returned in this byte vector.
+///
+/// # Return
+/// `Err(InvalidOutputLen)` under the conditions of `scrypt()`.
+pub fn scrypt_vectored(
+    password: &[&[u8]], salt: &[&[u8]], params: &Params, output: &mut [u8]
+) -> Result<(), InvalidOutputLen> {
+    check_output_len(output)?;
+    let hmac = PartsHmac::new(password);
+    let mut scratch = Scratch::default();
+    let b = mix_with(params, &mut scratch, |b| hmac.pbkdf2(salt, b));
+    hmac.pbkdf2(&[&*b], output);
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! Kani proofs that the index arithmetic of ROMix and BlockMix stays in
+//! bounds for all parameters accepted by `Params::new`. Run them with
+//! `cargo kani` in this directory, or one with `cargo kani --harness
+//! <name>`. `tests/indexing.rs` checks the same bounds for every small
+//! parameter set in the regular tests.
+use params::Params;
+use romix::{integerify, shuffled_index, v_offset, Block};
+
+/// Valid symbolic parameters, with the `2 * r` blocks of a block of `B`
+/// and `N`.
+fn any_params() -> (Params, usize, usize) {
+    let params = Params::new(kani::any(), kani::any(), kani::any());
+    kani::assume(params.is_ok());
+    let params = params.unwrap();
+    (params, params.r as usize * 2, 1usize << params.log_n)
+}
+
+/// `V` and the `p` blocks of `B` have lengths which fit into a `usize`.
+#[kani::proof]
+fn lengths_fit() {
+    let (params, r2, n) = any_params();
+    assert!((r2 * 64).checked_mul(n).is_some());
+    assert!((r2 * 64).checked_mul(params.p as usize).is_some());
+}
+
+/// Integerify is below `N` for any block, so the block `j` of `V` is
+/// within the `2 * r * N` blocks of `V`.
+#[kani::proof]
+fn integerify_in_bounds() {
+    let (_, r2, n) = any_params();
+    // only the last block of `x` is read, whatever `r`
+    let x = [Block(kani::any()), Block(kani::any())];
+    let j = integerify(&x, n);
+    assert!(j < n);
+    assert!(v_offset(j, r2) + r2 <= r2 * n);
+}
+
+/// The blocks `i < N` of the first loop of ROMix are within `V`.
+#[kani::proof]
+fn v_blocks_in_bounds() {
+    let (_, r2, n) = any_params();
+    let i: usize = kani::any();
+    kani::assume(i < n);
+    assert!(v_offset(i + 1, r2) <= r2 * n);
+    assert_eq!(v_offset(i + 1, r2) - v_offset(i, r2), r2);
+}
+
+/// The `2 * r` Salsa20/8 blocks of BlockMix are written within its
+/// output, each to its own index.
+#[kani::proof]
+fn shuffled_indices_in_bounds() {
+    let (_, r2, _) = any_params();
+    let i: usize = kani::any();
+    let k: usize = kani::any();
+    kani::assume(i < r2 && k < r2);
+    assert!(shuffled_index(i, r2) < r2);
+    if i != k { assert!(shuffled_index(i, r2) != shuffled_index(k, r2)); }
+}
+
+/// The `p` blocks of `B` are within its `2 * r * p` blocks.
+#[kani::proof]
+fn b_blocks_in_bounds() {
+    let (params, r2, _) = any_params();
+    let block: usize = kani::any();
+    kani::assume(block < params.p as usize);
+    assert!(v_offset(block + 1, r2) <= r2 * params.p as usize);
+}
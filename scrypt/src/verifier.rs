@@ -0,0 +1,533 @@
+//! Verification restricted to allowed formats and parameters.
+use core::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+use backend::HashBackend;
+use client::ClientScheme;
+use errors::{CheckError, Rule};
+use format::{verify_backend, Format, HashParts};
+use hash::ScryptHash;
+use observer::{global_observer, ScryptObserver, VerifyEvent};
+use params::Params;
+use pepper::{self, KeyProvider};
+use prehash::{check_len, DEFAULT_MAX_PASSWORD_LEN};
+use {decode_rscrypt_fields, parse_checked, split_input, split_rscrypt, unwrap_legacy,
+    PasswordVerified, Scratch, V2Extension, V2Options, Verified, VerifiedWith, VerifyOutcome,
+    VerifyReport, WRAPPED_PREFIX};
+
+/// Requirements on the parameters of hashes accepted by a `Verifier`.
+///
+/// `ParamsPolicy::default()` accepts all parameters, e.g. to only set
+/// `min_log_n` with `..ParamsPolicy::default()`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParamsPolicy {
+    /// Smallest `log_n` accepted, hashes with a smaller `N` are too weak.
+    pub min_log_n: u8,
+    /// Bytes `scrypt` may allocate mixing one lane at a time,
+    /// `Params::serial_memory()`.
+    pub max_memory: u64,
+    /// Salsa20/8 core invocations `scrypt` may perform, `4 * N * r * p`.
+    pub max_ops: u64,
+}
+
+impl ParamsPolicy {
+    fn check(&self, params: &Params) -> Result<(), CheckError> {
+        if params.log_n() < self.min_log_n { Err(CheckError::Refused(Rule::MinLogN))?; }
+        if params.serial_memory() > self.max_memory as u128 {
+            Err(CheckError::Refused(Rule::MaxMemory))?;
+        }
+        if params.salsa_ops() > self.max_ops as u128 {
+            Err(CheckError::Refused(Rule::MaxOps))?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for ParamsPolicy {
+    fn default() -> ParamsPolicy {
+        ParamsPolicy { min_log_n: 0, max_memory: u64::MAX, max_ops: u64::MAX }
+    }
+}
+
+/// Verifies passwords like `scrypt_check`, but only against hashes which
+/// satisfy every rule, e.g. of a security policy.
+///
+/// The rules are checked before the hash is computed, a hash breaking one
+/// is reported as `Err(CheckError::Refused)` with the rule, even if the
+/// password is wrong. Created with `Verifier::builder()`, which starts
+/// without rules.
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    formats: Option<Vec<Format>>,
+    versions: Option<Vec<u8>>,
+    policy: ParamsPolicy,
+    strict: bool,
+    trim: bool,
+    default_lengths: bool,
+    wrapped: bool,
+    max_password_len: Option<usize>,
+    observer: Observer,
+    backend: Backend,
+    keys: Keys,
+    client_scheme: Option<ClientScheme>,
+    min_params: Option<Params>,
+}
+
+/// The observer of a `Verifier`, which is `Debug` without requiring it of
+/// `ScryptObserver`.
+#[derive(Clone, Default)]
+struct Observer(Option<Arc<dyn ScryptObserver>>);
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(ScryptObserver)" } else { "None" })
+    }
+}
+
+/// The backend of a `Verifier`, `Debug` like `Observer`.
+#[derive(Clone, Default)]
+struct Backend(Option<Arc<dyn HashBackend>>);
+
+impl fmt::Debug for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(HashBackend)" } else { "None" })
+    }
+}
+
+/// What a verification mixes into the password besides the stored string.
+#[derive(Clone, Copy, Default)]
+struct Inputs<'a> {
+    /// The peppers, instead of those of the `key_provider`.
+    keys: Option<&'a dyn KeyProvider>,
+    /// The associated data the string must be bound to.
+    ad: Option<&'a [u8]>,
+}
+
+/// The pepper provider of a `Verifier`, `Debug` like `Observer`.
+#[derive(Clone, Default)]
+struct Keys(Option<Arc<dyn KeyProvider + Send + Sync>>);
+
+impl fmt::Debug for Keys {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(KeyProvider)" } else { "None" })
+    }
+}
+
+impl Verifier {
+    /// Start a verifier without rules.
+    pub fn builder() -> VerifierBuilder {
+        VerifierBuilder {
+            verifier: Verifier {
+                formats: None,
+                versions: None,
+                policy: ParamsPolicy::default(),
+                strict: false,
+                trim: false,
+                default_lengths: false,
+                wrapped: false,
+                max_password_len: Some(DEFAULT_MAX_PASSWORD_LEN),
+                observer: Observer::default(),
+                backend: Backend::default(),
+                keys: Keys::default(),
+                client_scheme: None,
+                min_params: None,
+            },
+        }
+    }
+
+    /// Compare `password` against `stored` if it satisfies the rules.
+    ///
+    /// # Return
+    /// `Err(CheckError::PasswordTooLong)` if the password is longer than
+    /// the limit, `Err(CheckError::Refused)` with the first rule `stored`
+    /// breaks, in the order format, version, encoding and parameters,
+    /// `Err(CheckError::KeyUnavailable)` if the `key_provider` can not
+    /// fetch the pepper of `stored`, `Err(CheckError::ClientSchemeMismatch)`
+    /// if `stored` is not of the `client_scheme` and the errors of
+    /// `scrypt_check` otherwise.
+    pub fn verify(&self, password: &str, stored: &str) -> Result<(), CheckError> {
+        self.verify_bytes(password.as_bytes(), stored)
+    }
+
+    /// `verify()` for passwords which are not valid UTF-8, e.g. the
+    /// client-side digests of a `client_scheme`.
+    pub fn verify_bytes(&self, password: &[u8], stored: &str) -> Result<(), CheckError> {
+        self.verify_parsed(password, stored, Inputs::default(), &mut None)
+    }
+
+    /// Same as `verify`, with the peppers of `keys` instead of the
+    /// `key_provider`, e.g. of a `PepperSet` during a key rotation.
+    pub fn verify_with_keys(&self, password: &str, stored: &str, keys: &dyn KeyProvider)
+        -> Result<(), CheckError>
+    {
+        let inputs = Inputs { keys: Some(keys), ..Inputs::default() };
+        self.verify_parsed(password.as_bytes(), stored, inputs, &mut None)
+    }
+
+    /// Same as `verify` for the strings of `SimpleConfig::hash_with_ad`,
+    /// which are bound to the associated data `ad`.
+    ///
+    /// Strings without the binding are rejected like a wrong `ad`, otherwise
+    /// any string of `scrypt_simple()` could be copied into the context.
+    ///
+    /// # Return
+    /// `Err(CheckError::HashMismatch)` if password or `ad` do not match or
+    /// if `stored` is not bound to associated data,
+    /// `Err(CheckError::NeedsPepper)` for peppered strings and the errors of
+    /// `verify` otherwise.
+    pub fn verify_with_ad(&self, password: &str, stored: &str, ad: &[u8])
+        -> Result<(), CheckError>
+    {
+        let inputs = Inputs { ad: Some(ad), ..Inputs::default() };
+        self.verify_parsed(password.as_bytes(), stored, inputs, &mut None)
+    }
+
+    /// Same as `verify`, and reports the format, parameters and hash length
+    /// of `stored` if the password matches, e.g. to track the migration to
+    /// new parameters without parsing the string again.
+    pub fn verify_detailed(&self, password: &str, stored: &str)
+        -> Result<VerifiedWith, CheckError>
+    {
+        let mut parsed = None;
+        self.verify_parsed(password.as_bytes(), stored, Inputs::default(), &mut parsed)?;
+        Ok(parsed.expect("a verified string is parsed"))
+    }
+
+    /// Same as `verify_bytes`, but returns a `PasswordVerified` token if the
+    /// password matches.
+    pub fn verify_token<P: AsRef<[u8]>>(&self, password: P, stored: &str)
+        -> Result<PasswordVerified, CheckError>
+    {
+        self.verify_bytes(password.as_ref(), stored)?;
+        Ok(PasswordVerified { _private: () })
+    }
+
+    /// Same as `verify`, but also reports how long the verification took
+    /// and what it cost, e.g. for anomaly detection in logs. A mismatch is
+    /// part of the report, so that failed attempts are timed as well.
+    ///
+    /// `Instant` is used for the timing, which is not available on
+    /// `wasm32-unknown-unknown`.
+    ///
+    /// # Return
+    /// `Ok(VerifyReport)` for a match and a mismatch, the other errors of
+    /// `verify` otherwise.
+    pub fn verify_report(&self, password: &str, stored: &str)
+        -> Result<VerifyReport, CheckError>
+    {
+        let start = Instant::now();
+        let mut parsed = None;
+        let outcome = match self.verify_parsed(
+            password.as_bytes(), stored, Inputs::default(), &mut parsed)
+        {
+            Ok(()) => VerifyOutcome::Match,
+            Err(CheckError::HashMismatch) => VerifyOutcome::Mismatch,
+            Err(e) => Err(e)?,
+        };
+        let params = parsed.expect("a compared string is parsed").params();
+        Ok(VerifyReport {
+            outcome,
+            params,
+            elapsed: start.elapsed(),
+            memory_bytes: params.serial_memory(),
+            parallel_memory_bytes: params.required_memory(),
+        })
+    }
+
+    /// Same as `verify`, but reports a `stored` hash computed with
+    /// parameters weaker than the `min_params` as
+    /// `Ok(Verified::OkButWeak)`, so that the password can be rehashed.
+    /// The password is verified first: a mismatch is reported as
+    /// `Err(CheckError::HashMismatch)` regardless of the parameter strength.
+    pub fn verify_strength(&self, password: &str, stored: &str)
+        -> Result<Verified, CheckError>
+    {
+        let mut parsed = None;
+        self.verify_parsed(password.as_bytes(), stored, Inputs::default(), &mut parsed)?;
+        match (parsed.map(|parsed| parsed.params()), self.min_params) {
+            (Some(params), Some(min)) if params.is_weaker_than(&min) => {
+                Ok(Verified::OkButWeak(params))
+            }
+            _ => Ok(Verified::Ok),
+        }
+    }
+
+    /// `verify_bytes()` with `inputs`, which stores the format, parameters
+    /// and hash length of `stored` into `parsed` once it is parsed.
+    fn verify_parsed(
+        &self, password: &[u8], stored: &str, inputs: Inputs,
+        parsed: &mut Option<VerifiedWith>,
+    ) -> Result<(), CheckError> {
+        let observer = match self.observer.0.clone().or_else(global_observer) {
+            Some(observer) => observer,
+            None => return self.verify_unobserved(password, stored, inputs, parsed),
+        };
+        let start = Instant::now();
+        let result = self.verify_unobserved(password, stored, inputs, parsed);
+        observer.on_verify(&VerifyEvent {
+            params: parsed.map(|parsed| parsed.params()),
+            format: parsed.map(|parsed| parsed.format()),
+            result,
+            elapsed: start.elapsed(),
+        });
+        result
+    }
+
+    /// `verify_parsed()` without events.
+    fn verify_unobserved(
+        &self, password: &[u8], stored: &str, inputs: Inputs, parsed: &mut Option<VerifiedWith>,
+    ) -> Result<(), CheckError> {
+        check_len(password, self.max_password_len)?;
+        let stored = if self.trim {
+            stored.trim_matches(|c: char| c.is_ascii_whitespace())
+        } else {
+            stored
+        };
+        match inputs.ad {
+            Some(ad) => self.verify_associated(password, stored, ad, parsed),
+            None if self.wrapped && stored.starts_with(WRAPPED_PREFIX) => {
+                let (inner, rscrypt) = unwrap_legacy(stored)?;
+                // only rscrypt versions 0 and 1 are wrapped
+                if split_rscrypt(&rscrypt)?.version > 1 { Err(CheckError::InvalidFormat)?; }
+                self.verify_stored(&inner.digest(password), &rscrypt, inputs, parsed)
+            }
+            None => self.verify_stored(password, stored, inputs, parsed),
+        }
+    }
+
+    /// `verify_unobserved()` of a string which is not wrapped, without
+    /// associated data.
+    fn verify_stored(
+        &self, password: &[u8], stored: &str, inputs: Inputs, parsed: &mut Option<VerifiedWith>,
+    ) -> Result<(), CheckError> {
+        let keys = inputs.keys.or_else(|| self.keys.0.as_ref().map(|keys| &**keys as _));
+        let hash = match (parse_checked(stored), keys, self.client_scheme) {
+            (Err(CheckError::NeedsPepper(_)), Some(_), _)
+            | (Err(CheckError::NeedsClientScheme(_)), _, Some(_)) => {
+                return self.verify_input(password, stored, keys, parsed);
+            }
+            // strings without a scheme do not hold a hash of the digest
+            (Ok(_), _, Some(scheme))
+            | (Err(CheckError::NeedsPepper(_)), None, Some(scheme))
+            | (Err(CheckError::NeedsAssociatedData), _, Some(scheme)) => {
+                Err(CheckError::ClientSchemeMismatch { stored: None, claimed: scheme.id() })?
+            }
+            (hash, _, _) => hash?,
+        };
+        *parsed = Some(VerifiedWith::new(&hash));
+        self.check_rules(hash.format(), hash.format_version(), &hash.params(), stored,
+            &|| hash.to_string())?;
+        if self.default_lengths { check_default_lengths(&hash)?; }
+        match self.backend.0 {
+            Some(ref backend) => hash.verify_bytes_with_backend(password, &**backend),
+            None => hash.verify_bytes(password),
+        }
+    }
+
+    /// `verify_unobserved()` of an rscrypt string with a pepper or a client
+    /// scheme, the key is only fetched from the `key_provider` and the
+    /// scheme only compared if it satisfies the rules.
+    fn verify_input(
+        &self, password: &[u8], stored: &str, keys: Option<&dyn KeyProvider>,
+        parsed: &mut Option<VerifiedWith>,
+    ) -> Result<(), CheckError> {
+        let input = split_input(stored)?.expect("an rscrypt string with an input");
+        let HashParts { ref params, ref hash, .. } = input.parts;
+        *parsed = Some(VerifiedWith::rscrypt(2, params, hash));
+        self.check_rules(Format::Rscrypt, Some(2), params, stored, &|| input.encode())?;
+        let prehashed;
+        let password = match (input.extension, keys, self.client_scheme) {
+            (V2Extension::Pepper(id), Some(keys), _) => {
+                prehashed = pepper::prehash_provided(keys, id, password)
+                    .map_err(|e| e.check_error(id))?;
+                &prehashed[..]
+            }
+            (V2Extension::ClientScheme(id), _, Some(scheme)) => {
+                let claimed = scheme.id();
+                if id != claimed {
+                    Err(CheckError::ClientSchemeMismatch { stored: Some(id), claimed })?;
+                }
+                password
+            }
+            _ => unreachable!("`verify_unobserved` checked the input"),
+        };
+        match self.backend.0 {
+            Some(ref backend) => {
+                verify_backend(password, params, &input.scrypt_salt, hash, &**backend)
+            }
+            None => input.verify(password),
+        }
+    }
+
+    /// `verify_unobserved()` of a string bound to the associated data `ad`,
+    /// with the options its header declares.
+    fn verify_associated(
+        &self, password: &[u8], stored: &str, ad: &[u8], parsed: &mut Option<VerifiedWith>,
+    ) -> Result<(), CheckError> {
+        let fields = split_rscrypt(stored)?;
+        if let Some(id) = fields.pepper { Err(CheckError::NeedsPepper(id))?; }
+        let (params, salt, hash) = decode_rscrypt_fields(&fields)?;
+        *parsed = Some(VerifiedWith::rscrypt(fields.version, &params, &hash));
+        if !fields.associated { Err(CheckError::HashMismatch)?; }
+        let options = V2Options::of(&fields);
+        self.check_rules(Format::Rscrypt, Some(2), &params, stored,
+            &|| options.encode(&params, &salt, &hash))?;
+        match self.backend.0 {
+            Some(ref backend) => {
+                if options.final_rounds.is_some() { Err(CheckError::Unrepresentable)?; }
+                let mut digest = [0u8; 32];
+                let password = options.prehash(password, &mut digest);
+                let salt = options.scrypt_salt(&params, &salt, hash.len(), ad);
+                verify_backend(password, &params, &salt, &hash, &**backend)
+            }
+            None => options.verify_with(password, &params, &salt, &hash, ad,
+                &mut vec![0u8; hash.len()], &mut Scratch::default()),
+        }
+    }
+
+    /// Check the rules against a hash of `format`, `version` and `params`,
+    /// `encode` returns its canonical encoding.
+    fn check_rules(
+        &self, format: Format, version: Option<u8>, params: &Params, stored: &str,
+        encode: &dyn Fn() -> String,
+    ) -> Result<(), CheckError> {
+        if let Some(ref formats) = self.formats {
+            if !formats.contains(&format) { Err(CheckError::Refused(Rule::Format))?; }
+        }
+        if let (Some(ref versions), Some(version)) = (&self.versions, version) {
+            if !versions.contains(&version) { Err(CheckError::Refused(Rule::Version))?; }
+        }
+        if self.strict && encode() != stored {
+            Err(CheckError::Refused(Rule::Encoding))?;
+        }
+        self.policy.check(params)
+    }
+}
+
+/// Reject rscrypt strings of versions 0 and 1 unless their salt is 16 and
+/// their hash 32 bytes long, see `VerifierBuilder::default_lengths`.
+fn check_default_lengths(hash: &ScryptHash) -> Result<(), CheckError> {
+    let (salt_len, hash_len) = (hash.salt().len(), hash.hash_bytes().len());
+    match hash.format_version() {
+        Some(0) | Some(1) if salt_len != 16 || hash_len != 32 => {
+            Err(CheckError::UnexpectedFieldLen { salt_len, hash_len })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Builder of `Verifier`, see `Verifier::builder()`.
+#[derive(Debug, Clone)]
+pub struct VerifierBuilder {
+    verifier: Verifier,
+}
+
+impl VerifierBuilder {
+    /// Accept only hashes in `formats`, all formats by default.
+    pub fn allow_formats(&mut self, formats: &[Format]) -> &mut VerifierBuilder {
+        self.verifier.formats = Some(formats.to_vec());
+        self
+    }
+
+    /// Accept only rscrypt strings of `versions`, all versions by default.
+    /// The other formats are not affected.
+    pub fn allow_rscrypt_versions(&mut self, versions: &[u8]) -> &mut VerifierBuilder {
+        self.verifier.versions = Some(versions.to_vec());
+        self
+    }
+
+    /// Accept only hashes whose parameters satisfy `policy`.
+    pub fn require(&mut self, policy: ParamsPolicy) -> &mut VerifierBuilder {
+        self.verifier.policy = policy;
+        self
+    }
+
+    /// Report hashes weaker than `min_params`, as defined by
+    /// `Params::is_weaker_than()`, from `Verifier::verify_strength`. Unlike
+    /// `require`, they still verify.
+    pub fn min_params(&mut self, min_params: Params) -> &mut VerifierBuilder {
+        self.verifier.min_params = Some(min_params);
+        self
+    }
+
+    /// Accept only hashes which are stored exactly as `ScryptHash` would
+    /// encode them, e.g. with explicit PHC parameters in their usual order,
+    /// the standard base64 alphabet and lowercase hex. Off by default.
+    pub fn strict_encoding(&mut self, strict: bool) -> &mut VerifierBuilder {
+        self.verifier.strict = strict;
+        self
+    }
+
+    /// Remove ASCII whitespace, e.g. the newline of a line copied from a
+    /// file, from both ends of stored hashes before they are parsed.
+    /// Whitespace within a hash is still invalid. Off by default.
+    pub fn trim_whitespace(&mut self, trim: bool) -> &mut VerifierBuilder {
+        self.verifier.trim = trim;
+        self
+    }
+
+    /// Accept only rscrypt strings of versions 0 and 1 whose salt is 16 and
+    /// whose hash is 32 bytes long, as produced by `scrypt_simple()`, others
+    /// are rejected with `Err(CheckError::UnexpectedFieldLen)` before the
+    /// hash is computed. Version 2 strings declare their lengths and other
+    /// formats are not affected. Off by default.
+    pub fn default_lengths(&mut self, default_lengths: bool) -> &mut VerifierBuilder {
+        self.verifier.default_lengths = default_lengths;
+        self
+    }
+
+    /// Verify the strings of `wrap_legacy_hash()` as well: the inner digest
+    /// of the password is verified against the wrapped rscrypt string, to
+    /// which the rules apply. Off by default.
+    pub fn legacy_wrapped(&mut self, wrapped: bool) -> &mut VerifierBuilder {
+        self.verifier.wrapped = wrapped;
+        self
+    }
+
+    /// Accept only passwords of at most `max` bytes,
+    /// `DEFAULT_MAX_PASSWORD_LEN` by default. `None` removes the limit.
+    /// Longer passwords are rejected before `stored` is parsed.
+    pub fn max_password_len(&mut self, max: Option<usize>) -> &mut VerifierBuilder {
+        self.verifier.max_password_len = max;
+        self
+    }
+
+    /// Report every verification to `observer` instead of the one of
+    /// `set_global_observer()`.
+    pub fn observer(&mut self, observer: Arc<dyn ScryptObserver>) -> &mut VerifierBuilder {
+        self.verifier.observer = Observer(Some(observer));
+        self
+    }
+
+    /// Compute the hashes with `backend` instead of in this process.
+    /// Parsing and the rules stay local.
+    pub fn backend(&mut self, backend: Arc<dyn HashBackend>) -> &mut VerifierBuilder {
+        self.verifier.backend = Backend(Some(backend));
+        self
+    }
+
+    /// Verify peppered rscrypt strings with the keys of `keys`, e.g. of a
+    /// KMS. Without a provider they are rejected with
+    /// `Err(CheckError::NeedsPepper)`.
+    pub fn key_provider(&mut self, keys: Arc<dyn KeyProvider + Send + Sync>)
+        -> &mut VerifierBuilder
+    {
+        self.verifier.keys = Keys(Some(keys));
+        self
+    }
+
+    /// Verify client-side digests of `scheme`, see `scrypt_simple_client`,
+    /// instead of passwords. The digest is only hashed if the stored string
+    /// records the same scheme, strings without a scheme are rejected, as
+    /// the digest is not their password.
+    pub fn client_scheme(&mut self, scheme: ClientScheme) -> &mut VerifierBuilder {
+        self.verifier.client_scheme = Some(scheme);
+        self
+    }
+
+    /// Create the verifier.
+    pub fn build(&self) -> Verifier {
+        self.verifier.clone()
+    }
+}
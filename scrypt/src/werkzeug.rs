@@ -0,0 +1,76 @@
+//! The format of Werkzeug's `generate_password_hash(method="scrypt")`:
+//!
+//! `scrypt:<N>:<r>:<p>$<salt>$<hex(hash)>`
+//!
+//! Like Django, Werkzeug stores `N` itself and passes the plain text salt
+//! to scrypt verbatim, it is not prepended to the password. Werkzeug
+//! generates 16 alphanumeric characters. The hash is always 512 bits,
+//! encoded as lowercase hex.
+//!
+//! Werkzeug also accepts a bare `scrypt` method with its default parameters,
+//! but never produces it, so it is not supported here.
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use errors::CheckError;
+use params::Params;
+use phc::decode_decimal;
+use {hex, hex_string};
+
+/// Prefix identifying Werkzeug strings.
+pub(crate) const PREFIX: &str = "scrypt:";
+
+/// Check if `hashed_value` is a Werkzeug string. Hex strings share the
+/// prefix, but `ln=` can not start a Werkzeug method.
+pub(crate) fn matches(hashed_value: &str) -> bool {
+    hashed_value.starts_with(PREFIX) && !hashed_value.starts_with(hex_string::PREFIX)
+}
+
+/// Length of the hash produced by Werkzeug.
+pub(crate) const HASH_LEN: usize = 64;
+
+/// Length of the salt generated by Werkzeug.
+#[cfg(feature="simple")]
+pub(crate) const SALT_LEN: usize = 16;
+
+/// Encode a Werkzeug string.
+pub(crate) fn encode(params: &Params, salt: &str, hash: &[u8]) -> String {
+    format!(
+        "scrypt:{}:{}:{}${}${}",
+        1u64 << params.log_n, params.r, params.p, salt, hex::encode(hash),
+    )
+}
+
+/// Decode a Werkzeug string into parameters, salt and hash.
+pub(crate) fn decode(hashed_value: &str)
+    -> Result<(Params, Vec<u8>, Vec<u8>), CheckError>
+{
+    let fields: Vec<&str> = hashed_value.split('$').collect();
+    if fields.len() != 3 { Err(CheckError::InvalidFormat)?; }
+
+    let method: Vec<&str> = fields[0].split(':').collect();
+    if method.len() != 4 || method[0] != "scrypt" {
+        Err(CheckError::InvalidFormat)?;
+    }
+    let n: u64 = decode_decimal(method[1])?;
+    if !n.is_power_of_two() || n < 2 { Err(CheckError::InvalidFormat)?; }
+    let params = Params::new(
+        n.trailing_zeros() as u8,
+        decode_decimal(method[2])?,
+        decode_decimal(method[3])?,
+    ).map_err(|_| CheckError::InvalidFormat)?;
+
+    let hash = decode_hex(fields[2])?;
+    if hash.len() != HASH_LEN { Err(CheckError::InvalidFormat)?; }
+
+    Ok((params, fields[1].as_bytes().to_vec(), hash))
+}
+
+/// Decode lowercase hex, Werkzeug and `php-scrypt` compare the hex strings
+/// themselves.
+pub(crate) fn decode_hex(src: &str) -> Result<Vec<u8>, CheckError> {
+    if src.bytes().any(|b| b.is_ascii_uppercase()) {
+        Err(CheckError::InvalidFormat)?;
+    }
+    hex::decode(src).ok_or(CheckError::InvalidFormat)
+}
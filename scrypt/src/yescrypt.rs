@@ -0,0 +1,594 @@
+//! yescrypt, the scrypt derived password hash of the `$y$` crypt(3) strings
+//! of libxcrypt, e.g. in `/etc/shadow`.
+//!
+//! `$y$<flavor><N><r>[<have>[<p>][<t>]]$<salt>$<hash>`
+//!
+//! The parameters are variable-length numbers of the `./0-9A-Za-z`
+//! alphabet, `have` flags which of the optional ones follow. The salt is
+//! decoded and the 256-bit hash encoded like the hash of `$7$` strings.
+//!
+//! Three flavors are implemented: classic scrypt, `YESCRYPT_WORM` and
+//! `YESCRYPT_RW` with the pwxform settings of libxcrypt, which writes
+//! only the latter. Hash upgrades (`g`) and ROMs are not implemented,
+//! strings using them are reported as `CheckError::UnsupportedFormat`.
+use byteorder::{ByteOrder, LittleEndian};
+use hmac::{Hmac, Mac};
+use pbkdf2::pbkdf2;
+#[cfg(feature="simple")]
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+use {constant_time_eq, crypt7};
+use errors::{CheckError, InvalidParams, ParamsReason};
+#[cfg(feature="simple")]
+use errors::HashError;
+#[cfg(feature="simple")]
+use os_rng;
+use romix::salsa20_words;
+
+/// Prefix identifying `$y$` strings.
+pub(crate) const PREFIX: &str = "$y$";
+
+/// Longest salt libxcrypt accepts, in bytes.
+const MAX_SALT_LEN: usize = 64;
+
+const WORM: u32 = 0x001;
+const RW: u32 = 0x002;
+/// `YESCRYPT_ROUNDS_6 | YESCRYPT_GATHER_4 | YESCRYPT_SIMPLE_2 |
+/// YESCRYPT_SBOX_12K`, the pwxform settings of libxcrypt.
+const RW_DEFAULTS: u32 = 0x0b4;
+/// The largest flavor, `YESCRYPT_RW` with every pwxform setting.
+const MAX_FLAVOR: u32 = RW + (0x3fc >> 2);
+
+const PWX_SIMPLE: usize = 2;
+const PWX_GATHER: usize = 4;
+const PWX_ROUNDS: usize = 6;
+const S_WIDTH: usize = 8;
+const PWX_BYTES: usize = PWX_GATHER * PWX_SIMPLE * 8;
+const PWX_WORDS: usize = PWX_BYTES / 4;
+/// Words of each of the S-boxes `S0`, `S1` and `S2`.
+const S_WORDS: usize = (1 << S_WIDTH) * PWX_SIMPLE * 2;
+const S_MASK: u32 = (((1 << S_WIDTH) - 1) * PWX_SIMPLE * 8) as u32;
+
+/// The flavors of yescrypt.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Flavor {
+    /// Classic scrypt, `t` must be 0.
+    Scrypt,
+    /// `YESCRYPT_WORM`: scrypt with the time parameter `t` and the
+    /// pre- and post-hashing of yescrypt.
+    Worm,
+    /// `YESCRYPT_RW` with 6 pwxform rounds, gather 4, simple 2 and 12 KiB
+    /// S-boxes, the flavor of the strings libxcrypt writes.
+    ReadWrite,
+}
+
+impl Flavor {
+    fn flags(self) -> u32 {
+        match self {
+            Flavor::Scrypt => 0,
+            Flavor::Worm => WORM,
+            Flavor::ReadWrite => RW | RW_DEFAULTS,
+        }
+    }
+}
+
+/// The parameters of a yescrypt hash.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct YescryptParams {
+    flavor: Flavor,
+    log_n: u8,
+    r: u32,
+    p: u32,
+    t: u32,
+}
+
+impl YescryptParams {
+    /// Create parameters for `N = 2^log_n`, the block size `r`, the
+    /// parallelization `p` and the time parameter `t`.
+    ///
+    /// # Return
+    /// `Err(InvalidParams)` if `log_n` is 0 or above 63, `r` or `p` is 0,
+    /// `r * p >= 2^30`, `t >= 2^30`, the `128 * r * N` bytes of `V` do not
+    /// fit into memory, `Flavor::ReadWrite` has `N / p < 2` or
+    /// `Flavor::Scrypt` has `t != 0`.
+    pub fn new(flavor: Flavor, log_n: u8, r: u32, p: u32, t: u32)
+        -> Result<YescryptParams, InvalidParams>
+    {
+        let invalid = InvalidParams::new;
+        if log_n == 0 { Err(invalid(ParamsReason::LogNZero))?; }
+        if log_n > 63 { Err(invalid(ParamsReason::LogNTooLarge))?; }
+        if r == 0 { Err(invalid(ParamsReason::RZero))?; }
+        if p == 0 { Err(invalid(ParamsReason::PZero))?; }
+        if r as u64 * p as u64 >= 1 << 30 { Err(invalid(ParamsReason::RpTooLarge))?; }
+        let n = 1u64 << log_n;
+        if t >= 1 << 30 || (flavor == Flavor::ReadWrite && n / p as u64 <= 1)
+            || (flavor == Flavor::Scrypt && t != 0)
+        {
+            Err(invalid(ParamsReason::Other))?;
+        }
+        if n > usize::MAX as u64 / 128 / r as u64 { Err(invalid(ParamsReason::MemoryOverflow))?; }
+        if nloop_all(n, t, flavor).is_none() { Err(invalid(ParamsReason::Other))?; }
+        Ok(YescryptParams { flavor, log_n, r, p, t })
+    }
+
+    /// The flavor.
+    pub fn flavor(&self) -> Flavor { self.flavor }
+
+    /// log2 of `N`.
+    pub fn log_n(&self) -> u8 { self.log_n }
+
+    /// The block size `r`.
+    pub fn r(&self) -> u32 { self.r }
+
+    /// The parallelization `p`.
+    pub fn p(&self) -> u32 { self.p }
+
+    /// The time parameter `t`.
+    pub fn t(&self) -> u32 { self.t }
+}
+
+impl Default for YescryptParams {
+    /// The default of libxcrypt, `$y$j9T$`: `Flavor::ReadWrite`, `N = 4096`,
+    /// `r = 32`, `p = 1` and `t = 0`.
+    fn default() -> YescryptParams {
+        YescryptParams { flavor: Flavor::ReadWrite, log_n: 12, r: 32, p: 1, t: 0 }
+    }
+}
+
+/// The 256-bit yescrypt hash of `password`, as stored in `$y$` strings.
+pub fn yescrypt(password: &[u8], salt: &[u8], params: &YescryptParams) -> [u8; 32] {
+    let n_p = (1u64 << params.log_n) / params.p as u64;
+    if params.flavor == Flavor::ReadWrite && n_p >= 0x100 && n_p * params.r as u64 >= 0x20000 {
+        // large hashes first replace the password with a hash of N / 64
+        let prehash_params = YescryptParams { log_n: params.log_n - 6, t: 0, ..*params };
+        let prehash = kdf(password, salt, &prehash_params, true);
+        return kdf(&prehash, salt, params, false);
+    }
+    kdf(password, salt, params, false)
+}
+
+/// yescrypt without the pre-hashing of large hashes, or the pre-hashing
+/// itself, which lacks the final SHA-256.
+fn kdf(password: &[u8], salt: &[u8], params: &YescryptParams, prehash: bool) -> [u8; 32] {
+    let flags = params.flavor.flags();
+    let (r, p) = (params.r as usize, params.p as usize);
+    let s = 32 * r;
+
+    let mut key = match (flags, prehash) {
+        (0, _) => password.to_vec(),
+        (_, false) => hmac_sha256(b"yescrypt", password).to_vec(),
+        (_, true) => hmac_sha256(b"yescrypt-prehash", password).to_vec(),
+    };
+    let mut bytes = vec![0u8; 128 * r * p];
+    pbkdf2::<Hmac<Sha256>>(&key, salt, 1, &mut bytes);
+    if flags != 0 { key.copy_from_slice(&bytes[..32]); }
+
+    let mut b = vec![0u32; s * p];
+    LittleEndian::read_u32_into(&bytes, &mut b);
+    let mut v = vec![0u32; s << params.log_n];
+    if flags & RW != 0 {
+        let mut ctxs: Vec<Pwxform> = (0..p).map(|_| Pwxform::new()).collect();
+        smix(&mut b, params, p, &mut v, Some(&mut ctxs), &mut key);
+    } else {
+        for block in b.chunks_mut(s) {
+            smix(block, params, 1, &mut v, None, &mut key);
+        }
+    }
+    LittleEndian::write_u32_into(&b, &mut bytes);
+
+    let mut hash = [0u8; 32];
+    pbkdf2::<Hmac<Sha256>>(&key, &bytes, 1, &mut hash);
+    if flags != 0 && !prehash {
+        // the StoredKey of SCRAM (RFC 5802)
+        let client_key = hmac_sha256(&hash, b"Client Key");
+        hash.copy_from_slice(&Sha256::digest(&client_key));
+    }
+    hash
+}
+
+/// Generate a `$y$` setting string for `params` with a random 128-bit salt
+/// drawn from `OsRng`, like libxcrypt's `crypt_gensalt`.
+///
+/// The result can be passed to `crypt()`.
+#[cfg(feature="simple")]
+pub fn gensalt(params: &YescryptParams) -> Result<String, HashError> {
+    let mut rng = os_rng();
+    gensalt_with_rng(params, &mut rng)
+}
+
+/// Same as `gensalt`, but draws the salt from the provided `rng`.
+#[cfg(feature="simple")]
+pub fn gensalt_with_rng<R: RngCore + CryptoRng>(
+    params: &YescryptParams, rng: &mut R,
+) -> Result<String, HashError> {
+    let mut salt = [0u8; 16];
+    rng.try_fill_bytes(&mut salt)?;
+    Ok(setting(params, &salt))
+}
+
+/// Build a `$y$` setting string from `params` and the raw `salt` bytes.
+#[cfg(feature="simple")]
+fn setting(params: &YescryptParams, salt: &[u8]) -> String {
+    let flags = params.flavor.flags();
+    let flavor = if flags < RW { flags } else { RW + (flags >> 2) };
+    let mut setting = String::from(PREFIX);
+    encode_u32(&mut setting, flavor, 0);
+    encode_u32(&mut setting, params.log_n as u32, 1);
+    encode_u32(&mut setting, params.r, 1);
+    let have = (params.p != 1) as u32 | ((params.t != 0) as u32) << 1;
+    if have != 0 { encode_u32(&mut setting, have, 1); }
+    if params.p != 1 { encode_u32(&mut setting, params.p, 2); }
+    if params.t != 0 { encode_u32(&mut setting, params.t, 1); }
+    setting.push('$');
+    crypt7::encode(&mut setting, salt);
+    setting
+}
+
+/// Hash `password` according to the `$y$` `setting`, like `crypt(3)` does.
+///
+/// The salt extends to the last `$` of `setting`, so the hash of a
+/// complete `$y$` string is ignored and the result can be compared with a
+/// stored string.
+///
+/// # Return
+/// The complete `$y$` string, `Err(CheckError::InvalidFormat)` if
+/// `setting` is malformed and `Err(CheckError::UnsupportedFormat)` if it
+/// uses a flavor, hash upgrades or a ROM which are not implemented.
+pub fn crypt(password: &[u8], setting: &str) -> Result<String, CheckError> {
+    let (params, salt_start, salt_end) = decode_setting(setting)?;
+    let salt = crypt7::decode_bytes(&setting[salt_start..salt_end])?;
+    if salt.len() > MAX_SALT_LEN { Err(CheckError::InvalidFormat)?; }
+
+    let mut result = setting[..salt_end].to_string();
+    result.push('$');
+    crypt7::encode(&mut result, &yescrypt(password, &salt, &params));
+    Ok(result)
+}
+
+/// Verify `password` against a `$y$` string.
+///
+/// # Return
+/// `Ok(())` if the password matches, `Err(CheckError::HashMismatch)` if it
+/// does not and the errors of `crypt()` otherwise, a missing hash being
+/// malformed.
+pub fn verify(password: &[u8], hashed_value: &str) -> Result<(), CheckError> {
+    let (_, _, salt_end) = decode_setting(hashed_value)?;
+    let expected = hashed_value.get(salt_end + 1..).unwrap_or("");
+    if crypt7::decode_bytes(expected)?.len() != 32 { Err(CheckError::InvalidFormat)?; }
+
+    let computed = crypt(password, hashed_value)?;
+    if constant_time_eq(&computed.as_bytes()[salt_end + 1..], expected.as_bytes()) {
+        Ok(())
+    } else {
+        Err(CheckError::HashMismatch)
+    }
+}
+
+/// Decode the parameters of a `$y$` setting and find its salt, which
+/// extends from the `$` after the parameters to the last `$` or the end.
+fn decode_setting(setting: &str) -> Result<(YescryptParams, usize, usize), CheckError> {
+    if !setting.starts_with(PREFIX) { Err(CheckError::InvalidFormat)?; }
+    let mut src = &setting.as_bytes()[PREFIX.len()..];
+
+    let flavor = match decode_u32(&mut src, 0)? {
+        0 => Flavor::Scrypt,
+        WORM => Flavor::Worm,
+        f if f == RW + (RW_DEFAULTS >> 2) => Flavor::ReadWrite,
+        f if f <= MAX_FLAVOR => Err(CheckError::UnsupportedFormat)?,
+        _ => Err(CheckError::InvalidFormat)?,
+    };
+    let log_n = decode_u32(&mut src, 1)?;
+    let r = decode_u32(&mut src, 1)?;
+    let (mut p, mut t) = (1, 0);
+    if src.first() != Some(&b'$') {
+        let have = decode_u32(&mut src, 1)?;
+        if have > 0xf { Err(CheckError::InvalidFormat)?; }
+        // hash upgrades and ROMs
+        if have & 0xc != 0 { Err(CheckError::UnsupportedFormat)?; }
+        if have & 1 != 0 { p = decode_u32(&mut src, 2)?; }
+        if have & 2 != 0 { t = decode_u32(&mut src, 1)?; }
+    }
+    if src.first() != Some(&b'$') || log_n > 63 { Err(CheckError::InvalidFormat)?; }
+    let params = YescryptParams::new(flavor, log_n as u8, r, p, t)
+        .map_err(|_| CheckError::InvalidFormat)?;
+
+    let salt_start = setting.len() - src.len() + 1;
+    let salt_end = match setting.rfind('$') {
+        Some(i) if i >= salt_start => i,
+        _ => setting.len(),
+    };
+    Ok((params, salt_start, salt_end))
+}
+
+/// Encode `value >= min` in as few characters as possible: the first
+/// character tells the length, values below `48 + min` take a single one.
+#[cfg(feature="simple")]
+fn encode_u32(dst: &mut String, value: u32, min: u32) {
+    let mut value = value - min;
+    let (mut start, mut end, mut chars, mut bits) = (0u32, 47u32, 1, 0);
+    loop {
+        let count = (end + 1 - start) << bits;
+        if value < count { break; }
+        value -= count;
+        start = end + 1;
+        end = start + (62 - end) / 2;
+        chars += 1;
+        bits += 6;
+    }
+    dst.push(crypt7::ITOA64[(start + (value >> bits)) as usize] as char);
+    for _ in 1..chars {
+        bits -= 6;
+        dst.push(crypt7::ITOA64[(value >> bits & 0x3f) as usize] as char);
+    }
+}
+
+/// Decode a number encoded by `encode_u32` from the start of `src` and
+/// advance `src` past it.
+fn decode_u32(src: &mut &[u8], min: u32) -> Result<u32, CheckError> {
+    let mut next = || -> Result<u32, CheckError> {
+        let (&c, rest) = src.split_first().ok_or(CheckError::InvalidFormat)?;
+        *src = rest;
+        crypt7::decode_char(c)
+    };
+    let c = next()?;
+    let (mut start, mut end, mut chars, mut bits) = (0u32, 47u32, 1, 0);
+    let mut value = min;
+    while c > end {
+        value += (end + 1 - start) << bits;
+        start = end + 1;
+        end = start + (62 - end) / 2;
+        chars += 1;
+        bits += 6;
+    }
+    value += (c - start) << bits;
+    for _ in 1..chars {
+        bits -= 6;
+        value += next()? << bits;
+    }
+    Ok(value)
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_varkey(key)
+        .expect("HMAC accepts keys of any length");
+    mac.input(data);
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&mac.result().code());
+    result
+}
+
+/// The number of BlockMix operations of all SMix2 calls with blocks of
+/// `n` for the time parameter `t`, `None` on overflow.
+fn nloop_all(n: u64, t: u32, flavor: Flavor) -> Option<u64> {
+    let t = t as u64;
+    match (flavor, t) {
+        (Flavor::ReadWrite, 0) => Some(n.div_ceil(3)),
+        (Flavor::ReadWrite, 1) => n.checked_mul(2).map(|n| n.div_ceil(3)),
+        (Flavor::ReadWrite, _) => n.checked_mul(t - 1),
+        (_, 0) => Some(n),
+        (_, 1) => n.checked_add(n.div_ceil(2)),
+        (_, _) => n.checked_mul(t),
+    }
+}
+
+/// The S-boxes and the write position of pwxform for one of the `p` blocks.
+struct Pwxform {
+    s: Vec<u32>,
+    s0: usize,
+    s1: usize,
+    s2: usize,
+    w: usize,
+}
+
+impl Pwxform {
+    fn new() -> Pwxform {
+        Pwxform { s: vec![0; 3 * S_WORDS], s0: 2 * S_WORDS, s1: S_WORDS, s2: 0, w: 0 }
+    }
+
+    /// One pwxform block of `PWX_WORDS`, writing to `S2` in the rounds
+    /// between the first and the last and rotating the S-boxes.
+    fn pwxform(&mut self, x: &mut [u32; PWX_WORDS]) {
+        let mut w = self.w;
+        for i in 0..PWX_ROUNDS {
+            for j in 0..PWX_GATHER {
+                let x_j = j * PWX_SIMPLE * 2;
+                // byte offsets into the S-boxes of 8-byte words
+                let p0 = self.s0 + (x[x_j] & S_MASK) as usize / 4;
+                let p1 = self.s1 + (x[x_j + 1] & S_MASK) as usize / 4;
+                for k in 0..PWX_SIMPLE {
+                    let (lo, hi) = (x_j + 2 * k, x_j + 2 * k + 1);
+                    let s0 = (self.s[p0 + 2 * k + 1] as u64) << 32 | self.s[p0 + 2 * k] as u64;
+                    let s1 = (self.s[p1 + 2 * k + 1] as u64) << 32 | self.s[p1 + 2 * k] as u64;
+                    let value = (x[hi] as u64 * x[lo] as u64).wrapping_add(s0) ^ s1;
+                    x[lo] = value as u32;
+                    x[hi] = (value >> 32) as u32;
+                    if i != 0 && i != PWX_ROUNDS - 1 {
+                        self.s[self.s2 + 2 * w] = value as u32;
+                        self.s[self.s2 + 2 * w + 1] = (value >> 32) as u32;
+                        w += 1;
+                    }
+                }
+            }
+        }
+        let (s0, s1, s2) = (self.s0, self.s1, self.s2);
+        self.s0 = s2;
+        self.s1 = s0;
+        self.s2 = s1;
+        self.w = w & ((1 << S_WIDTH) * PWX_SIMPLE - 1);
+    }
+
+    /// The BlockMix of `YESCRYPT_RW`: pwxform over the blocks of
+    /// `PWX_WORDS`, followed by salsa20/2 of the last 16 words.
+    fn block_mix(&mut self, b: &mut [u32], r: usize) {
+        let r1 = 128 * r / PWX_BYTES;
+        let mut x = [0u32; PWX_WORDS];
+        x.copy_from_slice(&b[(r1 - 1) * PWX_WORDS..]);
+        for block in b.chunks_mut(PWX_WORDS) {
+            if r1 > 1 { xor(&mut x, block); }
+            self.pwxform(&mut x);
+            block.copy_from_slice(&x);
+        }
+        let last = (r1 - 1) * PWX_BYTES / 64;
+        salsa20_shuffled(&mut b[last * 16..(last + 1) * 16], 2);
+    }
+}
+
+fn xor(dst: &mut [u32], src: &[u32]) {
+    for (d, &s) in dst.iter_mut().zip(src.iter()) {
+        *d ^= s;
+    }
+}
+
+/// yescrypt keeps the blocks of SMix in the order of its SIMD code, word
+/// `i` of a block being word `5 * i mod 16` of the block.
+fn shuffle(b: &[u32], x: &mut [u32]) {
+    for (b, x) in b.chunks(16).zip(x.chunks_mut(16)) {
+        for (i, x_i) in x.iter_mut().enumerate() {
+            *x_i = b[i * 5 % 16];
+        }
+    }
+}
+
+fn unshuffle(x: &[u32], b: &mut [u32]) {
+    for (x, b) in x.chunks(16).zip(b.chunks_mut(16)) {
+        for (i, &x_i) in x.iter().enumerate() {
+            b[i * 5 % 16] = x_i;
+        }
+    }
+}
+
+/// The salsa20 core of `rounds` rounds on a shuffled block.
+fn salsa20_shuffled(b: &mut [u32], rounds: usize) {
+    let mut x = [0u32; 16];
+    for (i, &b_i) in b.iter().enumerate() {
+        x[i * 5 % 16] = b_i;
+    }
+    salsa20_words(&mut x, rounds);
+    for (i, b_i) in b.iter_mut().enumerate() {
+        *b_i = x[i * 5 % 16];
+    }
+}
+
+/// The BlockMix of scrypt on shuffled blocks, `y` is scratch space of the
+/// size of `b`.
+fn block_mix_salsa8(b: &mut [u32], y: &mut [u32], r: usize) {
+    let mut x = [0u32; 16];
+    x.copy_from_slice(&b[(2 * r - 1) * 16..]);
+    for (b_i, y_i) in b.chunks(16).zip(y.chunks_mut(16)) {
+        xor(&mut x, b_i);
+        salsa20_shuffled(&mut x, 8);
+        y_i.copy_from_slice(&x);
+    }
+    for (i, y_i) in y.chunks(16).enumerate() {
+        let pos = if i % 2 == 0 { i / 2 } else { i / 2 + r };
+        b[pos * 16..(pos + 1) * 16].copy_from_slice(y_i);
+    }
+}
+
+fn block_mix(x: &mut [u32], y: &mut [u32], r: usize, ctx: Option<&mut Pwxform>) {
+    match ctx {
+        Some(ctx) => ctx.block_mix(x, r),
+        None => block_mix_salsa8(x, y, r),
+    }
+}
+
+/// The last 64 bits of a shuffled block of `2 * r` salsa20 blocks.
+fn integerify(x: &[u32], r: usize) -> u64 {
+    let last = &x[(2 * r - 1) * 16..];
+    (last[13] as u64) << 32 | last[0] as u64
+}
+
+/// The largest power of 2 not above `x`.
+fn p2floor(x: u64) -> u64 {
+    1 << (63 - x.leading_zeros())
+}
+
+/// `x` mapped to the blocks of `V` between the last power of 2 below `i`
+/// and `i`, and the blocks before.
+fn wrap(x: u64, i: u64) -> u64 {
+    let n = p2floor(i);
+    (x & (n - 1)) + (i - n)
+}
+
+/// Fill the `n` blocks of `v` from the block `b`, reading back from `v` if
+/// `rw` is set.
+fn smix1(b: &mut [u32], r: usize, n: usize, rw: bool, v: &mut [u32],
+    mut ctx: Option<&mut Pwxform>)
+{
+    let s = 32 * r;
+    let (mut x, mut y) = (vec![0u32; s], vec![0u32; s]);
+    shuffle(&b[..s], &mut x);
+    for i in 0..n {
+        v[i * s..(i + 1) * s].copy_from_slice(&x);
+        if rw && i > 1 {
+            let j = wrap(integerify(&x, r), i as u64) as usize;
+            xor(&mut x, &v[j * s..(j + 1) * s]);
+        }
+        block_mix(&mut x, &mut y, r, ctx.as_deref_mut());
+    }
+    unshuffle(&x, &mut b[..s]);
+}
+
+/// Mix the block `b` with `nloop` blocks of the first `n` of `v`, writing
+/// them back if `rw` is set.
+fn smix2(b: &mut [u32], r: usize, n: u64, nloop: u64, rw: bool, v: &mut [u32],
+    mut ctx: Option<&mut Pwxform>)
+{
+    if nloop == 0 { return; }
+    let s = 32 * r;
+    let (mut x, mut y) = (vec![0u32; s], vec![0u32; s]);
+    shuffle(&b[..s], &mut x);
+    for _ in 0..nloop {
+        let j = (integerify(&x, r) & (n - 1)) as usize;
+        xor(&mut x, &v[j * s..(j + 1) * s]);
+        if rw { v[j * s..(j + 1) * s].copy_from_slice(&x); }
+        block_mix(&mut x, &mut y, r, ctx.as_deref_mut());
+    }
+    unshuffle(&x, &mut b[..s]);
+}
+
+/// SMix of `p` blocks of `b`, which share `v`. `ctxs` are the pwxform
+/// states of `YESCRYPT_RW`, which also updates `passwd`.
+fn smix(b: &mut [u32], params: &YescryptParams, p: usize, v: &mut [u32],
+    mut ctxs: Option<&mut [Pwxform]>, passwd: &mut [u8])
+{
+    let r = params.r as usize;
+    let s = 32 * r;
+    let n = 1u64 << params.log_n;
+    let mut n_chunk = n / p as u64;
+    let mut nloop_all = nloop_all(n_chunk, params.t, params.flavor)
+        .expect("checked by YescryptParams::new");
+    let mut nloop_rw = if ctxs.is_some() { nloop_all / p as u64 } else { 0 };
+    // rounded to even
+    n_chunk &= !1;
+    nloop_all = (nloop_all + 1) & !1;
+    nloop_rw = (nloop_rw + 1) & !1;
+
+    for (i, b_i) in b.chunks_mut(s).enumerate() {
+        let v_start = i as u64 * n_chunk;
+        let n_i = if i < p - 1 { n_chunk } else { n - v_start };
+        let v_i = &mut v[v_start as usize * s..(v_start + n_i) as usize * s];
+        match ctxs {
+            Some(ref mut ctxs) => {
+                let ctx = &mut ctxs[i];
+                // the S-boxes are the V of classic scrypt with r = 1
+                smix1(b_i, 1, 3 * S_WORDS / 32, false, &mut ctx.s, None);
+                if i == 0 {
+                    let mut key = [0u8; 64];
+                    LittleEndian::write_u32_into(&b_i[s - 16..], &mut key);
+                    let mac = hmac_sha256(&key, passwd);
+                    passwd.copy_from_slice(&mac);
+                }
+                smix1(b_i, r, n_i as usize, true, v_i, Some(ctx));
+                smix2(b_i, r, p2floor(n_i), nloop_rw, true, v_i, Some(ctx));
+            }
+            None => smix1(b_i, r, n_i as usize, false, v_i, None),
+        }
+    }
+
+    for (i, b_i) in b.chunks_mut(s).enumerate() {
+        let ctx = ctxs.as_mut().map(|ctxs| &mut ctxs[i]);
+        smix2(b_i, r, n, nloop_all - nloop_rw, false, v, ctx);
+    }
+}
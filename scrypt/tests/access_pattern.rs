@@ -0,0 +1,76 @@
+#![cfg(feature="research")]
+//! The indices `j` of the second loop of ROMix, which the known answers
+//! only see through the derived key: a wrong integerify, e.g. of the wrong
+//! word or with the wrong byte order, still gives random looking keys but
+//! reads few rows of `V`.
+extern crate scrypt;
+
+use scrypt::research::{scrypt_instrumented, scrypt_instrumented_on, RomixEvent};
+use scrypt::{Backend, Params};
+
+const LOG_N: u8 = 12;
+const BINS: usize = 64;
+
+fn params() -> Params {
+    Params::new(LOG_N, 2, 2).unwrap()
+}
+
+fn events_on(backend: Backend, password: &[u8]) -> (Vec<RomixEvent>, [u8; 32]) {
+    let mut events = Vec::new();
+    let mut output = [0u8; 32];
+    scrypt_instrumented_on(backend, password, b"salt", &params(), &mut output,
+        &mut |event| events.push(event)).unwrap();
+    (events, output)
+}
+
+fn indices(password: &[u8]) -> Vec<usize> {
+    let mut indices = Vec::new();
+    scrypt_instrumented(password, b"salt", &params(), &mut [0u8; 32],
+        &mut |event| indices.push(event.j)).unwrap();
+    indices
+}
+
+#[test]
+fn test_uniform() {
+    let n = 1usize << LOG_N;
+    for password in [&b"password"[..], b"", b"correct horse battery staple"].iter() {
+        let indices = indices(password);
+        for lane in indices.chunks(n) {
+            let mut counts = [0usize; BINS];
+            for &j in lane { counts[j * BINS / n] += 1; }
+            let expected = (n / BINS) as f64;
+            let chi2: f64 = counts.iter()
+                .map(|&c| (c as f64 - expected).powi(2) / expected)
+                .sum();
+            // 63 degrees of freedom, mean 63, above 120 has a probability
+            // of about 1e-5 for uniform indices
+            assert!(chi2 < 120.0, "chi-squared {} for {:?}", chi2, password);
+        }
+    }
+}
+
+#[test]
+fn test_distinct_rows() {
+    let n = 1usize << LOG_N;
+    for lane in indices(b"password").chunks(n) {
+        let mut seen = vec![false; n];
+        for &j in lane { seen[j] = true; }
+        // `N` uniform draws hit `1 - 1/e`, 63 %, of the `N` rows
+        let distinct = seen.iter().filter(|&&s| s).count();
+        assert!(distinct * 100 > n * 60, "{} of {} rows", distinct, n);
+    }
+}
+
+#[test]
+fn test_backends_agree() {
+    let (expected, key) = events_on(Backend::Portable, b"password");
+    assert_eq!(expected.len(), 2 << LOG_N);
+    for &backend in Backend::compiled() {
+        let (events, output) = events_on(backend, b"password");
+        assert_eq!(events, expected, "{}", backend);
+        assert_eq!(output, key, "{}", backend);
+    }
+    let mut plain = [0u8; 32];
+    scrypt::scrypt(b"password", b"salt", &params(), &mut plain).unwrap();
+    assert_eq!(key, plain);
+}
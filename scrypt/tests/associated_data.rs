@@ -0,0 +1,152 @@
+#![cfg(feature="simple")]
+extern crate base64ct;
+extern crate rand;
+extern crate scrypt;
+
+use std::num::NonZeroU32;
+
+use base64ct::{Base64Unpadded, Encoding};
+use rand::{CryptoRng, RngCore};
+
+use scrypt::{scrypt_check, scrypt_simple, scrypt_simple_ad, scrypt_simple_peppered,
+    scrypt_simple_v2, verify_any, ClientScheme, LengthPolicy, Pepper, PepperSet, ScryptHash,
+    ScryptHashRef, Params, SimpleConfig, Verifier};
+use scrypt::convert::rscrypt_to_phc;
+use scrypt::errors::CheckError;
+
+// Generated with Python's `hmac` and `hashlib.scrypt`: scrypt of the salt
+// `HMAC-SHA256(salt, ad)`, the stored salts are `00 01 .. 0f` and the one of
+// the PHC fixtures.
+const FIXTURES: &[(&str, &str, u8, u32, u32, &str)] = &[
+    (
+        "password", "tenant-a", 4, 8, 1,
+        "$rscrypt$2$BAgAAAABAAAAECAC$AAECAwQFBgcICQoLDA0ODw$\
+         ayODfgC5zLRj7ix5T1whw4Y3ExN3sZaaPd4Chk3qZA4",
+    ),
+    (
+        "password", "", 4, 8, 1,
+        "$rscrypt$2$BAgAAAABAAAAECAC$AAECAwQFBgcICQoLDA0ODw$\
+         mJDTbTwxlE76HDLDvYexAwTvkOo7Jd5gfnHVriZHn5g",
+    ),
+    (
+        "correct horse battery staple", "example.com", 6, 300, 2,
+        "$rscrypt$2$BiwBAAACAAAAECAC$ABEiM0RVZneImaq7zN3u/w$\
+         pqf4dFddfZmI1hz1xp3hx0hQqmcKDRCCbduyvmumJjA",
+    ),
+];
+
+fn verify_ad(password: &str, hashed: &str, ad: &[u8]) -> Result<(), CheckError> {
+    Verifier::builder().build().verify_with_ad(password, hashed, ad)
+}
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_ad_fixtures() {
+    let salts = [(0..16).collect(), (0..16).collect(),
+        vec![0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+             0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff]];
+    for (&(password, ad, log_n, r, p, hashed), salt) in FIXTURES.iter().zip(salts.iter()) {
+        let params = Params::new(log_n, r, p).unwrap();
+        let ad = ad.as_bytes();
+        let mut rng = FixedRng(salt.clone());
+        let config = SimpleConfig::builder().params(params).associated_data(true).build()
+            .unwrap();
+        assert_eq!(config.hash_with_ad_rng(password, ad, &mut rng).unwrap(), hashed);
+
+        assert_eq!(verify_ad(password, hashed, ad), Ok(()));
+        assert_eq!(verify_ad("wrong", hashed, ad), Err(CheckError::HashMismatch));
+        // the associated data of another context
+        assert_eq!(verify_ad(password, hashed, b"tenant-b"), Err(CheckError::HashMismatch));
+        let mut longer = ad.to_vec();
+        longer.push(0);
+        assert_eq!(verify_ad(password, hashed, &longer), Err(CheckError::HashMismatch));
+    }
+}
+
+#[test]
+fn test_ad_needs_ad() {
+    let hashed = FIXTURES[0].5;
+    assert_eq!(scrypt_check("password", hashed), Err(CheckError::NeedsAssociatedData));
+    assert_eq!(verify_any("password", hashed), Err(CheckError::NeedsAssociatedData));
+    assert_eq!(ScryptHash::parse(hashed), Err(CheckError::NeedsAssociatedData));
+    assert_eq!(ScryptHashRef::parse(hashed), Err(CheckError::NeedsAssociatedData));
+    assert_eq!(rscrypt_to_phc(hashed), Err(CheckError::NeedsAssociatedData));
+    assert_eq!(Verifier::builder().build().verify_with_keys("password", hashed, &PepperSet::new()),
+        Err(CheckError::NeedsAssociatedData));
+}
+
+#[test]
+fn test_ad_rejects_unbound() {
+    // hashes without the binding could be copied into any context
+    let params = Params::new(4, 8, 1).unwrap();
+    for hashed in [
+        scrypt_simple("password", &params).unwrap(),
+        scrypt_simple_v2("password", &params).unwrap(),
+    ].iter() {
+        assert_eq!(scrypt_check("password", hashed), Ok(()));
+        assert_eq!(verify_ad("password", hashed, b""), Err(CheckError::HashMismatch));
+    }
+
+    let hashed = scrypt_simple_peppered("password", &params, &Pepper { id: 7, key: b"key" })
+        .unwrap();
+    assert_eq!(verify_ad("password", &hashed, b""), Err(CheckError::NeedsPepper(7)));
+    let phc = "$scrypt$ln=4,r=8,p=1$AAECAwQFBgcICQoLDA0ODw$\
+               ayODfgC5zLRj7ix5T1whw4Y3ExN3sZaaPd4Chk3qZA4";
+    assert_eq!(verify_ad("password", phc, b"tenant-a"), Err(CheckError::InvalidFormat));
+}
+
+#[test]
+fn test_scrypt_simple_ad() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashed = scrypt_simple_ad("password", &params, b"tenant-a").unwrap();
+    assert!(hashed.starts_with("$rscrypt$2$BAgAAAABAAAAECAC$"), "{}", hashed);
+    assert_ne!(scrypt_simple_ad("password", &params, b"tenant-a").unwrap(), hashed);
+    assert_eq!(verify_ad("password", &hashed, b"tenant-a"), Ok(()));
+    assert_eq!(verify_ad("password", &hashed, b"tenant-b"), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_ad_extensions() {
+    // associated data is only combined with the bound header, which is part
+    // of the salt
+    let params = Params::new(4, 8, 1).unwrap();
+    let config = SimpleConfig::builder().params(params).associated_data(true).bound(true)
+        .build().unwrap();
+    let hashed = config.hash_with_ad("password", b"tenant-a").unwrap();
+    let strict = Verifier::builder().strict_encoding(true).build();
+    assert_eq!(strict.verify_with_ad("password", &hashed, b"tenant-a"), Ok(()));
+    assert_eq!(verify_ad("wrong", &hashed, b"tenant-a"), Err(CheckError::HashMismatch));
+    assert_eq!(verify_ad("password", &hashed, b"tenant-b"), Err(CheckError::HashMismatch));
+
+    let mut builders = vec![SimpleConfig::builder(); 3];
+    builders[0].prehash(LengthPolicy { prehash_above: 16, max_len: 64 });
+    builders[1].final_rounds(NonZeroU32::new(10).unwrap());
+    builders[2].client_scheme(ClientScheme(1));
+    for builder in builders.iter_mut() {
+        let hashed = builder.params(params).build().unwrap().hash_with("password").unwrap();
+        let fields: Vec<&str> = hashed.split('$').collect();
+        let mut header = Base64Unpadded::decode_vec(fields[3]).unwrap();
+        header[11] |= 0x02;
+        let hashed = format!("$rscrypt$2${}${}${}",
+            Base64Unpadded::encode_string(&header), fields[4], fields[5]);
+        assert_eq!(verify_ad("password", &hashed, b""), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+}
@@ -0,0 +1,90 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use std::sync::{Arc, Mutex};
+
+use rand::SeedableRng;
+use rand::prng::ChaChaRng;
+
+use scrypt::errors::{BackendError, CheckError, ErrorCode, HashError};
+use scrypt::{scrypt_check, Format, HashBackend, LocalBackend, Params, ScryptHash,
+    SimpleConfig, Verifier};
+
+/// Records the salts, parameters and output lengths of its calls and defers
+/// to `LocalBackend`.
+#[derive(Default)]
+struct MockBackend {
+    calls: Mutex<Vec<(Vec<u8>, Params, usize)>>,
+}
+
+impl HashBackend for MockBackend {
+    fn derive(&self, password: &[u8], salt: &[u8], params: &Params, output: &mut [u8])
+        -> Result<(), BackendError>
+    {
+        self.calls.lock().unwrap().push((salt.to_vec(), *params, output.len()));
+        LocalBackend.derive(password, salt, params, output)
+    }
+}
+
+struct DownBackend;
+
+impl HashBackend for DownBackend {
+    fn derive(&self, _: &[u8], _: &[u8], _: &Params, _: &mut [u8]) -> Result<(), BackendError> {
+        Err(BackendError::Unavailable)
+    }
+}
+
+#[test]
+fn test_hash_same_as_local() {
+    let mock = MockBackend::default();
+    let params = Params::new(4, 8, 1).unwrap();
+    for &format in [Format::Rscrypt, Format::Phc, Format::Django, Format::Crypt7].iter() {
+        let config = SimpleConfig::builder().params(params).format(format).build().unwrap();
+        let local = config.hash_with_rng("password", &mut ChaChaRng::from_seed([1; 32]))
+            .unwrap();
+        let delegated = config.hash_with_backend(
+            "password", &mut ChaChaRng::from_seed([1; 32]), &mock,
+        ).unwrap();
+        assert_eq!(delegated, local);
+        assert_eq!(scrypt_check("password", &delegated), Ok(()));
+
+        let calls = mock.calls.lock().unwrap().drain(..).collect::<Vec<_>>();
+        let hash = ScryptHash::parse(&local).unwrap();
+        assert_eq!(calls, vec![(hash.salt().to_vec(), params, config.dk_len())]);
+    }
+}
+
+#[test]
+fn test_verifier_backend() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let config = SimpleConfig::builder().params(params).build().unwrap();
+    let stored = config.hash_with_rng("password", &mut ChaChaRng::from_seed([2; 32])).unwrap();
+
+    let mock = Arc::new(MockBackend::default());
+    let verifier = Verifier::builder().backend(mock.clone()).build();
+    assert_eq!(verifier.verify("password", &stored), Ok(()));
+    assert_eq!(verifier.verify("wrong", &stored), Err(CheckError::HashMismatch));
+    assert_eq!(mock.calls.lock().unwrap().len(), 2);
+
+    // parsing stays local, malformed hashes never reach the backend
+    assert_eq!(verifier.verify("password", "$rscrypt$"), Err(CheckError::InvalidFormat));
+    assert_eq!(mock.calls.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn test_backend_errors() {
+    let config = SimpleConfig::builder().params(Params::new(4, 8, 1).unwrap()).build().unwrap();
+    let stored = config.hash_with_rng("password", &mut ChaChaRng::from_seed([3; 32])).unwrap();
+
+    let verifier = Verifier::builder().backend(Arc::new(DownBackend)).build();
+    let err = verifier.verify("password", &stored).unwrap_err();
+    assert_eq!(err, CheckError::Backend(BackendError::Unavailable));
+    assert_eq!(err.code(), ErrorCode::Backend);
+    assert_eq!(err.to_string(), "hash backend failed: hash backend unavailable");
+
+    match config.hash_with_backend("password", &mut ChaChaRng::from_seed([3; 32]), &DownBackend) {
+        Err(HashError::Backend(BackendError::Unavailable)) => (),
+        res => panic!("{:?}", res),
+    }
+}
@@ -0,0 +1,140 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use std::cell::Cell;
+
+use scrypt::{scrypt_simple, scrypt_simple_django, scrypt_simple_phc, verify_candidates,
+    verify_many, BatchOptions, Params, VerifyLimits};
+use scrypt::errors::CheckError;
+
+fn items() -> Vec<(String, String)> {
+    let cheap = Params::new(4, 8, 1).unwrap();
+    let costly = Params::new(8, 8, 2).unwrap();
+    let mut items = Vec::new();
+    for i in 0..12 {
+        let password = format!("password {}", i);
+        let hashed = match i % 3 {
+            0 => scrypt_simple(&password, &cheap),
+            1 => scrypt_simple_phc(&password, &costly),
+            _ => scrypt_simple_django(&password, &cheap),
+        }.unwrap();
+        items.push((password, hashed));
+    }
+    items
+}
+
+fn expected(i: usize) -> Result<(), CheckError> {
+    match i % 4 {
+        0 | 3 => Ok(()),
+        1 => Err(CheckError::HashMismatch),
+        _ => Err(CheckError::InvalidFormat),
+    }
+}
+
+/// `items()` with every second password wrong and every fourth hash
+/// malformed.
+fn mixed() -> Vec<(String, String)> {
+    items().into_iter().enumerate().map(|(i, (password, hashed))| match i % 4 {
+        1 => ("wrong".to_string(), hashed),
+        2 => (password, hashed.replacen('$', "#", 1)),
+        _ => (password, hashed),
+    }).collect()
+}
+
+#[test]
+fn test_verify_many() {
+    let mixed = mixed();
+    let items: Vec<(&[u8], &str)> = mixed.iter()
+        .map(|(password, hashed)| (password.as_bytes(), &hashed[..]))
+        .collect();
+    let results = verify_many(&items, &BatchOptions { threads: 4, limits: None });
+    assert_eq!(results.len(), items.len());
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(*result, expected(i), "{} {}", i, items[i].1);
+    }
+}
+
+#[test]
+fn test_verify_many_threads() {
+    let mixed = mixed();
+    let items: Vec<(&str, &str)> = mixed.iter()
+        .map(|(password, hashed)| (&password[..], &hashed[..]))
+        .collect();
+    let single = verify_many(&items, &BatchOptions { threads: 1, limits: None });
+    for &threads in [0, 2, 3, 7, 100].iter() {
+        let options = BatchOptions { threads, limits: None };
+        assert_eq!(verify_many(&items, &options), single, "{}", threads);
+    }
+    assert_eq!(verify_many(&items[..0], &BatchOptions::default()), vec![]);
+    assert_eq!(verify_many(&items[..1], &BatchOptions::default()), vec![Ok(())]);
+}
+
+#[test]
+fn test_verify_many_limits() {
+    let items = items();
+    let items: Vec<(Vec<u8>, &str)> = items.iter()
+        .map(|(password, hashed)| (password.clone().into_bytes(), &hashed[..]))
+        .collect();
+    // `log_n = 4, r = 8` fits, `log_n = 8` does not
+    let limits = VerifyLimits { max_memory: 1 << 20, max_ops: 1 << 10 };
+    let results = verify_many(&items, &BatchOptions { threads: 2, limits: Some(limits) });
+    for (i, result) in results.iter().enumerate() {
+        let expected = if i % 3 == 1 { Err(CheckError::CostExceedsLimit) } else { Ok(()) };
+        assert_eq!(*result, expected, "{}", items[i].1);
+    }
+}
+
+/// A candidate counting how often its password is read.
+struct Counted<'a> {
+    password: &'a str,
+    reads: &'a Cell<usize>,
+}
+
+impl<'a> AsRef<[u8]> for Counted<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.reads.set(self.reads.get() + 1);
+        self.password.as_bytes()
+    }
+}
+
+#[test]
+fn test_verify_candidates() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let stored = [
+        scrypt_simple("current", &params).unwrap(),
+        scrypt_simple_phc("current", &params).unwrap(),
+        scrypt_simple_django("current", &params).unwrap(),
+    ];
+    for stored in stored.iter() {
+        for i in 0..3 {
+            let mut candidates = vec!["old", "older", "oldest"];
+            candidates[i] = "current";
+            assert_eq!(verify_candidates(&candidates, stored), Ok(Some(i)));
+        }
+        assert_eq!(verify_candidates(&["old", "older", "oldest"], stored), Ok(None));
+        assert_eq!(verify_candidates(&["current", "current"], stored), Ok(Some(0)));
+        assert_eq!(verify_candidates::<&str>(&[], stored), Ok(None));
+        assert_eq!(verify_candidates(&[b"current".to_vec()], stored), Ok(Some(0)));
+    }
+
+    let malformed = stored[0].replacen('$', "#", 1);
+    assert_eq!(verify_candidates(&["current"], &malformed), Err(CheckError::InvalidFormat));
+    assert_eq!(verify_candidates::<&str>(&[], &malformed), Err(CheckError::InvalidFormat));
+}
+
+#[test]
+fn test_verify_candidates_constant_work() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let stored = scrypt_simple("current", &params).unwrap();
+    // every candidate is hashed once, whether and where one matches
+    for matching in 0..4 {
+        let reads: Vec<Cell<usize>> = (0..3).map(|_| Cell::new(0)).collect();
+        let candidates: Vec<Counted> = reads.iter().enumerate().map(|(i, reads)| Counted {
+            password: if i == matching { "current" } else { "wrong" },
+            reads,
+        }).collect();
+        let expected = if matching < 3 { Some(matching) } else { None };
+        assert_eq!(verify_candidates(&candidates, &stored), Ok(expected));
+        assert!(reads.iter().all(|reads| reads.get() == 1), "{}", matching);
+    }
+}
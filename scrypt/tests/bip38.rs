@@ -0,0 +1,103 @@
+#![cfg(feature="bip38")]
+extern crate scrypt;
+
+use scrypt::bip38::{derive_bip38_ec_key, derive_bip38_key, derive_bip38_passfactor};
+
+/// The vectors of BIP-38 without EC multiplication: passphrase, address
+/// hash of the encrypted key and `derivedhalf1 || derivedhalf2`. The
+/// derived keys decrypt the encrypted keys of the BIP to its private keys.
+const KEYS: &[(&str, &str, &str)] = &[
+    // 6PRVWUbkzzsbcVac2qwfssoUJAN1Xhrg6bNk8J7Nzm5H7kxEbn2Nh2ZoGg
+    ("TestingOneTwoThree", "e957a24a",
+     "f87648a6b42fdd86ef6837a249cde15318f264d43a859b610e78ea63d51cb2d3\
+      e60bf44bfb29d543bba24afcccfadbfc6ef9312fcccf589fa5ea1366ec21e4c0"),
+    // 6PRNFFkZc2NZ6dJqFfhRoFNMR9Lnyj7dYGrzdgXXVMXcxoKTePPX1dWByq
+    ("Satoshi", "572e117e",
+     "02d4a6b94240bd1cdaa6773f430e43a0d9a8cbc9a83b044998f7ef2e3f31a4de\
+      7f2436fede417c46b988879f4ef0595b75a55bcaec27848ef94e9f4b4d684cb9"),
+    // 6PRW5o9FLp4gJDDVqJQKJFTpMvdsSGJxMYHtHaQBF3ooa8mwD69bapcDQn, the NFC
+    // form of "\u{3d2}\u{301}\u{0}\u{10400}\u{1f4a9}"
+    ("\u{3d3}\u{0}\u{10400}\u{1f4a9}", "f4e775a8",
+     "981726c732b25e1eede74a32ba72fd113144c52d2eadc0f4bb12ec9ccb2e05cf\
+      c2579a6c3280d21cee2e2e6b4bf23d3b8cf2a39574b942e6f9f4381659db4c6f"),
+    // 6PYNKZ1EAgYgmQfmNVamxyXVWHzK5s6DGhwP4J5o44cvXdoY7sRzhtpUeo, compressed
+    ("TestingOneTwoThree", "43be4179",
+     "731ef3c737b55df4998b44fa8a547a3f38df424da240de389b11d1875ba47767\
+      2f2fe81b0532b5950e3ea6fff92c65d467aa7d054969821de2344f7a86d42569"),
+    // 6PYLtMnXvfG3oJde97zRyLYFZCYizPU5T3LwgdYJz1fRhh16bU7u6PPmY7, compressed
+    ("Satoshi", "26e017d2",
+     "0478e3e18d96ae2fbe033e3261944670c0ead16336890e4af46f55851ae211d2\
+      2c97d288383bfd14983e5c574dafeb66f31b16bad037d40a6467019840ffa323"),
+];
+
+/// The vectors of BIP-38 with EC multiplication: passphrase, lot and
+/// sequence flag, `ownerentropy`, `passfactor`, `passpoint`, address hash
+/// and `derivedhalf1 || derivedhalf2`.
+const EC_KEYS: &[(&str, bool, &str, &str, &str, &str, &str)] = &[
+    // 6PfQu77ygVyJLZjfvMLyhLMQbYnu5uguoJJ4kMCLqWwPEdfpwANVS76gTX
+    ("TestingOneTwoThree", false, "a50dba6772cb9383",
+     "c8ff7a1c8c8898a0361e477fa8f0f05c00d07c5d9626f00b03c0140a307c98f4",
+     "020eac136e97ce6bf3e2bceb65d906742f7317b6518c54c64353c43dcc36688c47",
+     "62b5b722",
+     "da2d320e2ca088575369601e94dd71f210fc69c047a3d0f48bdbaab595916dc7\
+      b8d083ea2678b5a71558c0fb0efa58b565227d05adf0c25fa0b9a74755477827"),
+    // 6PfLGnQs6VZnrNpmVKfjotbnQuaJK4KZoPFrAjx1JMJUa1Ft8gnf5WxfKd
+    ("Satoshi", false, "67010a9573418906",
+     "e8a9722cf7988c31f929bd656085ca6470595e068bae22858ea7d84fb4197a99",
+     "022413a674b5bceab5abe0b14ce44dfa7fc6b55ecdbed88e7c50c0b4e953f1e05e",
+     "059a5481",
+     "dc7d942ea3c6c8953b30ee010c147a3222f6f5c52923e28185832f64d86781bc\
+      5120c42e25509460892ac9fec45e1bc52613238e1b5c1ead9d41bdeea8892c5c"),
+    // 6PgNBNNzDkKdhkT6uJntUXwwzQV8Rr2tZcbkDcuC9DZRsS6AtHts4Ypo1j, lot
+    // 263183, sequence 1
+    ("MOLON LABE", true, "4fca5a974040f001",
+     "c3980b33a727ab5f28086b51c7ec4bb1baa6307fb84380cac91939ea29f9b64b",
+     "02a6bf1824208903aa344833d614f7fa3ba46f4f8d57b2e219a1cfac961a9b7395",
+     "bb458cef",
+     "a8bc4ad35fb69cc37f129abb458245e4523c97133b22a5cad88035f99d0b1d50\
+      ffc8317a1eaea330e1e17305539ec5c5ce36168a35d6d13fefa21d5e2cb1c1e9"),
+    // 6PgGWtx25kUg8QWvwuJAgorN6k9FbE25rv5dMRwu5SKMnfpfVe5mar2ngH, lot
+    // 806938, sequence 1
+    ("\u{39c}\u{39f}\u{39b}\u{3a9}\u{39d} \u{39b}\u{391}\u{392}\u{395}", true, "c40ea76fc501a001",
+     "6d8b90461d423baddbea4fe1cec5a277069a45a33720702e2253fd7894493642",
+     "030a7a6f6536951f1cdf450e9ef6c1f615b904af58f7c17598cec3274e6769d3ef",
+     "494af136",
+     "1471d24b21c21e164f48237e9a5f0926493bf6118373a0d2e18387a7c345646d\
+      6889d2c30be9721874f10844fb98794de1caba62bb659a51492d4f33ca3237d7"),
+];
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn array<T: Default + AsMut<[u8]>>(s: &str) -> T {
+    let mut array = T::default();
+    array.as_mut().copy_from_slice(&hex(s));
+    array
+}
+
+#[test]
+fn test_bip38_keys() {
+    for &(passphrase, address_hash, derived) in KEYS {
+        let key = derive_bip38_key(passphrase, &array(address_hash));
+        assert_eq!(key.to_vec(), hex(derived), "{:?}", passphrase);
+    }
+}
+
+#[test]
+fn test_bip38_ec_keys() {
+    for &(passphrase, lot_sequence, entropy, passfactor, passpoint, address_hash, derived)
+        in EC_KEYS
+    {
+        let entropy = array(entropy);
+        let factor = derive_bip38_passfactor(passphrase, &entropy, lot_sequence);
+        assert_eq!(factor.to_vec(), hex(passfactor), "{:?}", passphrase);
+
+        let mut point = [0u8; 33];
+        point.copy_from_slice(&hex(passpoint));
+        let key = derive_bip38_ec_key(&point, &array(address_hash), &entropy);
+        assert_eq!(key.to_vec(), hex(derived), "{:?}", passphrase);
+    }
+}
@@ -0,0 +1,65 @@
+extern crate scrypt;
+
+use scrypt::{blind_index, derive_salt, scrypt, Params, BLIND_INDEX_TAG};
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn params() -> Params {
+    Params::new(4, 8, 1).unwrap()
+}
+
+#[test]
+fn test_vectors() {
+    // computed with Python's hmac and hashlib.scrypt
+    let vectors: &[(&[u8], &[u8], &str)] = &[
+        (b"server key", b"alice@example.com",
+            "f807303f7100689b46ff15492541bd987657dee017922132dc7ffadd42ababb2"),
+        (b"server key", b"bob@example.com",
+            "b57904db5185577dd023a0f9d119c77006ebd6f7871de1449dc680be58fb2678"),
+        (b"other key", b"alice@example.com",
+            "1dae92e7f6c29f1f6a69c903f37d812dc82f8750cef2b6e852f0463c6d58ef54"),
+    ];
+    for &(key, value, index) in vectors {
+        assert_eq!(hex(&blind_index(key, value, &params(), 32).unwrap()), index);
+    }
+    // truncated indexes are prefixes
+    assert_eq!(hex(&blind_index(b"server key", b"alice@example.com", &params(), 8).unwrap()),
+        "f807303f7100689b");
+
+    let mut expected = [0u8; 32];
+    scrypt(b"alice@example.com", &derive_salt(b"server key", BLIND_INDEX_TAG), &params(),
+        &mut expected).unwrap();
+    assert_eq!(blind_index(b"server key", b"alice@example.com", &params(), 32).unwrap(),
+        expected);
+}
+
+#[test]
+fn test_deterministic() {
+    let index = blind_index(b"server key", b"alice@example.com", &params(), 32).unwrap();
+    assert_eq!(blind_index(b"server key", b"alice@example.com", &params(), 32).unwrap(), index);
+}
+
+#[test]
+fn test_avalanche() {
+    // a changed key or value flips about half of the 256 bits
+    let distance = |a: &[u8], b: &[u8]| -> u32 {
+        a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+    };
+    let index = blind_index(b"server key", b"alice@example.com", &params(), 32).unwrap();
+    let others = [
+        blind_index(b"server kez", b"alice@example.com", &params(), 32).unwrap(),
+        blind_index(b"server key", b"alice@example.con", &params(), 32).unwrap(),
+        blind_index(b"", b"alice@example.com", &params(), 32).unwrap(),
+        blind_index(b"server key", b"", &params(), 32).unwrap(),
+    ];
+    for other in others.iter() {
+        assert!((80..=176).contains(&distance(&index, other)), "{}", hex(other));
+    }
+}
+
+#[test]
+fn test_invalid_len() {
+    assert_eq!(blind_index(b"key", b"value", &params(), 0).unwrap_err().provided_len(), 0);
+}
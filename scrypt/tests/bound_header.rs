@@ -0,0 +1,156 @@
+#![cfg(feature="simple")]
+extern crate base64ct;
+extern crate rand;
+extern crate scrypt;
+
+use std::num::NonZeroU32;
+
+use base64ct::{Base64Unpadded, Encoding};
+use rand::SeedableRng;
+use rand::prng::ChaChaRng;
+
+use scrypt::errors::CheckError;
+use scrypt::{needs_rehash, scrypt_check, scrypt_check_and_rehash_peppered_with_rng,
+    scrypt_check_and_rehash_with_rng, scrypt_simple_bound,
+    verify_many, BatchOptions, PepperSet, Params, ScryptHash, ScryptHashRef, SimpleConfig,
+    Verifier};
+
+/// "password" under "NaCl", `log_n = 4`, `r = 1` and `p = 2`, generated with
+/// `hashlib.scrypt` under the salt `SHA-256(header || "NaCl")`.
+const HASHED: &str =
+    "$rscrypt$2$BAEAAAACAAAABCAg$TmFDbA$mMTmTXjxli3HQP3jWmhM2rytf4xc+Ohzd+jNDik/vl8";
+/// `HASHED` with `r = 2`.
+const EDITED: &str =
+    "$rscrypt$2$BAIAAAACAAAABCAg$TmFDbA$mMTmTXjxli3HQP3jWmhM2rytf4xc+Ohzd+jNDik/vl8";
+/// `HASHED` without the flag.
+const STRIPPED: &str =
+    "$rscrypt$2$BAEAAAACAAAABCAA$TmFDbA$mMTmTXjxli3HQP3jWmhM2rytf4xc+Ohzd+jNDik/vl8";
+/// `HASHED` peppered with `KEY` of id 7, generated with Python's `hmac` and
+/// `hashlib.scrypt`, the pepper id is part of the bound header.
+const PEPPERED: &str =
+    "$rscrypt$2$BAEAAAACAAAABCAhBw$TmFDbA$eYh/n5oVvxeQeko0+rb/EFTqMspJp9CHFR22Sqz/Acw";
+/// `HASHED` with 10 final PBKDF2 iterations, generated with a Python
+/// transcription of RFC 7914, the count is part of the bound header.
+const ROUNDS: &str =
+    "$rscrypt$2$BAEAAAACAAAABCAwCgAAAA$TmFDbA$oeSWnPETj5Nv6OX719f1e+axS54T4aAZo9t2sdm5quo";
+
+const KEY: &[u8] = b"pepper key 1";
+
+fn params() -> Params {
+    Params::new(4, 1, 2).unwrap()
+}
+
+/// `KEY` under the ids 7 and 8.
+fn keys() -> PepperSet {
+    let mut keys = PepperSet::new();
+    keys.insert(7, KEY);
+    keys.insert(8, KEY);
+    keys
+}
+
+/// The decoded header of `hashed`.
+fn header(hashed: &str) -> Vec<u8> {
+    Base64Unpadded::decode_vec(hashed.split('$').nth(3).unwrap()).unwrap()
+}
+
+/// `hashed` with the header edited by `edit`.
+fn edit_header<F: Fn(&mut Vec<u8>)>(hashed: &str, edit: F) -> String {
+    let fields: Vec<&str> = hashed.split('$').collect();
+    let mut header = header(hashed);
+    edit(&mut header);
+    format!("$rscrypt$2${}${}${}", Base64Unpadded::encode_string(&header), fields[4], fields[5])
+}
+
+#[test]
+fn test_check() {
+    assert_eq!(scrypt_check("password", HASHED), Ok(()));
+    assert_eq!(scrypt_check("wrong", HASHED), Err(CheckError::HashMismatch));
+    assert_eq!(Verifier::builder().build().verify("password", HASHED), Ok(()));
+    let items = [("password", HASHED), ("wrong", HASHED)];
+    assert_eq!(verify_many(&items, &BatchOptions { threads: 1, limits: None }),
+        vec![Ok(()), Err(CheckError::HashMismatch)]);
+
+    let hash = ScryptHash::parse(HASHED).unwrap();
+    assert_eq!(hash.to_string(), HASHED);
+    assert_eq!(hash.salt(), b"NaCl");
+    assert_eq!(hash.verify("password"), Ok(()));
+    assert_eq!(hash.to_record(), Err(CheckError::Unrepresentable));
+    assert_eq!(ScryptHashRef::parse(HASHED).unwrap().verify("password"), Ok(()));
+}
+
+#[test]
+fn test_edited() {
+    assert_eq!(scrypt_check("password", EDITED), Err(CheckError::HashMismatch));
+    assert_eq!(scrypt_check("password", STRIPPED), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_peppered() {
+    let verifier = Verifier::builder().build();
+    assert_eq!(verifier.verify_with_keys("password", PEPPERED, &keys()), Ok(()));
+    assert_eq!(verifier.verify_with_keys("wrong", PEPPERED, &keys()),
+        Err(CheckError::HashMismatch));
+    assert_eq!(scrypt_check("password", PEPPERED), Err(CheckError::NeedsPepper(7)));
+    // another id of the same key does not verify
+    let edited = edit_header(PEPPERED, |h| h[12] = 8);
+    assert_eq!(verifier.verify_with_keys("password", &edited, &keys()),
+        Err(CheckError::HashMismatch));
+
+    // the new hash stays bound
+    let mut peppers = PepperSet::new();
+    peppers.insert(7, KEY);
+    peppers.insert(8, b"pepper key 2");
+    let rehashed = scrypt_check_and_rehash_peppered_with_rng("password", PEPPERED, &params(),
+        &peppers, &mut ChaChaRng::from_seed([7; 32])).unwrap().unwrap();
+    assert_eq!(&header(&rehashed)[11..], &[0x21, 8]);
+    assert_eq!(verifier.verify_with_keys("password", &rehashed, &peppers), Ok(()));
+}
+
+#[test]
+fn test_final_rounds() {
+    assert_eq!(scrypt_check("password", ROUNDS), Ok(()));
+    assert_eq!(scrypt_check("wrong", ROUNDS), Err(CheckError::HashMismatch));
+    let hash = ScryptHash::parse(ROUNDS).unwrap();
+    assert_eq!(hash.final_rounds(), NonZeroU32::new(10));
+    assert_eq!(hash.to_string(), ROUNDS);
+    // another count does not verify
+    let edited = edit_header(ROUNDS, |h| h[12] = 11);
+    assert_eq!(scrypt_check("password", &edited), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_simple() {
+    let config = SimpleConfig::builder().params(params()).bound(true).build().unwrap();
+    let hashed = config.hash_with_rng("password", &mut ChaChaRng::from_seed([7; 32])).unwrap();
+    assert!(hashed.starts_with("$rscrypt$2$"));
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(needs_rehash(&hashed, &params()), Ok(false));
+
+    let hashed = scrypt_simple_bound("password", &params()).unwrap();
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+
+    // the new hash stays bound
+    let desired = Params::new(5, 1, 2).unwrap();
+    let rehashed = scrypt_check_and_rehash_with_rng("password", &hashed, &desired,
+        &mut ChaChaRng::from_seed([8; 32])).unwrap().unwrap();
+    assert_eq!(header(&rehashed)[11], 0x20);
+    assert_eq!(scrypt_check("password", &rehashed), Ok(()));
+}
+
+#[test]
+fn test_malformed_header() {
+    assert_eq!(header(HASHED)[11], 0x20);
+
+    let mut malformed = Vec::new();
+    // no field follows the header
+    malformed.push(edit_header(HASHED, |h| h.push(0)));
+    // the flags of a field without it
+    for &flag in [0x01u8, 0x04, 0x08, 0x10, 0x40].iter() {
+        malformed.push(edit_header(HASHED, |h| h[11] |= flag));
+    }
+    for hashed in malformed.iter() {
+        assert_eq!(scrypt_check("password", hashed), Err(CheckError::InvalidFormat), "{}",
+            hashed);
+    }
+}
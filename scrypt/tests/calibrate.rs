@@ -0,0 +1,214 @@
+#![cfg(feature="std")]
+extern crate scrypt;
+
+use std::cell::Cell;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use scrypt::{Params, StdTime, TimeSource};
+
+/// A machine on which a hash at `log_n = 10` takes `base` and every
+/// doubling of `N` doubles the time: the `k`-th measurement, which starts
+/// and ends with a call of `now()`, takes `base << k`.
+struct Doubling {
+    base: Duration,
+    calls: Cell<u32>,
+    now: Cell<Duration>,
+}
+
+impl Doubling {
+    fn new(base: Duration) -> Doubling {
+        Doubling { base, calls: Cell::new(0), now: Cell::new(Duration::from_secs(1)) }
+    }
+
+    fn measurements(&self) -> u32 { self.calls.get() / 2 }
+}
+
+impl TimeSource for Doubling {
+    fn now(&self) -> Duration {
+        let calls = self.calls.get();
+        if calls % 2 == 1 {
+            self.now.set(self.now.get() + self.base * (1 << (calls / 2)));
+        }
+        self.calls.set(calls + 1);
+        self.now.get()
+    }
+}
+
+const MS: Duration = Duration::from_millis(1);
+
+#[test]
+fn test_search() {
+    // 1, 2, 4 and 8 ms fit into 10 ms, 16 ms would not
+    let clock = Doubling::new(MS);
+    let params = Params::calibrate_with(MS * 10, usize::MAX, &clock);
+    assert_eq!(params, Params::new(13, 8, 1).unwrap());
+    assert_eq!(clock.measurements(), 4);
+
+    // exactly twice the last measurement still doubles
+    let clock = Doubling::new(MS);
+    assert_eq!(Params::calibrate_with(MS * 16, usize::MAX, &clock).log_n(), 14);
+    assert_eq!(clock.measurements(), 5);
+}
+
+#[test]
+fn test_memory_limit() {
+    // `log_n = 13` needs 8 MiB and 2 KiB
+    let clock = Doubling::new(MS);
+    let params = Params::calibrate_with(Duration::from_secs(60), 8 << 20, &clock);
+    assert_eq!(params, Params::new(12, 8, 1).unwrap());
+    assert_eq!(clock.measurements(), 3);
+}
+
+#[test]
+fn test_slow_machine() {
+    // the smallest parameters, even above the target
+    let clock = Doubling::new(MS * 50);
+    assert_eq!(Params::calibrate_with(MS * 10, usize::MAX, &clock),
+        Params::new(10, 8, 1).unwrap());
+    assert_eq!(clock.measurements(), 1);
+    let clock = Doubling::new(MS);
+    assert_eq!(Params::calibrate_with(MS * 10, 0, &clock).log_n(), 10);
+}
+
+#[test]
+fn test_std_clock() {
+    let clock = StdTime::new();
+    let start = clock.now();
+    assert!(clock.now() >= start);
+
+    let params = Params::calibrate(MS * 20, 16 << 20);
+    assert!(params.log_n() >= 10);
+    assert!(params.log_n() == 10 || params.required_memory() <= 16 << 20);
+    assert_eq!((params.r(), params.p()), (8, 1));
+}
+
+/// An empty directory for the cache files of the test `name`.
+fn cache_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("scrypt-calibrate-{}-{}", process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Replace the line of the cache file starting with `field`.
+fn rewrite(cache: &Path, field: &str, value: &str) {
+    let contents = fs::read_to_string(cache).unwrap();
+    let contents: Vec<String> = contents.lines()
+        .map(|line| if line.starts_with(field) { value.to_string() } else { line.to_string() })
+        .collect();
+    fs::write(cache, contents.join("\n") + "\n").unwrap();
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+#[test]
+fn test_cache_warm_start() {
+    let dir = cache_dir("warm");
+    let cache = dir.join("calibration");
+
+    // a cold start measures like `calibrate_with`
+    let clock = Doubling::new(MS);
+    let params = Params::calibrate_cached_with(MS * 10, usize::MAX, &cache, &clock);
+    assert_eq!(params, Params::new(13, 8, 1).unwrap());
+    assert_eq!(clock.measurements(), 4);
+    let contents = fs::read_to_string(&cache).unwrap();
+    assert!(contents.starts_with("scrypt-calibration 1\nhardware "), "{}", contents);
+    assert!(contents.ends_with("\nlog_n 13\nnanos 8000000\n"), "{}", contents);
+    // the temporary file was renamed
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+    // a warm start does not measure, for any target
+    let clock = Doubling::new(MS);
+    assert_eq!(Params::calibrate_cached_with(MS * 10, usize::MAX, &cache, &clock), params);
+    let expected = Params::calibrate_with(MS * 40, 16 << 20, &Doubling::new(MS));
+    assert_eq!(Params::calibrate_cached_with(MS * 40, 16 << 20, &cache, &clock), expected);
+    assert_eq!(Params::calibrate_cached_with(MS * 40, usize::MAX, &cache, &clock).log_n(), 15);
+    assert_eq!(Params::calibrate_cached_with(MS, usize::MAX, &cache, &clock).log_n(), 10);
+    assert_eq!(clock.measurements(), 0);
+    assert_eq!(fs::read_to_string(&cache).unwrap(), contents);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cache_invalidation() {
+    let dir = cache_dir("invalidation");
+    let cache = dir.join("calibration");
+    Params::calibrate_cached_with(MS * 10, usize::MAX, &cache, &Doubling::new(MS));
+
+    let stale = format!("measured {}", unix_now() - 31 * 24 * 60 * 60);
+    let future = format!("measured {}", unix_now() + 24 * 60 * 60);
+    let cases = [
+        ("measured", stale.as_str()),
+        ("measured", future.as_str()),
+        ("hardware", "hardware other/CPU/1024"),
+        ("scrypt-calibration", "scrypt-calibration 2"),
+        ("nanos", "nanos fast"),
+        ("log_n", "log_n 200"),
+    ];
+    for &(field, value) in cases.iter() {
+        rewrite(&cache, field, value);
+        // measured again on a twice as fast machine, and stored
+        let clock = Doubling::new(MS / 2);
+        let params = Params::calibrate_cached_with(MS * 10, usize::MAX, &cache, &clock);
+        assert_eq!(params.log_n(), 14, "{}", value);
+        assert_eq!(clock.measurements(), 5, "{}", value);
+        let contents = fs::read_to_string(&cache).unwrap();
+        assert!(!contents.contains(value), "{}", value);
+        assert!(contents.ends_with("\nlog_n 14\nnanos 8000000\n"), "{}", contents);
+    }
+
+    fs::write(&cache, "").unwrap();
+    let clock = Doubling::new(MS);
+    assert_eq!(Params::calibrate_cached_with(MS * 10, usize::MAX, &cache, &clock).log_n(), 13);
+    assert_eq!(clock.measurements(), 4);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cache_unwritable() {
+    let dir = cache_dir("unwritable");
+    // a regular file as the directory, and a directory as the file
+    let file = dir.join("file");
+    fs::write(&file, "").unwrap();
+    for cache in [file.join("calibration"), dir.clone()].iter() {
+        for _ in 0..2 {
+            let clock = Doubling::new(MS);
+            let params = Params::calibrate_cached_with(MS * 10, usize::MAX, cache, &clock);
+            assert_eq!(params.log_n(), 13);
+            assert_eq!(clock.measurements(), 4);
+        }
+    }
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cache_read_only() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = cache_dir("read-only");
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o555)).unwrap();
+    let cache = dir.join("calibration");
+    for _ in 0..2 {
+        let clock = Doubling::new(MS);
+        let params = Params::calibrate_cached_with(MS * 10, usize::MAX, &cache, &clock);
+        assert_eq!(params.log_n(), 13);
+    }
+    // root may write anyway, but never leaves a temporary file behind
+    assert!(fs::read_dir(&dir).unwrap().all(|entry| {
+        entry.unwrap().file_name() == "calibration"
+    }));
+
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+}
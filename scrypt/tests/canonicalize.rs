@@ -0,0 +1,110 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use scrypt::{canonicalize, scrypt_check, scrypt_simple, scrypt_simple_ad, scrypt_simple_peppered,
+    scrypt_simple_v2, Format, Pepper, Params, SimpleConfig};
+use scrypt::errors::CheckError;
+
+// Generated with Python's `hashlib.scrypt`.
+const V0: &str = "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$";
+const V2: &str =
+    "$rscrypt$2$BAgAAAABAAAABCAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM";
+
+/// Strings written by the crate, which are canonical.
+fn canonical_corpus() -> Vec<String> {
+    let params = Params::new(4, 8, 1).unwrap();
+    let large = Params::new(2, 300, 2).unwrap();
+    let mut corpus = vec![
+        V0.to_string(),
+        V2.to_string(),
+        scrypt_simple("password", &params).unwrap(),
+        scrypt_simple("password", &large).unwrap(),
+        scrypt_simple_v2("password", &params).unwrap(),
+        scrypt_simple_v2("password", &large).unwrap(),
+        scrypt_simple_peppered("password", &params, &Pepper { id: 7, key: b"key" }).unwrap(),
+        scrypt_simple_ad("password", &params, b"tenant").unwrap(),
+    ];
+    for &format in [Format::Phc, Format::Crypt7, Format::Lambdaworks, Format::Django,
+        Format::Werkzeug, Format::Hex].iter()
+    {
+        let config = SimpleConfig::builder().params(params).format(format).build().unwrap();
+        corpus.push(config.hash_with("password").unwrap());
+    }
+    corpus
+}
+
+/// Variants of `V0` written by other tools, with the same parameters,
+/// salt and hash.
+const V0_VARIANTS: &[&str] = &[
+    // no trailing `$`, or too many
+    "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=",
+    "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$$",
+    "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$$$",
+    // missing, partial and superfluous padding
+    "$rscrypt$0$BAgB$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM$",
+    "$rscrypt$0$BAgB$TmFDbA=$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+    "$rscrypt$0$BAgB=$TmFDbA=$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM==$",
+    // nonzero unused bits
+    "$rscrypt$0$BAgB$TmFDbB==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dN=$",
+    // the URL-safe alphabet, padded or not
+    "$rscrypt$0$BAgB$TmFDbA$9d-zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM$",
+    "$rscrypt$0$BAgB$TmFDbA==$9d-zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=",
+    // version 1, although `r` and `p` fit into a byte
+    "$rscrypt$1$BAgAAAABAAAA$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+    "$rscrypt$1$BAgAAAABAAAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+];
+
+const V2_VARIANTS: &[&str] = &[
+    "$rscrypt$2$BAgAAAABAAAABCAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM$",
+    "$rscrypt$2$BAgAAAABAAAABCAA$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=",
+    "$rscrypt$2$BAgAAAABAAAABCAA$TmFDbB$9d-zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dN",
+];
+
+#[test]
+fn test_canonical_unchanged() {
+    for hashed in canonical_corpus() {
+        assert_eq!(canonicalize(&hashed).as_ref(), Ok(&hashed), "{}", hashed);
+    }
+}
+
+#[test]
+fn test_variants() {
+    for &(variants, canonical) in [(V0_VARIANTS, V0), (V2_VARIANTS, V2)].iter() {
+        for &variant in variants {
+            assert_eq!(canonicalize(variant).as_ref().map(|s| &s[..]), Ok(canonical),
+                "{}", variant);
+        }
+    }
+    assert_eq!(scrypt_check("password", &canonicalize(V0_VARIANTS[6]).unwrap()), Ok(()));
+}
+
+#[test]
+fn test_idempotent() {
+    let corpus = canonical_corpus();
+    let all = corpus.iter().map(|s| &s[..]).chain(V0_VARIANTS.iter().cloned())
+        .chain(V2_VARIANTS.iter().cloned());
+    for hashed in all {
+        let once = canonicalize(hashed).unwrap();
+        assert_eq!(canonicalize(&once).as_ref(), Ok(&once), "{}", hashed);
+    }
+}
+
+#[test]
+fn test_unrepairable() {
+    let hashes = [
+        ("", CheckError::UnsupportedFormat),
+        ("$rscrypt$0$", CheckError::InvalidFormat),
+        ("$rscrypt$0$BAgB$Tm*DbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+            CheckError::InvalidFormat),
+        ("$rscrypt$0$BAgB$TmFDbA==$9d+z$ly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+            CheckError::InvalidFormat),
+        ("$rscrypt$7$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+            CheckError::UnsupportedVersion),
+        // mixed alphabets
+        ("$rscrypt$0$BAgB$TmFDbA$9d-zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1d/$",
+            CheckError::InvalidFormat),
+    ];
+    for &(hashed, expected) in hashes.iter() {
+        assert_eq!(canonicalize(hashed), Err(expected), "{}", hashed);
+    }
+}
@@ -0,0 +1,90 @@
+#![cfg(all(feature="capi", unix))]
+//! Builds the crate as a static library, links `tests/capi/test.c` against
+//! it with the system C compiler and runs the program.
+extern crate scrypt;
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use scrypt::errors::ErrorCode;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+fn run(command: &mut Command) {
+    let output = command.output().unwrap_or_else(|e| panic!("{:?}: {}", command, e));
+    assert!(output.status.success(), "{:?}\n{}{}", command,
+        String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn test_c_program() {
+    if Command::new("cc").arg("--version").output().is_err() {
+        eprintln!("skipping the C interface test, no `cc` found");
+        return;
+    }
+
+    // a target directory of its own, the one of `cargo test` is locked
+    let target = Path::new(env!("CARGO_TARGET_TMPDIR")).join("capi");
+    run(Command::new(env!("CARGO"))
+        .args(["rustc", "--quiet", "--lib", "--release", "--features", "capi",
+            "--crate-type", "staticlib", "--manifest-path"])
+        .arg(Path::new(MANIFEST_DIR).join("Cargo.toml"))
+        .arg("--target-dir").arg(&target));
+
+    let program = target.join("capi-test");
+    let mut cc = Command::new("cc");
+    cc.arg(Path::new(MANIFEST_DIR).join("tests/capi/test.c"))
+        .arg("-I").arg(Path::new(MANIFEST_DIR).join("include"))
+        .arg(target.join("release/libscrypt.a"))
+        .arg("-o").arg(&program)
+        .args(["-lpthread", "-lm"]);
+    if cfg!(target_os = "linux") { cc.arg("-ldl"); }
+    run(&mut cc);
+    run(&mut Command::new(&program));
+}
+
+/// `FooBar` to `FOO_BAR`.
+fn screaming_snake(name: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 { result.push('_'); }
+        result.push(c.to_ascii_uppercase());
+    }
+    result
+}
+
+#[test]
+fn test_header_error_codes() {
+    let header = fs::read_to_string(Path::new(MANIFEST_DIR).join("include/scrypt.h")).unwrap();
+    let defines: Vec<(String, u32)> = header.lines()
+        .filter_map(|line| line.strip_prefix("#define SCRYPT_E_"))
+        .map(|line| {
+            let mut parts = line.split_whitespace();
+            (parts.next().unwrap().to_string(), parts.next().unwrap().parse().unwrap())
+        })
+        .collect();
+
+    // retired codes have no define
+    let expected: Vec<(String, u32)> = (1..256)
+        .filter_map(|value| ErrorCode::from_code(value)
+            .map(|code| (screaming_snake(&format!("{:?}", code)), value)))
+        .collect();
+    assert_eq!(defines, expected);
+}
+
+#[test]
+fn test_header_declares_exports() {
+    let header = fs::read_to_string(Path::new(MANIFEST_DIR).join("include/scrypt.h")).unwrap();
+    let source = fs::read_to_string(Path::new(MANIFEST_DIR).join("src/capi.rs")).unwrap();
+    let exports: Vec<&str> = source.split("#[no_mangle]").skip(1)
+        .map(|item| {
+            let name = &item[item.find("fn ").unwrap() + 3..];
+            &name[..name.find('(').unwrap()]
+        })
+        .collect();
+    assert_eq!(exports.len(), 6);
+    for name in exports {
+        assert!(header.contains(&format!(" {}(", name)), "{} is not declared", name);
+    }
+}
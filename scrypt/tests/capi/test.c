@@ -0,0 +1,155 @@
+/* Driven by tests/capi.rs: exits with 0 if all checks pass, prints the
+ * failing line otherwise. */
+#include <errno.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "scrypt.h"
+
+static int failures = 0;
+
+#define CHECK(cond) do { \
+    if (!(cond)) { \
+        fprintf(stderr, "%s:%d: %s\n", __FILE__, __LINE__, #cond); \
+        failures++; \
+    } \
+} while (0)
+
+struct vector {
+    const char *passwd;
+    const char *salt;
+    uint64_t N;
+    uint32_t r;
+    uint32_t p;
+    const char *dk;
+};
+
+/* RFC 7914, section 12, without the N = 2^20 vector */
+static const struct vector VECTORS[] = {
+    { "", "", 16, 1, 1,
+      "\x77\xd6\x57\x62\x38\x65\x7b\x20\x3b\x19\xca\x42\xc1\x8a\x04\x97"
+      "\xf1\x6b\x48\x44\xe3\x07\x4a\xe8\xdf\xdf\xfa\x3f\xed\xe2\x14\x42"
+      "\xfc\xd0\x06\x9d\xed\x09\x48\xf8\x32\x6a\x75\x3a\x0f\xc8\x1f\x17"
+      "\xe8\xd3\xe0\xfb\x2e\x0d\x36\x28\xcf\x35\xe2\x0c\x38\xd1\x89\x06" },
+    { "password", "NaCl", 1024, 8, 16,
+      "\xfd\xba\xbe\x1c\x9d\x34\x72\x00\x78\x56\xe7\x19\x0d\x01\xe9\xfe"
+      "\x7c\x6a\xd7\xcb\xc8\x23\x78\x30\xe7\x73\x76\x63\x4b\x37\x31\x62"
+      "\x2e\xaf\x30\xd9\x2e\x22\xa3\x88\x6f\xf1\x09\x27\x9d\x98\x30\xda"
+      "\xc7\x27\xaf\xb9\x4a\x83\xee\x6d\x83\x60\xcb\xdf\xa2\xcc\x06\x40" },
+    { "pleaseletmein", "SodiumChloride", 16384, 8, 1,
+      "\x70\x23\xbd\xcb\x3a\xfd\x73\x48\x46\x1c\x06\xcd\x81\xfd\x38\xeb"
+      "\xfd\xa8\xfb\xba\x90\x4f\x8e\x3e\xa9\xb5\x43\xf6\x54\x5d\xa1\xf2"
+      "\xd5\x43\x29\x55\x61\x3f\x0f\xcf\x62\xd4\x97\x05\x24\x2a\x9a\xf9"
+      "\xe6\x1e\x85\xdc\x0d\x65\x1e\x40\xdf\xcf\x01\x7b\x45\x57\x58\x87" },
+};
+
+static void test_vectors(void) {
+    size_t i;
+    for (i = 0; i < sizeof(VECTORS) / sizeof(VECTORS[0]); i++) {
+        const struct vector *v = &VECTORS[i];
+        uint8_t buf[64];
+        CHECK(crypto_scrypt((const uint8_t *)v->passwd, strlen(v->passwd),
+                            (const uint8_t *)v->salt, strlen(v->salt),
+                            v->N, v->r, v->p, buf, sizeof(buf)) == 0);
+        CHECK(memcmp(buf, v->dk, sizeof(buf)) == 0);
+    }
+}
+
+static int scrypt_errno(uint64_t N, uint32_t r, uint32_t p, size_t buflen) {
+    uint8_t buf[1];
+    errno = 0;
+    if (crypto_scrypt((const uint8_t *)"p", 1, (const uint8_t *)"s", 1,
+                      N, r, p, buf, buflen) != -1) {
+        return 0;
+    }
+    return errno;
+}
+
+static void test_errors(void) {
+    uint8_t buf[1];
+    CHECK(scrypt_errno(0, 1, 1, 1) == EINVAL);
+    CHECK(scrypt_errno(1, 1, 1, 1) == EINVAL);
+    CHECK(scrypt_errno(1000, 8, 1, 1) == EINVAL);
+    CHECK(scrypt_errno(16, 0, 1, 1) == EINVAL);
+    CHECK(scrypt_errno(16, 1 << 15, 1 << 15, 1) == EFBIG);
+    /* empty inputs may be null */
+    CHECK(crypto_scrypt(NULL, 0, NULL, 0, 16, 1, 1, buf, sizeof(buf)) == 0);
+    CHECK(crypto_scrypt(NULL, 0, NULL, 0, 16, 1, 1, NULL, 0) == 0);
+    errno = 0;
+    CHECK(crypto_scrypt(NULL, 1, NULL, 0, 16, 1, 1, buf, sizeof(buf)) == -1);
+    CHECK(errno == EINVAL);
+}
+
+static void test_strings(void) {
+    char hashed[128];
+    char small[16];
+
+    CHECK(scrypt_simple_str("p\xc3\xa4ssword", 4, 8, 1, hashed, sizeof(hashed)) == 0);
+    CHECK(strncmp(hashed, "$rscrypt$0$BAgB$", 16) == 0);
+    CHECK(scrypt_check_str(hashed, "p\xc3\xa4ssword") == 1);
+    CHECK(scrypt_check_str(hashed, "password") == 0);
+
+    errno = 0;
+    CHECK(scrypt_simple_str("password", 4, 8, 1, small, sizeof(small)) == -1);
+    CHECK(errno == ERANGE);
+    errno = 0;
+    CHECK(scrypt_simple_str("password", 4, 0, 1, hashed, sizeof(hashed)) == -1);
+    CHECK(errno == EINVAL);
+
+    errno = 0;
+    CHECK(scrypt_check_str("$rscrypt$0$", "password") == -1);
+    CHECK(errno == EINVAL);
+    CHECK(scrypt_check_str(NULL, "password") == -1);
+    CHECK(scrypt_check_str("$rscrypt$0$BAgB$15ieabOWBi+lTfggDq1u4A==$"
+                           "LSoB1to848I8SrfvmQAiAa2ajqs+iVgB5TecmxomqfU=$",
+                           "password") == 1);
+}
+
+static void test_hash_password(void) {
+    scrypt_params_t params = { 4, 8, 1 };
+    scrypt_params_t large = { 4, 256, 1 };
+    scrypt_params_t invalid = { 4, 0, 1 };
+    char hashed[128];
+    char small[86];
+
+    CHECK(scrypt_hash_password_len(&params) == 87);
+    CHECK(scrypt_hash_password_len(NULL) == 87);
+    CHECK(scrypt_hash_password_len(&large) == 95);
+
+    CHECK(scrypt_hash_password("p\xc3\xa4ssword", &params, hashed, 87) == SCRYPT_OK);
+    CHECK(strlen(hashed) == 86);
+    CHECK(strncmp(hashed, "$rscrypt$0$BAgB$", 16) == 0);
+    CHECK(scrypt_verify_password("p\xc3\xa4ssword", hashed) == SCRYPT_OK);
+    CHECK(scrypt_verify_password("password", hashed) == SCRYPT_E_HASH_MISMATCH);
+
+    CHECK(scrypt_hash_password("password", &large, hashed, sizeof(hashed)) == SCRYPT_OK);
+    CHECK(strlen(hashed) + 1 == scrypt_hash_password_len(&large));
+    CHECK(scrypt_verify_password("password", hashed) == SCRYPT_OK);
+
+    /* the buffer is never overflowed and always NUL-terminated */
+    memset(small, 'x', sizeof(small));
+    CHECK(scrypt_hash_password("password", &params, small, sizeof(small))
+          == SCRYPT_E_BUFFER_TOO_SMALL);
+    CHECK(small[0] == '\0');
+    CHECK(small[1] == 'x');
+    CHECK(scrypt_hash_password("password", &params, NULL, 0) == SCRYPT_E_INVALID_ARGUMENT);
+
+    memset(hashed, 'x', sizeof(hashed));
+    CHECK(scrypt_hash_password("password", &invalid, hashed, sizeof(hashed))
+          == SCRYPT_E_INVALID_PARAMS);
+    CHECK(hashed[0] == '\0');
+    CHECK(scrypt_hash_password(NULL, &params, hashed, sizeof(hashed))
+          == SCRYPT_E_INVALID_ARGUMENT);
+
+    CHECK(scrypt_verify_password("password", "$rscrypt$0$") == SCRYPT_E_INVALID_FORMAT);
+    CHECK(scrypt_verify_password(NULL, "$rscrypt$0$") == SCRYPT_E_INVALID_ARGUMENT);
+    CHECK(scrypt_verify_password("password", "\xff") == SCRYPT_E_INVALID_FORMAT);
+}
+
+int main(void) {
+    test_vectors();
+    test_errors();
+    test_strings();
+    test_hash_password();
+    return failures == 0 ? 0 : 1;
+}
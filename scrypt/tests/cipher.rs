@@ -0,0 +1,43 @@
+#![cfg(feature="cipher")]
+extern crate scrypt;
+
+use scrypt::cipher::typenum::{U16, U32, U64};
+use scrypt::cipher::{derive_key, derive_key_and_nonce, GenericArray};
+use scrypt::{derive_key_and_iv, scrypt, Params};
+
+fn raw(len: usize, params: &Params) -> Vec<u8> {
+    let mut output = vec![0u8; len];
+    scrypt(b"password", b"NaCl", params, &mut output).unwrap();
+    output
+}
+
+#[test]
+fn test_derive_key() {
+    let params = Params::new(10, 8, 1).unwrap();
+    // the key sizes of AES-128-GCM, AES-256-GCM and ChaCha20Poly1305, and
+    // of AES-256-SIV
+    let key: GenericArray<u8, U16> = derive_key(b"password", b"NaCl", &params).unwrap();
+    assert_eq!(&key[..], &raw(16, &params)[..]);
+    let key = derive_key::<U32>(b"password", b"NaCl", &params).unwrap();
+    assert_eq!(&key[..], &raw(32, &params)[..]);
+    let key = derive_key::<U64>(b"password", b"NaCl", &params).unwrap();
+    assert_eq!(&key[..], &raw(64, &params)[..]);
+}
+
+#[test]
+fn test_derive_key_and_nonce() {
+    let params = Params::new(10, 8, 1).unwrap();
+    // the 96 bit nonces of AES-GCM and ChaCha20Poly1305
+    let (key, nonce) = derive_key_and_nonce::<U32>(b"password", b"NaCl", &params, 12).unwrap();
+    let output = raw(44, &params);
+    assert_eq!(&key[..], &output[..32]);
+    assert_eq!(nonce, &output[32..]);
+
+    let (raw_key, raw_nonce) = derive_key_and_iv(b"password", b"NaCl", &params, 32, 12).unwrap();
+    assert_eq!(&key[..], raw_key.as_bytes());
+    assert_eq!(nonce, raw_nonce);
+
+    let (key, nonce) = derive_key_and_nonce::<U16>(b"password", b"NaCl", &params, 0).unwrap();
+    assert_eq!(&key[..], &raw(16, &params)[..]);
+    assert!(nonce.is_empty());
+}
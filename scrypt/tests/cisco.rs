@@ -0,0 +1,95 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::{scrypt_check, scrypt_simple_cisco, Format, Params, SimpleConfig};
+use scrypt::errors::CheckError;
+
+const FIXTURES: &[(&str, &str)] = &[
+    // the example hash of hashcat mode 9300
+    ("hashcat", "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6"),
+    // generated with Python's `hashlib.scrypt`, verified against the above
+    ("cisco", "$9$nhEmQVczB7dqsO$X.HsgL6x1il0RxkOSSvyQYwucySCt7qFm4v7pqCxkKM"),
+    ("Ünïcødé", "$9$./0123456789Az$ATs2r1kHVUSnWz3qYS0Uqt5PO.n1bRswiWdrzWDPQ3w"),
+    ("", "$9$zzzzzzzzzzzzzz$0TZvmAOESUufpVZxmSwNGcSBDGuyI.TMQbek.dYf/nM"),
+];
+
+#[test]
+fn test_cisco_fixtures() {
+    for &(password, hashed) in FIXTURES {
+        assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+        assert_eq!(
+            scrypt_check("wrong", hashed),
+            Err(CheckError::HashMismatch),
+            "{}", hashed
+        );
+    }
+}
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_cisco_fixed_salt() {
+    // every byte is used, modulo 64
+    let bytes = vec![
+        51, 109, 144, 242, 28, 97, 168, 255, 13, 73, 169, 246, 56, 90,
+    ];
+    let config = SimpleConfig::builder().params(Params::new(14, 1, 1).unwrap())
+        .format(Format::Cisco).build().unwrap();
+    let hashed = config.hash_with_rng("cisco", &mut FixedRng(bytes)).unwrap();
+    assert_eq!(hashed, FIXTURES[1].1);
+}
+
+#[test]
+fn test_cisco_round_trip() {
+    let hashed = scrypt_simple_cisco("password").unwrap();
+    assert!(hashed.starts_with("$9$"));
+    assert_eq!(hashed.len(), 3 + 14 + 1 + 43);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_cisco_malformed() {
+    let invalid = [
+        // salt too short, too long and outside of the alphabet
+        "$9$2MJBozw/9R3Us$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+        "$9$2MJBozw/9R3UsUU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+        "$9$$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+        "$9$2MJBozw+9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+        "$9$2MJBozw/9R3Us\u{fc}$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+        // hash outside of the alphabet, wrong lengths, padding and
+        // non-canonical trailing bits
+        "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo+",
+        "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo",
+        "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6.",
+        "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6=",
+        "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo7",
+        "$9$2MJBozw/9R3UsU$",
+        // missing and trailing fields
+        "$9$2MJBozw/9R3UsU",
+        "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6$",
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(scrypt_check("hashcat", hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+}
@@ -0,0 +1,124 @@
+#![cfg(feature="simple")]
+extern crate base64ct;
+extern crate rand;
+extern crate scrypt;
+extern crate sha2;
+
+use base64ct::{Base64Unpadded, Encoding};
+use rand::SeedableRng;
+use rand::prng::ChaChaRng;
+use sha2::{Digest, Sha256};
+
+use scrypt::errors::{CheckError, ErrorCode, Rule};
+use scrypt::{cost_of, scrypt_check, scrypt_check_bytes, scrypt_simple, scrypt_simple_client,
+    scrypt_simple_client_with_rng, scrypt_simple_peppered, scrypt_simple_prehashed,
+    ClientScheme, LengthPolicy, Params, ParamsPolicy, Pepper, ScryptHash, ScryptHashRef, Verifier};
+
+const SHA256: ClientScheme = ClientScheme(1);
+const SHA256_V2: ClientScheme = ClientScheme(2);
+
+fn params() -> Params {
+    Params::new(4, 8, 1).unwrap()
+}
+
+fn digest(password: &str) -> Vec<u8> {
+    Sha256::digest(password.as_bytes()).to_vec()
+}
+
+/// Verify `client_digest` against `hashed` with a `Verifier` of `scheme`.
+fn check_client(client_digest: &[u8], hashed: &str, scheme: ClientScheme)
+    -> Result<(), CheckError>
+{
+    Verifier::builder().client_scheme(scheme).build().verify_bytes(client_digest, hashed)
+}
+
+#[test]
+fn test_matching_scheme() {
+    let hashed = scrypt_simple_client(&digest("password"), SHA256, &params()).unwrap();
+    assert!(hashed.starts_with("$rscrypt$2$"));
+    assert_eq!(check_client(&digest("password"), &hashed, SHA256), Ok(()));
+    assert_eq!(check_client(&digest("wrong"), &hashed, SHA256),
+        Err(CheckError::HashMismatch));
+    assert_eq!(cost_of(&hashed).unwrap().params, params());
+
+    // the salt comes from the rng, the digest is hashed as the password
+    let seeded = |scheme| scrypt_simple_client_with_rng(&digest("password"), scheme, &params(),
+        &mut ChaChaRng::from_seed([1; 32])).unwrap();
+    assert_eq!(seeded(SHA256), seeded(SHA256));
+    assert!(seeded(SHA256) != seeded(SHA256_V2));
+
+    // the rules of the verifier apply before the scheme is compared
+    let verifier = Verifier::builder()
+        .client_scheme(SHA256_V2)
+        .strict_encoding(true)
+        .require(ParamsPolicy { min_log_n: 10, ..ParamsPolicy::default() })
+        .build();
+    assert_eq!(verifier.verify_bytes(&digest("password"), &hashed),
+        Err(CheckError::Refused(Rule::MinLogN)));
+    let verifier = Verifier::builder().client_scheme(SHA256).strict_encoding(true).build();
+    assert_eq!(verifier.verify_bytes(&digest("password"), &hashed), Ok(()));
+}
+
+#[test]
+fn test_mismatched_scheme() {
+    let hashed = scrypt_simple_client(&digest("password"), SHA256, &params()).unwrap();
+    let err = check_client(&digest("password"), &hashed, SHA256_V2).unwrap_err();
+    assert_eq!(err, CheckError::ClientSchemeMismatch { stored: Some(1), claimed: 2 });
+    assert_eq!(err.code(), ErrorCode::ClientSchemeMismatch);
+    assert_eq!(err.to_string(), "digest of the client scheme 2, `stored` requires 1");
+
+    // the other functions do not mistake the digest for a password
+    let err = scrypt_check("password", &hashed).unwrap_err();
+    assert_eq!(err, CheckError::NeedsClientScheme(1));
+    assert_eq!(err.code(), ErrorCode::NeedsClientScheme);
+    assert_eq!(scrypt_check_bytes(&digest("password"), &hashed),
+        Err(CheckError::NeedsClientScheme(1)));
+    assert_eq!(ScryptHash::parse(&hashed), Err(CheckError::NeedsClientScheme(1)));
+    assert_eq!(ScryptHashRef::parse(&hashed), Err(CheckError::NeedsClientScheme(1)));
+}
+
+#[test]
+fn test_legacy_hashes() {
+    // strings without a scheme are rejected, whatever their format
+    let legacy = [
+        scrypt_simple("password", &params()).unwrap(),
+        scrypt_simple_peppered("password", &params(), &Pepper { id: 3, key: b"key" }).unwrap(),
+        scrypt_simple_prehashed("password", &params(), &LengthPolicy::default()).unwrap(),
+        "$scrypt$ln=4,r=8,p=1$c2FsdA$2NOl1LdjPjRcRCzCFAidwdGpgWBUtT+3zS9qZ93T9vo".to_string(),
+    ];
+    for hashed in legacy.iter() {
+        let err = check_client(&digest("password"), hashed, SHA256).unwrap_err();
+        assert_eq!(err, CheckError::ClientSchemeMismatch { stored: None, claimed: 1 });
+        assert_eq!(err.to_string(),
+            "digest of the client scheme 1, `stored` has no client scheme");
+    }
+
+    // malformed strings are reported as by `scrypt_check`
+    for &hashed in ["$rscrypt$", "", "$scrypt$ln=4"].iter() {
+        assert_eq!(check_client(&digest("password"), hashed, SHA256),
+            Err(CheckError::InvalidFormat));
+    }
+}
+
+#[test]
+fn test_malformed_header() {
+    let hashed = scrypt_simple_client(&digest("password"), SHA256, &params()).unwrap();
+    let fields: Vec<&str> = hashed.split('$').collect();
+    let header = Base64Unpadded::decode_vec(fields[3]).unwrap();
+    assert_eq!(header[11], 0x08);
+    assert_eq!(&header[12..], &[1]);
+    let encode = |header: &[u8]| format!("$rscrypt$2${}${}${}",
+        Base64Unpadded::encode_string(header), fields[4], fields[5]);
+
+    // the scheme id is missing
+    assert_eq!(check_client(&digest("password"), &encode(&header[..12]), SHA256),
+        Err(CheckError::InvalidFormat));
+    // client schemes are not combined with the other flags
+    for &flag in [0x01, 0x02, 0x04].iter() {
+        let mut combined = header.clone();
+        combined[11] |= flag;
+        if flag == 0x04 { combined.push(0); }
+        assert_eq!(check_client(&digest("password"), &encode(&combined), SHA256),
+            Err(CheckError::InvalidFormat));
+    }
+}
@@ -0,0 +1,157 @@
+//! Sharing of the public types between threads.
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Barrier};
+use std::thread;
+use std::time::Duration;
+
+use rand::rngs::OsRng;
+
+use scrypt::errors::{CheckError, HashError, InvalidOutputLen, InvalidParams};
+use scrypt::observer::{set_global_observer, set_memory_warning_threshold, HashEvent,
+    ScryptObserver, VerifyEvent, Warning};
+use scrypt::{verify_many, BatchOptions, Format, HashParts, Password, PepperSet, Registry,
+    ScryptHash, ScryptHashRef, Params, SimpleConfig, SimpleConfigBuilder, StaticKeys, Verifier,
+    VerifierBuilder};
+
+const THREADS: usize = 32;
+const ROUNDS: usize = 4;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_send_sync() {
+    assert_send_sync::<Params>();
+    assert_send_sync::<Password>();
+    assert_send_sync::<Format>();
+    assert_send_sync::<HashParts>();
+    assert_send_sync::<Registry>();
+    assert_send_sync::<ScryptHash>();
+    assert_send_sync::<ScryptHashRef<'static>>();
+    assert_send_sync::<PepperSet>();
+    assert_send_sync::<StaticKeys>();
+    assert_send_sync::<SimpleConfig>();
+    assert_send_sync::<SimpleConfigBuilder>();
+    assert_send_sync::<Verifier>();
+    assert_send_sync::<VerifierBuilder>();
+    assert_send_sync::<BatchOptions>();
+    assert_send_sync::<scrypt::credfile::CredFile>();
+    assert_send_sync::<CheckError>();
+    assert_send_sync::<HashError>();
+    assert_send_sync::<InvalidOutputLen>();
+    assert_send_sync::<InvalidParams>();
+    #[cfg(feature="enc")]
+    assert_send_sync::<scrypt::errors::EncError>();
+    #[cfg(feature="yescrypt")]
+    assert_send_sync::<scrypt::yescrypt::YescryptParams>();
+}
+
+#[derive(Default)]
+struct Counter {
+    hashes: AtomicUsize,
+    verifications: AtomicUsize,
+    warnings: AtomicUsize,
+}
+
+impl ScryptObserver for Counter {
+    fn on_hash(&self, _event: &HashEvent) {
+        self.hashes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_verify(&self, _event: &VerifyEvent) {
+        self.verifications.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_warning(&self, _warning: &Warning) {
+        self.warnings.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Hash and verify with shared instances on `THREADS` threads, while the
+/// global observer and the memory threshold are changed.
+fn stress() -> Arc<Counter> {
+    let params = Params::new(4, 8, 1).unwrap();
+    let counter = Arc::new(Counter::default());
+    // the global observer receives the warnings and, when installed, the
+    // events of `SimpleConfig::verify`, so it does not share the counts
+    let global = Arc::new(Counter::default());
+    let verifier = Arc::new(Verifier::builder().observer(counter.clone()).build());
+    let config = Arc::new(SimpleConfig::builder().params(params).build().unwrap());
+    let registry = Arc::new(Registry::builtin());
+    let barrier = Arc::new(Barrier::new(THREADS));
+
+    let threads: Vec<_> = (0..THREADS).map(|i| {
+        let (counter, global, verifier, config, registry, barrier) = (counter.clone(),
+            global.clone(), verifier.clone(), config.clone(), registry.clone(),
+            barrier.clone());
+        thread::spawn(move || {
+            let mut rng = OsRng::new().unwrap();
+            barrier.wait();
+            for round in 0..ROUNDS {
+                let password = format!("password {} {}", i, round);
+                let hashed = config.hash_observed(&password, &mut rng, &*counter).unwrap();
+                assert_eq!(verifier.verify(&password, &hashed), Ok(()));
+                assert_eq!(verifier.verify("wrong", &hashed), Err(CheckError::HashMismatch));
+                assert!(config.verify(&password, &hashed).is_ok());
+                assert!(registry.verify(&password, &hashed).unwrap().matches(&hashed));
+                match i % 4 {
+                    0 => set_global_observer(Some(global.clone())),
+                    1 => set_global_observer(None),
+                    2 => set_memory_warning_threshold(round as u64 * 1024),
+                    _ => {
+                        let items = [(password.as_bytes(), &hashed[..]), (b"wrong", &hashed[..])];
+                        let results = verify_many(&items,
+                            &BatchOptions { threads: 2, limits: None });
+                        assert_eq!(results, [Ok(()), Err(CheckError::HashMismatch)]);
+                    }
+                }
+            }
+        })
+    }).collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    counter
+}
+
+/// Run `f` on another thread, failing instead of hanging if it deadlocks.
+fn with_watchdog<T: Send + 'static, F: FnOnce() -> T + Send + 'static>(f: F) -> T {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || sender.send(panic::catch_unwind(panic::AssertUnwindSafe(f))));
+    match receiver.recv_timeout(Duration::from_secs(120)) {
+        Ok(Ok(result)) => result,
+        Ok(Err(panic)) => panic::resume_unwind(panic),
+        Err(_) => panic!("deadlock: the threads did not finish in time"),
+    }
+}
+
+struct Panicking;
+
+impl ScryptObserver for Panicking {
+    fn on_warning(&self, _warning: &Warning) {
+        panic!("observer panicked");
+    }
+}
+
+// the global observer is per process, so everything is checked in one test
+#[test]
+fn test_stress() {
+    let counter = with_watchdog(stress);
+    assert_eq!(counter.hashes.load(Ordering::Relaxed), THREADS * ROUNDS);
+    assert_eq!(counter.verifications.load(Ordering::Relaxed), 2 * THREADS * ROUNDS);
+
+    // a panicking observer poisons no lock the others need
+    with_watchdog(|| {
+        set_global_observer(Some(Arc::new(Panicking)));
+        let weak = Params::new(3, 8, 1).unwrap();
+        let config = SimpleConfig::builder().params(weak).build().unwrap();
+        assert!(panic::catch_unwind(|| config.hash_with("password")).is_err());
+        set_global_observer(None);
+        set_memory_warning_threshold(u64::MAX);
+        stress()
+    });
+}
@@ -0,0 +1,335 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use std::num::NonZeroU32;
+
+use rand::SeedableRng;
+use rand::prng::ChaChaRng;
+
+use scrypt::{check_and_derive, scrypt_check, scrypt_simple_client_with_rng,
+    ClientScheme, Format, HashParts, LengthPolicy, Pepper, ScryptHash, Params, SimpleConfig,
+    StaticKeys, Verified, Verifier};
+use scrypt::errors::{CheckError, HashError};
+
+#[test]
+fn test_default() {
+    let config = SimpleConfig::default();
+    assert_eq!(config.params(), Params::new(15, 8, 1).unwrap());
+    assert_eq!((config.salt_len(), config.dk_len()), (16, 32));
+    assert_eq!(config.format(), Format::Rscrypt);
+    assert_eq!(SimpleConfig::builder().build().unwrap(), config);
+}
+
+#[test]
+fn test_combinations() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let configs = [
+        (Format::Rscrypt, None, None, "$rscrypt$0$", 16, 32),
+        (Format::Rscrypt, Some(8), Some(64), "$rscrypt$0$", 8, 64),
+        (Format::Phc, Some(64), Some(16), "$scrypt$ln=4,r=8,p=1$", 64, 16),
+        (Format::Hex, Some(24), Some(48), "scrypt:ln=4,r=8,p=1:", 24, 48),
+        (Format::Lambdaworks, Some(32), None, "$s0$40801$", 32, 32),
+        (Format::Crypt7, Some(43), None, "$7$26..../....", 43, 32),
+        (Format::Django, None, None, "scrypt$16$", 22, 64),
+        (Format::Werkzeug, Some(32), None, "scrypt:16:8:1$", 32, 64),
+        (Format::PhpScrypt, None, None, "16$8$1$", 12, 32),
+        (Format::SimpleScrypt, None, Some(16), "16$8$1$", 16, 16),
+        (Format::RubyScrypt, Some(8), None, "10$8$1$", 8, 32),
+    ];
+    for &(format, salt_len, dk_len, prefix, hash_salt_len, hash_len) in configs.iter() {
+        let mut builder = SimpleConfig::builder();
+        builder.params(params).format(format);
+        if let Some(salt_len) = salt_len { builder.salt_len(salt_len); }
+        if let Some(dk_len) = dk_len { builder.dk_len(dk_len); }
+        let config = builder.build().unwrap();
+
+        let hashed = config.hash_with("password").unwrap();
+        assert!(hashed.starts_with(prefix), "{}", hashed);
+        assert_eq!(scrypt_check("password", &hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+        assert_eq!(config.verify("password", &hashed), Ok(Verified::Ok));
+        assert_eq!(config.verify("wrong", &hashed), Err(CheckError::HashMismatch));
+
+        let hash = ScryptHash::parse(&hashed).unwrap();
+        assert_eq!(hash.format(), format);
+        assert_eq!(hash.params(), params);
+        assert_eq!(hash.salt().len(), hash_salt_len, "{}", hashed);
+        assert_eq!(hash.hash_bytes().len(), hash_len, "{}", hashed);
+        assert_ne!(config.hash_with("password").unwrap(), hashed);
+    }
+
+    let cisco = SimpleConfig::builder()
+        .params(Params::new(14, 1, 1).unwrap())
+        .format(Format::Cisco)
+        .build().unwrap();
+    let hashed = cisco.hash_with("cisco").unwrap();
+    assert!(hashed.starts_with("$9$") && hashed.len() == 61, "{}", hashed);
+    assert_eq!(scrypt_check("cisco", &hashed), Ok(()));
+}
+
+#[test]
+fn test_verify_weak() {
+    let weak = Params::new(4, 8, 1).unwrap();
+    let strong = Params::new(5, 8, 1).unwrap();
+    let hashed = SimpleConfig::builder().params(weak).build().unwrap()
+        .hash_with("password").unwrap();
+    let config = SimpleConfig::builder().params(strong).build().unwrap();
+    assert_eq!(config.verify("password", &hashed), Ok(Verified::OkButWeak(weak)));
+    assert_eq!(config.verify("wrong", &hashed), Err(CheckError::HashMismatch));
+    assert_eq!(config.verify("password", "$rscrypt$"), Err(CheckError::InvalidFormat));
+}
+
+#[test]
+fn test_invalid() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let large_r = Params::new(4, 256, 1).unwrap();
+    let invalid = [
+        // out of range for every format
+        (Format::Rscrypt, params, Some(0), None, "salt 0"),
+        (Format::Rscrypt, params, Some(65), None, "salt 65"),
+        (Format::Phc, params, None, Some(15), "dk 15"),
+        (Format::Phc, params, None, Some(65), "dk 65"),
+        // lengths the format can not hold
+        (Format::Lambdaworks, params, None, Some(64), "dk 64"),
+        (Format::Django, params, None, Some(32), "dk 32"),
+        (Format::Werkzeug, params, None, Some(32), "dk 32"),
+        (Format::Cisco, Params::new(14, 1, 1).unwrap(), Some(16), None, "salt 16"),
+        // parameters the format can not hold
+        (Format::Lambdaworks, large_r, None, None, "params"),
+        (Format::Cisco, params, None, None, "params"),
+    ];
+    for &(format, params, salt_len, dk_len, expected) in invalid.iter() {
+        let mut builder = SimpleConfig::builder();
+        builder.params(params).format(format);
+        if let Some(salt_len) = salt_len { builder.salt_len(salt_len); }
+        if let Some(dk_len) = dk_len { builder.dk_len(dk_len); }
+        let error = match builder.build() {
+            Err(HashError::InvalidSaltLen(len)) => format!("salt {}", len),
+            Err(HashError::InvalidDkLen(len)) => format!("dk {}", len),
+            Err(HashError::UnsupportedParams) => "params".to_string(),
+            r => panic!("{:?}: {:?}", format, r),
+        };
+        assert_eq!(error, expected, "{:?}", format);
+    }
+}
+
+#[test]
+fn test_derived_salt() {
+    let config = SimpleConfig::builder().params(Params::new(4, 8, 1).unwrap()).build().unwrap();
+    let hash = config.derive_hash(b"password", b"global key", b"alice");
+    // scrypt under the salt of `derive_salt`, as computed by Python's hashlib
+    assert_eq!(hex(&hash), "c10ee6bcca412d3a9c63dc587b637c1e8e2093a40ff64cebce683ddea3f91c98");
+
+    assert_eq!(config.verify_derived(b"password", b"global key", b"alice", &hash), Ok(()));
+    assert_eq!(config.verify_derived(b"passwort", b"global key", b"alice", &hash),
+        Err(CheckError::HashMismatch));
+    assert_eq!(config.verify_derived(b"password", b"global key", b"bob", &hash),
+        Err(CheckError::HashMismatch));
+    assert_eq!(config.verify_derived(b"password", b"other key", b"alice", &hash),
+        Err(CheckError::HashMismatch));
+    assert_eq!(config.verify_derived(b"password", b"global key", b"alice", &hash[..16]),
+        Err(CheckError::InvalidFormat));
+
+    let long = SimpleConfig::builder().params(Params::new(4, 8, 1).unwrap()).dk_len(64)
+        .build().unwrap();
+    assert_eq!(&long.derive_hash(b"password", b"global key", b"alice")[..32], &hash[..]);
+}
+
+#[test]
+fn test_rscrypt_options() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let rng = || ChaChaRng::from_seed([3; 32]);
+    let hash = |builder: &mut scrypt::SimpleConfigBuilder| {
+        builder.params(params).build().unwrap().hash_with_rng("password", &mut rng()).unwrap()
+    };
+
+    let hashed = hash(SimpleConfig::builder().version(2));
+    assert!(hashed.starts_with("$rscrypt$2$"), "{}", hashed);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    let hashed = hash(SimpleConfig::builder().url_safe(true));
+    assert!(hashed.starts_with("$rscrypt$0$"), "{}", hashed);
+    assert!(!hashed.contains(['+', '/', '=']), "{}", hashed);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+
+    // the wrapper produces the strings of its option
+    let limits = LengthPolicy { prehash_above: 4, max_len: 16 };
+    let c = NonZeroU32::new(10).unwrap();
+    assert_eq!(hash(SimpleConfig::builder().client_scheme(ClientScheme(5))),
+        scrypt_simple_client_with_rng(b"password", ClientScheme(5), &params, &mut rng())
+            .unwrap());
+
+    let prehashed = SimpleConfig::builder().params(params).prehash(limits).build().unwrap();
+    assert_eq!(prehashed.prehash_above(), Some(4));
+    assert_eq!(prehashed.max_password_len(), Some(16));
+    assert!(matches!(prehashed.hash_with(&"x".repeat(17)),
+        Err(HashError::PasswordTooLong { len: 17, max: 16 })));
+    // a single final round is plain scrypt
+    let config = SimpleConfig::builder().final_rounds(NonZeroU32::new(1).unwrap()).build()
+        .unwrap();
+    assert_eq!((config.final_rounds(), config.version()), (None, 0));
+
+    let generated = ScryptHash::generate("password", &SimpleConfig::builder().params(params)
+        .final_rounds(c).build().unwrap(), &mut rng()).unwrap();
+    assert_eq!(generated.to_string(), hash(SimpleConfig::builder().final_rounds(c)));
+    let bound = SimpleConfig::builder().params(params).bound(true).build().unwrap();
+    assert!(matches!(HashParts::generate("password", &bound, &mut rng()),
+        Err(HashError::UnsupportedParams)));
+}
+
+#[test]
+fn test_pepper() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let rng = || ChaChaRng::from_seed([4; 32]);
+    let config = SimpleConfig::builder().params(params).pepper_id(7).build().unwrap();
+    assert_eq!(config.pepper_id(), Some(7));
+    let mut keys = StaticKeys::new();
+    keys.insert(7, b"pepper key");
+
+    let hashed = config.hash_with_keys_rng("password", &keys, &mut rng()).unwrap();
+    let pepper = Pepper { id: 7, key: b"pepper key" };
+    assert_eq!(hashed, config.hash_with_keys_rng("password", &pepper, &mut rng()).unwrap());
+    assert_eq!(scrypt_check("password", &hashed), Err(CheckError::NeedsPepper(7)));
+
+    // the key is required
+    assert!(matches!(config.hash_with("password"), Err(HashError::UnknownPepper(7))));
+    assert!(matches!(config.hash_with_keys("password", &StaticKeys::new()),
+        Err(HashError::UnknownPepper(7))));
+    assert!(matches!(SimpleConfig::default().hash_with_keys("password", &keys),
+        Err(HashError::UnsupportedParams)));
+    assert!(matches!(ScryptHash::generate("password", &config, &mut rng()),
+        Err(HashError::UnsupportedParams)));
+}
+
+#[test]
+fn test_associated_data() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let config = SimpleConfig::builder().params(params).associated_data(true).build().unwrap();
+    assert_eq!((config.version(), config.associated_data()), (2, true));
+    let hashed = config.hash_with_ad("password", b"tenant 1").unwrap();
+    let verifier = Verifier::builder().build();
+    assert_eq!(verifier.verify_with_ad("password", &hashed, b"tenant 1"), Ok(()));
+    assert_eq!(verifier.verify_with_ad("password", &hashed, b"tenant 2"),
+        Err(CheckError::HashMismatch));
+
+    // the data is required, and only taken by configurations which bind it
+    assert!(matches!(config.hash_with("password"), Err(HashError::UnsupportedParams)));
+    assert!(matches!(SimpleConfig::default().hash_with_ad("password", b"tenant 1"),
+        Err(HashError::UnsupportedParams)));
+    assert!(matches!(ScryptHash::generate("password", &config, &mut ChaChaRng::from_seed([0; 32])),
+        Err(HashError::UnsupportedParams)));
+    let bound = SimpleConfig::builder().params(params).associated_data(true).bound(true).build()
+        .unwrap();
+    let hashed = bound.hash_with_ad("password", b"tenant 1").unwrap();
+    assert_eq!(verifier.verify_with_ad("password", &hashed, b"tenant 1"), Ok(()));
+}
+
+#[test]
+fn test_hash_and_derive() {
+    let config = SimpleConfig::builder().params(Params::new(4, 8, 1).unwrap()).dk_len(16)
+        .salt_len(8).build().unwrap();
+    let (hashed, key) = config.hash_and_derive("password", 24).unwrap();
+    assert_eq!(key.as_bytes().len(), 24);
+    assert_eq!(check_and_derive("password", &hashed, 24).unwrap().as_bytes(), key.as_bytes());
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+
+    // the flag of derived keys is not combined with other options
+    let mut builders = vec![SimpleConfig::builder(); 4];
+    builders[0].bound(true);
+    builders[1].record_created_at(true);
+    builders[2].url_safe(true);
+    builders[3].format(Format::Phc);
+    for builder in builders.iter_mut() {
+        let config = builder.params(Params::new(4, 8, 1).unwrap()).build().unwrap();
+        assert!(matches!(config.hash_and_derive("password", 16),
+            Err(HashError::UnsupportedParams)), "{:?}", builder);
+    }
+    assert!(matches!(SimpleConfig::default().hash_and_derive("password", 0),
+        Err(HashError::InvalidOutputLen(_))));
+}
+
+// the deprecated wrappers keep producing the strings of their options
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_wrappers() {
+    use scrypt::*;
+
+    let params = Params::new(4, 8, 1).unwrap();
+    let rng = || ChaChaRng::from_seed([5; 32]);
+    let hash = |builder: &mut SimpleConfigBuilder| {
+        builder.params(params).build().unwrap().hash_with_rng("password", &mut rng()).unwrap()
+    };
+    assert_eq!(scrypt_simple_with_rng("password", &params, &mut rng()).unwrap(),
+        hash(&mut SimpleConfig::builder()));
+    let limits = LengthPolicy { prehash_above: 4, max_len: 16 };
+    assert_eq!(scrypt_simple_prehashed_with_rng("password", &params, &limits, &mut rng()).unwrap(),
+        hash(SimpleConfig::builder().prehash(limits)));
+    let c = NonZeroU32::new(10).unwrap();
+    assert_eq!(scrypt_simple_final_rounds_with_rng("password", &params, c, &mut rng()).unwrap(),
+        hash(SimpleConfig::builder().final_rounds(c)));
+    assert_eq!(scrypt_simple_bound_with_rng("password", &params, &mut rng()).unwrap(),
+        hash(SimpleConfig::builder().bound(true)));
+    type Wrapper = fn(&str, &Params, &mut ChaChaRng) -> Result<String, HashError>;
+    let formats: [(Wrapper, Format); 9] = [
+        (scrypt_simple_phc_with_rng, Format::Phc),
+        (scrypt_simple_lambdaworks_with_rng, Format::Lambdaworks),
+        (scrypt_simple_django_with_rng, Format::Django),
+        (scrypt_simple_werkzeug_with_rng, Format::Werkzeug),
+        (scrypt_simple_scryptkdf_with_rng, Format::ScryptKdf),
+        (scrypt_simple_php_with_rng, Format::PhpScrypt),
+        (scrypt_simple_go_with_rng, Format::SimpleScrypt),
+        (scrypt_simple_ruby_with_rng, Format::RubyScrypt),
+        (scrypt_simple_hex_with_rng, Format::Hex),
+    ];
+    for &(wrapper, format) in formats.iter() {
+        assert_eq!(wrapper("password", &params, &mut rng()).unwrap(),
+            hash(SimpleConfig::builder().format(format)), "{:?}", format);
+    }
+    let cisco = SimpleConfig::builder().params(Params::new(14, 1, 1).unwrap())
+        .format(Format::Cisco).build().unwrap();
+    assert_eq!(scrypt_simple_cisco_with_rng("password", &mut rng()).unwrap(),
+        cisco.hash_with_rng("password", &mut rng()).unwrap());
+
+    let pepper = Pepper { id: 7, key: b"pepper key" };
+    let peppered = SimpleConfig::builder().params(params).pepper_id(7).build().unwrap()
+        .hash_with_keys_rng("password", &pepper, &mut rng()).unwrap();
+    assert_eq!(scrypt_simple_peppered_with_rng("password", &params, &pepper, &mut rng()).unwrap(),
+        peppered);
+    assert_eq!(scrypt_simple_provided_with_rng("password", &params, &pepper, 7, &mut rng())
+        .unwrap(), peppered);
+    let ad = SimpleConfig::builder().params(params).associated_data(true).build().unwrap();
+    assert_eq!(scrypt_simple_ad_with_rng("password", &params, b"ad", &mut rng()).unwrap(),
+        ad.hash_with_ad_rng("password", b"ad", &mut rng()).unwrap());
+    let (hashed, key) = hash_and_derive_with_rng("password", &params, 16, &mut rng()).unwrap();
+    let config = SimpleConfig::builder().params(params).build().unwrap();
+    let (expected, expected_key) = config.hash_and_derive_with_rng("password", 16, &mut rng())
+        .unwrap();
+    assert_eq!((hashed, key.as_bytes()), (expected, expected_key.as_bytes()));
+}
+
+#[test]
+fn test_invalid_rscrypt_options() {
+    let c = NonZeroU32::new(2).unwrap();
+    let mut builders = vec![SimpleConfig::builder(); 11];
+    builders[0].version(1);
+    builders[1].version(3);
+    builders[2].version(0).bound(true);
+    builders[3].version(2).url_safe(true);
+    builders[4].format(Format::Phc).version(2);
+    builders[5].format(Format::Phc).bound(true);
+    builders[6].format(Format::Phc).url_safe(true);
+    builders[7].prehash(LengthPolicy::default()).final_rounds(c);
+    builders[8].pepper_id(1).record_created_at(true);
+    builders[9].associated_data(true).pepper_id(1);
+    builders[10].associated_data(true).record_created_at(true);
+    for builder in builders.iter() {
+        assert!(matches!(builder.build(), Err(HashError::UnsupportedParams)), "{:?}", builder);
+    }
+    // the bound header combines with the others
+    let config = SimpleConfig::builder().final_rounds(c).bound(true).build().unwrap();
+    assert_eq!((config.version(), config.bound()), (2, true));
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,61 @@
+extern crate scrypt;
+
+use scrypt::{scrypt, scrypt_ctx, ScryptIntermediate, Params};
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn derive(password: &[u8], salt: &[u8], context: &[u8], params: &Params) -> [u8; 32] {
+    let mut output = [0u8; 32];
+    scrypt_ctx(password, salt, context, params, &mut output).unwrap();
+    output
+}
+
+// Generated with Python's `hashlib.scrypt` of the salt
+// `sha256(struct.pack(">Q", len(context)) + context + salt)`.
+const VECTORS: &[(&[u8], &str)] = &[
+    (b"", "d2a4ce093daa204e52fc260193632ac2cc347e47c39077d7c237055026e5881c"),
+    (b"disk", "c86b7d6056cabe11285e1e48ae5255a5b84f51a4e4ff38ab3dc63d2a10e7d7b4"),
+    (b"backup", "2de56944b8a242cc0876cfb698129a462981a2a5b6618b136fa45a49d3c04b03"),
+    (b"auth", "0b0ebe123c7a43954127796b9e5f28178eff6353c453dd01f528eec6874721ae"),
+];
+
+#[test]
+fn test_scrypt_ctx_vectors() {
+    let params = Params::new(10, 8, 1).unwrap();
+    for &(context, expected) in VECTORS {
+        assert_eq!(to_hex(&derive(b"password", b"NaCl", context, &params)), expected);
+    }
+}
+
+#[test]
+fn test_scrypt_ctx_separation() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let mut plain = [0u8; 32];
+    scrypt(b"password", b"NaCl", &params, &mut plain).unwrap();
+
+    let keys: Vec<[u8; 32]> = [&b""[..], b"disk", b"backup", b"auth", b"disk\0"].iter()
+        .map(|context| derive(b"password", b"NaCl", context, &params))
+        .collect();
+    for (i, key) in keys.iter().enumerate() {
+        // the empty context is distinct from plain scrypt
+        assert_ne!(key, &plain);
+        for other in &keys[i + 1..] {
+            assert_ne!(key, other);
+        }
+    }
+
+    // the length prefix separates moving bytes between context and salt
+    assert_ne!(derive(b"password", b"kNaCl", b"dis", &params),
+        derive(b"password", b"NaCl", b"disk", &params));
+}
+
+#[test]
+fn test_intermediate_ctx() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let intermediate = ScryptIntermediate::compute_ctx(b"password", b"NaCl", b"disk", &params);
+    let mut expanded = [0u8; 32];
+    intermediate.expand(b"password", &mut expanded).unwrap();
+    assert_eq!(expanded, derive(b"password", b"NaCl", b"disk", &params));
+}
@@ -0,0 +1,102 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use scrypt::{scrypt_check, scrypt_simple, scrypt_simple_phc, Format, HashFormat,
+    HashParts, Params};
+use scrypt::convert::{phc_to_rscrypt, rscrypt_to_phc};
+use scrypt::errors::CheckError;
+
+fn assert_verifies(password: &str, hashed: &str) {
+    assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+    assert_eq!(
+        scrypt_check("wrong", hashed),
+        Err(CheckError::HashMismatch),
+        "{}", hashed
+    );
+}
+
+#[test]
+fn test_rscrypt_to_phc() {
+    for &(log_n, r, p) in [(4, 8, 1), (1, 256, 1), (2, 1, 300)].iter() {
+        let params = Params::new(log_n, r, p).unwrap();
+        let rscrypt = scrypt_simple("password", &params).unwrap();
+
+        let phc = rscrypt_to_phc(&rscrypt).unwrap();
+        assert!(phc.starts_with(&format!("$scrypt$ln={},r={},p={}$", log_n, r, p)));
+        assert_verifies("password", &phc);
+        assert_eq!(phc_to_rscrypt(&phc).unwrap(), rscrypt);
+    }
+}
+
+#[test]
+fn test_phc_to_rscrypt() {
+    for &(log_n, r, p) in [(4, 8, 1), (1, 256, 1), (2, 1, 300)].iter() {
+        let params = Params::new(log_n, r, p).unwrap();
+        let phc = scrypt_simple_phc("password", &params).unwrap();
+
+        let rscrypt = phc_to_rscrypt(&phc).unwrap();
+        assert!(rscrypt.starts_with("$rscrypt$"));
+        assert_verifies("password", &rscrypt);
+        assert_eq!(rscrypt_to_phc(&rscrypt).unwrap(), phc);
+    }
+}
+
+#[test]
+fn test_phc_to_rscrypt_foreign() {
+    // omitted parameters are written explicitly, short salts are kept
+    let phc = "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM";
+    let rscrypt = phc_to_rscrypt(phc).unwrap();
+    assert!(rscrypt.starts_with("$rscrypt$0$BAgB$jgs=$"));
+    assert_verifies("Ünïcødé", &rscrypt);
+    assert_eq!(
+        rscrypt_to_phc(&rscrypt).unwrap(),
+        "$scrypt$ln=4,r=8,p=1$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+    );
+}
+
+#[test]
+fn test_phc_to_rscrypt_unrepresentable() {
+    let phcs = [
+        // 128-bit hash
+        "$scrypt$ln=5,r=1,p=1$AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8$\
+         clvNdiyEBKCJo2ceWyls+w",
+        // 384-bit hash
+        "$scrypt$p=2,r=4,ln=6$Wpw$MxqUplG0o8ju5tUXwBHolCna81hp+dZN1D6ziZ23k0Z\
+         QkJIoHMdBppgzPx2hrOp9DOhd89jpO1tASgvH8Jg9fA",
+    ];
+    for &phc in phcs.iter() {
+        assert_eq!(phc_to_rscrypt(phc), Err(CheckError::Unrepresentable), "{}", phc);
+    }
+}
+
+#[test]
+fn test_rscrypt_to_phc_lossy() {
+    // `scrypt_check` accepts rscrypt strings with any hash length, which PHC
+    // can hold but which can not be converted back
+    let params = Params::new(4, 8, 1).unwrap();
+    let parts = HashParts::new(b"password", &params, b"salt", 64).unwrap();
+    let rscrypt = Format::Rscrypt.encode(&parts);
+    let phc = rscrypt_to_phc(&rscrypt).unwrap();
+    assert_verifies("password", &phc);
+    assert_eq!(phc_to_rscrypt(&phc), Err(CheckError::Unrepresentable));
+
+    // PHC strings require a hash
+    assert_eq!(
+        rscrypt_to_phc("$rscrypt$0$BAEB$c2FsdA==$$"),
+        Err(CheckError::Unrepresentable),
+    );
+}
+
+#[test]
+fn test_convert_invalid() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let rscrypt = scrypt_simple("password", &params).unwrap();
+    let phc = scrypt_simple_phc("password", &params).unwrap();
+
+    assert_eq!(rscrypt_to_phc(&phc), Err(CheckError::InvalidFormat));
+    assert_eq!(phc_to_rscrypt(&rscrypt), Err(CheckError::InvalidFormat));
+    for &hashed in ["", "$rscrypt$", "$scrypt$", "$rscrypt$0$BAgB$$"].iter() {
+        assert_eq!(rscrypt_to_phc(hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+        assert_eq!(phc_to_rscrypt(hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+    }
+}
@@ -0,0 +1,67 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use scrypt::{cost_of, crypt7, scrypt_simple, scrypt_simple_ad, scrypt_simple_cisco,
+    scrypt_simple_django, scrypt_simple_hex, scrypt_simple_lambdaworks,
+    scrypt_simple_peppered, scrypt_simple_phc, scrypt_simple_url_safe, scrypt_simple_v2,
+    scrypt_simple_werkzeug, sodium, wrap_legacy_hash, LegacyDigest, Pepper, Params};
+use scrypt::errors::CheckError;
+
+#[test]
+fn test_cost_of_formats() {
+    let params = Params::new(5, 8, 2).unwrap();
+    let pepper = Pepper { id: 1, key: b"key" };
+    let setting = crypt7::gensalt(&params).unwrap();
+    let hashes = [
+        scrypt_simple("password", &params).unwrap(),
+        scrypt_simple_url_safe("password", &params).unwrap(),
+        scrypt_simple_v2("password", &params).unwrap(),
+        scrypt_simple_peppered("password", &params, &pepper).unwrap(),
+        scrypt_simple_ad("password", &params, b"tenant").unwrap(),
+        scrypt_simple_phc("password", &params).unwrap(),
+        scrypt_simple_lambdaworks("password", &params).unwrap(),
+        scrypt_simple_django("password", &params).unwrap(),
+        scrypt_simple_werkzeug("password", &params).unwrap(),
+        scrypt_simple_hex("password", &params).unwrap(),
+        crypt7::crypt(b"password", &setting).unwrap(),
+        sodium::hash(b"password", &params).unwrap(),
+        wrap_legacy_hash(LegacyDigest::Md5, &[0; 16], &params).unwrap(),
+    ];
+    for hashed in hashes.iter() {
+        let cost = cost_of(hashed).unwrap();
+        assert_eq!(cost.params, params, "{}", hashed);
+        assert_eq!(cost.memory_bytes, 128 * 8 * (32 + 2 + 1), "{}", hashed);
+        assert_eq!(cost.approx_ops, 4 * 32 * 8 * 2, "{}", hashed);
+    }
+
+    // Cisco uses fixed parameters
+    let cost = cost_of(&scrypt_simple_cisco("password").unwrap()).unwrap();
+    assert_eq!((cost.params.log_n(), cost.params.r(), cost.params.p()), (14, 1, 1));
+    assert_eq!(cost.memory_bytes, 128 * ((1 << 14) + 2));
+}
+
+#[test]
+fn test_cost_of_hostile() {
+    // log_n = 40, r = 8: a petabyte, which must not be allocated
+    let hashed = "$rscrypt$1$KAgAAAABAAAA$AAECAwQFBgcICQoLDA0ODw==$\
+                  AAECAwQFBgcICQoLDA0ODwABAgMEBQYHCAkKCwwNDg8=$";
+    let cost = cost_of(hashed).unwrap();
+    assert_eq!(cost.memory_bytes, 128 * 8 * ((1u128 << 40) + 2));
+    assert_eq!(cost.approx_ops, (4 * 8) << 40);
+    let phc = "$scrypt$ln=20,r=2,p=1000000$AAECAwQFBgcICQoLDA0ODw$\
+               AAECAwQFBgcICQoLDA0ODwABAgMEBQYHCAkKCwwNDg8";
+    assert_eq!(cost_of(phc).unwrap().approx_ops, (8 * 1_000_000) << 20);
+
+    // parameters scrypt cannot run are rejected
+    for &hashed in [
+        "$scrypt$ln=64,r=8,p=1$AAECAwQFBgcICQoLDA0ODw$AAECAwQFBgcICQoLDA0ODwABAgMEBQYHCAkKCwwNDg8",
+        "$scrypt$ln=4,r=8,p=999999999999$AAECAwQFBgcICQoLDA0ODw$AAECAwQFBgcICQoLDA0ODw",
+    ].iter() {
+        assert_eq!(cost_of(hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+    }
+    assert_eq!(cost_of("$argon2id$v=19$m=65536,t=2,p=1$c2FsdA$aGFzaA"),
+        Err(CheckError::InvalidFormat));
+    let wrapped = wrap_legacy_hash(LegacyDigest::Sha1, &[0; 20], &Params::new(4, 8, 1)
+        .unwrap()).unwrap();
+    assert_eq!(cost_of(&wrapped.replacen("sha1", "sha3", 1)), Err(CheckError::UnsupportedFormat));
+}
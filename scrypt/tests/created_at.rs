@@ -0,0 +1,164 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::SeedableRng;
+use rand::prng::ChaChaRng;
+
+use scrypt::errors::{CheckError, HashError};
+use scrypt::{needs_rehash, needs_rotation, needs_rotation_at, scrypt_check,
+    scrypt_check_and_rehash, scrypt_check_and_rehash_with_rng, scrypt_simple, Format, Params,
+    ScryptHash, ScryptHashRef, SimpleConfig};
+
+/// "password" under "NaCl", `log_n = 4`, `r = 8` and `p = 1`, created on
+/// day 20000, 2024-10-04.
+const DATED: &str =
+    "$rscrypt$2$BAgAAAABAAAABCBAIE4AAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM";
+/// `DATED` without the flag and the day.
+const UNDATED: &str =
+    "$rscrypt$2$BAgAAAABAAAABCAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM";
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// The current day in days since the Unix epoch.
+fn today() -> u32 {
+    (SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() / DAY) as u32
+}
+
+/// Check that `hashed` was created between `before` and the current day.
+fn assert_created_since(hashed: &str, before: u32) {
+    let days = ScryptHash::parse(hashed).unwrap().created_at().unwrap();
+    assert!(before <= days && days <= today(), "{} not in {}..={}", days, before, today());
+}
+
+#[test]
+fn test_parse() {
+    let hash = ScryptHash::parse(DATED).unwrap();
+    assert_eq!(hash.created_at(), Some(20000));
+    assert_eq!(hash.format_version(), Some(2));
+    assert_eq!(hash.to_string(), DATED);
+    assert_eq!(ScryptHashRef::parse(DATED).unwrap().created_at(), Some(20000));
+    assert_eq!(ScryptHash::from(ScryptHashRef::parse(DATED).unwrap()), hash);
+    // the day is not part of the computation
+    assert_eq!(scrypt_check("password", DATED), Ok(()));
+    assert_eq!(scrypt_check("wrong", DATED), Err(CheckError::HashMismatch));
+    assert_eq!(ScryptHash::parse(UNDATED).unwrap().parts(), hash.parts());
+
+    // strings without the day report none
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashes = [UNDATED.to_string(), scrypt_simple("password", &params).unwrap(),
+        "$scrypt$ln=4,r=8,p=1$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM".to_string()];
+    for hashed in hashes.iter() {
+        assert_eq!(ScryptHash::parse(hashed).unwrap().created_at(), None, "{}", hashed);
+        assert_eq!(needs_rotation(hashed, Duration::from_secs(0)), Ok(false));
+    }
+    assert_eq!(ScryptHashRef::parse(UNDATED).unwrap().created_at(), None);
+}
+
+#[test]
+fn test_malformed() {
+    let hashes = [
+        // combined with the pepper flag
+        "$rscrypt$2$BAgAAAABAAAABCBBIE4AAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+        // a day of two bytes
+        "$rscrypt$2$BAgAAAABAAAABCBAIE4$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+        // the day without the flag
+        "$rscrypt$2$BAgAAAABAAAABCAAIE4AAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+    ];
+    for &hashed in hashes.iter() {
+        assert_eq!(scrypt_check("password", hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+        assert_eq!(ScryptHash::parse(hashed), Err(CheckError::InvalidFormat));
+        assert_eq!(needs_rotation(hashed, Duration::from_secs(0)),
+            Err(CheckError::InvalidFormat));
+    }
+}
+
+#[test]
+fn test_emission() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let config = SimpleConfig::builder().params(params).record_created_at(true).build().unwrap();
+    assert!(config.records_created_at());
+    let before = today();
+    let hashed = config.hash_with_rng("password", &mut ChaChaRng::from_seed([1; 32])).unwrap();
+    assert!(hashed.starts_with("$rscrypt$2$"), "{}", hashed);
+    assert_created_since(&hashed, before);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(config.verify("password", &hashed).map(|_| ()), Ok(()));
+
+    // the same salt and hash as without the day
+    let plain = SimpleConfig::builder().params(params).build().unwrap();
+    assert!(!plain.records_created_at());
+    let undated = plain.hash_with_rng("password", &mut ChaChaRng::from_seed([1; 32])).unwrap();
+    assert_eq!(ScryptHash::parse(&undated).unwrap().parts(),
+        ScryptHash::parse(&hashed).unwrap().parts());
+    assert_eq!(ScryptHash::parse(&undated).unwrap().created_at(), None);
+
+    let hash = ScryptHash::generate("password", &config, &mut ChaChaRng::from_seed([1; 32]))
+        .unwrap();
+    assert_eq!(hash.format_version(), Some(2));
+    assert_created_since(&hash.to_string(), before);
+    assert_eq!(hash.to_string(), hashed);
+
+    // other formats can not record the day
+    let err = SimpleConfig::builder().format(Format::Phc).record_created_at(true).build();
+    assert!(matches!(err, Err(HashError::UnsupportedParams)));
+}
+
+#[test]
+fn test_rotation_boundary() {
+    let created = UNIX_EPOCH + Duration::from_secs(20000 * DAY);
+    let max_age = Duration::from_secs(90 * DAY);
+    let rotation = |now| needs_rotation_at(DATED, max_age, now).unwrap();
+    // the age counts from the start of the day
+    assert!(!rotation(created));
+    assert!(!rotation(created + max_age - Duration::from_secs(1)));
+    assert!(rotation(created + max_age));
+    assert!(rotation(created + max_age + Duration::from_secs(DAY)));
+    // a day after the current one is not old
+    assert!(!rotation(created - Duration::from_secs(1)));
+    assert!(!rotation(UNIX_EPOCH));
+
+    assert_eq!(needs_rotation_at(DATED, Duration::from_secs(0), created), Ok(true));
+    assert_eq!(needs_rotation_at(UNDATED, Duration::from_secs(0), created), Ok(false));
+    assert_eq!(needs_rotation(DATED, max_age), Ok(true));
+    assert_eq!(needs_rotation("$unknown$", max_age), Err(CheckError::UnsupportedFormat));
+
+    // rotation is independent of the parameters
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashed = SimpleConfig::builder().params(params).record_created_at(true).build().unwrap()
+        .hash_with("password").unwrap();
+    assert_eq!(needs_rehash(&hashed, &params), Ok(false));
+    assert_eq!(needs_rotation(&hashed, max_age), Ok(false));
+    assert_eq!(needs_rotation(&hashed, Duration::from_secs(2 * DAY)), Ok(false));
+    assert_eq!(needs_rehash(&hashed, &Params::new(5, 8, 1).unwrap()), Ok(true));
+}
+
+#[test]
+fn test_check_and_rehash() {
+    let before = today();
+    let desired = Params::new(5, 8, 1).unwrap();
+    let mut rng = ChaChaRng::from_seed([2; 32]);
+    let rehashed = scrypt_check_and_rehash_with_rng("password", DATED, &desired, &mut rng)
+        .unwrap()
+        .unwrap();
+    // the new hash records the day of the rehash
+    assert_created_since(&rehashed, before);
+    assert_eq!(ScryptHash::parse(&rehashed).unwrap().params(), desired);
+    assert_eq!(needs_rotation(&rehashed, Duration::from_secs(90 * DAY)), Ok(false));
+    assert_eq!(scrypt_check("password", &rehashed), Ok(()));
+
+    // hashes without the day stay without it
+    let rehashed = scrypt_check_and_rehash("password", UNDATED, &desired).unwrap().unwrap();
+    assert_eq!(ScryptHash::parse(&rehashed).unwrap().created_at(), None);
+
+    // up to date hashes are not rehashed, whatever their age
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashed = SimpleConfig::builder().params(params).record_created_at(true).build().unwrap()
+        .hash_with("password").unwrap();
+    assert_eq!(needs_rotation(&hashed, Duration::from_secs(0)), Ok(true));
+    assert_eq!(scrypt_check_and_rehash("password", &hashed, &params), Ok(None));
+    assert_eq!(scrypt_check_and_rehash("wrong", DATED, &desired),
+        Err(CheckError::HashMismatch));
+}
@@ -0,0 +1,117 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use scrypt::{credfile, scrypt_simple, Params};
+use scrypt::errors::{CheckError, HashError, LineErrorKind};
+
+fn params() -> Params {
+    Params::new(4, 8, 1).unwrap()
+}
+
+fn sample() -> String {
+    let alice = scrypt_simple("alice's password", &params()).unwrap();
+    let bob = scrypt_simple("bob's password", &params()).unwrap();
+    let mallory = scrypt_simple("mallory's password", &params()).unwrap();
+    format!("# users of the build server\n\
+             alice:{}\n\
+             \n\
+             bob:{}\n\
+             alice:{}\n\
+             carol\n\
+             :{}\n\
+             dave:$rscrypt$0$xx$\n\
+             # end\n", alice, bob, mallory, bob)
+}
+
+#[test]
+fn test_roundtrip() {
+    let text = sample();
+    let file = credfile::load(text.as_bytes()).unwrap();
+    let mut written = Vec::new();
+    file.write(&mut written).unwrap();
+    assert_eq!(String::from_utf8(written).unwrap(), text);
+    assert_eq!(file.users(), vec!["alice", "bob"]);
+}
+
+#[test]
+fn test_errors() {
+    let file = credfile::load(sample().as_bytes()).unwrap();
+    let errors: Vec<_> = file.errors().iter().map(|e| (e.line(), e.kind())).collect();
+    assert_eq!(errors, vec![
+        (5, LineErrorKind::DuplicateUser),
+        (6, LineErrorKind::MissingSeparator),
+        (7, LineErrorKind::EmptyUser),
+        (8, LineErrorKind::InvalidHash(CheckError::InvalidFormat)),
+    ]);
+    assert_eq!(file.errors()[1].to_string(), "line 6: missing `:`");
+}
+
+#[test]
+fn test_verify() {
+    let file = credfile::load(sample().as_bytes()).unwrap();
+    assert_eq!(file.verify("alice", "alice's password"), Ok(()));
+    assert_eq!(file.verify("bob", "bob's password"), Ok(()));
+    // the first entry of a user wins
+    assert_eq!(file.verify("alice", "mallory's password"), Err(CheckError::HashMismatch));
+    assert_eq!(file.verify("bob", "alice's password"), Err(CheckError::HashMismatch));
+    // unknown users and malformed lines do not verify
+    assert_eq!(file.verify("nobody", "alice's password"), Err(CheckError::HashMismatch));
+    assert_eq!(file.verify("carol", ""), Err(CheckError::HashMismatch));
+    assert_eq!(file.verify("dave", ""), Err(CheckError::HashMismatch));
+
+    let empty = credfile::load(&b""[..]).unwrap();
+    assert_eq!(empty.verify("alice", "alice's password"), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_set_and_remove() {
+    let text = sample();
+    let mut file = credfile::load(text.as_bytes()).unwrap();
+    file.set("alice", "new password", &params()).unwrap();
+    file.set("erin", "erin's password", &params()).unwrap();
+    assert_eq!(file.verify("alice", "new password"), Ok(()));
+    assert_eq!(file.verify("alice", "alice's password"), Err(CheckError::HashMismatch));
+    assert_eq!(file.verify("erin", "erin's password"), Ok(()));
+    assert_eq!(file.users(), vec!["alice", "bob", "erin"]);
+
+    assert!(file.remove("bob"));
+    assert!(!file.remove("bob"));
+    assert_eq!(file.verify("bob", "bob's password"), Err(CheckError::HashMismatch));
+
+    let mut written = Vec::new();
+    file.write(&mut written).unwrap();
+    let written = String::from_utf8(written).unwrap();
+    let lines: Vec<&str> = written.lines().collect();
+    let original: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), original.len());
+    assert!(lines[1].starts_with("alice:$rscrypt$") && lines[1] != original[1]);
+    assert_eq!(lines[2], original[2]);
+    assert_eq!(&lines[3..8], &original[4..9]);
+    assert!(lines[8].starts_with("erin:$rscrypt$"));
+
+    let reloaded = credfile::load(written.as_bytes()).unwrap();
+    assert_eq!(reloaded.verify("erin", "erin's password"), Ok(()));
+    assert_eq!(reloaded.errors().len(), 4);
+}
+
+#[test]
+fn test_crlf() {
+    let text = sample();
+    let file = credfile::load(text.replace('\n', "\r\n").as_bytes()).unwrap();
+    assert_eq!(file.verify("bob", "bob's password"), Ok(()));
+    let mut written = Vec::new();
+    file.write(&mut written).unwrap();
+    assert_eq!(String::from_utf8(written).unwrap(), text);
+}
+
+#[test]
+fn test_set_invalid_user() {
+    let mut file = credfile::load(&b""[..]).unwrap();
+    for &user in ["", "eve:admin", "eve\nmallory:$rscrypt$", "eve\r"].iter() {
+        match file.set(user, "password", &params()) {
+            Err(HashError::InvalidUser) => {}
+            other => panic!("{:?}: {:?}", user, other),
+        }
+    }
+    assert!(file.users().is_empty());
+}
@@ -0,0 +1,92 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use scrypt::{scrypt_check, Params};
+use scrypt::crypt7::{crypt, gensalt};
+use scrypt::errors::CheckError;
+
+// Generated with libxcrypt's `crypt(3)`, the first two from settings returned
+// by its `crypt_gensalt("$7$", ...)`.
+const FIXTURES: &[(&str, &str)] = &[
+    (
+        "password",
+        "$7$CU..../....0MwH40nbd5gVP9nfV712T0$n2x65d5y2jy1N3ypR2GOR5BC5YF0ywXEHt6K04LcmYA",
+    ),
+    (
+        "Ünïcødé",
+        "$7$BU..../....hJKHBLa1ppp9dmtVn2oGA1$gzYDkxxFMarl0OV8NgqF8qAmIy79sINM/9TtiRJM3b7",
+    ),
+    (
+        "password",
+        "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+    ),
+    (
+        "pw",
+        "$7$4/..../....$bcEdqHd4SH2Nwpxejxfz8p/H5diP7c42IWu14ih7M3B",
+    ),
+    (
+        "",
+        "$7$3/..../0...x./Y$9EGwbAHmtgpZX/l5IemVezkAvOE.bEYbPcHTVRyyRr1",
+    ),
+    (
+        "pleaseletmein",
+        "$7$5/..../....SodiumChloride$bU5hdDWH10UDUYZ/xA3KG2mCOaUma63/AafKzfboQB1",
+    ),
+    (
+        "correct horse",
+        "$7$56..../....a/b.c/d.e/f.g/h.i/j.k/l.m/n.o/p.q/r.s/t.u/v.w/x.y/$\
+         YY5ILoPmX89pgl265MlSQieAfBompKiQbdUmxkj1gy6",
+    ),
+];
+
+#[test]
+fn test_crypt7_fixtures() {
+    for &(password, hashed) in FIXTURES {
+        assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("wrong", hashed), Err(CheckError::HashMismatch));
+        assert_eq!(crypt(password.as_bytes(), hashed).unwrap(), hashed);
+    }
+}
+
+#[test]
+fn test_crypt7_gensalt() {
+    let params = Params::new(4, 8, 2).unwrap();
+    let setting = gensalt(&params).unwrap();
+    assert!(setting.starts_with("$7$26....0...."));
+    assert_eq!(setting.len(), 3 + 11 + 22);
+    assert!(setting != gensalt(&params).unwrap());
+
+    let hashed = crypt(b"password", &setting).unwrap();
+    assert!(hashed.starts_with(&setting));
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_crypt7_malformed() {
+    let invalid = [
+        // missing hash
+        "$7$2/..../....saltsalt",
+        "$7$2/..../....saltsalt$",
+        // truncated parameters
+        "$7$2/..../...",
+        // log_n of 0 and r * p too large
+        "$7$./..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+        "$7$2zzzzzzzzzzsaltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+        // characters outside of the alphabet
+        "$7$2/..../....salt-salt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+        "$7$2/..!./....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+        "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosd+1",
+        // non-canonical trailing bits and impossible lengths
+        "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdYz",
+        "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1F",
+        // trailing data
+        "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1$",
+        "$7$2/..../ü...saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(scrypt_check("password", hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+    assert_eq!(crypt(b"password", "$7$2/..../"), Err(CheckError::InvalidFormat));
+}
@@ -0,0 +1,79 @@
+#![cfg(feature="simple")]
+extern crate base64ct;
+extern crate rand;
+extern crate scrypt;
+
+use base64ct::{Base64, Base64Unpadded, Base64UrlUnpadded, Encoding};
+use rand::{Rng, SeedableRng};
+use rand::prng::XorShiftRng;
+
+use scrypt::{ScryptHash, ScryptHashRef};
+
+// 32 bytes of hash, so every v0 string has padding and the standard alphabet
+const HASH: &str = "9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=";
+const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=*. ";
+
+fn random_field(rng: &mut XorShiftRng) -> String {
+    let len = rng.gen_range(0, 13);
+    (0..len).map(|_| CHARS[rng.gen_range(0, CHARS.len())] as char).collect()
+}
+
+#[test]
+fn test_decodes_like_base64() {
+    let mut rng = XorShiftRng::from_seed(*b"constant time b6");
+    for _ in 0..200 {
+        let len = rng.gen_range(0, 64);
+        let salt: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        let hash: Vec<u8> = (0..rng.gen_range(1, 65)).map(|_| rng.gen()).collect();
+        let hashes = [
+            format!("$rscrypt$0$BAgB${}${}$",
+                Base64::encode_string(&salt), Base64::encode_string(&hash)),
+            format!("$rscrypt$0$BAgB${}${}$",
+                Base64UrlUnpadded::encode_string(&salt),
+                Base64UrlUnpadded::encode_string(&hash)),
+            format!("$scrypt$ln=4,r=8,p=1${}${}",
+                Base64Unpadded::encode_string(&salt),
+                Base64Unpadded::encode_string(&hash)),
+        ];
+        for hashed in hashes.iter() {
+            // PHC strings need a salt
+            if salt.is_empty() && hashed.starts_with("$scrypt$") { continue; }
+            let parsed = ScryptHash::parse(hashed).unwrap();
+            assert_eq!((parsed.salt(), parsed.hash_bytes()), (&salt[..], &hash[..]), "{}", hashed);
+            let parsed = ScryptHashRef::parse(hashed).unwrap();
+            assert_eq!((parsed.salt(), parsed.hash_bytes()), (&salt[..], &hash[..]), "{}", hashed);
+        }
+    }
+}
+
+#[test]
+fn test_rejects_like_base64() {
+    let mut rng = XorShiftRng::from_seed(*b"malformed base64");
+    for _ in 0..5000 {
+        let field = random_field(&mut rng);
+        let hashed = format!("$rscrypt$0$BAgB${}${}$", field, HASH);
+        // canonical padded base64 is the only valid salt of a v0 string,
+        // the only base64 `base64ct` decodes
+        let expected = Base64::decode_vec(&field).ok();
+        let parsed = ScryptHash::parse(&hashed).ok();
+        assert_eq!(parsed.as_ref().map(|p| p.salt()), expected.as_ref().map(|s| &s[..]),
+            "{:?}", field);
+        let parsed = ScryptHashRef::parse(&hashed).ok();
+        assert_eq!(parsed.as_ref().map(|p| p.salt()), expected.as_ref().map(|s| &s[..]),
+            "{:?}", field);
+    }
+}
+
+#[test]
+fn test_malformed_fields() {
+    let salts = ["TmFDbA=", "TmFDbA===", "TmFDb", "TmF=bA==", "=TmFDbA=", "TmFD*A==",
+        "TmFD\u{e9}A==", "TmFDbA==TmFDbA==", "TmFDbA-="];
+    for &salt in salts.iter() {
+        let hashed = format!("$rscrypt$0$BAgB${}${}$", salt, HASH);
+        assert!(ScryptHash::parse(&hashed).is_err(), "{}", salt);
+        assert!(ScryptHashRef::parse(&hashed).is_err(), "{}", salt);
+        let hashed = format!("$scrypt$ln=4,r=8,p=1${}$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+            salt);
+        assert!(ScryptHash::parse(&hashed).is_err(), "{}", salt);
+    }
+}
@@ -0,0 +1,55 @@
+#![cfg(feature="simple-verify")]
+extern crate scrypt;
+
+use scrypt::{HashParts, ScryptHash, Params};
+use scrypt::kdf;
+
+const V0: &str = "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$";
+const PHC: &str = "$scrypt$ln=4,r=8,p=1$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM";
+
+fn parts(salt: &[u8]) -> HashParts {
+    HashParts::new(b"password", &Params::new(4, 8, 1).unwrap(), salt, 32).unwrap()
+}
+
+#[test]
+fn test_hash_parts() {
+    let parts = parts(b"NaCl");
+    let debug = format!("{:?}", parts);
+    assert_eq!(debug, "HashParts { params: Params { log_n: 4, r: 8, p: 1 }, \
+                       salt: <4 bytes, fingerprint dbcd6d34>, hash: <32 bytes, redacted> }");
+    assert_eq!(parts.reveal_debug(),
+        format!("HashParts {{ params: {:?}, salt: {:?}, hash: {:?} }}",
+            parts.params, parts.salt, parts.hash));
+}
+
+#[test]
+fn test_fingerprint_is_stable() {
+    let debug = |salt: &[u8]| format!("{:?}", parts(salt));
+    assert_eq!(debug(b"NaCl"), debug(b"NaCl"));
+    assert_ne!(debug(b"NaCl"), debug(b"NaCm"));
+    // the same salt gives the same fingerprint in every format and type
+    let rscrypt = format!("{:?}", ScryptHash::parse(V0).unwrap());
+    let phc = format!("{:?}", ScryptHash::parse(PHC).unwrap());
+    let salt = "salt: <4 bytes, fingerprint dbcd6d34>";
+    assert!(rscrypt.contains(salt) && phc.contains(salt) && debug(b"NaCl").contains(salt));
+}
+
+#[test]
+fn test_kdf_record() {
+    // `tests/kdf.rs` has the password of this record
+    let hex = "73637279707400060000000200000001e907887a246da9da57fb85a4b459f9e0\
+               31fdd5e29e6d9e6bc5d351ac84a095518b70c1a82894418350a34c0336986e0b\
+               cd986bd80fc0a84208600a5fca050a0ff331c311ea2d45489450129ae7c6df29";
+    let bytes: Vec<u8> = (0..hex.len() / 2)
+        .map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap())
+        .collect();
+    let record = kdf::parse(&bytes).unwrap();
+    let debug = format!("{:?}", record);
+    assert!(debug.starts_with("KdfRecord { params: Params { log_n: 6, r: 2, p: 1 }, \
+                               salt: <32 bytes, fingerprint "), "{}", debug);
+    assert!(debug.ends_with(">, hmac: <32 bytes, redacted> }"), "{}", debug);
+    assert!(!debug.contains("233") && !debug.contains("205"));
+    assert!(record.reveal_debug().contains("salt: [233, 7, 136"));
+    assert!(record.reveal_debug().contains("hmac: [205, 152, 107"));
+    assert_eq!(debug, format!("{:?}", kdf::parse(&bytes).unwrap()));
+}
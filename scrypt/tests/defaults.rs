@@ -0,0 +1,36 @@
+//! Pins the defaults of `hash_password()`. A change of these tests must be
+//! deliberate and listed in CHANGELOG.md.
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use scrypt::{cost_of, hash_password, scrypt_check, verify_password, Format, ScryptHash,
+    Params, SimpleConfig};
+use scrypt::errors::CheckError;
+
+#[test]
+fn test_hash_password_defaults() {
+    let stored = hash_password("hunter2").unwrap();
+    let parts = ScryptHash::parse(&stored).unwrap();
+    assert_eq!(parts.format(), Format::Rscrypt);
+    assert_eq!(parts.params(), Params::new(15, 8, 1).unwrap());
+    assert_eq!(parts.salt().len(), 16);
+    assert_eq!(parts.hash_bytes().len(), 32);
+    assert_eq!(cost_of(&stored).unwrap().memory_bytes, 128 * 8 * ((1 << 15) + 2));
+    // the version 0 string of `log_n = 15, r = 8, p = 1`
+    assert!(stored.starts_with("$rscrypt$0$DwgB$"));
+    assert_eq!(stored.len(), 86);
+
+    let config = SimpleConfig::default();
+    assert_eq!((config.params(), config.salt_len(), config.dk_len(), config.format()),
+        (parts.params(), 16, 32, Format::Rscrypt));
+}
+
+#[test]
+fn test_verify_password() {
+    let stored = hash_password("hunter2").unwrap();
+    assert_eq!(verify_password("hunter2", &stored), Ok(()));
+    assert_eq!(scrypt_check("hunter2", &stored), Ok(()));
+    assert_eq!(verify_password("hunter3", &stored), Err(CheckError::HashMismatch));
+    assert_eq!(verify_password("hunter2", "$rscrypt$"), Err(CheckError::InvalidFormat));
+    assert_ne!(hash_password("hunter2").unwrap(), stored);
+}
@@ -0,0 +1,140 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::{scrypt_check, scrypt_simple_django, Format, Params, SimpleConfig};
+use scrypt::errors::CheckError;
+
+const FIXTURES: &[(&str, &str)] = &[
+    // from Django's own test suite
+    (
+        "lètmein",
+        "scrypt$16384$seasalt$8$1$Qj3+9PPyRjSJIebHnG81TMjsqtaIGxNQG/aEB/NYafTJ7t\
+         ibgfYz71m0ldQESkXFRkdVCBhhY8mx7rQwite/Pw==",
+    ),
+    // generated with Python's `hashlib.scrypt`, following
+    // `ScryptPasswordHasher.encode`
+    (
+        "password",
+        "scrypt$1024$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1$8o+UXoyVmLUMNMva1u++F60z8m/s4Zu\
+         OJl8PF7yvO1D772qNhsEtEq4Sxx1271PyFoVcdiru2f3g9PqXXEXTtg==",
+    ),
+    (
+        "pleaseletmein",
+        "scrypt$16$NaCl+/=:$4$3$DnwvgKmwHYAHXipj9NETvn+TTqWylESYhMj7GZWx3Q8C+/g\
+         nnY7ZibdgtGayC8c7+ZZfsMTFGlepsEefTB5ESw==",
+    ),
+    (
+        "Ünïcødé",
+        "scrypt$2$sël$1$1$MbY5a6bob9Ls6RaeNUjmfa+noDsixdIwX+tWM5gfDxMEeztvVeNZ+\
+         2WDCMX5Zch449tsPzQMS0FZN2LOHt9O2Q==",
+    ),
+    (
+        "",
+        "scrypt$256$x$2$8$5nYOeoHCZez6JB9VlrSHij/Nd3PvHSP+gAqpdgeQKZXnOITXuTqyEY\
+         tWeWXC3gIezOpMU7dmou7EHiLVz4sR7w==",
+    ),
+];
+
+#[test]
+fn test_django_fixtures() {
+    for &(password, hashed) in FIXTURES {
+        assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+        assert_eq!(
+            scrypt_check("wrong", hashed),
+            Err(CheckError::HashMismatch),
+            "{}", hashed
+        );
+    }
+}
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_django_fixed_salt() {
+    // bytes above 247 are skipped, the others are taken modulo 62
+    let bytes = vec![
+        248, 52, 62, 152, 215, 17, 253, 83, 167, 220, 56, 71, 250, 136, 191,
+        53, 113, 166, 255, 244, 37, 101, 172, 186, 249, 61, 104,
+    ];
+    let params = Params::new(10, 8, 1).unwrap();
+    let config = SimpleConfig::builder().params(params).format(Format::Django).build().unwrap();
+    let hashed = config.hash_with_rng("password", &mut FixedRng(bytes)).unwrap();
+    assert_eq!(hashed, FIXTURES[1].1);
+}
+
+#[test]
+fn test_django_round_trip() {
+    let params = Params::new(4, 2, 3).unwrap();
+    let hashed = scrypt_simple_django("password", &params).unwrap();
+    let fields: Vec<&str> = hashed.split('$').collect();
+    assert_eq!(fields.len(), 6);
+    assert_eq!((fields[0], fields[1], fields[3], fields[4]), ("scrypt", "16", "2", "3"));
+    assert_eq!(fields[2].len(), 22);
+    assert!(fields[2].bytes().all(|b| b.is_ascii_alphanumeric()));
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_django_malformed() {
+    let hash = "8o+UXoyVmLUMNMva1u++F60z8m/s4ZuOJl8PF7yvO1D772qNhsEtEq4Sxx1271Py\
+                FoVcdiru2f3g9PqXXEXTtg==";
+    let invalid_fields = [
+        // N must be a power of two larger than one, no log2
+        "10$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1",
+        "1000$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1",
+        "1$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1",
+        "0$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1",
+        "+1024$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1",
+        "01024$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1",
+        // empty salt, invalid r and p
+        "1024$$8$1",
+        "1024$0aCDrvRI4jmf1ZQ6LNWa9Q$0$1",
+        "1024$0aCDrvRI4jmf1ZQ6LNWa9Q$8$0",
+        "1024$0aCDrvRI4jmf1ZQ6LNWa9Q$8$-1",
+        "1024$0aCDrvRI4jmf1ZQ6LNWa9Q$8",
+        "1024$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1$1",
+    ];
+    for fields in invalid_fields.iter() {
+        let hashed = format!("scrypt${}${}", fields, hash);
+        assert_eq!(scrypt_check("password", &hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+
+    let invalid = [
+        "scrypt$1024$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1$",
+        // 256-bit hash and missing padding
+        "scrypt$1024$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1$\
+         8o+UXoyVmLUMNMva1u++F60z8m/s4ZuOJl8PF7yvO1A=",
+        "scrypt$1024$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1$8o+UXoyVmLUMNMva1u++F60z8m/s4Zu\
+         OJl8PF7yvO1D772qNhsEtEq4Sxx1271PyFoVcdiru2f3g9PqXXEXTtg",
+        // non-canonical trailing bits
+        "scrypt$1024$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1$8o+UXoyVmLUMNMva1u++F60z8m/s4Zu\
+         OJl8PF7yvO1D772qNhsEtEq4Sxx1271PyFoVcdiru2f3g9PqXXEXTth==",
+        "$scrypt$1024$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1$8o+UXoyVmLUMNMva1u++F60z8m/s4Zu\
+         OJl8PF7yvO1D772qNhsEtEq4Sxx1271PyFoVcdiru2f3g9PqXXEXTtg==",
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(scrypt_check("password", hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+}
@@ -0,0 +1,58 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use std::time::{Duration, Instant};
+
+use scrypt::{scrypt_simple, verify_or_dummy, Params};
+use scrypt::errors::CheckError;
+
+#[test]
+fn test_verify_or_dummy() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashed = scrypt_simple("password", &params).unwrap();
+
+    assert_eq!(verify_or_dummy("password", Some(&hashed), &params), Ok(()));
+    assert_eq!(
+        verify_or_dummy("wrong", Some(&hashed), &params),
+        Err(CheckError::HashMismatch)
+    );
+    assert_eq!(
+        verify_or_dummy("password", Some("$rscrypt$"), &params),
+        Err(CheckError::InvalidFormat)
+    );
+    // no password matches the dummy hash
+    for &password in ["password", "", "scrypt dummy sal"].iter() {
+        assert_eq!(verify_or_dummy(password, None, &params), Err(CheckError::HashMismatch));
+    }
+}
+
+/// The fastest of several runs, which is least affected by other tests.
+fn fastest<F: Fn()>(f: F) -> Duration {
+    (0..5).map(|_| {
+        let start = Instant::now();
+        f();
+        start.elapsed()
+    }).min().unwrap()
+}
+
+#[test]
+fn test_verify_or_dummy_timing() {
+    let params = Params::new(12, 8, 1).unwrap();
+    let hashed = scrypt_simple("password", &params).unwrap();
+
+    let known = fastest(|| {
+        assert!(verify_or_dummy("wrong", Some(&hashed), &params).is_err());
+    });
+    let unknown = fastest(|| {
+        assert!(verify_or_dummy("wrong", None, &params).is_err());
+    });
+    // a coarse band, the dummy path must not skip the work
+    assert!(unknown * 2 > known && known * 2 > unknown, "{:?} {:?}", known, unknown);
+
+    // and it uses the given parameters
+    let cheap = Params::new(4, 8, 1).unwrap();
+    let cheap = fastest(|| {
+        assert!(verify_or_dummy("wrong", None, &cheap).is_err());
+    });
+    assert!(cheap * 10 < unknown, "{:?} {:?}", cheap, unknown);
+}
@@ -0,0 +1,258 @@
+#![cfg(feature="enc")]
+extern crate rand;
+extern crate scrypt;
+
+use std::io::{self, Read, Write};
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::Params;
+use scrypt::enc::{decrypt, encrypt, encrypt_with_rng, params, Decryptor, Encryptor};
+use scrypt::errors::{EncError, ErrorCode};
+
+/// Password, plaintext, `(log_n, r, p)` and the encrypted data in hex.
+type Fixture = (&'static str, &'static str, (u8, u32, u32), &'static str);
+
+// Generated with Python's `hashlib.scrypt` and the AES-256-CTR of the
+// `cryptography` package, following the format of `scrypt enc`.
+const FIXTURES: &[Fixture] = &[
+    ("password", "The quick brown fox jumps over the lazy dog.\n", (10, 8, 1),
+     "736372797074000a0000000800000001000102030405060708090a0b0c0d0e0f\
+      101112131415161718191a1b1c1d1e1fda46ceb5d5738b6fc865e137d56ab589\
+      05f46646ed2d05b149c69c790d806299b825cd8f16b420e41f46771bc4b04311\
+      368d0566d7a3b974e3c3fca7960e3bc2c504bcfc27ce21b7d85af58032acfb56\
+      b7cb5ed96e00254b1609081335dbb789e84a2d06cd9e137def52535e37f3807e\
+      98f240dcf430c712b6f30c99f4"),
+    ("pleaseletmein", "", (4, 2, 2),
+     "736372797074000400000002000000026465666768696a6b6c6d6e6f70717273\
+      7475767778797a7b7c7d7e7f80818283b06687326fffc8611b007846f88cacc4\
+      5640f6876f210abb7aea0f1fdcabbcb94e9c27027ea03d6396e134f2fa50ee30\
+      4b48c13ca59e51e8cce3f98cb556a7ad0a44b412ff14942f307942bf74c15d6a"),
+];
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+}
+
+fn fixture() -> Vec<u8> {
+    hex(FIXTURES[0].3)
+}
+
+#[test]
+fn test_decrypt_fixtures() {
+    for &(password, plaintext, (log_n, r, p), ciphertext) in FIXTURES {
+        let ciphertext = hex(ciphertext);
+        assert_eq!(decrypt(password.as_bytes(), &ciphertext).unwrap(), plaintext.as_bytes());
+        let params = params(&ciphertext).unwrap();
+        assert_eq!((params.log_n(), params.r(), params.p()), (log_n, r, p));
+    }
+}
+
+#[test]
+fn test_encrypt_fixtures() {
+    for &(password, plaintext, (log_n, r, p), ciphertext) in FIXTURES {
+        let ciphertext = hex(ciphertext);
+        let params = Params::new(log_n, r, p).unwrap();
+        let mut rng = FixedRng(ciphertext[16..48].to_vec());
+        let encrypted = encrypt_with_rng(
+            password.as_bytes(), plaintext.as_bytes(), &params, &mut rng).unwrap();
+        assert_eq!(encrypted, ciphertext);
+    }
+}
+
+#[test]
+fn test_roundtrip() {
+    let params = Params::new(4, 8, 1).unwrap();
+    for len in &[0, 1, 15, 16, 17, 1000] {
+        let plaintext: Vec<u8> = (0..*len).map(|i| i as u8).collect();
+        let first = encrypt(b"secret", &plaintext, &params).unwrap();
+        let second = encrypt(b"secret", &plaintext, &params).unwrap();
+        assert_eq!(first.len(), 128 + len);
+        assert_ne!(first, second);
+        assert_eq!(decrypt(b"secret", &first).unwrap(), plaintext);
+        assert_eq!(decrypt(b"secret", &second).unwrap(), plaintext);
+    }
+}
+
+#[test]
+fn test_wrong_password() {
+    assert_eq!(decrypt(b"passwore", &fixture()), Err(EncError::WrongPassword));
+    assert_eq!(decrypt(b"", &fixture()), Err(EncError::WrongPassword));
+}
+
+#[test]
+fn test_invalid_header() {
+    assert_eq!(decrypt(b"password", b""), Err(EncError::NotEncrypted));
+    assert_eq!(decrypt(b"password", b"scryp"), Err(EncError::NotEncrypted));
+    assert_eq!(decrypt(b"password", b"$rscrypt$0$CggB$"), Err(EncError::NotEncrypted));
+
+    let mut data = fixture();
+    data[6] = 1;
+    assert_eq!(decrypt(b"password", &data), Err(EncError::UnsupportedVersion));
+
+    for &i in &[7, 11, 15, 16, 47, 48, 63] {
+        let mut data = fixture();
+        data[i] ^= 1;
+        assert_eq!(params(&data).unwrap_err(), EncError::InvalidChecksum, "byte {}", i);
+    }
+
+    assert_eq!(decrypt(b"password", &fixture()[..127]), Err(EncError::Corrupt));
+}
+
+#[test]
+fn test_invalid_params() {
+    // the header of `log_n = 10, r = 0, p = 1` with a zero salt and a valid
+    // checksum, followed by zeros
+    let mut data = hex("736372797074000a0000000000000001\
+                        00000000000000000000000000000000\
+                        00000000000000000000000000000000\
+                        16f76a015c4e71c5f1a979029bd1e582");
+    data.resize(128, 0);
+    assert_eq!(params(&data).unwrap_err(), EncError::InvalidParams);
+    assert_eq!(decrypt(b"password", &data), Err(EncError::InvalidParams));
+}
+
+#[test]
+fn test_tampered_data() {
+    let len = fixture().len();
+    for &i in &[64, 95, 96, 100, len - 33, len - 32, len - 1] {
+        let mut data = fixture();
+        data[i] ^= 0x80;
+        let expected = if i < 96 { EncError::WrongPassword } else { EncError::Corrupt };
+        assert_eq!(decrypt(b"password", &data), Err(expected), "byte {}", i);
+    }
+
+    let mut data = fixture();
+    data.push(0);
+    assert_eq!(decrypt(b"password", &data), Err(EncError::Corrupt));
+    let mut data = fixture();
+    data.remove(100);
+    assert_eq!(decrypt(b"password", &data), Err(EncError::Corrupt));
+}
+
+#[test]
+fn test_error_conversions() {
+    assert_eq!(EncError::WrongPassword.code(), ErrorCode::HashMismatch);
+    assert_eq!(EncError::Corrupt.code(), ErrorCode::Corrupt);
+    assert_eq!(EncError::NotEncrypted.code(), ErrorCode::UnsupportedFormat);
+
+    let e: io::Error = EncError::WrongPassword.into();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(EncError::from_io_error(&e), Some(EncError::WrongPassword));
+    let e: io::Error = EncError::Corrupt.into();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(EncError::from_io_error(&e), Some(EncError::Corrupt));
+}
+
+/// A reader returning at most `1` byte per call, to split every boundary.
+struct Trickle<'a>(&'a [u8]);
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.0.len()).min(1);
+        buf[..len].copy_from_slice(&self.0[..len]);
+        self.0 = &self.0[len..];
+        Ok(len)
+    }
+}
+
+/// Decrypt `ciphertext` with a `Decryptor`, the error of `new()`, `read()`
+/// or `finish()` as an `EncError`.
+fn decrypt_stream<R: Read>(password: &[u8], ciphertext: R) -> Result<Vec<u8>, EncError> {
+    let to_enc = |e: io::Error| EncError::from_io_error(&e).expect("an EncError");
+    let mut decryptor = Decryptor::new(ciphertext, password).map_err(to_enc)?;
+    let mut plaintext = Vec::new();
+    decryptor.read_to_end(&mut plaintext).map_err(to_enc)?;
+    decryptor.finish().map_err(to_enc)?;
+    Ok(plaintext)
+}
+
+#[test]
+fn test_stream_fixtures() {
+    for &(password, plaintext, (log_n, r, p), ciphertext) in FIXTURES {
+        let ciphertext = hex(ciphertext);
+        let password = password.as_bytes();
+        assert_eq!(decrypt_stream(password, &ciphertext[..]).unwrap(), plaintext.as_bytes());
+        assert_eq!(decrypt_stream(password, Trickle(&ciphertext)).unwrap(),
+            plaintext.as_bytes());
+
+        let params = Params::new(log_n, r, p).unwrap();
+        let mut rng = FixedRng(ciphertext[16..48].to_vec());
+        let mut encryptor = Encryptor::with_rng(Vec::new(), password, &params, &mut rng)
+            .unwrap();
+        for byte in plaintext.as_bytes().chunks(1) {
+            encryptor.write_all(byte).unwrap();
+        }
+        assert_eq!(encryptor.finish().unwrap(), ciphertext);
+    }
+}
+
+#[test]
+fn test_stream_roundtrip() {
+    let params = Params::new(4, 8, 1).unwrap();
+    // several chunks of the streams, not a multiple of the AES block
+    let plaintext: Vec<u8> = (0..100_003u32).map(|i| (i * 7) as u8).collect();
+    let mut encryptor = Encryptor::new(Vec::new(), b"secret", &params).unwrap();
+    for piece in plaintext.chunks(5000) {
+        encryptor.write_all(piece).unwrap();
+    }
+    let ciphertext = encryptor.finish().unwrap();
+    assert_eq!(ciphertext.len(), 128 + plaintext.len());
+    assert_eq!(decrypt(b"secret", &ciphertext).unwrap(), plaintext);
+
+    let decrypted = decrypt_stream(b"secret", &ciphertext[..]).unwrap();
+    assert_eq!(decrypted, plaintext);
+    let decryptor = Decryptor::new(&ciphertext[..], b"secret").unwrap();
+    assert_eq!(decryptor.params(), params);
+    // `finish()` reads what was not read
+    decryptor.finish().unwrap();
+}
+
+#[test]
+fn test_stream_errors() {
+    let data = fixture();
+    assert_eq!(decrypt_stream(b"passwore", &data[..]), Err(EncError::WrongPassword));
+    assert_eq!(decrypt_stream(b"password", &b""[..]), Err(EncError::NotEncrypted));
+    assert_eq!(decrypt_stream(b"password", &data[..50]), Err(EncError::Corrupt));
+    let mut version = data.clone();
+    version[6] = 1;
+    assert_eq!(decrypt_stream(b"password", &version[..]), Err(EncError::UnsupportedVersion));
+
+    // truncated anywhere after the header
+    for &len in &[96, 100, 127, data.len() - 32, data.len() - 1] {
+        assert_eq!(decrypt_stream(b"password", &data[..len]), Err(EncError::Corrupt),
+            "length {}", len);
+    }
+    // tampered plaintext or HMAC, or appended data
+    for &i in &[96, 100, data.len() - 33, data.len() - 32, data.len() - 1] {
+        let mut tampered = data.clone();
+        tampered[i] ^= 0x80;
+        assert_eq!(decrypt_stream(b"password", &tampered[..]), Err(EncError::Corrupt),
+            "byte {}", i);
+    }
+    let mut appended = data.clone();
+    appended.push(0);
+    assert_eq!(decrypt_stream(b"password", &appended[..]), Err(EncError::Corrupt));
+
+    let refused = Decryptor::new_checked(&data[..], b"password", |params| params.log_n() < 10);
+    let e = refused.err().unwrap();
+    assert_eq!(EncError::from_io_error(&e), Some(EncError::InvalidParams));
+}
@@ -0,0 +1,148 @@
+#![cfg(feature="std")]
+extern crate scrypt;
+
+use std::env;
+use std::sync::Mutex;
+
+use scrypt::errors::{EnvErrorKind, ErrorCode, ParamsReason};
+#[cfg(feature="simple-verify")]
+use scrypt::ParamsPolicy;
+use scrypt::Params;
+
+// the tests set process-wide variables
+static ENV: Mutex<()> = Mutex::new(());
+
+const VARS: &[&str] = &[
+    "SCRYPT_PARAMS", "SCRYPT_LOG_N", "SCRYPT_R", "SCRYPT_P",
+    "SCRYPT_MIN_LOG_N", "SCRYPT_MAX_MEMORY", "SCRYPT_MAX_OPS",
+];
+
+type Vars = &'static [(&'static str, &'static str)];
+
+/// Run `f` with exactly `vars` set.
+fn with_env<F: FnOnce()>(vars: &[(&str, &str)], f: F) {
+    let _guard = ENV.lock().unwrap_or_else(|e| e.into_inner());
+    for var in VARS { env::remove_var(var); }
+    for &(var, value) in vars { env::set_var(var, value); }
+    f();
+    for var in VARS { env::remove_var(var); }
+}
+
+#[test]
+fn test_params_unset() {
+    with_env(&[], || assert_eq!(Params::from_env("SCRYPT"), Ok(None)));
+    // other prefixes are not read
+    with_env(&[("SCRYPT_LOG_N", "12")], || {
+        assert_eq!(Params::from_env("OTHER"), Ok(None));
+    });
+}
+
+#[test]
+fn test_params_combined() {
+    let cases = [
+        ("ln=12,r=4,p=2", (12, 4, 2)),
+        ("p=2, r=4, ln=12", (12, 4, 2)),
+        ("ln=12", (12, 8, 1)),
+        ("r=16", (15, 16, 1)),
+    ];
+    for &(value, (log_n, r, p)) in cases.iter() {
+        with_env(&[("SCRYPT_PARAMS", value)], || {
+            assert_eq!(Params::from_env("SCRYPT"), Ok(Some(Params::new(log_n, r, p).unwrap())),
+                "{}", value);
+        });
+    }
+}
+
+#[test]
+fn test_params_separate() {
+    with_env(&[("SCRYPT_LOG_N", "12"), ("SCRYPT_R", "4"), ("SCRYPT_P", " 2 ")], || {
+        assert_eq!(Params::from_env("SCRYPT"), Ok(Some(Params::new(12, 4, 2).unwrap())));
+    });
+    with_env(&[("SCRYPT_LOG_N", "17")], || {
+        assert_eq!(Params::from_env("SCRYPT"), Ok(Some(Params::new(17, 8, 1).unwrap())));
+    });
+}
+
+#[test]
+fn test_params_errors() {
+    let cases: &[(Vars, &str, EnvErrorKind)] = &[
+        (&[("SCRYPT_LOG_N", "fifteen")], "SCRYPT_LOG_N", EnvErrorKind::InvalidNumber),
+        (&[("SCRYPT_LOG_N", "256")], "SCRYPT_LOG_N", EnvErrorKind::InvalidNumber),
+        (&[("SCRYPT_R", "-8")], "SCRYPT_R", EnvErrorKind::InvalidNumber),
+        (&[("SCRYPT_P", "")], "SCRYPT_P", EnvErrorKind::InvalidNumber),
+        (&[("SCRYPT_PARAMS", "ln=x")], "SCRYPT_PARAMS", EnvErrorKind::InvalidNumber),
+        (&[("SCRYPT_PARAMS", "ln=12,n=4")], "SCRYPT_PARAMS", EnvErrorKind::Malformed),
+        (&[("SCRYPT_PARAMS", "ln=12,ln=13")], "SCRYPT_PARAMS", EnvErrorKind::Malformed),
+        (&[("SCRYPT_PARAMS", "12,8,1")], "SCRYPT_PARAMS", EnvErrorKind::Malformed),
+        (&[("SCRYPT_PARAMS", "ln=12"), ("SCRYPT_P", "2")], "SCRYPT_P", EnvErrorKind::Conflict),
+    ];
+    for &(vars, var, kind) in cases.iter() {
+        with_env(vars, || {
+            let err = Params::from_env("SCRYPT").unwrap_err();
+            assert_eq!((err.var(), err.kind()), (var, kind), "{:?}", vars);
+            assert_eq!(err.code(), ErrorCode::InvalidFormat);
+        });
+    }
+}
+
+#[test]
+fn test_params_invalid() {
+    let cases: &[(Vars, &str, ParamsReason)] = &[
+        (&[("SCRYPT_PARAMS", "ln=0")], "SCRYPT_PARAMS", ParamsReason::LogNZero),
+        (&[("SCRYPT_LOG_N", "0")], "SCRYPT_LOG_N", ParamsReason::LogNZero),
+        (&[("SCRYPT_LOG_N", "4"), ("SCRYPT_R", "0")], "SCRYPT_R", ParamsReason::RZero),
+        (&[("SCRYPT_P", "0")], "SCRYPT_P", ParamsReason::PZero),
+        (&[("SCRYPT_LOG_N", "64")], "SCRYPT_LOG_N", ParamsReason::LogNTooLarge),
+        (&[("SCRYPT_PARAMS", "ln=16,r=1")], "SCRYPT_PARAMS", ParamsReason::NTooLargeForR),
+        (&[("SCRYPT_P", "1073741824")], "SCRYPT_P", ParamsReason::RpTooLarge),
+    ];
+    for &(vars, var, reason) in cases.iter() {
+        with_env(vars, || {
+            let err = Params::from_env("SCRYPT").unwrap_err();
+            assert_eq!(err.var(), var, "{:?}", vars);
+            match err.kind() {
+                EnvErrorKind::InvalidParams(e) => assert_eq!(e.reason(), reason),
+                kind => panic!("{:?}", kind),
+            }
+            assert_eq!(err.code(), ErrorCode::InvalidParams);
+        });
+    }
+
+    with_env(&[("SCRYPT_LOG_N", "x")], || {
+        let err = Params::from_env("SCRYPT").unwrap_err();
+        assert_eq!(err.value(), "x");
+        assert_eq!(err.to_string(), "SCRYPT_LOG_N=\"x\": not a valid number");
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn test_params_not_unicode() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let _guard = ENV.lock().unwrap_or_else(|e| e.into_inner());
+    env::set_var("SCRYPT_NOT_UNICODE_R", OsStr::from_bytes(b"\xff"));
+    let err = Params::from_env("SCRYPT_NOT_UNICODE").unwrap_err();
+    env::remove_var("SCRYPT_NOT_UNICODE_R");
+    assert_eq!((err.var(), err.kind()), ("SCRYPT_NOT_UNICODE_R", EnvErrorKind::NotUnicode));
+}
+
+#[cfg(feature="simple-verify")]
+#[test]
+fn test_policy() {
+    with_env(&[], || assert_eq!(ParamsPolicy::from_env("SCRYPT"), Ok(None)));
+    with_env(&[("SCRYPT_MIN_LOG_N", "14")], || {
+        let policy = ParamsPolicy { min_log_n: 14, ..ParamsPolicy::default() };
+        assert_eq!(ParamsPolicy::from_env("SCRYPT"), Ok(Some(policy)));
+    });
+    with_env(&[("SCRYPT_MIN_LOG_N", "14"), ("SCRYPT_MAX_MEMORY", "67108864"),
+        ("SCRYPT_MAX_OPS", "1000000000")], || {
+        let policy = ParamsPolicy { min_log_n: 14, max_memory: 1 << 26, max_ops: 1_000_000_000 };
+        assert_eq!(ParamsPolicy::from_env("SCRYPT"), Ok(Some(policy)));
+    });
+    with_env(&[("SCRYPT_MAX_MEMORY", "64M")], || {
+        let err = ParamsPolicy::from_env("SCRYPT").unwrap_err();
+        assert_eq!((err.var(), err.kind()), ("SCRYPT_MAX_MEMORY", EnvErrorKind::InvalidNumber));
+    });
+}
@@ -0,0 +1,194 @@
+extern crate scrypt;
+
+use scrypt::{scrypt, Params};
+use scrypt::errors::{ErrorCode, InvalidOutputLen};
+#[cfg(feature="simple-verify")]
+use scrypt::errors::CheckError;
+
+#[test]
+fn test_invalid_output_len_display() {
+    let params = Params::new(1, 1, 1).unwrap();
+    let err = scrypt(b"password", b"salt", &params, &mut []).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        "invalid output buffer length 0, expected 1..=137438953440"
+    );
+}
+
+#[test]
+fn test_invalid_output_len_zero() {
+    let params = Params::new(1, 1, 1).unwrap();
+    let err = scrypt(b"password", b"salt", &params, &mut []).unwrap_err();
+    assert_eq!(err.provided_len(), 0);
+    assert_eq!(err.min(), 1);
+    assert_eq!(err.max(), 0xffff_ffff_u64 * 32);
+}
+
+// Allocates (but never touches) more than 128 GiB, which only works with
+// overcommit enabled.
+#[cfg(target_pointer_width = "64")]
+#[test]
+#[ignore]
+fn test_invalid_output_len_too_long() {
+    let len = InvalidOutputLen::MAX as usize + 1;
+    let mut output = vec![0u8; len];
+    let params = Params::new(1, 1, 1).unwrap();
+    let err = scrypt(b"password", b"salt", &params, &mut output).unwrap_err();
+    assert_eq!(err.provided_len(), len);
+    assert_eq!(err.min(), 1);
+    assert_eq!(err.max(), InvalidOutputLen::MAX);
+}
+
+#[test]
+fn test_invalid_params_display() {
+    let err = Params::new(1, 0, 1).err().unwrap();
+    assert_eq!(format!("{}", err), "invalid scrypt parameters");
+}
+
+#[cfg(feature="simple-verify")]
+#[test]
+fn test_check_error_display() {
+    assert_eq!(format!("{}", CheckError::HashMismatch), "password hash mismatch");
+    assert_eq!(
+        format!("{}", CheckError::InvalidFormat),
+        "invalid `hashed_value` format"
+    );
+}
+
+#[cfg(all(feature="simple-verify", feature="std"))]
+#[test]
+fn test_check_error_hash_len() {
+    use std::error::Error;
+
+    let hashed = "$rscrypt$0$BAEB$c2FsdA==$$";
+    let err = scrypt::scrypt_check("password", hashed).unwrap_err();
+    match err {
+        CheckError::InvalidHashLen(e) => assert_eq!(e.provided_len(), 0),
+        _ => panic!("unexpected error: {:?}", err),
+    }
+    let source = err.source().unwrap();
+    assert!(source.downcast_ref::<InvalidOutputLen>().is_some());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_std_error_impls() {
+    fn assert_error<E: std::error::Error>() {}
+
+    assert_error::<scrypt::errors::InvalidOutputLen>();
+    assert_error::<scrypt::errors::InvalidParams>();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_io_error_round_trip() {
+    use std::io;
+    use scrypt::errors::InvalidParams;
+
+    let params = Params::new(1, 1, 1).unwrap();
+    let err = scrypt(b"password", b"salt", &params, &mut []).unwrap_err();
+    let io_err: io::Error = err.into();
+    assert_eq!(io_err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(InvalidOutputLen::from_io_error(&io_err), Some(err));
+    assert_eq!(InvalidParams::from_io_error(&io_err), None);
+
+    let err = Params::new(1, 0, 1).err().unwrap();
+    let io_err: io::Error = err.into();
+    assert_eq!(io_err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(InvalidParams::from_io_error(&io_err), Some(err));
+
+    let io_err = io::Error::other("unrelated");
+    assert_eq!(InvalidOutputLen::from_io_error(&io_err), None);
+}
+
+#[cfg(all(feature="simple-verify", feature="std"))]
+#[test]
+fn test_check_error_io_round_trip() {
+    use std::io;
+
+    let cases = [
+        (CheckError::HashMismatch, io::ErrorKind::InvalidInput),
+        (CheckError::InvalidFormat, io::ErrorKind::InvalidData),
+        (CheckError::UnknownYear(2014), io::ErrorKind::InvalidInput),
+        (CheckError::RehashFailed, io::ErrorKind::Other),
+        (CheckError::KeyUnavailable(7), io::ErrorKind::Other),
+    ];
+    for &(err, kind) in cases.iter() {
+        let io_err: io::Error = err.into();
+        assert_eq!(io_err.kind(), kind);
+        assert_eq!(CheckError::from_io_error(&io_err), Some(err));
+        let inner = io_err.into_inner().unwrap();
+        assert_eq!(inner.downcast_ref::<CheckError>(), Some(&err));
+    }
+
+    let err = scrypt::scrypt_check("password", "$rscrypt$0$BAEB$c2FsdA==$$")
+        .unwrap_err();
+    let io_err: io::Error = err.into();
+    assert_eq!(io_err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(CheckError::from_io_error(&io_err), Some(err));
+}
+
+#[test]
+fn test_error_codes_pinned() {
+    let codes = [
+        (ErrorCode::InvalidOutputLen, 1),
+        (ErrorCode::InvalidParams, 2),
+        (ErrorCode::HashMismatch, 3),
+        (ErrorCode::InvalidFormat, 4),
+        (ErrorCode::InvalidHashLen, 5),
+        (ErrorCode::Rng, 6),
+        (ErrorCode::UnsupportedParams, 7),
+        (ErrorCode::InvalidChecksum, 8),
+        (ErrorCode::UnsupportedFormat, 9),
+        (ErrorCode::Unrepresentable, 10),
+        (ErrorCode::InvalidSaltLen, 11),
+        (ErrorCode::InvalidDkLen, 12),
+        (ErrorCode::HashTooShort, 13),
+        (ErrorCode::UnsupportedVersion, 14),
+        (ErrorCode::UnexpectedFieldLen, 15),
+        (ErrorCode::NonCanonicalBase64, 16),
+        (ErrorCode::RehashFailed, 17),
+        (ErrorCode::CostExceedsLimit, 18),
+        (ErrorCode::NeedsPepper, 19),
+        (ErrorCode::Refused, 20),
+        (ErrorCode::NeedsAssociatedData, 21),
+        (ErrorCode::Corrupt, 22),
+        (ErrorCode::InvalidArgument, 23),
+        (ErrorCode::BufferTooSmall, 24),
+        (ErrorCode::Internal, 25),
+        (ErrorCode::InvalidField, 26),
+        (ErrorCode::InvalidBufferLen, 27),
+        (ErrorCode::SelfTestFailed, 28),
+        (ErrorCode::Backend, 29),
+        (ErrorCode::PasswordTooLong, 30),
+        (ErrorCode::NeedsClientScheme, 32),
+        (ErrorCode::ClientSchemeMismatch, 33),
+        (ErrorCode::TamperSuspected, 34),
+        (ErrorCode::UnknownYear, 37),
+        (ErrorCode::KeyUnavailable, 38),
+    ];
+    for &(code, value) in codes.iter() {
+        assert_eq!(code.code(), value);
+        assert_eq!(ErrorCode::from_code(value), Some(code));
+    }
+    // retired codes are not reused
+    for &value in [0, 31, 35, 36, 39].iter() {
+        assert_eq!(ErrorCode::from_code(value), None);
+    }
+
+    let params = Params::new(1, 1, 1).unwrap();
+    let err = scrypt(b"password", b"salt", &params, &mut []).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidOutputLen);
+    let err = Params::new(1, 0, 1).err().unwrap();
+    assert_eq!(err.code(), ErrorCode::InvalidParams);
+}
+
+#[cfg(feature="simple-verify")]
+#[test]
+fn test_check_error_codes() {
+    assert_eq!(CheckError::HashMismatch.code(), ErrorCode::HashMismatch);
+    assert_eq!(CheckError::InvalidFormat.code(), ErrorCode::InvalidFormat);
+    let err = scrypt::scrypt_check("password", "$rscrypt$0$BAEB$c2FsdA==$$")
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidHashLen);
+}
@@ -0,0 +1,124 @@
+//! Parameters of production scale, which take minutes and gigabytes and
+//! are ignored by default. Run them in release mode, on a 64-bit machine
+//! with 5 GiB of free memory, with
+//!
+//! ```text
+//! cargo test --release --all-features --test expensive --test large -- --ignored
+//! ```
+//!
+//! and one at a time with `--test-threads 1` if memory is short. The
+//! expected outputs are those of `openssl kdf ... SCRYPT` and Python's
+//! `hashlib.scrypt`.
+#![cfg(target_pointer_width = "64")]
+extern crate hmac;
+extern crate pbkdf2;
+extern crate scrypt;
+extern crate sha2;
+
+use sha2::{Digest, Sha256};
+
+use scrypt::test_vectors::SCRYPT;
+use scrypt::{scrypt, Params};
+
+// N = 2^20, 1 GiB
+#[test]
+#[ignore]
+fn test_rfc_vector_4() {
+    SCRYPT[3].run().unwrap();
+}
+
+// p = 8 of 128 MiB each, against
+// `openssl kdf -keylen 64 -kdfopt pass:password -kdfopt salt:NaCl
+//  -kdfopt n:131072 -kdfopt r:8 -kdfopt p:8 SCRYPT`
+const P8_OUTPUT: [u8; 64] = [
+    0x71, 0x55, 0x1a, 0x1d, 0x32, 0x8f, 0x18, 0x89, 0x36, 0x0a, 0x2e, 0x4c, 0x0a, 0x73,
+    0x64, 0xd3, 0x7d, 0xd7, 0x50, 0xf2, 0x9b, 0x89, 0x28, 0x29, 0xee, 0x2a, 0x16, 0xe6,
+    0x1f, 0xc5, 0x83, 0xe7, 0x92, 0xcb, 0xd4, 0x44, 0x76, 0xd9, 0xe0, 0xe9, 0x90, 0xbe,
+    0x3b, 0x12, 0xc3, 0xcc, 0xd1, 0x27, 0xe0, 0x24, 0x64, 0x04, 0x5c, 0x35, 0x15, 0x26,
+    0x01, 0xa2, 0x37, 0xc4, 0x4a, 0xcd, 0xcb, 0xd6,
+];
+
+#[test]
+#[ignore]
+fn test_p8() {
+    let params = Params::new(17, 8, 8).unwrap();
+    let mut output = [0u8; 64];
+    scrypt(b"password", b"NaCl", &params, &mut output).unwrap();
+    assert_eq!(&output[..], &P8_OUTPUT[..]);
+}
+
+// the ROMix of each of the 8 blocks on its own thread gives the key of the
+// sequential loop of `scrypt()`
+#[cfg(feature="low-level")]
+#[test]
+#[ignore]
+fn test_p8_parallel() {
+    use std::thread;
+
+    use hmac::Hmac;
+    use pbkdf2::pbkdf2;
+    use scrypt::scrypt_core;
+
+    let block = Params::new(17, 8, 1).unwrap();
+    let mut b = vec![0u8; 128 * 8 * 8];
+    pbkdf2::<Hmac<Sha256>>(b"password", b"NaCl", 1, &mut b);
+    let threads: Vec<_> = b.chunks(128 * 8).map(|chunk| {
+        let mut chunk = chunk.to_vec();
+        thread::spawn(move || {
+            scrypt_core(&mut chunk, &block).unwrap();
+            chunk
+        })
+    }).collect();
+    let mixed: Vec<u8> = threads.into_iter().flat_map(|t| t.join().unwrap()).collect();
+    let mut output = [0u8; 64];
+    pbkdf2::<Hmac<Sha256>>(b"password", &mixed, 1, &mut output);
+    assert_eq!(&output[..], &P8_OUTPUT[..]);
+}
+
+// N = 2^21, r = 9, a V of 2.25 GiB, beyond the offsets of an `i32`, against
+// `openssl kdf -keylen 64 -kdfopt pass:password -kdfopt salt:NaCl
+//  -kdfopt n:2097152 -kdfopt r:9 -kdfopt p:1
+//  -kdfopt maxmem_bytes:3000000000 SCRYPT`
+#[test]
+#[ignore]
+fn test_above_2_gib() {
+    let params = Params::new(21, 9, 1).unwrap();
+    assert!(params.required_memory() > 1 << 31);
+    let mut output = [0u8; 64];
+    scrypt(b"password", b"NaCl", &params, &mut output).unwrap();
+    let expected = [
+        0xd3, 0x7a, 0xac, 0xfa, 0x4c, 0xc9, 0x31, 0xcc, 0x27, 0xe2, 0xc2, 0xd5, 0xc3, 0xb4,
+        0x0f, 0x9a, 0x39, 0x0d, 0xb2, 0xa2, 0xbc, 0x00, 0xf9, 0xb2, 0x22, 0x92, 0x29, 0xd8,
+        0xed, 0x45, 0x22, 0xb4, 0x1b, 0x44, 0x71, 0x74, 0x3d, 0x22, 0x62, 0x61, 0x93, 0x55,
+        0xe7, 0xee, 0xd5, 0x5d, 0x4c, 0xa0, 0x36, 0x0b, 0xcb, 0xf4, 0x10, 0x45, 0xfc, 0xef,
+        0x4a, 0xdf, 0x36, 0xf8, 0x70, 0xdb, 0xa0, 0xb0,
+    ];
+    assert_eq!(&output[..], &expected[..]);
+}
+
+// A 256 MiB output, 2^23 blocks of the final PBKDF2. The limit of
+// `(2^32 - 1) * 32` bytes does not fit the memory of a test machine.
+#[test]
+#[ignore]
+fn test_large_output() {
+    let params = Params::new(10, 8, 1).unwrap();
+    let mut output = vec![0u8; 1 << 28];
+    scrypt(b"password", b"NaCl", &params, &mut output).unwrap();
+
+    // the first block is the 32 byte key
+    let mut key = [0u8; 32];
+    scrypt(b"password", b"NaCl", &params, &mut key).unwrap();
+    assert_eq!(&output[..32], &key[..]);
+    let last = [
+        0x16, 0xe8, 0x46, 0xbc, 0x0a, 0xde, 0x2a, 0x1f, 0xeb, 0x40, 0x63, 0xcb, 0xd2, 0x3f,
+        0x9d, 0x56, 0xdd, 0x9f, 0xdf, 0xdd, 0x94, 0x08, 0xb3, 0x3e, 0x67, 0x19, 0xf0, 0x75,
+        0xc9, 0xa0, 0xbf, 0x8a,
+    ];
+    assert_eq!(&output[output.len() - 32..], &last[..]);
+    let digest = [
+        0xf9, 0xdb, 0x7e, 0x27, 0x39, 0xa6, 0x22, 0x77, 0x8f, 0x81, 0xaf, 0xaa, 0x1f, 0x1d,
+        0x3a, 0x6d, 0x73, 0x16, 0x95, 0x77, 0x5d, 0xc3, 0x1e, 0xb5, 0x44, 0x88, 0x13, 0xe5,
+        0x54, 0x6b, 0xe9, 0x33,
+    ];
+    assert_eq!(&Sha256::digest(&output)[..], &digest[..]);
+}
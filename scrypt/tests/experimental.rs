@@ -0,0 +1,60 @@
+#![cfg(feature="experimental-chacha")]
+extern crate scrypt;
+
+use scrypt::experimental::scrypt_chacha;
+use scrypt::test_vectors::{Operation, SCRYPT};
+use scrypt::{scrypt, Params};
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Password, salt, `log_n`, `r`, `p` and the expected output.
+type Vector = (&'static [u8], &'static [u8], u8, u32, u32, &'static str);
+
+// Generated with a Python transcription of RFC 7914 with the Salsa20/8
+// core replaced by ChaCha20/8, on the inputs of the RFC vectors.
+const VECTORS: &[Vector] = &[
+    (b"", b"", 4, 1, 1,
+     "ef8f448fc3ef7813b226a72a40a1987fc87f0d5f4066a205074fc7ac3b47070c\
+      f5204676207bee516d5ffa9c27aca93662bdde0ba3c06684de82d01ab4d1b5fe"),
+    (b"password", b"NaCl", 10, 8, 16,
+     "f194f75f1512104d6efb048c35c451b61104a79bb046af7b4739f0acb28afa45\
+      09868f104bc6ee001138737a6ad8256785a4104ea92f15fecf63e1e8cfabe8bd"),
+    (b"pleaseletmein", b"SodiumChloride", 14, 8, 1,
+     "b998a2bd7cfc025f14996f5f93fe9e60380e97389d7491405df9978170cb82d1\
+      ec7f06b1322136d29d90bd2744f2802a90562fd499449d209b1914a0395bf356"),
+];
+
+#[test]
+fn test_chacha_vectors() {
+    for &(password, salt, log_n, r, p, expected) in VECTORS {
+        let params = Params::new(log_n, r, p).unwrap();
+        let mut output = [0u8; 64];
+        scrypt_chacha(password, salt, &params, &mut output).unwrap();
+        assert_eq!(hex(&output), expected);
+
+        // not scrypt
+        let mut standard = [0u8; 64];
+        scrypt(password, salt, &params, &mut standard).unwrap();
+        assert_ne!(output, standard);
+    }
+}
+
+#[test]
+fn test_standard_vectors() {
+    // the last vector needs 1 GiB
+    for vector in &SCRYPT[..3] {
+        if let Operation::Scrypt { password, salt, log_n, r, p } = vector.operation {
+            let mut output = vec![0u8; vector.expected.len()];
+            scrypt(password, salt, &Params::new(log_n, r, p).unwrap(), &mut output).unwrap();
+            assert_eq!(&output[..], vector.expected, "{}", vector.name);
+        }
+    }
+}
+
+#[test]
+fn test_invalid_output_len() {
+    let params = Params::new(4, 1, 1).unwrap();
+    assert_eq!(scrypt_chacha(b"", b"", &params, &mut []).unwrap_err().provided_len(), 0);
+}
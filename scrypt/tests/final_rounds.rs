@@ -0,0 +1,152 @@
+#![cfg(feature="simple")]
+extern crate base64ct;
+extern crate rand;
+extern crate scrypt;
+
+use std::num::NonZeroU32;
+
+use base64ct::{Base64Unpadded, Encoding};
+use rand::SeedableRng;
+use rand::prng::ChaChaRng;
+
+use scrypt::errors::CheckError;
+use scrypt::{needs_rehash, scrypt, scrypt_check, scrypt_check_and_rehash_with_rng,
+    scrypt_simple_final_rounds, scrypt_simple_v2, scrypt_with_final_rounds, verify_many,
+    BatchOptions, Format, HashFormat, HashParts, Params, ScryptHash, ScryptHashRef, SimpleConfig,
+    Verifier};
+
+// Generated with a Python transcription of RFC 7914, with `c` iterations of
+// the final `hashlib.pbkdf2_hmac`.
+const C1: [u8; 32] = [
+    0xc2, 0x47, 0xea, 0xbb, 0x7c, 0x5d, 0x38, 0x94, 0xe0, 0x45, 0xed, 0xf2, 0xa4, 0xe9, 0x92, 0x73,
+    0x7d, 0x95, 0x3a, 0x3b, 0x18, 0x1f, 0x2e, 0x48, 0x2c, 0x2b, 0x7c, 0x3a, 0x70, 0x60, 0xa1, 0x1c,
+];
+const C10: [u8; 32] = [
+    0x7f, 0x97, 0x2f, 0x6d, 0xb9, 0xa4, 0x2b, 0x46, 0xfe, 0x9c, 0xdb, 0xb5, 0xe2, 0x72, 0xce, 0xcf,
+    0x15, 0xe1, 0xa8, 0x02, 0x5d, 0x7d, 0xd6, 0x08, 0x77, 0x1b, 0x4c, 0x37, 0x3f, 0xd0, 0x5f, 0x59,
+];
+
+/// `C10` under "NaCl", `log_n = 4`, `r = 1`, `p = 2` and `c = 10`.
+const HASHED_C10: &str =
+    "$rscrypt$2$BAEAAAACAAAABCAQCgAAAA$TmFDbA$f5cvbbmkK0b+nNu14nLOzxXhqAJdfdYIdxtMNz/QX1k";
+/// `HASHED_C10` without the flag and the count.
+const STRIPPED: &str =
+    "$rscrypt$2$BAEAAAACAAAABCAA$TmFDbA$f5cvbbmkK0b+nNu14nLOzxXhqAJdfdYIdxtMNz/QX1k";
+
+fn params() -> Params {
+    Params::new(4, 1, 2).unwrap()
+}
+
+fn rounds(c: u32) -> NonZeroU32 {
+    NonZeroU32::new(c).unwrap()
+}
+
+#[test]
+fn test_vectors() {
+    let mut output = [0u8; 32];
+    scrypt_with_final_rounds(b"password", b"NaCl", &params(), rounds(1), &mut output).unwrap();
+    assert_eq!(output, C1);
+    let mut plain = [0u8; 32];
+    scrypt(b"password", b"NaCl", &params(), &mut plain).unwrap();
+    assert_eq!(plain, C1);
+
+    scrypt_with_final_rounds(b"password", b"NaCl", &params(), rounds(10), &mut output).unwrap();
+    assert_eq!(output, C10);
+    assert!(scrypt_with_final_rounds(b"password", b"NaCl", &params(), rounds(10), &mut [])
+        .is_err());
+}
+
+#[test]
+fn test_check() {
+    assert_eq!(scrypt_check("password", HASHED_C10), Ok(()));
+    assert_eq!(scrypt_check("wrong", HASHED_C10), Err(CheckError::HashMismatch));
+    assert_eq!(Verifier::builder().build().verify("password", HASHED_C10), Ok(()));
+    let items = [("password", HASHED_C10), ("wrong", HASHED_C10)];
+    assert_eq!(verify_many(&items, &BatchOptions { threads: 1, limits: None }),
+        vec![Ok(()), Err(CheckError::HashMismatch)]);
+    let hashed = scrypt_simple_final_rounds("password", &params(), rounds(10)).unwrap();
+    assert_eq!(needs_rehash(&hashed, &params()), Ok(false));
+    assert_eq!(needs_rehash(&hashed, &Params::new(5, 1, 2).unwrap()), Ok(true));
+}
+
+#[test]
+fn test_stripped() {
+    assert_eq!(scrypt_check("password", STRIPPED), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_parse() {
+    let hash = ScryptHash::parse(HASHED_C10).unwrap();
+    assert_eq!(hash.final_rounds(), Some(rounds(10)));
+    assert_eq!(hash.hash_bytes(), &C10[..]);
+    assert_eq!(hash.to_string(), HASHED_C10);
+    assert_eq!(hash.verify("password"), Ok(()));
+    assert_eq!(hash.verify("wrong"), Err(CheckError::HashMismatch));
+    assert_eq!(ScryptHash::parse(STRIPPED).unwrap().final_rounds(), None);
+
+    let hash_ref = ScryptHashRef::parse(HASHED_C10).unwrap();
+    assert_eq!(hash_ref.final_rounds(), Some(rounds(10)));
+    assert_eq!(hash_ref.verify("password"), Ok(()));
+    assert_eq!(ScryptHash::from(hash_ref), hash);
+
+    // the parts alone are plain scrypt, which can not verify the string
+    assert_eq!(HashParts::from(hash).verify(b"password"), Err(CheckError::HashMismatch));
+    assert!(matches!(Format::Rscrypt.parse(HASHED_C10), Err(CheckError::Unrepresentable)));
+}
+
+#[test]
+fn test_rehash() {
+    let desired = Params::new(5, 1, 2).unwrap();
+    let rehashed = scrypt_check_and_rehash_with_rng("password", HASHED_C10, &desired,
+        &mut ChaChaRng::from_seed([7; 32])).unwrap().unwrap();
+    // the new hash keeps the iterations
+    let hash = ScryptHash::parse(&rehashed).unwrap();
+    assert_eq!(hash.params(), desired);
+    assert_eq!(hash.final_rounds(), Some(rounds(10)));
+    assert_eq!(scrypt_check("password", &rehashed), Ok(()));
+}
+
+#[test]
+fn test_simple() {
+    let config = SimpleConfig::builder().params(params()).final_rounds(rounds(3)).build()
+        .unwrap();
+    let hashed = config.hash_with_rng("password", &mut ChaChaRng::from_seed([7; 32])).unwrap();
+    assert!(hashed.starts_with("$rscrypt$2$"));
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(ScryptHash::parse(&hashed).unwrap().final_rounds(), Some(rounds(3)));
+
+    // one iteration is the plain string
+    let hashed = scrypt_simple_final_rounds("password", &params(), rounds(1)).unwrap();
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(hashed.split('$').nth(3), scrypt_simple_v2("password", &params()).unwrap()
+        .split('$').nth(3));
+}
+
+#[test]
+fn test_malformed_header() {
+    let fields: Vec<&str> = HASHED_C10.split('$').collect();
+    let header = Base64Unpadded::decode_vec(fields[3]).unwrap();
+    assert_eq!(header[11], 0x10);
+    assert_eq!(&header[12..], &[10, 0, 0, 0]);
+    let encode = |header: &[u8]| format!("$rscrypt$2${}${}${}",
+        Base64Unpadded::encode_string(header), fields[4], fields[5]);
+
+    let mut malformed = Vec::new();
+    // the count without its last byte
+    malformed.push(encode(&header[..15]));
+    // counts of 0 and 1, which are written without the flag
+    for &c in [0u8, 1].iter() {
+        let mut h = header.clone();
+        h[12] = c;
+        malformed.push(encode(&h));
+    }
+    // not combined with the other flags
+    for &flag in [0x01u8, 0x02, 0x04, 0x08].iter() {
+        let mut h = header.clone();
+        h[11] |= flag;
+        malformed.push(encode(&h));
+    }
+    for hashed in malformed.iter() {
+        assert_eq!(scrypt_check("password", hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+    }
+}
@@ -0,0 +1,586 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{Rng, SeedableRng};
+use rand::prng::{ChaChaRng, XorShiftRng};
+
+use scrypt::{scrypt_check, scrypt_simple, scrypt_simple_url_safe, Format, HashFormat,
+    HashParts, ScryptHash, ScryptHashRef, Params, SimpleConfig};
+use scrypt::errors::{CheckError, Field};
+
+// Generated with Python's `hashlib.scrypt`.
+const V0: &str = "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$";
+const V1: &str =
+    "$rscrypt$1$BAgAAAABAAAA$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$";
+const V1_LARGE_R: &str =
+    "$rscrypt$1$AiwBAAACAAAA$TmFDbA==$PmzIZZObALd8+7qr+zqWbvsmpj0sC3IxE0eiOAJnY/8=$";
+const HASH: [u8; 32] = [
+    0xf5, 0xdf, 0xb3, 0x97, 0x2e, 0x79, 0x08, 0xb2, 0x24, 0x10, 0xc5, 0xc5,
+    0xf3, 0x78, 0x89, 0x07, 0xcd, 0xbd, 0x1a, 0x79, 0x97, 0x1b, 0x12, 0x27,
+    0x75, 0x02, 0xbd, 0x4a, 0x77, 0xe6, 0xd5, 0xd3,
+];
+
+#[test]
+fn test_rscrypt_versions() {
+    for &(hashed, version) in [(V0, 0), (V1, 1)].iter() {
+        let hash = ScryptHash::parse(hashed).unwrap();
+        assert_eq!(hash.format(), Format::Rscrypt);
+        assert_eq!(hash.format_version(), Some(version));
+        assert_eq!(hash.params(), Params::new(4, 8, 1).unwrap());
+        assert_eq!(hash.salt(), b"NaCl");
+        assert_eq!(hash.hash_bytes(), &HASH[..]);
+        assert_eq!(hash.verify("password"), Ok(()));
+        assert_eq!(hash.verify("wrong"), Err(CheckError::HashMismatch));
+    }
+
+    let hash = ScryptHash::parse(V1_LARGE_R).unwrap();
+    assert_eq!(hash.format_version(), Some(1));
+    assert_eq!(hash.params(), Params::new(2, 300, 2).unwrap());
+    assert_eq!(hash.verify("password"), Ok(()));
+}
+
+#[test]
+fn test_scrypt_simple_versions() {
+    let params = Params::new(2, 255, 1).unwrap();
+    let hashed = scrypt_simple("password", &params).unwrap();
+    let hash = ScryptHash::parse(&hashed).unwrap();
+    assert_eq!(hash.format_version(), Some(0));
+    assert_eq!(hash.params(), params);
+    assert_eq!(hash.salt().len(), 16);
+    assert_eq!(hash.hash_bytes().len(), 32);
+
+    let params = Params::new(2, 256, 1).unwrap();
+    let hashed = scrypt_simple("password", &params).unwrap();
+    let hash = ScryptHash::parse(&hashed).unwrap();
+    assert_eq!(hash.format_version(), Some(1));
+    assert_eq!(hash.params(), params);
+}
+
+#[test]
+fn test_other_formats() {
+    let hash = ScryptHash::parse(
+        "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+    ).unwrap();
+    assert_eq!(hash.format(), Format::Phc);
+    assert_eq!(hash.format_version(), None);
+    assert_eq!(hash.params(), Params::new(4, 8, 1).unwrap());
+    assert_eq!(hash.salt(), &[0x8e, 0x0b]);
+    assert_eq!(hash.hash_bytes().len(), 32);
+    assert_eq!(hash.verify("Ünïcødé"), Ok(()));
+
+    // plain text salts are returned as passed to scrypt
+    let hash = ScryptHash::parse(
+        "scrypt$2$sël$1$1$MbY5a6bob9Ls6RaeNUjmfa+noDsixdIwX+tWM5gfDxMEeztvVeNZ+\
+         2WDCMX5Zch449tsPzQMS0FZN2LOHt9O2Q==",
+    ).unwrap();
+    assert_eq!(hash.format(), Format::Django);
+    assert_eq!(hash.format_version(), None);
+    assert_eq!(hash.params(), Params::new(1, 1, 1).unwrap());
+    assert_eq!(hash.salt(), "sël".as_bytes());
+    assert_eq!(hash.hash_bytes().len(), 64);
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(ScryptHash::parse(""), Err(CheckError::UnsupportedFormat));
+    assert_eq!(ScryptHash::parse("$rscrypt"), Err(CheckError::UnsupportedFormat));
+    assert_eq!(ScryptHash::parse("$rscrypt$2$BAgB$TmFDbA==$$"), Err(CheckError::InvalidFormat));
+    assert_eq!(ScryptHash::parse("$rscrypt$0$BAgB$TmFDbA==$"), Err(CheckError::InvalidFormat));
+    assert_eq!(ScryptHash::parse("$rscrypt$1$BAgB$TmFDbA==$$"), Err(CheckError::InvalidFormat));
+    assert_eq!(ScryptHash::parse("$rscrypt$0$BAgAAAABAAAA$TmFDbA==$$"), Err(CheckError::InvalidFormat));
+
+    // an empty hash parses, but can never verify
+    let hash = ScryptHash::parse("$rscrypt$0$BAgB$TmFDbA==$$").unwrap();
+    assert!(hash.hash_bytes().is_empty());
+    match hash.verify("password") {
+        Err(CheckError::InvalidHashLen(e)) => assert_eq!(e.provided_len(), 0),
+        res => panic!("{:?}", res),
+    }
+}
+
+#[test]
+fn test_optional_trailing_dollar() {
+    for &hashed in [V0, V1, V1_LARGE_R].iter() {
+        let stripped = &hashed[..hashed.len() - 1];
+        assert_eq!(scrypt_check("password", stripped), Ok(()), "{}", stripped);
+        assert_eq!(scrypt_check("wrong", stripped), Err(CheckError::HashMismatch));
+        assert_eq!(ScryptHash::parse(stripped), ScryptHash::parse(hashed));
+        assert_eq!(ScryptHashRef::parse(stripped).unwrap().verify("password"), Ok(()));
+        // the canonical form has the `$`
+        assert_eq!(ScryptHash::parse(stripped).unwrap().to_string(),
+            ScryptHash::parse(hashed).unwrap().to_string());
+    }
+
+    let malformed = [
+        // truncated, without a hash
+        "$rscrypt$0$BAgB$TmFDbA==",
+        "$rscrypt$0$BAgB$TmFDbA==$",
+        // data after the final field
+        "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$x",
+        "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$$",
+    ];
+    for &hashed in malformed.iter() {
+        assert_eq!(scrypt_check("password", hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+        assert_eq!(ScryptHashRef::parse(hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+    }
+}
+
+#[test]
+fn test_non_canonical_base64() {
+    assert_eq!(scrypt_check("password", V0), Ok(()));
+    let hashes = [
+        // the same bytes with nonzero trailing bits
+        ("$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dN=$", Field::Hash),
+        ("$rscrypt$0$BAgB$TmFDbB==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$", Field::Salt),
+        // mixed and missing padding
+        ("$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM$", Field::Hash),
+        ("$rscrypt$0$BAgB$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$", Field::Salt),
+    ];
+    for &(hashed, field) in hashes.iter() {
+        let expected = Err(CheckError::NonCanonicalBase64(field));
+        assert_eq!(scrypt_check("password", hashed), expected, "{}", hashed);
+        assert_eq!(ScryptHash::parse(hashed).map(|_| ()), expected, "{}", hashed);
+        assert_eq!(ScryptHashRef::parse(hashed).map(|_| ()), expected, "{}", hashed);
+    }
+    assert_eq!(
+        CheckError::NonCanonicalBase64(Field::Salt).to_string(),
+        "non-canonical base64 in the salt field of `hashed_value`",
+    );
+}
+
+/// Re-encode a standard rscrypt string with the URL-safe alphabet.
+fn url_safe(hashed: &str) -> String {
+    hashed.replace('+', "-").replace('/', "_").replace('=', "")
+}
+
+#[test]
+fn test_url_safe() {
+    // 0xfb 0xff 0xbf encodes to `+/+/` and `-_-_`
+    let params = Params::new(4, 8, 1).unwrap();
+    let salt: Vec<u8> = b"\xfb\xff\xbf".iter().cycle().take(16).cloned().collect();
+    let parts = HashParts::new(b"password", &params, &salt, 32).unwrap();
+    let standard = Format::Rscrypt.encode(&parts);
+    assert!(standard.starts_with("$rscrypt$0$BAgB$+/+/+/+/+/+/+/+/+/+/+w==$"), "{}", standard);
+    let url = url_safe(&standard);
+    assert!(url.starts_with("$rscrypt$0$BAgB$-_-_-_-_-_-_-_-_-_-_-w$"), "{}", url);
+
+    for hashed in [&standard, &url].iter() {
+        assert_eq!(scrypt_check("password", hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("wrong", hashed), Err(CheckError::HashMismatch));
+        assert_eq!(ScryptHash::parse(hashed).unwrap().salt(), &salt[..]);
+        assert_eq!(ScryptHashRef::parse(hashed).unwrap().salt(), &salt[..]);
+    }
+    // the canonical form uses the standard alphabet
+    assert_eq!(ScryptHash::parse(&url).unwrap().to_string(), standard);
+
+    // unpadded strings without `-` and `_` are URL-safe, even for version 1
+    for &hashed in [V0, V1].iter() {
+        let unpadded = hashed.replace('=', "");
+        assert!(!unpadded.contains('-') && !unpadded.contains('_'));
+        assert_eq!(scrypt_check("password", &url_safe(hashed)), Ok(()), "{}", hashed);
+    }
+
+    let mixed = [
+        // URL-safe salt, standard hash
+        url.replace("-w$", "-w$+"),
+        standard.replacen('+', "-", 1),
+        format!("{}$", url_safe(&standard[..standard.len() - 1])).replacen('_', "/", 1),
+    ];
+    for hashed in mixed.iter() {
+        assert_eq!(scrypt_check("password", hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+    }
+    // URL-safe fields are unpadded
+    let padded = standard.replace('+', "-").replace('/', "_");
+    assert_eq!(scrypt_check("password", &padded), Err(CheckError::NonCanonicalBase64(Field::Salt)));
+}
+
+#[test]
+fn test_scrypt_simple_url_safe() {
+    for &(log_n, r, p) in [(4, 8, 1), (1, 256, 1)].iter() {
+        let params = Params::new(log_n, r, p).unwrap();
+        let hashed = scrypt_simple_url_safe("password", &params).unwrap();
+        assert!(!hashed.contains(&['+', '/', '='][..]), "{}", hashed);
+        assert_eq!(scrypt_check("password", &hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+        let hash = ScryptHash::parse(&hashed).unwrap();
+        assert_eq!(hash.params(), params);
+        assert_eq!(hash.salt().len(), 16);
+        assert_eq!(url_safe(&hash.to_string()), hashed);
+    }
+}
+
+#[test]
+fn test_verify_matches_scrypt_check() {
+    let hashes = [
+        V0,
+        V1,
+        V1_LARGE_R,
+        "$rscrypt$0$BAgB$TmFDbA==$$",
+        "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM$",
+        "$rscrypt$0$BAgB$TmFDbA==$9e+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+        "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=",
+        "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$$",
+        "$rscrypt$0$BAAB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+        "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+        "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+        "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+        "",
+        "$",
+        "rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+        "$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG",
+    ];
+    for &hashed in hashes.iter() {
+        for &password in ["password", "Ünïcødé", "hashcat", ""].iter() {
+            let verified = ScryptHash::parse(hashed).and_then(|h| h.verify(password));
+            let expected = match verified {
+                Err(CheckError::UnsupportedFormat) => Err(CheckError::InvalidFormat),
+                res => res,
+            };
+            assert_eq!(scrypt_check(password, hashed), expected, "{}", hashed);
+        }
+    }
+}
+
+#[test]
+fn test_scrypt_check_results_pinned() {
+    // the results of `scrypt_check` before it was based on `ScryptHash`
+    let results = [
+        (V0, Ok(())),
+        (V1, Ok(())),
+        ("$rscrypt$0$BAgB$TmFDbA==$9e+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+            Err(CheckError::HashMismatch)),
+        // rejected until the trailing `$` became optional
+        ("$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=",
+            Ok(())),
+        ("$rscrypt$0$BAAB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+            Err(CheckError::InvalidFormat)),
+        ("", Err(CheckError::InvalidFormat)),
+        ("$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG",
+            Err(CheckError::InvalidFormat)),
+    ];
+    for &(hashed, expected) in results.iter() {
+        assert_eq!(scrypt_check("password", hashed), expected, "{}", hashed);
+    }
+}
+
+#[test]
+fn test_display_canonical() {
+    let hashes = [
+        (V0, V0),
+        (V1, V0),
+        (V1_LARGE_R, V1_LARGE_R),
+        (
+            "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+            "$scrypt$ln=4,r=8,p=1$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+        ),
+        (
+            "$scrypt$p=2,r=4,ln=6$Wpw$MxqUplG0o8ju5tUXwBHolCna81hp+dZN1D6ziZ23k0Z\
+             QkJIoHMdBppgzPx2hrOp9DOhd89jpO1tASgvH8Jg9fA",
+            "$scrypt$ln=6,r=4,p=2$Wpw$MxqUplG0o8ju5tUXwBHolCna81hp+dZN1D6ziZ23k0Z\
+             QkJIoHMdBppgzPx2hrOp9DOhd89jpO1tASgvH8Jg9fA",
+        ),
+        (
+            "$s0$6010C$6xFRVKObC3j7pn8argJYdw$\
+             nUmr44BPG1eSmZ5wWz8bnGJjIP5Vv5Ud2EeKVEuhZ2w",
+            "$s0$6010c$6xFRVKObC3j7pn8argJYdw==$\
+             nUmr44BPG1eSmZ5wWz8bnGJjIP5Vv5Ud2EeKVEuhZ2w=",
+        ),
+        (
+            "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+            "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+        ),
+        (
+            "scrypt:16:4:3$Vo8WJf01$8fa4d2de4726a5b6f188803247ba64c41bb9b4e8b3\
+             c2be8dcfd6ac891b20c98acc2aab903903ef746900557831128fad50af2a2d5c\
+             bfecf2c9ecd74a234ac075",
+            "scrypt:16:4:3$Vo8WJf01$8fa4d2de4726a5b6f188803247ba64c41bb9b4e8b3\
+             c2be8dcfd6ac891b20c98acc2aab903903ef746900557831128fad50af2a2d5c\
+             bfecf2c9ecd74a234ac075",
+        ),
+        (
+            "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+            "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+        ),
+    ];
+    for &(hashed, canonical) in hashes.iter() {
+        let hash: ScryptHash = hashed.parse().unwrap();
+        assert_eq!(hash.to_string(), canonical);
+        assert_same(&canonical.parse().unwrap(), &hash);
+    }
+
+    assert_eq!("".parse::<ScryptHash>(), Err(CheckError::UnsupportedFormat));
+    assert_eq!("$rscrypt$".parse::<ScryptHash>(), Err(CheckError::InvalidFormat));
+}
+
+#[test]
+fn test_debug_redacts_hash() {
+    let hash = ScryptHash::parse(V0).unwrap();
+    let debug = format!("{:?}", hash);
+    // the fingerprint is the start of `SHA256("NaCl")`
+    assert_eq!(
+        debug,
+        "ScryptHash { format: Rscrypt, version: Some(0), params: Params { \
+         log_n: 4, r: 8, p: 1 }, salt: <4 bytes, fingerprint dbcd6d34>, \
+         hash: <32 bytes, redacted> }",
+    );
+    assert!(!debug.contains("245") && !debug.contains("78, 97"));
+    assert!(hash.reveal_debug().contains("salt: [78, 97, 67, 108], hash: [245, 223, 179"));
+
+    let hash_ref = ScryptHashRef::parse(V0).unwrap();
+    let debug = format!("{:?}", hash_ref);
+    assert!(debug.contains("salt: <4 bytes, fingerprint dbcd6d34>, hash: <32 bytes, redacted>"));
+    assert!(!debug.contains("245"));
+    assert!(hash_ref.reveal_debug().contains("salt: [78, 97, 67, 108], hash: [245, 223, 179"));
+}
+
+/// Alphabet of `$7$` and Cisco salts.
+const ALPHABET: &[u8] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+#[test]
+fn test_display_roundtrip() {
+    let formats = [
+        Format::Rscrypt, Format::Phc, Format::Crypt7, Format::Lambdaworks,
+        Format::Django, Format::Werkzeug, Format::Cisco, Format::Hex, Format::ScryptKdf,
+        Format::PhpScrypt, Format::SimpleScrypt, Format::RubyScrypt,
+    ];
+    let mut rng = XorShiftRng::from_seed(*b"scrypt roundtrip");
+    let mut covered = [0; 12];
+    for _ in 0..2000 {
+        let log_n = rng.gen_range(1, 8);
+        let r = if rng.gen() { rng.gen_range(1, 4) } else { rng.gen_range(250, 260) };
+        let p = if rng.gen() { rng.gen_range(1, 4) } else { rng.gen_range(250, 260) };
+        // the Cisco parameters, salt length and alphabet are fixed
+        let params = if rng.gen_range(0, 4) == 0 {
+            Params::new(14, 1, 1).unwrap()
+        } else {
+            Params::new(log_n, r, p).unwrap()
+        };
+        let salt_len = if rng.gen() { 14 } else { rng.gen_range(0, 24) };
+        let salt: Vec<u8> = match rng.gen_range(0, 3) {
+            0 => (0..salt_len).map(|_| ALPHABET[rng.gen_range(0, 64)]).collect(),
+            1 => (0..salt_len).map(|_| rng.gen_range(b' ', b'~')).collect(),
+            _ => (0..salt_len).map(|_| rng.gen()).collect(),
+        };
+        let hash: Vec<u8> = (0..[16, 32, 64][rng.gen_range(0, 3)]).map(|_| rng.gen()).collect();
+        let parts = HashParts { params, salt, hash };
+
+        for (i, format) in formats.iter().enumerate() {
+            if !format.supports(&parts) { continue; }
+            covered[i] += 1;
+
+            let encoded = format.encode(&parts);
+            let parsed: ScryptHash = encoded.parse().unwrap();
+            assert_eq!(parsed.format(), *format);
+            assert_eq!(parsed.params(), parts.params);
+            assert_eq!(parsed.salt(), &parts.salt[..]);
+            assert_eq!(parsed.hash_bytes(), &parts.hash[..]);
+
+            let displayed = parsed.to_string();
+            assert_eq!(displayed, encoded);
+            assert_same(&displayed.parse().unwrap(), &parsed);
+
+            let decoded = ScryptHash::from_bytes(&parsed.to_bytes()).unwrap();
+            assert_same(&decoded, &parsed);
+            assert_eq!(decoded.to_string(), encoded);
+        }
+    }
+    // every format was exercised
+    assert!(covered.iter().all(|&n| n > 10), "{:?}", covered);
+}
+
+#[test]
+fn test_generate() {
+    let mut rng = ChaChaRng::from_seed([7; 32]);
+    let cases = [
+        (Format::Rscrypt, Params::new(4, 8, 1).unwrap(), Some(0)),
+        (Format::Rscrypt, Params::new(2, 300, 1).unwrap(), Some(1)),
+        (Format::Phc, Params::new(4, 8, 1).unwrap(), None),
+        (Format::Django, Params::new(4, 8, 1).unwrap(), None),
+        (Format::Crypt7, Params::new(4, 8, 1).unwrap(), None),
+    ];
+    for &(format, params, version) in cases.iter() {
+        let config = SimpleConfig::builder().params(params).format(format).build().unwrap();
+        let hash = ScryptHash::generate("password", &config, &mut rng).unwrap();
+        assert_eq!(hash.format(), format);
+        assert_eq!(hash.format_version(), version);
+        assert_eq!(hash.params(), params);
+        assert_eq!(hash.salt().len(), config.salt_len());
+        assert_eq!(hash.hash_bytes().len(), config.dk_len());
+        assert_eq!(hash.verify("password"), Ok(()));
+        assert_eq!(hash.verify("wrong"), Err(CheckError::HashMismatch));
+
+        let encoded = hash.to_string();
+        assert_eq!(scrypt_check("password", &encoded), Ok(()));
+        let parsed = ScryptHash::parse(&encoded).unwrap();
+        assert_eq!(parsed, hash);
+        assert_eq!(parsed.to_string(), encoded);
+    }
+}
+
+fn assert_same(a: &ScryptHash, b: &ScryptHash) {
+    assert_eq!(a.format(), b.format());
+    assert_eq!(a.params(), b.params());
+    assert_eq!(a.salt(), b.salt());
+    assert_eq!(a.hash_bytes(), b.hash_bytes());
+}
+
+#[test]
+fn test_bytes() {
+    let hash = ScryptHash::parse(V0).unwrap();
+    let mut expected = vec![0, 0, 4, 8, 0, 0, 0, 1, 0, 0, 0, 4, 0, 0, 0];
+    expected.extend_from_slice(b"NaCl");
+    expected.extend_from_slice(&[32, 0, 0, 0]);
+    expected.extend_from_slice(&HASH);
+    assert_eq!(hash.to_bytes(), expected);
+    assert_eq!(ScryptHash::from_bytes(&expected), Ok(hash));
+
+    let hash = ScryptHash::parse(V1_LARGE_R).unwrap();
+    let bytes = hash.to_bytes();
+    assert_eq!(&bytes[..11], &[0, 1, 2, 44, 1, 0, 0, 2, 0, 0, 0]);
+    assert_eq!(ScryptHash::from_bytes(&bytes), Ok(hash));
+
+    let hash = ScryptHash::parse(
+        "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+    ).unwrap();
+    let bytes = hash.to_bytes();
+    assert_eq!(&bytes[..2], &[6, 0]);
+    let decoded = ScryptHash::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.format_version(), None);
+    assert_eq!(decoded, hash);
+    assert_eq!(decoded.verify("hashcat"), Ok(()));
+}
+
+#[test]
+fn test_bytes_invalid() {
+    let valid = ScryptHash::parse(V0).unwrap().to_bytes();
+    let modified = |f: &dyn Fn(&mut Vec<u8>)| {
+        let mut bytes = valid.clone();
+        f(&mut bytes);
+        bytes
+    };
+    let invalid = [
+        vec![],
+        valid[..14].to_vec(),
+        valid[..valid.len() - 1].to_vec(),
+        modified(&|b| b.push(0)),
+        // unknown format
+        modified(&|b| b[0] = 12),
+        // unknown rscrypt version, version 0 with `r` above 255
+        modified(&|b| b[1] = 3),
+        modified(&|b| b[4] = 1),
+        // version of a format without versions
+        modified(&|b| { b[0] = 1; b[1] = 1; }),
+        // invalid parameters
+        modified(&|b| b[3] = 0),
+        modified(&|b| b[2] = 64),
+        // salt length beyond the end
+        modified(&|b| b[11] = 200),
+        modified(&|b| b[14] = 0xff),
+        // digest of length zero
+        modified(&|b| { b.truncate(19); b[15..19].copy_from_slice(&[0; 4]); }),
+        // parameters a Cisco secret can not have
+        modified(&|b| b[0] = 6),
+        // a Django salt with `$`
+        {
+            let hash = ScryptHash::parse(
+                "scrypt$2$sël$1$1$MbY5a6bob9Ls6RaeNUjmfa+noDsixdIwX+tWM5gfDxMEeztvVeNZ+\
+                 2WDCMX5Zch449tsPzQMS0FZN2LOHt9O2Q==",
+            ).unwrap();
+            let mut bytes = hash.to_bytes();
+            assert!(ScryptHash::from_bytes(&bytes).is_ok());
+            bytes[15] = b'$';
+            bytes
+        },
+    ];
+    for bytes in invalid.iter() {
+        assert_eq!(ScryptHash::from_bytes(bytes), Err(CheckError::InvalidFormat), "{:?}", bytes);
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn test_record_pinned() {
+    let record = ScryptHash::parse(V0).unwrap().to_record().unwrap();
+    assert_eq!(hex(&record), "735200\
+                              04\
+                              08000000\
+                              01000000\
+                              04\
+                              4e61436c\
+                              20\
+                              f5dfb3972e7908b22410c5c5f3788907cdbd1a79971b12277502bd4a77e6d5d3");
+    assert_eq!(record.len(), ScryptHash::record_len(4, 32));
+    assert_eq!(ScryptHash::from_record(&record).unwrap().to_string(), V0);
+}
+
+#[test]
+fn test_record_roundtrip() {
+    let phc = Format::Phc.encode(&HashParts::new(
+        b"password", &Params::new(4, 8, 1).unwrap(), b"NaCl", 32,
+    ).unwrap());
+    for &hashed in [V0, V1, V1_LARGE_R, &phc[..]].iter() {
+        let hash = ScryptHash::parse(hashed).unwrap();
+        let record = hash.to_record().unwrap();
+        assert_eq!(record.len(), ScryptHash::record_len(hash.salt().len(),
+            hash.hash_bytes().len()));
+        let decoded = ScryptHash::from_record(&record).unwrap();
+        assert_eq!(decoded.format(), Format::Rscrypt);
+        assert_same(&ScryptHash::parse(&decoded.to_string()).unwrap(), &decoded);
+        assert_eq!(decoded.params(), hash.params());
+        assert_eq!(decoded.salt(), hash.salt());
+        assert_eq!(decoded.hash_bytes(), hash.hash_bytes());
+        assert_eq!(decoded.to_record().unwrap(), record);
+    }
+    let decoded = ScryptHash::from_record(
+        &ScryptHash::parse(V1_LARGE_R).unwrap().to_record().unwrap(),
+    ).unwrap();
+    assert_eq!(decoded.format_version(), Some(1));
+    assert_eq!(decoded.to_string(), V1_LARGE_R);
+
+    // salts of up to 255 bytes fit
+    let parts = HashParts::new(b"password", &Params::new(1, 1, 1).unwrap(), &[1; 255], 16)
+        .unwrap();
+    let hash = ScryptHash::parse(&Format::Phc.encode(&parts)).unwrap();
+    assert!(ScryptHash::from_record(&hash.to_record().unwrap()).is_ok());
+    let parts = HashParts { salt: vec![1; 256], ..parts };
+    let hash = ScryptHash::parse(&Format::Phc.encode(&parts)).unwrap();
+    assert_eq!(hash.to_record(), Err(CheckError::Unrepresentable));
+}
+
+#[test]
+fn test_record_invalid() {
+    let valid = ScryptHash::parse(V0).unwrap().to_record().unwrap();
+    // every truncation
+    for len in 0..valid.len() {
+        let expected = if len < 2 { CheckError::UnsupportedFormat } else { CheckError::InvalidFormat };
+        assert_eq!(ScryptHash::from_record(&valid[..len]), Err(expected), "{}", len);
+    }
+
+    let modified = |f: &dyn Fn(&mut Vec<u8>)| {
+        let mut record = valid.clone();
+        f(&mut record);
+        record
+    };
+    let invalid = [
+        (modified(&|r| r[0] = b'S'), CheckError::UnsupportedFormat),
+        (modified(&|r| r[2] = 1), CheckError::UnsupportedVersion),
+        (modified(&|r| r.push(0)), CheckError::InvalidFormat),
+        // invalid parameters
+        (modified(&|r| r[3] = 0), CheckError::InvalidFormat),
+        (modified(&|r| r[4] = 0), CheckError::InvalidFormat),
+        (modified(&|r| r[8] = 0), CheckError::InvalidFormat),
+        // salt length beyond the end, inconsistent lengths
+        (modified(&|r| r[12] = 200), CheckError::InvalidFormat),
+        (modified(&|r| r[12] = 3), CheckError::InvalidFormat),
+        (modified(&|r| r[17] = 31), CheckError::InvalidFormat),
+        // empty hash
+        (modified(&|r| { r.truncate(18); r[17] = 0; }), CheckError::InvalidFormat),
+    ];
+    for &(ref record, expected) in invalid.iter() {
+        assert_eq!(ScryptHash::from_record(record), Err(expected), "{:?}", record);
+    }
+}
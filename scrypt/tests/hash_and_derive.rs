@@ -0,0 +1,169 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::{check_and_derive, hash_and_derive, scrypt, scrypt_check,
+    scrypt_check_and_rehash, scrypt_check_and_rehash_peppered, scrypt_simple, Params, PepperSet,
+    ScryptHash, SimpleConfig};
+use scrypt::errors::{CheckError, ErrorCode, HashError};
+
+// Generated with Python's `hashlib.scrypt` of `32 + key_len` bytes, the
+// first 32 are stored in a version 2 string with the flag `0x80`, the rest
+// are the key. The keys must not change, data encrypted with earlier
+// versions depends on them: password, string, key. The first is the vector
+// of `tests/key_iv.rs` with a key of 32 bytes.
+const VECTORS: &[(&str, &str, &str)] = &[
+    (
+        "password",
+        "$rscrypt$2$CggAAAABAAAABCCA$TmFDbA$J7QYxnTHadElAfux9TusMt9lFMDyjQQ4crFIs0iWGnk",
+        "057a6861cc3553246aa0ddb63bc074450b924022547a799538d603396835dd62",
+    ),
+    (
+        "correct horse",
+        "$rscrypt$2$BAgAAAABAAAAECCA$AAECAwQFBgcICQoLDA0ODw$Q7EcH175P/kcU7JE2HRU+qTRrq/BS6UbCRYPdBaC5y8",
+        "87e2bad1cbc544b1644e5a60dc4550d8a193fd3e99708068",
+    ),
+];
+
+/// The strings of `VECTORS` without the flag.
+const UNMARKED: &[&str] = &[
+    "$rscrypt$0$CggB$TmFDbA==$J7QYxnTHadElAfux9TusMt9lFMDyjQQ4crFIs0iWGnk=$",
+    "$rscrypt$2$BAgAAAABAAAAECAA$AAECAwQFBgcICQoLDA0ODw$Q7EcH175P/kcU7JE2HRU+qTRrq/BS6UbCRYPdBaC5y8",
+];
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn test_vectors() {
+    for &(password, stored, key) in VECTORS {
+        let derived = check_and_derive(password, stored, key.len() / 2).unwrap();
+        assert_eq!(to_hex(&derived), key);
+        assert_eq!(scrypt_check(password, stored), Ok(()));
+    }
+
+    let params = Params::new(4, 8, 1).unwrap();
+    let mut rng = FixedRng((0..16).collect());
+    let config = SimpleConfig::builder().params(params).build().unwrap();
+    let (hashed, key) = config.hash_and_derive_with_rng("correct horse", 24, &mut rng).unwrap();
+    assert_eq!(hashed, VECTORS[1].1);
+    assert_eq!(to_hex(&key), VECTORS[1].2);
+}
+
+#[test]
+fn test_round_trip() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let (hashed, key) = hash_and_derive("password", &params, 32).unwrap();
+    assert_eq!(key.len(), 32);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(check_and_derive("password", &hashed, 32).unwrap().as_bytes(), key.as_bytes());
+    assert_eq!(check_and_derive("wrong", &hashed, 32).unwrap_err(), CheckError::HashMismatch);
+
+    // the output of scrypt only grows with the length, a shorter key is a
+    // prefix of a longer one
+    let short = check_and_derive("password", &hashed, 16).unwrap();
+    assert_eq!(short.as_bytes(), &key[..16]);
+}
+
+#[test]
+fn test_key_not_stored() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let (hashed, key) = hash_and_derive("password", &params, 32).unwrap();
+    let hash = ScryptHash::parse(&hashed).unwrap();
+    assert_eq!(hash.hash_bytes().len(), 32);
+    assert!(!hashed.contains(&to_hex(&key)));
+
+    // the stored fields without the password give other bytes, only the
+    // password derives the key
+    let mut output = [0u8; 64];
+    scrypt(hash.hash_bytes(), hash.salt(), &params, &mut output).unwrap();
+    assert_ne!(&output[32..], key.as_bytes());
+    scrypt(b"", hash.salt(), &params, &mut output).unwrap();
+    assert_ne!(&output[32..], key.as_bytes());
+    scrypt(b"password", hash.salt(), &params, &mut output).unwrap();
+    assert_eq!(&output[..32], hash.hash_bytes());
+    assert_eq!(&output[32..], key.as_bytes());
+}
+
+#[test]
+fn test_errors() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let (hashed, _) = hash_and_derive("password", &params, 16).unwrap();
+    assert_eq!(check_and_derive("password", "$rscrypt$", 16).unwrap_err(),
+        CheckError::InvalidFormat);
+    let long = "x".repeat(scrypt::DEFAULT_MAX_PASSWORD_LEN + 1);
+    assert!(matches!(check_and_derive(&long, &hashed, 16),
+        Err(CheckError::PasswordTooLong { .. })));
+}
+
+#[test]
+fn test_unmarked() {
+    // strings of other functions never returned a key
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashed = scrypt_simple("password", &params).unwrap();
+    assert_eq!(check_and_derive("password", &hashed, 16).unwrap_err(),
+        CheckError::UnsupportedFormat);
+    for (&unmarked, &(password, _, key)) in UNMARKED.iter().zip(VECTORS) {
+        assert_eq!(scrypt_check(password, unmarked), Ok(()));
+        assert_eq!(check_and_derive(password, unmarked, key.len() / 2).unwrap_err(),
+            CheckError::UnsupportedFormat);
+    }
+}
+
+#[test]
+fn test_not_rehashed() {
+    // a new salt or new parameters would change the key
+    let params = Params::new(4, 8, 1).unwrap();
+    let desired = Params::new(5, 8, 1).unwrap();
+    let (hashed, key) = hash_and_derive("password", &params, 16).unwrap();
+    assert_eq!(scrypt_check_and_rehash("password", &hashed, &desired), Ok(None));
+    let mut peppers = PepperSet::new();
+    peppers.insert(1, b"pepper");
+    assert_eq!(scrypt_check_and_rehash_peppered("password", &hashed, &desired, &peppers),
+        Ok(None));
+    assert_eq!(scrypt_check_and_rehash("wrong", &hashed, &desired),
+        Err(CheckError::HashMismatch));
+    assert_eq!(check_and_derive("password", &hashed, 16).unwrap().as_bytes(), key.as_bytes());
+}
+
+#[test]
+fn test_bound() {
+    // `hash_and_derive` never binds the header
+    let bound = "$rscrypt$2$CggAAAABAAAABCCg$TmFDbA$J7QYxnTHadElAfux9TusMt9lFMDyjQQ4crFIs0iWGnk";
+    assert_eq!(scrypt_check("password", bound), Err(CheckError::InvalidFormat));
+    assert_eq!(check_and_derive("password", bound, 32).unwrap_err(), CheckError::InvalidFormat);
+}
+
+#[test]
+fn test_key_len() {
+    let params = Params::new(4, 8, 1).unwrap();
+    for &key_len in [0, usize::MAX - 31, usize::MAX].iter() {
+        match hash_and_derive("password", &params, key_len) {
+            Err(HashError::InvalidOutputLen(e)) => assert_eq!(e.code(), ErrorCode::InvalidOutputLen),
+            other => panic!("{}: {:?}", key_len, other.map(|(hashed, _)| hashed)),
+        }
+        assert!(matches!(check_and_derive("password", VECTORS[1].1, key_len),
+            Err(CheckError::InvalidOutputLen(_))), "{}", key_len);
+    }
+}
@@ -0,0 +1,93 @@
+#![cfg(feature="simple-verify")]
+extern crate scrypt;
+
+use scrypt::{Format, HashFormat, HashParts, ScryptHash, ScryptHashRef, Params};
+use scrypt::errors::CheckError;
+
+// Generated with Python's `hashlib.scrypt`.
+const HASHES: &[&str] = &[
+    "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+    "$rscrypt$1$BAgAAAABAAAA$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+    "$rscrypt$1$AiwBAAACAAAA$TmFDbA==$PmzIZZObALd8+7qr+zqWbvsmpj0sC3IxE0eiOAJnY/8=$",
+    "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+    "$scrypt$ln=10,r=8,p=1$LRtfeowOkT+kTmsMHS4/QA$\
+     oQoHWim7dB7vcgd11aYdc4M4LhnrM0sAiuLqU82afpQ",
+    "$scrypt$ln=5,r=1,p=1$AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8$\
+     clvNdiyEBKCJo2ceWyls+w",
+    // an empty hash parses, but never verifies
+    "$rscrypt$0$BAgB$TmFDbA==$$",
+];
+
+#[test]
+fn test_parse_like_scrypt_hash() {
+    for &hashed in HASHES {
+        let owned = ScryptHash::parse(hashed).unwrap();
+        let borrowed = ScryptHashRef::parse(hashed).unwrap();
+        assert_eq!(borrowed.as_str(), hashed);
+        assert_eq!(borrowed.format(), owned.format());
+        assert_eq!(borrowed.format_version(), owned.format_version());
+        assert_eq!(borrowed.params(), owned.params());
+        assert_eq!(borrowed.salt(), owned.salt());
+        assert_eq!(borrowed.hash_bytes(), owned.hash_bytes());
+        assert_eq!(ScryptHash::from(borrowed), owned);
+
+        for &password in ["password", "Ünïcødé", "", "wrong"].iter() {
+            assert_eq!(borrowed.verify(password), owned.verify(password), "{}", hashed);
+        }
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    let malformed = [
+        "$rscrypt$",
+        "$rscrypt$2$BAgB$TmFDbA==$$",
+        "$rscrypt$0$BAgAAAABAAAA$TmFDbA==$$",
+        "$rscrypt$0$BAgB$TmFDbA==$",
+        "$rscrypt$0$BAgB$TmFDbA==$$$",
+        "$rscrypt$0$BAgB$TmF!bA==$$",
+        "$rscrypt$0$BAgBBAgBBAgBBAgBBAgB$TmFDbA==$$",
+        "$scrypt$",
+        "$scrypt$ln=4$jgs",
+        "$scrypt$ln=4$jgs$",
+        "$scrypt$ln=4$jgs=$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+        "$scrypt$ln=4$jg$s$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+    ];
+    for &hashed in malformed.iter() {
+        assert_eq!(ScryptHash::parse(hashed).err(), Some(CheckError::InvalidFormat), "{}", hashed);
+        assert_eq!(ScryptHashRef::parse(hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+    }
+
+    // only the formats of this crate are parsed without allocating
+    let others = [
+        "",
+        "$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG",
+        "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+        "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+    ];
+    for &hashed in others.iter() {
+        assert_eq!(ScryptHashRef::parse(hashed), Err(CheckError::UnsupportedFormat), "{}", hashed);
+    }
+}
+
+#[test]
+fn test_inline_capacity() {
+    assert_eq!(ScryptHashRef::MAX_LEN, 64);
+    let params = Params::new(1, 1, 1).unwrap();
+    for &format in [Format::Rscrypt, Format::Phc].iter() {
+        let parts = HashParts::new(b"password", &params, &[0xff; 64], 64).unwrap();
+        let hashed = format.encode(&parts);
+        let hash = ScryptHashRef::parse(&hashed).unwrap();
+        assert_eq!(hash.salt(), &parts.salt[..]);
+        assert_eq!(hash.hash_bytes(), &parts.hash[..]);
+        assert_eq!(hash.verify("password"), Ok(()));
+
+        for &(salt_len, hash_len) in [(65, 32), (16, 65), (200, 200)].iter() {
+            let parts = HashParts::new(b"password", &params, &vec![0xff; salt_len],
+                hash_len).unwrap();
+            let hashed = format.encode(&parts);
+            assert_eq!(ScryptHashRef::parse(&hashed), Err(CheckError::Unrepresentable));
+            assert!(ScryptHash::parse(&hashed).unwrap().verify("password").is_ok());
+        }
+    }
+}
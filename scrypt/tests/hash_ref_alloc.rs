@@ -0,0 +1,57 @@
+#![cfg(feature="simple-verify")]
+extern crate scrypt;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use scrypt::{ScryptHash, ScryptHashRef};
+
+/// Counts the allocations of the current thread, the test harness allocates
+/// on other threads.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(|n| n.get());
+    f();
+    ALLOCATIONS.with(|n| n.get()) - before
+}
+
+#[test]
+fn test_parse_does_not_allocate() {
+    let hashes = [
+        "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+        "$rscrypt$1$AiwBAAACAAAA$TmFDbA==$PmzIZZObALd8+7qr+zqWbvsmpj0sC3IxE0eiOAJnY/8=$",
+        "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+        "$scrypt$ln=10,r=8,p=1$LRtfeowOkT+kTmsMHS4/QA$\
+         oQoHWim7dB7vcgd11aYdc4M4LhnrM0sAiuLqU82afpQ",
+        // errors do not allocate either
+        "$rscrypt$0$BAgB$TmFDbA==$",
+        "$scrypt$ln=4$jgs",
+        "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+    ];
+    for &hashed in hashes.iter() {
+        assert_eq!(allocations(|| { let _ = ScryptHashRef::parse(hashed); }), 0, "{}", hashed);
+        // the counter works: the owning type allocates for salt and hash
+        if ScryptHashRef::parse(hashed).is_ok() {
+            assert!(allocations(|| { ScryptHash::parse(hashed).unwrap(); }) > 0);
+        }
+    }
+}
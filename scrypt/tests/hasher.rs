@@ -0,0 +1,60 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use scrypt::errors::CheckError;
+use scrypt::{scrypt_check, InsecureFastHasher, Params, PasswordHasher, ScryptHash,
+    SimpleConfig, Verifier};
+
+fn hashers() -> Vec<Box<dyn PasswordHasher>> {
+    let params = Params::new(4, 8, 1).unwrap();
+    vec![
+        Box::new(SimpleConfig::builder().params(params).build().unwrap()),
+        Box::new(InsecureFastHasher),
+    ]
+}
+
+#[test]
+fn test_boxed_hashers() {
+    for hasher in hashers() {
+        let stored = hasher.hash(b"password").unwrap();
+        assert_eq!(hasher.verify(b"password", &stored), Ok(()), "{}", stored);
+        assert_eq!(hasher.verify(b"wrong", &stored), Err(CheckError::HashMismatch));
+        // new salts
+        assert_ne!(hasher.hash(b"password").unwrap(), stored);
+    }
+}
+
+#[test]
+fn test_insecure_rejected() {
+    let stored = InsecureFastHasher.hash(b"password").unwrap();
+    assert!(stored.starts_with("$insecure$"), "{}", stored);
+
+    let config = SimpleConfig::default();
+    assert_eq!(ScryptHash::parse(&stored), Err(CheckError::UnsupportedFormat));
+    assert_eq!(scrypt_check("password", &stored), Err(CheckError::InvalidFormat));
+    assert_eq!(PasswordHasher::verify(&config, b"password", &stored),
+        Err(CheckError::InvalidFormat));
+    assert_eq!(Verifier::builder().build().verify("password", &stored),
+        Err(CheckError::InvalidFormat));
+
+    // and the insecure hasher only accepts its own strings
+    let real = PasswordHasher::hash(&SimpleConfig::builder()
+        .params(Params::new(4, 8, 1).unwrap()).build().unwrap(), b"password").unwrap();
+    assert_eq!(InsecureFastHasher.verify(b"password", &real),
+        Err(CheckError::UnsupportedFormat));
+}
+
+#[test]
+fn test_insecure_malformed() {
+    let stored = InsecureFastHasher.hash(b"password").unwrap();
+    let malformed = [
+        "$insecure$".to_string(),
+        format!("{}$", stored),
+        stored[..stored.len() - 2].to_string(),
+        format!("{}g", &stored[..stored.len() - 1]),
+    ];
+    for hashed in malformed.iter() {
+        assert_eq!(InsecureFastHasher.verify(b"password", hashed),
+            Err(CheckError::InvalidFormat), "{}", hashed);
+    }
+}
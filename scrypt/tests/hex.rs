@@ -0,0 +1,155 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::{scrypt_check, scrypt_simple_hex, verify_any, Format, HashFormat, HashParts,
+    ScryptHash, Params, SimpleConfig};
+use scrypt::errors::CheckError;
+
+// Generated with Python's `hashlib.scrypt`, the salt is `00 01 .. 0f`.
+const HEX: &str = "scrypt:ln=4,r=8,p=1:000102030405060708090a0b0c0d0e0f:\
+                   4a27cb03b2b4b79c8ce895055927f012ed9bdb54d41a88bb9b5a77c7b7e03282";
+
+// The same salt and hash in the other encodings.
+const ENCODED: &[(Format, &str)] = &[
+    (Format::Rscrypt, "$rscrypt$0$BAgB$AAECAwQFBgcICQoLDA0ODw==$\
+                       SifLA7K0t5yM6JUFWSfwEu2b21TUGoi7m1p3x7fgMoI=$"),
+    (Format::Phc, "$scrypt$ln=4,r=8,p=1$AAECAwQFBgcICQoLDA0ODw$\
+                   SifLA7K0t5yM6JUFWSfwEu2b21TUGoi7m1p3x7fgMoI"),
+    (Format::Hex, HEX),
+];
+
+#[test]
+fn test_hex_fixture() {
+    assert_eq!(scrypt_check("password", HEX), Ok(()));
+    assert_eq!(scrypt_check("wrong", HEX), Err(CheckError::HashMismatch));
+    assert_eq!(verify_any("password", HEX).unwrap().format(), Format::Hex);
+    assert_eq!(Format::detect(HEX), Some(Format::Hex));
+
+    // either case is accepted, the canonical form is lowercase
+    let upper = HEX.to_uppercase().replace("SCRYPT:LN=", "scrypt:ln=")
+        .replace(",R=", ",r=").replace(",P=", ",p=");
+    let mixed = HEX.replacen("0a0b", "0A0b", 1).replacen("4a27cb", "4A27Cb", 1);
+    for hashed in [upper, mixed].iter() {
+        assert_ne!(hashed, HEX);
+        assert_eq!(scrypt_check("password", hashed), Ok(()), "{}", hashed);
+        assert_eq!(ScryptHash::parse(hashed).unwrap().to_string(), HEX);
+    }
+}
+
+#[test]
+fn test_hex_same_as_other_encodings() {
+    let parts = Format::Hex.parse(HEX).unwrap();
+    assert_eq!(parts.params, Params::new(4, 8, 1).unwrap());
+    for &(format, hashed) in ENCODED {
+        assert_eq!(format.parse(hashed).unwrap(), parts, "{}", hashed);
+        assert_eq!(format.encode(&parts), hashed);
+        for &password in ["password", "Password", ""].iter() {
+            assert_eq!(scrypt_check(password, hashed), scrypt_check(password, HEX));
+        }
+    }
+
+    // freshly hashed parts verify identically in every encoding
+    let params = Params::new(3, 2, 2).unwrap();
+    let parts = HashParts::new("Ünïcødé".as_bytes(), &params, b"saltsaltsalt", 48).unwrap();
+    for &(format, _) in ENCODED {
+        let hashed = format.encode(&parts);
+        assert_eq!(format.parse(&hashed), Ok(parts.clone()));
+        assert_eq!(scrypt_check("Ünïcødé", &hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+    }
+}
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_scrypt_simple_hex() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let mut rng = FixedRng((0..16).collect());
+    let config = SimpleConfig::builder().params(params).format(Format::Hex).build().unwrap();
+    assert_eq!(config.hash_with_rng("password", &mut rng).unwrap(), HEX);
+
+    let hashed = scrypt_simple_hex("password", &params).unwrap();
+    assert!(hashed.starts_with("scrypt:ln=4,r=8,p=1:"), "{}", hashed);
+    assert_eq!(hashed.len(), HEX.len());
+    assert!(!hashed.contains('$'));
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_hex_lengths() {
+    let params = Params::new(1, 1, 1).unwrap();
+    let parts = |salt_len, hash_len| {
+        HashParts::new(b"password", &params, &vec![0xab; salt_len], hash_len).unwrap()
+    };
+    for &(salt_len, hash_len) in [(8, 16), (64, 64), (16, 32)].iter() {
+        let parts = parts(salt_len, hash_len);
+        assert!(Format::Hex.supports(&parts));
+        let hashed = Format::Hex.encode(&parts);
+        assert_eq!(scrypt_check("password", &hashed), Ok(()), "{}", hashed);
+    }
+    for &(salt_len, hash_len) in [(7, 32), (65, 32), (16, 15), (16, 65)].iter() {
+        let parts = parts(salt_len, hash_len);
+        assert!(!Format::Hex.supports(&parts), "{} {}", salt_len, hash_len);
+        // an encoder without the check, e.g. another implementation
+        let hashed = format!(
+            "scrypt:ln=1,r=1,p=1:{}:{}",
+            "ab".repeat(salt_len),
+            parts.hash.iter().map(|b| format!("{:02x}", b)).collect::<String>(),
+        );
+        assert_eq!(scrypt_check("password", &hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+}
+
+#[test]
+fn test_hex_malformed() {
+    let salt = "000102030405060708090a0b0c0d0e0f";
+    let hash = "4a27cb03b2b4b79c8ce895055927f012ed9bdb54d41a88bb9b5a77c7b7e03282";
+    let invalid = [
+        "scrypt:ln=".to_string(),
+        format!("scrypt:ln=4,r=8,p=1:{}", salt),
+        format!("scrypt:ln=4,r=8,p=1:{}:{}:", salt, hash),
+        format!("scrypt:ln=4,r=8,p=1::{}", hash),
+        format!("scrypt:ln=4,r=8,p=1:{}:", salt),
+        // parameters are required, in order and canonical
+        format!("scrypt:ln=4,r=8:{}:{}", salt, hash),
+        format!("scrypt:ln=4,p=1,r=8:{}:{}", salt, hash),
+        format!("scrypt:ln=04,r=8,p=1:{}:{}", salt, hash),
+        format!("scrypt:ln=4,r=+8,p=1:{}:{}", salt, hash),
+        format!("scrypt:ln=4,r=8,p=1,x=1:{}:{}", salt, hash),
+        format!("scrypt:ln=4,r=0,p=1:{}:{}", salt, hash),
+        format!("scrypt:ln=256,r=8,p=1:{}:{}", salt, hash),
+        // odd lengths and other characters
+        format!("scrypt:ln=4,r=8,p=1:{}0:{}", salt, hash),
+        format!("scrypt:ln=4,r=8,p=1:{}:{}0", salt, hash),
+        format!("scrypt:ln=4,r=8,p=1:{}:{}", salt, hash.replace('a', "g")),
+        format!("scrypt:ln=4,r=8,p=1:{}:{}", salt, hash.replacen("4a", " 4", 1)),
+        format!("scrypt:ln=4,r=8,p=1:{}:0x{}", salt, &hash[2..]),
+        format!("scrypt:ln=4,r=8,p=1:{}$:{}", &salt[..30], hash),
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(Format::detect(hashed), Some(Format::Hex), "{}", hashed);
+        assert_eq!(scrypt_check("password", hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+}
@@ -0,0 +1,78 @@
+//! The bounds of the proofs of `src/verification.rs` for every parameter
+//! set up to `N = 2^10`, `r = 8` and `p = 4`.
+extern crate scrypt;
+
+use scrypt::test_vectors::{integerify, shuffled_offset, v_offset};
+use scrypt::Params;
+
+/// The valid parameters of the enumeration, with `r * 128` and `N`.
+fn small_params() -> Vec<(Params, usize, usize)> {
+    let mut all = Vec::new();
+    for log_n in 0..=10 {
+        for r in 1..=8 {
+            for p in 1..=4 {
+                if let Ok(params) = Params::new(log_n, r, p) {
+                    all.push((params, r as usize * 128, 1 << log_n));
+                }
+            }
+        }
+    }
+    all
+}
+
+/// The 128 bytes of two blocks whose last block starts with the
+/// little-endian `value`.
+fn blocks(value: u64) -> [u8; 128] {
+    let mut x = [0xa5u8; 128];
+    x[64..72].copy_from_slice(&value.to_le_bytes());
+    x
+}
+
+#[test]
+fn test_integerify_in_bounds() {
+    for (_, r128, n) in small_params() {
+        let values = (0..4 * n as u64).chain(vec![u64::MAX, u64::MAX - 1, 1 << 32, 1 << 63]);
+        for value in values {
+            let j = integerify(&blocks(value), n);
+            assert!(j < n, "N = {}, {:#x}", n, value);
+            assert!(v_offset(j, r128) + r128 <= r128 * n);
+        }
+    }
+}
+
+#[test]
+fn test_v_blocks_in_bounds() {
+    for (_, r128, n) in small_params() {
+        for i in 0..n {
+            assert!(v_offset(i + 1, r128) <= r128 * n);
+            assert_eq!(v_offset(i + 1, r128) - v_offset(i, r128), r128);
+        }
+        assert_eq!(v_offset(n, r128), r128 * n);
+    }
+}
+
+#[test]
+fn test_shuffled_offsets() {
+    for (params, r128, _) in small_params() {
+        // a permutation of the 2 * r blocks
+        let mut offsets: Vec<usize> = (0..r128 / 64).map(|i| shuffled_offset(i, r128)).collect();
+        let aligned = offsets.iter().all(|&pos| pos % 64 == 0 && pos + 64 <= r128);
+        assert!(aligned, "{:?}", params);
+        offsets.sort();
+        assert_eq!(offsets, (0..r128 / 64).map(|i| i * 64).collect::<Vec<_>>());
+    }
+    // the even blocks in the first half, the odd ones in the second
+    let r128 = 3 * 128;
+    let offsets: Vec<usize> = (0..6).map(|i| shuffled_offset(i, r128)).collect();
+    assert_eq!(offsets, [0, 192, 64, 256, 128, 320]);
+}
+
+#[test]
+fn test_b_blocks_in_bounds() {
+    for (params, r128, _) in small_params() {
+        let p = params.p() as usize;
+        for block in 0..p {
+            assert!(v_offset(block + 1, r128) <= r128 * p);
+        }
+    }
+}
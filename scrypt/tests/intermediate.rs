@@ -0,0 +1,82 @@
+extern crate scrypt;
+
+use scrypt::test_vectors::{Operation, SCRYPT};
+use scrypt::{scrypt, ScryptIntermediate, Params};
+
+#[test]
+fn test_intermediate_rfc_vectors() {
+    for vector in &SCRYPT[..2] {
+        let (password, salt, log_n, r, p) = match vector.operation {
+            Operation::Scrypt { password, salt, log_n, r, p } => (password, salt, log_n, r, p),
+            _ => unreachable!(),
+        };
+        let params = Params::new(log_n, r, p).unwrap();
+        let intermediate = ScryptIntermediate::compute(password, salt, &params);
+        assert_eq!(intermediate.params(), params);
+        let mut output = vec![0u8; vector.expected.len()];
+        intermediate.expand(password, &mut output).unwrap();
+        assert_eq!(output, vector.expected, "{}", vector.name);
+    }
+}
+
+#[test]
+fn test_intermediate_matches_scrypt() {
+    let params = Params::new(4, 3, 2).unwrap();
+    let intermediate = ScryptIntermediate::compute(b"password", b"salt", &params);
+    for &len in [1, 16, 31, 32, 33, 64, 100, 257].iter() {
+        let mut expanded = vec![0u8; len];
+        intermediate.expand(b"password", &mut expanded).unwrap();
+        let mut expected = vec![0u8; len];
+        scrypt(b"password", b"salt", &params, &mut expected).unwrap();
+        assert_eq!(expanded, expected, "{} bytes", len);
+    }
+}
+
+// PBKDF2 with one iteration: outputs are prefixes of longer outputs
+#[test]
+fn test_intermediate_prefixes() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let intermediate = ScryptIntermediate::compute(b"password", b"salt", &params);
+    let mut long = [0u8; 64];
+    intermediate.expand(b"password", &mut long).unwrap();
+    let mut short = [0u8; 32];
+    intermediate.expand(b"password", &mut short).unwrap();
+    assert_eq!(&long[..32], &short[..]);
+    let mut odd = [0u8; 40];
+    intermediate.expand(b"password", &mut odd).unwrap();
+    assert_eq!(&long[..40], &odd[..]);
+
+    // expanding twice gives the same key, not the second half
+    let mut again = [0u8; 32];
+    intermediate.expand(b"password", &mut again).unwrap();
+    assert_eq!(again, short);
+    assert_ne!(&long[32..], &again[..]);
+}
+
+#[test]
+fn test_intermediate_other_password() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let intermediate = ScryptIntermediate::compute(b"password", b"salt", &params);
+    let mut expanded = [0u8; 32];
+    intermediate.expand(b"other", &mut expanded).unwrap();
+    let mut expected = [0u8; 32];
+    scrypt(b"password", b"salt", &params, &mut expected).unwrap();
+    assert_ne!(expanded, expected);
+}
+
+#[test]
+fn test_intermediate_invalid_output_len() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let intermediate = ScryptIntermediate::compute(b"password", b"salt", &params);
+    let err = intermediate.expand(b"password", &mut []).unwrap_err();
+    assert_eq!(err.provided_len(), 0);
+}
+
+#[test]
+fn test_intermediate_debug() {
+    let params = Params::new(4, 8, 2).unwrap();
+    let intermediate = ScryptIntermediate::compute(b"password", b"salt", &params);
+    assert_eq!(format!("{:?}", intermediate),
+        "ScryptIntermediate { b: <2048 bytes, redacted>, \
+         params: Params { log_n: 4, r: 8, p: 2 } }");
+}
@@ -0,0 +1,117 @@
+#!/usr/bin/env python3
+"""Regenerate the golden vectors of the interop harness.
+
+Run from this directory with the `openssl` command (1.1.1 or later) in the
+`PATH` and libsodium installed:
+
+    python3 generate.py
+
+The fixtures are written to `kdf.txt` and `strings.txt`. Each implementation
+computes the same cases independently, so a mismatch between them fails here
+before it reaches the Rust tests. Strings which embed a random salt are only
+deterministic once they are committed, regenerating them changes the files.
+"""
+import ctypes
+import ctypes.util
+import hashlib
+import subprocess
+
+# password, log_n, r, p, salt, key length
+KDF_CASES = [
+    (b"", 4, 1, 1, b"", 64),
+    (b"password", 10, 8, 16, b"NaCl", 64),
+    (b"pleaseletmein", 14, 8, 1, b"SodiumChloride", 64),
+    (b"password", 1, 1, 1, b"salt", 1),
+    (b"password", 2, 3, 2, b"salt", 16),
+    (b"password", 12, 1, 1, b"saltsaltsaltsalt", 32),
+    (b"p\xc3\xa4ssw\xc3\xb6rd", 8, 4, 3, b"\x00\x01\x02\x03\xff\xfe", 33),
+    # longer than a SHA-256 block, HMAC hashes the key first
+    (b"x" * 100, 5, 2, 1, b"y" * 100, 100),
+    (b"\x00password\x00", 6, 16, 1, b"s\x00lt", 48),
+    (b"password", 15, 1, 1, b"NaCl", 32),
+]
+
+# password, opslimit, memlimit
+SODIUM_STR_CASES = [
+    (b"", 0, 0),
+    (b"password", 32768, 1 << 21),
+    (b"correct horse battery staple", 524288, 16777216),
+    (b"p\xc3\xa4ssw\xc3\xb6rd", 1000000, 16384),
+]
+
+
+def python_kdf(password, log_n, r, p, salt, dk_len):
+    return hashlib.scrypt(password, salt=salt, n=1 << log_n, r=r, p=p,
+                          maxmem=256 << 20, dklen=dk_len)
+
+
+def openssl_kdf(password, log_n, r, p, salt, dk_len):
+    args = ["openssl", "kdf", "-binary", "-keylen", str(dk_len),
+            "-kdfopt", "hexpass:" + password.hex(),
+            "-kdfopt", "hexsalt:" + salt.hex(),
+            "-kdfopt", "n:%d" % (1 << log_n),
+            "-kdfopt", "r:%d" % r, "-kdfopt", "p:%d" % p,
+            "-kdfopt", "maxmem_bytes:%d" % (256 << 20), "SCRYPT"]
+    return subprocess.run(args, check=True, stdout=subprocess.PIPE).stdout
+
+
+def load_sodium():
+    sodium = ctypes.CDLL(ctypes.util.find_library("sodium"))
+    if sodium.sodium_init() < 0:
+        raise RuntimeError("sodium_init failed")
+    return sodium
+
+
+def sodium_kdf(sodium, password, log_n, r, p, salt, dk_len):
+    out = ctypes.create_string_buffer(dk_len)
+    rc = sodium.crypto_pwhash_scryptsalsa208sha256_ll(
+        password, ctypes.c_size_t(len(password)), salt, ctypes.c_size_t(len(salt)),
+        ctypes.c_uint64(1 << log_n), ctypes.c_uint32(r), ctypes.c_uint32(p),
+        out, ctypes.c_size_t(dk_len))
+    if rc != 0:
+        raise RuntimeError("crypto_pwhash_scryptsalsa208sha256_ll failed")
+    return out.raw
+
+
+def sodium_str(sodium, password, opslimit, memlimit):
+    out = ctypes.create_string_buffer(102)
+    rc = sodium.crypto_pwhash_scryptsalsa208sha256_str(
+        out, password, ctypes.c_uint64(len(password)),
+        ctypes.c_uint64(opslimit), ctypes.c_size_t(memlimit))
+    if rc != 0:
+        raise RuntimeError("crypto_pwhash_scryptsalsa208sha256_str failed")
+    return out.value.decode()
+
+
+def main():
+    sodium = load_sodium()
+    sources = [
+        ("openssl", openssl_kdf),
+        ("python", python_kdf),
+        ("libsodium", lambda *case: sodium_kdf(sodium, *case)),
+    ]
+    with open("kdf.txt", "w") as f:
+        f.write("# source password_hex log_n r p salt_hex key_hex\n")
+        f.write("# generated by generate.py, `-` is an empty field\n")
+        for case in KDF_CASES:
+            password, log_n, r, p, salt, _ = case
+            keys = set()
+            for name, kdf in sources:
+                key = kdf(*case)
+                keys.add(key)
+                f.write("%s %s %d %d %d %s %s\n" % (
+                    name, password.hex() or "-", log_n, r, p,
+                    salt.hex() or "-", key.hex()))
+            if len(keys) != 1:
+                raise RuntimeError("implementations disagree on %r" % (case,))
+
+    with open("strings.txt", "w") as f:
+        f.write("# source password_hex encoded\n")
+        f.write("# generated by generate.py, `-` is an empty field\n")
+        for password, opslimit, memlimit in SODIUM_STR_CASES:
+            encoded = sodium_str(sodium, password, opslimit, memlimit)
+            f.write("libsodium %s %s\n" % (password.hex() or "-", encoded))
+
+
+if __name__ == "__main__":
+    main()
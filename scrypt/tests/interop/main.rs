@@ -0,0 +1,67 @@
+//! Golden vectors of other scrypt implementations, see `generate.py`.
+extern crate scrypt;
+
+use scrypt::{scrypt, Params};
+#[cfg(feature="simple-verify")]
+use scrypt::{scrypt_check_bytes, sodium};
+#[cfg(feature="simple-verify")]
+use scrypt::errors::CheckError;
+
+const KDF: &str = include_str!("kdf.txt");
+#[cfg(feature="simple-verify")]
+const STRINGS: &str = include_str!("strings.txt");
+
+/// The whitespace separated fields of the lines of `fixtures` which are not
+/// comments.
+fn records(fixtures: &str) -> Vec<Vec<&str>> {
+    fixtures.lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.split_whitespace().collect())
+        .collect()
+}
+
+fn from_hex(s: &str) -> Vec<u8> {
+    if s == "-" { return Vec::new(); }
+    assert_eq!(s.len() % 2, 0, "{}", s);
+    (0..s.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_kdf_vectors() {
+    let records = records(KDF);
+    assert!(!records.is_empty());
+    for record in records {
+        assert_eq!(record.len(), 7, "{:?}", record);
+        let password = from_hex(record[1]);
+        let params = Params::new(record[2].parse().unwrap(),
+            record[3].parse().unwrap(), record[4].parse().unwrap()).unwrap();
+        let salt = from_hex(record[5]);
+        let expected = from_hex(record[6]);
+
+        let mut output = vec![0u8; expected.len()];
+        scrypt(&password, &salt, &params, &mut output).unwrap();
+        assert_eq!(output, expected, "{:?}", record);
+    }
+}
+
+#[cfg(feature="simple-verify")]
+#[test]
+fn test_string_vectors() {
+    let records = records(STRINGS);
+    assert!(!records.is_empty());
+    for record in records {
+        assert_eq!(record.len(), 3, "{:?}", record);
+        let password = from_hex(record[1]);
+        let encoded = record[2];
+
+        assert_eq!(scrypt_check_bytes(&password, encoded), Ok(()), "{:?}", record);
+        let mut wrong = password.clone();
+        wrong.push(b'!');
+        assert_eq!(scrypt_check_bytes(&wrong, encoded), Err(CheckError::HashMismatch));
+        if record[0] == "libsodium" {
+            assert_eq!(sodium::verify(&password, encoded), Ok(()), "{:?}", record);
+        }
+    }
+}
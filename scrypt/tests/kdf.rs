@@ -0,0 +1,141 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+extern crate sha2;
+
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+use scrypt::Params;
+use scrypt::errors::CheckError;
+use scrypt::kdf::{kdf, kdf_with_rng, parse, verify};
+
+fn from_hex(s: &str) -> Vec<u8> {
+    (0..s.len() / 2)
+        .map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+        .collect()
+}
+
+// Generated with Node's `crypto.scryptSync`, following `kdf()` of the
+// `scrypt-kdf` package.
+const FIXTURES: &[(&str, (u8, u32, u32), &str)] = &[
+    (
+        "my secret password", (15, 8, 1),
+        "736372797074000f0000000800000001bd9d54df4e7148d0867a9efcf46a688f\
+         1eea8e44b62b14c5c9a8380ac3fa30628eee40f414f519f685aeff9fc731022f\
+         d390ad552611f84dd4812b6e074b0d2fb8a5868786028b83fdc44b89648108ed",
+    ),
+    (
+        "pleaseletmein", (10, 8, 16),
+        "736372797074000a0000000800000010993d81d92fea0b855bb960cef073a6f7\
+         69191b74343c55bcc79bdfd5e802ac8686ebf70329ef32ecddcf86c5d17f76c7\
+         389127e10fc889e26a09ed42e98fb78a940a535276b09c6af971c873d0c39ae2",
+    ),
+    (
+        "Ünïcødé", (4, 1, 3),
+        "73637279707400040000000100000003bfe71b22df5d518a248e0508916b5967\
+         190db161c77ae4c651f20313235517b208cbdfc9eab6489aea152734d9dfe267\
+         5d64f6e0f411747f293a2810af33ae3a18d0ba61fa242bf10641d2e70a2c315d",
+    ),
+    (
+        "", (6, 2, 1),
+        "73637279707400060000000200000001e907887a246da9da57fb85a4b459f9e0\
+         31fdd5e29e6d9e6bc5d351ac84a095518b70c1a82894418350a34c0336986e0b\
+         cd986bd80fc0a84208600a5fca050a0ff331c311ea2d45489450129ae7c6df29",
+    ),
+];
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_kdf_fixtures() {
+    for &(password, (log_n, r, p), record) in FIXTURES {
+        let record = from_hex(record);
+        let parsed = parse(&record).unwrap();
+        let params = Params::new(log_n, r, p).unwrap();
+        assert_eq!(parsed.params(), params);
+        assert_eq!(&parsed.salt()[..], &record[16..48]);
+
+        assert_eq!(verify(password.as_bytes(), &record), Ok(()), "{}", password);
+        assert_eq!(verify(b"wrong", &record), Err(CheckError::HashMismatch));
+
+        let rng = &mut FixedRng(record[16..48].to_vec());
+        let generated = kdf_with_rng(password.as_bytes(), &params, rng).unwrap();
+        assert_eq!(&generated[..], &record[..]);
+    }
+}
+
+#[test]
+fn test_kdf_round_trip() {
+    let params = Params::new(4, 2, 3).unwrap();
+    let record = kdf(b"password", &params).unwrap();
+    assert_eq!(&record[..7], b"scrypt\0");
+    assert_eq!(verify(b"password", &record), Ok(()));
+    assert_eq!(verify(b"wrong", &record), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_kdf_tampered() {
+    let record = from_hex(FIXTURES[2].2);
+
+    // any change to the header fails on the checksum, even with the correct
+    // password
+    for &i in [7, 11, 15, 16, 47, 48, 63].iter() {
+        let mut tampered = record.clone();
+        tampered[i] ^= 1;
+        assert_eq!(verify("Ünïcødé".as_bytes(), &tampered),
+            Err(CheckError::InvalidChecksum), "{}", i);
+        assert_eq!(parse(&tampered), Err(CheckError::InvalidChecksum));
+    }
+
+    // the HMAC is only checked with the password
+    let mut tampered = record.clone();
+    tampered[95] ^= 1;
+    assert!(parse(&tampered).is_ok());
+    assert_eq!(verify("Ünïcødé".as_bytes(), &tampered),
+        Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_kdf_malformed() {
+    let record = from_hex(FIXTURES[2].2);
+    let mut invalid = vec![
+        record[..95].to_vec(),
+        [&record[..], &[0]].concat(),
+        Vec::new(),
+    ];
+    for &(i, value) in [(0, b'S'), (6, 1)].iter() {
+        let mut tampered = record.clone();
+        tampered[i] = value;
+        invalid.push(tampered);
+    }
+    // log_n of 0 and r * p too large, with a matching checksum
+    for &(i, value) in [(7, 0), (8, 0x40)].iter() {
+        let mut tampered = record.clone();
+        tampered[i] = value;
+        let checksum = Sha256::digest(&tampered[..48]);
+        tampered[48..64].copy_from_slice(&checksum[..16]);
+        invalid.push(tampered);
+    }
+    for record in invalid.iter() {
+        assert_eq!(parse(record), Err(CheckError::InvalidFormat));
+        assert_eq!(verify("Ünïcødé".as_bytes(), record),
+            Err(CheckError::InvalidFormat));
+    }
+}
@@ -0,0 +1,71 @@
+extern crate scrypt;
+
+use scrypt::{derive_key_and_iv, scrypt, Params};
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Generated with Python's `hashlib.scrypt` and checked with `openssl kdf`,
+// the first `key_len` bytes of one output are the key, the rest the IV. The
+// vectors must not change, ciphertexts of earlier versions depend on them:
+// password, salt, `(log_n, r, p)`, key and IV.
+type Vector = (&'static [u8], &'static [u8], (u8, u32, u32), &'static str, &'static str);
+
+const VECTORS: &[Vector] = &[
+    (b"password", b"NaCl", (10, 8, 1),
+     "27b418c674c769d12501fbb1f53bac32df6514c0f28d043872b148b348961a79",
+     "057a6861cc3553246aa0ddb63bc07445"),
+    (b"pleaseletmein", b"SodiumChloride", (14, 8, 1),
+     "7023bdcb3afd7348461c06cd81fd38eb", "fda8fbba904f8e3ea9b543f6"),
+    (b"", b"salt", (4, 1, 1),
+     "eec80a460eeaab62fe1630b19497e7ba6a1ff85f50807b9cfe52a9f192e5b60c", ""),
+];
+
+#[test]
+fn test_key_and_iv_vectors() {
+    for &(password, salt, (log_n, r, p), key, iv) in VECTORS {
+        let params = Params::new(log_n, r, p).unwrap();
+        let (derived_key, derived_iv) =
+            derive_key_and_iv(password, salt, &params, key.len() / 2, iv.len() / 2).unwrap();
+        assert_eq!(to_hex(&derived_key), key);
+        assert_eq!(to_hex(&derived_iv), iv);
+    }
+}
+
+#[test]
+fn test_key_and_iv_split() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let mut output = [0u8; 48];
+    scrypt(b"password", b"salt", &params, &mut output).unwrap();
+    let (key, iv) = derive_key_and_iv(b"password", b"salt", &params, 32, 16).unwrap();
+    assert_eq!(key.as_bytes(), &output[..32]);
+    assert_eq!(&iv[..], &output[32..]);
+    // other lengths split another output, the key is not a prefix of it
+    let (key, iv) = derive_key_and_iv(b"password", b"salt", &params, 16, 32).unwrap();
+    assert_eq!(key.as_bytes(), &output[..16]);
+    assert_eq!(&iv[..], &output[16..]);
+}
+
+#[test]
+fn test_key_and_iv_lengths() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let err = derive_key_and_iv(b"password", b"salt", &params, 0, 16).unwrap_err();
+    assert_eq!(err.provided_len(), 16);
+    assert!(derive_key_and_iv(b"password", b"salt", &params, 0, 0).is_err());
+    let err = derive_key_and_iv(b"password", b"salt", &params, usize::MAX, 1).unwrap_err();
+    assert_eq!(err.provided_len(), usize::MAX);
+    if usize::MAX as u64 > 0xffff_ffff * 32 {
+        let len = 0xffff_ffff * 32 + 1;
+        let err = derive_key_and_iv(b"password", b"salt", &params, len as usize - 16, 16)
+            .unwrap_err();
+        assert_eq!(err.provided_len() as u64, len);
+    }
+}
+
+#[test]
+fn test_key_debug() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let (key, _) = derive_key_and_iv(b"password", b"salt", &params, 32, 16).unwrap();
+    assert_eq!(format!("{:?}", key), "DerivedKey(<redacted>)");
+}
@@ -0,0 +1,219 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::{scrypt_simple, scrypt_simple_provided, KeyProvider, ParamsPolicy, Params, PepperSet,
+    SimpleConfig, StaticKeys, Verifier};
+use scrypt::errors::{CheckError, ErrorCode, HashError, KeyError, Rule};
+
+const KEY: &[u8] = b"pepper key 1";
+
+// The vector of `tests/pepper.rs`: pepper 7, salt `00 01 .. 0f`.
+const PEPPERED: &str = "$rscrypt$2$BAgAAAABAAAAECABBw$AAECAwQFBgcICQoLDA0ODw$\
+                        rHLE6Ijo8iU76Eb/+7LuWCzbgR5PvTvU6yyOJW4Vs14";
+
+/// A KMS which copies its keys into a buffer for every fetch and wipes it
+/// after the call.
+struct MockKms {
+    available: bool,
+    fetches: AtomicUsize,
+    buffer: Mutex<Vec<u8>>,
+}
+
+impl MockKms {
+    fn new(available: bool) -> MockKms {
+        MockKms { available, fetches: AtomicUsize::new(0), buffer: Mutex::new(Vec::new()) }
+    }
+
+    fn fetches(&self) -> usize {
+        self.fetches.load(Ordering::SeqCst)
+    }
+
+    fn assert_wiped(&self) {
+        let buffer = self.buffer.lock().unwrap();
+        assert!(buffer.iter().all(|&b| b == 0), "{:?}", buffer);
+    }
+}
+
+impl KeyProvider for MockKms {
+    fn key(&self, id: u8, f: &mut dyn FnMut(&[u8])) -> Result<(), KeyError> {
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+        if !self.available { return Err(KeyError::Unavailable); }
+        let key: &[u8] = match id {
+            7 => KEY,
+            8 => b"pepper key 2",
+            _ => return Err(KeyError::UnknownId),
+        };
+        let mut buffer = self.buffer.lock().unwrap();
+        buffer.clear();
+        buffer.extend_from_slice(key);
+        f(&buffer);
+        for b in buffer.iter_mut() { *b = 0; }
+        Ok(())
+    }
+}
+
+/// A provider which returns `Ok` without a key.
+struct Forgetful;
+
+impl KeyProvider for Forgetful {
+    fn key(&self, _id: u8, _f: &mut dyn FnMut(&[u8])) -> Result<(), KeyError> { Ok(()) }
+}
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+/// Verify `password` against `hashed` with a `Verifier` of `keys`.
+fn check_provided<K: KeyProvider + Send + Sync + 'static>(
+    password: &str, hashed: &str, keys: &Arc<K>,
+) -> Result<(), CheckError> {
+    Verifier::builder().key_provider(keys.clone()).build().verify(password, hashed)
+}
+
+#[test]
+fn test_provided_fixture() {
+    let kms = Arc::new(MockKms::new(true));
+    assert_eq!(check_provided("password", PEPPERED, &kms), Ok(()));
+    assert_eq!(kms.fetches(), 1);
+    kms.assert_wiped();
+    assert_eq!(check_provided("wrong", PEPPERED, &kms), Err(CheckError::HashMismatch));
+    assert_eq!(kms.fetches(), 2);
+    kms.assert_wiped();
+
+    let params = Params::new(4, 8, 1).unwrap();
+    let mut rng = FixedRng((0..16).collect());
+    let config = SimpleConfig::builder().params(params).pepper_id(7).build().unwrap();
+    let hashed = config.hash_with_keys_rng("password", &*kms, &mut rng);
+    assert_eq!(hashed.unwrap(), PEPPERED);
+    assert_eq!(kms.fetches(), 3);
+    kms.assert_wiped();
+
+    // a trait object works as well
+    let keys: &dyn KeyProvider = &*kms;
+    let hashed = scrypt_simple_provided("Ünïcødé", &params, keys, 8).unwrap();
+    assert_eq!(check_provided("Ünïcødé", &hashed, &kms), Ok(()));
+    assert_eq!(kms.fetches(), 5);
+    kms.assert_wiped();
+}
+
+#[test]
+fn test_unknown_id() {
+    let kms = Arc::new(MockKms::new(true));
+    let params = Params::new(4, 8, 1).unwrap();
+    let err = scrypt_simple_provided("password", &params, &*kms, 9).unwrap_err();
+    assert!(matches!(err, HashError::UnknownPepper(9)));
+    assert_eq!(err.code(), ErrorCode::NeedsPepper);
+
+    let mut keys = StaticKeys::new();
+    keys.insert(8, b"pepper key 2");
+    assert_eq!(check_provided("password", PEPPERED, &Arc::new(keys)),
+        Err(CheckError::NeedsPepper(7)));
+    assert_eq!(kms.fetches(), 1);
+}
+
+#[test]
+fn test_unavailable() {
+    let kms = Arc::new(MockKms::new(false));
+    let err = check_provided("password", PEPPERED, &kms).unwrap_err();
+    assert_eq!(err, CheckError::KeyUnavailable(7));
+    assert_eq!(err.code(), ErrorCode::KeyUnavailable);
+    assert_eq!(err.to_string(), "the pepper 7 can not be fetched");
+    assert_ne!(err, CheckError::NeedsPepper(7));
+
+    let params = Params::new(4, 8, 1).unwrap();
+    let err = scrypt_simple_provided("password", &params, &*kms, 7).unwrap_err();
+    assert!(matches!(err, HashError::KeyUnavailable(7)));
+    assert_eq!(err.code(), ErrorCode::KeyUnavailable);
+    assert_eq!(kms.fetches(), 2);
+
+    // a provider which does not lend the key has not provided it
+    assert_eq!(check_provided("password", PEPPERED, &Arc::new(Forgetful)),
+        Err(CheckError::KeyUnavailable(7)));
+    assert!(matches!(scrypt_simple_provided("password", &params, &Forgetful, 7),
+        Err(HashError::KeyUnavailable(7))));
+}
+
+#[test]
+fn test_no_fetch() {
+    let kms = Arc::new(MockKms::new(true));
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashed = scrypt_simple("password", &params).unwrap();
+    assert_eq!(check_provided("password", &hashed, &kms), Ok(()));
+    assert_eq!(check_provided("wrong", &hashed, &kms), Err(CheckError::HashMismatch));
+    assert_eq!(check_provided("password", "", &kms), Err(CheckError::InvalidFormat));
+
+    // the key is only fetched for well-formed peppered strings
+    let malformed = "$rscrypt$2$BAgAAAABAAAAECABBw$AAECAwQFBgcICQoLDA0ODw$!";
+    assert!(check_provided("password", malformed, &kms).is_err());
+    let long = "x".repeat(scrypt::DEFAULT_MAX_PASSWORD_LEN + 1);
+    assert!(matches!(check_provided(&long, PEPPERED, &kms),
+        Err(CheckError::PasswordTooLong { .. })));
+    assert_eq!(kms.fetches(), 0);
+}
+
+#[test]
+fn test_static_keys() {
+    let mut peppers = PepperSet::new();
+    peppers.insert(7, KEY);
+    let keys = Arc::new(StaticKeys::from(peppers.clone()));
+    assert_eq!(check_provided("password", PEPPERED, &keys), Ok(()));
+
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashed = scrypt_simple_provided("password", &params, &*keys, 7).unwrap();
+    assert_eq!(Verifier::builder().build().verify_with_keys("password", &hashed, &peppers), Ok(()));
+    assert_eq!(check_provided("wrong", &hashed, &keys), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_verifier() {
+    let kms = Arc::new(MockKms::new(true));
+    let verifier = Verifier::builder().key_provider(kms.clone()).strict_encoding(true).build();
+    assert_eq!(verifier.verify("password", PEPPERED), Ok(()));
+    assert_eq!(verifier.verify("wrong", PEPPERED), Err(CheckError::HashMismatch));
+    assert_eq!(kms.fetches(), 2);
+    kms.assert_wiped();
+
+    let hashed = scrypt_simple("password", &Params::new(4, 8, 1).unwrap()).unwrap();
+    assert_eq!(verifier.verify("password", &hashed), Ok(()));
+    assert_eq!(kms.fetches(), 2);
+
+    // peppered strings must satisfy the rules before the key is fetched
+    let verifier = Verifier::builder()
+        .key_provider(kms.clone())
+        .require(ParamsPolicy { min_log_n: 10, ..ParamsPolicy::default() })
+        .build();
+    assert_eq!(verifier.verify("password", PEPPERED),
+        Err(CheckError::Refused(Rule::MinLogN)));
+    let verifier = Verifier::builder()
+        .key_provider(kms.clone())
+        .allow_rscrypt_versions(&[0, 1])
+        .build();
+    assert_eq!(verifier.verify("password", PEPPERED),
+        Err(CheckError::Refused(Rule::Version)));
+    assert_eq!(kms.fetches(), 2);
+
+    let verifier = Verifier::builder().key_provider(Arc::new(MockKms::new(false))).build();
+    assert_eq!(verifier.verify("password", PEPPERED), Err(CheckError::KeyUnavailable(7)));
+    let verifier = Verifier::builder().build();
+    assert_eq!(verifier.verify("password", PEPPERED), Err(CheckError::NeedsPepper(7)));
+}
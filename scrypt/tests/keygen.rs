@@ -0,0 +1,272 @@
+#![cfg(feature="keygen")]
+//! The public keys of derived seeds, computed by a plain implementation of
+//! X25519 and Ed25519 key generation, which is checked against the vectors
+//! of RFC 7748 and RFC 8032 first.
+extern crate scrypt;
+extern crate sha2;
+
+use std::mem::MaybeUninit;
+use std::ptr;
+
+use sha2::{Digest, Sha512};
+
+use scrypt::keygen::{derive_ed25519_seed, derive_x25519_seed};
+use scrypt::Params;
+
+/// An element of GF(2^255 - 19) in five limbs of 51 bits.
+type Fe = [u64; 5];
+
+const MASK: u64 = (1 << 51) - 1;
+
+fn fe(small: u64) -> Fe {
+    [small, 0, 0, 0, 0]
+}
+
+fn carry(mut r: [u128; 5]) -> Fe {
+    for i in 0..4 {
+        r[i + 1] += r[i] >> 51;
+        r[i] &= MASK as u128;
+    }
+    r[0] += (r[4] >> 51) * 19;
+    r[4] &= MASK as u128;
+    r[1] += r[0] >> 51;
+    r[0] &= MASK as u128;
+    [r[0] as u64, r[1] as u64, r[2] as u64, r[3] as u64, r[4] as u64]
+}
+
+fn add(a: Fe, b: Fe) -> Fe {
+    let mut r = [0u128; 5];
+    for i in 0..5 { r[i] = (a[i] + b[i]) as u128; }
+    carry(r)
+}
+
+fn sub(a: Fe, b: Fe) -> Fe {
+    // a + 2p - b
+    let two_p = [(1 << 52) - 38, (1 << 52) - 2, (1 << 52) - 2, (1 << 52) - 2, (1 << 52) - 2];
+    let mut r = [0u128; 5];
+    for i in 0..5 { r[i] = (a[i] + two_p[i] - b[i]) as u128; }
+    carry(r)
+}
+
+fn mul(a: Fe, b: Fe) -> Fe {
+    let m = |x: u64, y: u64| x as u128 * y as u128;
+    let b19 = [0, b[1] * 19, b[2] * 19, b[3] * 19, b[4] * 19];
+    carry([
+        m(a[0], b[0]) + m(a[1], b19[4]) + m(a[2], b19[3]) + m(a[3], b19[2]) + m(a[4], b19[1]),
+        m(a[0], b[1]) + m(a[1], b[0]) + m(a[2], b19[4]) + m(a[3], b19[3]) + m(a[4], b19[2]),
+        m(a[0], b[2]) + m(a[1], b[1]) + m(a[2], b[0]) + m(a[3], b19[4]) + m(a[4], b19[3]),
+        m(a[0], b[3]) + m(a[1], b[2]) + m(a[2], b[1]) + m(a[3], b[0]) + m(a[4], b19[4]),
+        m(a[0], b[4]) + m(a[1], b[3]) + m(a[2], b[2]) + m(a[3], b[1]) + m(a[4], b[0]),
+    ])
+}
+
+fn square(a: Fe) -> Fe {
+    mul(a, a)
+}
+
+/// `a^(p - 2)`, where the bits of `p - 2 = 2^255 - 21` are all set but 2
+/// and 4.
+fn invert(a: Fe) -> Fe {
+    let mut r = fe(1);
+    for i in (0..255).rev() {
+        r = square(r);
+        if i != 2 && i != 4 { r = mul(r, a); }
+    }
+    r
+}
+
+/// The field element of the low 255 bits of `bytes`.
+fn from_bytes(bytes: &[u8; 32]) -> Fe {
+    let mut r = [0u64; 5];
+    for i in 0..255 {
+        r[i / 51] |= u64::from(bytes[i / 8] >> (i % 8) & 1) << (i % 51);
+    }
+    r
+}
+
+fn to_bytes(a: Fe) -> [u8; 32] {
+    // subtract p if `a >= p`
+    let mut a = carry([a[0] as u128, a[1] as u128, a[2] as u128, a[3] as u128, a[4] as u128]);
+    let mut q = (a[0] + 19) >> 51;
+    for limb in &a[1..] { q = (limb + q) >> 51; }
+    a[0] += 19 * q;
+    for i in 0..4 {
+        a[i + 1] += a[i] >> 51;
+        a[i] &= MASK;
+    }
+    a[4] &= MASK;
+    let mut bytes = [0u8; 32];
+    for i in 0..255 {
+        bytes[i / 8] |= ((a[i / 51] >> (i % 51) & 1) as u8) << (i % 8);
+    }
+    bytes
+}
+
+fn clamp(scalar: &[u8]) -> [u8; 32] {
+    let mut k = [0u8; 32];
+    k.copy_from_slice(scalar);
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+    k
+}
+
+fn bit(k: &[u8; 32], i: usize) -> bool {
+    k[i / 8] >> (i % 8) & 1 == 1
+}
+
+/// The public key of the X25519 secret `k`, the ladder of RFC 7748.
+fn x25519_public(k: &[u8; 32]) -> [u8; 32] {
+    let k = clamp(k);
+    let x1 = fe(9);
+    let (mut x2, mut z2, mut x3, mut z3) = (fe(1), fe(0), x1, fe(1));
+    let mut swap = false;
+    for t in (0..255).rev() {
+        let k_t = bit(&k, t);
+        if swap != k_t {
+            std::mem::swap(&mut x2, &mut x3);
+            std::mem::swap(&mut z2, &mut z3);
+        }
+        swap = k_t;
+        let (a, b) = (add(x2, z2), sub(x2, z2));
+        let (aa, bb) = (square(a), square(b));
+        let e = sub(aa, bb);
+        let (da, cb) = (mul(sub(x3, z3), a), mul(add(x3, z3), b));
+        x3 = square(add(da, cb));
+        z3 = mul(x1, square(sub(da, cb)));
+        x2 = mul(aa, bb);
+        z2 = mul(e, add(aa, mul(fe(121665), e)));
+    }
+    if swap {
+        std::mem::swap(&mut x2, &mut x3);
+        std::mem::swap(&mut z2, &mut z3);
+    }
+    to_bytes(mul(x2, invert(z2)))
+}
+
+/// A point of edwards25519 in extended coordinates.
+#[derive(Clone, Copy)]
+struct Point { x: Fe, y: Fe, z: Fe, t: Fe }
+
+/// The addition of "Twisted Edwards Curves Revisited", which also doubles.
+fn point_add(p: Point, q: Point, d2: Fe) -> Point {
+    let a = mul(sub(p.y, p.x), sub(q.y, q.x));
+    let b = mul(add(p.y, p.x), add(q.y, q.x));
+    let c = mul(mul(p.t, d2), q.t);
+    let d = mul(add(p.z, p.z), q.z);
+    let (e, f, g, h) = (sub(b, a), sub(d, c), add(d, c), add(b, a));
+    Point { x: mul(e, f), y: mul(g, h), z: mul(f, g), t: mul(e, h) }
+}
+
+/// The public key of the Ed25519 seed, section 5.1.5 of RFC 8032.
+fn ed25519_public(seed: &[u8; 32]) -> [u8; 32] {
+    let s = clamp(&Sha512::digest(seed)[..32]);
+    let d = sub(fe(0), mul(fe(121665), invert(fe(121666))));
+    let d2 = add(d, d);
+    let mut bx = [0u8; 32];
+    bx.copy_from_slice(&hex("1ad5258f602d56c9b2a7259560c72c695cdcd6fd31e2a4c0fe536ecdd3366921"));
+    let (x, y) = (from_bytes(&bx), mul(fe(4), invert(fe(5))));
+    let base = Point { x, y, z: fe(1), t: mul(x, y) };
+
+    let mut p = Point { x: fe(0), y: fe(1), z: fe(1), t: fe(0) };
+    for i in (0..256).rev() {
+        p = point_add(p, p, d2);
+        if bit(&s, i) { p = point_add(p, base, d2); }
+    }
+    let z = invert(p.z);
+    let mut public = to_bytes(mul(p.y, z));
+    public[31] |= (to_bytes(mul(p.x, z))[0] & 1) << 7;
+    public
+}
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len() / 2)
+        .map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+        .collect()
+}
+
+fn array(s: &str) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&hex(s));
+    bytes
+}
+
+#[test]
+fn test_rfc_vectors() {
+    // RFC 7748, section 6.1 and RFC 8032, section 7.1, test 1
+    assert_eq!(
+        x25519_public(&array("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a")),
+        array("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"));
+    assert_eq!(
+        ed25519_public(&array("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60")),
+        array("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"));
+}
+
+struct Fixture {
+    passphrase: &'static [u8],
+    salt: &'static [u8],
+    ed25519_seed: &'static str,
+    ed25519_public: &'static str,
+    x25519_secret: &'static str,
+    x25519_public: &'static str,
+}
+
+// Generated with Python's `hashlib.scrypt` and the Ed25519 and X25519 keys
+// of the `cryptography` package, with `log_n = 4`, `r = 8` and `p = 1`.
+const FIXTURES: &[Fixture] = &[
+    Fixture {
+        passphrase: b"correct horse battery staple",
+        salt: b"wallet@example.com",
+        ed25519_seed: "0048d4106c1087281d17ad6bc4263f2fdf01f7b90611d9d3ae03d50bd0aa5ea1",
+        ed25519_public: "4b0264d0161a95f84aed16709824e2c88893bbceae28a3c8649528331ad40fa9",
+        x25519_secret: "e9eceb54d26469add5d30c5baa7a6b17fdc7b528b98dfd51d08350338b31f45d",
+        x25519_public: "39c7240ff531954b5ff41cc553188f48c460b41e52322404445efdf45364a936",
+    },
+    Fixture {
+        passphrase: b"",
+        salt: b"",
+        ed25519_seed: "95fb08016f08312fe2a2e25d333db5cbf6a934e4daf5db4b886ede21e80c96ec",
+        ed25519_public: "33ecca8b6939a29a7c99de1905700468ee5cfc9ada23cbbfa2ad583d7222c016",
+        x25519_secret: "3dfb339672e0ea4744cc139fd31070e4283df7f6d33a0cde6630db98549267a0",
+        x25519_public: "bd306a1696ea54a89015be7db65094ab950fcb9aab4245e6b88dff995628ef5c",
+    },
+];
+
+#[test]
+fn test_fixtures() {
+    let params = Params::new(4, 8, 1).unwrap();
+    for f in FIXTURES {
+        let seed = derive_ed25519_seed(f.passphrase, f.salt, &params);
+        assert_eq!(seed.as_bytes(), &array(f.ed25519_seed));
+        assert_eq!(ed25519_public(&seed), array(f.ed25519_public));
+
+        let secret = derive_x25519_seed(f.passphrase, f.salt, &params);
+        assert_eq!(&secret[..], &hex(f.x25519_secret)[..]);
+        assert_eq!(x25519_public(&secret), array(f.x25519_public));
+    }
+}
+
+#[test]
+fn test_separated() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let seed = derive_ed25519_seed(b"passphrase", b"salt", &params);
+    assert_ne!(*seed, *derive_x25519_seed(b"passphrase", b"salt", &params));
+    assert_ne!(*seed, *derive_ed25519_seed(b"passphrase", b"salu", &params));
+    assert_ne!(*seed, *derive_ed25519_seed(b"passphrase", b"salt",
+        &Params::new(5, 8, 1).unwrap()));
+}
+
+#[test]
+fn test_redacted_and_zeroized() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let seed = derive_ed25519_seed(b"passphrase", b"salt", &params);
+    assert_eq!(format!("{:?}", seed), "Seed(<redacted>)");
+    assert_ne!(seed.as_bytes(), &[0; 32]);
+
+    let mut slot = MaybeUninit::new(seed);
+    let bytes = unsafe {
+        ptr::drop_in_place(slot.as_mut_ptr());
+        ptr::read(slot.as_ptr() as *const [u8; 32])
+    };
+    assert_eq!(bytes, [0; 32]);
+}
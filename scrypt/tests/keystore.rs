@@ -0,0 +1,93 @@
+#![cfg(feature="keystore")]
+extern crate scrypt;
+
+use scrypt::errors::ParamsReason;
+use scrypt::keystore::{derive_keystore_key, KdfParams};
+
+fn kdf_params(n: u64, r: u32, p: u32, salt: &str) -> KdfParams {
+    KdfParams { dklen: 32, n, p, r, salt: salt.to_string() }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// The derived keys were computed with Python's `hashlib.scrypt`, the MAC key
+// is their second half.
+const FIXTURES: &[(&str, u64, u32, u32, &str, &str)] = &[
+    (
+        // go-ethereum's "light" parameters
+        "testpassword", 4096, 8, 6,
+        "c1386a1f4132f86ef055eeaedf56f5d49424a74f7fa9c3042ec4235148f9eba2",
+        "1d66cd19c037caecec841a1023017549e49b9e715e885dc2ebd98557bbe75cde",
+    ),
+    (
+        // go-ethereum's standard parameters
+        "foo", 262144, 8, 1,
+        "7d30bca787274ab3a4540cca62ce26e513e63e3847593a3b416c5dd0014e8b0f",
+        "7c36e7e199fd7becb5f6ef427045391aeba58ab23dd79e660d2dd00d41202af2",
+    ),
+    (
+        "Ünïcødé", 2, 8, 1,
+        "B25E5C57B159AAD75E6289853334A24F444EF8137F06842E4854CDB7629B4550",
+        "5b28ad716853bd14da4e4fa11ca1afb75c00356ebd7a7c9eb992dd799e1f978a",
+    ),
+];
+
+#[test]
+fn test_keystore_fixtures() {
+    for &(password, n, r, p, salt, key) in FIXTURES {
+        let params = kdf_params(n, r, p, salt);
+        let derived = derive_keystore_key(password.as_bytes(), &params).unwrap();
+        assert_eq!(to_hex(&derived), key, "{}", password);
+    }
+}
+
+#[test]
+fn test_keystore_wiki_vector() {
+    // The scrypt test vector of the Web3 Secret Storage definition: geth
+    // derives fac192ce...bba6bd (MAC 2103ac29...6e3097), but `N = 2^18` is
+    // too large for `r = 1` according to RFC 7914.
+    let params = kdf_params(262144, 1, 8,
+        "ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19");
+    assert_eq!(derive_keystore_key(b"testpassword", &params).unwrap_err().reason(),
+        ParamsReason::NTooLargeForR);
+}
+
+#[test]
+fn test_keystore_invalid() {
+    let salt = "c1386a1f4132f86ef055eeaedf56f5d49424a74f7fa9c3042ec4235148f9eba2";
+    let mut invalid = vec![
+        (kdf_params(0, 8, 1, salt), ParamsReason::NotPowerOfTwo),
+        (kdf_params(1, 8, 1, salt), ParamsReason::LogNZero),
+        (kdf_params(4095, 8, 1, salt), ParamsReason::NotPowerOfTwo),
+        (kdf_params(4096, 0, 1, salt), ParamsReason::RZero),
+        (kdf_params(4096, 8, 0, salt), ParamsReason::PZero),
+        (kdf_params(4096, 8, 1, &salt[1..]), ParamsReason::Other),
+        (kdf_params(4096, 8, 1, "0x00"), ParamsReason::Other),
+        (kdf_params(4096, 8, 1, "zz"), ParamsReason::Other),
+    ];
+    if cfg!(target_pointer_width = "64") {
+        invalid.push((kdf_params(1 << 63, 8, 1, salt), ParamsReason::MemoryOverflow));
+    }
+    for &dklen in [0, 16, 31, 33, 64].iter() {
+        let mut params = kdf_params(4096, 8, 1, salt);
+        params.dklen = dklen;
+        invalid.push((params, ParamsReason::Other));
+    }
+    for &(ref params, reason) in invalid.iter() {
+        assert_eq!(derive_keystore_key(b"password", params).map_err(|e| e.reason()),
+            Err(reason), "{:?}", params);
+    }
+    assert_eq!(kdf_params(4096, 8, 1, "").salt_bytes(), Ok(Vec::new()));
+}
+
+#[test]
+fn test_keystore_debug() {
+    let salt = FIXTURES[1].4;
+    let debug = format!("{:?}", kdf_params(1 << 18, 8, 1, salt));
+    assert!(debug.starts_with("KdfParams { dklen: 32, n: 262144, p: 1, r: 8, \
+                               salt: <64 bytes, fingerprint "), "{}", debug);
+    assert!(!debug.contains(salt));
+    assert_eq!(debug, format!("{:?}", kdf_params(1 << 18, 8, 1, salt)));
+}
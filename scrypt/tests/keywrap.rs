@@ -0,0 +1,140 @@
+#![cfg(feature="keywrap")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand::prng::ChaChaRng;
+
+use scrypt::errors::{ErrorCode, HashError, UnwrapError};
+use scrypt::keywrap::{unwrap_key, wrap_key, wrap_key_with_rng, WrappedKey};
+use scrypt::Params;
+
+fn from_hex(s: &str) -> Vec<u8> {
+    (0..s.len() / 2)
+        .map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+        .collect()
+}
+
+fn params() -> Params {
+    Params::new(4, 8, 1).unwrap()
+}
+
+/// A generator which repeats `salt`, to wrap under the salts of `FIXTURES`.
+struct FixedSalt([u8; 16]);
+
+impl RngCore for FixedSalt {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) { dest.copy_from_slice(&self.0[..dest.len()]) }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedSalt {}
+
+// Generated with Python's `hashlib.scrypt` and `aes_key_wrap` of the
+// `cryptography` package: (salt, data key, blob), all with the passphrase
+// `passphrase`, `log_n = 4`, `r = 8` and `p = 1`.
+const FIXTURES: &[(&str, &str, &str)] = &[
+    ("000102030405060708090a0b0c0d0e0f",
+     "202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f",
+     "734b5700040800000001000000000102030405060708090a0b0c0d0e0fa3007f0fe5e64879977ad37a8e19\
+      96087c3b64f15650e1318108fd35e4dc102eabdb8bbb70e9cd65"),
+    ("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+     "000102030405060708090a0b0c0d0e0f",
+     "734b5700040800000001000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa4303a8b9c35570641263550871b3\
+      0819a70c38cd95021fb7"),
+];
+
+#[test]
+fn test_fixtures() {
+    for &(salt, data_key, blob) in FIXTURES {
+        let (data_key, blob) = (from_hex(data_key), from_hex(blob));
+        let mut fixed = [0u8; 16];
+        fixed.copy_from_slice(&from_hex(salt));
+
+        let wrapped = wrap_key_with_rng(b"passphrase", &data_key, &params(),
+            &mut FixedSalt(fixed)).unwrap();
+        assert_eq!(wrapped.to_bytes(), blob);
+        assert_eq!(wrapped.salt(), &fixed[..]);
+        assert_eq!(wrapped.key_len(), data_key.len());
+
+        let parsed = WrappedKey::from_bytes(&blob).unwrap();
+        assert_eq!(parsed, wrapped);
+        assert_eq!(parsed.params(), params());
+        assert_eq!(unwrap_key(b"passphrase", &parsed).unwrap().as_bytes(), &data_key[..]);
+    }
+}
+
+#[test]
+fn test_roundtrip() {
+    let mut rng = ChaChaRng::from_seed([3; 32]);
+    for &len in [16, 24, 32, 64, 512].iter() {
+        let data_key: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let wrapped = wrap_key_with_rng(b"passphrase", &data_key, &params(), &mut rng).unwrap();
+        let parsed = WrappedKey::from_bytes(&wrapped.to_bytes()).unwrap();
+        assert_eq!(unwrap_key(b"passphrase", &parsed).unwrap().as_bytes(), &data_key[..]);
+    }
+
+    // every wrap has a new salt
+    let a = wrap_key(b"passphrase", &[7; 32], &params()).unwrap();
+    let b = wrap_key(b"passphrase", &[7; 32], &params()).unwrap();
+    assert_ne!(a.salt(), b.salt());
+    assert_ne!(a.to_bytes(), b.to_bytes());
+    assert_eq!(unwrap_key(b"passphrase", &a).unwrap().as_bytes(), &[7; 32]);
+}
+
+#[test]
+fn test_wrong_passphrase() {
+    let blob = from_hex(FIXTURES[0].2);
+    let wrapped = WrappedKey::from_bytes(&blob).unwrap();
+    let err = unwrap_key(b"passphrasf", &wrapped).unwrap_err();
+    assert_eq!(err, UnwrapError::IntegrityCheckFailed);
+    assert_eq!(err.code(), ErrorCode::HashMismatch);
+}
+
+#[test]
+fn test_corrupted() {
+    let blob = from_hex(FIXTURES[0].2);
+    // a flipped bit of the salt changes the key-encryption key, one of the
+    // wrapped key fails the integrity check
+    for i in 13..blob.len() {
+        let mut corrupted = blob.clone();
+        corrupted[i] ^= 0x10;
+        let wrapped = WrappedKey::from_bytes(&corrupted).unwrap();
+        assert_eq!(unwrap_key(b"passphrase", &wrapped).unwrap_err(),
+            UnwrapError::IntegrityCheckFailed, "{}", i);
+    }
+
+    let cases: &[(&[u8], UnwrapError)] = &[
+        (b"", UnwrapError::NotWrapped),
+        (b"sKV\x00", UnwrapError::NotWrapped),
+        (b"sKW", UnwrapError::Malformed),
+        (b"sKW\x01", UnwrapError::UnsupportedVersion),
+        (&blob[..28], UnwrapError::Malformed),
+        // a wrapped key of 8 bytes, and one which is not a multiple of 8
+        (&blob[..45], UnwrapError::Malformed),
+        (&blob[..blob.len() - 1], UnwrapError::Malformed),
+    ];
+    for &(bytes, err) in cases {
+        assert_eq!(WrappedKey::from_bytes(bytes), Err(err), "{:?}", bytes);
+    }
+
+    let mut invalid = blob.clone();
+    invalid[4] = 0;
+    assert_eq!(WrappedKey::from_bytes(&invalid), Err(UnwrapError::InvalidParams));
+    assert_eq!(UnwrapError::InvalidParams.code(), ErrorCode::InvalidParams);
+    assert_eq!(UnwrapError::NotWrapped.code(), ErrorCode::UnsupportedFormat);
+}
+
+#[test]
+fn test_invalid_key_len() {
+    for &len in [0, 8, 15, 17, 33].iter() {
+        match wrap_key(b"passphrase", &vec![0; len], &params()) {
+            Err(HashError::InvalidKeyLen(l)) => assert_eq!(l, len),
+            res => panic!("{:?}", res),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use std::io;
+
+use scrypt::{scrypt_check, scrypt_simple_lambdaworks, Params};
+use scrypt::errors::{CheckError, ErrorCode, HashError};
+
+const FIXTURES: &[(&str, &str)] = &[
+    // from the documentation of the Java library, default parameters
+    (
+        "secret",
+        "$s0$e0801$epIxT/h6HbbwHaehFnh/bw==$\
+         7H0vsXlY8UxxyW/BWx/9GuY7jEvGjT71GFd6O4SZND0=",
+    ),
+    // generated with Python's `hashlib.scrypt`, following `SCryptUtil.scrypt`
+    (
+        "secret",
+        "$s0$a1002$aX8yc5o1Hsv8L5gjvTWb/g==$\
+         LnUKL1v2fH3YsTDLQrI4tfsrXYH5cDWvPbtSo9rzOSQ=",
+    ),
+    (
+        "Ünïcødé",
+        "$s0$4ff01$7aYzvKZbLz7mIlwdQkU/zw==$\
+         uxN9fW6YUTfb5XqsHttSYf3wnEN0xuPKxH7RwjiJADI=",
+    ),
+    (
+        "",
+        "$s0$6010c$6xFRVKObC3j7pn8argJYdw==$\
+         nUmr44BPG1eSmZ5wWz8bnGJjIP5Vv5Ud2EeKVEuhZ2w=",
+    ),
+    (
+        "password",
+        "$s0$c0401$aD6TbcmBCMzahYHkZ79f8Q==$\
+         CYwH77Zvq/Ik62h2Uq7+oweIuUOzCqwiknmrfv4hJi8=",
+    ),
+];
+
+#[test]
+fn test_lambdaworks_fixtures() {
+    for &(password, hashed) in FIXTURES {
+        assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+        assert_eq!(
+            scrypt_check("wrong", hashed),
+            Err(CheckError::HashMismatch),
+            "{}", hashed
+        );
+    }
+}
+
+#[test]
+fn test_lambdaworks_round_trip() {
+    let params = Params::new(4, 255, 3).unwrap();
+    let hashed = scrypt_simple_lambdaworks("password", &params).unwrap();
+    assert!(hashed.starts_with("$s0$4ff03$"));
+    assert_eq!(hashed.len(), 4 + 5 + 1 + 24 + 1 + 44);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_lambdaworks_unsupported_params() {
+    for &(r, p) in [(256, 1), (1, 256)].iter() {
+        let params = Params::new(4, r, p).unwrap();
+        let err = scrypt_simple_lambdaworks("password", &params).unwrap_err();
+        match err {
+            HashError::UnsupportedParams => (),
+            ref e => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(err.code(), ErrorCode::UnsupportedParams);
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn test_lambdaworks_malformed() {
+    let salt_hash = "$aD6TbcmBCMzahYHkZ79f8Q==$\
+                     CYwH77Zvq/Ik62h2Uq7+oweIuUOzCqwiknmrfv4hJi8=";
+    let invalid_params = ["", "+c0401", "-c0401", "c04 1", "1000401", "c0001",
+        "c0400", "400401", "100c0401"];
+    for params in invalid_params.iter() {
+        let hashed = format!("$s0${}{}", params, salt_hash);
+        assert_eq!(scrypt_check("password", &hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+
+    let invalid = [
+        "$s0$c0401$aD6TbcmBCMzahYHkZ79f8Q==",
+        "$s0$c0401$aD6TbcmBCMzahYHkZ79f8Q==$",
+        "$s0$c0401$aD6TbcmBCMzahYHkZ79f8Q==$CYwH77Zvq/Ik62h2Uq7+ow==",
+        "$s0$c0401$aD6TbcmBCMzahYHkZ79f8Q==$\
+         CYwH77Zvq/Ik62h2Uq7+oweIuUOzCqwiknmrfv4hJi8=$",
+        "$s0$c0401$aD6TbcmBCMzahYHkZ79f8Q==$\
+         CYwH77Zvq/Ik62h2Uq7-oweIuUOzCqwiknmrfv4hJi8=",
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(scrypt_check("password", hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+
+    // uppercase hex digits are accepted like Java's `Long.parseLong` does
+    let hashed = format!("$s0$C0401{}", salt_hash);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+}
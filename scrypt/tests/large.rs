@@ -0,0 +1,75 @@
+//! `V` buffers larger than 4 GiB: the index arithmetic of ROMix with
+//! synthetic values, and one real configuration, ignored by default.
+extern crate scrypt;
+
+use scrypt::test_vectors::{integerify, v_offset};
+use scrypt::{scrypt, Params};
+
+/// Blocks of `r = 1` whose last block starts with the little-endian `value`.
+fn blocks(value: u64) -> [u8; 128] {
+    let mut x = [0xffu8; 128];
+    x[64..72].copy_from_slice(&value.to_le_bytes());
+    x
+}
+
+#[test]
+fn test_integerify() {
+    // the bytes after the first 8 of the last block do not matter
+    assert_eq!(integerify(&blocks(0x1234_5678), 1 << 4), 0x8);
+    assert_eq!(integerify(&blocks(0), 1 << 20), 0);
+    assert_eq!(integerify(&blocks(u64::MAX), 2), 1);
+    // only the last block counts
+    let mut x = [0u8; 256];
+    x[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert_eq!(integerify(&x, 1 << 10), 0);
+    // how a 32 bit read would truncate
+    assert_eq!(integerify(&blocks(0x1_0000_0000), 1 << 20), 0);
+}
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn test_integerify_above_32_bits() {
+    assert_eq!(integerify(&blocks(0x1_0000_0001), 1 << 33), 0x1_0000_0001);
+    assert_eq!(integerify(&blocks(0xdead_beef_0123_4567), 1 << 40), 0xef_0123_4567);
+    assert_eq!(integerify(&blocks(u64::MAX), 1 << 63), (1 << 63) - 1);
+}
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn test_v_offset_above_32_bits() {
+    // N = 2^22, r = 64: a V of 32 GiB
+    let len = 128 * 64;
+    assert_eq!(v_offset((1 << 22) - 1, len), (1 << 35) - len);
+    // N = 2^15, r = 1025 of `test_above_4_gib`
+    let len = 128 * 1025;
+    assert_eq!(v_offset((1 << 15) - 1, len), 4_299_030_400);
+    assert_eq!(v_offset(1 << 15, len) as u64, 128 * 1025 * (1u64 << 15));
+}
+
+#[test]
+fn test_v_offset() {
+    assert_eq!(v_offset(0, 1024), 0);
+    assert_eq!(v_offset(3, 128), 384);
+    assert_eq!(v_offset((1 << 20) - 1, 1024), (1 << 30) - 1024);
+}
+
+// 128 * 1025 * 2^15 bytes, 4.004 GiB, against
+// `openssl kdf -keylen 64 -kdfopt pass:password -kdfopt salt:NaCl
+//  -kdfopt n:32768 -kdfopt r:1025 -kdfopt p:1 SCRYPT`
+#[cfg(target_pointer_width = "64")]
+#[test]
+#[ignore]
+fn test_above_4_gib() {
+    let params = Params::new(15, 1025, 1).unwrap();
+    assert!(params.required_memory() > 1 << 32);
+    let mut output = [0u8; 64];
+    scrypt(b"password", b"NaCl", &params, &mut output).unwrap();
+    let expected = [
+        0xc5, 0x37, 0x43, 0x89, 0x57, 0xff, 0xaa, 0x53, 0x25, 0x56, 0x5a, 0xc1, 0xb4, 0xaf,
+        0xe6, 0xd0, 0xe8, 0x7b, 0xd8, 0x53, 0xa5, 0x4e, 0xae, 0x7a, 0x53, 0xb8, 0x01, 0x45,
+        0x79, 0xcd, 0x03, 0xa2, 0x8f, 0x6a, 0x4e, 0x21, 0x01, 0x70, 0xca, 0xd5, 0xf1, 0xb0,
+        0x2e, 0xc9, 0xc0, 0x82, 0x41, 0x95, 0x15, 0xca, 0x46, 0x05, 0x3f, 0x43, 0x79, 0x15,
+        0x02, 0x7d, 0xe3, 0x0a, 0x1d, 0x3f, 0x55, 0x00,
+    ];
+    assert_eq!(&output[..], &expected[..]);
+}
@@ -0,0 +1,131 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::{scrypt_check, verify_any, wrap_legacy_hash, wrap_legacy_hash_with_rng,
+    LegacyDigest, Params, Verifier};
+use scrypt::errors::CheckError;
+
+fn verify_wrapped(password: &str, hashed: &str) -> Result<(), CheckError> {
+    Verifier::builder().legacy_wrapped(true).build().verify(password, hashed)
+}
+
+// Generated with Python's `hashlib`: the digests of "password" and their
+// scrypt with `log_n = 4, r = 8, p = 1` and the salt `00 01 .. 0f`.
+const FIXTURES: &[(LegacyDigest, &str, &str)] = &[
+    (LegacyDigest::Plain, "70617373776f7264",
+     "$rscrypt-wrapped$plain$0$BAgB$AAECAwQFBgcICQoLDA0ODw==$\
+      SifLA7K0t5yM6JUFWSfwEu2b21TUGoi7m1p3x7fgMoI=$"),
+    (LegacyDigest::Md5, "5f4dcc3b5aa765d61d8327deb882cf99",
+     "$rscrypt-wrapped$md5$0$BAgB$AAECAwQFBgcICQoLDA0ODw==$\
+      bHMVOCDZTGLf413VOBk40S2SqI2FUa6ha5eqmQaxlNw=$"),
+    (LegacyDigest::Sha1, "5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8",
+     "$rscrypt-wrapped$sha1$0$BAgB$AAECAwQFBgcICQoLDA0ODw==$\
+      JmAfVml8UjWdKTTnQIBl5V8h4Bb68deDIvFV4JC8NbY=$"),
+    (LegacyDigest::Sha256, "5e884898da28047151d0e56f8dc6292773603d0d6aabbdd62a11ef721d1542d8",
+     "$rscrypt-wrapped$sha256$0$BAgB$AAECAwQFBgcICQoLDA0ODw==$\
+      6jmLLGCNebIgEtI3rrACHXF+1lJ92aNWDKKzU6xchWM=$"),
+];
+
+// MD5 of `(i * 7) % 256` for `i < len`, around the padding boundaries
+const MD5_VECTORS: &[(usize, &str)] = &[
+    (0, "d41d8cd98f00b204e9800998ecf8427e"),
+    (1, "93b885adfe0da089cdf634904fd59f71"),
+    (55, "8d24280288a696559fd8d5aa1b6d8c6e"),
+    (56, "ef2c72b7254c92459e498eddd4ace573"),
+    (63, "c4c8c6d513f4e1604eb18508a1769364"),
+    (64, "a2fcb39a253b9b785b1f97518fa37683"),
+    (65, "e49fe82d0bb12967a196c85de313e446"),
+    (200, "d96c89b9da43f6903ceaa14a7166a22e"),
+];
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+}
+
+#[test]
+fn test_wrap_fixtures() {
+    let params = Params::new(4, 8, 1).unwrap();
+    for &(inner, digest, wrapped) in FIXTURES {
+        assert_eq!(inner.digest(b"password"), hex(digest), "{:?}", inner);
+        assert_eq!(LegacyDigest::from_name(inner.name()), Some(inner));
+
+        let mut rng = FixedRng((0..16).collect());
+        assert_eq!(wrap_legacy_hash_with_rng(inner, &hex(digest), &params, &mut rng).unwrap(),
+            wrapped);
+        assert_eq!(verify_wrapped("password", wrapped), Ok(()), "{}", wrapped);
+        assert_eq!(verify_wrapped("wrong", wrapped), Err(CheckError::HashMismatch));
+    }
+}
+
+#[test]
+fn test_md5_vectors() {
+    for &(len, digest) in MD5_VECTORS {
+        let data: Vec<u8> = (0..len).map(|i| (i * 7 % 256) as u8).collect();
+        assert_eq!(LegacyDigest::Md5.digest(&data), hex(digest), "{}", len);
+    }
+}
+
+#[test]
+fn test_wrap_legacy_hash() {
+    let params = Params::new(4, 300, 1).unwrap();
+    let digest = LegacyDigest::Sha1.digest("Ünïcødé".as_bytes());
+    let wrapped = wrap_legacy_hash(LegacyDigest::Sha1, &digest, &params).unwrap();
+    assert!(wrapped.starts_with("$rscrypt-wrapped$sha1$1$"), "{}", wrapped);
+    assert_ne!(wrap_legacy_hash(LegacyDigest::Sha1, &digest, &params).unwrap(), wrapped);
+    assert_eq!(verify_wrapped("Ünïcødé", &wrapped), Ok(()));
+    // the digest itself is not the password
+    let hex_digest: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    assert_eq!(verify_wrapped(&hex_digest, &wrapped), Err(CheckError::HashMismatch));
+    // another inner digest of the same password
+    let md5 = wrapped.replacen("sha1", "md5", 1);
+    assert_eq!(verify_wrapped("Ünïcødé", &md5), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_wrapped_invalid() {
+    let wrapped = FIXTURES[2].2;
+    for &tag in ["sha512", "SHA1", "", "bcrypt"].iter() {
+        let hashed = wrapped.replacen("sha1", tag, 1);
+        assert_eq!(verify_wrapped("password", &hashed),
+            Err(CheckError::UnsupportedFormat), "{}", hashed);
+    }
+    for &hashed in [
+        "",
+        "$rscrypt-wrapped$",
+        "$rscrypt-wrapped$sha1",
+        "$rscrypt-wrapped$sha1$",
+        "$rscrypt-wrapped$sha1$0$BAgB$AAECAwQFBgcICQoLDA0ODw==$",
+        &wrapped["$rscrypt-wrapped$sha1".len()..],
+    ].iter() {
+        assert!(verify_wrapped("password", hashed).is_err(), "{}", hashed);
+    }
+    // unwrapped strings hash the password itself, and wrapped ones are not
+    // rscrypt
+    let rscrypt = format!("$rscrypt{}", &wrapped["$rscrypt-wrapped$sha1".len()..]);
+    assert_eq!(verify_wrapped("password", &rscrypt), Err(CheckError::HashMismatch));
+    assert_eq!(Verifier::builder().build().verify("password", wrapped),
+        Err(CheckError::InvalidFormat));
+    assert_eq!(scrypt_check("password", wrapped), Err(CheckError::InvalidFormat));
+    assert!(verify_any("password", wrapped).is_err());
+}
@@ -0,0 +1,72 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use std::time::{Duration, Instant};
+
+use scrypt::{scrypt_check, scrypt_simple, ParamsPolicy, Params, Verifier};
+use scrypt::errors::{CheckError, Rule};
+
+// `log_n` 4, `r` 8 and `p` 1 take 128 * 8 * (16 + 1 + 1) bytes and
+// 4 * 16 * 8 Salsa20/8 invocations.
+const MEMORY: u64 = 18432;
+const OPS: u64 = 512;
+
+fn verifier(max_memory: u64, max_ops: u64) -> Verifier {
+    Verifier::builder().require(ParamsPolicy { max_memory, max_ops, ..ParamsPolicy::default() })
+        .build()
+}
+
+#[test]
+fn test_limits() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashed = scrypt_simple("password", &params).unwrap();
+
+    let exact = verifier(MEMORY, OPS);
+    assert_eq!(exact.verify("password", &hashed), Ok(()));
+    assert_eq!(exact.verify("wrong", &hashed), Err(CheckError::HashMismatch));
+
+    let limits = [
+        (MEMORY - 1, OPS, Rule::MaxMemory),
+        (MEMORY, OPS - 1, Rule::MaxOps),
+        (0, 0, Rule::MaxMemory),
+    ];
+    for &(max_memory, max_ops, rule) in limits.iter() {
+        // the limits are checked before the password
+        for &password in ["password", "wrong"].iter() {
+            assert_eq!(
+                verifier(max_memory, max_ops).verify(password, &hashed),
+                Err(CheckError::Refused(rule)),
+                "{} {}", max_memory, max_ops
+            );
+        }
+    }
+
+    let generous = verifier(u64::MAX, u64::MAX);
+    for &hashed in ["", "$rscrypt$0$BAgB$"].iter() {
+        assert_eq!(generous.verify("password", hashed), scrypt_check("password", hashed));
+    }
+}
+
+#[test]
+fn test_limits_hostile() {
+    // `log_n` 30 and `r` 1024 would allocate 128 GiB, as would a tiny `N`
+    // with the largest `p`
+    let hostile = [
+        "$rscrypt$1$HgAEAAABAAAA$AAAAAAAAAAAAAAAAAAAAAA==$\
+         AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=$",
+        "$scrypt$ln=30,r=1024,p=1$AAAAAAAAAAAAAAAAAAAAAA$\
+         AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+        "$scrypt$ln=1,r=1,p=1073741823$AAAAAAAAAAAAAAAAAAAAAA$\
+         AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+    ];
+    let verifier = verifier(64 << 20, 1 << 30);
+    for &hashed in hostile.iter() {
+        let start = Instant::now();
+        assert_eq!(
+            verifier.verify("password", hashed),
+            Err(CheckError::Refused(Rule::MaxMemory)),
+            "{}", hashed
+        );
+        assert!(start.elapsed() < Duration::from_millis(100), "{:?}", start.elapsed());
+    }
+}
@@ -0,0 +1,100 @@
+//! The simple API with parameters small enough for Miri, which draws its
+//! salts from the default `DeterministicSource` there, see `entropy`. Run
+//! with
+//!
+//! ```text
+//! cargo +nightly miri test --test miri
+//! ```
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use std::sync::{Arc, Mutex};
+
+use scrypt::entropy::{set_entropy_source, DeterministicSource, EntropySource};
+use scrypt::errors::HashError;
+use scrypt::{scrypt, scrypt_check, scrypt_simple, scrypt_simple_phc, scrypt_simple_v2,
+    scrypt_vectored, Params, ScryptHash, SimpleConfig};
+
+// the tests install process-wide sources
+static SOURCE: Mutex<()> = Mutex::new(());
+
+fn params() -> Params {
+    Params::new(2, 1, 1).unwrap()
+}
+
+#[test]
+fn test_simple_api() {
+    let _guard = SOURCE.lock().unwrap_or_else(|e| e.into_inner());
+    for hashed in [
+        scrypt_simple("password", &params()).unwrap(),
+        scrypt_simple_phc("password", &params()).unwrap(),
+        scrypt_simple_v2("password", &params()).unwrap(),
+        SimpleConfig::builder().params(params()).build().unwrap().hash_with("password").unwrap(),
+    ].iter() {
+        assert_eq!(scrypt_check("password", hashed), Ok(()));
+        assert!(scrypt_check("wrong", hashed).is_err());
+    }
+}
+
+#[test]
+fn test_default_source() {
+    let _guard = SOURCE.lock().unwrap_or_else(|e| e.into_inner());
+    // `OsRng`, or the `DeterministicSource` of Miri, draws a new salt for
+    // every hash
+    let salt = |hashed: &str| ScryptHash::parse(hashed).unwrap().salt().to_vec();
+    let first = scrypt_simple("password", &params()).unwrap();
+    let second = scrypt_simple("password", &params()).unwrap();
+    assert_ne!(salt(&first), salt(&second));
+    assert_eq!(scrypt_check("password", &second), Ok(()));
+}
+
+#[test]
+fn test_scrypt_buffers() {
+    // outputs of partial and several blocks, and `p` above 1
+    for &(log_n, r, p, len) in [(1, 1, 1, 1), (2, 2, 3, 33), (3, 1, 2, 64)].iter() {
+        let params = Params::new(log_n, r, p).unwrap();
+        let (mut output, mut vectored) = (vec![0u8; len], vec![0u8; len]);
+        scrypt(b"password", b"salt", &params, &mut output).unwrap();
+        scrypt_vectored(&[b"pass", b"word"], &[b"salt"], &params, &mut vectored).unwrap();
+        assert_eq!(output, vectored);
+    }
+}
+
+#[test]
+fn test_injected_source() {
+    let _guard = SOURCE.lock().unwrap_or_else(|e| e.into_inner());
+    let hash = |seed| {
+        set_entropy_source(Some(Arc::new(DeterministicSource::new(seed))));
+        let hashed = scrypt_simple("password", &params()).unwrap();
+        set_entropy_source(None);
+        ScryptHash::parse(&hashed).unwrap().salt().to_vec()
+    };
+    // the salt is the first block of the seed
+    assert_eq!(hash([1; 32]), hash([1; 32]));
+    assert_ne!(hash([1; 32]), hash([2; 32]));
+
+    let mut expected = [0u8; 16];
+    DeterministicSource::new([1; 32]).fill(&mut expected).unwrap();
+    assert_eq!(hash([1; 32]), expected);
+}
+
+struct FailingSource;
+
+impl EntropySource for FailingSource {
+    fn fill(&self, _dest: &mut [u8]) -> Result<(), rand::Error> {
+        Err(rand::Error::new(rand::ErrorKind::Unavailable, "no entropy"))
+    }
+}
+
+#[test]
+fn test_failing_source() {
+    let _guard = SOURCE.lock().unwrap_or_else(|e| e.into_inner());
+    set_entropy_source(Some(Arc::new(FailingSource)));
+    let result = scrypt_simple("password", &params());
+    set_entropy_source(None);
+    match result {
+        Err(HashError::Rng(_)) => (),
+        res => panic!("{:?}", res),
+    }
+}
@@ -1,92 +1,70 @@
 extern crate scrypt;
+#[cfg(feature="simple")]
+extern crate rand;
 
-use scrypt::{scrypt, ScryptParams};
-#[cfg(feature="include_simple")]
-use scrypt::{scrypt_simple, scrypt_check};
-#[cfg(feature="include_simple")]
-use scrypt::errors::CheckError;
-
-struct Test {
-    password: &'static str,
-    salt: &'static str,
-    log_n: u8,
-    r: u32,
-    p: u32,
-    expected: Vec<u8>
-}
-
-// Test vectors from [1]. The last test vector is omitted because it takes
-// too long to run.
-fn tests() -> Vec<Test> {
-    vec![
-        Test {
-            password: "",
-            salt: "",
-            log_n: 4,
-            r: 1,
-            p: 1,
-            expected: vec![
-                0x77, 0xd6, 0x57, 0x62, 0x38, 0x65, 0x7b, 0x20,
-                0x3b, 0x19, 0xca, 0x42, 0xc1, 0x8a, 0x04, 0x97,
-                0xf1, 0x6b, 0x48, 0x44, 0xe3, 0x07, 0x4a, 0xe8,
-                0xdf, 0xdf, 0xfa, 0x3f, 0xed, 0xe2, 0x14, 0x42,
-                0xfc, 0xd0, 0x06, 0x9d, 0xed, 0x09, 0x48, 0xf8,
-                0x32, 0x6a, 0x75, 0x3a, 0x0f, 0xc8, 0x1f, 0x17,
-                0xe8, 0xd3, 0xe0, 0xfb, 0x2e, 0x0d, 0x36, 0x28,
-                0xcf, 0x35, 0xe2, 0x0c, 0x38, 0xd1, 0x89, 0x06 ]
-        },
-        Test {
-            password: "password",
-            salt: "NaCl",
-            log_n: 10,
-            r: 8,
-            p: 16,
-            expected: vec![
-                0xfd, 0xba, 0xbe, 0x1c, 0x9d, 0x34, 0x72, 0x00,
-                0x78, 0x56, 0xe7, 0x19, 0x0d, 0x01, 0xe9, 0xfe,
-                0x7c, 0x6a, 0xd7, 0xcb, 0xc8, 0x23, 0x78, 0x30,
-                0xe7, 0x73, 0x76, 0x63, 0x4b, 0x37, 0x31, 0x62,
-                0x2e, 0xaf, 0x30, 0xd9, 0x2e, 0x22, 0xa3, 0x88,
-                0x6f, 0xf1, 0x09, 0x27, 0x9d, 0x98, 0x30, 0xda,
-                0xc7, 0x27, 0xaf, 0xb9, 0x4a, 0x83, 0xee, 0x6d,
-                0x83, 0x60, 0xcb, 0xdf, 0xa2, 0xcc, 0x06, 0x40 ]
-        },
-        Test {
-            password: "pleaseletmein",
-            salt: "SodiumChloride",
-            log_n: 14,
-            r: 8,
-            p: 1,
-            expected: vec![
-                0x70, 0x23, 0xbd, 0xcb, 0x3a, 0xfd, 0x73, 0x48,
-                0x46, 0x1c, 0x06, 0xcd, 0x81, 0xfd, 0x38, 0xeb,
-                0xfd, 0xa8, 0xfb, 0xba, 0x90, 0x4f, 0x8e, 0x3e,
-                0xa9, 0xb5, 0x43, 0xf6, 0x54, 0x5d, 0xa1, 0xf2,
-                0xd5, 0x43, 0x29, 0x55, 0x61, 0x3f, 0x0f, 0xcf,
-                0x62, 0xd4, 0x97, 0x05, 0x24, 0x2a, 0x9a, 0xf9,
-                0xe6, 0x1e, 0x85, 0xdc, 0x0d, 0x65, 0x1e, 0x40,
-                0xdf, 0xcf, 0x01, 0x7b, 0x45, 0x57, 0x58, 0x87 ]
-        },
-    ]
-}
+use scrypt::{scrypt, Params};
+use scrypt::test_vectors::{self_test, Operation, BLOCK_MIX, RO_MIX, SALSA20_8, SCRYPT};
+#[cfg(feature="simple")]
+use scrypt::{scrypt_simple, scrypt_simple_with_salt,
+    scrypt_simple_with_salt_len, scrypt_simple_with_dk_len, scrypt_check};
+#[cfg(feature="simple")]
+use scrypt::{Format, HashFormat, HashParts, ScryptHash, ScryptHashRef};
+#[cfg(feature="simple")]
+use scrypt::{needs_rehash, needs_rehash_for_year, scrypt_simple_v2, Verified, Verifier};
+#[cfg(feature="simple")]
+use scrypt::{scrypt_check_and_rehash, scrypt_check_and_rehash_with_rng, scrypt_simple_hex};
+#[cfg(feature="simple")]
+use scrypt::{scrypt_check_bytes, scrypt_simple_bytes, scrypt_simple_bytes_with_rng};
+#[cfg(feature="simple")]
+use scrypt::{scrypt_simple_phc, SimpleConfig};
+#[cfg(feature="simple")]
+use scrypt::{scrypt_simple_fmt, scrypt_simple_fmt_with_rng, verify_any, OutputFormat};
+#[cfg(feature="simple")]
+use scrypt::{generate_salt, generate_salt_array, generate_salt_array_with_rng,
+    generate_salt_with_rng};
+#[cfg(feature="simple")]
+use scrypt::errors::{CheckError, HashError};
+#[cfg(feature="simple")]
+use rand::{CryptoRng, RngCore, ErrorKind, SeedableRng};
+#[cfg(feature="simple")]
+use rand::prng::ChaChaRng;
 
+// The last scrypt vector of RFC 7914 is omitted because it takes too long
+// to run.
 #[test]
 fn test_scrypt() {
-    let tests = tests();
-    for t in tests.iter() {
-        let mut result = vec![0u8; t.expected.len()];
-        let params = ScryptParams::new(t.log_n, t.r, t.p).unwrap();
-        scrypt(t.password.as_bytes(), t.salt.as_bytes(), &params, &mut result)
-            .unwrap();
-        assert!(result == t.expected);
+    for vector in &SCRYPT[..3] {
+        let (password, salt, log_n, r, p) = match vector.operation {
+            Operation::Scrypt { password, salt, log_n, r, p } => (password, salt, log_n, r, p),
+            _ => unreachable!(),
+        };
+        let mut result = vec![0u8; vector.expected.len()];
+        let params = Params::new(log_n, r, p).unwrap();
+        scrypt(password, salt, &params, &mut result).unwrap();
+        assert!(result == vector.expected, "{}", vector.name);
     }
 }
 
-#[cfg(feature="include_simple")]
+#[test]
+fn test_vectors_self_test() {
+    assert_eq!(self_test(), Ok(()));
+    for vector in &[SALSA20_8, BLOCK_MIX, RO_MIX] {
+        assert_eq!(vector.run(), Ok(()), "{}", vector.name);
+    }
+
+    let mut wrong = SCRYPT[0];
+    wrong.expected = SCRYPT[1].expected;
+    let mismatch = wrong.run().unwrap_err();
+    assert_eq!(mismatch.name, "RFC 7914 scrypt 1");
+    assert_eq!(mismatch.actual, SCRYPT[0].expected);
+    assert_eq!(mismatch.to_string(), "test vector `RFC 7914 scrypt 1` does not match");
+}
+
+#[cfg(feature="simple")]
 fn test_scrypt_simple(log_n: u8, r: u32, p: u32) {
     let password = "password";
 
-    let params = ScryptParams::new(log_n, r, p).unwrap();
+    let params = Params::new(log_n, r, p).unwrap();
     let out1 = scrypt_simple(password, &params).unwrap();
     let out2 = scrypt_simple(password, &params).unwrap();
 
@@ -101,7 +79,7 @@ fn test_scrypt_simple(log_n: u8, r: u32, p: u32) {
     assert_eq!(scrypt_check("wrong", &out2[..]), Err(CheckError::HashMismatch));
 }
 
-#[cfg(feature="include_simple")]
+#[cfg(feature="simple")]
 #[test]
 fn test_scrypt_simple_compact() {
     // These parameters are intentionally very weak - the goal is to make
@@ -109,10 +87,686 @@ fn test_scrypt_simple_compact() {
     test_scrypt_simple(7, 8, 1);
 }
 
-#[cfg(feature="include_simple")]
+#[cfg(feature="simple")]
 #[test]
 fn test_scrypt_simple_expanded() {
     // These parameters are intentionally very weak - the goal is to make
     // the test run quickly!
     test_scrypt_simple(3, 1, 256);
 }
+
+#[cfg(feature="simple")]
+struct FailingRng;
+
+#[cfg(feature="simple")]
+impl RngCore for FailingRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, _: &mut [u8]) { unimplemented!() }
+    fn try_fill_bytes(&mut self, _: &mut [u8]) -> Result<(), rand::Error> {
+        Err(rand::Error::new(ErrorKind::Unavailable, "no entropy"))
+    }
+}
+
+#[cfg(feature="simple")]
+impl CryptoRng for FailingRng {}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_simple_seeded_rng() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let hash = |seed| {
+        let mut rng = ChaChaRng::from_seed([seed; 32]);
+        let config = SimpleConfig::builder().params(params).build().unwrap();
+        config.hash_with_rng("password", &mut rng).unwrap()
+    };
+    // the salt is the first block of the ChaCha20 keystream for an all-zero
+    // key, see RFC 7539
+    let hashed = hash(0);
+    assert_eq!(
+        hashed,
+        "$rscrypt$0$BAgB$drjgraDxPZBAXWrlU4a9KA==$\
+         Ddj9uEadcXOS79L42Coz5Fhi96TNEo/CDeU/mfdA4bM=$",
+    );
+    assert_eq!(hash(0), hashed);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_ne!(hash(1), hashed);
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_simple_rng_failure() {
+    let params = Params::new(3, 1, 1).unwrap();
+    let config = SimpleConfig::builder().params(params).build().unwrap();
+    match config.hash_with_rng("password", &mut FailingRng) {
+        Err(HashError::Rng(ref e)) => assert_eq!(e.kind, ErrorKind::Unavailable),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let err = config.hash_with_rng("password", &mut FailingRng).unwrap_err();
+    assert_eq!(err.code(), scrypt::errors::ErrorCode::Rng);
+
+    let err: std::io::Error = err.into();
+    assert_eq!(err.kind(), std::io::ErrorKind::Other);
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_simple_with_salt() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashed = scrypt_simple_with_salt("password", b"0123456789abcdef", &params).unwrap();
+    assert_eq!(hashed, "$rscrypt$0$BAgB$MDEyMzQ1Njc4OWFiY2RlZg==$jU+wVnnRO8xMJ6kk2pn2W1IFgOT9r8PK+dHZ+HH3bt4=$");
+    assert_eq!(scrypt_simple_with_salt("password", b"0123456789abcdef", &params).unwrap(),
+        hashed);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+
+    // a different salt gives a different string
+    assert_ne!(scrypt_simple_with_salt("password", b"0123456789abcdeg", &params).unwrap(),
+        hashed);
+
+    let params = Params::new(1, 256, 1).unwrap();
+    let hashed = scrypt_simple_with_salt("password", &[0; 16], &params).unwrap();
+    assert!(hashed.starts_with("$rscrypt$1$AQABAAABAAAA$AAAAAAAAAAAAAAAAAAAAAA==$"));
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_simple_with_salt_len() {
+    let params = Params::new(4, 8, 1).unwrap();
+    for &len in [8, 16, 32, 64].iter() {
+        let hashed = scrypt_simple_with_salt_len("password", &params, len).unwrap();
+        assert_eq!(ScryptHash::parse(&hashed).unwrap().salt().len(), len);
+        assert_eq!(scrypt_check("password", &hashed), Ok(()));
+        assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+    }
+
+    for &len in [0, 7, 65, 1000].iter() {
+        match scrypt_simple_with_salt_len("password", &params, len) {
+            Err(HashError::InvalidSaltLen(l)) => assert_eq!(l, len),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+    let err = scrypt_simple_with_salt_len("password", &params, 7).unwrap_err();
+    assert_eq!(err.to_string(), "invalid salt length 7, expected 8..=64");
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_simple_with_dk_len() {
+    let params = Params::new(4, 8, 1).unwrap();
+    for &len in [16, 32, 64].iter() {
+        let hashed = scrypt_simple_with_dk_len("password", &params, len).unwrap();
+        assert_eq!(ScryptHash::parse(&hashed).unwrap().hash_bytes().len(), len);
+        assert_eq!(scrypt_check("password", &hashed), Ok(()));
+        assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+    }
+
+    for &len in [0, 4, 15, 65].iter() {
+        match scrypt_simple_with_dk_len("password", &params, len) {
+            Err(HashError::InvalidDkLen(l)) => assert_eq!(l, len),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+    let err = scrypt_simple_with_dk_len("password", &params, 65).unwrap_err();
+    assert_eq!(err.to_string(), "invalid derived key length 65, expected 16..=64");
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_check_truncated_hash() {
+    let params = Params::new(4, 8, 1).unwrap();
+    for &len in [1, 4, 15].iter() {
+        let parts = HashParts::new(b"password", &params, b"NaCl", len).unwrap();
+        for &format in [Format::Rscrypt, Format::Phc].iter() {
+            let hashed = format.encode(&parts);
+            assert_eq!(scrypt_check("password", &hashed), Err(CheckError::HashTooShort(len)));
+            assert_eq!(
+                ScryptHashRef::parse(&hashed).unwrap().verify("password"),
+                Err(CheckError::HashTooShort(len)),
+            );
+        }
+        assert_eq!(parts.verify(b"password"), Err(CheckError::HashTooShort(len)));
+    }
+    assert_eq!(
+        CheckError::HashTooShort(4).to_string(),
+        "hash of 4 bytes in `hashed_value` is shorter than 16",
+    );
+
+    let parts = HashParts::new(b"password", &params, b"NaCl", 16).unwrap();
+    assert_eq!(scrypt_check("password", &Format::Rscrypt.encode(&parts)), Ok(()));
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_check_bytes() {
+    // generated with Python's `hashlib.scrypt` for b"\xffpass\xc3"
+    let password = b"\xffpass\xc3";
+    let fixture = "$rscrypt$0$BAgB$c2FsdHNhbHRzYWx0c2FsdA==$\
+                   +aQ7lo6Xw+CZLJDc4kDbPfMDE4rSJ9ADagrh11NsJ48=$";
+    assert_eq!(scrypt_check_bytes(password, fixture), Ok(()));
+    assert_eq!(scrypt_check_bytes(b"\xffpass", fixture), Err(CheckError::HashMismatch));
+    // the lossy conversion does not verify
+    let lossy = String::from_utf8_lossy(password);
+    assert_eq!(scrypt_check(&lossy, fixture), Err(CheckError::HashMismatch));
+
+    // hashed with the raw API, in every format with binary salts
+    let params = Params::new(4, 8, 1).unwrap();
+    let parts = HashParts::new(password, &params, b"saltsaltsaltsalt", 32).unwrap();
+    let mut output = [0u8; 32];
+    scrypt(password, b"saltsaltsaltsalt", &params, &mut output).unwrap();
+    assert_eq!(&parts.hash[..], &output[..]);
+    for &format in [Format::Rscrypt, Format::Phc, Format::Hex].iter() {
+        let hashed = format.encode(&parts);
+        assert_eq!(scrypt_check_bytes(password, &hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check_bytes(b"", &hashed), Err(CheckError::HashMismatch));
+    }
+    assert_eq!(Format::Rscrypt.encode(&parts), fixture);
+
+    // valid UTF-8 behaves like `scrypt_check`
+    let hashed = scrypt_simple("Ünïcødé", &params).unwrap();
+    assert_eq!(scrypt_check_bytes("Ünïcødé".as_bytes(), &hashed), Ok(()));
+    assert_eq!(scrypt_check_bytes(b"", "$rscrypt$"), Err(CheckError::InvalidFormat));
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_simple_bytes() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let passwords: [&[u8]; 5] = [
+        b"", b"\0pass", b"pass\0word", b"\xff\xff\xfe", b"\xc3\x28\0\xff",
+    ];
+    for &password in passwords.iter() {
+        let hashed = scrypt_simple_bytes(password, &params).unwrap();
+        assert!(hashed.starts_with("$rscrypt$0$BAgB$"));
+        assert_eq!(scrypt_check_bytes(password, &hashed), Ok(()), "{:?}", password);
+        for &other in passwords.iter().filter(|&&other| other != password) {
+            assert_eq!(scrypt_check_bytes(other, &hashed), Err(CheckError::HashMismatch));
+        }
+    }
+
+    // valid UTF-8 is interchangeable with the `str` functions
+    let hashed = scrypt_simple_bytes("Ünïcødé".as_bytes(), &params).unwrap();
+    assert_eq!(scrypt_check("Ünïcødé", &hashed), Ok(()));
+    let hashed = scrypt_simple("Ünïcødé", &params).unwrap();
+    assert_eq!(scrypt_check_bytes("Ünïcødé".as_bytes(), &hashed), Ok(()));
+
+    let seeded = |password: &[u8]| {
+        let mut rng = ChaChaRng::from_seed([0; 32]);
+        scrypt_simple_bytes_with_rng(password, &params, &mut rng).unwrap()
+    };
+    let mut rng = ChaChaRng::from_seed([0; 32]);
+    assert_eq!(
+        seeded(b"password"),
+        SimpleConfig::builder().params(params).build().unwrap()
+            .hash_with_rng("password", &mut rng).unwrap()
+    );
+    assert_ne!(seeded(b"\0pass"), seeded(b"pass"));
+    // HMAC pads keys with zeros, so trailing NUL bytes are not significant
+    // for passwords of up to 64 bytes, as in every scrypt implementation
+    assert_eq!(seeded(b"pass\0"), seeded(b"pass"));
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_default_lengths() {
+    let verifier = Verifier::builder().default_lengths(true).build();
+    let params = Params::new(4, 8, 1).unwrap();
+    for hashed in [
+        scrypt_simple("password", &params).unwrap(),
+        scrypt_simple("password", &Params::new(1, 256, 1).unwrap()).unwrap(),
+        scrypt_simple_v2("password", &params).unwrap(),
+        scrypt_simple_phc("password", &params).unwrap(),
+    ].iter() {
+        assert_eq!(verifier.verify("password", hashed), Ok(()), "{}", hashed);
+        assert_eq!(verifier.verify("wrong", hashed), Err(CheckError::HashMismatch));
+    }
+
+    // handcrafted hashes verify, but not with the default lengths
+    let short_salt = Format::Rscrypt.encode(
+        &HashParts::new(b"password", &params, b"NaCl", 32).unwrap());
+    let long_hash = scrypt_simple_with_dk_len("password", &params, 64).unwrap();
+    let stripped = &short_salt[..short_salt.len() - 1];
+    for &(hashed, salt_len, hash_len) in
+        [(&short_salt[..], 4, 32), (&long_hash[..], 16, 64), (stripped, 4, 32)].iter()
+    {
+        assert_eq!(scrypt_check("password", hashed), Ok(()));
+        let err = verifier.verify("password", hashed).unwrap_err();
+        assert_eq!(err, CheckError::UnexpectedFieldLen { salt_len, hash_len });
+    }
+    assert_eq!(
+        verifier.verify("password", &short_salt).unwrap_err().to_string(),
+        "salt of 4 and hash of 32 bytes in `hashed_value`, expected 16 and 32",
+    );
+
+    // lengths are checked before computing the hash, which would need 16 GiB
+    // here
+    let expensive = Format::Rscrypt.encode(&HashParts {
+        params: Params::new(24, 8, 1).unwrap(),
+        salt: b"NaCl".to_vec(),
+        hash: vec![0; 32],
+    });
+    assert_eq!(
+        verifier.verify("password", &expensive),
+        Err(CheckError::UnexpectedFieldLen { salt_len: 4, hash_len: 32 }),
+    );
+
+    // short version 2 salts are declared, other formats are not affected
+    let v2 = "$rscrypt$2$BAgAAAABAAAABCAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM";
+    assert_eq!(verifier.verify("password", v2), Ok(()));
+    let phc = "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM";
+    assert_eq!(verifier.verify("Ünïcødé", phc), Ok(()));
+    assert_eq!(verifier.verify("password", ""), Err(CheckError::InvalidFormat));
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_verify_strength() {
+    let weak = Params::new(3, 1, 1).unwrap();
+    let strong = Params::new(4, 2, 1).unwrap();
+    let hashed = scrypt_simple("password", &weak).unwrap();
+    let verifier = |min| Verifier::builder().min_params(min).build();
+
+    assert_eq!(verifier(weak).verify_strength("password", &hashed), Ok(Verified::Ok));
+    assert_eq!(
+        verifier(strong).verify_strength("password", &hashed),
+        Ok(Verified::OkButWeak(weak))
+    );
+    assert_eq!(
+        verifier(strong).verify_strength("wrong", &hashed),
+        Err(CheckError::HashMismatch)
+    );
+    assert_eq!(Verifier::builder().build().verify_strength("password", &hashed),
+        Ok(Verified::Ok));
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_needs_rehash() {
+    let desired = Params::new(4, 8, 2).unwrap();
+    let cases = [
+        // stronger, equal and weaker parameters
+        ((5, 8, 2), false),
+        ((4, 8, 3), false),
+        ((4, 8, 2), false),
+        ((3, 8, 2), true),
+        ((4, 8, 1), true),
+        ((5, 1, 1), true),
+        // equivalent memory and work in other shapes
+        ((5, 4, 2), false),
+        ((4, 16, 1), false),
+        // more work at less memory
+        ((3, 8, 8), true),
+    ];
+    for &((log_n, r, p), expected) in cases.iter() {
+        let params = Params::new(log_n, r, p).unwrap();
+        let hashed = scrypt_simple("password", &params).unwrap();
+        assert_eq!(needs_rehash(&hashed, &desired), Ok(expected), "{:?}", params);
+        let phc = scrypt_simple_phc("password", &params).unwrap();
+        assert_eq!(needs_rehash(&phc, &desired), Ok(expected), "{:?}", params);
+    }
+
+    // salts and hashes shorter than those of `scrypt_simple`
+    let params = Params::new(4, 8, 1).unwrap();
+    let short = [
+        "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+        "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+        "$scrypt$ln=4,r=8,p=1$AAECAwQFBgcICQoLDA0ODw$SifLA7K0t5yM6JUFWSfwEu2b21TUGoi7m1p3",
+        // Cisco salts are 14 characters
+        "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+    ];
+    for &hashed in short.iter() {
+        assert_eq!(needs_rehash(hashed, &params), Ok(true), "{}", hashed);
+    }
+    let long = "$scrypt$ln=4,r=8,p=1$AAECAwQFBgcICQoLDA0ODw$\
+                SifLA7K0t5yM6JUFWSfwEu2b21TUGoi7m1p3x7fgMoI";
+    assert_eq!(needs_rehash(long, &params), Ok(false));
+
+    let argon2 = "$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG";
+    assert_eq!(needs_rehash(argon2, &params), Err(CheckError::UnsupportedFormat));
+    assert_eq!(needs_rehash("", &params), Err(CheckError::UnsupportedFormat));
+    assert_eq!(needs_rehash("$rscrypt$0$BAgB$", &params), Err(CheckError::InvalidFormat));
+    assert_eq!(needs_rehash("$scrypt$ln=4$jgs", &params), Err(CheckError::InvalidFormat));
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_deprecated_version() {
+    // `r` of 256 needs rscrypt version 1, which is deprecated
+    let params = Params::new(1, 256, 1).unwrap();
+    let stored = scrypt_simple("password", &params).unwrap();
+    assert!(stored.starts_with("$rscrypt$1$"), "{}", stored);
+    assert_eq!(needs_rehash(&stored, &params), Ok(true));
+    assert_eq!(needs_rehash(&stored, &Params::new(1, 8, 1).unwrap()), Ok(true));
+
+    // the new hash is written in version 2, which is up to date
+    let rehashed = scrypt_check_and_rehash("password", &stored, &params).unwrap().unwrap();
+    let hash = ScryptHash::parse(&rehashed).unwrap();
+    assert_eq!((hash.format(), hash.format_version()), (Format::Rscrypt, Some(2)));
+    assert_eq!(hash.params(), params);
+    assert_eq!(needs_rehash(&rehashed, &params), Ok(false));
+    assert_eq!(scrypt_check_and_rehash("password", &rehashed, &params), Ok(None));
+
+    // so are new hashes of other strings which would need version 1
+    let stored = scrypt_simple("password", &Params::new(1, 8, 1).unwrap()).unwrap();
+    let rehashed = scrypt_check_and_rehash("password", &stored, &params).unwrap().unwrap();
+    assert_eq!(ScryptHash::parse(&rehashed).unwrap().format_version(), Some(2));
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_needs_rehash_for_year() {
+    let at = |log_n: u8| format!("$scrypt$ln={},r=8,p=1$AAECAwQFBgcICQoLDA0ODw$\
+        SifLA7K0t5yM6JUFWSfwEu2b21TUGoi7m1p3x7fgMoI", log_n);
+    assert_eq!(needs_rehash_for_year(&at(14), 2016), Ok(false));
+    assert_eq!(needs_rehash_for_year(&at(14), 2017), Ok(true));
+    assert_eq!(needs_rehash_for_year(&at(17), 2026), Ok(false));
+    assert_eq!(needs_rehash_for_year(&at(16), 2023), Ok(true));
+    let weak = scrypt_simple("password", &Params::new(4, 8, 1).unwrap()).unwrap();
+    assert_eq!(needs_rehash_for_year(&weak, 2015), Ok(true));
+
+    let err = needs_rehash_for_year(&at(17), 2014).unwrap_err();
+    assert_eq!(err, CheckError::UnknownYear(2014));
+    assert_eq!(err.to_string(), "no parameter presets for the year 2014");
+    assert_eq!(needs_rehash_for_year("", 2027), Err(CheckError::UnknownYear(2027)));
+    assert_eq!(needs_rehash_for_year("", 2026), Err(CheckError::UnsupportedFormat));
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_check_and_rehash() {
+    let weak = Params::new(3, 1, 1).unwrap();
+    let strong = Params::new(4, 2, 1).unwrap();
+    let stored = scrypt_simple("password", &weak).unwrap();
+
+    // match and weak: a new hash with the desired parameters
+    let rehashed = scrypt_check_and_rehash("password", &stored, &strong)
+        .unwrap().unwrap();
+    assert_eq!(ScryptHash::parse(&rehashed).unwrap().params(), strong);
+    assert_eq!(ScryptHash::parse(&rehashed).unwrap().format(), Format::Rscrypt);
+    assert_eq!(scrypt_check("password", &rehashed), Ok(()));
+    assert_eq!(scrypt_check_and_rehash("password", &rehashed, &strong), Ok(None));
+
+    // match and strong
+    assert_eq!(scrypt_check_and_rehash("password", &stored, &weak), Ok(None));
+
+    // mismatch, weak and strong, never computes a hash
+    for params in [weak, strong].iter() {
+        assert_eq!(
+            scrypt_check_and_rehash("wrong", &stored, params),
+            Err(CheckError::HashMismatch)
+        );
+        assert_eq!(
+            scrypt_check_and_rehash_with_rng("wrong", &stored, params, &mut FailingRng),
+            Err(CheckError::HashMismatch)
+        );
+    }
+
+    assert_eq!(
+        scrypt_check_and_rehash_with_rng("password", &stored, &strong, &mut FailingRng),
+        Err(CheckError::RehashFailed)
+    );
+    assert_eq!(
+        scrypt_check_and_rehash_with_rng("password", &stored, &weak, &mut FailingRng),
+        Ok(None)
+    );
+    assert_eq!(scrypt_check_and_rehash("password", "", &weak), Err(CheckError::InvalidFormat));
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_check_and_rehash_formats() {
+    let weak = Params::new(3, 1, 1).unwrap();
+    let strong = Params::new(4, 2, 1).unwrap();
+    let own = [
+        (scrypt_simple("password", &weak).unwrap(), Format::Rscrypt, Some(0)),
+        (scrypt_simple_v2("password", &weak).unwrap(), Format::Rscrypt, Some(2)),
+        (scrypt_simple_phc("password", &weak).unwrap(), Format::Phc, None),
+        (scrypt_simple_hex("password", &weak).unwrap(), Format::Hex, None),
+    ];
+    for &(ref stored, format, version) in own.iter() {
+        assert_eq!(scrypt_check_and_rehash("password", stored, &weak), Ok(None));
+        let rehashed = scrypt_check_and_rehash("password", stored, &strong)
+            .unwrap().unwrap();
+        let hash = ScryptHash::parse(&rehashed).unwrap();
+        assert_eq!((hash.format(), hash.format_version()), (format, version), "{}", rehashed);
+        assert_eq!(hash.params(), strong);
+        assert_eq!(hash.verify("password"), Ok(()));
+    }
+
+    // formats of other implementations are upgraded to rscrypt, even with
+    // strong parameters
+    let django = "scrypt$2$sël$1$1$MbY5a6bob9Ls6RaeNUjmfa+noDsixdIwX+tWM5gfDxMEeztvV\
+                  eNZ+2WDCMX5Zch449tsPzQMS0FZN2LOHt9O2Q==";
+    let rehashed = scrypt_check_and_rehash("Ünïcødé", django, &weak).unwrap().unwrap();
+    assert!(rehashed.starts_with("$rscrypt$0$AwEB$"), "{}", rehashed);
+    assert_eq!(scrypt_check("Ünïcødé", &rehashed), Ok(()));
+
+    // short salts are replaced
+    let phc = "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM";
+    let rehashed = scrypt_check_and_rehash("Ünïcødé", phc, &weak).unwrap().unwrap();
+    assert!(rehashed.starts_with("$scrypt$ln=3,r=1,p=1$"), "{}", rehashed);
+    assert_eq!(ScryptHash::parse(&rehashed).unwrap().salt().len(), 16);
+}
+
+#[test]
+fn test_params_is_weaker_than() {
+    let base = Params::new(10, 8, 1).unwrap();
+    assert!(!base.is_weaker_than(&base));
+    assert!(Params::new(9, 8, 1).unwrap().is_weaker_than(&base));
+    assert!(Params::new(10, 8, 1).unwrap()
+        .is_weaker_than(&Params::new(10, 8, 2).unwrap()));
+    // same memory and work, different shape
+    assert!(!Params::new(9, 16, 1).unwrap().is_weaker_than(&base));
+    // more memory does not make up for less total work
+    assert!(Params::new(11, 8, 1).unwrap()
+        .is_weaker_than(&Params::new(10, 8, 4).unwrap()));
+}
+
+/// Replays a fixed sequence of bytes, used to pin the salt.
+#[cfg(feature="simple")]
+struct FixedRng(Vec<u8>);
+
+#[cfg(feature="simple")]
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+#[cfg(feature="simple")]
+impl CryptoRng for FixedRng {}
+
+// Generated with Python's `hashlib.scrypt` (OpenSSL) and encoded by hand
+// following the PHC string format.
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_simple_phc_fixtures() {
+    let fixtures = [
+        (
+            "password",
+            "2d1b5f7a8c0e913fa44e6b0c1d2e3f40",
+            (10, 8, 1),
+            "$scrypt$ln=10,r=8,p=1$LRtfeowOkT+kTmsMHS4/QA$\
+             oQoHWim7dB7vcgd11aYdc4M4LhnrM0sAiuLqU82afpQ",
+        ),
+        (
+            "correct horse battery staple",
+            "00112233445566778899aabbccddeeff",
+            (6, 4, 2),
+            "$scrypt$ln=6,r=4,p=2$ABEiM0RVZneImaq7zN3u/w$\
+             AGEW3tTNrs3pyN7+plz3mahFv8asYRunJMl5AcLTn+Q",
+        ),
+    ];
+    for &(password, salt, (log_n, r, p), expected) in fixtures.iter() {
+        let salt = (0..salt.len()).step_by(2)
+            .map(|i| u8::from_str_radix(&salt[i..i + 2], 16).unwrap())
+            .collect();
+        let params = Params::new(log_n, r, p).unwrap();
+        let config = SimpleConfig::builder().params(params).format(Format::Phc).build().unwrap();
+        let hashed = config.hash_with_rng(password, &mut FixedRng(salt)).unwrap();
+        assert_eq!(hashed, expected);
+    }
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_simple_phc() {
+    let params = Params::new(3, 1, 2).unwrap();
+    let out1 = scrypt_simple_phc("password", &params).unwrap();
+    let out2 = scrypt_simple_phc("password", &params).unwrap();
+    assert!(out1 != out2);
+    assert!(out1.starts_with("$scrypt$ln=3,r=1,p=2$"));
+    // no padding in the salt and hash fields
+    assert!(!out1.rsplitn(3, '$').take(2).any(|field| field.contains('=')));
+}
+
+// Generated with Python's `hashlib.scrypt` (OpenSSL) and encoded by hand,
+// the second salt is the one of the PHC fixtures
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_simple_fmt_fixtures() {
+    let fixtures = [
+        (
+            "password",
+            "2d1b5f7a8c0e913fa44e6b0c1d2e3f40",
+            (10, 8, 1),
+            [
+                "$rscrypt$0$CggB$LRtfeowOkT+kTmsMHS4/QA==$\
+                 oQoHWim7dB7vcgd11aYdc4M4LhnrM0sAiuLqU82afpQ=$",
+                "$rscrypt$2$CggAAAABAAAAECAA$LRtfeowOkT+kTmsMHS4/QA$\
+                 oQoHWim7dB7vcgd11aYdc4M4LhnrM0sAiuLqU82afpQ",
+                "$scrypt$ln=10,r=8,p=1$LRtfeowOkT+kTmsMHS4/QA$\
+                 oQoHWim7dB7vcgd11aYdc4M4LhnrM0sAiuLqU82afpQ",
+            ],
+        ),
+        (
+            "correct horse battery staple",
+            "00112233445566778899aabbccddeeff",
+            (6, 300, 2),
+            [
+                "$rscrypt$1$BiwBAAACAAAA$ABEiM0RVZneImaq7zN3u/w==$\
+                 J17A7Y9aC1+j0VskmBfHnZyPIX68CnFEGMtcdf+GLdk=$",
+                "$rscrypt$2$BiwBAAACAAAAECAA$ABEiM0RVZneImaq7zN3u/w$\
+                 J17A7Y9aC1+j0VskmBfHnZyPIX68CnFEGMtcdf+GLdk",
+                "$scrypt$ln=6,r=300,p=2$ABEiM0RVZneImaq7zN3u/w$\
+                 J17A7Y9aC1+j0VskmBfHnZyPIX68CnFEGMtcdf+GLdk",
+            ],
+        ),
+    ];
+    let formats = [OutputFormat::Rscrypt, OutputFormat::RscryptV2, OutputFormat::Phc];
+    for &(password, salt, (log_n, r, p), ref expected) in fixtures.iter() {
+        let salt: Vec<u8> = (0..salt.len()).step_by(2)
+            .map(|i| u8::from_str_radix(&salt[i..i + 2], 16).unwrap())
+            .collect();
+        let params = Params::new(log_n, r, p).unwrap();
+        for (&format, &expected) in formats.iter().zip(expected.iter()) {
+            let hashed = scrypt_simple_fmt_with_rng(password, &params, format,
+                &mut FixedRng(salt.clone())).unwrap();
+            assert_eq!(hashed, expected, "{:?}", format);
+            assert_eq!(scrypt_check(password, &hashed), Ok(()));
+            assert!(verify_any(password, &hashed).is_ok());
+        }
+
+        // the default is the output of `scrypt_simple`
+        let config = SimpleConfig::builder().params(params).build().unwrap();
+        let simple = config.hash_with_rng(password, &mut FixedRng(salt.clone())).unwrap();
+        assert_eq!(simple, expected[0]);
+        let default = scrypt_simple_fmt_with_rng(password, &params, OutputFormat::default(),
+            &mut FixedRng(salt)).unwrap();
+        assert_eq!(default, simple);
+    }
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_scrypt_simple_fmt() {
+    let params = Params::new(3, 1, 2).unwrap();
+    let prefixes = [
+        (OutputFormat::Rscrypt, "$rscrypt$0$AwEC$"),
+        (OutputFormat::RscryptV2, "$rscrypt$2$AwEAAAACAAAAECAA$"),
+        (OutputFormat::Phc, "$scrypt$ln=3,r=1,p=2$"),
+    ];
+    for &(format, prefix) in prefixes.iter() {
+        let hashed = scrypt_simple_fmt("password", &params, format).unwrap();
+        assert!(hashed.starts_with(prefix), "{}", hashed);
+        assert_ne!(scrypt_simple_fmt("password", &params, format).unwrap(), hashed);
+        assert_eq!(scrypt_check("password", &hashed), Ok(()));
+        assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+    }
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_generate_salt() {
+    for &len in [8, 16, 64].iter() {
+        let salts: Vec<Vec<u8>> = (0..8).map(|_| generate_salt(len).unwrap()).collect();
+        for (i, salt) in salts.iter().enumerate() {
+            assert_eq!(salt.len(), len);
+            // a repetition of 64 random bits is practically impossible
+            assert!(salts[i + 1..].iter().all(|other| other != salt));
+        }
+    }
+    for &len in [0, 7, 65, usize::MAX].iter() {
+        match generate_salt(len) {
+            Err(HashError::InvalidSaltLen(l)) => assert_eq!(l, len),
+            r => panic!("{}: {:?}", len, r),
+        }
+    }
+
+    let mut rng = FixedRng((0..24).collect());
+    assert_eq!(generate_salt_with_rng(8, &mut rng).unwrap(), (0..8).collect::<Vec<u8>>());
+    let salt: [u8; 16] = generate_salt_array_with_rng(&mut rng).unwrap();
+    assert_eq!(salt.to_vec(), (8..24).collect::<Vec<u8>>());
+
+    let a: [u8; 16] = generate_salt_array().unwrap();
+    let b: [u8; 16] = generate_salt_array().unwrap();
+    assert_ne!(a, b);
+    match generate_salt_array::<4>() {
+        Err(HashError::InvalidSaltLen(4)) => (),
+        r => panic!("{:?}", r),
+    }
+    match generate_salt_array::<128>() {
+        Err(HashError::InvalidSaltLen(128)) => (),
+        r => panic!("{:?}", r),
+    }
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_verify_detailed() {
+    let verifier = Verifier::builder().build();
+    let cases = [
+        (Params::new(4, 8, 1).unwrap(), Some(0)),
+        (Params::new(3, 300, 2).unwrap(), Some(1)),
+    ];
+    for &(params, version) in cases.iter() {
+        let hashed = scrypt_simple("password", &params).unwrap();
+        let info = verifier.verify_detailed("password", &hashed).unwrap();
+        assert_eq!(info.params(), params);
+        assert_eq!(info.format(), Format::Rscrypt);
+        assert_eq!(info.format_version(), version);
+        assert_eq!(info.dk_len(), 32);
+        assert_eq!(verifier.verify_detailed("wrong", &hashed), Err(CheckError::HashMismatch));
+    }
+
+    let params = Params::new(5, 2, 3).unwrap();
+    let info = verifier.verify_detailed("password",
+        &scrypt_simple_with_dk_len("password", &params, 64).unwrap()).unwrap();
+    assert_eq!((info.params(), info.dk_len()), (params, 64));
+    let info = verifier.verify_detailed("password",
+        &scrypt_simple_phc("password", &params).unwrap()).unwrap();
+    assert_eq!((info.format(), info.format_version()), (Format::Phc, None));
+
+    // unknown formats are malformed rscrypt strings, like for `verify`
+    assert_eq!(verifier.verify_detailed("password", ""), Err(CheckError::InvalidFormat));
+}
@@ -0,0 +1,63 @@
+extern crate scrypt;
+
+use scrypt::errors::{Error, ErrorCode};
+use scrypt::{scrypt, Params};
+
+fn derive(log_n: u8, output: &mut [u8]) -> Result<(), Error> {
+    let params = Params::new(log_n, 8, 1)?;
+    scrypt(b"password", b"salt", &params, output)?;
+    Ok(())
+}
+
+#[test]
+fn test_unified_error() {
+    let mut output = [0u8; 32];
+    assert!(derive(4, &mut output).is_ok());
+
+    let err = derive(0, &mut output).unwrap_err();
+    match err {
+        Error::InvalidParams(_) => (),
+        ref e => panic!("{:?}", e),
+    }
+    assert_eq!(err.code(), ErrorCode::InvalidParams);
+    assert_eq!(err.to_string(), "invalid scrypt parameters");
+
+    let err = derive(4, &mut []).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidOutputLen);
+    #[cfg(feature = "std")]
+    {
+        use std::error::Error as _;
+        assert_eq!(err.to_string(), err.source().unwrap().to_string());
+    }
+}
+
+#[cfg(feature="simple-verify")]
+#[test]
+fn test_unified_check_error() {
+    use scrypt::errors::CheckError;
+    use scrypt::scrypt_check;
+
+    fn check(password: &str, hashed: &str) -> Result<(), Error> {
+        Ok(scrypt_check(password, hashed)?)
+    }
+    match check("password", "$rscrypt$") {
+        Err(Error::Check(CheckError::InvalidFormat)) => (),
+        res => panic!("{:?}", res),
+    }
+    let err = Error::from(CheckError::HashMismatch);
+    assert_eq!(err.code(), ErrorCode::HashMismatch);
+    assert_eq!(err.to_string(), "password hash mismatch");
+}
+
+// the old name keeps compiling, with a deprecation warning
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_names() {
+    use scrypt::ScryptParams;
+
+    let old: ScryptParams = ScryptParams::new(4, 8, 1).unwrap();
+    let new: Params = old;
+    assert_eq!(new, Params::new(4, 8, 1).unwrap());
+    let mut output = [0u8; 32];
+    scrypt(b"password", b"salt", &old, &mut output).unwrap();
+}
@@ -0,0 +1,279 @@
+#![cfg(feature="async")]
+extern crate scrypt;
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake};
+use std::thread::{self, Thread};
+
+use scrypt::errors::{CheckError, TaskError};
+use scrypt::nonblocking::{hash_async, iter, spawn, verify_async, verify_stream, Spawner, Stream,
+    ThreadSpawner, VerifyJob};
+use scrypt::{scrypt_check, scrypt_simple, BatchOptions, Format, Password, Params,
+    SimpleConfig, VerifyLimits};
+
+/// The executor of the tests, which parks the thread until it is woken.
+/// The futures of the crate depend on no runtime.
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Arc::new(Unpark(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// A spawner which keeps the jobs until `run` or drops them in `clear`.
+#[derive(Default)]
+struct Queue(Mutex<Vec<Box<dyn FnOnce() + Send>>>);
+
+impl Spawner for Queue {
+    fn spawn_blocking(&self, job: Box<dyn FnOnce() + Send>) {
+        self.0.lock().unwrap().push(job);
+    }
+}
+
+impl Queue {
+    fn run(&self) {
+        let jobs: Vec<_> = self.0.lock().unwrap().drain(..).collect();
+        for job in jobs { job(); }
+    }
+
+    fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
+fn password(password: &str) -> Password {
+    Password::from(password.to_string())
+}
+
+fn config() -> SimpleConfig {
+    SimpleConfig::builder().params(Params::new(4, 8, 1).unwrap()).build().unwrap()
+}
+
+#[test]
+fn test_hash_and_verify() {
+    let hashed = block_on(hash_async(&ThreadSpawner, config(), password("hunter2")))
+        .unwrap();
+    assert_eq!(scrypt_check("hunter2", &hashed), Ok(()));
+    assert_eq!(
+        block_on(verify_async(&ThreadSpawner, password("hunter2"), hashed.clone())),
+        Ok(()),
+    );
+    assert_eq!(
+        block_on(verify_async(&ThreadSpawner, password("wrong"), hashed)),
+        Err(TaskError::Failed(CheckError::HashMismatch)),
+    );
+    assert_eq!(
+        block_on(verify_async(&ThreadSpawner, password("hunter2"), "$rscrypt$".into())),
+        Err(TaskError::Failed(CheckError::InvalidFormat)),
+    );
+
+    let config = SimpleConfig::builder().params(Params::new(4, 8, 1).unwrap())
+        .format(Format::Phc).build().unwrap();
+    let hashed = block_on(hash_async(&ThreadSpawner, config, password("hunter2")))
+        .unwrap();
+    assert!(hashed.starts_with("$scrypt$"), "{}", hashed);
+}
+
+#[test]
+fn test_other_spawners() {
+    // the job runs wherever the spawner runs it, here on the calling thread
+    let queue = Queue::default();
+    let future = hash_async(&queue, config(), password("hunter2"));
+    queue.run();
+    let hashed = block_on(future).unwrap();
+    assert_eq!(scrypt_check("hunter2", &hashed), Ok(()));
+
+    // as a trait object
+    let spawner: &dyn Spawner = &ThreadSpawner;
+    assert_eq!(block_on(verify_async(spawner, password("hunter2"), hashed)), Ok(()));
+}
+
+#[test]
+fn test_panic() {
+    let future = spawn(&ThreadSpawner, || -> Result<(), CheckError> { panic!("in the job") });
+    assert_eq!(block_on(future), Err(TaskError::Panicked));
+}
+
+#[test]
+fn test_dropped_job() {
+    let queue = Queue::default();
+    let future = verify_async(&queue, password("hunter2"), "$rscrypt$".into());
+    queue.clear();
+    assert_eq!(block_on(future), Err(TaskError::Dropped));
+}
+
+#[test]
+fn test_cancel() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let queue = Queue::default();
+    let job = |runs: &Arc<AtomicUsize>| {
+        let runs = runs.clone();
+        move || -> Result<(), CheckError> {
+            runs.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    };
+    // a future dropped before its job started cancels it
+    drop(spawn(&queue, job(&runs)));
+    let kept = spawn(&queue, job(&runs));
+    queue.run();
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    assert_eq!(block_on(kept), Ok(()));
+}
+
+#[test]
+fn test_wake_from_other_thread() {
+    let queue = Arc::new(Queue::default());
+    let future = verify_async(&*queue, password("x"), "$rscrypt$".into());
+    let runner = {
+        let queue = queue.clone();
+        thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(20));
+            queue.run();
+        })
+    };
+    assert_eq!(block_on(future), Err(TaskError::Failed(CheckError::InvalidFormat)));
+    runner.join().unwrap();
+}
+
+#[test]
+fn test_display() {
+    let err: TaskError<CheckError> = TaskError::Failed(CheckError::HashMismatch);
+    assert_eq!(err.to_string(), CheckError::HashMismatch.to_string());
+    assert!(!TaskError::<CheckError>::Panicked.to_string().is_empty());
+}
+
+/// The hashes of the stream tests and whether "password" matches each.
+fn stored() -> Vec<(String, Result<(), CheckError>)> {
+    let cheap = Params::new(2, 8, 1).unwrap();
+    let costly = Params::new(12, 8, 1).unwrap();
+    vec![
+        (scrypt_simple("password", &cheap).unwrap(), Ok(())),
+        (scrypt_simple("other", &cheap).unwrap(), Err(CheckError::HashMismatch)),
+        ("$rscrypt$".to_string(), Err(CheckError::InvalidFormat)),
+        (scrypt_simple("password", &costly).unwrap(), Err(CheckError::CostExceedsLimit)),
+    ]
+}
+
+fn jobs(stored: &[(String, Result<(), CheckError>)], len: usize) -> Vec<VerifyJob<usize>> {
+    (0..len).map(|id| VerifyJob {
+        id,
+        password: password("password"),
+        stored: stored[id % stored.len()].0.clone(),
+    }).collect()
+}
+
+fn options(threads: usize) -> BatchOptions {
+    let limits = VerifyLimits { max_memory: 1 << 20, max_ops: 1 << 20 };
+    BatchOptions { threads, limits: Some(limits) }
+}
+
+/// A `ThreadSpawner` counting the jobs it started.
+#[derive(Default)]
+struct Counting(AtomicUsize);
+
+impl Spawner for Counting {
+    fn spawn_blocking(&self, job: Box<dyn FnOnce() + Send>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        ThreadSpawner.spawn_blocking(job);
+    }
+}
+
+/// A stream counting the jobs taken from it.
+struct Taken<S> {
+    inner: S,
+    taken: Arc<AtomicUsize>,
+}
+
+impl<S: Stream + Unpin> Stream for Taken<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<S::Item>> {
+        let item = Pin::new(&mut self.inner).poll_next(cx);
+        if let Poll::Ready(Some(_)) = item { self.taken.fetch_add(1, Ordering::SeqCst); }
+        item
+    }
+}
+
+#[test]
+fn test_verify_stream() {
+    let stored = stored();
+    let spawner = Counting::default();
+    let taken = Arc::new(AtomicUsize::new(0));
+    let input = Taken { inner: iter(jobs(&stored, 300)), taken: taken.clone() };
+    let mut stream = verify_stream(&spawner, input, &options(4));
+
+    let mut ids = HashSet::new();
+    while let Some(result) = block_on(stream.next()) {
+        assert_eq!(result.result, stored[result.id % 4].1.map_err(TaskError::Failed),
+            "{}", result.id);
+        assert!(ids.insert(result.id));
+        // no more jobs are read and started than results were taken and
+        // jobs may run at once
+        assert!(stream.running() <= 4);
+        assert!(spawner.0.load(Ordering::SeqCst) <= ids.len() + 4);
+        assert!(taken.load(Ordering::SeqCst) <= ids.len() + 4);
+    }
+    assert_eq!(ids.len(), 300);
+    assert_eq!(taken.load(Ordering::SeqCst), 300);
+    assert_eq!(spawner.0.load(Ordering::SeqCst), 300);
+    assert_eq!(block_on(stream.next()), None);
+}
+
+#[test]
+fn test_verify_stream_completion_order() {
+    let stored = stored();
+    let queue = Queue::default();
+    let mut stream = verify_stream(&queue, iter(jobs(&stored, 6)), &options(3));
+    let waker = Arc::new(Unpark(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+
+    // three jobs wait in the queue, the others in the input
+    assert!(Pin::new(&mut stream).poll_next(&mut cx).is_pending());
+    assert_eq!(stream.running(), 3);
+    // the last job to start is the first to complete
+    let jobs: Vec<_> = queue.0.lock().unwrap().drain(..).collect();
+    let mut jobs = jobs.into_iter().rev();
+    jobs.next().unwrap()();
+    match Pin::new(&mut stream).poll_next(&mut cx) {
+        Poll::Ready(Some(result)) => assert_eq!(result.id, 2),
+        other => panic!("{:?}", other),
+    }
+    // the free slot takes the next job
+    assert_eq!(stream.running(), 2);
+    assert!(Pin::new(&mut stream).poll_next(&mut cx).is_pending());
+    assert_eq!(stream.running(), 3);
+    for job in jobs { job(); }
+    queue.run();
+    let mut ids: Vec<usize> = Vec::new();
+    while let Some(result) = block_on(stream.next()) {
+        ids.push(result.id);
+        queue.run();
+    }
+    ids.sort();
+    assert_eq!(ids, vec![0, 1, 3, 4, 5]);
+}
+
+#[test]
+fn test_verify_stream_empty() {
+    let mut stream = verify_stream(ThreadSpawner, iter(Vec::<VerifyJob<()>>::new()),
+        &BatchOptions::default());
+    assert_eq!(block_on(stream.next()), None);
+}
@@ -0,0 +1,129 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use std::sync::{Arc, Mutex};
+
+use rand::{CryptoRng, OsRng, RngCore};
+
+use scrypt::{scrypt_check, scrypt_simple, Format, ParamsPolicy, Params, SimpleConfig,
+    Verifier};
+use scrypt::errors::{CheckError, ErrorCode, Rule};
+use scrypt::observer::{set_global_observer, HashEvent, ScryptObserver, VerifyEvent};
+
+#[derive(Default)]
+struct Collector {
+    hashes: Mutex<Vec<HashEvent>>,
+    verifications: Mutex<Vec<VerifyEvent>>,
+}
+
+impl ScryptObserver for Collector {
+    fn on_hash(&self, event: &HashEvent) {
+        self.hashes.lock().unwrap().push(*event);
+    }
+
+    fn on_verify(&self, event: &VerifyEvent) {
+        self.verifications.lock().unwrap().push(*event);
+    }
+}
+
+struct Silent;
+
+impl ScryptObserver for Silent {}
+
+fn params() -> Params {
+    Params::new(4, 8, 1).unwrap()
+}
+
+#[test]
+fn test_explicit_observer() {
+    let collector = Arc::new(Collector::default());
+    let config = SimpleConfig::builder().params(params()).format(Format::Phc).build().unwrap();
+    let hashed = config.hash_observed("password", &mut OsRng::new().unwrap(), &*collector)
+        .unwrap();
+
+    let verifier = Verifier::builder()
+        .require(ParamsPolicy { min_log_n: 4, ..ParamsPolicy::default() })
+        .observer(collector.clone())
+        .build();
+    assert_eq!(verifier.verify("password", &hashed), Ok(()));
+    assert_eq!(verifier.verify("wrong", &hashed), Err(CheckError::HashMismatch));
+    let weak = scrypt_simple("password", &Params::new(3, 8, 1).unwrap()).unwrap();
+    assert_eq!(verifier.verify("password", &weak), Err(CheckError::Refused(Rule::MinLogN)));
+    assert_eq!(verifier.verify("password", "$rscrypt$"), Err(CheckError::InvalidFormat));
+
+    let hashes = collector.hashes.lock().unwrap();
+    assert_eq!(hashes.len(), 1);
+    assert_eq!(hashes[0].params, params());
+    assert_eq!(hashes[0].format, Format::Phc);
+    assert_eq!(hashes[0].result, Ok(()));
+
+    let verifications = collector.verifications.lock().unwrap();
+    let summary: Vec<_> = verifications.iter()
+        .map(|e| (e.params.map(|p| p.log_n()), e.format, e.result))
+        .collect();
+    assert_eq!(summary, vec![
+        (Some(4), Some(Format::Phc), Ok(())),
+        (Some(4), Some(Format::Phc), Err(CheckError::HashMismatch)),
+        (Some(3), Some(Format::Rscrypt), Err(CheckError::Refused(Rule::MinLogN))),
+        (None, None, Err(CheckError::InvalidFormat)),
+    ]);
+    // only the verifications which computed scrypt take measurable time
+    assert!(verifications[0].elapsed > verifications[3].elapsed);
+}
+
+#[test]
+fn test_failed_hash() {
+    struct FailingRng;
+    impl RngCore for FailingRng {
+        fn next_u32(&mut self) -> u32 { unimplemented!() }
+        fn next_u64(&mut self) -> u64 { unimplemented!() }
+        fn fill_bytes(&mut self, _dest: &mut [u8]) { unimplemented!() }
+        fn try_fill_bytes(&mut self, _dest: &mut [u8]) -> Result<(), rand::Error> {
+            Err(rand::Error::new(rand::ErrorKind::Unavailable, "no entropy"))
+        }
+    }
+    impl CryptoRng for FailingRng {}
+
+    let collector = Collector::default();
+    let config = SimpleConfig::builder().params(params()).build().unwrap();
+    assert!(config.hash_observed("password", &mut FailingRng, &collector).is_err());
+    let hashes = collector.hashes.lock().unwrap();
+    assert_eq!(hashes.len(), 1);
+    assert_eq!(hashes[0].result, Err(ErrorCode::Rng));
+}
+
+#[test]
+fn test_default_methods() {
+    let config = SimpleConfig::builder().params(params()).build().unwrap();
+    let hashed = config.hash_observed("password", &mut OsRng::new().unwrap(), &Silent).unwrap();
+    let verifier = Verifier::builder().observer(Arc::new(Silent)).build();
+    assert_eq!(verifier.verify("password", &hashed), Ok(()));
+}
+
+// The only test of this file using the global observer, which every other
+// test would report to.
+#[test]
+fn test_global_observer() {
+    let collector = Arc::new(Collector::default());
+    set_global_observer(Some(collector.clone()));
+
+    let config = SimpleConfig::builder().params(params()).build().unwrap();
+    let hashed = config.hash_with("password").unwrap();
+    let verifier = Verifier::builder().build();
+    assert_eq!(verifier.verify("password", &hashed), Ok(()));
+    // the free functions do not report
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    scrypt_simple("password", &params()).unwrap();
+
+    set_global_observer(None);
+    config.hash_with("password").unwrap();
+    assert_eq!(verifier.verify("password", &hashed), Ok(()));
+
+    let hashes = collector.hashes.lock().unwrap();
+    let verifications = collector.verifications.lock().unwrap();
+    assert_eq!(hashes.len(), 1);
+    assert_eq!(hashes[0].format, Format::Rscrypt);
+    assert_eq!(verifications.len(), 1);
+    assert_eq!(verifications[0].result, Ok(()));
+}
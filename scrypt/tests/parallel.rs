@@ -0,0 +1,146 @@
+#![cfg(all(feature="parallel", feature="simple"))]
+extern crate byteorder;
+extern crate hmac;
+extern crate pbkdf2;
+extern crate rand;
+extern crate rayon;
+extern crate scrypt;
+extern crate sha2;
+
+use rand::{Rng, SeedableRng};
+use rand::prng::XorShiftRng;
+use rayon::ThreadPoolBuilder;
+
+use scrypt::test_vectors::{Operation, SCRYPT};
+use scrypt::{cost_of, scrypt, scrypt_simple, scrypt_vectored, ParamsPolicy, Params, Verifier};
+use scrypt::errors::{CheckError, Rule};
+
+// the serial ROMix of the RFC
+#[path = "../src/reference.rs"]
+#[allow(dead_code)]
+mod reference;
+
+/// `scrypt_vectored` computes its PBKDF2 serially.
+fn serial(password: &[u8], salt: &[u8], params: &Params, output: &mut [u8]) {
+    scrypt_vectored(&[password], &[salt], params, output).unwrap();
+}
+
+#[test]
+fn test_rfc_vector_p16() {
+    let params = Params::new(10, 8, 16).unwrap();
+    let mut output = [0u8; 64];
+    scrypt(b"password", b"NaCl", &params, &mut output).unwrap();
+    assert_eq!(&output[..], &[
+        0xfd, 0xba, 0xbe, 0x1c, 0x9d, 0x34, 0x72, 0x00, 0x78, 0x56, 0xe7, 0x19, 0x0d, 0x01,
+        0xe9, 0xfe, 0x7c, 0x6a, 0xd7, 0xcb, 0xc8, 0x23, 0x78, 0x30, 0xe7, 0x73, 0x76, 0x63,
+        0x4b, 0x37, 0x31, 0x62, 0x2e, 0xaf, 0x30, 0xd9, 0x2e, 0x22, 0xa3, 0x88, 0x6f, 0xf1,
+        0x09, 0x27, 0x9d, 0x98, 0x30, 0xda, 0xc7, 0x27, 0xaf, 0xb9, 0x4a, 0x83, 0xee, 0x6d,
+        0x83, 0x60, 0xcb, 0xdf, 0xa2, 0xcc, 0x06, 0x40,
+    ][..]);
+}
+
+#[test]
+fn test_same_as_serial() {
+    let mut rng = XorShiftRng::from_seed([9; 16]);
+    // around the threshold of 8 KiB of blocks and its chunks of 2 KiB, and
+    // outputs of partial blocks
+    let cases = [(8, 7, 16), (8, 8, 32), (4, 9, 64), (4, 17, 33), (1, 64, 100), (2, 40, 8191)];
+    for &(r, p, len) in cases.iter() {
+        let params = Params::new(3, r, p).unwrap();
+        let password: Vec<u8> = (0..rng.gen_range(0, 100)).map(|_| rng.gen()).collect();
+        let salt: Vec<u8> = (0..rng.gen_range(0, 40)).map(|_| rng.gen()).collect();
+        let (mut parallel, mut expected) = (vec![0u8; len], vec![0u8; len]);
+        scrypt(&password, &salt, &params, &mut parallel).unwrap();
+        serial(&password, &salt, &params, &mut expected);
+        assert_eq!(parallel, expected, "r={} p={} len={}", r, p, len);
+    }
+}
+
+#[test]
+fn test_long_output() {
+    // the final expansion of outputs of 8 KiB and more is parallel as well
+    let params = Params::new(2, 1, 1).unwrap();
+    for &len in [8 * 1024 - 1, 8 * 1024, 8 * 1024 + 1, 20_000, 100_003].iter() {
+        let (mut parallel, mut expected) = (vec![0u8; len], vec![0u8; len]);
+        scrypt(b"password", b"salt", &params, &mut parallel).unwrap();
+        serial(b"password", b"salt", &params, &mut expected);
+        assert!(parallel == expected, "len={}", len);
+    }
+}
+
+#[test]
+fn test_rfc_vectors() {
+    // the third vector, `log_n = 14` and `p = 1`, and the fourth, of 1 GiB,
+    // take long without optimizations
+    for vector in SCRYPT[..2].iter() {
+        if let Operation::Scrypt { password, salt, log_n, r, p } = vector.operation {
+            let mut output = vec![0u8; vector.expected.len()];
+            scrypt(password, salt, &Params::new(log_n, r, p).unwrap(), &mut output).unwrap();
+            assert_eq!(output, vector.expected, "{}", vector.name);
+        }
+    }
+}
+
+#[test]
+fn test_ro_mix_same_as_serial() {
+    let mut rng = XorShiftRng::from_seed([3; 16]);
+    // `p = 1` is mixed without rayon, the others on its threads
+    let cases = [(4, 1, 1), (3, 2, 2), (5, 1, 3), (2, 3, 4), (4, 8, 5), (1, 1, 16)];
+    for &(log_n, r, p) in cases.iter() {
+        let params = Params::new(log_n, r, p).unwrap();
+        let password: Vec<u8> = (0..rng.gen_range(0, 100)).map(|_| rng.gen()).collect();
+        let salt: Vec<u8> = (0..rng.gen_range(0, 40)).map(|_| rng.gen()).collect();
+        let mut parallel = [0u8; 64];
+        scrypt(&password, &salt, &params, &mut parallel).unwrap();
+        let expected = reference::scrypt(&password, &salt, 1 << log_n, r as usize, p as usize, 64);
+        assert_eq!(&parallel[..], &expected[..], "log_n={} r={} p={}", log_n, r, p);
+    }
+}
+
+#[test]
+fn test_fewer_threads_than_blocks() {
+    let params = Params::new(4, 2, 5).unwrap();
+    let expected = reference::scrypt(b"password", b"salt", 1 << 4, 2, 5, 32);
+    for &threads in [1, 2, 3].iter() {
+        let pool = ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        let mut output = [0u8; 32];
+        pool.install(|| scrypt(b"password", b"salt", &params, &mut output)).unwrap();
+        assert_eq!(&output[..], &expected[..], "threads={}", threads);
+    }
+}
+
+#[test]
+fn test_required_memory() {
+    // every thread mixes with a `V` and `T` of its own
+    let params = Params::new(4, 1, 8).unwrap();
+    for &(threads, lanes) in [(1, 1), (3, 3), (16, 8)].iter() {
+        let pool = ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        assert_eq!(pool.install(|| params.required_memory()), 128 * (lanes * 17 + 8),
+            "threads={}", threads);
+    }
+    // `p = 1` is mixed without rayon
+    let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+    assert_eq!(pool.install(|| Params::new(4, 1, 1).unwrap().required_memory()), 128 * 18);
+}
+
+#[test]
+fn test_limits_ignore_threads() {
+    // the limits compare against one lane, so that a hash is accepted or
+    // refused the same on every machine
+    let params = Params::new(4, 1, 8).unwrap();
+    let hashed = scrypt_simple("password", &params).unwrap();
+    let policy = |max_memory| ParamsPolicy { max_memory, ..ParamsPolicy::default() };
+    let exact = Verifier::builder().require(policy(128 * 25)).build();
+    let below = Verifier::builder().require(policy(128 * 25 - 1)).build();
+    for &(threads, lanes) in [(1, 1), (3, 3), (16, 8)].iter() {
+        let pool = ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        pool.install(|| {
+            assert_eq!(exact.verify("password", &hashed), Ok(()), "threads={}", threads);
+            assert_eq!(below.verify("password", &hashed),
+                Err(CheckError::Refused(Rule::MaxMemory)), "threads={}", threads);
+            let cost = cost_of(&hashed).unwrap();
+            assert_eq!((cost.memory_bytes, cost.parallel_memory_bytes),
+                (128 * 25, 128 * (lanes * 17 + 8)), "threads={}", threads);
+        });
+    }
+}
@@ -0,0 +1,212 @@
+//! The boundaries of every constraint of `Params::new`: the last
+//! accepted and the first rejected value, with the reason of the rejection,
+//! the mapping of `Params::from_costs` and the presets of `Params::for_year`.
+extern crate scrypt;
+
+use scrypt::errors::ParamsReason;
+use scrypt::{scrypt, Params};
+
+fn params(log_n: u8, r: u32, p: u32) -> Result<(u8, u32, u32), ParamsReason> {
+    Params::new(log_n, r, p)
+        .map(|params| (params.log_n(), params.r(), params.p()))
+        .map_err(|e| e.reason())
+}
+
+fn accepted(log_n: u8, r: u32, p: u32) {
+    assert_eq!(params(log_n, r, p), Ok((log_n, r, p)), "({}, {}, {})", log_n, r, p);
+}
+
+fn rejected(log_n: u8, r: u32, p: u32, reason: ParamsReason) {
+    assert_eq!(params(log_n, r, p), Err(reason), "({}, {}, {})", log_n, r, p);
+}
+
+#[test]
+fn test_log_n_zero() {
+    rejected(0, 1, 1, ParamsReason::LogNZero);
+    rejected(0, 8, 1, ParamsReason::LogNZero);
+    accepted(1, 1, 1);
+
+    let mut output = [0u8; 32];
+    scrypt(b"password", b"salt", &Params::new(1, 1, 1).unwrap(), &mut output).unwrap();
+}
+
+#[test]
+fn test_r_min() {
+    rejected(1, 0, 1, ParamsReason::RZero);
+    accepted(1, 1, 1);
+}
+
+#[test]
+fn test_p_min() {
+    rejected(1, 1, 0, ParamsReason::PZero);
+    accepted(1, 1, 1);
+}
+
+// N < 2^(16 r)
+#[test]
+fn test_n_max_of_r() {
+    accepted(15, 1, 1);
+    rejected(16, 1, 1, ParamsReason::NTooLargeForR);
+    accepted(31, 2, 1);
+    rejected(32, 2, 1, ParamsReason::NTooLargeForR);
+}
+
+// r p < 2^30
+#[test]
+fn test_rp_max() {
+    accepted(1, 1, (1 << 30) - 1);
+    rejected(1, 1, 1 << 30, ParamsReason::RpTooLarge);
+    accepted(1, (1 << 30) - 1, 1);
+    rejected(1, 1 << 30, 1, ParamsReason::RpTooLarge);
+    accepted(1, 8, (1 << 27) - 1);
+    rejected(1, 8, 1 << 27, ParamsReason::RpTooLarge);
+    accepted(1, 1 << 15, (1 << 15) - 1);
+    rejected(1, 1 << 15, 1 << 15, ParamsReason::RpTooLarge);
+    rejected(1, u32::MAX, 1, ParamsReason::RpTooLarge);
+    rejected(1, 1, u32::MAX, ParamsReason::RpTooLarge);
+    rejected(1, u32::MAX, u32::MAX, ParamsReason::RpTooLarge);
+}
+
+// N fits into a usize, 128 r N bytes fit into a usize
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn test_platform_limits() {
+    // passes the check of `log_n`, but not of the memory
+    rejected(63, 4, 1, ParamsReason::MemoryOverflow);
+    rejected(64, 5, 1, ParamsReason::LogNTooLarge);
+    rejected(u8::MAX, u32::MAX, u32::MAX, ParamsReason::LogNTooLarge);
+
+    // 128 * 4 * 2^54 = 2^63
+    accepted(54, 4, 1);
+    rejected(55, 4, 1, ParamsReason::MemoryOverflow);
+    // 128 * (2^30 - 1) * 2^27 = 2^64 - 2^34
+    accepted(27, (1 << 30) - 1, 1);
+    rejected(28, (1 << 30) - 1, 1, ParamsReason::MemoryOverflow);
+}
+
+#[cfg(target_pointer_width = "32")]
+#[test]
+fn test_platform_limits() {
+    rejected(31, 2, 1, ParamsReason::MemoryOverflow);
+    rejected(32, 3, 1, ParamsReason::LogNTooLarge);
+
+    // 128 * 2^20 * 2^4 = 2^31
+    accepted(4, 1 << 20, 1);
+    rejected(5, 1 << 20, 1, ParamsReason::MemoryOverflow);
+    // 128 * r * p of `B`
+    accepted(1, 1, (1 << 25) - 1);
+    rejected(1, 1, 1 << 25, ParamsReason::MemoryOverflow);
+}
+
+// a value breaking several constraints is reported for the first of them
+#[test]
+fn test_reason_order() {
+    rejected(0, 0, 0, ParamsReason::LogNZero);
+    rejected(1, 0, 0, ParamsReason::RZero);
+    rejected(16, 1, 0, ParamsReason::NTooLargeForR);
+    rejected(1, 1, 0, ParamsReason::PZero);
+    #[cfg(target_pointer_width = "64")]
+    rejected(63, 1 << 30, 1 << 30, ParamsReason::RpTooLarge);
+}
+
+#[test]
+fn test_from_costs() {
+    const KIB: usize = 1 << 10;
+    const MIB: usize = 1 << 20;
+    let table: &[(usize, u32, (u8, u32, u32))] = &[
+        (32 * MIB + 2 * KIB, 1, (15, 8, 1)),
+        (32 * MIB, 1, (14, 8, 1)),
+        (64 * MIB, 1, (15, 8, 1)),
+        (64 * MIB, 2, (15, 8, 2)),
+        (1 << 30, 4, (19, 8, 4)),
+        (16 * KIB, 1, (3, 8, 1)),
+        (4 * KIB, 1, (1, 8, 1)),
+        (4 * KIB - 1, 1, (1, 7, 1)),
+        (3 * KIB, 1, (1, 6, 1)),
+        (512, 1, (1, 1, 1)),
+        (10 * KIB, 7, (1, 8, 7)),
+    ];
+    for &(memory, cpu_multiplier, expected) in table {
+        let params = Params::from_costs(memory, cpu_multiplier).unwrap();
+        assert_eq!((params.log_n(), params.r(), params.p()), expected,
+            "{} bytes, {}x", memory, cpu_multiplier);
+        assert!(params.required_memory() <= memory as u128);
+        // the next larger N does not fit
+        if let Ok(larger) = Params::new(params.log_n() + 1, params.r(), params.p()) {
+            assert!(larger.required_memory() > memory as u128);
+        }
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    assert_eq!(params_of_costs(usize::MAX, 1), Ok((53, 8, 1)));
+    assert_eq!(params_of_costs(511, 1), Err(ParamsReason::BudgetTooSmall));
+    assert_eq!(params_of_costs(639, 2), Err(ParamsReason::BudgetTooSmall));
+    assert_eq!(params_of_costs(640, 2), Ok((1, 1, 2)));
+    assert_eq!(params_of_costs(MIB, 0), Err(ParamsReason::PZero));
+    assert_eq!(params_of_costs(usize::MAX, 1 << 27), Err(ParamsReason::RpTooLarge));
+}
+
+fn params_of_costs(memory: usize, cpu_multiplier: u32) -> Result<(u8, u32, u32), ParamsReason> {
+    Params::from_costs(memory, cpu_multiplier)
+        .map(|params| (params.log_n(), params.r(), params.p()))
+        .map_err(|e| e.reason())
+}
+
+#[test]
+fn test_required_memory() {
+    assert_eq!(Params::new(15, 8, 1).unwrap().required_memory(),
+        32 * 1024 * 1024 + 2 * 1024);
+    assert_eq!(Params::new(1, 1, 1).unwrap().required_memory(), 512);
+    assert_eq!(Params::new(4, 1, 8).unwrap().serial_memory(), 128 * (16 + 8 + 1));
+}
+
+// the mapping is pinned, services compare costs
+#[test]
+fn test_from_cost() {
+    let table = [(0, 4), (1, 5), (8, 12), (10, 14), (11, 15), (14, 18), (16, 20)];
+    for &(cost, log_n) in table.iter() {
+        let params = Params::from_cost(cost);
+        assert_eq!((params.log_n(), params.r(), params.p()), (log_n, 8, 1), "cost {}", cost);
+        assert_eq!(params.cost(), Some(cost));
+    }
+    assert_eq!(Params::from_cost(10).required_memory(), 16 * (1 << 20) + 2048);
+    assert_eq!(Params::from_cost(11), Params::new(15, 8, 1).unwrap());
+
+    let max = Params::from_cost(Params::MAX_COST);
+    assert_eq!(Params::from_cost(Params::MAX_COST + 1), max);
+    assert_eq!(Params::from_cost(u8::MAX), max);
+    assert_eq!(max.cost(), Some(Params::MAX_COST));
+    assert!(Params::new(max.log_n() + 1, 8, 1).is_err());
+    #[cfg(target_pointer_width = "64")]
+    assert_eq!(Params::MAX_COST, 49);
+
+    for &(log_n, r, p) in [(3, 8, 1), (14, 8, 2), (14, 1, 1), (14, 16, 1)].iter() {
+        assert_eq!(Params::new(log_n, r, p).unwrap().cost(), None);
+    }
+}
+
+// the table is pinned, a change of an entry must be deliberate
+#[test]
+fn test_for_year() {
+    let table = [
+        (2015, 14), (2016, 14),
+        (2017, 15), (2018, 15), (2019, 15), (2020, 15),
+        (2021, 16), (2022, 16),
+        (2023, 17), (2024, 17), (2025, 17), (2026, 17),
+    ];
+    for &(year, log_n) in table.iter() {
+        assert_eq!(params_for_year(year), Ok((log_n, 8, 1)), "{}", year);
+    }
+    assert_eq!(Params::latest(), Params::for_year(2026).unwrap());
+
+    // not extrapolated
+    for &year in [0, 2014, 2027, u16::MAX].iter() {
+        assert_eq!(params_for_year(year), Err(ParamsReason::UnknownYear), "{}", year);
+    }
+}
+
+fn params_for_year(year: u16) -> Result<(u8, u32, u32), ParamsReason> {
+    Params::for_year(year)
+        .map(|params| (params.log_n(), params.r(), params.p()))
+        .map_err(|e| e.reason())
+}
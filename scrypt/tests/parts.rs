@@ -0,0 +1,131 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::SeedableRng;
+use rand::prng::ChaChaRng;
+
+use scrypt::{scrypt_check, Format, HashFormat, HashParts, ScryptHash, Params, SimpleConfig};
+use scrypt::errors::{CheckError, Field};
+
+/// The columns of a table storing hashes without strings.
+struct Row {
+    log_n: u8,
+    r: u32,
+    p: u32,
+    salt: Vec<u8>,
+    hash: Vec<u8>,
+}
+
+impl Row {
+    fn store(parts: &HashParts) -> Row {
+        Row {
+            log_n: parts.params.log_n(),
+            r: parts.params.r(),
+            p: parts.params.p(),
+            salt: parts.salt.clone(),
+            hash: parts.hash.clone(),
+        }
+    }
+
+    fn load(&self) -> Result<HashParts, CheckError> {
+        HashParts::from_fields(self.log_n, self.r, self.p, &self.salt, &self.hash)
+    }
+}
+
+#[test]
+fn test_columns_round_trip() {
+    let config = SimpleConfig::builder()
+        .params(Params::new(4, 8, 1).unwrap())
+        .salt_len(24)
+        .dk_len(48)
+        .build()
+        .unwrap();
+    let mut rng = ChaChaRng::from_seed([7; 32]);
+    let parts = HashParts::generate("password", &config, &mut rng).unwrap();
+    assert_eq!((parts.salt.len(), parts.hash.len()), (24, 48));
+
+    let loaded = Row::store(&parts).load().unwrap();
+    assert_eq!(loaded, parts);
+    assert_eq!(loaded.verify(b"password"), Ok(()));
+    assert_eq!(loaded.verify(b"wrong"), Err(CheckError::HashMismatch));
+
+    // a changed byte of either column does not verify
+    let mut row = Row::store(&parts);
+    row.salt[0] ^= 1;
+    assert_eq!(row.load().unwrap().verify(b"password"), Err(CheckError::HashMismatch));
+    let mut row = Row::store(&parts);
+    row.hash[47] ^= 1;
+    assert_eq!(row.load().unwrap().verify(b"password"), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_string_conversions() {
+    let params = Params::new(4, 8, 1).unwrap();
+    for &format in [Format::Rscrypt, Format::Phc, Format::Django, Format::Crypt7].iter() {
+        let config = SimpleConfig::builder().params(params).format(format).build().unwrap();
+        // the same salt as the string of `ScryptHash::generate`
+        let parts = HashParts::generate("password", &config, &mut ChaChaRng::from_seed([1; 32]))
+            .unwrap();
+        let hash = ScryptHash::generate("password", &config, &mut ChaChaRng::from_seed([1; 32]))
+            .unwrap();
+        assert_eq!(hash.parts(), &parts);
+        assert_eq!(HashParts::from(hash.clone()), parts);
+
+        let converted = ScryptHash::from_parts(format, parts.clone()).unwrap();
+        assert_eq!(converted, hash);
+        let encoded = converted.to_string();
+        assert_eq!(encoded, format.encode(&parts));
+        assert_eq!(scrypt_check("password", &encoded), Ok(()));
+        let parsed = ScryptHash::parse(&encoded).unwrap();
+        assert_eq!(HashParts::from(parsed), parts);
+        assert_eq!(format.parse(&encoded).unwrap(), parts);
+    }
+
+    // `r` and `p` above 255 are encoded in version 1
+    let parts = HashParts::new(b"password", &Params::new(2, 300, 1).unwrap(), &[1; 16], 32)
+        .unwrap();
+    let hash = ScryptHash::from_parts(Format::Rscrypt, parts.clone()).unwrap();
+    assert_eq!(hash.format_version(), Some(1));
+    assert_eq!(ScryptHash::parse(&hash.to_string()).unwrap(), hash);
+
+    // a string of other lengths converts losslessly, but not to columns
+    let parsed = ScryptHash::parse(
+        "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$").unwrap();
+    let parts = HashParts::from(parsed.clone());
+    assert_eq!(parts.verify(b"password"), Ok(()));
+    assert_eq!(ScryptHash::from_parts(Format::Rscrypt, parts.clone()).unwrap(), parsed);
+    assert_eq!(Row::store(&parts).load(), Err(CheckError::InvalidField(Field::Salt)));
+}
+
+#[test]
+fn test_unrepresentable() {
+    let parts = HashParts::new(b"password", &Params::new(4, 8, 1).unwrap(), &[1; 16], 32)
+        .unwrap();
+    assert_eq!(ScryptHash::from_parts(Format::Cisco, parts.clone()),
+        Err(CheckError::Unrepresentable));
+    assert!(ScryptHash::from_parts(Format::Phc, parts).is_ok());
+}
+
+#[test]
+fn test_field_bounds() {
+    let salt = [1u8; 16];
+    let hash = [2u8; 32];
+    assert!(HashParts::from_fields(4, 8, 1, &salt, &hash).is_ok());
+    assert!(HashParts::from_fields(4, 8, 1, &[1; 8], &[2; 16]).is_ok());
+    assert!(HashParts::from_fields(4, 8, 1, &[1; 64], &[2; 64]).is_ok());
+
+    let invalid = |log_n, r, p, salt: &[u8], hash: &[u8], field| {
+        assert_eq!(HashParts::from_fields(log_n, r, p, salt, hash),
+            Err(CheckError::InvalidField(field)), "{:?}", field);
+    };
+    invalid(0, 8, 1, &salt, &hash, Field::N);
+    invalid(64, 1, 1, &salt, &hash, Field::N);
+    invalid(4, 0, 1, &salt, &hash, Field::R);
+    invalid(4, 8, 0, &salt, &hash, Field::P);
+    invalid(4, 8, 1, &[], &hash, Field::Salt);
+    invalid(4, 8, 1, &[1; 7], &hash, Field::Salt);
+    invalid(4, 8, 1, &[1; 65], &hash, Field::Salt);
+    invalid(4, 8, 1, &salt, &[2; 15], Field::Hash);
+    invalid(4, 8, 1, &salt, &[2; 65], Field::Hash);
+}
@@ -0,0 +1,121 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::errors::CheckError;
+use scrypt::{scrypt_check, scrypt_simple_fmt, scrypt_simple_fmt_with_rng, verify_any,
+    OutputFormat, ScryptHash, ScryptHashRef, Params};
+
+// `$scrypt$` strings in the adapted base64 of passlib, `.` for `+`, made
+// with Python's `hashlib.scrypt` and passlib's `ab64_encode`, so that salt
+// and hash contain a `.`. The last one omits the parameters, which then
+// take the defaults of passlib, `ln=16`, `r=8` and `p=1`.
+const FIXTURES: &[(&str, &str, (u8, u32, u32))] = &[
+    (
+        "password",
+        "$scrypt$ln=4,r=8,p=1$8PHy8/T19vf4.fr7/P3./w$\
+         la5Z9OEOx42d6EdjueFDysVOAzIuyZR1qV8etCYzFmA",
+        (4, 8, 1),
+    ),
+    (
+        "correct horse",
+        "$scrypt$ln=6,r=4,p=2$........Pj8$i8JOetCh6SmSchFV845dIdINZGi6Lj7oh1hUlC0iiKM",
+        (6, 4, 2),
+    ),
+    (
+        "pässwörd",
+        "$scrypt$ln=5,r=1,p=1$.vv6./r7.vv6./r7.vv6./r7.vv6.w$\
+         gr/GFwvVvgUPHKzgAvsoB1iAiJYHC.shYmggZUAUj5k",
+        (5, 1, 1),
+    ),
+    (
+        "hunter2",
+        "$scrypt$ln=3,r=8,p=3$.Pj4.Pj4.Pj4.Pj4.Pj4.Pj4.Pj4.Pj4.Pj4.Pj4.Pg$\
+         TfYhwPQpphJKQMheoCTXWuloc.VevgfqsgrOCtMKGMEnPwJN5nzU5ImdO2uctPXr.uJFHuuQPeZPAZSFqIeF.w",
+        (3, 8, 3),
+    ),
+    (
+        "password",
+        "$scrypt$8PHy8/T19vf4.fr7/P3./w$L01k1kPWJxQj1S7mZoK13dkE91LGCbh1RAFN4RzLlus",
+        (16, 8, 1),
+    ),
+];
+
+#[test]
+fn test_passlib_fixtures() {
+    for &(password, hashed, (log_n, r, p)) in FIXTURES {
+        assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("wrong", hashed), Err(CheckError::HashMismatch));
+        assert!(verify_any(password, hashed).is_ok());
+
+        let parsed = ScryptHash::parse(hashed).unwrap();
+        assert_eq!(parsed.params(), Params::new(log_n, r, p).unwrap());
+        let parsed_ref = ScryptHashRef::parse(hashed).unwrap();
+        assert_eq!(parsed_ref.salt(), parsed.salt());
+        assert_eq!(parsed_ref.hash_bytes(), parsed.hash_bytes());
+        // re-encoded in the PHC alphabet
+        assert_eq!(scrypt_check(password, &parsed.to_string()), Ok(()));
+    }
+}
+
+#[test]
+fn test_phc_unchanged() {
+    // the same salt and hash as the first fixture in the standard alphabet
+    let hashed = "$scrypt$ln=4,r=8,p=1$8PHy8/T19vf4+fr7/P3+/w$\
+                  la5Z9OEOx42d6EdjueFDysVOAzIuyZR1qV8etCYzFmA";
+    assert_eq!(scrypt_check("password", hashed), Ok(()));
+    let passlib = ScryptHash::parse(FIXTURES[0].1).unwrap();
+    assert_eq!(ScryptHash::parse(hashed).unwrap(), passlib);
+
+    // omitted parameters of PHC strings keep the defaults of this crate
+    let params = ScryptHash::parse("$scrypt$8PHy8/T19vf4+fr7/P3+/w$\
+        L01k1kPWJxQj1S7mZoK13dkE91LGCbh1RAFN4RzLlus").unwrap().params();
+    assert_eq!(params, Params::new(15, 8, 1).unwrap());
+}
+
+#[test]
+fn test_mixed_alphabets() {
+    let invalid = [
+        "$scrypt$ln=4,r=8,p=1$8PHy8/T19vf4+fr7/P3./w$\
+         la5Z9OEOx42d6EdjueFDysVOAzIuyZR1qV8etCYzFmA",
+        "$scrypt$ln=4,r=8,p=1$8PHy8/T19vf4.fr7/P3./w$\
+         la5Z9OEOx42d6EdjueFDysVOAzIuyZR1qV8etCYzFm+",
+        "$scrypt$8PHy8/T19vf4.fr7/P3+/w$L01k1kPWJxQj1S7mZoK13dkE91LGCbh1RAFN4RzLlus",
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(ScryptHash::parse(hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+        assert_eq!(ScryptHashRef::parse(hashed).unwrap_err(), CheckError::InvalidFormat);
+    }
+}
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.copy_from_slice(&self.0[..dest.len()]);
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_emit_passlib() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let salt: Vec<u8> = (0xf0..=0xff).collect();
+    let hashed = scrypt_simple_fmt_with_rng("password", &params, OutputFormat::Passlib,
+        &mut FixedRng(salt)).unwrap();
+    assert_eq!(hashed, FIXTURES[0].1);
+
+    let hashed = scrypt_simple_fmt("password", &params, OutputFormat::Passlib).unwrap();
+    assert!(hashed.starts_with("$scrypt$ln=4,r=8,p=1$"), "{}", hashed);
+    assert!(!hashed.contains('+'), "{}", hashed);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+}
@@ -0,0 +1,156 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use rand::{Rng, SeedableRng};
+use rand::prng::ChaChaRng;
+
+use scrypt::{hash_password, scrypt, scrypt_check, scrypt_simple_password,
+    verify_password, Password, PasswordBuilder, Params};
+use scrypt::errors::{CheckError, ErrorCode};
+
+/// Records whether the buffer at `WATCHED` was all zeros when it was freed.
+struct Watcher;
+
+static WATCHED: AtomicUsize = AtomicUsize::new(0);
+static FREED_ZEROED: AtomicBool = AtomicBool::new(false);
+static FREED: AtomicBool = AtomicBool::new(false);
+
+unsafe impl GlobalAlloc for Watcher {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if ptr as usize == WATCHED.load(Ordering::SeqCst) {
+            let bytes = std::slice::from_raw_parts(ptr, layout.size());
+            FREED_ZEROED.store(bytes.iter().all(|&b| b == 0), Ordering::SeqCst);
+            FREED.store(true, Ordering::SeqCst);
+            WATCHED.store(0, Ordering::SeqCst);
+        }
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Watcher = Watcher;
+
+#[test]
+fn test_zeroize_on_drop() {
+    let mut secret = String::with_capacity(32);
+    secret.push_str("correct horse battery staple");
+    WATCHED.store(secret.as_ptr() as usize, Ordering::SeqCst);
+    let password = Password::from(secret);
+    assert_eq!(password.as_bytes(), b"correct horse battery staple");
+    drop(password);
+    assert!(FREED.load(Ordering::SeqCst));
+    assert!(FREED_ZEROED.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_redacted() {
+    let password = Password::from("hunter2".to_string());
+    assert_eq!(format!("{:?}", password), "Password(<redacted>)");
+    assert_eq!(password.to_string(), "<redacted>");
+    assert!(!format!("{:?}", Some(&password)).contains("hunter2"));
+    assert!(!format!("{:#?}", vec![password.clone()]).contains("hunter2"));
+}
+
+#[test]
+fn test_read_from() {
+    let password = Password::read_from(&b"hunter2\r\n"[..]).unwrap();
+    assert_eq!(password.as_str(), Some("hunter2"));
+    let password = Password::read_from(&b"hunter2\n\n"[..]).unwrap();
+    assert_eq!(password.as_bytes(), b"hunter2\n");
+    let long = vec![b'x'; 1000];
+    assert_eq!(Password::read_from(&long[..]).unwrap().as_bytes(), &long[..]);
+    let password = Password::read_from(&b"\xff\xfe"[..]).unwrap();
+    assert_eq!(password.as_str(), None);
+
+    struct Failing;
+    impl io::Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+        }
+    }
+    assert_eq!(Password::read_from(Failing).unwrap_err().kind(), io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn test_hash_and_verify() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let password = Password::from(b"hunter2".to_vec());
+    let hashed = scrypt_simple_password(&password, &params).unwrap();
+    assert_eq!(verify_password(&password, &hashed), Ok(()));
+    assert_eq!(verify_password("hunter2", &hashed), Ok(()));
+    assert_eq!(verify_password(String::from("hunter3"), &hashed), Err(CheckError::HashMismatch));
+
+    let stored = hash_password(&password).unwrap();
+    assert_eq!(verify_password(b"hunter2", &stored), Ok(()));
+    assert_eq!(verify_password(Password::from(b"\xff".to_vec()), &stored),
+        Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_builder_chunking() {
+    // the chunks are drawn from a seeded `ChaChaRng`, a failure is
+    // reproduced by running the test again
+    let mut rng = ChaChaRng::from_seed([7; 32]);
+    let params = Params::new(1, 1, 1).unwrap();
+    for _ in 0..64 {
+        let len = rng.gen_range(0, 300);
+        let password: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        let mut expected = [0u8; 32];
+        scrypt(&password, b"salt", &params, &mut expected).unwrap();
+
+        let mut builder = PasswordBuilder::with_max_len(300);
+        let mut rest = &password[..];
+        while !rest.is_empty() {
+            let (chunk, tail) = rest.split_at(rng.gen_range(0, rest.len() + 1));
+            builder.update(chunk).unwrap();
+            rest = tail;
+        }
+        assert_eq!(builder.len(), len);
+        let mut output = [0u8; 32];
+        builder.finalize_hash(b"salt", &params, &mut output).unwrap();
+        assert_eq!(output, expected);
+    }
+}
+
+#[test]
+fn test_builder_simple() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let mut builder = PasswordBuilder::new();
+    assert!(builder.is_empty());
+    for chunk in ["hun", "", "ter", "2"].iter() {
+        builder.update(chunk.as_bytes()).unwrap();
+    }
+    let hashed = builder.finalize_simple(&params).unwrap();
+    assert_eq!(scrypt_check("hunter2", &hashed), Ok(()));
+
+    let mut builder = PasswordBuilder::default();
+    builder.update(b"hunter2").unwrap();
+    assert_eq!(builder.finish().as_str(), Some("hunter2"));
+}
+
+#[test]
+fn test_builder_max_len() {
+    let mut builder = PasswordBuilder::with_max_len(8);
+    builder.update(b"hunter").unwrap();
+    let err = builder.update(b"123").unwrap_err();
+    assert_eq!((err.provided_len(), err.max()), (9, 8));
+    assert_eq!(err.code(), ErrorCode::PasswordTooLong);
+    assert_eq!(err.to_string(), "password of 9 bytes is too long, expected at most 8");
+    // nothing of the rejected chunk is appended
+    assert_eq!(builder.len(), 6);
+    builder.update(b"12").unwrap();
+    assert_eq!(builder.update(b"3").unwrap_err().provided_len(), 9);
+    assert_eq!(builder.finish().as_bytes(), b"hunter12");
+
+    assert_eq!(PasswordBuilder::new().update(&[0; PasswordBuilder::DEFAULT_MAX_LEN + 1])
+        .unwrap_err().max(), PasswordBuilder::DEFAULT_MAX_LEN);
+}
@@ -0,0 +1,88 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use scrypt::errors::{CheckError, ErrorCode, HashError};
+use scrypt::{scrypt, scrypt_check, scrypt_simple, scrypt_simple_v2,
+    scrypt_simple_with_salt, verify_or_dummy, wrap_legacy_hash, LegacyDigest, Params,
+    SimpleConfig, Verifier, DEFAULT_MAX_PASSWORD_LEN};
+
+fn params() -> Params {
+    Params::new(4, 8, 1).unwrap()
+}
+
+fn config(max: Option<usize>) -> SimpleConfig {
+    SimpleConfig::builder().params(params()).max_password_len(max).build().unwrap()
+}
+
+const TOO_LONG: CheckError = CheckError::PasswordTooLong { len: 4097, max: 4096 };
+
+#[test]
+fn test_boundary() {
+    assert_eq!(DEFAULT_MAX_PASSWORD_LEN, 4096);
+    assert_eq!(SimpleConfig::default().max_password_len(), Some(4096));
+    let at = "a".repeat(4096);
+    let hashed = scrypt_simple(&at, &params()).unwrap();
+    assert_eq!(scrypt_check(&at, &hashed), Ok(()));
+    assert!(config(Some(4096)).verify(&at, &hashed).is_ok());
+    assert_eq!(Verifier::builder().build().verify(&at, &hashed), Ok(()));
+}
+
+#[test]
+fn test_above() {
+    let above = "a".repeat(4097);
+    match scrypt_simple(&above, &params()) {
+        Err(HashError::PasswordTooLong { len: 4097, max: 4096 }) => (),
+        res => panic!("{:?}", res),
+    }
+    assert!(scrypt_simple_v2(&above, &params()).is_err());
+    match scrypt_simple_with_salt(&above, &[0; 16], &params()) {
+        Err(HashError::PasswordTooLong { len: 4097, max: 4096 }) => (),
+        res => panic!("{:?}", res),
+    }
+    match wrap_legacy_hash(LegacyDigest::Sha1, above.as_bytes(), &params()) {
+        Err(HashError::PasswordTooLong { len: 4097, max: 4096 }) => (),
+        res => panic!("{:?}", res),
+    }
+
+    // rejected before the string is looked at
+    let hashed = scrypt_simple("password", &params()).unwrap();
+    for &hashed in [&hashed[..], "$rscrypt$"].iter() {
+        let err = scrypt_check(&above, hashed).unwrap_err();
+        assert_eq!(err, TOO_LONG);
+        assert_eq!(err.code(), ErrorCode::PasswordTooLong);
+        assert_eq!(err.to_string(), "password of 4097 bytes is too long, expected at most 4096");
+        assert_eq!(Verifier::builder().default_lengths(true).build().verify(&above, hashed),
+            Err(TOO_LONG));
+        assert_eq!(verify_or_dummy(&above, Some(hashed), &params()), Err(TOO_LONG));
+        assert_eq!(Verifier::builder().build().verify(&above, hashed), Err(TOO_LONG));
+    }
+    assert_eq!(verify_or_dummy(&above, None, &params()), Err(TOO_LONG));
+
+    // the limit of the configuration applies
+    let err = config(Some(16)).hash_with(&"a".repeat(17)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::PasswordTooLong);
+    assert_eq!(config(Some(16)).verify(&"a".repeat(17), &hashed),
+        Err(CheckError::PasswordTooLong { len: 17, max: 16 }));
+    let verifier = Verifier::builder().max_password_len(Some(16)).build();
+    assert_eq!(verifier.verify(&"a".repeat(17), &hashed),
+        Err(CheckError::PasswordTooLong { len: 17, max: 16 }));
+}
+
+#[test]
+fn test_disabled() {
+    let long = "a".repeat(64 * 1024);
+    let unlimited = config(None);
+    assert_eq!(unlimited.max_password_len(), None);
+    let hashed = unlimited.hash_with(&long).unwrap();
+    assert!(unlimited.verify(&long, &hashed).is_ok());
+    assert_eq!(Verifier::builder().max_password_len(None).build().verify(&long, &hashed), Ok(()));
+    assert_eq!(Verifier::builder().max_password_len(None).build().verify("a", &hashed),
+        Err(CheckError::HashMismatch));
+    // the default limit of the free functions still applies
+    assert_eq!(scrypt_check(&long, &hashed),
+        Err(CheckError::PasswordTooLong { len: 64 * 1024, max: 4096 }));
+
+    // `scrypt()` is a KDF and takes inputs of any length
+    let mut output = [0u8; 32];
+    assert!(scrypt(long.as_bytes(), b"salt", &params(), &mut output).is_ok());
+}
@@ -0,0 +1,217 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::{scrypt_check, scrypt_check_and_rehash_peppered,
+    scrypt_check_and_rehash_peppered_with_rng, scrypt_simple, scrypt_simple_peppered, verify_any,
+    KeyProvider, Pepper, PepperSet, ScryptHash, ScryptHashRef, Params, SimpleConfig, Verifier};
+use scrypt::convert::rscrypt_to_phc;
+use scrypt::errors::{CheckError, KeyError};
+
+const KEY: &[u8] = b"pepper key 1";
+
+// Generated with Python's `hmac` and `hashlib.scrypt`: pepper 7, salt
+// `00 01 .. 0f`.
+const PEPPERED: &str = "$rscrypt$2$BAgAAAABAAAAECABBw$AAECAwQFBgcICQoLDA0ODw$\
+                        rHLE6Ijo8iU76Eb/+7LuWCzbgR5PvTvU6yyOJW4Vs14";
+
+fn keys() -> PepperSet {
+    let mut keys = PepperSet::new();
+    keys.insert(7, KEY);
+    keys.insert(8, b"pepper key 2");
+    keys
+}
+
+/// A set of the single key `key` of `id`.
+fn key(id: u8, key: &[u8]) -> PepperSet {
+    let mut keys = PepperSet::new();
+    keys.insert(id, key);
+    keys
+}
+
+fn verify(password: &str, hashed: &str, keys: &dyn KeyProvider) -> Result<(), CheckError> {
+    Verifier::builder().build().verify_with_keys(password, hashed, keys)
+}
+
+/// The provider of hashes which need no pepper.
+struct Never;
+
+impl KeyProvider for Never {
+    fn key(&self, _id: u8, _f: &mut dyn FnMut(&[u8])) -> Result<(), KeyError> {
+        panic!("unpeppered and malformed hashes need no pepper")
+    }
+}
+
+#[test]
+fn test_peppered_fixture() {
+    assert_eq!(verify("password", PEPPERED, &keys()), Ok(()));
+    assert_eq!(verify("wrong", PEPPERED, &keys()), Err(CheckError::HashMismatch));
+    // the wrong key and an unknown id
+    assert_eq!(verify("password", PEPPERED, &key(7, b"pepper key 2")),
+        Err(CheckError::HashMismatch));
+    assert_eq!(verify("password", PEPPERED, &PepperSet::new()), Err(CheckError::NeedsPepper(7)));
+
+    // the other functions can not verify it
+    assert_eq!(scrypt_check("password", PEPPERED), Err(CheckError::NeedsPepper(7)));
+    assert_eq!(verify_any("password", PEPPERED), Err(CheckError::NeedsPepper(7)));
+    assert_eq!(ScryptHash::parse(PEPPERED), Err(CheckError::NeedsPepper(7)));
+    assert_eq!(ScryptHashRef::parse(PEPPERED), Err(CheckError::NeedsPepper(7)));
+    assert_eq!(rscrypt_to_phc(PEPPERED), Err(CheckError::NeedsPepper(7)));
+}
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_scrypt_simple_peppered() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let pepper = Pepper { id: 7, key: KEY };
+    let mut rng = FixedRng((0..16).collect());
+    assert_eq!(
+        SimpleConfig::builder().params(params).pepper_id(pepper.id).build().unwrap()
+            .hash_with_keys_rng("password", &pepper, &mut rng).unwrap(),
+        PEPPERED
+    );
+
+    let pepper = Pepper { id: 8, key: b"pepper key 2" };
+    let hashed = scrypt_simple_peppered("Ünïcødé", &params, &pepper).unwrap();
+    assert_eq!(verify("Ünïcødé", &hashed, &keys()), Ok(()));
+    assert_eq!(verify("Ünïcødé", &hashed, &key(7, KEY)), Err(CheckError::NeedsPepper(8)));
+    assert_eq!(scrypt_check("Ünïcødé", &hashed), Err(CheckError::NeedsPepper(8)));
+
+    // rotating the key of an id invalidates its hashes
+    assert_eq!(verify("Ünïcødé", &hashed, &key(8, KEY)), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_unpeppered_hashes() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashes = [
+        scrypt_simple("password", &params).unwrap(),
+        "$rscrypt$2$BAgAAAABAAAABCAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM"
+            .to_string(),
+        "$scrypt$ln=4,r=8,p=1$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM".to_string(),
+    ];
+    let password = |hashed: &str| if hashed.starts_with("$scrypt$") { "Ünïcødé" } else { "password" };
+    for hashed in hashes.iter() {
+        assert_eq!(verify(password(hashed), hashed, &Never), Ok(()));
+        assert_eq!(verify("wrong", hashed, &Never), Err(CheckError::HashMismatch));
+    }
+    assert_eq!(verify("password", "", &Never), Err(CheckError::InvalidFormat));
+}
+
+#[test]
+fn test_peppered_malformed() {
+    let hashes = [
+        // the flag without an id, an id without the flag
+        "$rscrypt$2$BAgAAAABAAAAECAB$AAECAwQFBgcICQoLDA0ODw$\
+         rHLE6Ijo8iU76Eb/+7LuWCzbgR5PvTvU6yyOJW4Vs14",
+        "$rscrypt$2$BAgAAAABAAAAECAABw$AAECAwQFBgcICQoLDA0ODw$\
+         rHLE6Ijo8iU76Eb/+7LuWCzbgR5PvTvU6yyOJW4Vs14",
+        // two bytes after the header
+        "$rscrypt$2$BAgAAAABAAAAECABBwc$AAECAwQFBgcICQoLDA0ODw$\
+         rHLE6Ijo8iU76Eb/+7LuWCzbgR5PvTvU6yyOJW4Vs14",
+    ];
+    for &hashed in hashes.iter() {
+        assert_eq!(verify("password", hashed, &keys()), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+}
+
+#[test]
+fn test_pepper_rotation() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let mut peppers = PepperSet::new();
+    peppers.insert(1, b"pepper key 1");
+    let old = scrypt_simple_peppered("password", &params, &peppers.newest().unwrap()).unwrap();
+    assert_eq!(verify("password", &old, &peppers), Ok(()));
+    assert_eq!(scrypt_check_and_rehash_peppered("password", &old, &params, &peppers), Ok(None));
+
+    // rotate: new hashes use key 2, key 1 is kept for the old ones
+    peppers.insert(2, b"pepper key 2");
+    assert_eq!(peppers.newest().unwrap().id, 2);
+    assert_eq!(verify("password", &old, &peppers), Ok(()));
+    assert_eq!(
+        scrypt_check_and_rehash_peppered("wrong", &old, &params, &peppers),
+        Err(CheckError::HashMismatch)
+    );
+    let new = scrypt_check_and_rehash_peppered("password", &old, &params, &peppers)
+        .unwrap().unwrap();
+    assert_eq!(scrypt_check("password", &new), Err(CheckError::NeedsPepper(2)));
+    assert_eq!(scrypt_check_and_rehash_peppered("password", &new, &params, &peppers), Ok(None));
+
+    // retire key 1
+    let mut current = PepperSet::new();
+    current.insert(2, b"pepper key 2");
+    assert_eq!(verify("password", &new, &current), Ok(()));
+    assert_eq!(verify("wrong", &new, &current),
+        Err(CheckError::HashMismatch));
+    // unknown ids are not mismatches
+    assert_eq!(verify("password", &old, &current),
+        Err(CheckError::NeedsPepper(1)));
+    assert_eq!(scrypt_check_and_rehash_peppered("password", &old, &params, &current),
+        Err(CheckError::NeedsPepper(1)));
+}
+
+#[test]
+fn test_rehash_peppered() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let mut peppers = PepperSet::new();
+    peppers.insert(7, KEY);
+    assert_eq!(peppers.get(7), Some(KEY));
+    assert_eq!(peppers.get(8), None);
+
+    // stronger parameters, and unpeppered hashes, are upgraded
+    let stronger = Params::new(5, 8, 1).unwrap();
+    let unpeppered = scrypt_simple("password", &params).unwrap();
+    for &(stored, desired) in [(PEPPERED, &stronger), (&unpeppered[..], &params)].iter() {
+        let mut rng = FixedRng((0..16).collect());
+        let new = scrypt_check_and_rehash_peppered_with_rng("password", stored, desired,
+            &peppers, &mut rng).unwrap().unwrap();
+        assert!(new.starts_with("$rscrypt$2$"), "{}", new);
+        assert_eq!(verify("password", &new, &peppers), Ok(()));
+        assert_eq!(scrypt_check_and_rehash_peppered("password", &new, desired, &peppers),
+            Ok(None));
+    }
+    let mut rng = FixedRng((0..16).collect());
+    assert_eq!(
+        scrypt_check_and_rehash_peppered_with_rng("password", &unpeppered, &params, &peppers,
+            &mut rng),
+        Ok(Some(PEPPERED.to_string()))
+    );
+
+    // inserting an id again replaces its key and makes it the newest
+    peppers.insert(8, b"pepper key 2");
+    peppers.insert(7, b"other key");
+    assert_eq!(peppers.newest().unwrap().id, 7);
+    assert_eq!(peppers.get(7), Some(&b"other key"[..]));
+    assert_eq!(verify("password", PEPPERED, &peppers),
+        Err(CheckError::HashMismatch));
+
+    // without peppers, hashes are upgraded like `scrypt_check_and_rehash`
+    let empty = PepperSet::new();
+    assert_eq!(empty.newest().map(|p| p.id), None);
+    assert_eq!(scrypt_check_and_rehash_peppered("password", &unpeppered, &params, &empty),
+        Ok(None));
+    assert!(scrypt_check_and_rehash_peppered("password", &unpeppered, &stronger, &empty)
+        .unwrap().unwrap().starts_with("$rscrypt$0$BQgB$"));
+    assert_eq!(scrypt_check_and_rehash_peppered("password", PEPPERED, &params, &empty),
+        Err(CheckError::NeedsPepper(7)));
+}
@@ -0,0 +1,127 @@
+#![cfg(feature="simple")]
+extern crate base64ct;
+extern crate rand;
+extern crate scrypt;
+
+use base64ct::{Base64Unpadded, Encoding};
+use rand::{CryptoRng, RngCore};
+
+use scrypt::{scrypt_check, scrypt_simple_phc, Format, Params, SimpleConfig};
+use scrypt::errors::CheckError;
+
+// Generated with Python's `hashlib.scrypt` (OpenSSL), covering reordered and
+// omitted parameters as well as non-default salt and hash lengths.
+const FIXTURES: &[(&str, &str)] = &[
+    (
+        "password",
+        "$scrypt$ln=10,r=8,p=1$LRtfeowOkT+kTmsMHS4/QA$\
+         oQoHWim7dB7vcgd11aYdc4M4LhnrM0sAiuLqU82afpQ",
+    ),
+    (
+        "pleaseletmein",
+        "$scrypt$p=2,r=4,ln=6$Wpw$MxqUplG0o8ju5tUXwBHolCna81hp+dZN1D6ziZ23k0Z\
+         QkJIoHMdBppgzPx2hrOp9DOhd89jpO1tASgvH8Jg9fA",
+    ),
+    (
+        "",
+        "$scrypt$ln=5,r=1,p=1$AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8$\
+         clvNdiyEBKCJo2ceWyls+w",
+    ),
+    (
+        "Ünïcødé",
+        "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+    ),
+];
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_phc_fixtures() {
+    for &(password, hashed) in FIXTURES {
+        assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+        assert_eq!(
+            scrypt_check("wrong", hashed),
+            Err(CheckError::HashMismatch),
+            "{}", hashed
+        );
+    }
+}
+
+#[test]
+fn test_phc_round_trip() {
+    let params = Params::new(4, 2, 3).unwrap();
+    let hashed = scrypt_simple_phc("password", &params).unwrap();
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_phc_emit_fixture() {
+    // the salt of the first fixture gives its string byte for byte
+    let (password, hashed) = FIXTURES[0];
+    let salt = Base64Unpadded::decode_vec("LRtfeowOkT+kTmsMHS4/QA").unwrap();
+    let params = Params::new(10, 8, 1).unwrap();
+    let config = SimpleConfig::builder().params(params).format(Format::Phc).build().unwrap();
+    let emitted = config.hash_with_rng(password, &mut FixedRng(salt)).unwrap();
+    assert_eq!(emitted, hashed);
+}
+
+#[test]
+fn test_phc_malformed() {
+    let salt_hash = "$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM";
+    let invalid_params = [
+        "ln=4,ln=4",
+        "ln=4,r=8,r=8",
+        "ln=4,x=1",
+        "ln=4,r",
+        "ln=4,r=",
+        "ln=4,r=+8",
+        "ln=4,r=08",
+        "ln=4,r=-8",
+        "ln=4,r=4294967296",
+        "ln=256",
+        "ln=64",
+        "ln=0",
+        "ln=4,r=0",
+        "ln=4,",
+        "",
+    ];
+    for params in invalid_params.iter() {
+        let hashed = format!("$scrypt${}{}", params, salt_hash);
+        assert_eq!(scrypt_check("Ünïcødé", &hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+
+    let invalid = [
+        "$scrypt$ln=4$jgs",
+        "$scrypt$ln=4",
+        "$scrypt$ln=4$jgs$",
+        "$scrypt$ln=4$jg=$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+        "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM=",
+        "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM$",
+        "$scrypt$ln=4$jg!$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+        "$scrypt$ln=4$$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+        "$scrypt",
+        "scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(scrypt_check("Ünïcødé", hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+}
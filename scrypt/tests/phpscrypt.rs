@@ -0,0 +1,139 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::errors::CheckError;
+use scrypt::{scrypt_check, scrypt_simple_php, verify_any, Format, HashFormat, ScryptHash, Params,
+    SimpleConfig};
+
+// Strings of `php-scrypt`'s `Password::hash()`, with Python's
+// `hashlib.scrypt` and the steps of the library: its defaults with a
+// generated salt, then salts given by the caller, which it stores as their
+// base64 with `.` for `+`, with other parameters and an empty password.
+const FIXTURES: &[(&str, &str, (u8, u32, u32))] = &[
+    (
+        "password",
+        "16384$8$1$..8SNFZ4mrw=$eda222369d3ef88adf1dc58f09e8393a25e1a0748565f914ccafeefda776b791",
+        (14, 8, 1),
+    ),
+    (
+        "pleaseletmein",
+        "1024$4$2$U29kaXVtQ2hsb3JpZGU=$\
+         b99effdc4830d295f8bbe9379c414bea6cc99139c32d69d29e3166c3ae97b43b",
+        (10, 4, 2),
+    ),
+    (
+        "Ünïcødé",
+        "2048$16$3$yMnKy8zNzs8=$a70a33adddf10ce88b3bfbe2f00158f1c14d3f1c6f34c56d16b146eed36efa17",
+        (11, 16, 3),
+    ),
+    (
+        "",
+        "16$1$1$AAECAwQFBgc=$3b49f6031dee288b722ecf6f343ef1e4454a0724348366ec5bca8006f5af551c",
+        (4, 1, 1),
+    ),
+    (
+        "correct horse",
+        "4096$8$1$./AAPhJ3gKpf$ab487ea0156fd0753c4327b37d3cac6198d37e7d057d31c69a82be5ebf0ba436",
+        (12, 8, 1),
+    ),
+];
+
+#[test]
+fn test_php_fixtures() {
+    for &(password, hashed, (log_n, r, p)) in FIXTURES {
+        assert_eq!(Format::detect(hashed), Some(Format::PhpScrypt), "{}", hashed);
+        assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("wrong", hashed), Err(CheckError::HashMismatch));
+        assert_eq!(verify_any(password, hashed).unwrap().format(), Format::PhpScrypt);
+
+        let parts = Format::PhpScrypt.parse(hashed).unwrap();
+        assert_eq!(parts.params, Params::new(log_n, r, p).unwrap());
+        // the salt is the text between the separators, not decoded
+        assert_eq!(&parts.salt[..], hashed.split('$').nth(3).unwrap().as_bytes());
+        assert_eq!(Format::PhpScrypt.encode(&parts), hashed);
+        assert_eq!(ScryptHash::parse(hashed).unwrap().to_string(), hashed);
+    }
+}
+
+#[test]
+fn test_php_malformed() {
+    let rest = "$yMnKy8zNzs8=$a70a33adddf10ce88b3bfbe2f00158f1c14d3f1c6f34c56d16b146eed36efa17";
+    let invalid_params = [
+        "2048$16$3$1", "2047$16$3", "1$16$3", "02048$16$3", "2048$016$3",
+        "2048$0$3", "2048$16$0", "2048$16$4294967296", "18446744073709551616$16$3",
+    ];
+    for params in invalid_params.iter() {
+        let hashed = format!("{}{}", params, rest);
+        assert_eq!(Format::detect(&hashed), Some(Format::PhpScrypt), "{}", hashed);
+        assert_eq!(scrypt_check("Ünïcødé", &hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+
+    let hash = "a70a33adddf10ce88b3bfbe2f00158f1c14d3f1c6f34c56d16b146eed36efa17";
+    let invalid = [
+        "2048$16$3$yMnKy8zNzs8=".to_string(),
+        "2048$16$3$yMnKy8zNzs8=$".to_string(),
+        // `Password::check()` ignores extra fields
+        format!("2048$16$3$yMnKy8zNzs8=${}$", hash),
+        format!("2048$16$3$yMnKy8zNzs8=${}", &hash[..32]),
+        format!("2048$16$3$yMnKy8zNzs8=${}00", hash),
+        format!("2048$16$3$yMnKy8zNzs8=${}", hash.to_uppercase()),
+        format!("2048$16$3$yMnKy8zNzs8=${}g", &hash[..63]),
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(scrypt_check("Ünïcødé", hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+
+    // signs and empty parameters do not look like `php-scrypt` strings
+    for hashed in ["+2048$16$3", "2048$$3", "$16$3", "2048$16$ 3"].iter() {
+        assert_eq!(Format::detect(&format!("{}{}", hashed, rest)), None, "{}", hashed);
+    }
+    assert_eq!(Format::detect("2048$16$3"), None);
+}
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_php_fixed_salt() {
+    // the indices of the characters of the salt of the last fixture
+    let bytes = vec![62, 63, 0, 0, 15, 33, 9, 55, 32, 10, 41, 31];
+    let params = Params::new(12, 8, 1).unwrap();
+    let config = SimpleConfig::builder().params(params).format(Format::PhpScrypt).build()
+        .unwrap();
+    let hashed = config.hash_with_rng("correct horse", &mut FixedRng(bytes)).unwrap();
+    assert_eq!(hashed, FIXTURES[4].1);
+}
+
+#[test]
+fn test_php_round_trip() {
+    let params = Params::new(4, 2, 3).unwrap();
+    let hashed = scrypt_simple_php("password", &params).unwrap();
+    let fields: Vec<&str> = hashed.split('$').collect();
+    assert_eq!(fields.len(), 5);
+    assert_eq!(&fields[..3], &["16", "2", "3"]);
+    assert_eq!(fields[3].len(), 12);
+    assert!(fields[3].bytes().all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'/'));
+    assert_eq!(fields[4].len(), 64);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+}
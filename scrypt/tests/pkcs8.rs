@@ -0,0 +1,157 @@
+#![cfg(feature="pkcs8-params")]
+extern crate scrypt;
+
+use scrypt::errors::{DerError, ErrorCode};
+use scrypt::pkcs8::KdfParams;
+use scrypt::Params;
+
+// `keyDerivationFunc` of `openssl pkcs8 -topk8 -scrypt` of OpenSSL 3.0, with
+// its defaults and with `-scrypt_N 1024 -scrypt_r 4 -scrypt_p 2`.
+const OPENSSL: &[(&str, &str, (u8, u32, u32))] = &[
+    ("302106092b06010401da47040b301404080d8aa7599a81740602024000020108020101",
+     "0d8aa7599a817406", (14, 8, 1)),
+    ("302106092b06010401da47040b30140408afcc943e36db6b6102020400020104020102",
+     "afcc943e36db6b61", (10, 4, 2)),
+];
+
+/// The scrypt-params of the first key above.
+const PARAMS: &str = "301404080d8aa7599a81740602024000020108020101";
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_openssl() {
+    for &(der, salt, (log_n, r, p)) in OPENSSL {
+        let der = hex(der);
+        let kdf = KdfParams::from_algorithm_identifier(&der).unwrap();
+        assert_eq!(kdf.salt, hex(salt));
+        assert_eq!(kdf.params, Params::new(log_n, r, p).unwrap());
+        assert_eq!(kdf.key_length, None);
+        assert_eq!(kdf.to_algorithm_identifier(), der);
+        assert_eq!(KdfParams::from_der(&der[13..]).unwrap(), kdf);
+        assert_eq!(kdf.to_der(), &der[13..]);
+    }
+
+    // the AES-256-CBC key of the second key for "password"
+    let kdf = KdfParams::from_algorithm_identifier(&hex(OPENSSL[1].0)).unwrap();
+    let mut key = [0u8; 32];
+    kdf.derive(b"password", &mut key).unwrap();
+    assert_eq!(&key[..],
+        &hex("289607863c073e9cf4db1c3281736eb5837e717ab7b122ccc83b2823b408cac6")[..]);
+}
+
+#[test]
+fn test_rfc7914() {
+    // the example of section 13
+    let der = hex("301f06092b06010401da47040b301204054d6f7573650203100000020108020101");
+    let kdf = KdfParams::from_algorithm_identifier(&der).unwrap();
+    assert_eq!(kdf.salt, b"Mouse");
+    assert_eq!(kdf.params, Params::new(20, 8, 1).unwrap());
+    assert_eq!(kdf.to_algorithm_identifier(), der);
+}
+
+#[test]
+fn test_key_length() {
+    let mut kdf = KdfParams::from_der(&hex(PARAMS)).unwrap();
+    kdf.key_length = Some(32);
+    let der = kdf.to_der();
+    assert_eq!(der, hex("301704080d8aa7599a81740602024000020108020101020120"));
+    assert_eq!(KdfParams::from_der(&der).unwrap(), kdf);
+
+    let kdf = KdfParams::from_algorithm_identifier(&hex(OPENSSL[1].0)).unwrap();
+    let kdf = KdfParams { key_length: Some(16), ..kdf };
+    assert_eq!(kdf.derive(b"password", &mut [0u8; 32]), Err(DerError::InvalidKeyLength));
+    let mut key = [0u8; 16];
+    kdf.derive(b"password", &mut key).unwrap();
+    assert_eq!(&key[..], &hex("289607863c073e9cf4db1c3281736eb5")[..]);
+    let kdf = KdfParams { key_length: None, ..kdf };
+    assert_eq!(kdf.derive(b"password", &mut []), Err(DerError::InvalidKeyLength));
+}
+
+#[test]
+fn test_long_salt() {
+    let kdf = KdfParams {
+        salt: vec![0xa5; 200],
+        params: Params::new(4, 1, 1).unwrap(),
+        key_length: None,
+    };
+    let der = kdf.to_der();
+    assert_eq!(&der[..6], &hex("3081d40481c8")[..]);
+    assert_eq!(KdfParams::from_der(&der).unwrap(), kdf);
+}
+
+#[test]
+fn test_rejected() {
+    let salt = "04080d8aa7599a817406";
+    let malformed = [
+        // integers which are not minimal
+        format!("3015{}0203004000020108020101", salt),
+        format!("3014{}0202ff80020108020101", salt),
+        format!("3013{}020240000200020101", salt),
+        // trailing data, inside and after the sequence
+        format!("3016{}020240000201080201010500", salt),
+        format!("{}00", PARAMS),
+        // indefinite and long form lengths
+        format!("3080{}020240000201080201010000", salt),
+        format!("308114{}02024000020108020101", salt),
+        // other tags, truncated
+        "3014".to_string() + "0c080d8aa7599a81740602024000020108020101",
+        "3114".to_string() + &PARAMS[4..],
+        PARAMS[..PARAMS.len() - 2].to_string(),
+        String::new(),
+    ];
+    for der in malformed.iter() {
+        assert_eq!(KdfParams::from_der(&hex(der)), Err(DerError::Malformed), "{}", der);
+    }
+
+    let invalid_params = [
+        // N of 3 and 1, r of 0, a negative p
+        format!("3013{}020103020108020101", salt),
+        format!("3013{}020101020108020101", salt),
+        format!("3014{}02024000020100020101", salt),
+        format!("3014{}020240000201080201ff", salt),
+        // above u32 and u64
+        format!("3018{}0202400002010802050100000000", salt),
+        format!("301b{}0209010000000000000000020108020101", salt),
+    ];
+    for der in invalid_params.iter() {
+        assert_eq!(KdfParams::from_der(&hex(der)), Err(DerError::InvalidParams), "{}", der);
+    }
+
+    let invalid_key_length = [
+        format!("3017{}02024000020108020101020100", salt),
+        format!("301b{}020240000201080201010205010000000", salt) + "0",
+        format!("3017{}020240000201080201010201ff", salt),
+    ];
+    for der in invalid_key_length.iter() {
+        assert_eq!(KdfParams::from_der(&hex(der)), Err(DerError::InvalidKeyLength), "{}", der);
+    }
+
+    // PBKDF2
+    let der = OPENSSL[0].0.replace("2b06010401da47040b", "2a864886f70d01050c");
+    assert_eq!(KdfParams::from_algorithm_identifier(&hex(&der)),
+        Err(DerError::UnsupportedAlgorithm));
+    assert_eq!(KdfParams::from_algorithm_identifier(&hex(PARAMS)), Err(DerError::Malformed));
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(DerError::Malformed.code(), ErrorCode::InvalidFormat);
+    assert_eq!(DerError::UnsupportedAlgorithm.code(), ErrorCode::UnsupportedFormat);
+    assert_eq!(DerError::InvalidParams.code(), ErrorCode::InvalidParams);
+    assert_eq!(DerError::InvalidKeyLength.code(), ErrorCode::InvalidOutputLen);
+    assert!(!DerError::Malformed.to_string().is_empty());
+}
+
+#[test]
+fn test_debug() {
+    let kdf = KdfParams::from_der(&hex(PARAMS)).unwrap();
+    let debug = format!("{:?}", kdf);
+    assert!(debug.starts_with("KdfParams"), "{}", debug);
+    assert!(!debug.contains("0d8aa759"), "{}", debug);
+    assert!(!debug.contains("13, 138"), "{}", debug);
+}
@@ -0,0 +1,102 @@
+extern crate scrypt;
+extern crate sha2;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use scrypt::pow::scrypt_1024_1_1_256;
+use scrypt::{scrypt, Params};
+use sha2::{Digest, Sha256};
+
+/// Genesis blocks of coins with the scrypt proof of work: the 80-byte
+/// header, its double SHA-256 identifying the block and the proof of work
+/// hash.
+const GENESIS: &[(&str, &str, &str, &str)] = &[
+    ("Litecoin",
+     "01000000000000000000000000000000000000000000000000000000000000000000\
+      0000d9ced4ed1130f7b7faad9be25323ffafa33232a17c3edf6cfd97bee6bafbdd97\
+      b9aa8e4ef0ff0f1ecd513f7c",
+     "12a765e31ffd4059bada1e25190f6e98c99d9714d334efa41a195a7e7e04bfe2",
+     "001e67b013726fd7382e9acb69165b4b6316227fb3156b5b414ba6340c050000"),
+    ("Dogecoin",
+     "01000000000000000000000000000000000000000000000000000000000000000000\
+      0000696ad20e2dd4365c7459b4a4a5af743d5e92c6da3229e6532cd605f6533f2a5b\
+      24a6a152f0ff0f1e67860100",
+     "1a91e3dace36e2be3bf030a65679fe821aa1d6ef92e7c9902eb318182c355691",
+     "48b41053487d4159cfaacf3adad783cf2f2dedea1413250cca74783f6f020000"),
+];
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn header(s: &str) -> [u8; 80] {
+    let mut header = [0u8; 80];
+    header.copy_from_slice(&hex(s));
+    header
+}
+
+/// Counts the allocations of the current thread, the test harness allocates
+/// on other threads.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+#[test]
+fn test_genesis_blocks() {
+    for &(coin, header_hex, id, pow) in GENESIS {
+        let header = header(header_hex);
+        // the fixture is the real header
+        let mut block_id = Sha256::digest(&Sha256::digest(&header)).to_vec();
+        block_id.reverse();
+        assert_eq!(block_id, hex(id), "{}", coin);
+
+        let hash = scrypt_1024_1_1_256(&header);
+        assert_eq!(hash.to_vec(), hex(pow), "{}", coin);
+        // below the target of the header, 0x1e0ffff0: little-endian with
+        // at least 12 leading zero bits
+        assert_eq!(hash[31], 0, "{}", coin);
+        assert!(hash[30] < 0x10, "{}", coin);
+    }
+}
+
+#[test]
+fn test_same_as_scrypt() {
+    let params = Params::new(10, 1, 1).unwrap();
+    let mut input = [0u8; 80];
+    for i in 0..16u32 {
+        for (j, b) in input.iter_mut().enumerate() {
+            *b = (i.wrapping_mul(0x9e37_79b9) >> (j % 25)) as u8 ^ j as u8;
+        }
+        let mut expected = [0u8; 32];
+        scrypt(&input, &input, &params, &mut expected).unwrap();
+        assert_eq!(scrypt_1024_1_1_256(&input), expected, "input {}", i);
+    }
+}
+
+#[test]
+fn test_does_not_allocate() {
+    let input = header(GENESIS[0].1);
+    let before = ALLOCATIONS.with(|n| n.get());
+    let hash = scrypt_1024_1_1_256(&input);
+    assert_eq!(ALLOCATIONS.with(|n| n.get()), before);
+    assert_eq!(hash.to_vec(), hex(GENESIS[0].3));
+}
@@ -0,0 +1,156 @@
+#![cfg(feature="simple")]
+extern crate base64ct;
+extern crate rand;
+extern crate scrypt;
+extern crate sha2;
+
+use base64ct::{Base64Unpadded, Encoding};
+use rand::SeedableRng;
+use rand::prng::ChaChaRng;
+use sha2::{Digest, Sha256};
+
+use scrypt::errors::{CheckError, ErrorCode, HashError};
+use scrypt::{cost_of, needs_rehash, scrypt_check, scrypt_check_and_rehash_with_rng,
+    scrypt_check_bytes, scrypt_simple_prehashed,
+    scrypt_simple_v2, LengthPolicy, Params, ScryptHash, ScryptHashRef, SimpleConfig, Verifier};
+
+const LIMITS: LengthPolicy = LengthPolicy { prehash_above: 16, max_len: 64 };
+
+fn verify(password: &str, hashed: &str, limits: &LengthPolicy) -> Result<(), CheckError> {
+    Verifier::builder().max_password_len(Some(limits.max_len)).build().verify(password, hashed)
+}
+
+fn params() -> Params {
+    Params::new(4, 8, 1).unwrap()
+}
+
+fn config() -> SimpleConfig {
+    SimpleConfig::builder().params(params()).prehash(LIMITS).build().unwrap()
+}
+
+fn prehashed(password: &str) -> String {
+    config().hash_with_rng(password, &mut ChaChaRng::from_seed([1; 32])).unwrap()
+}
+
+/// `hashed` with the header of a plain `scrypt_simple_v2` string, which
+/// `scrypt_check` verifies against the password passed to scrypt.
+fn as_raw(hashed: &str) -> String {
+    let plain = scrypt_simple_v2("password", &params()).unwrap();
+    let header = plain.split('$').nth(3).unwrap();
+    let fields: Vec<&str> = hashed.split('$').collect();
+    format!("$rscrypt$2${}${}${}", header, fields[4], fields[5])
+}
+
+#[test]
+fn test_threshold_boundary() {
+    let at = "a".repeat(16);
+    let above = "a".repeat(17);
+
+    let hashed = prehashed(&at);
+    assert_eq!(verify(&at, &hashed, &LIMITS), Ok(()));
+    // up to the threshold the password is hashed as is
+    assert_eq!(scrypt_check(&at, &as_raw(&hashed)), Ok(()));
+
+    let hashed = prehashed(&above);
+    assert_eq!(verify(&above, &hashed, &LIMITS), Ok(()));
+    assert_eq!(verify(&at, &hashed, &LIMITS), Err(CheckError::HashMismatch));
+    // above it scrypt hashes the SHA-256 digest
+    let raw = as_raw(&hashed);
+    assert_eq!(scrypt_check(&above, &raw), Err(CheckError::HashMismatch));
+    assert_eq!(scrypt_check_bytes(&Sha256::digest(above.as_bytes()), &raw), Ok(()));
+}
+
+#[test]
+fn test_threshold_stored() {
+    // the threshold of the string applies, not that of the verifier
+    let hashed = prehashed(&"a".repeat(17));
+    let limits = LengthPolicy { prehash_above: 1024, ..LIMITS };
+    assert_eq!(verify(&"a".repeat(17), &hashed, &limits), Ok(()));
+}
+
+#[test]
+fn test_interop_with_raw() {
+    // strings without pre-hashing verify as with `scrypt_check`
+    let raw = scrypt_simple_v2("password", &params()).unwrap();
+    assert_eq!(verify("password", &raw, &LIMITS), Ok(()));
+    assert_eq!(verify("wrong", &raw, &LIMITS), Err(CheckError::HashMismatch));
+    assert_eq!(verify("password", "$rscrypt$", &LIMITS),
+        Err(CheckError::InvalidFormat));
+
+    // short passwords have the same hash either way
+    let hashed = prehashed("password");
+    assert_eq!(scrypt_check("password", &as_raw(&hashed)), Ok(()));
+    assert_eq!(verify("password", &as_raw(&hashed), &LIMITS), Ok(()));
+}
+
+#[test]
+fn test_direct() {
+    let hashed = scrypt_simple_prehashed("password", &params(), &LengthPolicy::default())
+        .unwrap();
+    assert!(hashed.starts_with("$rscrypt$2$"));
+    assert_eq!(cost_of(&hashed).unwrap().params, params());
+
+    // the threshold is stored, so the string verifies without `limits`
+    let long = "a".repeat(17);
+    let hashed = prehashed(&long);
+    assert_eq!(scrypt_check(&long, &hashed), Ok(()));
+    assert_eq!(scrypt_check(&"a".repeat(16), &hashed), Err(CheckError::HashMismatch));
+    assert_eq!(Verifier::builder().build().verify(&long, &hashed), Ok(()));
+    assert_eq!(needs_rehash(&hashed, &params()), Ok(false));
+
+    let hash = ScryptHash::parse(&hashed).unwrap();
+    assert_eq!(hash.prehash_above(), Some(16));
+    assert_eq!(hash.to_string(), hashed);
+    assert_eq!(hash.verify(&long), Ok(()));
+    let hash_ref = ScryptHashRef::parse(&hashed).unwrap();
+    assert_eq!(hash_ref.prehash_above(), Some(16));
+    assert_eq!(hash_ref.verify(&long), Ok(()));
+    assert_eq!(ScryptHash::from(hash_ref), hash);
+    assert_eq!(hash.to_record(), Err(CheckError::Unrepresentable));
+
+    // the new hash keeps the threshold
+    let desired = Params::new(5, 8, 1).unwrap();
+    let rehashed = scrypt_check_and_rehash_with_rng(&long, &hashed, &desired,
+        &mut ChaChaRng::from_seed([2; 32])).unwrap().unwrap();
+    assert_eq!(ScryptHash::parse(&rehashed).unwrap().prehash_above(), Some(16));
+    assert_eq!(scrypt_check(&long, &rehashed), Ok(()));
+}
+
+#[test]
+fn test_max_len() {
+    let at = "a".repeat(64);
+    let above = "a".repeat(65);
+    let hashed = prehashed(&at);
+    assert_eq!(verify(&at, &hashed, &LIMITS), Ok(()));
+
+    match config().hash_with_rng(&above, &mut ChaChaRng::from_seed([1; 32])) {
+        Err(HashError::PasswordTooLong { len: 65, max: 64 }) => (),
+        res => panic!("{:?}", res),
+    }
+    let err = verify(&above, &hashed, &LIMITS).unwrap_err();
+    assert_eq!(err, CheckError::PasswordTooLong { len: 65, max: 64 });
+    assert_eq!(err.code(), ErrorCode::PasswordTooLong);
+    assert_eq!(err.to_string(), "password of 65 bytes is too long, expected at most 64");
+    // rejected before the string is looked at
+    assert_eq!(verify(&above, "$rscrypt$", &LIMITS),
+        Err(CheckError::PasswordTooLong { len: 65, max: 64 }));
+}
+
+#[test]
+fn test_malformed_header() {
+    let hashed = prehashed("password");
+    let fields: Vec<&str> = hashed.split('$').collect();
+    let mut header = Base64Unpadded::decode_vec(fields[3]).unwrap();
+    assert_eq!(header[11], 0x04);
+    assert_eq!(&header[12..], &[16, 0]);
+    let encode = |header: &[u8]| format!("$rscrypt$2${}${}${}",
+        Base64Unpadded::encode_string(header), fields[4], fields[5]);
+
+    // the threshold without its second byte
+    assert_eq!(verify("password", &encode(&header[..13]), &LIMITS),
+        Err(CheckError::InvalidFormat));
+    // pre-hashing is not combined with a pepper
+    header[11] |= 0x01;
+    assert_eq!(verify("password", &encode(&header), &LIMITS),
+        Err(CheckError::InvalidFormat));
+}
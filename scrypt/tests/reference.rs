@@ -0,0 +1,118 @@
+//! The optimized implementations against the transcription of RFC 7914 in
+//! `src/reference.rs`, over random inputs.
+//!
+//! The file is included here, not used as `scrypt::reference`, so that the
+//! comparison runs without the `reference` feature on every test run.
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+extern crate byteorder;
+extern crate hmac;
+extern crate pbkdf2;
+extern crate scrypt;
+extern crate sha2;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use scrypt::pow::scrypt_1024_1_1_256;
+use scrypt::test_vectors::{ro_mix_stores, Operation, TestVector, BLOCK_MIX, RO_MIX, SALSA20_8,
+    SCRYPT};
+use scrypt::{scrypt, scrypt_vectored, ScryptIntermediate, Params};
+
+#[path = "../src/reference.rs"]
+#[allow(dead_code)]
+mod reference;
+
+const CASES: usize = 32;
+
+/// xorshift64*, seeded from the clock, the seed is printed on failures.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: u64) -> u64 { self.next() % n }
+
+    fn bytes(&mut self, max_len: u64) -> Vec<u8> {
+        let len = self.below(max_len + 1);
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+fn seed() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    (now.as_secs() << 32 ^ now.subsec_nanos() as u64) | 1
+}
+
+// the transcription itself, against the RFC
+#[test]
+fn test_reference_rfc_vectors() {
+    fn check(vector: &TestVector) {
+        let actual = match vector.operation {
+            Operation::Salsa20(input) => reference::salsa20_8(input),
+            Operation::BlockMix(input) => reference::block_mix(input),
+            Operation::RoMix { input, log_n } => reference::ro_mix(input, 1 << log_n),
+            Operation::Scrypt { password, salt, log_n, r, p } => reference::scrypt(password,
+                salt, 1 << log_n, r as usize, p as usize, vector.expected.len()),
+        };
+        assert_eq!(actual, vector.expected, "{}", vector.name);
+    }
+    for vector in [&SALSA20_8, &BLOCK_MIX, &RO_MIX].iter().cloned().chain(&SCRYPT[..2]) {
+        check(vector);
+    }
+}
+
+#[test]
+fn test_differential() {
+    let seed = seed();
+    let mut rng = Rng(seed);
+    for case in 0..CASES {
+        let log_n = 1 + rng.below(8) as u8;
+        let r = 1 + rng.below(4) as u32;
+        let p = 1 + rng.below(3) as u32;
+        let password = rng.bytes(100);
+        let salt = rng.bytes(100);
+        let dk_len = 1 + rng.below(100) as usize;
+        let name = format!("seed {:#x}, case {}: log_n {}, r {}, p {}, {} byte password, \
+            {} byte salt, {} byte key", seed, case, log_n, r, p, password.len(), salt.len(),
+            dk_len);
+
+        let expected = reference::scrypt(&password, &salt, 1 << log_n, r as usize, p as usize,
+            dk_len);
+        let params = Params::new(log_n, r, p).unwrap();
+        let mut output = vec![0u8; dk_len];
+        scrypt(&password, &salt, &params, &mut output).unwrap();
+        assert_eq!(output, expected, "scrypt, {}", name);
+
+        let intermediate = ScryptIntermediate::compute(&password, &salt, &params);
+        intermediate.expand(&password, &mut output).unwrap();
+        assert_eq!(output, expected, "ScryptIntermediate, {}", name);
+
+        let split = rng.below(password.len() as u64 + 1) as usize;
+        let (left, right) = password.split_at(split);
+        scrypt_vectored(&[left, right], &[&salt], &params, &mut output).unwrap();
+        assert_eq!(output, expected, "scrypt_vectored, {}", name);
+
+        let block: Vec<u8> = (0..128 * r).map(|_| rng.next() as u8).collect();
+        let expected = reference::ro_mix(&block, 1 << log_n);
+        assert_eq!(ro_mix_stores(&block, log_n, false), expected, "ROMix, {}", name);
+        assert_eq!(ro_mix_stores(&block, log_n, true), expected, "streaming ROMix, {}", name);
+    }
+}
+
+#[test]
+fn test_differential_pow() {
+    let seed = seed();
+    let mut rng = Rng(seed);
+    for case in 0..CASES / 4 {
+        let mut header = [0u8; 80];
+        for byte in header.iter_mut() { *byte = rng.next() as u8; }
+        let expected = reference::scrypt(&header, &header, 1024, 1, 1, 32);
+        assert_eq!(&scrypt_1024_1_1_256(&header)[..], &expected[..],
+            "seed {:#x}, case {}", seed, case);
+    }
+}
@@ -0,0 +1,197 @@
+#![cfg(feature="simple-verify")]
+extern crate scrypt;
+
+use scrypt::{Format, HashFormat, HashParts, Registry, Params};
+use scrypt::errors::CheckError;
+
+/// A toy homegrown layout: `{log_n}|{r}|{p}|{hex(salt)}|{hex(hash)}`.
+struct Legacy;
+
+impl HashFormat for Legacy {
+    fn matches(&self, hashed_value: &str) -> bool {
+        hashed_value.starts_with("legacy|")
+    }
+
+    fn parse(&self, hashed_value: &str) -> Result<HashParts, CheckError> {
+        let fields: Vec<&str> = hashed_value.split('|').collect();
+        if fields.len() != 6 { return Err(CheckError::InvalidFormat); }
+        let number = |s: &str| s.parse().map_err(|_| CheckError::InvalidFormat);
+        let params = Params::new(number(fields[1])? as u8, number(fields[2])?,
+            number(fields[3])?).map_err(|_| CheckError::InvalidFormat)?;
+        Ok(HashParts {
+            params,
+            salt: from_hex(fields[4]).ok_or(CheckError::InvalidFormat)?,
+            hash: from_hex(fields[5]).ok_or(CheckError::InvalidFormat)?,
+        })
+    }
+
+    fn encode(&self, parts: &HashParts) -> String {
+        format!(
+            "legacy|{}|{}|{}|{}|{}",
+            parts.params.log_n(), parts.params.r(), parts.params.p(),
+            to_hex(&parts.salt), to_hex(&parts.hash),
+        )
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 { return None; }
+    (0..s.len()).step_by(2)
+        .map(|i| s.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect()
+}
+
+/// A format claiming the `$7$` prefix of a built-in format.
+struct Greedy;
+
+impl HashFormat for Greedy {
+    fn matches(&self, hashed_value: &str) -> bool {
+        hashed_value.starts_with("$7$")
+    }
+
+    fn parse(&self, _hashed_value: &str) -> Result<HashParts, CheckError> {
+        Err(CheckError::InvalidFormat)
+    }
+
+    fn encode(&self, _parts: &HashParts) -> String { unimplemented!() }
+}
+
+const CRYPT7: &str =
+    "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1";
+
+#[test]
+fn test_custom_format() {
+    let mut registry = Registry::builtin();
+    registry.register(Legacy);
+
+    let params = Params::new(4, 8, 1).unwrap();
+    let parts = HashParts::new(b"password", &params, b"NaCl", 32).unwrap();
+    let hashed = Legacy.encode(&parts);
+    assert!(hashed.starts_with("legacy|4|8|1|4e61436c|"));
+    assert_eq!(Legacy.parse(&hashed), Ok(parts));
+
+    let format = registry.verify("password", &hashed).unwrap();
+    assert!(format.matches(&hashed));
+    assert_eq!(
+        registry.verify("wrong", &hashed).map(|_| ()),
+        Err(CheckError::HashMismatch),
+    );
+    match registry.verify("password", "legacy|4|8|1|4e61436c|") {
+        Err(CheckError::InvalidHashLen(e)) => assert_eq!(e.provided_len(), 0),
+        res => panic!("{:?}", res.map(|_| ())),
+    }
+    for &malformed in ["legacy|", "legacy|4|8|1|4e61436c", "legacy|4|8|1|zz|00"].iter() {
+        assert_eq!(
+            registry.verify("password", malformed).map(|_| ()),
+            Err(CheckError::InvalidFormat),
+        );
+    }
+
+    // the built-in formats are still consulted
+    let format = registry.verify("password", CRYPT7).unwrap();
+    assert!(format.matches(CRYPT7));
+    assert!(!format.matches(&hashed));
+}
+
+#[test]
+fn test_registry_unsupported() {
+    let mut registry = Registry::new();
+    assert_eq!(
+        registry.verify("password", CRYPT7).map(|_| ()),
+        Err(CheckError::UnsupportedFormat),
+    );
+
+    registry.register(Legacy);
+    for &hashed in [CRYPT7, "", "legacy", "LEGACY|4|8|1|00|00"].iter() {
+        assert!(registry.detect(hashed).is_none(), "{}", hashed);
+        assert_eq!(
+            registry.verify("password", hashed).map(|_| ()),
+            Err(CheckError::UnsupportedFormat),
+        );
+    }
+}
+
+#[test]
+fn test_registry_ambiguous() {
+    let mut registry = Registry::builtin();
+    assert!(registry.verify("password", CRYPT7).is_ok());
+
+    // a second format matching the same strings makes them ambiguous
+    registry.register(Greedy);
+    assert!(registry.detect(CRYPT7).is_none());
+    assert_eq!(
+        registry.verify("password", CRYPT7).map(|_| ()),
+        Err(CheckError::UnsupportedFormat),
+    );
+}
+
+#[test]
+fn test_builtin_roundtrip() {
+    let fixtures = [
+        (Format::Rscrypt, "$rscrypt$0$BAgB$15ieabOWBi+lTfggDq1u4A==$\
+                           LSoB1to848I8SrfvmQAiAa2ajqs+iVgB5TecmxomqfU=$"),
+        (Format::Phc, "$scrypt$ln=4,r=8,p=1$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM"),
+        (Format::Crypt7, CRYPT7),
+        (Format::Lambdaworks, "$s0$6010c$6xFRVKObC3j7pn8argJYdw==$\
+                               nUmr44BPG1eSmZ5wWz8bnGJjIP5Vv5Ud2EeKVEuhZ2w="),
+        (Format::Django, "scrypt$2$sël$1$1$MbY5a6bob9Ls6RaeNUjmfa+noDsixdIwX+tWM5gfDxMEe\
+                          ztvVeNZ+2WDCMX5Zch449tsPzQMS0FZN2LOHt9O2Q=="),
+        (Format::Werkzeug, "scrypt:16:4:3$Vo8WJf01$8fa4d2de4726a5b6f188803247ba64c41bb9\
+                            b4e8b3c2be8dcfd6ac891b20c98acc2aab903903ef746900557831128fad\
+                            50af2a2d5cbfecf2c9ecd74a234ac075"),
+        (Format::Cisco, "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6"),
+        (Format::Hex, "scrypt:ln=4,r=8,p=1:000102030405060708090a0b0c0d0e0f:4a27cb03b2b4b79c\
+                       8ce895055927f012ed9bdb54d41a88bb9b5a77c7b7e03282"),
+    ];
+    let registry = Registry::builtin();
+    for &(format, hashed) in fixtures.iter() {
+        assert!(format.matches(hashed), "{}", hashed);
+        assert!(registry.detect(hashed).unwrap().matches(hashed));
+        let parts = format.parse(hashed).unwrap();
+        assert!(format.supports(&parts), "{}", hashed);
+        assert_eq!(format.encode(&parts), hashed);
+    }
+}
+
+#[test]
+fn test_builtin_supports() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let parts = |salt: &[u8], len| HashParts::new(b"", &params, salt, len).unwrap();
+
+    assert!(Format::Rscrypt.supports(&parts(b"\xff$", 32)));
+    assert!(Format::Phc.supports(&parts(b"\xff$", 16)));
+    assert!(Format::Crypt7.supports(&parts(b"./Az09", 32)));
+    assert!(!Format::Crypt7.supports(&parts(b"salt-salt", 32)));
+    assert!(!Format::Crypt7.supports(&parts(b"salt", 64)));
+    assert!(!Format::Lambdaworks.supports(&parts(b"salt", 64)));
+    assert!(Format::Django.supports(&parts(b"s\xc3\xabl", 64)));
+    assert!(!Format::Django.supports(&parts(b"", 64)));
+    assert!(!Format::Django.supports(&parts(b"salt$", 64)));
+    assert!(!Format::Werkzeug.supports(&parts(b"\xff", 64)));
+    // Cisco secrets have fixed parameters
+    assert!(!Format::Cisco.supports(&parts(b"2MJBozw/9R3UsU", 32)));
+    assert!(Format::Hex.supports(&parts(b"\xff$saltsalt", 16)));
+    assert!(!Format::Hex.supports(&parts(b"salt", 32)));
+
+    let big_r = HashParts {
+        params: Params::new(1, 256, 1).unwrap(),
+        salt: b"salt".to_vec(),
+        hash: vec![0; 32],
+    };
+    assert!(!Format::Lambdaworks.supports(&big_r));
+    assert!(Format::Rscrypt.supports(&big_r));
+    let encoded = Format::Rscrypt.encode(&big_r);
+    assert_eq!(Format::Rscrypt.parse(&encoded), Ok(big_r));
+}
+
+#[test]
+#[should_panic]
+fn test_builtin_encode_unsupported() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let parts = HashParts::new(b"", &params, b"salt$", 64).unwrap();
+    Format::Django.encode(&parts);
+}
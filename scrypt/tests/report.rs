@@ -0,0 +1,39 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use std::time::Duration;
+
+use scrypt::{scrypt_check, scrypt_simple, scrypt_simple_phc, Params, Verifier, VerifyOutcome};
+use scrypt::errors::CheckError;
+
+#[test]
+fn test_report_match_and_mismatch() {
+    let verifier = Verifier::builder().build();
+    let params = Params::new(6, 8, 1).unwrap();
+    for hashed in &[scrypt_simple("password", &params).unwrap(),
+                    scrypt_simple_phc("password", &params).unwrap()] {
+        let report = verifier.verify_report("password", hashed).unwrap();
+        assert_eq!(report.outcome, VerifyOutcome::Match);
+        assert_eq!(report.params, params);
+        assert_eq!(report.memory_bytes, 128 * 8 * (64 + 1 + 1));
+        assert!(report.elapsed > Duration::from_secs(0));
+
+        let report = verifier.verify_report("wrong", hashed).unwrap();
+        assert_eq!(report.outcome, VerifyOutcome::Mismatch);
+        assert_eq!(report.params, params);
+        assert_eq!(report.memory_bytes, 128 * 8 * (64 + 1 + 1));
+        assert!(report.elapsed > Duration::from_secs(0));
+    }
+}
+
+#[test]
+fn test_report_malformed() {
+    let verifier = Verifier::builder().build();
+    assert_eq!(verifier.verify_report("password", ""), Err(CheckError::InvalidFormat));
+    assert_eq!(verifier.verify_report("password", "$rscrypt$0$BAgB$"),
+        Err(CheckError::InvalidFormat));
+    // the errors of `scrypt_check`
+    let argon2 = "$argon2id$v=19$m=65536,t=2,p=1$c2FsdA$aGFzaA";
+    assert_eq!(verifier.verify_report("password", argon2).unwrap_err(),
+        scrypt_check("password", argon2).unwrap_err());
+}
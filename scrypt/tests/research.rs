@@ -0,0 +1,64 @@
+#![cfg(feature="research")]
+extern crate scrypt;
+
+use scrypt::research::{scrypt_instrumented, RomixEvent};
+use scrypt::test_vectors::{Operation, SCRYPT};
+use scrypt::{scrypt, Params};
+
+fn events(password: &[u8], salt: &[u8], params: &Params, output: &mut [u8]) -> Vec<RomixEvent> {
+    let mut events = Vec::new();
+    scrypt_instrumented(password, salt, params, output, &mut |event| events.push(event))
+        .unwrap();
+    events
+}
+
+#[test]
+fn test_events_per_lane() {
+    for &(log_n, r, p) in [(1, 1, 1), (4, 1, 3), (6, 2, 2)].iter() {
+        let params = Params::new(log_n, r, p).unwrap();
+        let n = 1usize << log_n;
+        let events = events(b"password", b"salt", &params, &mut [0u8; 32]);
+        assert_eq!(events.len(), n * p as usize);
+        for (k, event) in events.iter().enumerate() {
+            // lanes in order, every iteration once
+            assert_eq!(event.lane, k / n);
+            assert_eq!(event.iteration, k % n);
+            assert!(event.j < n);
+        }
+    }
+}
+
+#[test]
+fn test_indices() {
+    // Generated with a Python transcription of RFC 7914.
+    let expected: [[usize; 16]; 2] = [
+        [14, 9, 8, 15, 15, 15, 15, 1, 11, 14, 3, 13, 7, 6, 13, 6],
+        [13, 7, 10, 11, 1, 11, 15, 7, 14, 12, 4, 3, 5, 10, 8, 10],
+    ];
+    let params = Params::new(4, 1, 2).unwrap();
+    let events = events(b"password", b"NaCl", &params, &mut [0u8; 32]);
+    let lanes: Vec<Vec<usize>> = events.chunks(16)
+        .map(|lane| lane.iter().map(|event| event.j).collect())
+        .collect();
+    assert_eq!(lanes, expected.iter().map(|lane| lane.to_vec()).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_key_unchanged() {
+    for vector in SCRYPT.iter() {
+        if let Operation::Scrypt { password, salt, log_n, r, p } = vector.operation {
+            if log_n > 14 { continue; }
+            let params = Params::new(log_n, r, p).unwrap();
+            let mut output = vec![0u8; vector.expected.len()];
+            events(password, salt, &params, &mut output);
+            assert_eq!(&output[..], vector.expected, "{}", vector.name);
+        }
+    }
+
+    let params = Params::new(5, 3, 2).unwrap();
+    let (mut observed, mut plain) = ([0u8; 48], [0u8; 48]);
+    events(b"password", b"salt", &params, &mut observed);
+    scrypt(b"password", b"salt", &params, &mut plain).unwrap();
+    assert_eq!(observed, plain);
+    assert!(scrypt_instrumented(b"", b"", &params, &mut [], &mut |_| ()).is_err());
+}
@@ -0,0 +1,155 @@
+#![cfg(feature="simple")]
+//! Randomized round trips through the simple API. The cases are drawn from a
+//! seeded `ChaChaRng`, so a failure is reproduced by running the test again.
+extern crate rand;
+extern crate scrypt;
+
+use rand::{Rng, SeedableRng};
+use rand::prng::ChaChaRng;
+
+use scrypt::{scrypt_check, scrypt_simple_with_salt, ScryptHash, Params, SimpleConfig};
+use scrypt::errors::CheckError;
+
+const CASES: usize = 64;
+
+fn rng(seed: u8) -> ChaChaRng {
+    ChaChaRng::from_seed([seed; 32])
+}
+
+/// Parameters cheap enough to hash thousands of times.
+fn cheap_params(rng: &mut ChaChaRng) -> Params {
+    Params::new(rng.gen_range(1, 5), rng.gen_range(1, 5), rng.gen_range(1, 3)).unwrap()
+}
+
+fn is_cheap(params: &Params) -> bool {
+    (params.r() as u64 * params.p() as u64) << params.log_n() <= 1 << 9
+}
+
+/// Replacements for single characters of a hash string: separators, base64
+/// of both alphabets, padding and characters outside of base64.
+const MUTATIONS: &str = "$=+/-_.,AZaz019 \u{0}\u{7f}\u{e9}\u{1F600}";
+
+fn password(rng: &mut ChaChaRng) -> String {
+    let len = rng.gen_range(0, 24);
+    (0..len).map(|_| {
+        if rng.gen() { rng.gen_range(b' ', b'~' + 1) as char } else { rng.gen::<char>() }
+    }).collect()
+}
+
+/// A password which differs from `password`, by one character if possible.
+fn mutate(rng: &mut ChaChaRng, password: &str) -> String {
+    let mut chars: Vec<char> = password.chars().collect();
+    if chars.is_empty() || rng.gen() {
+        let at = rng.gen_range(0, chars.len() + 1);
+        chars.insert(at, rng.gen());
+        return chars.into_iter().collect();
+    }
+    let at = rng.gen_range(0, chars.len());
+    let old = chars[at];
+    while chars[at] == old {
+        chars[at] = rng.gen();
+    }
+    chars.into_iter().collect()
+}
+
+#[test]
+fn test_with_salt_round_trip() {
+    let mut rng = rng(1);
+    for _ in 0..CASES {
+        let params = cheap_params(&mut rng);
+        let salt: [u8; 16] = rng.gen();
+        let password = password(&mut rng);
+        let wrong = mutate(&mut rng, &password);
+
+        let hashed = scrypt_simple_with_salt(&password, &salt, &params).unwrap();
+        assert_eq!(scrypt_check(&password, &hashed), Ok(()), "{:?} {}", password, hashed);
+        assert_eq!(scrypt_check(&wrong, &hashed), Err(CheckError::HashMismatch),
+            "{:?} {}", wrong, hashed);
+    }
+}
+
+#[test]
+fn test_salt_len_round_trip() {
+    let mut rng = rng(2);
+    for _ in 0..CASES {
+        let config = SimpleConfig::builder()
+            .params(cheap_params(&mut rng))
+            .salt_len(rng.gen_range(scrypt::MIN_SALT_LEN, scrypt::MAX_SALT_LEN + 1))
+            .build()
+            .unwrap();
+        let password = password(&mut rng);
+        let wrong = mutate(&mut rng, &password);
+
+        let hashed = config.hash_with_rng(&password, &mut rng).unwrap();
+        assert_eq!(ScryptHash::parse(&hashed).unwrap().salt().len(), config.salt_len());
+        assert_eq!(scrypt_check(&password, &hashed), Ok(()), "{:?} {}", password, hashed);
+        assert_eq!(scrypt_check(&wrong, &hashed), Err(CheckError::HashMismatch));
+    }
+}
+
+#[test]
+fn test_mutated_hash() {
+    let mut rng = rng(3);
+    // the header of version 1 only, its salt and hash are checked like the
+    // ones of version 0 and each KDF call is expensive
+    let params = [
+        (Params::new(1, 1, 1).unwrap(), false),
+        (Params::new(1, 256, 1).unwrap(), true),
+    ];
+    for &(ref params, header_only) in params.iter() {
+        let salt: [u8; 16] = rng.gen();
+        let hashed = scrypt_simple_with_salt("password", &salt, params).unwrap();
+        let original = ScryptHash::parse(&hashed).unwrap();
+
+        let end = match hashed.match_indices('$').nth(3) {
+            Some((salt_start, _)) if header_only => salt_start,
+            _ => hashed.len(),
+        };
+        for at in 0..end {
+            for c in MUTATIONS.chars() {
+                let mut mutated = hashed.clone();
+                mutated.replace_range(at..at + 1, c.encode_utf8(&mut [0; 4]));
+                if mutated == hashed { continue; }
+
+                // headers may now ask for gigabytes, skip what scrypt_check
+                // could not compute here
+                match ScryptHash::parse(&mutated) {
+                    Ok(ref hash) if !is_cheap(&hash.params()) => continue,
+                    _ => {}
+                }
+                // a wrong password is never accepted, the right one only if
+                // the string still describes the same hash
+                assert!(scrypt_check("wrong", &mutated).is_err(), "{}", mutated);
+                if scrypt_check("password", &mutated).is_ok() {
+                    assert_eq!(ScryptHash::parse(&mutated).as_ref(), Ok(&original), "{}", mutated);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_header_boundaries() {
+    let mut rng = rng(4);
+    let boundaries = [
+        (255, 1, "$rscrypt$0$"),
+        (256, 1, "$rscrypt$1$"),
+        (1, 255, "$rscrypt$0$"),
+        (1, 256, "$rscrypt$1$"),
+    ];
+    for &(r, p, prefix) in boundaries.iter() {
+        let params = Params::new(1, r, p).unwrap();
+        let salt: [u8; 16] = rng.gen();
+        let password = password(&mut rng);
+
+        let hashed = scrypt_simple_with_salt(&password, &salt, &params).unwrap();
+        assert!(hashed.starts_with(prefix), "{}", hashed);
+        let hash = ScryptHash::parse(&hashed).unwrap();
+        assert_eq!(hash.params(), params);
+        assert_eq!(hash.salt(), &salt[..]);
+        assert_eq!(hash.to_string(), hashed);
+        assert_eq!(scrypt_check(&password, &hashed), Ok(()));
+        assert_eq!(scrypt_check(&mutate(&mut rng, &password), &hashed),
+            Err(CheckError::HashMismatch));
+    }
+}
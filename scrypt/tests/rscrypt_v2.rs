@@ -0,0 +1,113 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use scrypt::{scrypt_check, scrypt_simple_v2, ScryptHash, ScryptHashRef, Params};
+use scrypt::convert::rscrypt_to_phc;
+use scrypt::errors::{CheckError, Field};
+
+// Generated with Python's `hashlib.scrypt`. The header of the first string
+// is `04 08000000 01000000 04 20 00`: `log_n` 4, `r` 8, `p` 1, a salt of 4
+// and a hash of 32 bytes and no flags.
+const FIXTURES: &[(&str, u8, u32, u32, usize, usize)] = &[
+    (
+        "$rscrypt$2$BAgAAAABAAAABCAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+        4, 8, 1, 4, 32,
+    ),
+    (
+        "$rscrypt$2$AiwBAAACAAAAEEAA$AAECAwQFBgcICQoLDA0ODw$6cq6PWpYeteuFwc8ZdiIHQFi+\
+         1Lb5haboeusazSqbFLZRnYsm45dVuScoTWmQriLE5RVt6POe+6TZYNaiwR3Ug",
+        2, 300, 2, 16, 64,
+    ),
+];
+
+#[test]
+fn test_v2_fixtures() {
+    for &(hashed, log_n, r, p, salt_len, hash_len) in FIXTURES {
+        assert_eq!(scrypt_check("password", hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("wrong", hashed), Err(CheckError::HashMismatch));
+
+        let hash = ScryptHash::parse(hashed).unwrap();
+        assert_eq!(hash.format_version(), Some(2));
+        assert_eq!(hash.params(), Params::new(log_n, r, p).unwrap());
+        assert_eq!(hash.salt().len(), salt_len);
+        assert_eq!(hash.hash_bytes().len(), hash_len);
+        // version 2 is kept when re-encoding
+        assert_eq!(hash.to_string(), hashed);
+        assert_eq!(ScryptHash::from_bytes(&hash.to_bytes()), Ok(hash.clone()));
+
+        let borrowed = ScryptHashRef::parse(hashed).unwrap();
+        assert_eq!(borrowed.format_version(), Some(2));
+        assert_eq!(ScryptHash::from(borrowed), hash);
+        assert_eq!(borrowed.verify("password"), Ok(()));
+
+        assert_eq!(scrypt_check("password", &rscrypt_to_phc(hashed).unwrap()), Ok(()));
+    }
+    assert_eq!(ScryptHash::parse(FIXTURES[0].0).unwrap().salt(), b"NaCl");
+}
+
+#[test]
+fn test_scrypt_simple_v2() {
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashed = scrypt_simple_v2("password", &params).unwrap();
+    assert!(hashed.starts_with("$rscrypt$2$BAgAAAABAAAAECAA$"), "{}", hashed);
+    assert!(!hashed.ends_with('$') && !hashed.contains('='), "{}", hashed);
+    assert_eq!(hashed.split('$').count(), 6);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+
+    let hash = ScryptHash::parse(&hashed).unwrap();
+    assert_eq!(hash.format_version(), Some(2));
+    assert_eq!(hash.salt().len(), 16);
+    assert_eq!(hash.hash_bytes().len(), 32);
+}
+
+#[test]
+fn test_unsupported_versions() {
+    let hashes = [
+        "$rscrypt$3$BAgAAAABAAAABCAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+        "$rscrypt$10$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+        "$rscrypt$99$",
+    ];
+    for &hashed in hashes.iter() {
+        assert_eq!(scrypt_check("password", hashed), Err(CheckError::UnsupportedVersion));
+        assert_eq!(ScryptHash::parse(hashed), Err(CheckError::UnsupportedVersion));
+        assert_eq!(ScryptHashRef::parse(hashed), Err(CheckError::UnsupportedVersion));
+    }
+}
+
+#[test]
+fn test_v2_malformed() {
+    let hashes = [
+        // trailing `$`
+        "$rscrypt$2$BAgAAAABAAAABCAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM$",
+        // salt and hash lengths differ from the header
+        "$rscrypt$2$BAgAAAABAAAABSAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+        "$rscrypt$2$BAgAAAABAAAABB8A$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+        // no hash
+        "$rscrypt$2$BAgAAAABAAAABAAA$TmFDbA$",
+        // headers of versions 0 and 1
+        "$rscrypt$2$BAgB$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+        "$rscrypt$2$BAgAAAABAAAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+        // invalid parameters
+        "$rscrypt$2$BAAAAAABAAAABCAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+        // no version
+        "$rscrypt$$BAgAAAABAAAABCAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+        "$rscrypt$v2$BAgAAAABAAAABCAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+        // the derived key combined with associated data
+        "$rscrypt$2$BAgAAAABAAAABCCC$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+    ];
+    for &hashed in hashes.iter() {
+        assert_eq!(scrypt_check("password", hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+        assert_eq!(ScryptHashRef::parse(hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+    }
+
+    let padded = [
+        ("$rscrypt$2$BAgAAAABAAAABCAA$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM",
+            Field::Salt),
+        ("$rscrypt$2$BAgAAAABAAAABCAA$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=",
+            Field::Hash),
+    ];
+    for &(hashed, field) in padded.iter() {
+        assert_eq!(scrypt_check("password", hashed), Err(CheckError::NonCanonicalBase64(field)));
+    }
+}
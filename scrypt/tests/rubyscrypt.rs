@@ -0,0 +1,220 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::errors::{CheckError, HashError};
+use scrypt::{scrypt_check, scrypt_simple_ruby, verify_any, Format, HashFormat, ScryptHash, Params,
+    SimpleConfig};
+
+// Strings of `SCrypt::Password.create` of the gem, with Python's
+// `hashlib.scrypt` and the steps of the gem: its default salt and hash
+// lengths, `r` and `p` above 9, leading zero bytes of the salt and a 20
+// byte salt, which the gem writes as 41 digits, and the shortest and a
+// longer hash.
+const FIXTURES: &[(&str, &str, (u8, u32, u32))] = &[
+    (
+        "password",
+        "4000$8$1$5f3c1a9e7b2d4c6f8a0e1b3d5c7f9a2e4b6d8f0a1c3e5b7d9f2a4c6e8b0d1f3a$\
+         f7fd97f0563d9c2d9ab6caec052990a7575295e0074389c2473e126475bd893e",
+        (14, 8, 1),
+    ),
+    (
+        "pleaseletmein",
+        "400$8$25$808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f$\
+         3099a2a8c9bed3cf7b44b0311ac19efc6c9216005e7c6c3b17b42bd95176a1d2",
+        (10, 8, 37),
+    ),
+    (
+        "Ünïcødé",
+        "800$4$2$0000abc1c2c3c4c5c6c7c8c9cacbcccdcecfd0d1d2d3d4d5d6d7d8d9dadbdcdd$\
+         2687a4dbc723fb15f973b944c6da15e9f95881b9a00ad43c082826df268401ba",
+        (11, 4, 2),
+    ),
+    (
+        "correct horse",
+        "200$2$1$03132333435363738393a3b3c3d3e3f4041424344$\
+         cc1f552bcc3ebca9fb79a1d9b75be4022b0349eadeea5d8a439446b201a1e0a2",
+        (9, 2, 1),
+    ),
+    (
+        "",
+        "10$1$1$c8c9cacbcccdcecf$be0854f3cf69c4c7be4b58c9902ba01b",
+        (4, 1, 1),
+    ),
+    (
+        "password",
+        "100$10$1$101112131415161718191a1b1c1d1e1f2021222324252627$\
+         a394e6a64006764160efebcc6f4b28462262581f043e676c6c372b9a1677a358\
+         0701408e4ec45df8cef13632348f33e560f46b1b8e899699a3924132221223fd",
+        (8, 16, 1),
+    ),
+];
+
+#[test]
+fn test_ruby_fixtures() {
+    for &(password, hashed, (log_n, r, p)) in FIXTURES {
+        assert_eq!(Format::detect(hashed), Some(Format::RubyScrypt), "{}", hashed);
+        assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("wrong", hashed), Err(CheckError::HashMismatch));
+        assert_eq!(verify_any(password, hashed).unwrap().format(), Format::RubyScrypt);
+
+        let parts = Format::RubyScrypt.parse(hashed).unwrap();
+        assert_eq!(parts.params, Params::new(log_n, r, p).unwrap());
+        // strings the gem does not write itself are re-encoded
+        let encoded = Format::RubyScrypt.encode(&parts);
+        assert_eq!(scrypt_check(password, &encoded), Ok(()), "{}", encoded);
+        assert_eq!(ScryptHash::parse(hashed).unwrap().to_string(), encoded);
+    }
+    for fixture in [&FIXTURES[0], &FIXTURES[1], &FIXTURES[4], &FIXTURES[5]].iter() {
+        let parts = Format::RubyScrypt.parse(fixture.1).unwrap();
+        assert_eq!(Format::RubyScrypt.encode(&parts), fixture.1);
+    }
+}
+
+#[test]
+fn test_ruby_salt_handling() {
+    // leading zero bytes are not passed to scrypt
+    let parts = Format::RubyScrypt.parse(FIXTURES[2].1).unwrap();
+    assert_eq!(parts.salt.len(), 30);
+    assert_eq!(parts.salt[0], 0xab);
+    // an odd number of digits is padded with a zero digit
+    let parts = Format::RubyScrypt.parse(FIXTURES[3].1).unwrap();
+    assert_eq!(parts.salt.len(), 21);
+    assert_eq!(&parts.salt[..2], &[0x03, 0x13]);
+    assert_eq!(parts.salt[20], 0x40);
+    // the hex of the salt may have either case
+    let upper = FIXTURES[1].1.replace("8a8b8c8d8e8f", "8A8B8C8D8E8F");
+    assert_eq!(scrypt_check("pleaseletmein", &upper), Ok(()));
+}
+
+#[test]
+fn test_ruby_malformed() {
+    let rest = "$c8c9cacbcccdcecf$be0854f3cf69c4c7be4b58c9902ba01b";
+    let invalid_params = [
+        "10$1$1$1", "010$1$1", "10$01$1", "10$1$0", "10$0$1", "10$100000000$1",
+        "8000000000000000$1$1",
+    ];
+    for params in invalid_params.iter() {
+        let hashed = format!("{}{}", params, rest);
+        assert_eq!(Format::detect(&hashed), Some(Format::RubyScrypt), "{}", hashed);
+        assert_eq!(scrypt_check("", &hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+    }
+
+    let salt = "10$1$1$c8c9cacbcccdcecf";
+    let hash = "be0854f3cf69c4c7be4b58c9902ba01b";
+    let invalid = [
+        salt.to_string(),
+        format!("{}$", salt),
+        format!("{}${}$", salt, hash),
+        // below the 16 bytes of the gem
+        format!("{}${}", salt, &hash[..30]),
+        format!("{}${}0", salt, hash),
+        // the gem compares the hex strings themselves
+        format!("{}${}", salt, hash.to_uppercase()),
+        // salts of 7 and 33 bytes, 40 digits of the SHA-1 hashes, not hex
+        format!("10$1$1$c8c9cacbcccdce${}", hash),
+        format!("10$1$1${}${}", "c8".repeat(33), hash),
+        format!("10$1$1${}${}", "c8".repeat(20), hash),
+        format!("10$1$1$c8c9cacbcccdcecg${}", hash),
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(Format::detect(hashed), Some(Format::RubyScrypt), "{}", hashed);
+        assert_eq!(scrypt_check("", hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+    }
+}
+
+// Strings which read as more than one dialect of `<N>$<r>$<p>$<salt>$<hash>`
+#[test]
+fn test_ruby_ambiguous() {
+    let hash = "a70a33adddf10ce88b3bfbe2f00158f1c14d3f1c6f34c56d16b146eed36efa17";
+    let cases = [
+        // `N` is a power of two in hex only
+        ("10$8$1$c8c9cacbcccdcecf", Format::RubyScrypt),
+        ("400$8$1$c8c9cacbcccdcecf", Format::RubyScrypt),
+        ("4000$8$1$yMnKy8zNzs8=", Format::RubyScrypt),
+        ("100$10$1$c8c9cacbcccdcecf", Format::RubyScrypt),
+        // in decimal only
+        ("16$8$1$c8c9cacbcccdcecf", Format::SimpleScrypt),
+        ("1024$8$1$c8c9cacbcccdcecf", Format::SimpleScrypt),
+        ("1024$8$1$yMnKy8zNzs8=", Format::PhpScrypt),
+        ("16384$10$1$c8c9cacbcccdcecf", Format::SimpleScrypt),
+        // in both, the same value
+        ("8$8$1$c8c9cacbcccdcecf", Format::SimpleScrypt),
+        ("2$1$1$yMnKy8zNzs8=", Format::PhpScrypt),
+        // in neither, which is malformed in the decimal dialects
+        ("12$8$1$c8c9cacbcccdcecf", Format::SimpleScrypt),
+    ];
+    for &(head, format) in cases.iter() {
+        let hashed = format!("{}${}", head, hash);
+        assert_eq!(Format::detect(&hashed), Some(format), "{}", hashed);
+    }
+
+    // hex letters in `N` are never decimal, but not always a power of two
+    for head in ["4a$8$1$c8c9cacbcccdcecf", "400$8$1f$YMNKY8ZNZS8=", "400$A$1$c8c9cacbcccdcecf"]
+        .iter()
+    {
+        let hashed = format!("{}${}", head, hash);
+        assert_ne!(Format::detect(&hashed), Some(Format::SimpleScrypt), "{}", hashed);
+        assert_ne!(Format::detect(&hashed), Some(Format::PhpScrypt), "{}", hashed);
+    }
+    assert_eq!(Format::detect(&format!("4a$8$1$c8c9cacbcccdcecf${}", hash)), None);
+    assert_eq!(Format::detect(&format!("400$A$1$c8c9cacbcccdcecf${}", hash)), None);
+    // a Ruby string with a salt the gem rejects still is one
+    assert_eq!(Format::detect(&format!("400$8$1f$YMNKY8ZNZS8=${}", hash)),
+        Some(Format::RubyScrypt));
+}
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_ruby_fixed_salt() {
+    // a leading zero byte is drawn again
+    let mut bytes = vec![0u8];
+    bytes.extend(0x81..0xa0);
+    bytes.extend_from_slice(&[0, 0x80]);
+    let mut rng = FixedRng(bytes);
+    let params = Params::new(10, 8, 37).unwrap();
+    let config = SimpleConfig::builder().params(params).format(Format::RubyScrypt).build()
+        .unwrap();
+    let hashed = config.hash_with_rng("pleaseletmein", &mut rng).unwrap();
+    assert_eq!(hashed, FIXTURES[1].1);
+    assert!(rng.0.is_empty());
+}
+
+#[test]
+fn test_ruby_round_trip() {
+    let params = Params::new(4, 16, 10).unwrap();
+    let hashed = scrypt_simple_ruby("password", &params).unwrap();
+    let fields: Vec<&str> = hashed.split('$').collect();
+    assert_eq!(fields.len(), 5);
+    assert_eq!(&fields[..3], &["10", "10", "a"]);
+    assert_eq!(fields[3].len(), 64);
+    assert_eq!(fields[4].len(), 64);
+    assert_eq!(Format::detect(&hashed), Some(Format::RubyScrypt));
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+
+    // the gem reads 40 digits as a SHA-1 salt
+    match SimpleConfig::builder().params(params).format(Format::RubyScrypt).salt_len(20).build() {
+        Err(HashError::InvalidSaltLen(20)) => (),
+        res => panic!("{:?}", res),
+    }
+}
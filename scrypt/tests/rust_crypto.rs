@@ -0,0 +1,53 @@
+#![cfg(feature="simple-verify")]
+extern crate scrypt;
+
+use scrypt::{scrypt_check, Format, ScryptHash};
+use scrypt::errors::CheckError;
+
+// The strings of `scrypt_simple()` of rust-crypto 0.2.x: parameters, salt
+// and hash in padded standard base64, the 16 byte salts taken from
+// `random.Random(158)`. r and p fit a byte in version 0, the others use
+// version 1. The hashes were computed with Python's `hashlib.scrypt`.
+const FIXTURES: &[(&str, u8, u32, u32, &str)] = &[
+    ("password", 4, 8, 1,
+     "$rscrypt$0$BAgB$cDQe1qjsFCA6ApAYHSmMEA==$yU89S4s6Jsib8593KObBF1NTE4eptI0oLU6855R68q8=$"),
+    ("", 4, 8, 1,
+     "$rscrypt$0$BAgB$aRzweSzHoTtTKkc1spqmqw==$9+D4LjWuxCvLB6ht36b3WoyyfWob5mdg4kvZ6gdxLtA=$"),
+    ("Ünïcødé", 5, 8, 2,
+     "$rscrypt$0$BQgC$nnmDgJtQttYZ6FJgqGUo3w==$Wv5YkQOJqeslEhI6wyqnjKgGQml3in7K44yuYNd4JdY=$"),
+    ("", 1, 1, 1,
+     "$rscrypt$0$AQEB$4h+USiBjokBHhpzO3xAeMw==$/qBbGMonfz3VPJnrhVn43LHCCErbBXBKxyvEG4XQAZc=$"),
+    ("パスワード", 3, 255, 1,
+     "$rscrypt$0$A/8B$HCrZBb17OJdY7uZlxcXYZQ==$Maau1SPagJOOJviPRwUdXfdwuxZSKd9GRy4kOob8oaI=$"),
+    ("password", 2, 1, 255,
+     "$rscrypt$0$AgH/$9BCCviJid7qzsI0x17QZ/g==$evq5eWMwy8MVGqmfu4cfxasy1BKFLfMFr84F+cvTpEo=$"),
+    ("password", 1, 255, 2,
+     "$rscrypt$0$Af8C$FUUg0tAHXtwg1KdHcMtTtg==$tGWLwHMNb7V/e2NBFSQLnsoCPLp7MgGS5xSMUwLABys=$"),
+    ("", 2, 256, 1,
+     "$rscrypt$1$AgABAAABAAAA$DC8rSm9cEpEp1LV4viwDHA==$kQXUXO//UFyGkMYn9urpZDTy0ZDac3l5b772HiPU3MY=$"),
+    ("Ünïcødé", 2, 1, 256,
+     "$rscrypt$1$AgEAAAAAAQAA$wM9opR1iAo2/L8oJF0l7Kw==$E78tlIFuLqxtVhQ1rnL0TbYkCWbG+rsj0uJU8CA1Rkw=$"),
+    ("password", 1, 256, 3,
+     "$rscrypt$1$AQABAAADAAAA$oZKkDsxuNHmH+e/BMbnBog==$RsUgaQ7izvQmTayR7aPv9UcPmAXw+pxRWxtdX5iSqlk=$"),
+];
+
+#[test]
+fn test_rust_crypto_check() {
+    for &(password, _, _, _, hashed) in FIXTURES {
+        assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("wrong", hashed), Err(CheckError::HashMismatch), "{}", hashed);
+    }
+}
+
+#[test]
+fn test_rust_crypto_parse() {
+    for &(_, log_n, r, p, hashed) in FIXTURES {
+        let hash = ScryptHash::parse(hashed).unwrap();
+        assert_eq!(hash.format(), Format::Rscrypt);
+        let params = hash.params();
+        assert_eq!((params.log_n(), params.r(), params.p()), (log_n, r, p), "{}", hashed);
+        assert_eq!((hash.salt().len(), hash.hash_bytes().len()), (16, 32));
+        // the strings are written exactly as this crate writes them
+        assert_eq!(hash.to_string(), hashed);
+    }
+}
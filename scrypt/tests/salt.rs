@@ -0,0 +1,38 @@
+extern crate scrypt;
+
+use scrypt::derive_salt;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn test_vectors() {
+    // the first 16 bytes of HMAC-SHA-256, as computed by Python's hmac
+    let vectors: &[(&[u8], &[u8], &str)] = &[
+        (b"", b"", "b613679a0814d9ec772f95d778c35fc5"),
+        (b"global key", b"alice", "9fa42d2a2a8cd6fcaab43c1bea98bf60"),
+        (b"global key", b"bob", "acd2ec25d4baca0b866319f8c5c04276"),
+        (b"other key", b"alice", "c0e051f65d7049e2a1a67cfb082f510b"),
+    ];
+    for &(key, identifier, salt) in vectors {
+        assert_eq!(hex(&derive_salt(key, identifier)), salt);
+    }
+}
+
+#[test]
+fn test_unrelated() {
+    // salts of similar identifiers differ in about half of their 128 bits
+    let salts: Vec<[u8; 16]> = (0..256u32)
+        .map(|i| derive_salt(b"global key", format!("user{}", i).as_bytes()))
+        .collect();
+    for (i, a) in salts.iter().enumerate() {
+        for b in &salts[i + 1..] {
+            let distance: u32 = a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum();
+            assert!((32..=96).contains(&distance), "{:?} {:?}", a, b);
+        }
+    }
+    let distance: u32 = derive_salt(b"global key", b"alice").iter()
+        .zip(&derive_salt(b"global keY", b"alice")).map(|(x, y)| (x ^ y).count_ones()).sum();
+    assert!((32..=96).contains(&distance));
+}
@@ -0,0 +1,59 @@
+#![cfg(feature="low-level")]
+extern crate hmac;
+extern crate pbkdf2;
+extern crate scrypt;
+extern crate sha2;
+
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use sha2::Sha256;
+
+use scrypt::errors::ErrorCode;
+use scrypt::test_vectors::{Operation, RO_MIX, SCRYPT};
+use scrypt::{scrypt_core, Params};
+
+/// `scrypt()` composed of the first PBKDF2, `scrypt_core` and the last.
+fn compose(password: &[u8], salt: &[u8], params: &Params, output: &mut [u8]) {
+    let mut b = vec![0u8; 128 * params.r() as usize * params.p() as usize];
+    pbkdf2::<Hmac<Sha256>>(password, salt, 1, &mut b);
+    scrypt_core(&mut b, params).unwrap();
+    pbkdf2::<Hmac<Sha256>>(password, &b, 1, output);
+}
+
+#[test]
+fn test_scrypt_core_rfc_vectors() {
+    // the last vector needs 1 GiB
+    for vector in &SCRYPT[..3] {
+        if let Operation::Scrypt { password, salt, log_n, r, p } = vector.operation {
+            let params = Params::new(log_n, r, p).unwrap();
+            let mut output = vec![0u8; vector.expected.len()];
+            compose(password, salt, &params, &mut output);
+            assert_eq!(&output[..], vector.expected, "{}", vector.name);
+        }
+    }
+}
+
+#[test]
+fn test_scrypt_core_ro_mix_vector() {
+    if let Operation::RoMix { input, log_n } = RO_MIX.operation {
+        let params = Params::new(log_n, 1, 1).unwrap();
+        let mut b = input.to_vec();
+        scrypt_core(&mut b, &params).unwrap();
+        assert_eq!(&b[..], RO_MIX.expected);
+    }
+}
+
+#[test]
+fn test_scrypt_core_buffer_len() {
+    let params = Params::new(4, 2, 3).unwrap();
+    for &len in [0, 128, 767, 769, 1536].iter() {
+        let mut b = vec![0u8; len];
+        let err = scrypt_core(&mut b, &params).unwrap_err();
+        assert_eq!(err.provided_len(), len);
+        assert_eq!(err.expected_len(), 768);
+        assert_eq!(err.code(), ErrorCode::InvalidBufferLen);
+        // the buffer is left as it was
+        assert!(b.iter().all(|&x| x == 0));
+    }
+    assert_eq!(scrypt_core(&mut [0u8; 768], &params), Ok(()));
+}
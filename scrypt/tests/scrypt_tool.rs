@@ -0,0 +1,100 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use std::env;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+use scrypt::{scrypt_check, ScryptHash};
+
+/// The example binary, which `cargo test` builds next to the test binaries.
+/// `cargo test --test scrypt_tool` alone does not, build it first with
+/// `cargo build --example scrypt-tool`.
+fn tool() -> PathBuf {
+    let mut path = env::current_exe().unwrap();
+    path.pop();
+    if path.ends_with("deps") { path.pop(); }
+    path.join("examples").join(format!("scrypt-tool{}", env::consts::EXE_SUFFIX))
+}
+
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(tool()).args(args)
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .spawn().unwrap_or_else(|e| panic!("{}: {}", tool().display(), e));
+    // the tool exits without reading stdin for invalid arguments
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_hash() {
+    let output = run(&["hash", "--params", "ln=4,r=8,p=1"], "Ünïcødé\n");
+    assert!(output.status.success());
+    let hashed = stdout(&output);
+    let hashed = hashed.trim_end();
+    assert!(hashed.starts_with("$rscrypt$0$BAgB$"), "{}", hashed);
+    assert_eq!(scrypt_check("Ünïcødé", hashed), Ok(()));
+
+    let output = run(&["hash", "--format", "phc", "--params", "ln=5,r=4,p=2"], "password\r\n");
+    assert!(output.status.success());
+    let phc = stdout(&output);
+    let hash = ScryptHash::parse(phc.trim_end()).unwrap();
+    assert!(phc.starts_with("$scrypt$ln=5,r=4,p=2$"), "{}", phc);
+    assert_eq!(hash.verify("password"), Ok(()));
+
+    // secrets are never printed
+    for output in [&output, &run(&["hash", "--params", "ln=4"], "password")].iter() {
+        assert!(!stdout(output).contains("password"));
+        assert!(!String::from_utf8_lossy(&output.stderr).contains("password"));
+    }
+}
+
+#[test]
+fn test_verify() {
+    let hashed = "$rscrypt$0$BAgB$15ieabOWBi+lTfggDq1u4A==$\
+                  LSoB1to848I8SrfvmQAiAa2ajqs+iVgB5TecmxomqfU=$";
+    let output = run(&["verify", "--hash", hashed], "password\n");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "match\n");
+
+    let output = run(&["verify", "--hash", hashed], "wrong\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "mismatch\n");
+    // only the line ending is removed from the password
+    assert_eq!(run(&["verify", "--hash", hashed], " password\n").status.code(), Some(1));
+
+    let output = run(&["verify", "--hash", &hashed[1..]], "password\n");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout(&output).is_empty());
+}
+
+#[test]
+fn test_usage_errors() {
+    for args in [
+        &[][..],
+        &["frobnicate"],
+        &["verify"],
+        &["hash", "--params", "ln=4,r=0,p=1"],
+        &["hash", "--params", "n=4"],
+        &["hash", "--format", "bcrypt"],
+        &["hash", "--params"],
+        &["bench", "--max-mem", "lots"],
+    ].iter() {
+        assert_eq!(run(args, "password\n").status.code(), Some(2), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_bench() {
+    // `ln=10` with r = 8 takes 2 KiB more than 1 MiB
+    let output = run(&["bench", "--target-ms", "60000", "--max-mem", "1MiB"], "");
+    assert!(output.status.success());
+    assert!(stdout(&output).starts_with("ln=9,r=8,p=1 ("), "{}", stdout(&output));
+    // nothing fits into a kibibyte
+    assert_eq!(run(&["bench", "--max-mem", "1KiB"], "").status.code(), Some(2));
+}
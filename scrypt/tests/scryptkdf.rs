@@ -0,0 +1,137 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::errors::{CheckError, ErrorCode, Field};
+use scrypt::{scrypt_check, scrypt_simple_scryptkdf, verify_any, Format, HashFormat, ScryptHash,
+    Params, SimpleConfig};
+
+// Strings of `Crypt::ScryptKDF::scrypt_hash`, with Python's `hashlib.scrypt`
+// and the layout of the module: its defaults, non-default `r` and `p`, a
+// 64 byte key and an empty password.
+const FIXTURES: &[(&str, &str, (u8, u32, u32))] = &[
+    (
+        "password",
+        "SCRYPT:16384:8:1:AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=:\
+         Qvo2YhnKa4mXBZkHpOtBbPjZEXSVct+80WcIS1gH9SQ=",
+        (14, 8, 1),
+    ),
+    (
+        "pleaseletmein",
+        "SCRYPT:1024:4:2:U29kaXVtQ2hsb3JpZGVTb2RpdW1DaGxvcmlkZU5hQ2w=:\
+         SH6EAdaSaA9rmqCriPXz+q3WkdmbjNr442ENCVX2jzU=",
+        (10, 4, 2),
+    ),
+    (
+        "Ünïcødé",
+        "SCRYPT:2048:16:3:ZGVmZ2hpamtsbW5vcHFyc3R1dnd4eXp7fH1+f4CBgoM=:\
+         aKjzcWJTJkshch2vpcqG6NoR5EnlOpF41JhKs0SSIhk8sQX/wcsbJRxALjkc9PjA/decUK0LLX6898YwVKMzFQ==",
+        (11, 16, 3),
+    ),
+    (
+        "",
+        "SCRYPT:16:1:1:yMnKy8zNzs/Q0dLT1NXW1w==:1Yx/mr66DN78fXHQxPd95MK5nDxhKHMwypgesOeM37E=",
+        (4, 1, 1),
+    ),
+];
+
+#[test]
+fn test_scryptkdf_fixtures() {
+    for &(password, hashed, (log_n, r, p)) in FIXTURES {
+        assert_eq!(Format::detect(hashed), Some(Format::ScryptKdf));
+        assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("wrong", hashed), Err(CheckError::HashMismatch));
+        assert_eq!(verify_any(password, hashed).unwrap().format(), Format::ScryptKdf);
+
+        let parts = Format::ScryptKdf.parse(hashed).unwrap();
+        assert_eq!(parts.params, Params::new(log_n, r, p).unwrap());
+        assert_eq!(Format::ScryptKdf.encode(&parts), hashed);
+        assert_eq!(ScryptHash::parse(hashed).unwrap().to_string(), hashed);
+    }
+    assert_eq!(Format::ScryptKdf.parse(FIXTURES[2].1).unwrap().hash.len(), 64);
+}
+
+const VALID: &str = "SCRYPT:16:1:1:yMnKy8zNzs/Q0dLT1NXW1w==:\
+                     1Yx/mr66DN78fXHQxPd95MK5nDxhKHMwypgesOeM37E=";
+
+#[test]
+fn test_scryptkdf_invalid_fields() {
+    let hash = "1Yx/mr66DN78fXHQxPd95MK5nDxhKHMwypgesOeM37E=";
+    let salt = "yMnKy8zNzs/Q0dLT1NXW1w==";
+    let cases = [
+        // missing fields
+        ("SCRYPT:".to_string(), Field::N),
+        ("SCRYPT:16".to_string(), Field::R),
+        ("SCRYPT:16:1".to_string(), Field::P),
+        ("SCRYPT:16:1:1".to_string(), Field::Salt),
+        (format!("SCRYPT:16:1:1:{}", salt), Field::Hash),
+        // malformed fields
+        (format!("SCRYPT:abc:1:1:{}:{}", salt, hash), Field::N),
+        (format!("SCRYPT::1:1:{}:{}", salt, hash), Field::N),
+        (format!("SCRYPT:-16:1:1:{}:{}", salt, hash), Field::N),
+        (format!("SCRYPT:15:1:1:{}:{}", salt, hash), Field::N),
+        (format!("SCRYPT:1:1:1:{}:{}", salt, hash), Field::N),
+        (format!("SCRYPT:016:1:1:{}:{}", salt, hash), Field::N),
+        (format!("SCRYPT:16:x:1:{}:{}", salt, hash), Field::R),
+        (format!("SCRYPT:16:0:1:{}:{}", salt, hash), Field::R),
+        (format!("SCRYPT:16:4294967297:1:{}:{}", salt, hash), Field::R),
+        (format!("SCRYPT:16:1:1.5:{}:{}", salt, hash), Field::P),
+        (format!("SCRYPT:16:1:0:{}:{}", salt, hash), Field::P),
+        (format!("SCRYPT:16:1:1::{}", hash), Field::Salt),
+        (format!("SCRYPT:16:1:1:yMnKy8zNzs/Q0dLT1NXW1w:{}", hash), Field::Salt),
+        (format!("SCRYPT:16:1:1:yMnKy8zNzs/Q0dLT1NXW1-==:{}", hash), Field::Salt),
+        (format!("SCRYPT:16:1:1:{}:", salt), Field::Hash),
+        (format!("SCRYPT:16:1:1:{}:1Yx/mr66DN78fXHQxPd95MK5nDxhKHMwypgesOeM37E", salt),
+            Field::Hash),
+    ];
+    for &(ref hashed, field) in cases.iter() {
+        assert_eq!(Format::detect(hashed), Some(Format::ScryptKdf), "{}", hashed);
+        assert_eq!(scrypt_check("", hashed), Err(CheckError::InvalidField(field)), "{}", hashed);
+    }
+
+    let err = CheckError::InvalidField(Field::N);
+    assert_eq!(err.code(), ErrorCode::InvalidField);
+    assert_eq!(err.to_string(), "missing or invalid N field in `hashed_value`");
+
+    // not a single field at fault
+    let extra = format!("{}:", VALID);
+    assert_eq!(scrypt_check("", &extra), Err(CheckError::InvalidFormat));
+    let rp = format!("SCRYPT:16:65536:65536:{}:{}", salt, hash);
+    assert_eq!(scrypt_check("", &rp), Err(CheckError::InvalidFormat));
+}
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.copy_from_slice(&self.0[..dest.len()]);
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_scrypt_simple_scryptkdf() {
+    let (password, expected, _) = FIXTURES[0];
+    let params = Params::new(14, 8, 1).unwrap();
+    let config = SimpleConfig::builder().params(params).format(Format::ScryptKdf).build()
+        .unwrap();
+    let hashed = config.hash_with_rng(password, &mut FixedRng((0..32).collect())).unwrap();
+    assert_eq!(hashed, expected);
+
+    let params = Params::new(4, 2, 3).unwrap();
+    let hashed = scrypt_simple_scryptkdf("password", &params).unwrap();
+    assert!(hashed.starts_with("SCRYPT:16:2:3:"), "{}", hashed);
+    let parts = Format::ScryptKdf.parse(&hashed).unwrap();
+    assert_eq!((parts.salt.len(), parts.hash.len()), (32, 32));
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_ne!(scrypt_simple_scryptkdf("password", &params).unwrap(), hashed);
+}
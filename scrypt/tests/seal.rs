@@ -0,0 +1,107 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use scrypt::errors::{CheckError, ErrorCode};
+use scrypt::{scrypt_simple, Params, PepperSet, RecordSealer};
+
+// Generated with Python's `hashlib.scrypt`.
+const HASHED: &str = "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$";
+// The MAC computed with Python's `hmac`.
+const SEALED: &str = "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$\
+    $mac=1,TXj2kH9tQtPXKFFVqze/yRjuxr2o6zqjZROgW5dda5k";
+
+fn keys(keys: &[(u8, &[u8])]) -> PepperSet {
+    let mut set = PepperSet::new();
+    for &(id, key) in keys.iter() {
+        set.insert(id, key);
+    }
+    set
+}
+
+fn sealer() -> RecordSealer {
+    RecordSealer::new(keys(&[(1, b"server key 1")])).unwrap()
+}
+
+#[test]
+fn test_valid_mac() {
+    let sealer = sealer();
+    assert_eq!(sealer.seal(HASHED, b"user 42"), SEALED);
+    assert_eq!(sealer.unseal(SEALED, b"user 42"), Ok(HASHED));
+    assert_eq!(sealer.verify("password", SEALED, b"user 42"), Ok(()));
+    assert_eq!(sealer.verify("wrong", SEALED, b"user 42"), Err(CheckError::HashMismatch));
+    assert!(!sealer.needs_reseal(SEALED));
+
+    assert!(RecordSealer::new(PepperSet::new()).is_none());
+}
+
+#[test]
+fn test_stripped_mac() {
+    let mut sealer = sealer();
+    let err = sealer.verify("password", HASHED, b"user 42").unwrap_err();
+    assert_eq!(err, CheckError::TamperSuspected);
+    assert_eq!(err.code(), ErrorCode::TamperSuspected);
+
+    // records of before the sealing verify if allowed, and are resealed
+    sealer.allow_unsealed(true);
+    assert_eq!(sealer.verify("password", HASHED, b"user 42"), Ok(()));
+    assert!(sealer.needs_reseal(HASHED));
+    assert_eq!(sealer.reseal(HASHED, b"user 42"), Ok(SEALED.to_string()));
+    // a sealed record must still carry a valid MAC
+    assert_eq!(sealer.verify("password", &SEALED.replace(",TX", ",AX"), b"user 42"),
+        Err(CheckError::TamperSuspected));
+}
+
+#[test]
+fn test_swapped_hash() {
+    let sealer = sealer();
+    let params = Params::new(4, 8, 1).unwrap();
+    let known = scrypt_simple("known", &params).unwrap();
+
+    // a hash of a known password, sealed under a key of the attacker
+    let attacker = RecordSealer::new(keys(&[(1, b"attacker key")])).unwrap();
+    let swapped = attacker.seal(&known, b"user 42");
+    assert_eq!(sealer.verify("known", &swapped, b"user 42"), Err(CheckError::TamperSuspected));
+
+    // the record of another account
+    let other = sealer.seal(&known, b"user 7");
+    assert_eq!(sealer.verify("known", &other, b"user 7"), Ok(()));
+    assert_eq!(sealer.verify("known", &other, b"user 42"), Err(CheckError::TamperSuspected));
+
+    // the hash replaced under the original MAC
+    let replaced = SEALED.replace(HASHED, &known);
+    assert_eq!(sealer.verify("known", &replaced, b"user 42"), Err(CheckError::TamperSuspected));
+
+    // the MAC is checked before the hash is computed, which would take a
+    // terabyte here
+    let expensive = attacker.seal("$scrypt$ln=30,r=8,p=1$c2FsdA$AAAAAAAAAAAAAAAAAAAAAA",
+        b"user 42");
+    assert_eq!(sealer.verify("known", &expensive, b"user 42"), Err(CheckError::TamperSuspected));
+
+    for &field in ["$mac=", "$mac=1", "$mac=256,AAAA", "$mac=1,AAAA"].iter() {
+        assert_eq!(sealer.unseal(&format!("{}{}", HASHED, field), b"user 42"),
+            Err(CheckError::InvalidFormat), "{}", field);
+    }
+}
+
+#[test]
+fn test_key_rotation() {
+    let rotated = RecordSealer::new(keys(&[(1, b"server key 1"), (2, b"server key 2")]))
+        .unwrap();
+    // the old key still verifies, the new one seals
+    assert_eq!(rotated.verify("password", SEALED, b"user 42"), Ok(()));
+    assert!(rotated.needs_reseal(SEALED));
+    let resealed = rotated.reseal(SEALED, b"user 42").unwrap();
+    assert!(resealed.starts_with(HASHED));
+    assert!(resealed.contains("$mac=2,"));
+    assert!(!rotated.needs_reseal(&resealed));
+    assert_eq!(rotated.verify("password", &resealed, b"user 42"), Ok(()));
+
+    // once the old key is removed, its records no longer verify
+    let retired = RecordSealer::new(keys(&[(2, b"server key 2")])).unwrap();
+    assert_eq!(retired.verify("password", &resealed, b"user 42"), Ok(()));
+    assert_eq!(retired.verify("password", SEALED, b"user 42"),
+        Err(CheckError::TamperSuspected));
+    // the key id is covered by the MAC
+    assert_eq!(rotated.verify("password", &resealed.replace("$mac=2,", "$mac=1,"), b"user 42"),
+        Err(CheckError::TamperSuspected));
+}
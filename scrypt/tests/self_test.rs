@@ -0,0 +1,41 @@
+//! `self_test`, in a test binary of its own: the hook corrupting a backend
+//! is global to the process.
+extern crate scrypt;
+
+use std::time::{Duration, Instant};
+
+#[cfg(feature="test-util")]
+use scrypt::errors::ErrorCode;
+#[cfg(feature="test-util")]
+use scrypt::testing::corrupt_self_test;
+use scrypt::{self_test, Backend};
+
+#[test]
+fn test_self_test() {
+    let start = Instant::now();
+    assert_eq!(self_test(), Ok(()));
+    if !cfg!(debug_assertions) {
+        assert!(start.elapsed() < Duration::from_millis(100), "{:?}", start.elapsed());
+    }
+    assert!(Backend::compiled().contains(&Backend::Portable));
+}
+
+#[cfg(feature="test-util")]
+#[test]
+fn test_corrupt_self_test() {
+    for &backend in Backend::compiled() {
+        corrupt_self_test(Some(backend));
+        let err = self_test().unwrap_err();
+        assert_eq!(err.backend(), backend);
+        let vector = if backend == Backend::Portable { "RFC 7914 Salsa20/8" } else {
+            "RFC 7914 ROMix"
+        };
+        assert_eq!(err.vector(), vector);
+        assert_eq!(err.code(), ErrorCode::SelfTestFailed);
+        assert!(err.to_string().contains(vector), "{}", err);
+        assert!(err.to_string().contains(&backend.to_string()), "{}", err);
+    }
+
+    corrupt_self_test(None);
+    assert_eq!(self_test(), Ok(()));
+}
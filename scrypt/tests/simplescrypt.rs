@@ -0,0 +1,166 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::errors::CheckError;
+use scrypt::{scrypt_check, scrypt_simple_go, verify_any, Format, HashFormat, ScryptHash, Params,
+    SimpleConfig};
+
+// Strings of simple-scrypt's `GenerateFromPassword()`, with Python's
+// `hashlib.scrypt` and the layout of the package: its `DefaultParams`, then
+// custom parameters with the shortest salt and hash it accepts and a 64
+// byte hash.
+const FIXTURES: &[(&str, &str, (u8, u32, u32))] = &[
+    (
+        "password",
+        "16384$8$1$7f3a9c2e5b014d86a2c7e1f09b3d5a48$\
+         94b1243c6042ae045033214cce568d3a99dfa089539dca1d0373b0a9a6e89543",
+        (14, 8, 1),
+    ),
+    (
+        "pleaseletmein",
+        "1024$4$2$536f6469756d43686c6f726964652121$\
+         4b690850ba6d042b464b35dc754090d39e06d7007e6fb24c09d6467d24f7a282",
+        (10, 4, 2),
+    ),
+    (
+        "Ünïcødé",
+        "2048$16$3$c8c9cacbcccdcecf$56c8c406d3597dc3d3451435509a4664",
+        (11, 16, 3),
+    ),
+    (
+        "",
+        "16$1$1$000102030405060708090a0b0c0d0e0f1011121314151617$\
+         1324afe19215635f69eb5bb5a04c63d9c39282154039c1854f79fcf8232e388b\
+         5c757a00893b773c4012e638f592e9eb86da88090565e0f459b8394d73ef1bf8",
+        (4, 1, 1),
+    ),
+    (
+        "correct horse",
+        "4096$8$1$fbf0003e127780aa5f01020304050607$\
+         b7ec5e2a416bc4709345871c026f620e7fdc2d745bda921e06376aa86294ebb6",
+        (12, 8, 1),
+    ),
+];
+
+#[test]
+fn test_go_fixtures() {
+    for &(password, hashed, (log_n, r, p)) in FIXTURES {
+        assert_eq!(Format::detect(hashed), Some(Format::SimpleScrypt), "{}", hashed);
+        assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("wrong", hashed), Err(CheckError::HashMismatch));
+        assert_eq!(verify_any(password, hashed).unwrap().format(), Format::SimpleScrypt);
+
+        let parts = Format::SimpleScrypt.parse(hashed).unwrap();
+        assert_eq!(parts.params, Params::new(log_n, r, p).unwrap());
+        assert_eq!(Format::SimpleScrypt.encode(&parts), hashed);
+        assert_eq!(ScryptHash::parse(hashed).unwrap().to_string(), hashed);
+    }
+
+    // Go reads hex of either case
+    let upper = FIXTURES[0].1.to_uppercase();
+    assert_eq!(scrypt_check("password", &upper), Ok(()));
+    assert_eq!(ScryptHash::parse(&upper).unwrap().to_string(), FIXTURES[0].1);
+}
+
+#[test]
+fn test_go_detection() {
+    let hash = "a70a33adddf10ce88b3bfbe2f00158f1c14d3f1c6f34c56d16b146eed36efa17";
+    let salts = [
+        // php-scrypt salts: generated, too short or not hex for simple-scrypt
+        ("yMnKy8zNzs8=", Format::PhpScrypt),
+        ("c8c9cacbcccdce", Format::PhpScrypt),
+        ("c8c9cacbcccdcecfd", Format::PhpScrypt),
+        ("c8c9cacbcccdcecg", Format::PhpScrypt),
+        ("", Format::PhpScrypt),
+        // hex of 8 bytes and more
+        ("c8c9cacbcccdcecf", Format::SimpleScrypt),
+        ("C8C9CACBCCCDCECF", Format::SimpleScrypt),
+        ("c8c9cacbcccdcecfd0d1d2d3d4d5d6d7", Format::SimpleScrypt),
+    ];
+    for &(salt, format) in salts.iter() {
+        let hashed = format!("2048$16$3${}${}", salt, hash);
+        assert_eq!(Format::detect(&hashed), Some(format), "{}", hashed);
+    }
+}
+
+#[test]
+fn test_go_malformed() {
+    let rest = "$c8c9cacbcccdcecf$56c8c406d3597dc3d3451435509a4664";
+    let invalid_params = [
+        "2048$16$3$1", "2047$16$3", "1$16$3", "02048$16$3", "2048$016$3",
+        "2048$0$3", "2048$16$0", "2048$16$4294967296", "18446744073709551616$16$3",
+    ];
+    for params in invalid_params.iter() {
+        let hashed = format!("{}{}", params, rest);
+        assert_eq!(scrypt_check("Ünïcødé", &hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+
+    let salt = "2048$16$3$c8c9cacbcccdcecf";
+    let hash = "56c8c406d3597dc3d3451435509a4664";
+    let invalid = [
+        salt.to_string(),
+        format!("{}$", salt),
+        format!("{}${}$", salt, hash),
+        // below the 16 bytes simple-scrypt accepts
+        format!("{}${}", salt, &hash[..30]),
+        format!("{}${}0", salt, hash),
+        format!("{}${}g", salt, &hash[..31]),
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(Format::detect(hashed), Some(Format::SimpleScrypt), "{}", hashed);
+        assert_eq!(scrypt_check("Ünïcødé", hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+
+    // a salt below 8 bytes is not a simple-scrypt string
+    let short = "2048$16$3$c8c9cacbcccdce$56c8c406d3597dc3d3451435509a4664";
+    assert_eq!(Format::SimpleScrypt.parse(short), Err(CheckError::InvalidFormat));
+}
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_go_fixed_salt() {
+    let salt = vec![
+        0xfb, 0xf0, 0x00, 0x3e, 0x12, 0x77, 0x80, 0xaa, 0x5f, 1, 2, 3, 4, 5, 6, 7,
+    ];
+    let params = Params::new(12, 8, 1).unwrap();
+    let config = SimpleConfig::builder().params(params).format(Format::SimpleScrypt).build()
+        .unwrap();
+    let hashed = config.hash_with_rng("correct horse", &mut FixedRng(salt)).unwrap();
+    assert_eq!(hashed, FIXTURES[4].1);
+}
+
+#[test]
+fn test_go_round_trip() {
+    let params = Params::new(4, 2, 3).unwrap();
+    let hashed = scrypt_simple_go("password", &params).unwrap();
+    let fields: Vec<&str> = hashed.split('$').collect();
+    assert_eq!(fields.len(), 5);
+    assert_eq!(&fields[..3], &["16", "2", "3"]);
+    assert_eq!(fields[3].len(), 32);
+    assert_eq!(fields[4].len(), 64);
+    assert_eq!(Format::detect(&hashed), Some(Format::SimpleScrypt));
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+}
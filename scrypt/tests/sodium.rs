@@ -0,0 +1,147 @@
+#![cfg(feature="simple-verify")]
+extern crate scrypt;
+
+use scrypt::{scrypt_check, Params};
+use scrypt::errors::CheckError;
+use scrypt::sodium::{hash_with_salt, pickparams, verify, OPSLIMIT_INTERACTIVE,
+    MEMLIMIT_INTERACTIVE, OPSLIMIT_SENSITIVE, MEMLIMIT_SENSITIVE, OPSLIMIT_MIN, OPSLIMIT_MAX,
+    MEMLIMIT_MIN};
+
+fn from_hex(s: &str) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (i, b) in out.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+    }
+    out
+}
+
+// opslimit, memlimit, log_n, r, p as chosen by libsodium
+const PICKPARAMS: &[(u64, usize, u8, u32, u32)] = &[
+    (0, 0, 1, 8, 512),
+    (32768, 1 << 21, 10, 8, 1),
+    (OPSLIMIT_INTERACTIVE, MEMLIMIT_INTERACTIVE, 14, 8, 1),
+    (1_000_000, 16384, 4, 8, 1953),
+    (OPSLIMIT_SENSITIVE, MEMLIMIT_INTERACTIVE, 14, 8, 64),
+    (OPSLIMIT_SENSITIVE, MEMLIMIT_SENSITIVE, 20, 8, 1),
+    (OPSLIMIT_MIN, MEMLIMIT_MIN, 10, 8, 1),
+    (OPSLIMIT_MAX, MEMLIMIT_MIN, 14, 8, 8191),
+    (OPSLIMIT_MAX, 1 << 30, 20, 8, 127),
+    (300_000, 1 << 24, 13, 8, 1),
+];
+
+#[test]
+fn test_pickparams() {
+    for &(opslimit, memlimit, log_n, r, p) in PICKPARAMS {
+        let params = pickparams(opslimit, memlimit).unwrap();
+        assert_eq!((params.log_n(), params.r(), params.p()), (log_n, r, p),
+            "{} {}", opslimit, memlimit);
+        assert_eq!(Params::from_sodium_limits(opslimit, memlimit), Ok(params));
+    }
+}
+
+#[test]
+fn test_sodium_limits() {
+    for &(opslimit, memlimit, _, _, _) in PICKPARAMS {
+        let params = Params::from_sodium_limits(opslimit, memlimit).unwrap();
+        let (ops, mem) = params.sodium_limits().unwrap();
+        assert_eq!(Params::from_sodium_limits(ops, mem), Ok(params));
+    }
+    assert_eq!(Params::new(14, 8, 1).unwrap().sodium_limits(),
+        Some((OPSLIMIT_INTERACTIVE, MEMLIMIT_INTERACTIVE)));
+    assert_eq!(Params::new(20, 8, 1).unwrap().sodium_limits(),
+        Some((OPSLIMIT_SENSITIVE, MEMLIMIT_SENSITIVE)));
+
+    // libsodium always uses `r = 8` and at least `N * p = 1024`
+    assert_eq!(Params::new(14, 4, 1).unwrap().sodium_limits(), None);
+    assert_eq!(Params::new(4, 8, 1).unwrap().sodium_limits(), None);
+}
+
+// Generated with libsodium's `crypto_pwhash_scryptsalsa208sha256_str` for the
+// password "x", the salt is the decoded salt of the string.
+const FIXTURES: &[(u64, usize, &str, &str)] = &[
+    (
+        0, 0,
+        "83d42b67de1537e230fc8fddc1c48ea048a5794376985a72b08418a64276a945",
+        "$7$/6.....6...1Gx8btR3r6CAwzMr/HgXUWIdtBYRMeZQkG64a8YRdK2$\
+         9LY6BfezUg3UZmmnRcV.D5wNJFt5lYE6.VkDccptX8D",
+    ),
+    (
+        32768, 1 << 21,
+        "c9c7c2497dd93989d9ee677f5d62ab3a1351b6c42926becbf5fd320ff79eea56",
+        "$7$86..../....7Tgk7pLqtYMqiTqTR7qeuAFIqGQ8asvmprjADQjbeP3$\
+         gV0bdK7PIZNktm1H7HTq5SKNW5VyDgoBbmq4O6CExG3",
+    ),
+    (
+        OPSLIMIT_INTERACTIVE, MEMLIMIT_INTERACTIVE,
+        "e0d135e0273312fa69895265eb997f043e09fd39c58079bbf5b1609d427ed485",
+        "$7$C6..../....U5RBUTmAGcTO78JNfbtT2sH0xbHl.arip59MR8YTIL6$\
+         AFtQkWxRzXVAs59Njk6mPq.XcrFOqNVvpQrWI0H0bW/",
+    ),
+    (
+        1_000_000, 16384,
+        "4a66cb5f3bf10143879ad1c112017d7f6307f52e85009837428f39e79d55874f",
+        "$7$26....VS...8NqmThHw/AoVO4RkG2ETzBq/pvGV.UtB0xMCbrNJ5y2$\
+         r5NEGfXCyFSWL4fRsPhRxbqtTww7iSO5C9y2CLhnJgB",
+    ),
+];
+
+#[test]
+fn test_sodium_fixtures() {
+    for &(opslimit, memlimit, salt, hashed) in FIXTURES {
+        let params = pickparams(opslimit, memlimit).unwrap();
+        assert_eq!(hash_with_salt(b"x", &params, &from_hex(salt)), hashed);
+        assert_eq!(verify(b"x", hashed), Ok(()));
+        assert_eq!(verify(b"y", hashed), Err(CheckError::HashMismatch));
+        assert_eq!(scrypt_check("x", hashed), Ok(()));
+    }
+}
+
+#[test]
+fn test_sodium_verify() {
+    // also generated with libsodium
+    let hashed = "$7$26....VS...KUD.aaPdmcpgnzTrPcWkdPMo9JHSt4F9Th1LHlqiBmD$\
+                  XAkwrCTWp2WlOAw4gEzA2HG5nrwXuSRS7FEAKTChFiD";
+    assert_eq!(verify(b"pass\xffword", hashed), Ok(()));
+    assert_eq!(verify(b"password", hashed), Err(CheckError::HashMismatch));
+
+    // accepted by libsodium, but never produced by it
+    let crafted = [
+        "$7$86..../....TKVynXtY1T9xr$KbL3qPa5TaAnQMGWt8trlzZNHWRX7$\
+         fE/U3/2hXikFYwC0itAv4SSUY1Vk.4DRATrFO8tRbE8",
+        "$7$86..../....TKVynXtY1T9xr/KbL3qPa5TaAnQMGWt8trlzZNHW-X7$\
+         ojLOYuPoXqVjDhaaC6m7ji.GPU6PSIBcOQk5ZXCtuu1",
+    ];
+    for hashed in crafted.iter() {
+        assert_eq!(verify(b"password", hashed), Ok(()), "{}", hashed);
+        assert_eq!(scrypt_check("password", hashed),
+            Err(CheckError::InvalidFormat), "{}", hashed);
+    }
+}
+
+#[test]
+fn test_sodium_malformed() {
+    let invalid = [
+        // libxcrypt style strings with a shorter salt
+        "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+        // truncated and trailing data
+        "$7$86..../....TKVynXtY1T9xr/KbL3qPa5TaAnQMGWt8trlzZNHWRX7$\
+         A1/VrDO/r1WtnHcTgKRMpfdmNI/NZ5F2EqfYkMwJis",
+        "$7$86..../....TKVynXtY1T9xr/KbL3qPa5TaAnQMGWt8trlzZNHWRX7$\
+         A1/VrDO/r1WtnHcTgKRMpfdmNI/NZ5F2EqfYkMwJisB$",
+        // wrong prefix, invalid parameters and hash characters
+        "$8$86..../....TKVynXtY1T9xr/KbL3qPa5TaAnQMGWt8trlzZNHWRX7$\
+         A1/VrDO/r1WtnHcTgKRMpfdmNI/NZ5F2EqfYkMwJisB",
+        "$7$.6..../....TKVynXtY1T9xr/KbL3qPa5TaAnQMGWt8trlzZNHWRX7$\
+         A1/VrDO/r1WtnHcTgKRMpfdmNI/NZ5F2EqfYkMwJisB",
+        "$7$86..$./....TKVynXtY1T9xr/KbL3qPa5TaAnQMGWt8trlzZNHWRX7$\
+         A1/VrDO/r1WtnHcTgKRMpfdmNI/NZ5F2EqfYkMwJisB",
+        "$7$86..../....TKVynXtY1T9xr/KbL3qPa5TaAnQMGWt8trlzZNHWRX7$\
+         A1/VrDO/r1WtnHcTgKRMpfdmNI/NZ5F2EqfYkMwJis-",
+        "$7$86..../....TKVynXtY1T9xr/KbL3qPa5TaAnQMGWt8trlzZNHWRX7$\
+         A1/VrDO/r1WtnHcTgKRMpfdmNI/NZ5F2EqfYkMwJisz",
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(verify(b"password", hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+}
@@ -0,0 +1,136 @@
+extern crate scrypt;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use scrypt::test_vectors::{Operation, SCRYPT};
+use scrypt::{scrypt, Progress, Params, ScryptStepper, StepResult};
+
+thread_local! {
+    // the size of the buffers to watch, and how many of them were freed and
+    // how many of those were all zeros
+    static WATCHED_LEN: Cell<usize> = const { Cell::new(0) };
+    static FREED: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+}
+
+/// Records whether the buffers of `WATCHED_LEN` bytes freed by the thread
+/// were all zeros.
+struct Watcher;
+
+unsafe impl GlobalAlloc for Watcher {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if WATCHED_LEN.with(Cell::get) == layout.size() {
+            let bytes = std::slice::from_raw_parts(ptr, layout.size());
+            let zeroed = bytes.iter().all(|&b| b == 0) as usize;
+            FREED.with(|freed| {
+                let (count, zeros) = freed.get();
+                freed.set((count + 1, zeros + zeroed));
+            });
+        }
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Watcher = Watcher;
+
+#[test]
+fn test_stepper_rfc_vectors() {
+    for vector in &SCRYPT[..2] {
+        let (password, salt, log_n, r, p) = match vector.operation {
+            Operation::Scrypt { password, salt, log_n, r, p } => (password, salt, log_n, r, p),
+            _ => unreachable!(),
+        };
+        let params = Params::new(log_n, r, p).unwrap();
+        let mut stepper = ScryptStepper::new(password, salt, &params);
+        assert_eq!(stepper.params(), params);
+        while stepper.step(5) != StepResult::Done {}
+        let mut output = vec![0u8; vector.expected.len()];
+        stepper.finish(&mut output).unwrap();
+        assert_eq!(output, vector.expected, "{}", vector.name);
+    }
+}
+
+#[test]
+fn test_stepper_chunk_sizes() {
+    let params = Params::new(4, 3, 2).unwrap();
+    let mut expected = [0u8; 40];
+    scrypt(b"password", b"salt", &params, &mut expected).unwrap();
+    // 64 iterations in all, 32 for each block
+    for &chunk in [1, 2, 7, 16, 31, 32, 33, 64, 1000, usize::MAX].iter() {
+        let mut stepper = ScryptStepper::new(b"password", b"salt", &params);
+        let mut done = 0;
+        let mut steps = 0;
+        loop {
+            steps += 1;
+            match stepper.step(chunk) {
+                StepResult::Pending(progress) => {
+                    let position = progress.block as usize * 32 + progress.iteration as usize;
+                    assert_eq!(position, done + chunk, "chunks of {}", chunk);
+                    done = position;
+                }
+                StepResult::Done => break,
+            }
+        }
+        assert_eq!(steps, 64usize.div_ceil(chunk), "chunks of {}", chunk);
+        let mut output = [0u8; 40];
+        stepper.finish(&mut output).unwrap();
+        assert_eq!(output, expected, "chunks of {}", chunk);
+    }
+}
+
+#[test]
+fn test_stepper_progress() {
+    let params = Params::new(3, 1, 3).unwrap();
+    let mut stepper = ScryptStepper::new(b"password", b"salt", &params);
+    let start = Progress { block: 0, blocks: 3, iteration: 0, iterations: 16 };
+    assert_eq!(stepper.progress(), Some(start));
+    assert_eq!(stepper.step(0), StepResult::Pending(start));
+    assert_eq!(start.fraction(), 0.0);
+
+    let progress = Progress { block: 1, blocks: 3, iteration: 8, iterations: 16 };
+    assert_eq!(stepper.step(24), StepResult::Pending(progress));
+    assert_eq!(progress.fraction(), 0.5);
+    assert_eq!(stepper.step(24), StepResult::Done);
+    assert_eq!(stepper.progress(), None);
+    assert_eq!(stepper.step(1), StepResult::Done);
+    assert!(!format!("{:?}", stepper).contains("password"));
+}
+
+// finish() runs the iterations which are left
+#[test]
+fn test_stepper_finish_early() {
+    let params = Params::new(5, 2, 2).unwrap();
+    let mut expected = [0u8; 64];
+    scrypt(b"pleaseletmein", b"SodiumChloride", &params, &mut expected).unwrap();
+    for &steps in [0, 1, 70].iter() {
+        let mut stepper = ScryptStepper::new(b"pleaseletmein", b"SodiumChloride", &params);
+        stepper.step(steps);
+        let mut output = [0u8; 64];
+        stepper.finish(&mut output).unwrap();
+        assert_eq!(&output[..], &expected[..], "after {} iterations", steps);
+        // and finishing again derives the same key
+        stepper.finish(&mut output).unwrap();
+        assert_eq!(&output[..], &expected[..]);
+    }
+
+    let mut stepper = ScryptStepper::new(b"password", b"salt", &params);
+    assert!(stepper.finish(&mut []).is_err());
+    assert!(stepper.progress().is_some());
+}
+
+#[test]
+fn test_stepper_zeroize_on_drop() {
+    // `V` of N = 2^4 and r = 5, a size no other buffer of the test has
+    let params = Params::new(4, 5, 1).unwrap();
+    let mut stepper = ScryptStepper::new(b"password", b"salt", &params);
+    stepper.step(20);
+    WATCHED_LEN.with(|len| len.set(128 * 5 * 16));
+    drop(stepper);
+    WATCHED_LEN.with(|len| len.set(0));
+    assert_eq!(FREED.with(Cell::get), (1, 1));
+}
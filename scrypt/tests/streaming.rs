@@ -0,0 +1,41 @@
+//! ROMix with and without non-temporal stores for `V`.
+extern crate scrypt;
+
+use scrypt::test_vectors::{ro_mix_stores, Operation, RO_MIX};
+use scrypt::{scrypt, Params};
+
+#[test]
+fn test_streaming_rfc_romix() {
+    let input = match RO_MIX.operation {
+        Operation::RoMix { input, .. } => input,
+        _ => unreachable!(),
+    };
+    assert_eq!(ro_mix_stores(input, 4, true), RO_MIX.expected);
+    assert_eq!(ro_mix_stores(input, 4, false), RO_MIX.expected);
+}
+
+#[test]
+fn test_streaming_identical() {
+    for &(log_n, r) in [(1, 1), (4, 2), (10, 8), (12, 3), (14, 16)].iter() {
+        let input: Vec<u8> = (0..128 * r).map(|i| (i * 7 + log_n as usize) as u8).collect();
+        assert_eq!(ro_mix_stores(&input, log_n, true), ro_mix_stores(&input, log_n, false),
+            "log_n {}, r {}", log_n, r);
+    }
+}
+
+// a V of 128 MiB, filled with non-temporal stores by `scrypt`
+#[test]
+fn test_streaming_scrypt() {
+    let params = Params::new(17, 8, 1).unwrap();
+    let mut output = [0u8; 64];
+    scrypt(b"password", b"NaCl", &params, &mut output).unwrap();
+    // python3 -c "import hashlib; print(hashlib.scrypt(b'password', salt=b'NaCl',
+    //     n=1<<17, r=8, p=1, maxmem=1<<28, dklen=64).hex())"
+    assert_eq!(hex(&output), EXPECTED_17_8_1);
+}
+
+const EXPECTED_17_8_1: &str = "c2071233af7bbe61585462b1f1e38324299497e9114e275b595b925b3832326767a293326f06d72af0c1475a9064dc3542cdfead8a898e78713a5e7d70005a66";
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
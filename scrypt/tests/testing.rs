@@ -0,0 +1,47 @@
+#![cfg(feature="test-util")]
+#[macro_use]
+extern crate scrypt;
+
+use std::panic;
+
+use scrypt::errors::CheckError;
+use scrypt::testing::{compare_stored, fixed_hash, FAST_PARAMS};
+use scrypt::{scrypt_simple, Params, ScryptHash};
+
+#[test]
+fn test_fixed_hash() {
+    // Generated with Python's `hashlib.scrypt`.
+    assert_eq!(fixed_hash("password"),
+        "$rscrypt$0$AQEB$c2NyeXB0IHRlc3Qgc2FsdA==$YAlFQgEbOX4CNgOXCt7BVALPBMgkbRtzM46ZOfsIvb8=$");
+    assert_eq!(fixed_hash("password"), fixed_hash("password"));
+    assert!(fixed_hash("password") != fixed_hash("Password"));
+    assert_eq!(ScryptHash::parse(&fixed_hash("")).unwrap().params(), FAST_PARAMS);
+    assert_eq!(FAST_PARAMS, Params::new(1, 1, 1).unwrap());
+}
+
+#[test]
+fn test_macros() {
+    let stored = fixed_hash("hunter2");
+    assert_verifies!("hunter2", &stored);
+    assert_rejects!("hunter3", &stored);
+    assert_rejects!("hunter2", "$rscrypt$", CheckError::InvalidFormat);
+    // any string of `scrypt_check` works
+    assert_verifies!("hunter2", &scrypt_simple("hunter2", &FAST_PARAMS).unwrap());
+
+    let failed = |f: fn()| panic::catch_unwind(f).is_err();
+    assert!(failed(|| assert_verifies!("hunter3", &fixed_hash("hunter2"))));
+    assert!(failed(|| assert_rejects!("hunter2", &fixed_hash("hunter2"))));
+    assert!(failed(|| assert_rejects!("hunter3", &fixed_hash("hunter2"),
+        CheckError::InvalidFormat)));
+}
+
+#[test]
+fn test_compare_stored() {
+    let stored = fixed_hash("password");
+    let hash = ScryptHash::parse(&stored).unwrap().hash_bytes().to_vec();
+    assert_eq!(compare_stored(&stored, &hash), Ok(()));
+    let mut different = hash.clone();
+    different[31] ^= 1;
+    assert_eq!(compare_stored(&stored, &different), Err(CheckError::HashMismatch));
+    assert_eq!(compare_stored("$rscrypt$", &hash), Err(CheckError::InvalidFormat));
+}
@@ -0,0 +1,122 @@
+//! A statistical test that verification does not leak where the computed
+//! hash differs from the stored one, after dudect ("Dude, is my code
+//! constant time?", Reparaz, Balasch and Verbauwhede, 2017).
+//!
+//! Timings are collected for two classes of inputs, hashes which are equal
+//! and hashes whose first byte differs, which an early-exit comparison
+//! would tell apart fastest. The class of every measurement is drawn at
+//! random, so that drifts of the machine affect both classes alike. Each
+//! measurement times a batch of calls, which lifts it well above the
+//! resolution of the clock, and measurements above the 90th percentile,
+//! e.g. of preemptions, are dropped. Welch's t-test then compares the
+//! means of the classes: |t| above 4.5, the threshold of dudect, makes the
+//! test fail.
+//!
+//! The comparison is measured on its own, through
+//! `testing::compare_stored()` which decodes and compares like
+//! `scrypt_check` but takes the computed hash, and within `scrypt_check`
+//! with `N = 2`, where scrypt costs the same for both classes. Timings are
+//! only meaningful for optimized code on a quiet machine, so the tests are
+//! ignored by default. Run them with
+//!
+//! ```text
+//! cargo test --release --features test-util --test timing -- --ignored --test-threads 1
+//! ```
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use std::time::Instant;
+
+use rand::{Rng, SeedableRng};
+use rand::prng::XorShiftRng;
+
+#[cfg(feature="test-util")]
+use scrypt::errors::CheckError;
+#[cfg(feature="test-util")]
+use scrypt::testing::compare_stored;
+use scrypt::{scrypt_check, Format, HashFormat, HashParts, Params};
+
+/// |t| above which the classes are distinguishable.
+const THRESHOLD: f64 = 4.5;
+
+/// Welch's t statistic of the means of `a` and `b`.
+fn welch_t(a: &[f64], b: &[f64]) -> f64 {
+    let stats = |xs: &[f64]| {
+        let n = xs.len() as f64;
+        let mean = xs.iter().sum::<f64>() / n;
+        let var = xs.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0);
+        (n, mean, var)
+    };
+    let ((na, ma, va), (nb, mb, vb)) = (stats(a), stats(b));
+    (ma - mb) / (va / na + vb / nb).sqrt()
+}
+
+/// Time `measurements` batches of `batch` calls of `f`, with the class
+/// passed to `f` drawn at random for every batch, and return the t
+/// statistic of the cropped timings.
+fn measure<F: FnMut(bool)>(measurements: usize, batch: usize, mut f: F) -> f64 {
+    let mut rng = XorShiftRng::from_seed([7; 16]);
+    let mut timings = Vec::with_capacity(measurements);
+    for _ in 0..measurements {
+        let class = rng.gen::<bool>();
+        let start = Instant::now();
+        for _ in 0..batch { f(class); }
+        timings.push((class, start.elapsed().as_nanos() as f64));
+    }
+
+    let mut sorted: Vec<f64> = timings.iter().map(|&(_, t)| t).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let crop = sorted[sorted.len() * 9 / 10];
+    let class = |c: bool| -> Vec<f64> {
+        timings.iter().filter(|&&(k, t)| k == c && t <= crop).map(|&(_, t)| t).collect()
+    };
+    welch_t(&class(true), &class(false))
+}
+
+fn parts() -> HashParts {
+    HashParts::new(b"password", &Params::new(1, 1, 1).unwrap(), b"NaCl", 32).unwrap()
+}
+
+#[test]
+fn test_welch_t() {
+    let a = [1.0, 2.0, 3.0, 4.0];
+    assert_eq!(welch_t(&a, &a), 0.0);
+    // means of 2.5 and 4.5 with variances of 5/3
+    let b = [3.0, 4.0, 5.0, 6.0];
+    assert!((welch_t(&a, &b) + 2.0 / (5.0f64 / 6.0).sqrt()).abs() < 1e-12);
+}
+
+#[cfg(feature="test-util")]
+#[test]
+#[ignore]
+fn test_compare_constant_time() {
+    let parts = parts();
+    let stored = Format::Rscrypt.encode(&parts);
+    let equal = parts.hash.clone();
+    let mut different = parts.hash.clone();
+    different[0] ^= 1;
+
+    let t = measure(20_000, 32, |class| {
+        let output = if class { &equal } else { &different };
+        let expected = if class { Ok(()) } else { Err(CheckError::HashMismatch) };
+        assert_eq!(compare_stored(&stored, output), expected);
+    });
+    assert!(t.abs() < THRESHOLD, "t = {}", t);
+}
+
+#[test]
+#[ignore]
+fn test_scrypt_check_constant_time() {
+    let parts = parts();
+    let matching = Format::Rscrypt.encode(&parts);
+    let mut hash = parts.hash.clone();
+    hash[0] ^= 1;
+    let mismatching = Format::Rscrypt.encode(&HashParts { hash, ..parts });
+
+    let t = measure(10_000, 8, |class| {
+        let stored = if class { &matching } else { &mismatching };
+        assert_eq!(scrypt_check("password", stored).is_ok(), class);
+    });
+    assert!(t.abs() < THRESHOLD, "t = {}", t);
+}
@@ -0,0 +1,90 @@
+extern crate scrypt;
+
+use scrypt::{scrypt, scrypt_vectored, Params};
+
+/// Ways to split `bytes` into parts, with empty parts: into two parts at
+/// every position, or around the SHA-256 blocks for long `bytes`, and into
+/// three parts for short `bytes`.
+fn splits(bytes: &[u8]) -> Vec<Vec<&[u8]>> {
+    let mut splits = vec![vec![], vec![bytes], vec![&bytes[..0], bytes, &bytes[bytes.len()..]]];
+    let positions: Vec<usize> = if bytes.len() <= 16 {
+        (0..=bytes.len()).collect()
+    } else {
+        [0, 1, 31, 32, 33, 55, 56, 63, 64, 65, bytes.len() - 1, bytes.len()].iter()
+            .cloned().filter(|&i| i <= bytes.len()).collect()
+    };
+    for &i in positions.iter() {
+        splits.push(vec![&bytes[..i], &bytes[i..]]);
+        if bytes.len() > 8 { continue; }
+        for j in i..=bytes.len() {
+            splits.push(vec![&bytes[..i], &bytes[i..j], &bytes[j..]]);
+        }
+    }
+    // single bytes
+    splits.push(bytes.chunks(1).collect());
+    splits.retain(|parts| parts.concat() == bytes);
+    splits
+}
+
+/// Compare every split of `password` and `salt`, of both at once for short
+/// inputs and of one while the other is whole for long ones.
+fn check(password: &[u8], salt: &[u8], params: &Params, len: usize) {
+    let mut expected = vec![0u8; len];
+    scrypt(password, salt, params, &mut expected).unwrap();
+    let mut cases = Vec::new();
+    if password.len() + salt.len() <= 8 {
+        for password_parts in splits(password) {
+            for salt_parts in splits(salt) {
+                cases.push((password_parts.clone(), salt_parts));
+            }
+        }
+    } else {
+        cases.extend(splits(password).into_iter().map(|parts| (parts, vec![salt])));
+        cases.extend(splits(salt).into_iter().map(|parts| (vec![password], parts)));
+    }
+    for (password_parts, salt_parts) in cases {
+        let mut output = vec![0u8; len];
+        scrypt_vectored(&password_parts, &salt_parts, params, &mut output).unwrap();
+        assert_eq!(output, expected, "{:?} {:?}", password_parts, salt_parts);
+    }
+}
+
+#[test]
+fn test_vectored_equals_concatenation() {
+    let params = Params::new(1, 1, 1).unwrap();
+    check(b"pass", b"NaCl", &params, 32);
+    check(b"password", b"NaCl", &params, 32);
+    check(b"", b"", &params, 1);
+    check(b"p", b"s", &params, 33);
+}
+
+// passwords around the SHA-256 block of the HMAC key, salts around the
+// block of the first PBKDF2
+#[test]
+fn test_vectored_block_boundaries() {
+    let params = Params::new(1, 1, 2).unwrap();
+    let bytes: Vec<u8> = (0..130).collect();
+    for &len in [63, 64, 65, 128].iter() {
+        check(&bytes[..len], b"salt", &params, 64);
+    }
+    for &len in [51, 55, 56, 64, 119, 120].iter() {
+        check(b"password", &bytes[..len], &params, 64);
+    }
+}
+
+#[test]
+fn test_vectored_rfc_vector() {
+    let params = Params::new(10, 8, 16).unwrap();
+    let mut expected = [0u8; 64];
+    scrypt(b"password", b"NaCl", &params, &mut expected).unwrap();
+    let mut output = [0u8; 64];
+    scrypt_vectored(&[b"pass", b"word"], &[b"Na", b"", b"Cl"], &params, &mut output).unwrap();
+    assert_eq!(&output[..], &expected[..]);
+}
+
+#[test]
+fn test_vectored_invalid_output_len() {
+    let params = Params::new(1, 1, 1).unwrap();
+    let err = scrypt_vectored(&[b"password"], &[b"salt"], &params, &mut []).unwrap_err();
+    assert_eq!(err.provided_len(), 0);
+}
@@ -0,0 +1,109 @@
+#!/usr/bin/env python3
+"""Regenerate the scrypt test vectors of `scrypt.json`.
+
+Run from this directory:
+
+    python3 generate.py
+
+The cases follow the layout of Wycheproof test vectors. Valid cases are
+computed with Python's `hashlib.scrypt`, invalid ones carry the error the
+Rust API reports. `n` is the scrypt parameter N itself, not its log2.
+"""
+import hashlib
+import json
+
+# comment, password, salt, n, r, p, dkLen
+VALID = [
+    ("RFC 7914 vector 1", b"", b"", 16, 1, 1, 64),
+    ("RFC 7914 vector 2", b"password", b"NaCl", 1024, 8, 16, 64),
+    ("RFC 7914 vector 3", b"pleaseletmein", b"SodiumChloride", 16384, 8, 1, 64),
+    ("smallest N", b"password", b"salt", 2, 1, 1, 32),
+    ("N = 4", b"password", b"salt", 4, 1, 1, 32),
+    ("N = 8", b"password", b"salt", 8, 1, 1, 32),
+    ("N = 2^12", b"password", b"salt", 4096, 1, 1, 32),
+    ("largest N of r = 1", b"password", b"salt", 32768, 1, 1, 32),
+    ("r = 2", b"password", b"salt", 16, 2, 1, 32),
+    ("odd r", b"password", b"salt", 16, 3, 1, 32),
+    ("odd r", b"password", b"salt", 16, 5, 1, 32),
+    ("odd r", b"password", b"salt", 32, 7, 1, 32),
+    ("odd r", b"password", b"salt", 16, 9, 2, 32),
+    ("odd r", b"password", b"salt", 16, 17, 1, 32),
+    ("large r", b"password", b"salt", 4, 64, 1, 32),
+    ("r = 255", b"password", b"salt", 2, 255, 1, 32),
+    ("r = 256", b"password", b"salt", 2, 256, 1, 32),
+    ("p = 2", b"password", b"salt", 16, 1, 2, 32),
+    ("odd p", b"password", b"salt", 16, 2, 3, 32),
+    ("large p", b"password", b"salt", 4, 1, 64, 32),
+    ("p = 255", b"password", b"salt", 2, 1, 255, 32),
+    ("p = 256", b"password", b"salt", 2, 1, 256, 32),
+    ("large p and r", b"password", b"salt", 2, 16, 100, 32),
+    ("empty password", b"", b"salt", 16, 1, 1, 32),
+    ("empty salt", b"password", b"", 16, 1, 1, 32),
+    ("empty password and salt", b"", b"", 2, 1, 1, 1),
+    ("password of a SHA-256 block", b"p" * 64, b"salt", 16, 1, 1, 32),
+    ("password longer than a SHA-256 block", b"p" * 65, b"salt", 16, 1, 1, 32),
+    ("long password", b"correct horse battery staple " * 20, b"salt", 16, 2, 1, 32),
+    ("long salt", b"password", bytes(range(256)), 16, 1, 1, 32),
+    ("long salt", b"password", b"s" * 1000, 16, 4, 2, 32),
+    ("trailing NUL in the password", b"password\x00", b"salt", 16, 1, 1, 32),
+    ("NUL bytes", b"\x00\x00", b"\x00", 16, 1, 1, 32),
+    ("binary password", bytes(range(255, -1, -1)), b"salt", 16, 1, 1, 32),
+    ("UTF-8 password", "pässwörd".encode(), b"salt", 16, 1, 1, 32),
+    ("dkLen = 1", b"password", b"salt", 16, 1, 1, 1),
+    ("dkLen = 31", b"password", b"salt", 16, 1, 1, 31),
+    ("dkLen = 33", b"password", b"salt", 16, 1, 1, 33),
+    ("dkLen of two PBKDF2 blocks", b"password", b"salt", 16, 1, 1, 64),
+    ("dkLen = 65", b"password", b"salt", 16, 1, 1, 65),
+    ("long dkLen", b"password", b"salt", 16, 2, 2, 257),
+    ("long dkLen", b"password", b"salt", 4, 1, 1, 1024),
+]
+
+# comment, n, r, p, dkLen, error
+INVALID = [
+    ("r = 0", 16, 0, 1, 32, "InvalidParams"),
+    ("p = 0", 16, 1, 0, 32, "InvalidParams"),
+    ("N = 0", 0, 1, 1, 32, "InvalidParams"),
+    ("N = 1", 1, 1, 1, 32, "InvalidParams"),
+    ("N is not a power of 2", 3, 1, 1, 32, "InvalidParams"),
+    ("N is not a power of 2", 1000, 8, 1, 32, "InvalidParams"),
+    ("N >= 2^(16 r)", 65536, 1, 1, 32, "InvalidParams"),
+    ("N >= 2^(16 r)", 1 << 32, 2, 1, 32, "InvalidParams"),
+    ("r p >= 2^30", 2, 1 << 15, 1 << 15, 32, "InvalidParams"),
+    ("r p >= 2^30", 2, 1, 1 << 30, 32, "InvalidParams"),
+    ("128 r N overflows", 1 << 60, 8, 1, 32, "InvalidParams"),
+    ("dkLen = 0", 16, 1, 1, 0, "InvalidOutputLen"),
+]
+
+
+def main():
+    tests = []
+    for comment, password, salt, n, r, p, dk_len in VALID:
+        key = hashlib.scrypt(password, salt=salt, n=n, r=r, p=p,
+                             maxmem=256 << 20, dklen=dk_len)
+        tests.append({
+            "tcId": len(tests) + 1, "comment": comment,
+            "password_hex": password.hex(), "salt_hex": salt.hex(),
+            "n": n, "r": r, "p": p, "dkLen": dk_len,
+            "expected_hex": key.hex(), "result": "valid",
+        })
+    for comment, n, r, p, dk_len, error in INVALID:
+        tests.append({
+            "tcId": len(tests) + 1, "comment": comment,
+            "password_hex": b"password".hex(), "salt_hex": b"salt".hex(),
+            "n": n, "r": r, "p": p, "dkLen": dk_len,
+            "expected_hex": "", "result": "invalid", "error": error,
+        })
+    vectors = {
+        "algorithm": "scrypt",
+        "generatorVersion": "1",
+        "numberOfTests": len(tests),
+        "notes": "n is N, not log2(N); invalid cases name the error of the Rust API",
+        "tests": tests,
+    }
+    with open("scrypt.json", "w") as f:
+        json.dump(vectors, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,257 @@
+//! The JSON test vectors of `scrypt.json`, see `generate.py`.
+extern crate scrypt;
+
+use std::collections::BTreeMap;
+
+use scrypt::{scrypt, Params};
+use scrypt::test_vectors::{Operation, SCRYPT};
+
+const VECTORS: &str = include_str!("scrypt.json");
+
+/// The values of the vector files, parsed by `Parser`.
+#[derive(Debug)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(BTreeMap<String, Json>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> &Json {
+        match *self {
+            Json::Object(ref members) => members.get(key)
+                .unwrap_or_else(|| panic!("missing member `{}`", key)),
+            _ => panic!("not an object: {:?}", self),
+        }
+    }
+
+    fn str(&self) -> &str {
+        match *self {
+            Json::String(ref s) => s,
+            _ => panic!("not a string: {:?}", self),
+        }
+    }
+
+    fn bool(&self) -> bool {
+        match *self {
+            Json::Bool(b) => b,
+            _ => panic!("not a boolean: {:?}", self),
+        }
+    }
+
+    fn u64(&self) -> u64 {
+        match *self {
+            Json::Number(n) => n,
+            _ => panic!("not a number: {:?}", self),
+        }
+    }
+
+    fn array(&self) -> &[Json] {
+        match *self {
+            Json::Array(ref items) => items,
+            _ => panic!("not an array: {:?}", self),
+        }
+    }
+}
+
+/// A JSON parser for the vector files, which have no fractions, negative
+/// numbers or escapes other than the basic ones.
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(input: &'a str) -> Json {
+        let mut parser = Parser { input: input.as_bytes(), pos: 0 };
+        let value = parser.value();
+        parser.whitespace();
+        assert_eq!(parser.pos, parser.input.len(), "trailing characters");
+        value
+    }
+
+    fn whitespace(&mut self) {
+        while self.pos < self.input.len() && b" \t\r\n".contains(&self.input[self.pos]) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> u8 {
+        self.whitespace();
+        *self.input.get(self.pos).expect("unexpected end of input")
+    }
+
+    fn expect(&mut self, c: u8) {
+        assert_eq!(self.peek() as char, c as char, "at byte {}", self.pos);
+        self.pos += 1;
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Json {
+        assert!(self.input[self.pos..].starts_with(word.as_bytes()), "at byte {}", self.pos);
+        self.pos += word.len();
+        value
+    }
+
+    fn value(&mut self) -> Json {
+        match self.peek() {
+            b'{' => {
+                self.pos += 1;
+                let mut members = BTreeMap::new();
+                if self.peek() == b'}' {
+                    self.pos += 1;
+                    return Json::Object(members);
+                }
+                loop {
+                    let key = self.string();
+                    self.expect(b':');
+                    let value = self.value();
+                    assert!(members.insert(key, value).is_none(), "duplicate member");
+                    if self.peek() == b',' { self.pos += 1; } else { break; }
+                }
+                self.expect(b'}');
+                Json::Object(members)
+            }
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if self.peek() == b']' {
+                    self.pos += 1;
+                    return Json::Array(items);
+                }
+                loop {
+                    items.push(self.value());
+                    if self.peek() == b',' { self.pos += 1; } else { break; }
+                }
+                self.expect(b']');
+                Json::Array(items)
+            }
+            b'"' => Json::String(self.string()),
+            b't' => self.literal("true", Json::Bool(true)),
+            b'f' => self.literal("false", Json::Bool(false)),
+            b'n' => self.literal("null", Json::Null),
+            b'0'..=b'9' => {
+                let start = self.pos;
+                while self.pos < self.input.len() && self.input[self.pos].is_ascii_digit() {
+                    self.pos += 1;
+                }
+                let digits = std::str::from_utf8(&self.input[start..self.pos]).unwrap();
+                Json::Number(digits.parse().expect("number out of range"))
+            }
+            c => panic!("unexpected `{}` at byte {}", c as char, self.pos),
+        }
+    }
+
+    fn string(&mut self) -> String {
+        self.expect(b'"');
+        let mut s = Vec::new();
+        loop {
+            let c = self.input[self.pos];
+            self.pos += 1;
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = self.input[self.pos];
+                    self.pos += 1;
+                    s.push(match escaped {
+                        b'"' | b'\\' | b'/' => escaped,
+                        b'n' => b'\n',
+                        b't' => b'\t',
+                        b'r' => b'\r',
+                        _ => panic!("unsupported escape `\\{}`", escaped as char),
+                    });
+                }
+                _ => s.push(c),
+            }
+        }
+        String::from_utf8(s).expect("invalid UTF-8")
+    }
+}
+
+fn from_hex(s: &str) -> Vec<u8> {
+    assert_eq!(s.len() % 2, 0, "{}", s);
+    (0..s.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// Run a case through `scrypt()`, with the name of the error on failure.
+/// `Params` takes log2(N), so an N which is not a power of 2 is
+/// refused before `Params::new` is called.
+fn run(case: &Json) -> Result<Vec<u8>, &'static str> {
+    let n = case.get("n").u64();
+    if !n.is_power_of_two() { return Err("InvalidParams"); }
+    let r = case.get("r").u64();
+    let p = case.get("p").u64();
+    assert!(r <= u32::MAX as u64 && p <= u32::MAX as u64);
+    let params = Params::new(n.trailing_zeros() as u8, r as u32, p as u32)
+        .map_err(|_| "InvalidParams")?;
+    let mut output = vec![0u8; case.get("dkLen").u64() as usize];
+    scrypt(&from_hex(case.get("password_hex").str()), &from_hex(case.get("salt_hex").str()),
+        &params, &mut output).map_err(|_| "InvalidOutputLen")?;
+    Ok(output)
+}
+
+#[test]
+fn test_vectors() {
+    let vectors = Parser::parse(VECTORS);
+    assert_eq!(vectors.get("algorithm").str(), "scrypt");
+    let tests = vectors.get("tests").array();
+    assert_eq!(tests.len() as u64, vectors.get("numberOfTests").u64());
+    assert!(tests.len() >= 40);
+
+    let mut ids = Vec::new();
+    for case in tests {
+        let id = case.get("tcId").u64();
+        ids.push(id);
+        let comment = case.get("comment").str();
+        match case.get("result").str() {
+            "valid" => {
+                let expected = from_hex(case.get("expected_hex").str());
+                assert_eq!(run(case), Ok(expected), "tcId {}: {}", id, comment);
+            }
+            "invalid" => {
+                let error = case.get("error").str();
+                assert_eq!(run(case), Err(error), "tcId {}: {}", id, comment);
+            }
+            result => panic!("tcId {}: unknown result `{}`", id, result),
+        }
+    }
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), tests.len(), "duplicate tcId");
+}
+
+// the RFC vectors of the file are those of `scrypt::test_vectors`
+#[test]
+fn test_rfc_vectors() {
+    let vectors = Parser::parse(VECTORS);
+    let tests = vectors.get("tests").array();
+    for (i, vector) in SCRYPT[..3].iter().enumerate() {
+        let comment = format!("RFC 7914 vector {}", i + 1);
+        let case = tests.iter().find(|case| case.get("comment").str() == comment)
+            .unwrap_or_else(|| panic!("missing `{}`", comment));
+        match vector.operation {
+            Operation::Scrypt { password, salt, log_n, r, p } => {
+                assert_eq!(from_hex(case.get("password_hex").str()), password);
+                assert_eq!(from_hex(case.get("salt_hex").str()), salt);
+                assert_eq!(case.get("n").u64(), 1 << log_n);
+                assert_eq!((case.get("r").u64(), case.get("p").u64()), (r as u64, p as u64));
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(from_hex(case.get("expected_hex").str()), vector.expected, "{}", comment);
+    }
+}
+
+#[test]
+fn test_parser() {
+    let json = Parser::parse(r#" {"a": [1, true, false, null, {}], "b\"\\/\n": "x", "c": []} "#);
+    assert_eq!(json.get("a").array().len(), 5);
+    assert_eq!(json.get("a").array()[0].u64(), 1);
+    assert!(json.get("a").array()[1].bool() && !json.get("a").array()[2].bool());
+    assert_eq!(json.get("b\"\\/\n").str(), "x");
+    assert!(json.get("c").array().is_empty());
+}
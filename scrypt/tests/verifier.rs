@@ -0,0 +1,204 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use scrypt::{scrypt_simple_phc, scrypt_simple_url_safe, Format, ParamsPolicy, Params,
+    Verifier};
+use scrypt::errors::{CheckError, Rule};
+
+// Generated with Python's `hashlib.scrypt`.
+const RSCRYPT_V0: &str =
+    "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$";
+const RSCRYPT_V1: &str =
+    "$rscrypt$1$AiwBAAACAAAA$TmFDbA==$PmzIZZObALd8+7qr+zqWbvsmpj0sC3IxE0eiOAJnY/8=$";
+const PHC: &str = "$scrypt$ln=4,r=8,p=1$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM";
+const DJANGO: &str = "scrypt$1024$0aCDrvRI4jmf1ZQ6LNWa9Q$8$1$8o+UXoyVmLUMNMva1u++F60z8m/s4Zu\
+    OJl8PF7yvO1D772qNhsEtEq4Sxx1271PyFoVcdiru2f3g9PqXXEXTtg==";
+
+fn refused(verifier: &Verifier, password: &str, hashed: &str, rule: Rule) {
+    // the rules apply before the password is checked
+    for &password in [password, "wrong"].iter() {
+        assert_eq!(verifier.verify(password, hashed), Err(CheckError::Refused(rule)),
+            "{}", hashed);
+    }
+}
+
+#[test]
+fn test_no_rules() {
+    let verifier = Verifier::builder().build();
+    for &(password, hashed) in [("password", RSCRYPT_V0), ("password", RSCRYPT_V1),
+        ("Ünïcødé", PHC)].iter()
+    {
+        assert_eq!(verifier.verify(password, hashed), Ok(()));
+        assert_eq!(verifier.verify("wrong", hashed), Err(CheckError::HashMismatch));
+    }
+    assert_eq!(verifier.verify("password", "$rscrypt$"), Err(CheckError::InvalidFormat));
+    assert_eq!(verifier.verify("password", ""), Err(CheckError::InvalidFormat));
+}
+
+#[test]
+fn test_allow_formats() {
+    let verifier = Verifier::builder().allow_formats(&[Format::Phc]).build();
+    assert_eq!(verifier.verify("Ünïcødé", PHC), Ok(()));
+    refused(&verifier, "password", RSCRYPT_V0, Rule::Format);
+    refused(&verifier, "password", DJANGO, Rule::Format);
+
+    let verifier = Verifier::builder().allow_formats(&[]).build();
+    refused(&verifier, "Ünïcødé", PHC, Rule::Format);
+}
+
+#[test]
+fn test_allow_rscrypt_versions() {
+    let verifier = Verifier::builder().allow_rscrypt_versions(&[1]).build();
+    assert_eq!(verifier.verify("password", RSCRYPT_V1), Ok(()));
+    refused(&verifier, "password", RSCRYPT_V0, Rule::Version);
+    // other formats have no version
+    assert_eq!(verifier.verify("Ünïcødé", PHC), Ok(()));
+}
+
+#[test]
+fn test_strict_encoding() {
+    let verifier = Verifier::builder().strict_encoding(true).build();
+    assert_eq!(verifier.verify("password", RSCRYPT_V0), Ok(()));
+    assert_eq!(verifier.verify("Ünïcødé", PHC), Ok(()));
+
+    let params = Params::new(4, 8, 1).unwrap();
+    let url_safe = scrypt_simple_url_safe("password", &params).unwrap();
+    assert_eq!(Verifier::builder().build().verify("password", &url_safe), Ok(()));
+    let lenient = [
+        ("Ünïcødé", "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM"),
+        ("Ünïcødé", "$scrypt$p=1,r=8,ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM"),
+        ("password", &url_safe),
+        // `r` and `p` fit into version 0
+        ("password", "$rscrypt$1$BAgAAAABAAAA$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$"),
+    ];
+    for &(password, hashed) in lenient.iter() {
+        assert_eq!(Verifier::builder().build().verify(password, hashed), Ok(()));
+        refused(&verifier, password, hashed, Rule::Encoding);
+    }
+}
+
+#[test]
+fn test_trim_whitespace() {
+    let strict = Verifier::builder().build();
+    let lenient = Verifier::builder().trim_whitespace(true).build();
+    let padded = [
+        format!("{}\n", RSCRYPT_V0),
+        format!("  {}", PHC),
+        format!("{}\r\n", RSCRYPT_V1),
+        format!("\t {} \n", DJANGO),
+    ];
+    for hashed in padded.iter() {
+        let password = if hashed.contains(PHC) { "Ünïcødé" } else { "password" };
+        assert_eq!(strict.verify(password, hashed), Err(CheckError::InvalidFormat), "{:?}", hashed);
+        assert_eq!(lenient.verify(password, hashed), Ok(()), "{:?}", hashed);
+        assert_eq!(lenient.verify("wrong", hashed), Err(CheckError::HashMismatch));
+    }
+
+    // the trimmed hash is checked against the other rules
+    let canonical = Verifier::builder().trim_whitespace(true).strict_encoding(true).build();
+    assert_eq!(canonical.verify("password", &padded[0]), Ok(()));
+
+    for &hashed in ["", " ", "\r\n", " \t\n "].iter() {
+        assert_eq!(lenient.verify("password", hashed), Err(CheckError::InvalidFormat));
+    }
+    // only the ends are trimmed
+    let embedded = RSCRYPT_V0.replacen("$", "$ ", 2);
+    for verifier in [&strict, &lenient].iter() {
+        assert!(verifier.verify("password", &embedded).is_err(), "{}", embedded);
+        assert!(verifier.verify("password", &format!(" {} ", embedded)).is_err());
+    }
+}
+
+#[test]
+fn test_require() {
+    let verifier = Verifier::builder()
+        .require(ParamsPolicy { min_log_n: 4, ..ParamsPolicy::default() })
+        .build();
+    assert_eq!(verifier.verify("password", RSCRYPT_V0), Ok(()));
+    refused(&verifier, "password", RSCRYPT_V1, Rule::MinLogN);
+
+    // 1 GiB of memory, refused without allocating it
+    let huge = "$rscrypt$0$FAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$";
+    let verifier = Verifier::builder()
+        .require(ParamsPolicy { max_memory: 128 << 20, ..ParamsPolicy::default() })
+        .build();
+    assert_eq!(verifier.verify("password", RSCRYPT_V0), Ok(()));
+    refused(&verifier, "password", huge, Rule::MaxMemory);
+
+    // `4 * N * r * p` is 512 for version 0 and 9600 for version 1
+    let verifier = Verifier::builder()
+        .require(ParamsPolicy { max_ops: 1000, ..ParamsPolicy::default() })
+        .build();
+    assert_eq!(verifier.verify("password", RSCRYPT_V0), Ok(()));
+    refused(&verifier, "password", RSCRYPT_V1, Rule::MaxOps);
+}
+
+#[test]
+fn test_full_chain() {
+    let verifier = Verifier::builder()
+        .allow_formats(&[Format::Phc, Format::Rscrypt])
+        .allow_rscrypt_versions(&[1])
+        .require(ParamsPolicy { min_log_n: 14, max_memory: 128 << 20, max_ops: 1 << 30 })
+        .strict_encoding(true)
+        .build();
+
+    let hashed = scrypt_simple_phc("password", &Params::new(14, 1, 1).unwrap()).unwrap();
+    assert_eq!(verifier.verify("password", &hashed), Ok(()));
+    assert_eq!(verifier.verify("wrong", &hashed), Err(CheckError::HashMismatch));
+
+    // the first rule broken is reported
+    refused(&verifier, "password", DJANGO, Rule::Format);
+    refused(&verifier, "password", RSCRYPT_V0, Rule::Version);
+    refused(&verifier, "password", RSCRYPT_V1, Rule::MinLogN);
+    refused(&verifier, "Ünïcødé", "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+        Rule::Encoding);
+    refused(&verifier, "Ünïcødé", PHC, Rule::MinLogN);
+    assert_eq!(verifier.verify("password", ""), Err(CheckError::InvalidFormat));
+}
+
+// the deprecated check functions keep their results
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_checks() {
+    use scrypt::*;
+
+    let params = Params::new(4, 8, 1).unwrap();
+    let hashed = scrypt_simple("password", &params).unwrap();
+    for &(password, result) in
+        [("password", Ok(())), ("wrong", Err(CheckError::HashMismatch))].iter()
+    {
+        assert_eq!(scrypt_check_strict(password, &hashed), result);
+        let bytes = Password::from(password.as_bytes().to_vec());
+        assert_eq!(scrypt_check_password(&bytes, &hashed), result);
+        assert_eq!(scrypt_check_token(password, &hashed).map(|_| ()), result);
+        assert_eq!(scrypt_check_detailed(password, &hashed).map(|_| ()), result);
+        let limits = VerifyLimits { max_memory: u64::MAX, max_ops: u64::MAX };
+        assert_eq!(scrypt_check_with_limits(password, &hashed, &limits), result);
+        let prehashed = scrypt_simple_prehashed("password", &params, &LengthPolicy::default())
+            .unwrap();
+        assert_eq!(scrypt_check_prehashed(password, &prehashed, &LengthPolicy::default()),
+            result);
+        let ad = scrypt_simple_ad("password", &params, b"ad").unwrap();
+        assert_eq!(scrypt_check_ad(password, &ad, b"ad"), result);
+        let peppered = scrypt_simple_peppered("password", &params,
+            &Pepper { id: 7, key: b"pepper key" }).unwrap();
+        assert_eq!(scrypt_check_peppered(password, &peppered, |id| {
+            assert_eq!(id, 7);
+            Some(b"pepper key")
+        }), result);
+        let mut peppers = PepperSet::new();
+        peppers.insert(7, b"pepper key");
+        assert_eq!(scrypt_check_with_peppers(password, &peppered, &peppers), result);
+    }
+    let report = scrypt_check_report("password", RSCRYPT_V0).unwrap();
+    assert_eq!((report.outcome, report.params), (VerifyOutcome::Match, params));
+
+    assert_eq!(scrypt_check_strict("password", RSCRYPT_V1),
+        Err(CheckError::UnexpectedFieldLen { salt_len: 4, hash_len: 32 }));
+    let limits = VerifyLimits { max_memory: u64::MAX, max_ops: 1000 };
+    assert_eq!(scrypt_check_with_limits("password", RSCRYPT_V1, &limits),
+        Err(CheckError::CostExceedsLimit));
+    assert_eq!(scrypt_check_peppered("password", RSCRYPT_V0, |_| None::<&[u8]>), Ok(()));
+    // only wrapped strings are accepted
+    assert_eq!(scrypt_check_wrapped("password", RSCRYPT_V0), Err(CheckError::InvalidFormat));
+}
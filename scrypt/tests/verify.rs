@@ -0,0 +1,46 @@
+#![cfg(feature="simple-verify")]
+extern crate scrypt;
+
+use scrypt::{scrypt_check, verify};
+use scrypt::errors::{CheckError, Field};
+
+// Generated with Python's `hashlib.scrypt`.
+const V0: &str = "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$";
+const PHC: &str = "$scrypt$ln=4,r=8,p=1$TmFDbA$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM";
+
+#[test]
+fn test_match_and_mismatch() {
+    for &hashed in [V0, PHC].iter() {
+        assert_eq!(verify("password", hashed), Ok(true), "{}", hashed);
+        assert_eq!(verify("passwore", hashed), Ok(false), "{}", hashed);
+        assert_eq!(verify("", hashed), Ok(false), "{}", hashed);
+    }
+}
+
+#[test]
+fn test_errors() {
+    let cases = [
+        ("", CheckError::InvalidFormat),
+        ("$rscrypt$0$", CheckError::InvalidFormat),
+        ("$rscrypt$0$BAgB$TmFD*A==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+            CheckError::InvalidFormat),
+        // unknown formats are reported like malformed strings by `scrypt_check`
+        ("$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$c29tZWhhc2g", CheckError::InvalidFormat),
+        ("$rscrypt$0$BAgB$TmFDbB==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$",
+            CheckError::NonCanonicalBase64(Field::Salt)),
+        ("$rscrypt$99$", CheckError::UnsupportedVersion),
+        ("$rscrypt$2$BAgAAAABAAAAECABBw$AAECAwQFBgcICQoLDA0ODw$\
+          rHLE6Ijo8iU76Eb/+7LuWCzbgR5PvTvU6yyOJW4Vs14", CheckError::NeedsPepper(7)),
+        ("$rscrypt$2$BAgAAAABAAAAECAC$AAECAwQFBgcICQoLDA0ODw$\
+          ayODfgC5zLRj7ix5T1whw4Y3ExN3sZaaPd4Chk3qZA4", CheckError::NeedsAssociatedData),
+    ];
+    for &(hashed, expected) in cases.iter() {
+        assert_eq!(verify("password", hashed), Err(expected), "{}", hashed);
+        assert_eq!(scrypt_check("password", hashed), Err(expected), "{}", hashed);
+    }
+
+    match verify("password", "$rscrypt$0$BAEB$c2FsdA==$$") {
+        Err(CheckError::InvalidHashLen(e)) => assert_eq!(e.provided_len(), 0),
+        r => panic!("{:?}", r),
+    }
+}
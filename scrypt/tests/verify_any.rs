@@ -0,0 +1,383 @@
+#![cfg(feature="simple-verify")]
+extern crate scrypt;
+
+use scrypt::{scrypt_check, verify_any, Format, Params};
+use scrypt::errors::CheckError;
+
+struct Case {
+    format: Format,
+    password: &'static str,
+    valid: &'static str,
+    log_n: u8,
+    r: u32,
+    p: u32,
+    // strings with the prefix of `format` which must be rejected as malformed
+    invalid: &'static [&'static str],
+}
+
+const CASES: &[Case] = &[
+    Case {
+        format: Format::Rscrypt,
+        password: "password",
+        valid: "$rscrypt$0$BAgB$15ieabOWBi+lTfggDq1u4A==$\
+                LSoB1to848I8SrfvmQAiAa2ajqs+iVgB5TecmxomqfU=$",
+        log_n: 4, r: 8, p: 1,
+        invalid: &[
+            "$rscrypt$",
+            "$rscrypt$0$BAgB$15ieabOWBi+lTfggDq1u4A==$",
+            "$rscrypt$1$BAgB$15ieabOWBi+lTfggDq1u4A==$\
+             LSoB1to848I8SrfvmQAiAa2ajqs+iVgB5TecmxomqfU=$",
+            "$rscrypt$0$BAgB$15ieabOWBi+lTfggDq1u4A==$\
+             LSoB1to848I8SrfvmQAiAa2ajqs+iVgB5TecmxomqfU=$$",
+            "$rscrypt$0$!!!!$15ieabOWBi+lTfggDq1u4A==$\
+             LSoB1to848I8SrfvmQAiAa2ajqs+iVgB5TecmxomqfU=$",
+        ],
+    },
+    Case {
+        format: Format::Phc,
+        password: "Ünïcødé",
+        valid: "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+        log_n: 4, r: 8, p: 1,
+        invalid: &[
+            "$scrypt$",
+            "$scrypt$ln=4$jgs",
+            "$scrypt$ln=4$jgs$",
+            "$scrypt$ln=x$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+            "$scrypt$n=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+            "$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM=",
+        ],
+    },
+    Case {
+        format: Format::Crypt7,
+        password: "password",
+        valid: "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+        log_n: 4, r: 1, p: 1,
+        invalid: &[
+            "$7$",
+            "$7$2/..../....saltsalt",
+            "$7$2/..../....saltsalt$",
+            "$7$./..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+            "$7$2/..../....salt-salt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+            "$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1F",
+        ],
+    },
+    Case {
+        format: Format::Lambdaworks,
+        password: "",
+        valid: "$s0$6010c$6xFRVKObC3j7pn8argJYdw==$\
+                nUmr44BPG1eSmZ5wWz8bnGJjIP5Vv5Ud2EeKVEuhZ2w=",
+        log_n: 6, r: 1, p: 12,
+        invalid: &[
+            "$s0$",
+            "$s0$6010c$6xFRVKObC3j7pn8argJYdw==",
+            "$s0$$6xFRVKObC3j7pn8argJYdw==$\
+             nUmr44BPG1eSmZ5wWz8bnGJjIP5Vv5Ud2EeKVEuhZ2w=",
+            "$s0$6010g$6xFRVKObC3j7pn8argJYdw==$\
+             nUmr44BPG1eSmZ5wWz8bnGJjIP5Vv5Ud2EeKVEuhZ2w=",
+            "$s0$6010c$6xFRVKObC3j7pn8argJYdw==$\
+             nUmr44BPG1eSmZ5wWz8bnGJjIP5Vv5Ud2EeKVEuh",
+            "$s0$6010c$6xFRVKObC3j7pn8argJYdw==$\
+             nUmr44BPG1eSmZ5wWz8bnGJjIP5Vv5Ud2EeKVEuhZ2w=$",
+        ],
+    },
+    Case {
+        format: Format::Django,
+        password: "Ünïcødé",
+        valid: "scrypt$2$sël$1$1$MbY5a6bob9Ls6RaeNUjmfa+noDsixdIwX+tWM5gfDxMEeztvV\
+                eNZ+2WDCMX5Zch449tsPzQMS0FZN2LOHt9O2Q==",
+        log_n: 1, r: 1, p: 1,
+        invalid: &[
+            "scrypt$",
+            "scrypt$2$sël$1$1$",
+            "scrypt$3$sël$1$1$MbY5a6bob9Ls6RaeNUjmfa+noDsixdIwX+tWM5gfDxMEeztvV\
+             eNZ+2WDCMX5Zch449tsPzQMS0FZN2LOHt9O2Q==",
+            "scrypt$2$$1$1$MbY5a6bob9Ls6RaeNUjmfa+noDsixdIwX+tWM5gfDxMEeztvV\
+             eNZ+2WDCMX5Zch449tsPzQMS0FZN2LOHt9O2Q==",
+            "scrypt$2$sël$1$1$MbY5a6bob9Ls6RaeNUjmfa+noDsixdIwX+tWM5gfDxMEeztvV\
+             eNZ+2WDCMX5Zch449tsPzQMS0FZN2LOHt9O2Q",
+            "scrypt$2$sël$1$1$1$MbY5a6bob9Ls6RaeNUjmfa+noDsixdIwX+tWM5gfDxMEeztvV\
+             eNZ+2WDCMX5Zch449tsPzQMS0FZN2LOHt9O2Q==",
+        ],
+    },
+    Case {
+        format: Format::Werkzeug,
+        password: "Ünïcødé",
+        valid: "scrypt:16:4:3$Vo8WJf01$8fa4d2de4726a5b6f188803247ba64c41bb9b4e8b3\
+                c2be8dcfd6ac891b20c98acc2aab903903ef746900557831128fad50af2a2d5c\
+                bfecf2c9ecd74a234ac075",
+        log_n: 4, r: 4, p: 3,
+        invalid: &[
+            "scrypt:",
+            "scrypt:16:4:3$Vo8WJf01",
+            "scrypt:16:4$Vo8WJf01$8fa4d2de4726a5b6f188803247ba64c41bb9b4e8b3\
+             c2be8dcfd6ac891b20c98acc2aab903903ef746900557831128fad50af2a2d5c\
+             bfecf2c9ecd74a234ac075",
+            "scrypt:15:4:3$Vo8WJf01$8fa4d2de4726a5b6f188803247ba64c41bb9b4e8b3\
+             c2be8dcfd6ac891b20c98acc2aab903903ef746900557831128fad50af2a2d5c\
+             bfecf2c9ecd74a234ac075",
+            "scrypt:16:4:3$Vo8WJf01$8FA4D2DE4726A5B6F188803247BA64C41BB9B4E8B3\
+             C2BE8DCFD6AC891B20C98ACC2AAB903903EF746900557831128FAD50AF2A2D5C\
+             BFECF2C9ECD74A234AC075",
+            "scrypt:16:4:3$Vo8WJf01$8fa4d2de4726a5b6f188803247ba64c41bb9b4e8b3",
+        ],
+    },
+    Case {
+        format: Format::Cisco,
+        password: "hashcat",
+        valid: "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+        log_n: 14, r: 1, p: 1,
+        invalid: &[
+            "$9$",
+            "$9$2MJBozw/9R3UsU",
+            "$9$2MJBozw/9R3Us$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+            "$9$2MJBozw+9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+            "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo7",
+            "$9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6$",
+        ],
+    },
+    Case {
+        format: Format::Hex,
+        password: "password",
+        valid: "scrypt:ln=4,r=8,p=1:000102030405060708090a0b0c0d0e0f:\
+                4a27cb03b2b4b79c8ce895055927f012ed9bdb54d41a88bb9b5a77c7b7e03282",
+        log_n: 4, r: 8, p: 1,
+        invalid: &[
+            "scrypt:ln=",
+            "scrypt:ln=4,r=8,p=1:000102030405060708090a0b0c0d0e0f",
+            "scrypt:ln=4,r=8,p=1:000102030405060708090a0b0c0d0e0f:\
+             4a27cb03b2b4b79c8ce895055927f012ed9bdb54d41a88bb9b5a77c7b7e0328",
+            "scrypt:ln=4,r=8,p=1:0001020304050607:4a27cb03b2b4b79c",
+        ],
+    },
+    Case {
+        format: Format::ScryptKdf,
+        password: "",
+        valid: "SCRYPT:16:1:1:yMnKy8zNzs/Q0dLT1NXW1w==:\
+                1Yx/mr66DN78fXHQxPd95MK5nDxhKHMwypgesOeM37E=",
+        log_n: 4, r: 1, p: 1,
+        // the other malformed strings name their field, see `scryptkdf.rs`
+        invalid: &[
+            "SCRYPT:16:1:1:yMnKy8zNzs/Q0dLT1NXW1w==:\
+             1Yx/mr66DN78fXHQxPd95MK5nDxhKHMwypgesOeM37E=:",
+            "SCRYPT:16:65536:65536:yMnKy8zNzs/Q0dLT1NXW1w==:\
+             1Yx/mr66DN78fXHQxPd95MK5nDxhKHMwypgesOeM37E=",
+        ],
+    },
+    Case {
+        format: Format::PhpScrypt,
+        password: "Ünïcødé",
+        valid: "2048$16$3$yMnKy8zNzs8=$\
+                a70a33adddf10ce88b3bfbe2f00158f1c14d3f1c6f34c56d16b146eed36efa17",
+        log_n: 11, r: 16, p: 3,
+        invalid: &[
+            "2048$16$3$",
+            "2048$16$3$yMnKy8zNzs8=",
+            "2047$16$3$yMnKy8zNzs8=$\
+             a70a33adddf10ce88b3bfbe2f00158f1c14d3f1c6f34c56d16b146eed36efa17",
+            "2048$16$3$yMnKy8zNzs8=$\
+             A70A33ADDDF10CE88B3BFBE2F00158F1C14D3F1C6F34C56D16B146EED36EFA17",
+            "2048$16$3$yMnKy8zNzs8=$a70a33adddf10ce88b3bfbe2f00158f1",
+            "2048$16$3$yMnKy8zNzs8=$\
+             a70a33adddf10ce88b3bfbe2f00158f1c14d3f1c6f34c56d16b146eed36efa17$",
+        ],
+    },
+    Case {
+        format: Format::SimpleScrypt,
+        password: "Ünïcødé",
+        valid: "2048$16$3$c8c9cacbcccdcecf$56c8c406d3597dc3d3451435509a4664",
+        log_n: 11, r: 16, p: 3,
+        invalid: &[
+            "2048$16$3$c8c9cacbcccdcecf",
+            "2048$16$3$c8c9cacbcccdcecf$",
+            "2047$16$3$c8c9cacbcccdcecf$56c8c406d3597dc3d3451435509a4664",
+            "2048$16$3$c8c9cacbcccdcecf$56c8c406d3597dc3d3451435509a46",
+            "2048$16$3$c8c9cacbcccdcecf$56c8c406d3597dc3d3451435509a4664$",
+        ],
+    },
+    Case {
+        format: Format::RubyScrypt,
+        password: "",
+        valid: "10$1$1$c8c9cacbcccdcecf$be0854f3cf69c4c7be4b58c9902ba01b",
+        log_n: 4, r: 1, p: 1,
+        invalid: &[
+            "10$1$1$c8c9cacbcccdcecf",
+            "10$1$1$c8c9cacbcccdcecf$",
+            "10$1$1$c8c9cacbcccdce$be0854f3cf69c4c7be4b58c9902ba01b",
+            "10$1$1$c8c9cacbcccdcecf$BE0854F3CF69C4C7BE4B58C9902BA01B",
+            "10$1$1$c8c9cacbcccdcecf$be0854f3cf69c4c7be4b58c9902ba0",
+            "10$1$1$c8c9cacbcccdcecf$be0854f3cf69c4c7be4b58c9902ba01b$",
+        ],
+    },
+];
+
+#[test]
+fn test_verify_any_valid() {
+    for case in CASES {
+        assert_eq!(Format::detect(case.valid), Some(case.format), "{}", case.valid);
+        let verified = verify_any(case.password, case.valid).unwrap();
+        assert_eq!(verified.format(), case.format);
+        assert_eq!(
+            verified.params(),
+            Params::new(case.log_n, case.r, case.p).unwrap(),
+        );
+        assert_eq!(scrypt_check(case.password, case.valid), Ok(()));
+        assert_eq!(
+            verify_any("wrong", case.valid),
+            Err(CheckError::HashMismatch),
+            "{}", case.valid
+        );
+    }
+}
+
+#[test]
+fn test_verify_any_invalid() {
+    for case in CASES {
+        for &hashed in case.invalid {
+            assert_eq!(Format::detect(hashed), Some(case.format), "{}", hashed);
+            assert_eq!(
+                verify_any(case.password, hashed),
+                Err(CheckError::InvalidFormat),
+                "{}", hashed
+            );
+        }
+    }
+}
+
+#[test]
+fn test_verify_any_unsupported() {
+    let unknown = [
+        "",
+        "$",
+        "$$",
+        "scrypt",
+        "$scrypt",
+        "$rscrypt",
+        "$S0$6010c$6xFRVKObC3j7pn8argJYdw==$\
+         nUmr44BPG1eSmZ5wWz8bnGJjIP5Vv5Ud2EeKVEuhZ2w=",
+        "Scrypt:16:4:3$Vo8WJf01$8fa4d2de",
+        " $9$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+        "7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+        "$8$2MJBozw/9R3UsU$2lFhcKvpghcyw8deP25GOfyZaagyUOGBymkryvOdfo6",
+        "$2b$10$N9qo8uLOickgx2ZMRZoMyeIjZAgcfl7p92ldGxad68LJZdL17lhWy",
+        "$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG",
+        "pbkdf2_sha256$260000$salt$hash",
+    ];
+    for &hashed in unknown.iter() {
+        assert_eq!(Format::detect(hashed), None, "{}", hashed);
+        assert_eq!(
+            verify_any("password", hashed),
+            Err(CheckError::UnsupportedFormat),
+            "{}", hashed
+        );
+        // `scrypt_check` keeps reporting unknown strings as malformed
+        assert_eq!(
+            scrypt_check("password", hashed),
+            Err(CheckError::InvalidFormat),
+            "{}", hashed
+        );
+    }
+}
+
+#[test]
+fn test_verify_any_hybrids() {
+    // strings engineered to look like two formats at once are dispatched on
+    // their prefix alone and rejected by its parser
+    let hybrids = [
+        // a Django body under the PHC prefix
+        (
+            Format::Phc,
+            "$scrypt$2$sël$1$1$MbY5a6bob9Ls6RaeNUjmfa+noDsixdIwX+tWM5gfDxMEeztvV\
+             eNZ+2WDCMX5Zch449tsPzQMS0FZN2LOHt9O2Q==",
+        ),
+        // a Werkzeug method with Django's separator
+        (
+            Format::Django,
+            "scrypt$16:4:3$Vo8WJf01$8fa4d2de4726a5b6f188803247ba64c41bb9b4e8b3\
+             c2be8dcfd6ac891b20c98acc2aab903903ef746900557831128fad50af2a2d5c\
+             bfecf2c9ecd74a234ac075",
+        ),
+        // a Werkzeug string with the parameters of a hex string
+        (
+            Format::Hex,
+            "scrypt:ln=4,r=4,p=3$Vo8WJf01$8fa4d2de4726a5b6f188803247ba64c41bb9b4e8b3\
+             c2be8dcfd6ac891b20c98acc2aab903903ef746900557831128fad50af2a2d5c\
+             bfecf2c9ecd74a234ac075",
+        ),
+        // Django fields behind a Werkzeug method
+        (
+            Format::Werkzeug,
+            "scrypt:2$sël$1$1$MbY5a6bob9Ls6RaeNUjmfa+noDsixdIwX+tWM5gfDxMEeztvV\
+             eNZ+2WDCMX5Zch449tsPzQMS0FZN2LOHt9O2Q==",
+        ),
+        // a Lambdaworks string as the salt of a `$7$` string
+        (
+            Format::Crypt7,
+            "$7$$s0$6010c$6xFRVKObC3j7pn8argJYdw==$\
+             nUmr44BPG1eSmZ5wWz8bnGJjIP5Vv5Ud2EeKVEuhZ2w=",
+        ),
+        // a `$7$` string as the parameters of a Lambdaworks string
+        (
+            Format::Lambdaworks,
+            "$s0$7$2/..../....saltsalt$FrelyYshbhpd8Qs4396iF5YwOsHMhsSmcT/mEAosdY1",
+        ),
+        // a PHC string embedded in a Cisco string
+        (
+            Format::Cisco,
+            "$9$$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+        ),
+        // a PHC string embedded in an rscrypt string
+        (
+            Format::Rscrypt,
+            "$rscrypt$scrypt$ln=4$jgs$cQfhHLd/Uhiyv76L3wVTJQqVqT7MumSWYONYqGVgkLM",
+        ),
+    ];
+    for &(format, hashed) in hybrids.iter() {
+        assert_eq!(Format::detect(hashed), Some(format), "{}", hashed);
+        assert_eq!(
+            verify_any("Ünïcødé", hashed),
+            Err(CheckError::InvalidFormat),
+            "{}", hashed
+        );
+    }
+
+    // every valid string with the prefix of another format, which never
+    // verifies but may well be a well-formed string of that format
+    for case in CASES {
+        let body = &case.valid[prefix(case.format).len()..];
+        // `php-scrypt`, simple-scrypt and Ruby strings have no prefix to put
+        // in front of a body
+        let others = CASES.iter().filter(|other| {
+            other.format != case.format && !prefix(other.format).is_empty()
+        });
+        for other in others {
+            let hashed = format!("{}{}", prefix(other.format), body);
+            // hex strings start like Werkzeug strings, `ln=` picks hex
+            let expected = if hashed.starts_with(prefix(Format::Hex)) {
+                Format::Hex
+            } else {
+                other.format
+            };
+            assert_eq!(Format::detect(&hashed), Some(expected), "{}", hashed);
+            match verify_any(case.password, &hashed) {
+                Err(CheckError::InvalidFormat) | Err(CheckError::HashMismatch)
+                | Err(CheckError::InvalidField(_)) | Err(CheckError::UnsupportedVersion) => (),
+                res => panic!("{}: {:?}", hashed, res),
+            }
+        }
+    }
+}
+
+fn prefix(format: Format) -> &'static str {
+    match format {
+        Format::Rscrypt => "$rscrypt$",
+        Format::Phc => "$scrypt$",
+        Format::Crypt7 => "$7$",
+        Format::Lambdaworks => "$s0$",
+        Format::Django => "scrypt$",
+        Format::Werkzeug => "scrypt:",
+        Format::Cisco => "$9$",
+        Format::Hex => "scrypt:ln=",
+        Format::ScryptKdf => "SCRYPT:",
+        Format::PhpScrypt | Format::SimpleScrypt | Format::RubyScrypt => "",
+    }
+}
@@ -0,0 +1,48 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use scrypt::errors::CheckError;
+use scrypt::{scrypt_check, scrypt_simple, Params, PasswordVerified, Verifier};
+
+/// A session, which can only be created with the proof of a verification.
+#[derive(Debug)]
+struct Session {
+    user: String,
+}
+
+fn create_session(user: &str, _proof: PasswordVerified) -> Session {
+    Session { user: user.to_string() }
+}
+
+fn login(user: &str, password: &str, stored: &str) -> Result<Session, CheckError> {
+    let proof = Verifier::builder().build().verify_token(password, stored)?;
+    Ok(create_session(user, proof))
+}
+
+fn stored() -> String {
+    scrypt_simple("hunter2", &Params::new(4, 8, 1).unwrap()).unwrap()
+}
+
+#[test]
+fn test_token() {
+    let stored = stored();
+    assert_eq!(login("alice", "hunter2", &stored).unwrap().user, "alice");
+    assert_eq!(login("alice", "hunter3", &stored).unwrap_err(), CheckError::HashMismatch);
+    assert_eq!(login("alice", "hunter2", "$rscrypt$").unwrap_err(), CheckError::InvalidFormat);
+    assert!(Verifier::builder().build().verify_token(b"hunter2", &stored).is_ok());
+    assert_eq!(std::mem::size_of::<PasswordVerified>(), 0);
+}
+
+#[test]
+fn test_legacy_unaffected() {
+    let stored = stored();
+    assert_eq!(scrypt_check("hunter2", &stored), Ok(()));
+    assert_eq!(scrypt_check("hunter3", &stored), Err(CheckError::HashMismatch));
+}
+
+#[cfg(feature="test-util")]
+#[test]
+fn test_insecure_for_tests() {
+    let session = create_session("bob", PasswordVerified::insecure_for_tests());
+    assert_eq!(session.user, "bob");
+}
@@ -0,0 +1,91 @@
+#![cfg(feature="simple")]
+extern crate scrypt;
+
+use std::sync::{Arc, Mutex};
+
+use scrypt::{scrypt_check, scrypt_simple, scrypt_simple_v2, Format, Params, SimpleConfig};
+use scrypt::errors::CheckError;
+use scrypt::observer::{set_global_observer, set_memory_warning_threshold, ScryptObserver,
+    Warning};
+
+// Generated with Python's `hashlib.scrypt`.
+const V0: &str = "$rscrypt$0$BAgB$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$";
+const V1: &str =
+    "$rscrypt$1$BAgAAAABAAAA$TmFDbA==$9d+zly55CLIkEMXF83iJB829GnmXGxIndQK9Snfm1dM=$";
+
+#[derive(Default)]
+struct Capture {
+    warnings: Mutex<Vec<Warning>>,
+}
+
+impl ScryptObserver for Capture {
+    fn on_warning(&self, warning: &Warning) {
+        self.warnings.lock().unwrap().push(*warning);
+    }
+}
+
+impl Capture {
+    fn take(&self) -> Vec<Warning> {
+        self.warnings.lock().unwrap().drain(..).collect()
+    }
+}
+
+// the rate limit is per process, so everything is checked in one test
+#[test]
+fn test_warnings() {
+    let capture = Arc::new(Capture::default());
+    set_global_observer(Some(capture.clone()));
+    let weak = Params::new(4, 8, 1).unwrap();
+    let other = Params::new(5, 8, 1).unwrap();
+
+    // weak parameters, once per distinct parameters, from every hashing path
+    let hashed = scrypt_simple("password", &weak).unwrap();
+    scrypt_simple("password", &weak).unwrap();
+    SimpleConfig::builder().params(weak).format(Format::Phc).build().unwrap()
+        .hash_with("password").unwrap();
+    scrypt_simple_v2("password", &weak).unwrap();
+    assert_eq!(capture.take(), vec![Warning::WeakParams(weak)]);
+    scrypt_simple_v2("password", &other).unwrap();
+    assert_eq!(capture.take(), vec![Warning::WeakParams(other)]);
+
+    // verifying is not hashing, and the current versions are not deprecated
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("password", V0), Ok(()));
+    assert_eq!(capture.take(), vec![]);
+
+    // deprecated versions, once
+    assert_eq!(scrypt_check("password", V1), Ok(()));
+    assert_eq!(scrypt_check("wrong", V1), Err(CheckError::HashMismatch));
+    let deprecated = Warning::DeprecatedVersion { format: Format::Rscrypt, version: 1 };
+    assert_eq!(capture.take(), vec![deprecated]);
+
+    // memory above the threshold, once per distinct parameters
+    set_memory_warning_threshold(16 * 1024);
+    assert_eq!(scrypt_check("password", V0), Ok(()));
+    assert_eq!(scrypt_check("password", V0), Ok(()));
+    let memory = Warning::MemoryAboveThreshold { params: weak, memory_bytes: 128 * 8 * 18 };
+    assert_eq!(capture.take(), vec![memory]);
+    set_memory_warning_threshold(u64::MAX);
+    let hashed = scrypt_simple("x", &other).unwrap();
+    assert_eq!(scrypt_check("password", &hashed), Err(CheckError::HashMismatch));
+    assert_eq!(capture.take(), vec![]);
+
+    // messages carry parameters, never passwords, salts or hashes
+    for warning in [Warning::WeakParams(weak), deprecated, memory].iter() {
+        let message = warning.to_string();
+        assert!(message.contains("log_n") || message.contains("version 1"), "{}", message);
+        assert!(!message.contains("password") && !message.contains("TmFD"), "{}", message);
+    }
+    assert_eq!(memory.to_string(),
+        "verifying a hash with log_n = 4, r = 8, p = 1 needed 18432 bytes, above the \
+         warning threshold");
+
+    // without an observer nothing is recorded, a later one still gets it
+    set_global_observer(None);
+    let unseen = Params::new(6, 8, 1).unwrap();
+    scrypt_simple("password", &unseen).unwrap();
+    set_global_observer(Some(capture.clone()));
+    scrypt_simple("password", &unseen).unwrap();
+    assert_eq!(capture.take(), vec![Warning::WeakParams(unseen)]);
+    set_global_observer(None);
+}
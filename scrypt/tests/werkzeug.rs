@@ -0,0 +1,130 @@
+#![cfg(feature="simple")]
+extern crate rand;
+extern crate scrypt;
+
+use rand::{CryptoRng, RngCore};
+
+use scrypt::{scrypt_check, scrypt_simple_werkzeug, Format, Params, SimpleConfig};
+use scrypt::errors::CheckError;
+
+// Generated with Werkzeug 3.1's `generate_password_hash`.
+const FIXTURES: &[(&str, &str)] = &[
+    (
+        "password",
+        "scrypt:32768:8:1$uFnW4SH41CrpCBSh$145ac24248786b8716b72b293da259cbea59\
+         0347db9ac22b9dbf82db0a357537da4dd987732c21a959afb9dd7754f7220ec022a0ce\
+         357b1b315ee642a8e1889a",
+    ),
+    (
+        "pleaseletmein",
+        "scrypt:1024:8:1$fFKbe18UxHmue93E$d5e29167c4c878cda3abb1edfb5f7c59aee44e\
+         63d29eefc63424f2c64cbe5e8cd80320be1df4269fa2230540f2643f6538d9c1458e6d\
+         d22c3ff700b88ce7e5ea",
+    ),
+    (
+        "Ünïcødé",
+        "scrypt:16:4:3$Vo8WJf01$8fa4d2de4726a5b6f188803247ba64c41bb9b4e8b3c2be8d\
+         cfd6ac891b20c98acc2aab903903ef746900557831128fad50af2a2d5cbfecf2c9ecd7\
+         4a234ac075",
+    ),
+    (
+        "",
+        "scrypt:256:1:2$e$6b49c28e5220aa2f9e2ce964c7118d3880cc29a644fc9d36a453fb\
+         460cd296f242389d8cee19bdc2682fe5313d143b6661cd68b139c6cc07f0f00a2ade85\
+         a2cf",
+    ),
+    (
+        "correct horse",
+        "scrypt:4096:16:1$dkefx87t6h7jz6qAOrT7pe2rR45pWjU1IJqSFje1$2605169c2218c\
+         8a914dd9963973c24f1375f0df498f1905cb55e12d233a12475a3d11fc9055e707ae91\
+         d2e0d8ceff464f0464d652fe10504f82f3e39ebf556f9",
+    ),
+];
+
+#[test]
+fn test_werkzeug_fixtures() {
+    for &(password, hashed) in FIXTURES {
+        assert_eq!(scrypt_check(password, hashed), Ok(()), "{}", hashed);
+        assert_eq!(
+            scrypt_check("wrong", hashed),
+            Err(CheckError::HashMismatch),
+            "{}", hashed
+        );
+    }
+}
+
+struct FixedRng(Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 { unimplemented!() }
+    fn next_u64(&mut self) -> u64 { unimplemented!() }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_werkzeug_fixed_salt() {
+    let bytes = vec![
+        5, 31, 36, 1, 4, 53, 60, 46, 23, 33, 12, 20, 4, 61, 55, 30,
+    ];
+    let params = Params::new(10, 8, 1).unwrap();
+    let config = SimpleConfig::builder().params(params).format(Format::Werkzeug).build().unwrap();
+    let hashed = config.hash_with_rng("pleaseletmein", &mut FixedRng(bytes)).unwrap();
+    assert_eq!(hashed, FIXTURES[1].1);
+}
+
+#[test]
+fn test_werkzeug_round_trip() {
+    let params = Params::new(4, 2, 3).unwrap();
+    let hashed = scrypt_simple_werkzeug("password", &params).unwrap();
+    let fields: Vec<&str> = hashed.split('$').collect();
+    assert_eq!(fields.len(), 3);
+    assert_eq!(fields[0], "scrypt:16:2:3");
+    assert_eq!(fields[1].len(), 16);
+    assert!(fields[1].bytes().all(|b| b.is_ascii_alphanumeric()));
+    assert_eq!(fields[2].len(), 128);
+    assert_eq!(scrypt_check("password", &hashed), Ok(()));
+    assert_eq!(scrypt_check("wrong", &hashed), Err(CheckError::HashMismatch));
+}
+
+#[test]
+fn test_werkzeug_malformed() {
+    let rest = "$Vo8WJf01$8fa4d2de4726a5b6f188803247ba64c41bb9b4e8b3c2be8dcfd6ac89\
+                1b20c98acc2aab903903ef746900557831128fad50af2a2d5cbfecf2c9ecd74a23\
+                4ac075";
+    let invalid_methods = [
+        "scrypt:", "scrypt:16", "scrypt:16:4", "scrypt:16:4:3:1", "scrypt:15:4:3",
+        "scrypt:1:4:3", "scrypt:016:4:3", "scrypt:+16:4:3", "scrypt:16:0:3",
+        "scrypt:16:4:0", "scrypt:16:4:-3", "scrypt:16::3",
+    ];
+    for method in invalid_methods.iter() {
+        let hashed = format!("{}{}", method, rest);
+        assert_eq!(scrypt_check("Ünïcødé", &hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+
+    let hash = "8fa4d2de4726a5b6f188803247ba64c41bb9b4e8b3c2be8dcfd6ac891b20c98a\
+                cc2aab903903ef746900557831128fad50af2a2d5cbfecf2c9ecd74a234ac075";
+    let invalid = [
+        "scrypt:16:4:3$Vo8WJf01".to_string(),
+        "scrypt:16:4:3$Vo8WJf01$".to_string(),
+        format!("scrypt:16:4:3$Vo8WJf01${}$", hash),
+        format!("scrypt:16:4:3$Vo8WJf01${}", &hash[..64]),
+        format!("scrypt:16:4:3$Vo8WJf01${}0", hash),
+        format!("scrypt:16:4:3$Vo8WJf01${}", hash.to_uppercase()),
+        format!("scrypt:16:4:3$Vo8WJf01${}g", &hash[..127]),
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(scrypt_check("Ünïcødé", hashed), Err(CheckError::InvalidFormat),
+            "{}", hashed);
+    }
+}
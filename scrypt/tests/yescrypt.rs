@@ -0,0 +1,165 @@
+#![cfg(feature="yescrypt")]
+extern crate scrypt;
+
+use scrypt::{scrypt, Params};
+use scrypt::errors::{CheckError, InvalidParams, ParamsReason};
+#[cfg(feature="simple")]
+use scrypt::yescrypt::gensalt;
+use scrypt::yescrypt::{crypt, verify, yescrypt, Flavor, YescryptParams};
+
+// Generated with libxcrypt's `crypt(3)`, the first six from settings
+// returned by its `crypt_gensalt("$y$", ...)` for the counts 1 to 6.
+const FIXTURES: &[(&str, &str)] = &[
+    ("pleaseletmein", "$y$j75$bHvRrBegFS0WEoSXjwGPw0$ny.68dMgfgCjicMIbkXH2BqoDopQOtGHpEA.u.xFtM4"),
+    ("password", "$y$j85$C6nHFBb0sdiEYG3h0nDPc/$WrASYreI6A8tSi8dqSFJOwpvD7Pto.eFFO1EczXmls4"),
+    ("Pässwörd ✓", "$y$j7T$oDElvvorp1Djz4MMzdg8M1$X0e2S4wQGIPnum8SUOpVKmE3eZm4rNS.if66WvWXZk7"),
+    ("xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx",
+     "$y$j8T$6ZCvaZb9CAiS9IuT5El4R.$M11i2CxX4V1PGzcVAUmI1.cylp4XIANJJpisgzTZ332"),
+    // the default, large enough to be pre-hashed
+    ("", "$y$j9T$DSW/dJsEYejmowvnAAQ5/0$6pT65e47od0nkam3qYSWZeSGcHFVEwF0zpu8TRJ7oW4"),
+    ("pleaseletmein", "$y$jAT$O36LFy4pi/h/CQmbilW1n0$olLMKGUIGdmXQG4siQ9bsoKPRun67J8aXi45YPRvx/D"),
+    // classic scrypt and YESCRYPT_WORM
+    ("", "$y$.75$VQNd/cnWlg93/OrzbxpiZ.$Qat.xoV30Nsii0NUWBAMaNO8vRuSSMMMyXeMkD6fOJA"),
+    ("pleaseletmein", "$y$/75$skGNlZV.coTXoB4.EsYq./$b3Za0UjjSfrcy28wdvwdkSjB9GJ/YMMomGaTuL9mzU0"),
+    ("password", "$y$/75/0$OOsox8UGew2Ugm91lCSLa1$4M4GC4nGMkv.wmNIh3ElzpovCgawEzIKPlclfvFkKsD"),
+    // p and t
+    ("Pässwörd ✓", "$y$j75..$cw5Qq1wq4OJmC4aPdUsbM.$C8jrIdlE6TLRb7r.9tI/9/GGxDEy0fz45UzugLcIGt7"),
+    ("xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx",
+     "$y$j75/.$EKGwmTH2hxzpEQKfm7WLo.$9QlaZjHyYQtMvWxe19Q5b0YoE.YSwC7d3GTvHlAI/d4"),
+    ("", "$y$j75//$8WjJ4i04dntQJ56beh3Y7/$rYDPWxP1lJJKcFFEupxDrKtEuPe7SZvuO.OI0o4LVn7"),
+    ("pleaseletmein", "$y$j750/.$W.XuUUQ2wHcQZ9pvdFLby.$XrAcqJyF4rJddod/FfhmgdkFvt5sMIpzrUhairg6o1B"),
+    ("password", "$y$j9T/.$VCJVdjjymc8vtXRVNyhIw0$7iUQQPxP6IohPcZagxTAEb9bzK7ZSnmbKHEHH44Uew/"),
+    ("Pässwörd ✓", "$y$j9T..$QAh.Gu/to3jq//fsP5n1s0$rco7ITH9p7SWzOonDk1gVVdtekIr3bMjGIgup/MOeD1"),
+    // r = 1000 in three characters, N = 4 and r = 1
+    ("pleaseletmein", "$y$/1s4r$saltsalt$T5W1s/S9f/OnVaHBgiGk1IW3ZuqXBH0wxJID2cDI38A"),
+    ("xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx",
+     "$y$j/.$gehK4/XlM/JNokAgXUmJd.$UFy/uFaqtzWkBAn/L1QTa/ukWdgRFK9ReG84nN68xk."),
+    // empty and 64-byte salts
+    ("pleaseletmein", "$y$j75$$BzvHmI/e8QuyzYI6lWdm10RtdhckUnfCDQ2pl1WCceB"),
+    ("pleaseletmein",
+     "$y$j75$aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.\
+      $mJO4ktJsgLNAqVzcemjD8zQ5qhv0fN0AIU5w.q218I4"),
+];
+
+#[test]
+fn test_yescrypt_fixtures() {
+    for &(password, hashed) in FIXTURES {
+        assert_eq!(verify(password.as_bytes(), hashed), Ok(()), "{}", hashed);
+        assert_eq!(verify(b"wrong", hashed), Err(CheckError::HashMismatch), "{}", hashed);
+        assert_eq!(crypt(password.as_bytes(), hashed).unwrap(), hashed);
+    }
+    // the salt ends at the last `$`
+    let setting = "$y$j75$bHvRrBegFS0WEoSXjwGPw0";
+    assert_eq!(crypt(b"pleaseletmein", setting).unwrap(), FIXTURES[0].1);
+    assert_eq!(crypt(b"pleaseletmein", &format!("{}$", setting)).unwrap(), FIXTURES[0].1);
+}
+
+#[test]
+fn test_yescrypt_classic() {
+    let params = YescryptParams::new(Flavor::Scrypt, 10, 8, 2, 0).unwrap();
+    let mut expected = [0u8; 32];
+    scrypt(b"password", b"NaCl", &Params::new(10, 8, 2).unwrap(), &mut expected).unwrap();
+    assert_eq!(yescrypt(b"password", b"NaCl", &params), expected);
+}
+
+#[cfg(feature="simple")]
+#[test]
+fn test_yescrypt_gensalt() {
+    let setting = gensalt(&YescryptParams::default()).unwrap();
+    assert!(setting.starts_with("$y$j9T$"));
+    assert_eq!(setting.len(), 7 + 22);
+    assert!(setting != gensalt(&YescryptParams::default()).unwrap());
+
+    let params = YescryptParams::new(Flavor::ReadWrite, 10, 8, 2, 1).unwrap();
+    let setting = gensalt(&params).unwrap();
+    assert!(setting.starts_with("$y$j750..$"), "{}", setting);
+    let hashed = crypt(b"password", &setting).unwrap();
+    assert!(hashed.starts_with(&setting));
+    assert_eq!(verify(b"password", &hashed), Ok(()));
+    assert_eq!(verify(b"wrong", &hashed), Err(CheckError::HashMismatch));
+
+    // r takes three characters
+    let params = YescryptParams::new(Flavor::Worm, 4, 1000, 1, 0).unwrap();
+    assert!(gensalt(&params).unwrap().starts_with("$y$/1s4r$"));
+}
+
+fn reason(params: Result<YescryptParams, InvalidParams>)
+    -> Result<YescryptParams, ParamsReason>
+{
+    params.map_err(|e| e.reason())
+}
+
+#[test]
+fn test_yescrypt_params() {
+    assert_eq!(reason(YescryptParams::new(Flavor::ReadWrite, 0, 8, 1, 0)),
+        Err(ParamsReason::LogNZero));
+    assert_eq!(reason(YescryptParams::new(Flavor::ReadWrite, 64, 8, 1, 0)),
+        Err(ParamsReason::LogNTooLarge));
+    assert_eq!(reason(YescryptParams::new(Flavor::ReadWrite, 10, 0, 1, 0)),
+        Err(ParamsReason::RZero));
+    assert_eq!(reason(YescryptParams::new(Flavor::ReadWrite, 10, 8, 0, 0)),
+        Err(ParamsReason::PZero));
+    assert_eq!(reason(YescryptParams::new(Flavor::ReadWrite, 10, 1 << 15, 1 << 15, 0)),
+        Err(ParamsReason::RpTooLarge));
+    assert_eq!(reason(YescryptParams::new(Flavor::ReadWrite, 10, 8, 1, 1 << 30)),
+        Err(ParamsReason::Other));
+    // `N / p` must be at least 2 for ReadWrite
+    assert_eq!(reason(YescryptParams::new(Flavor::ReadWrite, 2, 8, 3, 0)),
+        Err(ParamsReason::Other));
+    assert!(YescryptParams::new(Flavor::Worm, 2, 8, 3, 0).is_ok());
+    assert_eq!(reason(YescryptParams::new(Flavor::Scrypt, 10, 8, 1, 1)), Err(ParamsReason::Other));
+    assert_eq!(reason(YescryptParams::new(Flavor::Scrypt, 63, 1 << 20, 1, 0)),
+        Err(ParamsReason::MemoryOverflow));
+
+    let params = YescryptParams::default();
+    assert_eq!((params.flavor(), params.log_n(), params.r(), params.p(), params.t()),
+        (Flavor::ReadWrite, 12, 32, 1, 0));
+}
+
+#[test]
+fn test_yescrypt_malformed() {
+    let hash = "ny.68dMgfgCjicMIbkXH2BqoDopQOtGHpEA.u.xFtM4";
+    let invalid = [
+        // missing or truncated hash
+        "$y$j75$bHvRrBegFS0WEoSXjwGPw0".to_string(),
+        "$y$j75$bHvRrBegFS0WEoSXjwGPw0$".to_string(),
+        "$y$j75$bHvRrBegFS0WEoSXjwGPw0$ny.68dMgfgCjicMIbkXH2BqoDopQOtGHpEA.u.xFtM".to_string(),
+        // non-canonical trailing bits of the hash
+        "$y$j75$bHvRrBegFS0WEoSXjwGPw0$ny.68dMgfgCjicMIbkXH2BqoDopQOtGHpEA.u.xFtMz".to_string(),
+        // missing, truncated and invalid parameters
+        format!("$y$$bHvRrBegFS0WEoSXjwGPw0${}", hash),
+        format!("$y$j7$bHvRrBegFS0WEoSXjwGPw0${}", hash),
+        format!("$y$j75.$bHvRrBegFS0WEoSXjwGPw0${}", hash),
+        format!("$y$j75-$bHvRrBegFS0WEoSXjwGPw0${}", hash),
+        format!("$y$jz5$bHvRrBegFS0WEoSXjwGPw0${}", hash),
+        // N / p below 2
+        format!("$y$j.5.$bHvRrBegFS0WEoSXjwGPw0${}", hash),
+        // undefined flags of `have`
+        format!("$y$j75z.$bHvRrBegFS0WEoSXjwGPw0${}", hash),
+        // salts of a single character, with trailing bits or too long
+        format!("$y$j75$b${}", hash),
+        format!("$y$j75$ab${}", hash),
+        format!("$y$j75$bHvRr-egFS0WEoSXjwGPw0${}", hash),
+        format!("$y$j75${}a.${}", "a".repeat(88), hash),
+        // the salt extends to the last `$`
+        format!("$y$j75$bHvR$rBegFS0WEoSXjwGPw0${}", hash),
+        format!("$7$j75$bHvRrBegFS0WEoSXjwGPw0${}", hash),
+    ];
+    for hashed in invalid.iter() {
+        assert_eq!(verify(b"pleaseletmein", hashed), Err(CheckError::InvalidFormat), "{}", hashed);
+    }
+
+    let unsupported = [
+        // YESCRYPT_RW with other pwxform settings
+        format!("$y$i75$bHvRrBegFS0WEoSXjwGPw0${}", hash),
+        format!("$y$075$bHvRrBegFS0WEoSXjwGPw0${}", hash),
+        // hash upgrades and ROMs
+        format!("$y$j751.$bHvRrBegFS0WEoSXjwGPw0${}", hash),
+        format!("$y$j757.$bHvRrBegFS0WEoSXjwGPw0${}", hash),
+    ];
+    for hashed in unsupported.iter() {
+        assert_eq!(verify(b"pleaseletmein", hashed), Err(CheckError::UnsupportedFormat),
+            "{}", hashed);
+        assert_eq!(crypt(b"pleaseletmein", hashed), Err(CheckError::UnsupportedFormat));
+    }
+}